@@ -6,7 +6,10 @@ use walkdir::WalkDir;
 use build_util::*;
 
 fn main() {
-    run_build_or_exit(build, "ABC");
+    // without the native library, only the parts implemented in Rust are built
+    if std::env::var_os("CARGO_FEATURE_NATIVE").is_some() {
+        run_build_or_exit(build, "ABC");
+    }
 }
 
 /// Run the build for ABC, by compiling the bundled C library.
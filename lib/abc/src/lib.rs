@@ -1,37 +1,83 @@
 //! Bindings to the ABC library with a selective set of functions
-//! for rewriting aiger circuits.
+//! for rewriting aiger circuits and mapping them to lookup tables.
+//!
+//! The bindings to the native library are only available with the feature `native`,
+//! which is enabled by default. The sequential sweeping of latches and the mapping
+//! to lookup tables are implemented in Rust and are always available.
 
+#[cfg(feature = "native")]
 #[doc(hidden)]
 mod bindings;
+mod lut;
+mod sweep;
 
+#[cfg(feature = "native")]
+use std::cell::Cell;
+#[cfg(feature = "native")]
 use std::error::Error;
+#[cfg(feature = "native")]
 use std::fmt;
+#[cfg(feature = "native")]
 use std::os::raw::c_int;
+#[cfg(feature = "native")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
+#[cfg(feature = "native")]
 use ::aiger::Aiger;
 
+#[cfg(feature = "native")]
 use bindings::*;
 
+pub use lut::{map_luts, LutNetwork, MAX_LUT_SIZE};
+pub use sweep::sweep_latches;
+
+/// Guard for the frame of the ABC framework.
+///
+/// ABC keeps its frame in global state, which is set up by `Abc_Start` and torn
+/// down by `Abc_Stop`, so at most one instance may exist at any time in the process.
+#[cfg(feature = "native")]
+static FRAME: Mutex<()> = Mutex::new(());
+
+#[cfg(feature = "native")]
+thread_local! {
+    /// Whether the current thread holds an instance of the ABC framework.
+    static FRAME_HELD: Cell<bool> = Cell::new(false);
+}
+
 /// An instance of the ABC framework.
+///
+/// As the ABC framework uses global state, instances are exclusive: creating an
+/// instance blocks until any other instance in the process has been dropped.
+/// Instances are neither `Send` nor `Sync`, and creating a second instance on
+/// a thread that already holds one fails instead of blocking.
+#[cfg(feature = "native")]
 #[derive(Debug)]
 pub struct Abc {
     /// Raw pointer to the frame.
     frame: *mut Abc_Frame_t,
+    /// Guard for exclusive access to the frame, released after the frame is stopped.
+    _guard: MutexGuard<'static, ()>,
 }
 
+#[cfg(feature = "native")]
 impl Drop for Abc {
     fn drop(&mut self) {
         unsafe { Abc_Stop(self.frame) }
+        FRAME_HELD.with(|held| held.set(false));
     }
 }
 
 /// An error returned by the ABC framework.
+#[cfg(feature = "native")]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum AbcError {
     /// The ABC framework could not perform an operation because memory could not be allocated.
     MemoryOut,
+    /// The ABC framework is already used by an instance on the current thread.
+    InUse,
 }
 
+#[cfg(feature = "native")]
 impl fmt::Display for AbcError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -39,25 +85,39 @@ impl fmt::Display for AbcError {
             "ABC error: {}",
             match self {
                 Self::MemoryOut => "Out of memory",
+                Self::InUse => "Already in use by this thread",
             }
         )
     }
 }
 
+#[cfg(feature = "native")]
 impl Error for AbcError {}
 
+#[cfg(feature = "native")]
 impl Abc {
-    /// Creates a new instance of the ABC framework.
+    /// Creates a new instance of the ABC framework, waiting until any other
+    /// instance in the process has been dropped.
     ///
     /// # Errors
     ///
-    /// Returns an error if the framework can not be initialized.
+    /// Returns an error if the current thread already holds an instance or
+    /// if the framework can not be initialized.
     pub fn new() -> Result<Self, AbcError> {
+        if FRAME_HELD.with(Cell::get) {
+            return Err(AbcError::InUse);
+        }
+        // the frame is stopped before the guard is released, even after a panic
+        let guard = FRAME.lock().unwrap_or_else(PoisonError::into_inner);
         let frame = unsafe { Abc_Start() };
         if frame.is_null() {
             Err(AbcError::MemoryOut)
         } else {
-            Ok(Self { frame })
+            FRAME_HELD.with(|held| held.set(true));
+            Ok(Self {
+                frame,
+                _guard: guard,
+            })
         }
     }
 
@@ -234,16 +294,50 @@ impl Abc {
         let params_ptr = &params as *const _ as *mut _;
         self.change_network_with(|ntk| unsafe { Abc_NtkDRefactor(ntk, params_ptr) });
     }
+
+    /// Performs sequential cleanup of the latches in the current network,
+    /// which preserves sequential equivalence.
+    ///
+    /// Removes latches outside the cone of influence of the outputs and replaces latches
+    /// that never change their initial value by constants. This is repeated until no more
+    /// latch can be removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `merge`: Also merge latches with the same initial value and structurally
+    ///   equal next-state functions (default: `true`).
+    pub fn sequential_sweep(&mut self, merge: bool) {
+        let aig = sweep::sweep_latches(&self.get_aiger(), merge);
+        self.set_aiger(&aig);
+    }
+    /// Maps the current network to a network of lookup tables for FPGAs.
+    ///
+    /// The mapping is optimized for depth first and area second.
+    ///
+    /// # Arguments
+    ///
+    /// * `lut_size`: The maximum number of inputs of a lookup table.
+    ///   Must be in range `2..=6` (default: `6`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if an argument is out of range.
+    pub fn map_luts(&mut self, lut_size: usize) -> LutNetwork {
+        lut::map_luts(&self.get_aiger(), lut_size)
+    }
 }
 
 /// Tests for the ABC framework.
 #[cfg(test)]
 mod tests {
-    use ::aiger::{AigerConstructor, Literal};
+    use ::aiger::AigerConstructor;
+    #[cfg(feature = "native")]
+    use ::aiger::Literal;
 
     use super::*;
 
     /// Helper function to construct a small aiger circuit with inputs, latches and outputs
+    #[cfg(feature = "native")]
     fn simple_aig() -> Aiger {
         let mut aig = AigerConstructor::new(2, 1).unwrap();
 
@@ -261,6 +355,7 @@ mod tests {
 
     /// Test setting and getting an aiger circuit in the ABC framework
     #[test]
+    #[cfg(feature = "native")]
     fn test_set_and_get() {
         let aig = simple_aig();
         let before = format!("{}", aig);
@@ -274,10 +369,22 @@ mod tests {
         assert_eq!(before, after);
     }
 
+    /// Test that a second instance on the same thread is rejected instead of blocking,
+    /// and that a new instance can be created once the first one is dropped.
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_exclusive_instance() {
+        let abc = Abc::new().unwrap();
+        assert_eq!(Abc::new().unwrap_err(), AbcError::InUse);
+        drop(abc);
+        assert!(Abc::new().is_ok());
+    }
+
     /// Test the operations in the ABC framework with different parameters.
     ///
     /// Only tests that no function panics, not that the output is correct.
     #[test]
+    #[cfg(feature = "native")]
     fn test_operations() {
         let aig = simple_aig();
         let mut abc = Abc::new().unwrap();
@@ -313,11 +420,18 @@ mod tests {
         abc.drefactor(2, 12, 5, true, false);
         abc.drefactor(2, 12, 5, true, true);
 
+        abc.sequential_sweep(false);
+        abc.sequential_sweep(true);
+
+        abc.map_luts(2);
+        abc.map_luts(6);
+
         abc.get_aiger();
     }
 
     /// Test the balance operation in the ABC framework and that it actually balances a circuit.
     #[test]
+    #[cfg(feature = "native")]
     fn test_balance() {
         let mut aig = AigerConstructor::new(4, 0).unwrap();
 
@@ -342,4 +456,84 @@ mod tests {
         let after = format!("{}", aig);
         assert_eq!(after, "aag 7 4 0 1 3\n2\n4\n6\n8\n14\n10 2 4\n12 6 8\n14 10 12\ni0 x0\ni1 x1\ni2 x2\ni3 x3\no0 out\n");
     }
+
+    /// Test the sequential sweep and that it removes constant, equivalent and unused latches.
+    #[test]
+    fn test_sequential_sweep() {
+        let mut aig = AigerConstructor::new(2, 4).unwrap();
+
+        let x = aig.add_input("x");
+        let y = aig.add_input("y");
+        let a = aig.add_latch("a");
+        let b = aig.add_latch("b");
+        let c = aig.add_latch("c");
+        let d = aig.add_latch("d");
+        let xy = aig.add_and(x, y);
+        aig.set_latch_next(a, xy);
+        aig.set_latch_next(b, xy);
+        aig.set_latch_next(c, c);
+        aig.set_latch_next(d, x);
+        let ab = aig.add_and(a, b);
+        aig.add_output("o1", ab);
+        let cy = aig.add_or(c, y);
+        aig.add_output("o2", cy);
+
+        let aig = aig.into_aiger();
+        let swept = sweep::sweep_latches(&aig, false);
+        assert_eq!(swept.num_latches(), 2);
+        let swept = sweep::sweep_latches(&aig, true);
+        assert_eq!(
+            format!("{}", swept),
+            "aag 4 2 1 2 1\n2\n4\n6 8\n6\n4\n8 2 4\ni0 x\ni1 y\nl0 a\no0 o1\no1 o2\n"
+        );
+    }
+
+    /// Test the mapping to lookup tables and the output in BLIF format.
+    #[test]
+    fn test_map_luts() {
+        let mut aig = AigerConstructor::new(4, 1).unwrap();
+
+        let x0 = aig.add_input("x0");
+        let x1 = aig.add_input("x1");
+        let x2 = aig.add_input("x2");
+        let x3 = aig.add_input("x3");
+        let l = aig.add_latch("l");
+        let x01 = aig.add_and(x0, x1);
+        let x012 = aig.add_or(x01, x2);
+        let x0123 = aig.add_and(x012, x3);
+        aig.set_latch_next(l, !x0123);
+        aig.add_output("out", x0123);
+        aig.add_output("state", l);
+
+        let aig = aig.into_aiger();
+        let luts = lut::map_luts(&aig, 4);
+        assert_eq!(luts.num_luts(), 1);
+        assert_eq!(luts.depth(), 1);
+        assert_eq!(
+            format!("{}", luts),
+            ".model controller\n.inputs x0 x1 x2 x3\n.outputs out state\n.latch $next:l l 0\n\
+            .names x0 x1 x2 x3 $n8\n1101 1\n0011 1\n1011 1\n0111 1\n1111 1\n\
+            .names $n8 $next:l\n0 1\n.names $n8 out\n1 1\n.names l state\n1 1\n.end\n"
+        );
+        let luts = lut::map_luts(&aig, 2);
+        assert_eq!(luts.num_luts(), 3);
+        assert_eq!(luts.depth(), 3);
+    }
+
+    /// Test that the names of the circuit do not collide with generated names.
+    #[test]
+    fn test_map_luts_names() {
+        let mut aig = AigerConstructor::new(1, 1).unwrap();
+        let x = aig.add_input("$n1");
+        let l = aig.add_latch("l");
+        aig.set_latch_next(l, !x);
+        aig.add_output("l_next", l);
+
+        let luts = lut::map_luts(&aig.into_aiger(), 4);
+        assert_eq!(
+            format!("{}", luts),
+            ".model controller\n.inputs $$n1\n.outputs l_next\n.latch $next:l l 0\n\
+            .names $$n1 $next:l\n0 1\n.names l l_next\n1 1\n.end\n"
+        );
+    }
 }
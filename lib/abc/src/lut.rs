@@ -0,0 +1,446 @@
+//! Mapping of aiger circuits to networks of lookup tables.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::os::raw::c_uint;
+
+use ::aiger::{aiger_lit2var, aiger_sign, Aiger, SymbolKind, AIGER_FALSE, AIGER_TRUE};
+
+/// The maximum number of inputs of a lookup table.
+pub const MAX_LUT_SIZE: usize = 6;
+
+/// The maximum number of cuts stored for each and gate during the mapping.
+const MAX_CUTS: usize = 8;
+
+/// The truth tables of the inputs of a lookup table.
+const VAR_MASKS: [u64; MAX_LUT_SIZE] = [
+    0xAAAA_AAAA_AAAA_AAAA,
+    0xCCCC_CCCC_CCCC_CCCC,
+    0xF0F0_F0F0_F0F0_F0F0,
+    0xFF00_FF00_FF00_FF00,
+    0xFFFF_0000_FFFF_0000,
+    0xFFFF_FFFF_0000_0000,
+];
+
+/// The prefix of the names generated for signals without a name, which are thus
+/// distinct from the escaped names of the circuit, see [`escape_name`].
+const RESERVED_PREFIX: char = '$';
+
+/// Escapes a name of the circuit such that it does not start with the reserved prefix
+/// followed by another character, by doubling a leading reserved prefix.
+fn escape_name(name: String) -> String {
+    if name.starts_with(RESERVED_PREFIX) {
+        format!("{}{}", RESERVED_PREFIX, name)
+    } else {
+        name
+    }
+}
+
+/// A cut of an and gate, i.e. a set of variables separating the gate from the inputs and latches.
+#[derive(Debug, Clone)]
+struct Cut {
+    /// The sorted variables of the leaves of the cut.
+    leaves: Vec<c_uint>,
+    /// The depth of the mapping using this cut.
+    depth: u32,
+    /// The estimated area of the mapping using this cut.
+    area_flow: f32,
+}
+
+/// A lookup table in a mapped network.
+#[derive(Debug, Clone)]
+struct Lut {
+    /// The variable of the and gate computed by the lookup table.
+    var: c_uint,
+    /// The variables of the inputs of the lookup table.
+    leaves: Vec<c_uint>,
+    /// The truth table of the lookup table, where bit `i` is the value
+    /// for the assignment with the `j`-th leaf set to bit `j` of `i`.
+    truth: u64,
+}
+
+/// A latch in a mapped network.
+#[derive(Debug, Clone)]
+struct Latch {
+    /// The variable of the latch.
+    var: c_uint,
+    /// The literal of the next value.
+    next: c_uint,
+    /// The initial value, if the latch is initialized.
+    init: Option<bool>,
+}
+
+/// A sequential network of lookup tables with a bounded number of inputs,
+/// obtained by mapping an aiger circuit.
+///
+/// The network is written in BLIF format when formatted.
+#[derive(Debug, Clone)]
+pub struct LutNetwork {
+    /// The names of the signals indexed by the variables of the original circuit.
+    names: Vec<String>,
+    /// The variables of the inputs.
+    inputs: Vec<c_uint>,
+    /// The latches.
+    latches: Vec<Latch>,
+    /// The names and literals of the outputs.
+    outputs: Vec<(String, c_uint)>,
+    /// The lookup tables in topological order.
+    luts: Vec<Lut>,
+    /// The maximum number of lookup tables on a path between inputs, latches and outputs.
+    depth: u32,
+}
+
+impl LutNetwork {
+    /// Returns the number of lookup tables in the network.
+    pub fn num_luts(&self) -> usize {
+        self.luts.len()
+    }
+
+    /// Returns the number of latches in the network.
+    pub fn num_latches(&self) -> usize {
+        self.latches.len()
+    }
+
+    /// Returns the maximum number of lookup tables on a path between
+    /// inputs, latches and outputs.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Writes a driver for the signal `target` with the value of the given literal.
+    fn write_buffer(&self, f: &mut fmt::Formatter<'_>, lit: c_uint, target: &str) -> fmt::Result {
+        match lit {
+            AIGER_FALSE => writeln!(f, ".names {}", target),
+            AIGER_TRUE => writeln!(f, ".names {}\n1", target),
+            _ => writeln!(
+                f,
+                ".names {} {}\n{} 1",
+                self.names[aiger_lit2var(lit) as usize],
+                target,
+                1 - aiger_sign(lit)
+            ),
+        }
+    }
+
+    /// Returns the name of the signal for the next value of the given latch,
+    /// and whether a separate driver is needed for it.
+    fn latch_input(&self, latch: &Latch) -> (String, bool) {
+        if latch.next == AIGER_FALSE || latch.next == AIGER_TRUE || aiger_sign(latch.next) == 1 {
+            (
+                format!("{}next:{}", RESERVED_PREFIX, self.names[latch.var as usize]),
+                true,
+            )
+        } else {
+            (
+                self.names[aiger_lit2var(latch.next) as usize].clone(),
+                false,
+            )
+        }
+    }
+}
+
+impl fmt::Display for LutNetwork {
+    /// Formats the network in the Berkeley Logic Interchange Format (BLIF).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, ".model controller")?;
+        write!(f, ".inputs")?;
+        for &var in &self.inputs {
+            write!(f, " {}", self.names[var as usize])?;
+        }
+        writeln!(f)?;
+        write!(f, ".outputs")?;
+        for (name, _) in &self.outputs {
+            write!(f, " {}", name)?;
+        }
+        writeln!(f)?;
+        for latch in &self.latches {
+            let (input, _) = self.latch_input(latch);
+            let init = match latch.init {
+                Some(false) => 0,
+                Some(true) => 1,
+                None => 3,
+            };
+            writeln!(
+                f,
+                ".latch {} {} {}",
+                input, self.names[latch.var as usize], init
+            )?;
+        }
+        for lut in &self.luts {
+            write!(f, ".names")?;
+            for &leaf in &lut.leaves {
+                write!(f, " {}", self.names[leaf as usize])?;
+            }
+            writeln!(f, " {}", self.names[lut.var as usize])?;
+            for row in 0..(1_u64 << lut.leaves.len()) {
+                if (lut.truth >> row) & 1 == 1 {
+                    let inputs: String = (0..lut.leaves.len())
+                        .map(|j| if (row >> j) & 1 == 1 { '1' } else { '0' })
+                        .collect();
+                    if inputs.is_empty() {
+                        writeln!(f, "1")?;
+                    } else {
+                        writeln!(f, "{} 1", inputs)?;
+                    }
+                }
+            }
+        }
+        for latch in &self.latches {
+            let (input, needs_driver) = self.latch_input(latch);
+            if needs_driver {
+                self.write_buffer(f, latch.next, &input)?;
+            }
+        }
+        for (name, lit) in &self.outputs {
+            self.write_buffer(f, *lit, name)?;
+        }
+        writeln!(f, ".end")
+    }
+}
+
+/// Returns the variables of the and gates of the circuit in topological order,
+/// given the right-hand sides of the and gates indexed by their variables.
+fn topological_order(gates: &[Option<(c_uint, c_uint)>]) -> Vec<c_uint> {
+    let mut order = Vec::new();
+    let mut visited = vec![false; gates.len()];
+    for (root, gate) in gates.iter().enumerate() {
+        if gate.is_none() || visited[root] {
+            continue;
+        }
+        let mut stack = vec![(root, false)];
+        while let Some((var, expanded)) = stack.pop() {
+            if expanded {
+                order.push(var as c_uint);
+                continue;
+            }
+            if visited[var] {
+                continue;
+            }
+            visited[var] = true;
+            stack.push((var, true));
+            if let Some((rhs0, rhs1)) = gates[var] {
+                for &rhs in &[rhs0, rhs1] {
+                    let child = aiger_lit2var(rhs) as usize;
+                    if gates[child].is_some() && !visited[child] {
+                        stack.push((child, false));
+                    }
+                }
+            }
+        }
+    }
+    order
+}
+
+/// Returns the union of the two sorted sets of leaves,
+/// or `None` if the union has more than `lut_size` elements.
+fn merge_leaves(lhs: &[c_uint], rhs: &[c_uint], lut_size: usize) -> Option<Vec<c_uint>> {
+    let mut leaves = Vec::with_capacity(lut_size);
+    let (mut i, mut j) = (0, 0);
+    while i < lhs.len() || j < rhs.len() {
+        let leaf = if j == rhs.len() || (i < lhs.len() && lhs[i] < rhs[j]) {
+            i += 1;
+            lhs[i - 1]
+        } else if i == lhs.len() || rhs[j] < lhs[i] {
+            j += 1;
+            rhs[j - 1]
+        } else {
+            i += 1;
+            j += 1;
+            lhs[i - 1]
+        };
+        if leaves.len() == lut_size {
+            return None;
+        }
+        leaves.push(leaf);
+    }
+    Some(leaves)
+}
+
+/// Computes the truth table of the given variable as a function of the given leaves.
+fn truth_table(var: c_uint, leaves: &[c_uint], gates: &[Option<(c_uint, c_uint)>]) -> u64 {
+    let mut values: HashMap<c_uint, u64> = leaves
+        .iter()
+        .zip(VAR_MASKS.iter())
+        .map(|(&leaf, &mask)| (leaf, mask))
+        .collect();
+    values.insert(0, 0);
+    let value = |values: &HashMap<c_uint, u64>, lit: c_uint| {
+        values
+            .get(&aiger_lit2var(lit))
+            .map(|&v| if aiger_sign(lit) == 1 { !v } else { v })
+    };
+    let mut stack = vec![var];
+    while let Some(&top) = stack.last() {
+        if values.contains_key(&top) {
+            stack.pop();
+            continue;
+        }
+        let (rhs0, rhs1) = gates[top as usize].expect("leaves do not form a cut");
+        match (value(&values, rhs0), value(&values, rhs1)) {
+            (Some(v0), Some(v1)) => {
+                values.insert(top, v0 & v1);
+                stack.pop();
+            }
+            (v0, v1) => {
+                if v0.is_none() {
+                    stack.push(aiger_lit2var(rhs0));
+                }
+                if v1.is_none() {
+                    stack.push(aiger_lit2var(rhs1));
+                }
+            }
+        }
+    }
+    values[&var]
+}
+
+/// Maps the given circuit to a network of lookup tables with at most `lut_size` inputs.
+///
+/// The mapping uses priority cuts and chooses for each gate the cut with the
+/// minimum depth, using the estimated area to break ties.
+///
+/// # Panics
+///
+/// Panics if `lut_size` is not in the range `2..=6`.
+pub fn map_luts(aig: &Aiger, lut_size: usize) -> LutNetwork {
+    assert!((2..=MAX_LUT_SIZE).contains(&lut_size));
+    let num_vars = aig.maxvar() as usize + 1;
+    let mut gates = vec![None; num_vars];
+    for (lhs, rhs0, rhs1) in aig.and_literals() {
+        gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+    }
+    let inputs: Vec<_> = aig
+        .input_literals()
+        .into_iter()
+        .map(aiger_lit2var)
+        .collect();
+    let latches: Vec<_> = aig
+        .latch_literals()
+        .into_iter()
+        .map(|(lit, next, reset)| Latch {
+            var: aiger_lit2var(lit),
+            next,
+            init: match reset {
+                AIGER_FALSE => Some(false),
+                AIGER_TRUE => Some(true),
+                _ => None,
+            },
+        })
+        .collect();
+    let output_lits = aig.output_literals();
+
+    // count the references to estimate the area
+    let mut refs = vec![0_u32; num_vars];
+    for &(rhs0, rhs1) in gates.iter().flatten() {
+        refs[aiger_lit2var(rhs0) as usize] += 1;
+        refs[aiger_lit2var(rhs1) as usize] += 1;
+    }
+    for &lit in latches.iter().map(|l| &l.next).chain(&output_lits) {
+        refs[aiger_lit2var(lit) as usize] += 1;
+    }
+
+    // enumerate the cuts of all and gates in topological order
+    let order = topological_order(&gates);
+    let mut cuts: Vec<Vec<Cut>> = vec![Vec::new(); num_vars];
+    let trivial = |var: c_uint| Cut {
+        leaves: if var == 0 { Vec::new() } else { vec![var] },
+        depth: 0,
+        area_flow: 0.0,
+    };
+    for &var in &order {
+        let (rhs0, rhs1) = gates[var as usize].unwrap();
+        let fanin_cuts = |cuts: &[Vec<Cut>], fanin: c_uint| -> Vec<Cut> {
+            let mut fanin_cuts = cuts[fanin as usize].clone();
+            fanin_cuts.push(trivial(fanin));
+            fanin_cuts
+        };
+        let cuts0 = fanin_cuts(&cuts, aiger_lit2var(rhs0));
+        let cuts1 = fanin_cuts(&cuts, aiger_lit2var(rhs1));
+        let mut candidates: Vec<Cut> = Vec::new();
+        for cut0 in &cuts0 {
+            for cut1 in &cuts1 {
+                let leaves = match merge_leaves(&cut0.leaves, &cut1.leaves, lut_size) {
+                    Some(leaves) => leaves,
+                    None => continue,
+                };
+                if candidates.iter().any(|c| c.leaves == leaves) {
+                    continue;
+                }
+                let best = |leaf: c_uint| cuts[leaf as usize].first();
+                let depth = 1 + leaves
+                    .iter()
+                    .map(|&leaf| best(leaf).map_or(0, |c| c.depth))
+                    .max()
+                    .unwrap_or(0);
+                let area = 1.0
+                    + leaves
+                        .iter()
+                        .map(|&leaf| best(leaf).map_or(0.0, |c| c.area_flow))
+                        .sum::<f32>();
+                candidates.push(Cut {
+                    leaves,
+                    depth,
+                    area_flow: area / refs[var as usize].max(1) as f32,
+                });
+            }
+        }
+        candidates.sort_by(|c1, c2| {
+            c1.depth
+                .cmp(&c2.depth)
+                .then(c1.area_flow.partial_cmp(&c2.area_flow).unwrap())
+                .then(c1.leaves.len().cmp(&c2.leaves.len()))
+        });
+        candidates.truncate(MAX_CUTS);
+        cuts[var as usize] = candidates;
+    }
+
+    // select the best cuts from the outputs and latches towards the inputs
+    let mut required = vec![false; num_vars];
+    for &lit in latches.iter().map(|l| &l.next).chain(&output_lits) {
+        required[aiger_lit2var(lit) as usize] = true;
+    }
+    let mut luts = Vec::new();
+    let mut depth = 0;
+    for &var in order.iter().rev() {
+        if !required[var as usize] {
+            continue;
+        }
+        let cut = &cuts[var as usize][0];
+        for &leaf in &cut.leaves {
+            required[leaf as usize] = true;
+        }
+        depth = depth.max(cut.depth);
+        luts.push(Lut {
+            var,
+            leaves: cut.leaves.clone(),
+            truth: truth_table(var, &cut.leaves, &gates),
+        });
+    }
+    luts.reverse();
+
+    // assign names to the signals, where generated names use the reserved prefix
+    let generated = |kind: char, index: usize| format!("{}{}{}", RESERVED_PREFIX, kind, index);
+    let mut names: Vec<_> = (0..num_vars).map(|var| generated('n', var)).collect();
+    let symbol_names = |kind| aig.symbol_names(kind).into_iter().enumerate();
+    for (i, name) in symbol_names(SymbolKind::Input) {
+        names[inputs[i] as usize] = name.map_or_else(|| generated('i', i), escape_name);
+    }
+    for (j, name) in symbol_names(SymbolKind::Latch) {
+        names[latches[j].var as usize] = name.map_or_else(|| generated('l', j), escape_name);
+    }
+    let outputs = symbol_names(SymbolKind::Output)
+        .map(|(i, name)| {
+            let name = name.map_or_else(|| generated('o', i), escape_name);
+            (name, output_lits[i])
+        })
+        .collect();
+
+    LutNetwork {
+        names,
+        inputs,
+        latches,
+        outputs,
+        luts,
+        depth,
+    }
+}
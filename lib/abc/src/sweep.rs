@@ -0,0 +1,229 @@
+//! Sequential sweeping of latches in aiger circuits.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::os::raw::c_uint;
+
+use ::aiger::{
+    aiger_lit2var, aiger_not, aiger_sign, aiger_var2lit, Aiger, SymbolKind, AIGER_FALSE, AIGER_TRUE,
+};
+
+/// A structurally hashed and-inverter graph, where variable `0` is the constant,
+/// the variables up to an offset are inputs or latches, and the remaining
+/// variables are and gates in topological order.
+struct Graph {
+    /// The first variable of an and gate.
+    offset: c_uint,
+    /// The right-hand sides of the and gates.
+    ands: Vec<(c_uint, c_uint)>,
+    /// The variables of the and gates indexed by their right-hand sides.
+    table: HashMap<(c_uint, c_uint), c_uint>,
+}
+
+impl Graph {
+    fn new(offset: c_uint) -> Self {
+        Self {
+            offset,
+            ands: Vec::new(),
+            table: HashMap::new(),
+        }
+    }
+
+    /// Returns the literal of the conjunction of the two literals,
+    /// adding a new and gate if no equivalent gate exists.
+    fn and(&mut self, lhs: c_uint, rhs: c_uint) -> c_uint {
+        let (lhs, rhs) = if lhs <= rhs { (lhs, rhs) } else { (rhs, lhs) };
+        if lhs == AIGER_FALSE || lhs == aiger_not(rhs) {
+            return AIGER_FALSE;
+        }
+        if lhs == AIGER_TRUE || lhs == rhs {
+            return rhs;
+        }
+        let offset = self.offset;
+        let ands = &mut self.ands;
+        let var = *self.table.entry((lhs, rhs)).or_insert_with(|| {
+            ands.push((lhs, rhs));
+            offset + ands.len() as c_uint - 1
+        });
+        aiger_var2lit(var)
+    }
+
+    /// Translates a literal of the original circuit into a literal of the graph,
+    /// where `gates` contains the right-hand sides of the and gates in the original
+    /// circuit and `map` the already translated variables.
+    fn translate(
+        &mut self,
+        lit: c_uint,
+        gates: &[Option<(c_uint, c_uint)>],
+        map: &mut [Option<c_uint>],
+    ) -> c_uint {
+        let var = |lit| aiger_lit2var(lit) as usize;
+        let mut stack = vec![var(lit)];
+        while let Some(&top) = stack.last() {
+            if map[top].is_some() {
+                stack.pop();
+                continue;
+            }
+            let (rhs0, rhs1) = gates[top].expect("undefined literal");
+            match (map[var(rhs0)], map[var(rhs1)]) {
+                (Some(lhs), Some(rhs)) => {
+                    map[top] = Some(self.and(lhs ^ aiger_sign(rhs0), rhs ^ aiger_sign(rhs1)));
+                    stack.pop();
+                }
+                (lhs, rhs) => {
+                    if lhs.is_none() {
+                        stack.push(var(rhs0));
+                    }
+                    if rhs.is_none() {
+                        stack.push(var(rhs1));
+                    }
+                }
+            }
+        }
+        map[var(lit)].unwrap() ^ aiger_sign(lit)
+    }
+}
+
+/// Returns a sequentially equivalent circuit with latches removed from the given circuit.
+///
+/// Latches with a constant reset value whose next value is their reset value or
+/// their current value are replaced by the constant, and latches outside the
+/// cone of influence of the outputs are removed. If `merge` is set, latches with
+/// the same reset value and structurally equal next values are merged.
+/// The steps are repeated until no more latch can be removed.
+///
+/// The inputs and outputs are retained, but comments are not copied.
+pub fn sweep_latches(aig: &Aiger, merge: bool) -> Aiger {
+    let inputs = aig.input_literals();
+    let latches = aig.latch_literals();
+    let outputs = aig.output_literals();
+    let num_vars = aig.maxvar() as usize + 1;
+    let mut gates = vec![None; num_vars];
+    for (lhs, rhs0, rhs1) in aig.and_literals() {
+        gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+    }
+    let input_var = |i: usize| 1 + i as c_uint;
+    let latch_var = |j: usize| 1 + (inputs.len() + j) as c_uint;
+    let offset = latch_var(latches.len());
+
+    // iteratively find latches that can be replaced by a constant or another latch
+    let mut replacements: Vec<Option<c_uint>> = vec![None; latches.len()];
+    let (graph, nexts, output_lits) = loop {
+        let mut graph = Graph::new(offset);
+        let mut map = vec![None; num_vars];
+        map[0] = Some(AIGER_FALSE);
+        for (i, &lit) in inputs.iter().enumerate() {
+            map[aiger_lit2var(lit) as usize] = Some(aiger_var2lit(input_var(i)));
+        }
+        for (j, &(lit, _, _)) in latches.iter().enumerate() {
+            // follow replacements, as the replacing latch may have been replaced later
+            let mut replacement = aiger_var2lit(latch_var(j));
+            while let Some(next_replacement) = replacements
+                .get((aiger_lit2var(replacement).wrapping_sub(latch_var(0))) as usize)
+                .copied()
+                .flatten()
+            {
+                replacement = next_replacement;
+            }
+            map[aiger_lit2var(lit) as usize] = Some(replacement);
+        }
+        let nexts: Vec<_> = latches
+            .iter()
+            .map(|&(_, next, _)| graph.translate(next, &gates, &mut map))
+            .collect();
+        let output_lits: Vec<_> = outputs
+            .iter()
+            .map(|&lit| graph.translate(lit, &gates, &mut map))
+            .collect();
+
+        let mut changed = false;
+        let mut classes = HashMap::new();
+        for (j, &(_, _, reset)) in latches.iter().enumerate() {
+            if replacements[j].is_some() || (reset != AIGER_FALSE && reset != AIGER_TRUE) {
+                continue;
+            }
+            if nexts[j] == reset || nexts[j] == aiger_var2lit(latch_var(j)) {
+                replacements[j] = Some(reset);
+                changed = true;
+            } else if merge {
+                match classes.entry((nexts[j], reset)) {
+                    Entry::Occupied(entry) => {
+                        replacements[j] = Some(*entry.get());
+                        changed = true;
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(aiger_var2lit(latch_var(j)));
+                    }
+                }
+            }
+        }
+        if !changed {
+            break (graph, nexts, output_lits);
+        }
+    };
+
+    // collect the cone of influence of the outputs
+    let mut used = vec![false; offset as usize + graph.ands.len()];
+    let mut stack: Vec<_> = output_lits.iter().map(|&lit| aiger_lit2var(lit)).collect();
+    while let Some(var) = stack.pop() {
+        if used[var as usize] {
+            continue;
+        }
+        used[var as usize] = true;
+        if var >= offset {
+            let (rhs0, rhs1) = graph.ands[(var - offset) as usize];
+            stack.push(aiger_lit2var(rhs0));
+            stack.push(aiger_lit2var(rhs1));
+        } else if var >= latch_var(0) {
+            let j = (var - latch_var(0)) as usize;
+            stack.push(aiger_lit2var(nexts[j]));
+        }
+    }
+
+    // renumber the used variables and construct the new circuit
+    let mut new_vars: Vec<c_uint> = (0..latch_var(0)).collect();
+    new_vars.resize(used.len(), 0);
+    let mut num_new_vars = latch_var(0);
+    for (var, &var_used) in used.iter().enumerate().skip(latch_var(0) as usize) {
+        if var_used {
+            new_vars[var] = num_new_vars;
+            num_new_vars += 1;
+        }
+    }
+    let new_lit =
+        |lit: c_uint| aiger_var2lit(new_vars[aiger_lit2var(lit) as usize]) ^ aiger_sign(lit);
+
+    let mut new_aig = Aiger::new().unwrap();
+    let input_names = aig.symbol_names(SymbolKind::Input);
+    for (i, name) in input_names.iter().enumerate() {
+        new_aig.add_input(aiger_var2lit(input_var(i)), name.as_deref());
+    }
+    let latch_names = aig.symbol_names(SymbolKind::Latch);
+    for (j, (&(_, _, reset), name)) in latches.iter().zip(&latch_names).enumerate() {
+        if used[latch_var(j) as usize] {
+            let lit = new_lit(aiger_var2lit(latch_var(j)));
+            new_aig.add_latch(lit, new_lit(nexts[j]), name.as_deref());
+            let reset = if reset == AIGER_FALSE || reset == AIGER_TRUE {
+                reset
+            } else {
+                lit
+            };
+            new_aig.add_reset(lit, reset);
+        }
+    }
+    let output_names = aig.symbol_names(SymbolKind::Output);
+    for (&lit, name) in output_lits.iter().zip(&output_names) {
+        new_aig.add_output(new_lit(lit), name.as_deref());
+    }
+    for (k, &(rhs0, rhs1)) in graph.ands.iter().enumerate() {
+        let var = offset + k as c_uint;
+        if used[var as usize] {
+            new_aig.add_and(
+                aiger_var2lit(new_vars[var as usize]),
+                new_lit(rhs0),
+                new_lit(rhs1),
+            );
+        }
+    }
+    new_aig
+}
@@ -3,8 +3,7 @@
 #[doc(hidden)]
 mod bindings;
 
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::io::{self, Read, Write};
@@ -54,6 +53,33 @@ impl Drop for Aiger {
     }
 }
 
+impl Clone for Aiger {
+    /// Returns a copy of this aiger circuit with its symbol table and comments.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new aiger circuit could not be initialized.
+    fn clone(&self) -> Self {
+        self.copy_with(|_, _, name| name.map(str::to_string), true)
+            .expect("failed to copy aiger circuit")
+    }
+}
+
+// Safety: the circuit exclusively owns the underlying aiger struct,
+// which does not reference any thread-local or global state.
+unsafe impl Send for Aiger {}
+
+/// The kind of a symbol in the symbol table of an aiger circuit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// An input of the circuit.
+    Input,
+    /// A latch of the circuit.
+    Latch,
+    /// An output of the circuit.
+    Output,
+}
+
 /// The mode for writing the aiger circuit.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum AigerMode {
@@ -92,7 +118,8 @@ impl Aiger {
     ///
     /// # Safety
     ///
-    /// The pointer `aiger` must point to a valid aiger struct.
+    /// The pointer `aiger` must point to a valid aiger struct,
+    /// which is not owned by any other value.
     pub unsafe fn from_raw(aiger: *mut AigerRaw) -> Self {
         Self { aiger }
     }
@@ -176,7 +203,7 @@ impl Aiger {
 
                 unsafe { aiger_add_latch(self.aiger, lit, next, c_name_ptr) };
             }
-            None => unsafe { aiger_add_input(self.aiger, lit, std::ptr::null()) },
+            None => unsafe { aiger_add_latch(self.aiger, lit, next, std::ptr::null()) },
         }
     }
 
@@ -187,6 +214,174 @@ impl Aiger {
         unsafe { aiger_add_reset(self.aiger, lit, reset) };
     }
 
+    /// Adds a line of comments to the aiger circuit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comment contains a new line or a nul character.
+    pub fn add_comment(&mut self, comment: &str) {
+        assert!(!comment.contains('\n'), "comment contains a new line");
+        let c_comment = CString::new(comment).unwrap();
+        unsafe { aiger_add_comment(self.aiger, c_comment.as_ptr()) };
+    }
+
+    /// Returns the lines of comments of the aiger circuit.
+    pub fn comments(&self) -> Vec<String> {
+        let mut comments = Vec::new();
+        let mut comment = unsafe { (*self.aiger).comments };
+        if comment.is_null() {
+            return comments;
+        }
+        while !unsafe { *comment }.is_null() {
+            let c_str = unsafe { CStr::from_ptr(*comment) };
+            comments.push(c_str.to_string_lossy().into_owned());
+            comment = unsafe { comment.add(1) };
+        }
+        comments
+    }
+
+    /// Returns the names of the symbols of the given kind in the symbol table,
+    /// or `None` for symbols without a name.
+    pub fn symbol_names(&self, kind: SymbolKind) -> Vec<Option<String>> {
+        self.symbols_of(kind)
+            .iter()
+            .map(|s| {
+                if s.name.is_null() {
+                    None
+                } else {
+                    let c_str = unsafe { CStr::from_ptr(s.name) };
+                    Some(c_str.to_string_lossy().into_owned())
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a copy of this aiger circuit with a new symbol table, keeping the comments.
+    ///
+    /// The function `rename` is called with the kind, the position and the current
+    /// name of each input, latch and output, and returns the new name, or `None`
+    /// if the symbol should not have a name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the new aiger circuit could not be initialized.
+    pub fn renamed<F>(&self, rename: F) -> Result<Self, &'static str>
+    where
+        F: FnMut(SymbolKind, usize, Option<&str>) -> Option<String>,
+    {
+        self.copy_with(rename, true)
+    }
+
+    /// Returns a copy of this aiger circuit without comments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the new aiger circuit could not be initialized.
+    pub fn without_comments(&self) -> Result<Self, &'static str> {
+        self.copy_with(|_, _, name| name.map(str::to_string), false)
+    }
+
+    /// Returns a copy of this aiger circuit without symbol table and comments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the new aiger circuit could not be initialized.
+    pub fn stripped(&self) -> Result<Self, &'static str> {
+        self.copy_with(|_, _, _| None, false)
+    }
+
+    /// Returns a copy of this aiger circuit with the given renaming of symbols,
+    /// including the comments if `comments` is set.
+    ///
+    /// Comments and symbols are removed by copying instead of using the functions
+    /// of the aiger library, as these leave the circuit in a state where no further
+    /// comments can be added.
+    fn copy_with<F>(&self, mut rename: F, comments: bool) -> Result<Self, &'static str>
+    where
+        F: FnMut(SymbolKind, usize, Option<&str>) -> Option<String>,
+    {
+        let mut aig = Self::new()?;
+        let mut names = |kind| -> Vec<Option<String>> {
+            self.symbol_names(kind)
+                .iter()
+                .enumerate()
+                .map(|(i, name)| rename(kind, i, name.as_deref()))
+                .collect()
+        };
+        let input_names = names(SymbolKind::Input);
+        let latch_names = names(SymbolKind::Latch);
+        let output_names = names(SymbolKind::Output);
+        for (lit, name) in self.input_literals().into_iter().zip(input_names) {
+            aig.add_input(lit, name.as_deref());
+        }
+        for ((lit, next, reset), name) in self.latch_literals().into_iter().zip(latch_names) {
+            aig.add_latch(lit, next, name.as_deref());
+            aig.add_reset(lit, reset);
+        }
+        for (lit, name) in self.output_literals().into_iter().zip(output_names) {
+            aig.add_output(lit, name.as_deref());
+        }
+        for (lhs, rhs0, rhs1) in self.and_literals() {
+            aig.add_and(lhs, rhs0, rhs1);
+        }
+        if comments {
+            for comment in self.comments() {
+                aig.add_comment(&comment);
+            }
+        }
+        Ok(aig)
+    }
+
+    /// Returns the symbols of the given kind as a slice.
+    fn symbols_of(&self, kind: SymbolKind) -> &[aiger_symbol] {
+        match kind {
+            SymbolKind::Input => self.symbols(unsafe { (*self.aiger).inputs }, self.num_inputs()),
+            SymbolKind::Latch => self.symbols(unsafe { (*self.aiger).latches }, self.num_latches()),
+            SymbolKind::Output => {
+                self.symbols(unsafe { (*self.aiger).outputs }, self.num_outputs())
+            }
+        }
+    }
+
+    /// Returns the symbols at the given pointer with the given length as a slice.
+    fn symbols(&self, symbols: *const aiger_symbol, len: c_uint) -> &[aiger_symbol] {
+        if len == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(symbols, len as usize) }
+        }
+    }
+
+    /// Returns the literals of the inputs.
+    pub fn input_literals(&self) -> Vec<c_uint> {
+        let symbols = self.symbols_of(SymbolKind::Input);
+        symbols.iter().map(|s| s.lit).collect()
+    }
+
+    /// Returns the literals of the latches, each together with
+    /// the literals of its next value and its reset value.
+    pub fn latch_literals(&self) -> Vec<(c_uint, c_uint, c_uint)> {
+        let symbols = self.symbols_of(SymbolKind::Latch);
+        symbols.iter().map(|s| (s.lit, s.next, s.reset)).collect()
+    }
+
+    /// Returns the literals of the outputs.
+    pub fn output_literals(&self) -> Vec<c_uint> {
+        let symbols = self.symbols_of(SymbolKind::Output);
+        symbols.iter().map(|s| s.lit).collect()
+    }
+
+    /// Returns the literals of the and gates, each as a triple of
+    /// the left-hand-side and the two right-hand-sides.
+    pub fn and_literals(&self) -> Vec<(c_uint, c_uint, c_uint)> {
+        let num_ands = self.num_ands();
+        if num_ands == 0 {
+            return Vec::new();
+        }
+        let ands = unsafe { std::slice::from_raw_parts((*self.aiger).ands, num_ands as usize) };
+        ands.iter().map(|a| (a.lhs, a.rhs0, a.rhs1)).collect()
+    }
+
     /// Writes the aiger circuit to the given writer in the given mode.
     ///
     /// # Errors
@@ -362,6 +557,122 @@ struct LiteralPair {
     lit1: Literal,
 }
 
+/// The configuration of the cache of and gates in an [`AigerConstructor`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AndCache {
+    /// Cache all and gates, such that an and gate with the same inputs is never
+    /// added twice.
+    Unbounded,
+    /// Cache at most the given number of and gates, where the least recently used
+    /// gate is evicted from a full cache. An evicted gate is added again if needed.
+    Bounded(usize),
+    /// Do not cache and gates, such that no memory is used for the cache.
+    Disabled,
+}
+
+impl Default for AndCache {
+    fn default() -> Self {
+        Self::Unbounded
+    }
+}
+
+/// Statistics of the cache of and gates in an [`AigerConstructor`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct AndCacheStats {
+    /// The number of and gates found in the cache.
+    pub hits: usize,
+    /// The number of and gates not found in the cache, which were added to the circuit.
+    pub misses: usize,
+    /// The number of and gates evicted from the cache.
+    pub evictions: usize,
+    /// The maximal number of and gates in the cache.
+    pub max_size: usize,
+}
+
+impl fmt::Display for AndCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hits: {}, misses: {}, evictions: {}, max size: {}",
+            self.hits, self.misses, self.evictions, self.max_size
+        )
+    }
+}
+
+/// A cache of and gates, mapping their inputs to the uncomplemented literal,
+/// with an optional capacity and least recently used eviction.
+struct AndGateCache {
+    config: AndCache,
+    /// The literal of each cached and gate with the time of its last use.
+    entries: HashMap<LiteralPair, (Literal, u64)>,
+    /// The inputs of the cached and gates by the time of their last use,
+    /// which is only maintained for a bounded cache.
+    recency: BTreeMap<u64, LiteralPair>,
+    time: u64,
+    stats: AndCacheStats,
+}
+
+impl AndGateCache {
+    fn new(config: AndCache) -> Self {
+        Self {
+            config,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            time: 0,
+            stats: AndCacheStats::default(),
+        }
+    }
+
+    /// Returns the literal of the and gate with the given inputs, if it is cached.
+    fn get(&mut self, pair: LiteralPair) -> Option<Literal> {
+        if self.config == AndCache::Disabled {
+            // no hashing at all for a disabled cache
+            self.stats.misses += 1;
+            return None;
+        }
+        self.time += 1;
+        let bounded = matches!(self.config, AndCache::Bounded(_));
+        match self.entries.get_mut(&pair) {
+            Some((lit, last_use)) => {
+                if bounded {
+                    self.recency.remove(last_use);
+                    self.recency.insert(self.time, pair);
+                }
+                *last_use = self.time;
+                self.stats.hits += 1;
+                Some(*lit)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Caches the literal of the and gate with the given inputs, evicting the least
+    /// recently used gate if the cache is full.
+    fn insert(&mut self, pair: LiteralPair, lit: Literal) {
+        match self.config {
+            AndCache::Disabled => return,
+            AndCache::Bounded(capacity) => {
+                if capacity == 0 {
+                    return;
+                }
+                if self.entries.len() >= capacity {
+                    let (&oldest, &evicted) = self.recency.iter().next().unwrap();
+                    self.recency.remove(&oldest);
+                    self.entries.remove(&evicted);
+                    self.stats.evictions += 1;
+                }
+                self.recency.insert(self.time, pair);
+            }
+            AndCache::Unbounded => (),
+        }
+        self.entries.insert(pair, (lit, self.time));
+        self.stats.max_size = self.stats.max_size.max(self.entries.len());
+    }
+}
+
 /// A high-level constructor for an aiger circuit that can be used to
 /// safely and incrementally add elements.
 ///
@@ -369,6 +680,8 @@ struct LiteralPair {
 /// inputs appear before latches and all latches before and gates.
 /// During construction, it hashes the inputs of existing and gates
 /// and reuses them, as well as performing simplications for the inputs.
+/// The cache of and gates may be bounded or disabled with [`AndCache`]
+/// to limit its memory for huge circuits.
 /// This ensures that an equivalent circuit is produced, but which may be smaller
 /// than a direct construction.
 ///
@@ -410,7 +723,7 @@ pub struct AigerConstructor {
     /// The count of and gates that were added.
     cur_and: usize,
     /// The cache of already added and gates, mapping their inputs to the uncomplemented literal.
-    and_cache: HashMap<LiteralPair, Literal>,
+    and_cache: AndGateCache,
 }
 
 impl AigerConstructor {
@@ -420,6 +733,20 @@ impl AigerConstructor {
     ///
     /// Returns an error if the initialization of the aiger circuit fails.
     pub fn new(num_inputs: usize, num_latches: usize) -> Result<Self, String> {
+        Self::with_and_cache(num_inputs, num_latches, AndCache::default())
+    }
+
+    /// Creates a new aiger circuit constructor with a pre-specified number of inputs and latches,
+    /// which caches and gates according to the given configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the initialization of the aiger circuit fails.
+    pub fn with_and_cache(
+        num_inputs: usize,
+        num_latches: usize,
+        and_cache: AndCache,
+    ) -> Result<Self, String> {
         let aig = Aiger::new()?;
         Ok(Self {
             aig,
@@ -429,10 +756,15 @@ impl AigerConstructor {
             num_latches,
             latches: Vec::with_capacity(num_latches),
             cur_and: 0,
-            and_cache: HashMap::new(),
+            and_cache: AndGateCache::new(and_cache),
         })
     }
 
+    /// Returns the statistics of the cache of and gates.
+    pub fn and_cache_stats(&self) -> AndCacheStats {
+        self.and_cache.stats
+    }
+
     /// Adds an and gate to the circuit with `lhs` and `rhs` as inputs,
     /// and returns the literal for the and gate.
     ///
@@ -454,18 +786,16 @@ impl AigerConstructor {
                 lit0: lhs,
                 lit1: rhs,
             };
-            match self.and_cache.entry(pair) {
-                Entry::Occupied(entry) => *entry.get(),
-                Entry::Vacant(entry) => {
-                    let lit = Literal(aiger_var2lit(
-                        (1 + self.num_inputs + self.num_latches + self.cur_and) as c_uint,
-                    ));
-                    self.aig.add_and(lit.0, lhs.0, rhs.0);
-                    self.cur_and += 1;
-                    entry.insert(lit);
-                    lit
-                }
+            if let Some(lit) = self.and_cache.get(pair) {
+                return lit;
             }
+            let lit = Literal(aiger_var2lit(
+                (1 + self.num_inputs + self.num_latches + self.cur_and) as c_uint,
+            ));
+            self.aig.add_and(lit.0, lhs.0, rhs.0);
+            self.cur_and += 1;
+            self.and_cache.insert(pair, lit);
+            lit
         }
     }
 
@@ -729,6 +1059,121 @@ mod tests {
         // the following does not yet work: ite(x, y, z) = (x ∧ y) ∨ (¬x ∧ ¬z)"
     }
 
+    /// Test that a bounded cache evicts the least recently used and gate, and that
+    /// a disabled cache adds and gates with the same inputs again.
+    #[test]
+    fn test_aiger_and_cache() {
+        let mut aig = AigerConstructor::with_and_cache(3, 0, AndCache::Bounded(2)).unwrap();
+        let x = aig.add_input("x");
+        let y = aig.add_input("y");
+        let z = aig.add_input("z");
+        let xy = aig.add_and(x, y);
+        let xz = aig.add_and(x, z);
+        assert_eq!(aig.add_and(y, x), xy);
+        // evicts x ∧ z, which was used least recently
+        let yz = aig.add_and(y, z);
+        assert_eq!(aig.add_and(x, y), xy);
+        assert_eq!(aig.add_and(y, z), yz);
+        assert_ne!(aig.add_and(x, z), xz);
+        assert_eq!(
+            aig.and_cache_stats(),
+            AndCacheStats {
+                hits: 3,
+                misses: 4,
+                evictions: 2,
+                max_size: 2,
+            }
+        );
+        assert_eq!(aig.into_aiger().num_ands(), 4);
+
+        let mut aig = AigerConstructor::with_and_cache(2, 0, AndCache::Disabled).unwrap();
+        let x = aig.add_input("x");
+        let y = aig.add_input("y");
+        assert_ne!(aig.add_and(x, y), aig.add_and(x, y));
+        assert_eq!(aig.add_and(x, Literal::TRUE), x);
+        assert_eq!(aig.and_cache_stats().max_size, 0);
+        assert_eq!(aig.into_aiger().num_ands(), 2);
+
+        let mut aig = AigerConstructor::new(2, 0).unwrap();
+        let x = aig.add_input("x");
+        let y = aig.add_input("y");
+        assert_eq!(aig.add_and(x, y), aig.add_and(y, x));
+        assert_eq!(aig.and_cache_stats().hits, 1);
+    }
+
+    /// Test that comments and the symbol table are preserved when writing, reading and
+    /// cloning an aiger circuit, and that they can be removed and renamed.
+    #[test]
+    fn test_aiger_symbols_and_comments() {
+        let mut aig = Aiger::new().unwrap();
+        aig.add_input(2, Some("x"));
+        aig.add_input(4, None);
+        aig.add_latch(6, 8, Some("l"));
+        aig.add_output(8, Some("y"));
+        aig.add_and(8, 2, 7);
+        aig.add_comment("first comment");
+        aig.add_comment("second comment");
+
+        let mut buffer = Vec::new();
+        aig.write(&mut buffer, AigerMode::Ascii).unwrap();
+        let read = Aiger::read(buffer.as_slice()).unwrap();
+        assert_eq!(read.comments(), vec!["first comment", "second comment"]);
+        assert_eq!(
+            read.symbol_names(SymbolKind::Input),
+            vec![Some("x".to_string()), None]
+        );
+        assert_eq!(
+            read.symbol_names(SymbolKind::Latch),
+            vec![Some("l".to_string())]
+        );
+        assert_eq!(
+            read.symbol_names(SymbolKind::Output),
+            vec![Some("y".to_string())]
+        );
+
+        let renamed = read
+            .renamed(|kind, i, name| match kind {
+                SymbolKind::Input => Some(format!("i{}", i)),
+                _ => name.map(str::to_uppercase),
+            })
+            .unwrap();
+        assert_eq!(
+            renamed.symbol_names(SymbolKind::Input),
+            vec![Some("i0".to_string()), Some("i1".to_string())]
+        );
+        assert_eq!(
+            renamed.symbol_names(SymbolKind::Output),
+            vec![Some("Y".to_string())]
+        );
+        assert_eq!(renamed.and_literals(), read.and_literals());
+        assert_eq!(renamed.comments(), read.comments());
+
+        let cloned = renamed.clone();
+        assert_eq!(cloned.comments(), renamed.comments());
+        assert_eq!(
+            cloned.symbol_names(SymbolKind::Input),
+            renamed.symbol_names(SymbolKind::Input)
+        );
+        assert_eq!(cloned.latch_literals(), renamed.latch_literals());
+
+        let without_comments = renamed.without_comments().unwrap();
+        assert!(without_comments.comments().is_empty());
+        assert_eq!(
+            without_comments.symbol_names(SymbolKind::Output),
+            vec![Some("Y".to_string())]
+        );
+
+        let stripped = renamed.stripped().unwrap();
+        assert!(stripped.comments().is_empty());
+        assert_eq!(stripped.symbol_names(SymbolKind::Latch), vec![None]);
+        let mut buffer = Vec::new();
+        stripped.write(&mut buffer, AigerMode::Ascii).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "aag 4 2 1 1 1\n2\n4\n6 8\n8\n8 2 7\n"
+        );
+    }
+
     /// Test reading an aiger circuit, including tests for errors.
     #[test]
     fn test_aiger_read() {
@@ -794,4 +1239,28 @@ mod tests {
         assert!(result.is_err());
         assert_ne!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
     }
+
+    /// Test that the literals of a circuit can be read after parsing it.
+    #[test]
+    fn test_aiger_literals() {
+        let aag = "aag 4 2 1 1 1\n2\n4\n6 9\n7\n8 2 4\n";
+        let aig = Aiger::read(aag.as_bytes()).unwrap();
+        assert_eq!(aig.input_literals(), vec![2, 4]);
+        assert_eq!(aig.latch_literals(), vec![(6, 9, 0)]);
+        assert_eq!(aig.output_literals(), vec![7]);
+        assert_eq!(aig.and_literals(), vec![(8, 2, 4)]);
+    }
+
+    /// Test that a latch without a name is added as a latch and not as an input.
+    #[test]
+    fn test_aiger_unnamed_latch() {
+        let mut aig = Aiger::new().unwrap();
+        aig.add_input(2, None);
+        aig.add_latch(4, 2, None);
+        aig.add_output(4, None);
+        assert_eq!(aig.num_inputs(), 1);
+        assert_eq!(aig.num_latches(), 1);
+        assert_eq!(aig.input_literals(), vec![2]);
+        assert_eq!(aig.latch_literals(), vec![(4, 2, 0)]);
+    }
 }
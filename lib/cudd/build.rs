@@ -6,7 +6,10 @@ use std::fs;
 use build_util::*;
 
 fn main() {
-    run_build_or_exit(build, "CUDD");
+    // without the native library, the BDD package implemented in Rust is used
+    if std::env::var_os("CARGO_FEATURE_NATIVE").is_some() {
+        run_build_or_exit(build, "CUDD");
+    }
 }
 
 /// Run the build for CUDD, by compiling the bundled C library.
@@ -10,6 +10,12 @@
 // include bindings generated by build.rs with bindgen
 include!(concat!(env!("OUT_DIR"), "/cudd_bindings.rs"));
 
+/// Group type for variable groups that are reordered internally, from `mtr.h`.
+pub(super) const MTR_DEFAULT: u32 = 0x00000000;
+
+/// Group type for variable groups with a fixed internal order, from `mtr.h`.
+pub(super) const MTR_FIXED: u32 = 0x00000004;
+
 /// Complements a node pointer
 pub(super) fn Cudd_Not(node: *mut DdNode) -> *mut DdNode {
     ((node as usize) ^ 1_usize) as *mut DdNode
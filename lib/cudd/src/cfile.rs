@@ -1,8 +1,15 @@
 //! Stripped down version of cfile crate, using the generated CUDD bindings.
+//!
+//! Temporary files are created with `tmpfile` on Unix. On other platforms, in particular
+//! Windows, where `tmpfile` creates the file in the root directory and commonly fails
+//! without administrator rights, a named file in the temporary directory of the user
+//! is used instead, which is removed when the stream is closed.
 
 use std::io;
 use std::mem;
 use std::os::raw::{c_int, c_long, c_void};
+#[cfg(not(unix))]
+use std::path::PathBuf;
 
 use crate::bindings::{self, size_t};
 
@@ -13,15 +20,23 @@ pub type FilePtr = *mut bindings::FILE;
 pub struct CFile {
     /// The wrapped raw pointer.
     ptr: FilePtr,
+    /// The path of a named temporary file, which is removed after closing the stream.
+    #[cfg(not(unix))]
+    path: Option<PathBuf>,
 }
 
 impl Drop for CFile {
     fn drop(&mut self) {
         unsafe { bindings::fclose(self.as_ptr()) };
+        #[cfg(not(unix))]
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
     }
 }
 
 /// Open a temporary file as a read/write stream.
+#[cfg(unix)]
 pub fn tmpfile() -> io::Result<CFile> {
     unsafe {
         let p = bindings::tmpfile();
@@ -34,6 +49,51 @@ pub fn tmpfile() -> io::Result<CFile> {
     }
 }
 
+/// Open a temporary file as a read/write stream.
+///
+/// The file is created with a unique name in the temporary directory and
+/// removed when the stream is dropped.
+#[cfg(not(unix))]
+pub fn tmpfile() -> io::Result<CFile> {
+    use std::ffi::CString;
+    use std::fs::OpenOptions;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir();
+    loop {
+        let name = format!(
+            "cudd-{}-{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = dir.join(name);
+        // reserve the name, retrying with the next name if it is already taken
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+        let path_cstring = path
+            .to_str()
+            .and_then(|s| CString::new(s).ok())
+            .ok_or_else(|| {
+                let _ = std::fs::remove_file(&path);
+                io::Error::new(io::ErrorKind::InvalidInput, "invalid temporary file path")
+            })?;
+        let p = unsafe { bindings::fopen(path_cstring.as_ptr(), b"w+b\0".as_ptr() as *const _) };
+        if p.is_null() {
+            let err = io::Error::last_os_error();
+            let _ = std::fs::remove_file(&path);
+            return Err(err);
+        }
+        let mut file = CFile::from_ptr(p);
+        file.path = Some(path);
+        return Ok(file);
+    }
+}
+
 impl io::Read for CFile {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.read_slice(buf)
@@ -85,7 +145,11 @@ impl io::Write for CFile {
 impl CFile {
     /// Creates a stream from a raw pointer.
     pub fn from_ptr(ptr: FilePtr) -> Self {
-        Self { ptr }
+        Self {
+            ptr,
+            #[cfg(not(unix))]
+            path: None,
+        }
     }
 
     /// Returns the raw pointer of the stream.
@@ -0,0 +1,1021 @@
+//! Bindings to the bundled CUDD library.
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::convert::AsRef;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::hash::Hash;
+use std::io;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::rc::Rc;
+
+use crate::bindings::*;
+use crate::cfile;
+use crate::{BddView, Cube, CubeValue, CuddError, ReorderingMethod};
+
+/// Internal wrapper for the CUDD manager. The manager
+/// should only be accessed through an [`Rc`] pointer to
+/// avoid dropping it while any BDDs created by it are still used.
+#[derive(Debug)]
+pub(crate) struct Manager {
+    /// Raw pointer to the CUDD manager.
+    manager: *mut DdManager,
+    /// The error handler to call in case of errors.
+    error_handler: fn(CuddError) -> (),
+}
+
+impl Drop for Manager {
+    fn drop(&mut self) {
+        unsafe { Cudd_Quit(self.manager) }
+    }
+}
+
+/// A manager for BDDs using the CUDD framework.
+#[derive(Debug)]
+pub struct Cudd {
+    /// Internal manager.
+    pub(crate) manager: Rc<Manager>,
+}
+
+impl Manager {
+    /// Checks the return value of a CUDD operation, and calls the error handler
+    /// if an error has occurred.
+    #[allow(non_snake_case)]
+    #[allow(non_upper_case_globals)]
+    fn check_return_value(&self, result: *const c_void) {
+        if result.is_null() {
+            let error_code = unsafe { Cudd_ReadErrorCode(self.manager) };
+            let error = match error_code {
+                Cudd_ErrorType_CUDD_MEMORY_OUT => CuddError::MemoryOut,
+                Cudd_ErrorType_CUDD_TOO_MANY_NODES => CuddError::TooManyNodes,
+                Cudd_ErrorType_CUDD_MAX_MEM_EXCEEDED => CuddError::MaxMemExceeded,
+                Cudd_ErrorType_CUDD_TERMINATION => CuddError::Termination,
+                Cudd_ErrorType_CUDD_INVALID_ARG => CuddError::InvalidArg,
+                Cudd_ErrorType_CUDD_INTERNAL_ERROR => CuddError::InternalError,
+                _ => CuddError::UnexpectedError,
+            };
+            (self.error_handler)(error);
+        }
+    }
+
+    /// Checks if a BDD is from this manager.
+    /// If this is the case, the manager pointer is returned,
+    /// and otherwise the error handler is called.
+    fn check_same_manager(&self, other: &Bdd) -> *mut DdManager {
+        if self.manager != other.cudd.manager {
+            (self.error_handler)(CuddError::DifferentManager);
+        }
+        self.manager
+    }
+}
+
+impl Cudd {
+    /// Creates a new CUDD manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_vars`: The initial number of BDD variables.
+    /// * `num_vars_z`: The initial number of ZDD variables.
+    /// * `num_slots`: Initial size of the unique tables.
+    /// * `cache_size`: Initial size of the cache.
+    /// * `max_memory`: Target maximum memory occupation.
+    /// * `error_handler`: A function which is called if an error occurs.
+    ///
+    /// If `max_memory` is 0, the function decides suitable values for
+    /// the maximum size of the cache and for the limit for fast
+    /// unique table growth based on the available memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the CUDD framework could not be instantiated.
+    pub fn new(
+        num_vars: usize,
+        num_vars_z: usize,
+        num_slots: usize,
+        cache_size: usize,
+        max_memory: usize,
+        error_handler: fn(CuddError) -> (),
+    ) -> Result<Self, CuddError> {
+        /*
+
+        */
+        let manager = unsafe {
+            Cudd_Init(
+                num_vars as c_uint,
+                num_vars_z as c_uint,
+                num_slots as c_uint,
+                cache_size as c_uint,
+                max_memory as size_t,
+            )
+        };
+        if manager.is_null() {
+            Err(CuddError::MemoryOut)
+        } else {
+            Ok(Self {
+                manager: Rc::new(Manager {
+                    manager,
+                    error_handler,
+                }),
+            })
+        }
+    }
+
+    /// The default error handler, which panics with the given error message.
+    pub fn default_handler(error: CuddError) {
+        panic!("{}", error)
+    }
+
+    /// Create a CUDD manager with default values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the CUDD framework could not be instantiated.
+    #[allow(clippy::should_implement_trait)]
+    pub fn default() -> Result<Self, CuddError> {
+        Self::with_vars(0)
+    }
+
+    /// Create a CUDD manager with the given number of initial BDD variables
+    /// and default values otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the CUDD framework could not be instantiated.
+    pub fn with_vars(num_vars: usize) -> Result<Self, CuddError> {
+        Self::new(
+            num_vars,
+            0,
+            CUDD_UNIQUE_SLOTS as usize,
+            CUDD_CACHE_SLOTS as usize,
+            0,
+            Self::default_handler,
+        )
+    }
+
+    /// Returns a new BDD variable.
+    ///
+    /// The new variable has an index equal to the largest previous index plus 1.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn bdd_new_var(&self) -> Bdd {
+        let node = unsafe { Cudd_bddNewVar(self.manager.manager) };
+        self.manager.check_return_value(node as *const c_void);
+        Bdd::new(&self.manager, node)
+    }
+
+    /// Returns the BDD variable with the given index.
+    ///
+    /// Retrieves the BDD variable if the given index if it already exists,
+    /// or creates a new BDD variable.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn bdd_var(&self, index: usize) -> Bdd {
+        let node = unsafe { Cudd_bddIthVar(self.manager.manager, index as c_int) };
+        self.manager.check_return_value(node as *const c_void);
+        Bdd::new(&self.manager, node)
+    }
+
+    /// Returns the one constant of the manager.
+    ///
+    /// The one constant is common to ADDs and BDDs.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn bdd_one(&self) -> Bdd {
+        let node = unsafe { Cudd_ReadOne(self.manager.manager) };
+        self.manager.check_return_value(node as *const c_void);
+        Bdd::new(&self.manager, node)
+    }
+
+    /// Returns the logic zero constant of the manager.
+    ///
+    /// The logic zero constant is the complement of the one
+    /// constant, and is distinct from the arithmetic zero.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn bdd_zero(&self) -> Bdd {
+        let node = unsafe { Cudd_ReadLogicZero(self.manager.manager) };
+        self.manager.check_return_value(node as *const c_void);
+        Bdd::new(&self.manager, node)
+    }
+
+    /// Returns the cube of the BDD variables with the given indices,
+    /// i.e. the conjunction of these variables.
+    ///
+    /// The cube can be used to quantify over the variables,
+    /// e.g. with [`Bdd::exist_abstract`].
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn bdd_cube(&self, vars: &[usize]) -> Bdd {
+        let mut indices: Vec<c_int> = vars.iter().map(|&var| var as c_int).collect();
+        let node = unsafe {
+            Cudd_IndicesToCube(
+                self.manager.manager,
+                indices.as_mut_ptr(),
+                indices.len() as c_int,
+            )
+        };
+        self.manager.check_return_value(node as *const c_void);
+        Bdd::new(&self.manager, node)
+    }
+
+    /// Returns the cube for the given values of BDD variables, i.e. the conjunction
+    /// of the variables with value `true` and the negated variables with value `false`.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn bdd_cube_with_values(&self, values: &[(usize, bool)]) -> Bdd {
+        let vars: Vec<_> = values.iter().map(|&(var, _)| self.bdd_var(var)).collect();
+        let mut nodes: Vec<_> = vars.iter().map(|bdd| bdd.node).collect();
+        let mut phases: Vec<c_int> = values.iter().map(|&(_, value)| value as c_int).collect();
+        let node = unsafe {
+            Cudd_bddComputeCube(
+                self.manager.manager,
+                nodes.as_mut_ptr(),
+                phases.as_mut_ptr(),
+                nodes.len() as c_int,
+            )
+        };
+        self.manager.check_return_value(node as *const c_void);
+        Bdd::new(&self.manager, node)
+    }
+
+    /// Returns a string with a Graphviz/DOT representation of the argument BDDs.
+    ///
+    /// The argument `in_names` is used for the names of the variables
+    /// and the argument `out_names` for the names of the BDDs.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn dump_dot<S: AsRef<str>>(&self, bdds: &[Bdd], in_names: &[S], out_names: &[S]) -> String {
+        let mut buffer = Vec::new();
+        self.write_dot(&mut buffer, bdds, in_names, out_names)
+            .unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    /// Writes a Graphviz/DOT representation of the argument BDDs to the given writer.
+    ///
+    /// The argument `in_names` is used for the names of the variables
+    /// and the argument `out_names` for the names of the BDDs.
+    /// The representation is streamed to the writer through a temporary file
+    /// without being held in memory as a whole.
+    ///
+    /// Calls the set error handler if an error occurs in CUDD.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs with the temporary file or the writer.
+    pub fn write_dot<W: io::Write, S: AsRef<str>>(
+        &self,
+        mut writer: W,
+        bdds: &[Bdd],
+        in_names: &[S],
+        out_names: &[S],
+    ) -> io::Result<()> {
+        use std::io::{Seek, SeekFrom, Write};
+
+        for bdd in bdds {
+            self.manager.check_same_manager(bdd);
+        }
+
+        let in_names_cstring: Vec<_> = in_names
+            .iter()
+            .map(|p| CString::new(p.as_ref()).unwrap())
+            .collect();
+        let in_names_ptr: Vec<_> = in_names_cstring
+            .iter()
+            .map(|arg| arg.as_ptr() as *mut c_char)
+            .collect();
+        let out_names_cstring: Vec<_> = out_names
+            .iter()
+            .map(|p| CString::new(p.as_ref()).unwrap())
+            .collect();
+        let out_names_ptr: Vec<_> = out_names_cstring
+            .iter()
+            .map(|arg| arg.as_ptr() as *mut c_char)
+            .collect();
+        let nodes: Vec<_> = bdds.iter().map(|b| b.node).collect();
+        let n = bdds.len();
+
+        // open a tempfile
+        let mut f = cfile::tmpfile()?;
+
+        let retval = unsafe {
+            Cudd_DumpDot(
+                self.manager.manager,
+                n as c_int,
+                nodes.as_ptr() as *mut _,
+                in_names_ptr.as_ptr() as *mut _,
+                out_names_ptr.as_ptr() as *mut _,
+                f.as_ptr(),
+            )
+        };
+        self.manager.check_return_value(retval as *const c_void);
+
+        // force to flush the stream
+        f.flush()?;
+
+        // seek to the beginning of stream and copy to writer
+        f.seek(SeekFrom::Start(0))?;
+        io::copy(&mut f, &mut writer)?;
+        Ok(())
+    }
+
+    /// Calls the given method for dynamic reordering.
+    ///
+    /// The argument `minsize`, if non-zero, can be used to give a bound below
+    /// which no reordering occurs.
+    ///
+    /// Changes the the variable order for all diagrams and clears the cache as
+    /// side effects.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn reduce_heap(&mut self, method: ReorderingMethod, minsize: usize) {
+        let result =
+            unsafe { Cudd_ReduceHeap(self.manager.manager, method.to_cudd(), minsize as c_int) };
+        self.manager.check_return_value(result as *const c_void);
+    }
+
+    /// Reorders the variables according to the given order, where `order[i]` is
+    /// the index of the variable that is moved to level `i`.
+    ///
+    /// The order must be a permutation of all variable indices of this manager.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn shuffle_heap(&mut self, order: &[usize]) {
+        let mut permutation: Vec<c_int> = order.iter().map(|&var| var as c_int).collect();
+        let result = unsafe { Cudd_ShuffleHeap(self.manager.manager, permutation.as_mut_ptr()) };
+        self.manager.check_return_value(result as *const c_void);
+    }
+
+    /// Creates a group of the `size` variables at the consecutive levels starting
+    /// at the level of the variable with index `low`, which are kept adjacent
+    /// during reordering.
+    ///
+    /// The variables within the group are reordered as well, unless `fixed` is set.
+    /// Groups may be nested, but must not partially overlap.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn make_group(&mut self, low: usize, size: usize, fixed: bool) {
+        let group_type = if fixed { MTR_FIXED } else { MTR_DEFAULT };
+        let result = unsafe {
+            Cudd_MakeTreeNode(
+                self.manager.manager,
+                low as c_uint,
+                size as c_uint,
+                group_type,
+            )
+        };
+        self.manager.check_return_value(result as *const c_void);
+    }
+
+    /// Returns the current variable order, where the element at position `i`
+    /// is the index of the variable at level `i`.
+    pub fn variable_order(&self) -> Vec<usize> {
+        let size = unsafe { Cudd_ReadSize(self.manager.manager) };
+        (0..size)
+            .map(|level| unsafe { Cudd_ReadInvPerm(self.manager.manager, level) } as usize)
+            .collect()
+    }
+
+    /// Enables automatic dynamic reordering of BDDs and ADDs.
+    ///
+    /// Parameter `method` is used to determine the method used for
+    /// reordering. If [`ReorderingMethod::Same`] is passed, the method is unchanged.
+    pub fn autodyn_enable(&mut self, method: ReorderingMethod) {
+        unsafe { Cudd_AutodynEnable(self.manager.manager, method.to_cudd()) };
+    }
+
+    /// Disables automatic dynamic reordering.
+    pub fn autodyn_disable(&mut self) {
+        unsafe { Cudd_AutodynDisable(self.manager.manager) };
+    }
+}
+
+impl ReorderingMethod {
+    /// Converts the reordering method to the CUDD enum variant.
+    const fn to_cudd(self) -> Cudd_ReorderingType {
+        match self {
+            Self::Same => Cudd_ReorderingType_CUDD_REORDER_SAME,
+            Self::None => Cudd_ReorderingType_CUDD_REORDER_NONE,
+            Self::Sift => Cudd_ReorderingType_CUDD_REORDER_SIFT,
+            Self::SiftConverge => Cudd_ReorderingType_CUDD_REORDER_SIFT_CONVERGE,
+            Self::Exact => Cudd_ReorderingType_CUDD_REORDER_EXACT,
+        }
+    }
+}
+
+/// A binary decision diagram (BDD).
+///
+/// As BDDs implement the correspond and, or and not operations,
+/// conjunction, disjunction and negation of BDDs can be performed
+/// using the operators `&`, `|` and `!`.
+/// Both the left-hand-side and right-hand-side of the operators can be references.
+/// BDDs are not copy types, but cloning a BDD is cheap as only a reference count is increased.
+///
+/// # Examples
+///
+/// The following example uses BDDs to show that the equivalence
+/// `!(x & y) == !x | !y` holds.
+///
+/// ```
+/// # use cudd::Cudd;
+/// let manager = Cudd::default().unwrap();
+/// let x = &manager.bdd_new_var();
+/// let y = &manager.bdd_new_var();
+/// let lhs = !(x & y);
+/// let rhs = !x | !y;
+/// assert_eq!(lhs, rhs);
+/// ```
+#[derive(Debug)]
+pub struct Bdd {
+    /// Pointer to the manager for this BDD.
+    pub(crate) cudd: Rc<Manager>,
+    /// Raw pointer to the BDD node.
+    node: *mut DdNode,
+}
+
+impl Drop for Bdd {
+    fn drop(&mut self) {
+        if !self.node.is_null() {
+            unsafe { Cudd_RecursiveDeref(self.cudd.manager, self.node) };
+        }
+    }
+}
+
+impl Bdd {
+    /// Returns the manager which was used to create this BDD.
+    pub fn manager(&self) -> Cudd {
+        Cudd {
+            manager: Rc::clone(&self.cudd),
+        }
+    }
+
+    /// Returns the unique node id for this BDD as an integer.
+    pub fn node_id(&self) -> usize {
+        self.node as usize
+    }
+
+    /// Compares this BDD with the given BDD by their truth tables, where valuations are
+    /// ordered lexicographically by the variable indices and false is before true.
+    ///
+    /// Unlike the node id, this order only depends on the represented functions and
+    /// not on the history or variable order of the manager.
+    ///
+    /// Calls the set error handler if an error occurs or the BDDs come from different managers.
+    pub fn canonical_cmp(&self, other: &Self) -> Ordering {
+        self.cudd.check_same_manager(other);
+        if self.node == other.node {
+            return Ordering::Equal;
+        }
+        // find the smallest valuation on which the functions differ
+        let manager = self.manager();
+        let mut difference = (self & !other) | (other & !self);
+        let size = unsafe { Cudd_ReadSize(self.cudd.manager) } as usize;
+        for var in 0..size {
+            let unset = &difference & !manager.bdd_var(var);
+            if unset.is_zero() {
+                difference &= manager.bdd_var(var);
+            } else {
+                difference = unset;
+            }
+        }
+        if (self & &difference).is_zero() {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }
+
+    /// Creates a new wrapped BDD for the raw pointer node.
+    ///
+    /// Increments the reference count for the node by one.
+    fn new(cudd: &Rc<Manager>, node: *mut DdNode) -> Self {
+        if !node.is_null() {
+            unsafe { Cudd_Ref(node) };
+        }
+        Self {
+            cudd: Rc::clone(cudd),
+            node,
+        }
+    }
+
+    /// Returns the regular version of this BDD.
+    pub fn regular(&self) -> Self {
+        Self::new(&self.cudd, Cudd_Regular(self.node))
+    }
+
+    /// Returns whether this BDD is a constant, i.e. zero or one.
+    pub fn is_constant(&self) -> bool {
+        unsafe { Cudd_IsConstant(self.node) != 0 }
+    }
+
+    /// Returns whether this BDD is complemented.
+    pub fn is_complement(&self) -> bool {
+        Cudd_IsComplement(self.node)
+    }
+
+    /// Returns whether this BDD is constant zero.
+    pub fn is_zero(&self) -> bool {
+        self.is_constant() && self.is_complement()
+    }
+
+    /// Returns whether this BDD is constant one.
+    pub fn is_one(&self) -> bool {
+        self.is_constant() && !self.is_complement()
+    }
+
+    /// Performs an if-then-else operation with this BDD and the given operands,
+    /// and returns the resulting BDD.
+    ///
+    /// Calls the set error handler if an error occurs or the BDDs come from different managers.
+    pub fn ite(&self, g: &Self, h: &Self) -> Self {
+        let mgr = self.cudd.check_same_manager(g);
+        self.cudd.check_same_manager(h);
+        let node = unsafe { Cudd_bddIte(mgr, self.node, g.node, h.node) };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&self.cudd, node)
+    }
+
+    /// Performs an if-then-else operation with this BDD and the given operands,
+    /// and assigns the result to itself.
+    ///
+    /// Calls the set error handler if an error occurs or the BDDs come from different managers.
+    pub fn ite_assign(&mut self, g: &Self, h: &Self) {
+        let mgr = self.cudd.check_same_manager(g);
+        self.cudd.check_same_manager(h);
+        let node = unsafe { Cudd_bddIte(mgr, self.node, g.node, h.node) };
+        self.cudd.check_return_value(node as *const c_void);
+        unsafe { Cudd_Ref(node) };
+        unsafe { Cudd_RecursiveDeref(mgr, self.node) };
+        self.node = node;
+    }
+
+    /// Simplifies this BDD with respect to the given care set, and returns the resulting BDD.
+    ///
+    /// The result agrees with this BDD on all valuations in the care set. It is
+    /// never larger than this BDD, but not guaranteed to be minimal.
+    ///
+    /// Calls the set error handler if an error occurs or the BDDs come from different managers.
+    pub fn restrict(&self, care: &Self) -> Self {
+        let mgr = self.cudd.check_same_manager(care);
+        let node = unsafe { Cudd_bddRestrict(mgr, self.node, care.node) };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&self.cudd, node)
+    }
+
+    /// Existentially quantifies the variables in the given cube from this BDD,
+    /// and returns the resulting BDD.
+    ///
+    /// The cube must be a conjunction of positive variables, e.g.
+    /// obtained from [`Cudd::bdd_cube`].
+    ///
+    /// Calls the set error handler if an error occurs, the cube is not a positive cube,
+    /// or the BDDs come from different managers.
+    pub fn exist_abstract(&self, cube: &Self) -> Self {
+        let mgr = self.cudd.check_same_manager(cube);
+        let node = unsafe { Cudd_bddExistAbstract(mgr, self.node, cube.node) };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&self.cudd, node)
+    }
+
+    /// Universally quantifies the variables in the given cube from this BDD,
+    /// and returns the resulting BDD.
+    ///
+    /// The cube must be a conjunction of positive variables, e.g.
+    /// obtained from [`Cudd::bdd_cube`].
+    ///
+    /// Calls the set error handler if an error occurs, the cube is not a positive cube,
+    /// or the BDDs come from different managers.
+    pub fn forall_abstract(&self, cube: &Self) -> Self {
+        let mgr = self.cudd.check_same_manager(cube);
+        let node = unsafe { Cudd_bddUnivAbstract(mgr, self.node, cube.node) };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&self.cudd, node)
+    }
+
+    /// Computes the conjunction of this BDD with the given BDD and existentially
+    /// quantifies the variables in the given cube from the result.
+    ///
+    /// This is equivalent to `(self & other).exist_abstract(cube)`, but does not
+    /// construct the conjunction as an intermediate result.
+    ///
+    /// Calls the set error handler if an error occurs, the cube is not a positive cube,
+    /// or the BDDs come from different managers.
+    pub fn and_abstract(&self, other: &Self, cube: &Self) -> Self {
+        let mgr = self.cudd.check_same_manager(other);
+        self.cudd.check_same_manager(cube);
+        let node = unsafe { Cudd_bddAndAbstract(mgr, self.node, other.node, cube.node) };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&self.cudd, node)
+    }
+
+    /// Substitutes the given BDD for the variable with the given index in this BDD,
+    /// and returns the resulting BDD.
+    ///
+    /// Calls the set error handler if an error occurs or the BDDs come from different managers.
+    pub fn compose(&self, g: &Self, var: usize) -> Self {
+        let mgr = self.cudd.check_same_manager(g);
+        let node = unsafe { Cudd_bddCompose(mgr, self.node, g.node, var as c_int) };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&self.cudd, node)
+    }
+
+    /// Simultaneously substitutes the BDD at index `i` of the given vector for
+    /// the variable with index `i` in this BDD, and returns the resulting BDD.
+    ///
+    /// Variables with an index not covered by the vector are left unchanged.
+    ///
+    /// Calls the set error handler if an error occurs or the BDDs come from different managers.
+    pub fn vector_compose(&self, vector: &[Self]) -> Self {
+        let mgr = self.cudd.manager;
+        let size = unsafe { Cudd_ReadSize(mgr) } as usize;
+        let manager = self.manager();
+        let mut bdds: Vec<_> = vector.iter().take(size).cloned().collect();
+        bdds.extend((bdds.len()..size).map(|var| manager.bdd_var(var)));
+        for bdd in &bdds {
+            self.cudd.check_same_manager(bdd);
+        }
+        let mut nodes: Vec<_> = bdds.iter().map(|bdd| bdd.node).collect();
+        let node = unsafe { Cudd_bddVectorCompose(mgr, self.node, nodes.as_mut_ptr()) };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&self.cudd, node)
+    }
+
+    /// Renames the variables in this BDD according to the given permutation,
+    /// where the variable with index `i` is replaced by the variable with
+    /// index `permutation[i]`, and returns the resulting BDD.
+    ///
+    /// Variables with an index not covered by the permutation are left unchanged.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn permute(&self, permutation: &[usize]) -> Self {
+        let mgr = self.cudd.manager;
+        let size = unsafe { Cudd_ReadSize(mgr) } as usize;
+        let mut permut: Vec<c_int> = permutation.iter().map(|&var| var as c_int).collect();
+        permut.extend((permut.len()..size).map(|var| var as c_int));
+        let node = unsafe { Cudd_bddPermute(mgr, self.node, permut.as_mut_ptr()) };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&self.cudd, node)
+    }
+
+    /// Swaps the variables with indices in `x` with the variables with the
+    /// indices at the same position in `y` in this BDD, and returns the resulting BDD.
+    ///
+    /// Calls the set error handler if an error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` and `y` have different lengths.
+    pub fn swap_variables(&self, x: &[usize], y: &[usize]) -> Self {
+        assert_eq!(x.len(), y.len(), "variables to swap have different lengths");
+        let manager = self.manager();
+        let x_vars: Vec<_> = x.iter().map(|&var| manager.bdd_var(var)).collect();
+        let y_vars: Vec<_> = y.iter().map(|&var| manager.bdd_var(var)).collect();
+        let mut x_nodes: Vec<_> = x_vars.iter().map(|bdd| bdd.node).collect();
+        let mut y_nodes: Vec<_> = y_vars.iter().map(|bdd| bdd.node).collect();
+        let node = unsafe {
+            Cudd_bddSwapVariables(
+                self.cudd.manager,
+                self.node,
+                x_nodes.as_mut_ptr(),
+                y_nodes.as_mut_ptr(),
+                x.len() as c_int,
+            )
+        };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&self.cudd, node)
+    }
+
+    /// Returns a factored form representation of this BDD with the given names.
+    ///
+    /// The factored form uses `&` for conjunction, `|` for disjunction
+    /// and `!` for negation.  Caution must be exercised because the factored
+    /// form may be exponentially larger than this BDD.
+    pub fn factored_form_string<S: AsRef<str>>(&self, names: &[S]) -> String {
+        let p_cstring: Vec<_> = names
+            .iter()
+            .map(|p| CString::new(p.as_ref()).unwrap())
+            .collect();
+
+        let p_ptr: Vec<_> = p_cstring
+            .iter() // do NOT into_iter()
+            .map(|arg| arg.as_ptr() as *mut c_char)
+            .collect();
+
+        let ff_cstring = unsafe {
+            Cudd_FactoredFormString(
+                self.cudd.manager,
+                self.node,
+                p_ptr.as_ptr() as *const *const _,
+            )
+        };
+        let string = unsafe { CStr::from_ptr(ff_cstring).to_str().unwrap().to_string() };
+        unsafe { Cudd_Free(ff_cstring as *mut c_void) };
+
+        string
+    }
+
+    /// Returns an iterator that iterates over the paths of this BDD
+    /// and returns the cube for each path.
+    #[must_use]
+    pub fn cube_iter(&self, num_vars: usize) -> CubeIter<'_> {
+        CubeIter::new(self, num_vars)
+    }
+
+    /// Returns an iterator that iterates over the paths of this BDD
+    /// and returns the BDD for each path.
+    #[must_use]
+    pub fn bdd_cube_iter(&self, num_vars: usize) -> BddCubeIter<'_> {
+        BddCubeIter::new(self, num_vars)
+    }
+
+    /// Converts this BDD to another manager and returns the copy in the destination manager.
+    ///
+    /// The order of the variables in the two managers my be different.
+    ///
+    /// Calls the set error handler if an error occurs.
+    pub fn transfer(&self, destination: &Cudd) -> Self {
+        let node =
+            unsafe { Cudd_bddTransfer(self.cudd.manager, destination.manager.manager, self.node) };
+        self.cudd.check_return_value(node as *const c_void);
+        Self::new(&destination.manager, node)
+    }
+
+    /// Returns a view into the node for this BDD.
+    #[must_use]
+    pub fn view(&self) -> BddView {
+        if self.is_constant() {
+            BddView::Constant
+        } else {
+            let var = unsafe { Cudd_NodeReadIndex(self.node) } as usize;
+            let bdd_then = Self::new(&self.cudd, unsafe { Cudd_T(self.node) });
+            let bdd_else = Self::new(&self.cudd, unsafe { Cudd_E(self.node) });
+            BddView::InnerNode {
+                var,
+                bdd_then,
+                bdd_else,
+            }
+        }
+    }
+}
+
+impl CubeValue {
+    /// Creates a cube value from a CUDD cube value.
+    fn from_cudd(val: c_int) -> Self {
+        match val {
+            0 => Self::Unset,
+            1 => Self::Set,
+            2 => Self::Unspecified,
+            _ => panic!("invalid cube value"),
+        }
+    }
+
+    /// Transform this cube value into a CUDD cube value.
+    const fn to_cudd(self) -> c_int {
+        match self {
+            Self::Unset => 0,
+            Self::Set => 1,
+            Self::Unspecified => 2,
+        }
+    }
+}
+
+impl Cube {
+    /// Create a new cube from the raw cube pointer with the given number of variables.
+    ///
+    /// The pointer must point to a valid memory region of the given size with valid cube values.
+    fn make(cube_ptr: *mut c_int, num_vars: usize) -> Self {
+        let slice = unsafe { std::slice::from_raw_parts(cube_ptr, num_vars) };
+        let cube = Self {
+            cube: slice.iter().cloned().map(CubeValue::from_cudd).collect(),
+        };
+        cube
+    }
+
+    /// Returns a rperesentation of this cube as a vector of CUDD cube values.
+    fn to_array(&self) -> Vec<c_int> {
+        self.cube.iter().map(|v| v.to_cudd()).collect()
+    }
+}
+
+/// An iterator over cubes corresponding to the paths in a source BDD.
+pub struct CubeIter<'a> {
+    /// The source BDD.
+    bdd: &'a Bdd,
+    /// The number of variables along each path.
+    num_vars: usize,
+    /// Raw pointer to the CUDD generator the next cube.
+    gen: *mut DdGen,
+    /// The next cube to return, or `None` if all cubes have been returned.
+    next_cube: Option<Cube>,
+}
+
+impl<'a> CubeIter<'a> {
+    /// Initialize the iterator with the first cube.
+    fn init(&mut self) {
+        let mut cube_ptr = std::ptr::null_mut();
+        // we don't need the value, but have to provide a memory location where it is stored
+        let mut value = std::mem::MaybeUninit::<CUDD_VALUE_TYPE>::uninit();
+        self.gen = unsafe {
+            Cudd_FirstCube(
+                self.bdd.cudd.manager,
+                self.bdd.node,
+                &mut cube_ptr,
+                value.as_mut_ptr(),
+            )
+        };
+        self.next_cube = Some(Cube::make(cube_ptr, self.num_vars));
+    }
+
+    /// Creates a new iterator with the given source BDD and number of variables.
+    fn new(bdd: &'a Bdd, num_vars: usize) -> Self {
+        let mut iter = Self {
+            bdd,
+            num_vars,
+            gen: std::ptr::null_mut(),
+            next_cube: None,
+        };
+        iter.init();
+        iter
+    }
+}
+
+impl<'a> Drop for CubeIter<'a> {
+    fn drop(&mut self) {
+        unsafe { Cudd_GenFree(self.gen) };
+    }
+}
+
+impl<'a> Iterator for CubeIter<'a> {
+    type Item = Cube;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_cube.take() {
+            None => None,
+            Some(cube) => {
+                let mut cube_ptr = std::ptr::null_mut();
+                let mut value = std::mem::MaybeUninit::<CUDD_VALUE_TYPE>::uninit();
+                unsafe { Cudd_NextCube(self.gen, &mut cube_ptr, value.as_mut_ptr()) };
+                if unsafe { Cudd_IsGenEmpty(self.gen) } == 1 {
+                    self.next_cube = None;
+                } else {
+                    self.next_cube = Some(Cube::make(cube_ptr, self.num_vars));
+                }
+                Some(cube)
+            }
+        }
+    }
+}
+
+/// An iterator over BDDs corresponding to the paths in a source BDD.
+pub struct BddCubeIter<'a> {
+    /// The cube iterator that is used to iterate over paths.
+    cube_iter: CubeIter<'a>,
+}
+
+impl<'a> BddCubeIter<'a> {
+    /// Creates a new iterator with the given source BDD and number of variables.
+    fn new(bdd: &'a Bdd, num_vars: usize) -> Self {
+        BddCubeIter {
+            cube_iter: CubeIter::new(bdd, num_vars),
+        }
+    }
+}
+
+impl<'a> Iterator for BddCubeIter<'a> {
+    type Item = Bdd;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cube_iter.next().map(|cube| {
+            let bdd = self.cube_iter.bdd;
+            let mgr = bdd.cudd.manager;
+            let array = cube.to_array();
+            let node = unsafe { Cudd_CubeArrayToBdd(mgr, array.as_ptr() as *mut _) };
+            bdd.cudd.check_return_value(node as *const c_void);
+            Bdd::new(&bdd.cudd, node)
+        })
+    }
+}
+
+impl fmt::Display for Bdd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ff_cstring =
+            unsafe { Cudd_FactoredFormString(self.cudd.manager, self.node, std::ptr::null()) };
+        let string = unsafe { CStr::from_ptr(ff_cstring).to_str().unwrap().to_string() };
+        unsafe { Cudd_Free(ff_cstring as *mut c_void) };
+        write!(f, "{}", string)
+    }
+}
+
+impl Hash for Bdd {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.node.hash(state);
+    }
+}
+
+impl PartialEq for Bdd {
+    fn eq(&self, other: &Self) -> bool {
+        self.cudd.check_same_manager(other);
+        self.node == other.node
+    }
+}
+impl Eq for Bdd {}
+
+impl PartialOrd for Bdd {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let mgr = self.cudd.check_same_manager(other);
+        if self.node == other.node {
+            Some(Ordering::Equal)
+        } else if unsafe { Cudd_bddLeq(mgr, self.node, other.node) } == 1 {
+            Some(Ordering::Less)
+        } else if unsafe { Cudd_bddLeq(mgr, other.node, self.node) } == 1 {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
+impl Clone for Bdd {
+    fn clone(&self) -> Self {
+        Self::new(&self.cudd, self.node)
+    }
+}
+
+macro_rules! not_impl {
+    ($t:ty) => {
+        impl std::ops::Not for $t {
+            type Output = Bdd;
+
+            fn not(self) -> Self::Output {
+                let node = Cudd_Not(self.node);
+                Bdd::new(&self.cudd, node)
+            }
+        }
+    };
+}
+
+not_impl!(Bdd);
+not_impl!(&Bdd);
+
+macro_rules! and_impl {
+    ($t:ty) => {
+        impl<R: Borrow<Bdd>> std::ops::BitAnd<R> for $t {
+            type Output = Bdd;
+
+            fn bitand(self, rhs: R) -> Self::Output {
+                let rhs = rhs.borrow();
+                let mgr = self.cudd.check_same_manager(rhs);
+                let node = unsafe { Cudd_bddAnd(mgr, self.node, rhs.node) };
+                self.cudd.check_return_value(node as *const c_void);
+                Bdd::new(&self.cudd, node)
+            }
+        }
+    };
+}
+
+and_impl!(Bdd);
+and_impl!(&Bdd);
+
+impl<R: Borrow<Bdd>> std::ops::BitAndAssign<R> for Bdd {
+    fn bitand_assign(&mut self, rhs: R) {
+        let rhs = rhs.borrow();
+        let mgr = self.cudd.check_same_manager(rhs);
+        let node = unsafe { Cudd_bddAnd(mgr, self.node, rhs.node) };
+        self.cudd.check_return_value(node as *const c_void);
+        unsafe { Cudd_Ref(node) };
+        unsafe { Cudd_RecursiveDeref(mgr, self.node) };
+        self.node = node;
+    }
+}
+
+macro_rules! or_impl {
+    ($t:ty) => {
+        impl<R: Borrow<Bdd>> std::ops::BitOr<R> for $t {
+            type Output = Bdd;
+
+            fn bitor(self, rhs: R) -> Self::Output {
+                let rhs = rhs.borrow();
+                let mgr = self.cudd.check_same_manager(rhs);
+                let node = unsafe { Cudd_bddOr(mgr, self.node, rhs.node) };
+                self.cudd.check_return_value(node as *const c_void);
+                Bdd::new(&self.cudd, node)
+            }
+        }
+    };
+}
+
+or_impl!(Bdd);
+or_impl!(&Bdd);
+
+impl<R: Borrow<Bdd>> std::ops::BitOrAssign<R> for Bdd {
+    fn bitor_assign(&mut self, rhs: R) {
+        let rhs = rhs.borrow();
+        let mgr = self.cudd.check_same_manager(rhs);
+        let node = unsafe { Cudd_bddOr(mgr, self.node, rhs.node) };
+        self.cudd.check_return_value(node as *const c_void);
+        unsafe { Cudd_Ref(node) };
+        unsafe { Cudd_RecursiveDeref(mgr, self.node) };
+        self.node = node;
+    }
+}
@@ -0,0 +1,1503 @@
+//! A simple package for BDDs implemented in Rust, which is used without the CUDD library.
+//!
+//! Like CUDD, the package stores nodes with complement edges in a unique table, so that
+//! equal functions are represented by equal edges. Nodes without references are reclaimed
+//! by a garbage collection between operations. Variables are never reordered dynamically,
+//! but the order can be changed explicitly with [`Cudd::shuffle_heap`].
+
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::convert::AsRef;
+use std::fmt;
+use std::hash::Hash;
+use std::io;
+use std::rc::Rc;
+
+use crate::{BddView, Cube, CubeValue, CuddError, ReorderingMethod};
+
+/// An edge to a node, given by the index of the node shifted by one bit,
+/// where the lowest bit marks a complemented edge.
+type Edge = u32;
+
+/// The edge to the constant one node, which has index 0.
+const ONE: Edge = 0;
+/// The edge to constant zero, i.e. the complemented edge to the constant one node.
+const ZERO: Edge = 1;
+
+/// The variable of the constant node.
+const CONSTANT: u32 = u32::MAX;
+/// The variable of nodes that have been reclaimed and can be reused.
+const FREE: u32 = u32::MAX - 1;
+
+/// The minimum number of live nodes before a garbage collection is triggered.
+const MIN_GC_LIMIT: usize = 1 << 16;
+
+/// Returns the index of the node the edge points to.
+const fn node_index(edge: Edge) -> usize {
+    (edge >> 1) as usize
+}
+
+/// Returns whether the edge is complemented.
+const fn is_complement(edge: Edge) -> bool {
+    edge & 1 == 1
+}
+
+/// Returns the regular version of the edge.
+const fn regular(edge: Edge) -> Edge {
+    edge & !1
+}
+
+/// Returns the complemented version of the edge.
+const fn complement(edge: Edge) -> Edge {
+    edge ^ 1
+}
+
+/// A node of a BDD, which is also the key of the node in the unique table.
+///
+/// The then edge of a node is never complemented.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Node {
+    /// The index of the variable of the node.
+    var: u32,
+    /// The edge to the then child.
+    then_edge: Edge,
+    /// The edge to the else child.
+    else_edge: Edge,
+}
+
+/// An operation with results stored in the computed table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum Operation {
+    /// If-then-else operation.
+    Ite,
+    /// Existential quantification.
+    Exist,
+    /// Simplification with respect to a care set.
+    Restrict,
+}
+
+/// The nodes and tables of a manager.
+struct State {
+    /// The nodes, where the node at index 0 is the constant one node.
+    nodes: Vec<Node>,
+    /// The number of references to each node from BDDs.
+    references: Vec<usize>,
+    /// The unique table, mapping nodes to their index.
+    unique: HashMap<Node, u32>,
+    /// The indices of reclaimed nodes.
+    free: Vec<u32>,
+    /// The level of each variable.
+    perm: Vec<usize>,
+    /// The variable at each level.
+    invperm: Vec<usize>,
+    /// The computed table for the results of operations.
+    computed: HashMap<(Operation, Edge, Edge, Edge), Edge>,
+    /// The number of live nodes at which the next garbage collection is triggered.
+    gc_limit: usize,
+}
+
+impl State {
+    /// Creates a new state with the given number of variables.
+    fn new(num_vars: usize) -> Self {
+        let constant = Node {
+            var: CONSTANT,
+            then_edge: ONE,
+            else_edge: ONE,
+        };
+        let mut state = Self {
+            nodes: vec![constant],
+            references: vec![0],
+            unique: HashMap::new(),
+            free: Vec::new(),
+            perm: Vec::new(),
+            invperm: Vec::new(),
+            computed: HashMap::new(),
+            gc_limit: MIN_GC_LIMIT,
+        };
+        if num_vars > 0 {
+            state.var(num_vars - 1);
+        }
+        state
+    }
+
+    /// Returns the number of variables.
+    fn size(&self) -> usize {
+        self.perm.len()
+    }
+
+    /// Returns the number of nodes that have not been reclaimed.
+    fn live_nodes(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    /// Returns whether the edge points to the constant node.
+    fn is_constant(&self, edge: Edge) -> bool {
+        node_index(edge) == 0
+    }
+
+    /// Returns whether the edge is a regular edge to the node of a variable.
+    fn is_var(&self, edge: Edge) -> bool {
+        let node = self.nodes[node_index(edge)];
+        !is_complement(edge)
+            && node.var != CONSTANT
+            && node.then_edge == ONE
+            && node.else_edge == ZERO
+    }
+
+    /// Returns the level of the node the edge points to,
+    /// where the constant node is below all variables.
+    fn level(&self, edge: Edge) -> usize {
+        match self.nodes[node_index(edge)].var {
+            CONSTANT => usize::MAX,
+            var => self.perm[var as usize],
+        }
+    }
+
+    /// Returns the positive and negative cofactor of the edge with respect
+    /// to the variable at the given level, which must not be below the edge.
+    fn cofactors(&self, edge: Edge, level: usize) -> (Edge, Edge) {
+        if self.level(edge) == level {
+            let node = self.nodes[node_index(edge)];
+            let mask = edge & 1;
+            (node.then_edge ^ mask, node.else_edge ^ mask)
+        } else {
+            (edge, edge)
+        }
+    }
+
+    /// Returns the edge for the variable with the given index,
+    /// creating new variables at the bottom of the order if needed.
+    fn var(&mut self, index: usize) -> Edge {
+        while self.size() <= index {
+            let level = self.size();
+            self.perm.push(level);
+            self.invperm.push(level);
+        }
+        self.make(index as u32, ONE, ZERO)
+    }
+
+    /// Returns the edge for the node with the given variable and children,
+    /// looking up the node in the unique table or creating a new node.
+    fn make(&mut self, var: u32, then_edge: Edge, else_edge: Edge) -> Edge {
+        if then_edge == else_edge {
+            return then_edge;
+        }
+        if is_complement(then_edge) {
+            return complement(self.make(var, complement(then_edge), complement(else_edge)));
+        }
+        let key = Node {
+            var,
+            then_edge,
+            else_edge,
+        };
+        if let Some(&index) = self.unique.get(&key) {
+            return index << 1;
+        }
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index as usize] = key;
+                index
+            }
+            None => {
+                self.nodes.push(key);
+                self.references.push(0);
+                (self.nodes.len() - 1) as u32
+            }
+        };
+        self.unique.insert(key, index);
+        index << 1
+    }
+
+    /// Reclaims all nodes that are not reachable from any BDD and clears the computed table.
+    fn collect_garbage(&mut self) {
+        let mut marked = vec![false; self.nodes.len()];
+        let mut stack: Vec<_> = (0..self.nodes.len())
+            .filter(|&index| self.references[index] > 0)
+            .collect();
+        stack.push(0);
+        while let Some(index) = stack.pop() {
+            if !marked[index] {
+                marked[index] = true;
+                let node = self.nodes[index];
+                if node.var != CONSTANT {
+                    stack.push(node_index(node.then_edge));
+                    stack.push(node_index(node.else_edge));
+                }
+            }
+        }
+        for (index, &marked) in marked.iter().enumerate() {
+            if !marked && self.nodes[index].var != FREE {
+                self.unique.remove(&self.nodes[index]);
+                self.nodes[index].var = FREE;
+                self.free.push(index as u32);
+            }
+        }
+        self.computed.clear();
+        self.gc_limit = MIN_GC_LIMIT.max(2 * self.live_nodes());
+    }
+
+    /// Reclaims unreachable nodes if the number of live nodes exceeds the limit.
+    ///
+    /// This may only be called before an operation, when all used nodes are referenced by BDDs.
+    fn collect_garbage_if_needed(&mut self) {
+        if self.live_nodes() >= self.gc_limit {
+            self.collect_garbage();
+        }
+    }
+
+    /// Returns the number of nodes of the BDD with the given edge.
+    fn dag_size(&self, edge: Edge) -> usize {
+        let mut visited = HashSet::new();
+        let mut stack = vec![node_index(edge)];
+        while let Some(index) = stack.pop() {
+            if visited.insert(index) && index != 0 {
+                let node = self.nodes[index];
+                stack.push(node_index(node.then_edge));
+                stack.push(node_index(node.else_edge));
+            }
+        }
+        visited.len()
+    }
+
+    /// Returns whether the edge is a conjunction of positive variables.
+    fn is_positive_cube(&self, mut cube: Edge) -> bool {
+        while cube != ONE {
+            let node = self.nodes[node_index(cube)];
+            if is_complement(cube) || node.else_edge != ZERO {
+                return false;
+            }
+            cube = node.then_edge;
+        }
+        true
+    }
+
+    /// Computes the if-then-else of the given edges.
+    fn ite(&mut self, f: Edge, g: Edge, h: Edge) -> Edge {
+        let (mut f, mut g, mut h) = (f, g, h);
+        // terminal cases
+        if f == ONE {
+            return g;
+        }
+        if f == ZERO {
+            return h;
+        }
+        if g == f {
+            g = ONE;
+        } else if g == complement(f) {
+            g = ZERO;
+        }
+        if h == f {
+            h = ZERO;
+        } else if h == complement(f) {
+            h = ONE;
+        }
+        if g == h {
+            return g;
+        }
+        if g == ONE && h == ZERO {
+            return f;
+        }
+        if g == ZERO && h == ONE {
+            return complement(f);
+        }
+        // normalize such that f and g are regular
+        if is_complement(f) {
+            f = complement(f);
+            std::mem::swap(&mut g, &mut h);
+        }
+        let mask = g & 1;
+        g ^= mask;
+        h ^= mask;
+
+        let key = (Operation::Ite, f, g, h);
+        if let Some(&result) = self.computed.get(&key) {
+            return result ^ mask;
+        }
+        let level = self.level(f).min(self.level(g)).min(self.level(h));
+        let (f1, f0) = self.cofactors(f, level);
+        let (g1, g0) = self.cofactors(g, level);
+        let (h1, h0) = self.cofactors(h, level);
+        let then_edge = self.ite(f1, g1, h1);
+        let else_edge = self.ite(f0, g0, h0);
+        let result = self.make(self.invperm[level] as u32, then_edge, else_edge);
+        self.computed.insert(key, result);
+        result ^ mask
+    }
+
+    /// Computes the conjunction of the given edges.
+    fn and(&mut self, f: Edge, g: Edge) -> Edge {
+        self.ite(f, g, ZERO)
+    }
+
+    /// Computes the disjunction of the given edges.
+    fn or(&mut self, f: Edge, g: Edge) -> Edge {
+        self.ite(f, ONE, g)
+    }
+
+    /// Existentially quantifies the variables in the positive cube from the given edge.
+    fn exist(&mut self, f: Edge, cube: Edge) -> Edge {
+        if cube == ONE || self.is_constant(f) {
+            return f;
+        }
+        let level = self.level(f);
+        let mut cube = cube;
+        while self.level(cube) < level {
+            cube = self.nodes[node_index(cube)].then_edge;
+        }
+        if cube == ONE {
+            return f;
+        }
+
+        let key = (Operation::Exist, f, cube, ONE);
+        if let Some(&result) = self.computed.get(&key) {
+            return result;
+        }
+        let (f1, f0) = self.cofactors(f, level);
+        let result = if self.level(cube) == level {
+            let rest = self.nodes[node_index(cube)].then_edge;
+            let then_edge = self.exist(f1, rest);
+            if then_edge == ONE {
+                ONE
+            } else {
+                let else_edge = self.exist(f0, rest);
+                self.or(then_edge, else_edge)
+            }
+        } else {
+            let then_edge = self.exist(f1, cube);
+            let else_edge = self.exist(f0, cube);
+            self.make(self.invperm[level] as u32, then_edge, else_edge)
+        };
+        self.computed.insert(key, result);
+        result
+    }
+
+    /// Simplifies the given edge with respect to the care set.
+    fn restrict(&mut self, f: Edge, care: Edge) -> Edge {
+        if care == ONE || care == ZERO || self.is_constant(f) {
+            return f;
+        }
+        if f == care {
+            return ONE;
+        }
+        if f == complement(care) {
+            return ZERO;
+        }
+
+        let key = (Operation::Restrict, f, care, ONE);
+        if let Some(&result) = self.computed.get(&key) {
+            return result;
+        }
+        let level_f = self.level(f);
+        let level_care = self.level(care);
+        let result = if level_care < level_f {
+            // the variable does not occur in f, so it is removed from the care set
+            let (care1, care0) = self.cofactors(care, level_care);
+            let care = self.or(care1, care0);
+            self.restrict(f, care)
+        } else {
+            let (f1, f0) = self.cofactors(f, level_f);
+            let (care1, care0) = self.cofactors(care, level_f);
+            if care1 == ZERO {
+                self.restrict(f0, care0)
+            } else if care0 == ZERO {
+                self.restrict(f1, care1)
+            } else {
+                let then_edge = self.restrict(f1, care1);
+                let else_edge = self.restrict(f0, care0);
+                self.make(self.invperm[level_f] as u32, then_edge, else_edge)
+            }
+        };
+        self.computed.insert(key, result);
+        result
+    }
+
+    /// Substitutes the edge at index `i` of the vector for the variable with
+    /// index `i` in the given edge, where `memo` stores the results for regular edges.
+    fn vector_compose(&mut self, f: Edge, vector: &[Edge], memo: &mut HashMap<Edge, Edge>) -> Edge {
+        if self.is_constant(f) {
+            return f;
+        }
+        let mask = f & 1;
+        let f = regular(f);
+        if let Some(&result) = memo.get(&f) {
+            return result ^ mask;
+        }
+        let node = self.nodes[node_index(f)];
+        let then_edge = self.vector_compose(node.then_edge, vector, memo);
+        let else_edge = self.vector_compose(node.else_edge, vector, memo);
+        let result = self.ite(vector[node.var as usize], then_edge, else_edge);
+        memo.insert(f, result);
+        result ^ mask
+    }
+
+    /// Swaps the variables at the given level and the level below in place,
+    /// such that all edges still represent the same functions.
+    fn swap_levels(&mut self, level: usize) {
+        let x = self.invperm[level] as u32;
+        let y = self.invperm[level + 1] as u32;
+        // nodes of x depending on y become nodes of y, all other nodes are unchanged
+        let dependent: Vec<_> = (0..self.nodes.len())
+            .filter(|&index| {
+                let node = self.nodes[index];
+                node.var == x
+                    && (self.nodes[node_index(node.then_edge)].var == y
+                        || self.nodes[node_index(node.else_edge)].var == y)
+            })
+            .collect();
+        for &index in &dependent {
+            self.unique.remove(&self.nodes[index]);
+        }
+        self.perm[x as usize] = level + 1;
+        self.perm[y as usize] = level;
+        self.invperm.swap(level, level + 1);
+        for index in dependent {
+            let node = self.nodes[index];
+            let (f11, f10) = self.cofactors(node.then_edge, level);
+            let (f01, f00) = self.cofactors(node.else_edge, level);
+            let then_edge = self.make(x, f11, f01);
+            let else_edge = self.make(x, f10, f00);
+            let node = Node {
+                var: y,
+                then_edge,
+                else_edge,
+            };
+            self.nodes[index] = node;
+            self.unique.insert(node, index as u32);
+        }
+    }
+
+    /// Appends the factored form of the given regular, non-constant edge to the string,
+    /// using the given function for the names of the variables.
+    fn factored_form(&self, out: &mut String, f: Edge, name: &dyn Fn(usize) -> String) {
+        let node = self.nodes[node_index(f)];
+        let var = node.var as usize;
+        let then_edge = node.then_edge;
+        let else_edge = node.else_edge;
+        if else_edge != ONE {
+            out.push_str(&name(var));
+        }
+        if then_edge != ONE {
+            if else_edge != ONE {
+                out.push_str(" & ");
+            }
+            self.factored_form_child(out, then_edge, name);
+        }
+        if else_edge == ZERO {
+            return;
+        }
+        out.push_str(" | ");
+        if then_edge != ONE {
+            out.push('!');
+            out.push_str(&name(var));
+        }
+        if regular(else_edge) != ONE {
+            if then_edge != ONE {
+                out.push_str(" & ");
+            }
+            if is_complement(else_edge) {
+                out.push('!');
+            }
+            self.factored_form_child(out, regular(else_edge), name);
+        }
+    }
+
+    /// Appends the factored form of the given regular child to the string,
+    /// in parentheses unless the child is a variable.
+    fn factored_form_child(&self, out: &mut String, f: Edge, name: &dyn Fn(usize) -> String) {
+        if self.is_var(f) {
+            self.factored_form(out, f, name);
+        } else {
+            out.push('(');
+            self.factored_form(out, f, name);
+            out.push(')');
+        }
+    }
+
+    /// Returns the factored form of the given edge,
+    /// using the given function for the names of the variables.
+    fn factored_form_string(&self, f: Edge, name: &dyn Fn(usize) -> String) -> String {
+        if f == ONE {
+            return "t".to_string();
+        }
+        if f == ZERO {
+            return "f".to_string();
+        }
+        let mut out = String::new();
+        let parentheses = is_complement(f) && !self.is_var(regular(f));
+        if is_complement(f) {
+            out.push_str(if parentheses { "!(" } else { "!" });
+        }
+        self.factored_form(&mut out, regular(f), name);
+        if parentheses {
+            out.push(')');
+        }
+        out
+    }
+
+    /// Appends the cubes for all paths from the given edge to constant one to the list,
+    /// taking else branches first, where `cube` holds the values along the current path.
+    fn cubes(&self, f: Edge, cube: &mut Vec<CubeValue>, num_vars: usize, cubes: &mut Vec<Cube>) {
+        if f == ZERO {
+            return;
+        }
+        if f == ONE {
+            cubes.push(Cube {
+                cube: cube[..num_vars].to_vec(),
+            });
+            return;
+        }
+        let level = self.level(f);
+        let var = self.invperm[level];
+        let (f1, f0) = self.cofactors(f, level);
+        cube[var] = CubeValue::Unset;
+        self.cubes(f0, cube, num_vars, cubes);
+        cube[var] = CubeValue::Set;
+        self.cubes(f1, cube, num_vars, cubes);
+        cube[var] = CubeValue::Unspecified;
+    }
+
+    /// Returns the indices of all nodes reachable from the given edges,
+    /// where children come before their parents.
+    fn reachable_nodes(&self, edges: &[Edge]) -> Vec<usize> {
+        let mut visited = HashSet::new();
+        let mut order = Vec::new();
+        let mut stack: Vec<_> = edges
+            .iter()
+            .map(|&edge| (node_index(edge), false))
+            .collect();
+        while let Some((index, expanded)) = stack.pop() {
+            if expanded {
+                order.push(index);
+            } else if visited.insert(index) {
+                stack.push((index, true));
+                let node = self.nodes[index];
+                if node.var != CONSTANT {
+                    stack.push((node_index(node.else_edge), false));
+                    stack.push((node_index(node.then_edge), false));
+                }
+            }
+        }
+        order
+    }
+}
+
+/// Returns the name for a node in the DOT format, given by its index
+/// in hexadecimal notation with prefix, or 0 for the constant node.
+fn dot_name(index: usize) -> String {
+    if index == 0 {
+        "0".to_string()
+    } else {
+        format!("{:#x}", index)
+    }
+}
+
+/// Internal wrapper for the BDD manager. The manager
+/// should only be accessed through an [`Rc`] pointer to
+/// avoid dropping it while any BDDs created by it are still used.
+pub(crate) struct Manager {
+    /// The nodes and tables of the manager.
+    state: RefCell<State>,
+    /// The error handler to call in case of errors.
+    error_handler: fn(CuddError) -> (),
+}
+
+impl fmt::Debug for Manager {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("Manager")
+            .field("size", &state.size())
+            .field("live_nodes", &state.live_nodes())
+            .finish()
+    }
+}
+
+impl Manager {
+    /// Checks if a BDD is from this manager, and otherwise calls the error handler.
+    fn check_same_manager(&self, other: &Bdd) {
+        if !std::ptr::eq(self, &*other.cudd) {
+            (self.error_handler)(CuddError::DifferentManager);
+        }
+    }
+
+    /// Checks if a BDD is a positive cube, and otherwise calls the error handler.
+    fn check_positive_cube(&self, cube: &Bdd) {
+        if !self.state.borrow().is_positive_cube(cube.edge) {
+            (self.error_handler)(CuddError::InvalidArg);
+        }
+    }
+}
+
+/// A manager for BDDs implemented in Rust with the interface of the CUDD framework.
+#[derive(Debug)]
+pub struct Cudd {
+    /// Internal manager.
+    pub(crate) manager: Rc<Manager>,
+}
+
+impl Cudd {
+    /// Creates a new BDD manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_vars`: The initial number of BDD variables.
+    /// * `num_vars_z`: The initial number of ZDD variables.
+    /// * `num_slots`: Initial size of the unique tables.
+    /// * `cache_size`: Initial size of the cache.
+    /// * `max_memory`: Target maximum memory occupation.
+    /// * `error_handler`: A function which is called if an error occurs.
+    ///
+    /// Only the number of BDD variables and the error handler are used, the
+    /// other arguments are specific to the CUDD library and are ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function does not return errors, but keeps the signature of the CUDD library.
+    pub fn new(
+        num_vars: usize,
+        _num_vars_z: usize,
+        _num_slots: usize,
+        _cache_size: usize,
+        _max_memory: usize,
+        error_handler: fn(CuddError) -> (),
+    ) -> Result<Self, CuddError> {
+        Ok(Self {
+            manager: Rc::new(Manager {
+                state: RefCell::new(State::new(num_vars)),
+                error_handler,
+            }),
+        })
+    }
+
+    /// The default error handler, which panics with the given error message.
+    pub fn default_handler(error: CuddError) {
+        panic!("{}", error)
+    }
+
+    /// Create a BDD manager with default values.
+    ///
+    /// # Errors
+    ///
+    /// This function does not return errors, but keeps the signature of the CUDD library.
+    #[allow(clippy::should_implement_trait)]
+    pub fn default() -> Result<Self, CuddError> {
+        Self::with_vars(0)
+    }
+
+    /// Create a BDD manager with the given number of initial BDD variables
+    /// and default values otherwise.
+    ///
+    /// # Errors
+    ///
+    /// This function does not return errors, but keeps the signature of the CUDD library.
+    pub fn with_vars(num_vars: usize) -> Result<Self, CuddError> {
+        Self::new(num_vars, 0, 0, 0, 0, Self::default_handler)
+    }
+
+    /// Returns a new BDD variable.
+    ///
+    /// The new variable has an index equal to the largest previous index plus 1.
+    pub fn bdd_new_var(&self) -> Bdd {
+        Bdd::apply(&self.manager, |state| state.var(state.size()))
+    }
+
+    /// Returns the BDD variable with the given index.
+    ///
+    /// Retrieves the BDD variable if the given index if it already exists,
+    /// or creates a new BDD variable.
+    pub fn bdd_var(&self, index: usize) -> Bdd {
+        Bdd::apply(&self.manager, |state| state.var(index))
+    }
+
+    /// Returns the one constant of the manager.
+    pub fn bdd_one(&self) -> Bdd {
+        Bdd::new(&self.manager, ONE)
+    }
+
+    /// Returns the logic zero constant of the manager,
+    /// which is the complement of the one constant.
+    pub fn bdd_zero(&self) -> Bdd {
+        Bdd::new(&self.manager, ZERO)
+    }
+
+    /// Returns the cube of the BDD variables with the given indices,
+    /// i.e. the conjunction of these variables.
+    ///
+    /// The cube can be used to quantify over the variables,
+    /// e.g. with [`Bdd::exist_abstract`].
+    pub fn bdd_cube(&self, vars: &[usize]) -> Bdd {
+        Bdd::apply(&self.manager, |state| {
+            vars.iter().fold(ONE, |cube, &var| {
+                let var = state.var(var);
+                state.and(cube, var)
+            })
+        })
+    }
+
+    /// Returns the cube for the given values of BDD variables, i.e. the conjunction
+    /// of the variables with value `true` and the negated variables with value `false`.
+    pub fn bdd_cube_with_values(&self, values: &[(usize, bool)]) -> Bdd {
+        Bdd::apply(&self.manager, |state| {
+            values.iter().fold(ONE, |cube, &(var, value)| {
+                let var = state.var(var);
+                state.and(cube, if value { var } else { complement(var) })
+            })
+        })
+    }
+
+    /// Returns a string with a Graphviz/DOT representation of the argument BDDs.
+    ///
+    /// The argument `in_names` is used for the names of the variables
+    /// and the argument `out_names` for the names of the BDDs.
+    ///
+    /// Calls the set error handler if the BDDs come from a different manager.
+    pub fn dump_dot<S: AsRef<str>>(&self, bdds: &[Bdd], in_names: &[S], out_names: &[S]) -> String {
+        let mut buffer = Vec::new();
+        self.write_dot(&mut buffer, bdds, in_names, out_names)
+            .unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    /// Writes a Graphviz/DOT representation of the argument BDDs to the given writer.
+    ///
+    /// The argument `in_names` is used for the names of the variables
+    /// and the argument `out_names` for the names of the BDDs.
+    /// The representation uses the same format as the CUDD library.
+    ///
+    /// Calls the set error handler if the BDDs come from a different manager.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs with the writer.
+    pub fn write_dot<W: io::Write, S: AsRef<str>>(
+        &self,
+        mut writer: W,
+        bdds: &[Bdd],
+        in_names: &[S],
+        out_names: &[S],
+    ) -> io::Result<()> {
+        for bdd in bdds {
+            self.manager.check_same_manager(bdd);
+        }
+        let state = self.manager.state.borrow();
+        let edges: Vec<_> = bdds.iter().map(|bdd| bdd.edge).collect();
+        let mut visited = state.reachable_nodes(&edges);
+        visited.sort_unstable();
+        let levels: Vec<Vec<usize>> = state
+            .invperm
+            .iter()
+            .map(|&var| {
+                visited
+                    .iter()
+                    .copied()
+                    .filter(|&index| state.nodes[index].var as usize == var)
+                    .collect()
+            })
+            .collect();
+        let in_name = |var: usize| match in_names.get(var) {
+            Some(name) => format!("\" {} \"", name.as_ref()),
+            None => format!("\" {} \"", var),
+        };
+        let out_name = |i: usize| match out_names.get(i) {
+            Some(name) => format!("\"  {}  \"", name.as_ref()),
+            None => format!("\"F{}\"", i),
+        };
+
+        // header and the subgraph of the variables
+        writeln!(writer, "digraph \"DD\" {{")?;
+        writeln!(
+            writer,
+            "size = \"7.5,10\"\ncenter = true;\nedge [dir = none];"
+        )?;
+        writeln!(writer, "{{ node [shape = plaintext];")?;
+        writeln!(writer, "  edge [style = invis];")?;
+        writeln!(writer, "  \"CONST NODES\" [style = invis];")?;
+        for (level, nodes) in levels.iter().enumerate() {
+            if !nodes.is_empty() {
+                write!(writer, "{} -> ", in_name(state.invperm[level]))?;
+            }
+        }
+        writeln!(writer, "\"CONST NODES\"; \n}}")?;
+
+        // subgraph of the outputs
+        writeln!(
+            writer,
+            "{{ rank = same; node [shape = box]; edge [style = invis];"
+        )?;
+        for i in 0..bdds.len() {
+            write!(writer, "{}", out_name(i))?;
+            if i == bdds.len() - 1 {
+                writeln!(writer, "; }}")?;
+            } else {
+                write!(writer, " -> ")?;
+            }
+        }
+
+        // nodes with the same variable have the same rank
+        for (level, nodes) in levels.iter().enumerate() {
+            if !nodes.is_empty() {
+                writeln!(writer, "{{ rank = same; {};", in_name(state.invperm[level]))?;
+                for &index in nodes {
+                    writeln!(writer, "\"{}\";", dot_name(index))?;
+                }
+                writeln!(writer, "}}")?;
+            }
+        }
+        writeln!(
+            writer,
+            "{{ rank = same; \"CONST NODES\";\n{{ node [shape = box]; \"{}\";\n}}\n}}",
+            dot_name(0)
+        )?;
+
+        // edges from the outputs and the nodes
+        for (i, &edge) in edges.iter().enumerate() {
+            let style = if is_complement(edge) {
+                "dotted"
+            } else {
+                "solid"
+            };
+            writeln!(
+                writer,
+                "{} -> \"{}\" [style = {}];",
+                out_name(i),
+                dot_name(node_index(edge)),
+                style
+            )?;
+        }
+        for &index in levels.iter().flatten() {
+            let node = state.nodes[index];
+            writeln!(
+                writer,
+                "\"{}\" -> \"{}\";",
+                dot_name(index),
+                dot_name(node_index(node.then_edge))
+            )?;
+            let style = if is_complement(node.else_edge) {
+                "dotted"
+            } else {
+                "dashed"
+            };
+            writeln!(
+                writer,
+                "\"{}\" -> \"{}\" [style = {}];",
+                dot_name(index),
+                dot_name(node_index(node.else_edge)),
+                style
+            )?;
+        }
+        writeln!(writer, "\"{}\" [label = \"1\"];", dot_name(0))?;
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+
+    /// Reclaims all nodes that are no longer used by any BDD.
+    ///
+    /// Unlike the CUDD library, this package does not reorder variables dynamically,
+    /// so the method and the argument `minsize` are ignored.
+    pub fn reduce_heap(&mut self, _method: ReorderingMethod, _minsize: usize) {
+        self.manager.state.borrow_mut().collect_garbage();
+    }
+
+    /// Reorders the variables according to the given order, where `order[i]` is
+    /// the index of the variable that is moved to level `i`.
+    ///
+    /// The order must be a permutation of all variable indices of this manager.
+    ///
+    /// Calls the set error handler if the order is not such a permutation.
+    pub fn shuffle_heap(&mut self, order: &[usize]) {
+        let mut state = self.manager.state.borrow_mut();
+        let mut sorted = order.to_vec();
+        sorted.sort_unstable();
+        if !sorted.iter().copied().eq(0..state.size()) {
+            drop(state);
+            (self.manager.error_handler)(CuddError::InvalidArg);
+            return;
+        }
+        state.collect_garbage();
+        for (level, &var) in order.iter().enumerate() {
+            for current in (level..state.perm[var]).rev() {
+                state.swap_levels(current);
+            }
+        }
+    }
+
+    /// Creates a group of the `size` variables at the consecutive levels starting
+    /// at the level of the variable with index `low`.
+    ///
+    /// As this package does not reorder variables dynamically,
+    /// groups have no effect.
+    pub fn make_group(&mut self, _low: usize, _size: usize, _fixed: bool) {}
+
+    /// Returns the current variable order, where the element at position `i`
+    /// is the index of the variable at level `i`.
+    pub fn variable_order(&self) -> Vec<usize> {
+        self.manager.state.borrow().invperm.clone()
+    }
+
+    /// Enables automatic dynamic reordering of BDDs.
+    ///
+    /// As this package does not reorder variables dynamically, this has no effect.
+    pub fn autodyn_enable(&mut self, _method: ReorderingMethod) {}
+
+    /// Disables automatic dynamic reordering.
+    pub fn autodyn_disable(&mut self) {}
+}
+
+/// A binary decision diagram (BDD).
+///
+/// As BDDs implement the correspond and, or and not operations,
+/// conjunction, disjunction and negation of BDDs can be performed
+/// using the operators `&`, `|` and `!`.
+/// Both the left-hand-side and right-hand-side of the operators can be references.
+/// BDDs are not copy types, but cloning a BDD is cheap as only a reference count is increased.
+///
+/// # Examples
+///
+/// The following example uses BDDs to show that the equivalence
+/// `!(x & y) == !x | !y` holds.
+///
+/// ```
+/// # use cudd::Cudd;
+/// let manager = Cudd::default().unwrap();
+/// let x = &manager.bdd_new_var();
+/// let y = &manager.bdd_new_var();
+/// let lhs = !(x & y);
+/// let rhs = !x | !y;
+/// assert_eq!(lhs, rhs);
+/// ```
+#[derive(Debug)]
+pub struct Bdd {
+    /// Pointer to the manager for this BDD.
+    pub(crate) cudd: Rc<Manager>,
+    /// The edge to the root node.
+    edge: Edge,
+}
+
+impl Drop for Bdd {
+    fn drop(&mut self) {
+        self.cudd.state.borrow_mut().references[node_index(self.edge)] -= 1;
+    }
+}
+
+impl Bdd {
+    /// Returns the manager which was used to create this BDD.
+    pub fn manager(&self) -> Cudd {
+        Cudd {
+            manager: Rc::clone(&self.cudd),
+        }
+    }
+
+    /// Returns the unique node id for this BDD as an integer.
+    pub fn node_id(&self) -> usize {
+        self.edge as usize
+    }
+
+    /// Compares this BDD with the given BDD by their truth tables, where valuations are
+    /// ordered lexicographically by the variable indices and false is before true.
+    ///
+    /// Unlike the node id, this order only depends on the represented functions and
+    /// not on the history or variable order of the manager.
+    ///
+    /// Calls the set error handler if the BDDs come from different managers.
+    pub fn canonical_cmp(&self, other: &Self) -> Ordering {
+        self.cudd.check_same_manager(other);
+        if self.edge == other.edge {
+            return Ordering::Equal;
+        }
+        // find the smallest valuation on which the functions differ
+        let manager = self.manager();
+        let mut difference = (self & !other) | (other & !self);
+        let size = self.cudd.state.borrow().size();
+        for var in 0..size {
+            let unset = &difference & !manager.bdd_var(var);
+            if unset.is_zero() {
+                difference &= manager.bdd_var(var);
+            } else {
+                difference = unset;
+            }
+        }
+        if (self & &difference).is_zero() {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }
+
+    /// Creates a new BDD for the given edge.
+    ///
+    /// Increments the reference count for the node by one.
+    fn new(cudd: &Rc<Manager>, edge: Edge) -> Self {
+        cudd.state.borrow_mut().references[node_index(edge)] += 1;
+        Self {
+            cudd: Rc::clone(cudd),
+            edge,
+        }
+    }
+
+    /// Creates a new BDD for the edge computed by the given operation.
+    ///
+    /// Unused nodes may be reclaimed before the operation.
+    fn apply<F: FnOnce(&mut State) -> Edge>(cudd: &Rc<Manager>, operation: F) -> Self {
+        let edge = {
+            let mut state = cudd.state.borrow_mut();
+            state.collect_garbage_if_needed();
+            operation(&mut state)
+        };
+        Self::new(cudd, edge)
+    }
+
+    /// Returns the regular version of this BDD.
+    pub fn regular(&self) -> Self {
+        Self::new(&self.cudd, regular(self.edge))
+    }
+
+    /// Returns whether this BDD is a constant, i.e. zero or one.
+    pub fn is_constant(&self) -> bool {
+        node_index(self.edge) == 0
+    }
+
+    /// Returns whether this BDD is complemented.
+    pub fn is_complement(&self) -> bool {
+        is_complement(self.edge)
+    }
+
+    /// Returns whether this BDD is constant zero.
+    pub fn is_zero(&self) -> bool {
+        self.edge == ZERO
+    }
+
+    /// Returns whether this BDD is constant one.
+    pub fn is_one(&self) -> bool {
+        self.edge == ONE
+    }
+
+    /// Performs an if-then-else operation with this BDD and the given operands,
+    /// and returns the resulting BDD.
+    ///
+    /// Calls the set error handler if the BDDs come from different managers.
+    pub fn ite(&self, g: &Self, h: &Self) -> Self {
+        self.cudd.check_same_manager(g);
+        self.cudd.check_same_manager(h);
+        Self::apply(&self.cudd, |state| state.ite(self.edge, g.edge, h.edge))
+    }
+
+    /// Performs an if-then-else operation with this BDD and the given operands,
+    /// and assigns the result to itself.
+    ///
+    /// Calls the set error handler if the BDDs come from different managers.
+    pub fn ite_assign(&mut self, g: &Self, h: &Self) {
+        *self = self.ite(g, h);
+    }
+
+    /// Simplifies this BDD with respect to the given care set, and returns the resulting BDD.
+    ///
+    /// The result agrees with this BDD on all valuations in the care set. It is
+    /// never larger than this BDD, but not guaranteed to be minimal.
+    ///
+    /// Calls the set error handler if the BDDs come from different managers.
+    pub fn restrict(&self, care: &Self) -> Self {
+        self.cudd.check_same_manager(care);
+        Self::apply(&self.cudd, |state| {
+            let result = state.restrict(self.edge, care.edge);
+            if state.dag_size(result) > state.dag_size(self.edge) {
+                self.edge
+            } else {
+                result
+            }
+        })
+    }
+
+    /// Existentially quantifies the variables in the given cube from this BDD,
+    /// and returns the resulting BDD.
+    ///
+    /// The cube must be a conjunction of positive variables, e.g.
+    /// obtained from [`Cudd::bdd_cube`].
+    ///
+    /// Calls the set error handler if the cube is not a positive cube,
+    /// or the BDDs come from different managers.
+    pub fn exist_abstract(&self, cube: &Self) -> Self {
+        self.cudd.check_same_manager(cube);
+        self.cudd.check_positive_cube(cube);
+        Self::apply(&self.cudd, |state| state.exist(self.edge, cube.edge))
+    }
+
+    /// Universally quantifies the variables in the given cube from this BDD,
+    /// and returns the resulting BDD.
+    ///
+    /// The cube must be a conjunction of positive variables, e.g.
+    /// obtained from [`Cudd::bdd_cube`].
+    ///
+    /// Calls the set error handler if the cube is not a positive cube,
+    /// or the BDDs come from different managers.
+    pub fn forall_abstract(&self, cube: &Self) -> Self {
+        self.cudd.check_same_manager(cube);
+        self.cudd.check_positive_cube(cube);
+        Self::apply(&self.cudd, |state| {
+            complement(state.exist(complement(self.edge), cube.edge))
+        })
+    }
+
+    /// Computes the conjunction of this BDD with the given BDD and existentially
+    /// quantifies the variables in the given cube from the result.
+    ///
+    /// This is equivalent to `(self & other).exist_abstract(cube)`.
+    ///
+    /// Calls the set error handler if the cube is not a positive cube,
+    /// or the BDDs come from different managers.
+    pub fn and_abstract(&self, other: &Self, cube: &Self) -> Self {
+        self.cudd.check_same_manager(other);
+        self.cudd.check_same_manager(cube);
+        self.cudd.check_positive_cube(cube);
+        Self::apply(&self.cudd, |state| {
+            let conjunction = state.and(self.edge, other.edge);
+            state.exist(conjunction, cube.edge)
+        })
+    }
+
+    /// Substitutes the given BDD for the variable with the given index in this BDD,
+    /// and returns the resulting BDD.
+    ///
+    /// Calls the set error handler if the BDDs come from different managers.
+    pub fn compose(&self, g: &Self, var: usize) -> Self {
+        let manager = self.manager();
+        let mut vector: Vec<_> = (0..var).map(|var| manager.bdd_var(var)).collect();
+        vector.push(g.clone());
+        self.vector_compose(&vector)
+    }
+
+    /// Simultaneously substitutes the BDD at index `i` of the given vector for
+    /// the variable with index `i` in this BDD, and returns the resulting BDD.
+    ///
+    /// Variables with an index not covered by the vector are left unchanged.
+    ///
+    /// Calls the set error handler if the BDDs come from different managers.
+    pub fn vector_compose(&self, vector: &[Self]) -> Self {
+        for bdd in vector {
+            self.cudd.check_same_manager(bdd);
+        }
+        Self::apply(&self.cudd, |state| {
+            let size = state.size();
+            let mut edges: Vec<_> = vector.iter().take(size).map(|bdd| bdd.edge).collect();
+            for var in edges.len()..size {
+                let edge = state.var(var);
+                edges.push(edge);
+            }
+            state.vector_compose(self.edge, &edges, &mut HashMap::new())
+        })
+    }
+
+    /// Renames the variables in this BDD according to the given permutation,
+    /// where the variable with index `i` is replaced by the variable with
+    /// index `permutation[i]`, and returns the resulting BDD.
+    ///
+    /// Variables with an index not covered by the permutation are left unchanged.
+    pub fn permute(&self, permutation: &[usize]) -> Self {
+        let manager = self.manager();
+        let vector: Vec<_> = permutation
+            .iter()
+            .map(|&var| manager.bdd_var(var))
+            .collect();
+        self.vector_compose(&vector)
+    }
+
+    /// Swaps the variables with indices in `x` with the variables with the
+    /// indices at the same position in `y` in this BDD, and returns the resulting BDD.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` and `y` have different lengths.
+    pub fn swap_variables(&self, x: &[usize], y: &[usize]) -> Self {
+        assert_eq!(x.len(), y.len(), "variables to swap have different lengths");
+        let size = x.iter().chain(y).map(|&var| var + 1).max().unwrap_or(0);
+        let mut permutation: Vec<_> = (0..size).collect();
+        for (&x_var, &y_var) in x.iter().zip(y) {
+            permutation[x_var] = y_var;
+            permutation[y_var] = x_var;
+        }
+        self.permute(&permutation)
+    }
+
+    /// Returns a factored form representation of this BDD with the given names.
+    ///
+    /// The factored form uses `&` for conjunction, `|` for disjunction
+    /// and `!` for negation.  Caution must be exercised because the factored
+    /// form may be exponentially larger than this BDD.
+    pub fn factored_form_string<S: AsRef<str>>(&self, names: &[S]) -> String {
+        let name = |var: usize| names[var].as_ref().to_string();
+        self.cudd
+            .state
+            .borrow()
+            .factored_form_string(self.edge, &name)
+    }
+
+    /// Returns an iterator that iterates over the paths of this BDD
+    /// and returns the cube for each path.
+    #[must_use]
+    pub fn cube_iter(&self, num_vars: usize) -> CubeIter<'_> {
+        CubeIter::new(self, num_vars)
+    }
+
+    /// Returns an iterator that iterates over the paths of this BDD
+    /// and returns the BDD for each path.
+    #[must_use]
+    pub fn bdd_cube_iter(&self, num_vars: usize) -> BddCubeIter<'_> {
+        BddCubeIter::new(self, num_vars)
+    }
+
+    /// Converts this BDD to another manager and returns the copy in the destination manager.
+    ///
+    /// The order of the variables in the two managers my be different.
+    pub fn transfer(&self, destination: &Cudd) -> Self {
+        let nodes: Vec<_> = {
+            let state = self.cudd.state.borrow();
+            state
+                .reachable_nodes(&[self.edge])
+                .into_iter()
+                .map(|index| (index, state.nodes[index]))
+                .collect()
+        };
+        Self::apply(&destination.manager, |state| {
+            let mut copies = HashMap::new();
+            copies.insert(0, ONE);
+            let copy =
+                |copies: &HashMap<usize, Edge>, edge: Edge| copies[&node_index(edge)] ^ (edge & 1);
+            for (index, node) in nodes {
+                if node.var != CONSTANT {
+                    let var = state.var(node.var as usize);
+                    let then_edge = copy(&copies, node.then_edge);
+                    let else_edge = copy(&copies, node.else_edge);
+                    let edge = state.ite(var, then_edge, else_edge);
+                    copies.insert(index, edge);
+                }
+            }
+            copy(&copies, self.edge)
+        })
+    }
+
+    /// Returns a view into the node for this BDD.
+    #[must_use]
+    pub fn view(&self) -> BddView {
+        if self.is_constant() {
+            BddView::Constant
+        } else {
+            let node = self.cudd.state.borrow().nodes[node_index(self.edge)];
+            BddView::InnerNode {
+                var: node.var as usize,
+                bdd_then: Self::new(&self.cudd, node.then_edge),
+                bdd_else: Self::new(&self.cudd, node.else_edge),
+            }
+        }
+    }
+}
+
+impl Cube {
+    /// Returns the BDD for this cube in the given manager.
+    fn to_bdd(&self, cudd: &Rc<Manager>) -> Bdd {
+        Bdd::apply(cudd, |state| {
+            self.cube
+                .iter()
+                .enumerate()
+                .fold(ONE, |cube, (var, value)| match value {
+                    CubeValue::Unset => {
+                        let var = state.var(var);
+                        state.and(cube, complement(var))
+                    }
+                    CubeValue::Set => {
+                        let var = state.var(var);
+                        state.and(cube, var)
+                    }
+                    CubeValue::Unspecified => cube,
+                })
+        })
+    }
+}
+
+/// An iterator over cubes corresponding to the paths in a source BDD.
+pub struct CubeIter<'a> {
+    /// The source BDD.
+    bdd: &'a Bdd,
+    /// The remaining cubes to return.
+    cubes: std::vec::IntoIter<Cube>,
+}
+
+impl<'a> CubeIter<'a> {
+    /// Creates a new iterator with the given source BDD and number of variables.
+    fn new(bdd: &'a Bdd, num_vars: usize) -> Self {
+        let state = bdd.cudd.state.borrow();
+        let mut cube = vec![CubeValue::Unspecified; state.size().max(num_vars)];
+        let mut cubes = Vec::new();
+        state.cubes(bdd.edge, &mut cube, num_vars, &mut cubes);
+        Self {
+            bdd,
+            cubes: cubes.into_iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for CubeIter<'a> {
+    type Item = Cube;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cubes.next()
+    }
+}
+
+/// An iterator over BDDs corresponding to the paths in a source BDD.
+pub struct BddCubeIter<'a> {
+    /// The cube iterator that is used to iterate over paths.
+    cube_iter: CubeIter<'a>,
+}
+
+impl<'a> BddCubeIter<'a> {
+    /// Creates a new iterator with the given source BDD and number of variables.
+    fn new(bdd: &'a Bdd, num_vars: usize) -> Self {
+        BddCubeIter {
+            cube_iter: CubeIter::new(bdd, num_vars),
+        }
+    }
+}
+
+impl<'a> Iterator for BddCubeIter<'a> {
+    type Item = Bdd;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cube_iter
+            .next()
+            .map(|cube| cube.to_bdd(&self.cube_iter.bdd.cudd))
+    }
+}
+
+impl fmt::Display for Bdd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let string = self
+            .cudd
+            .state
+            .borrow()
+            .factored_form_string(self.edge, &|var| var.to_string());
+        write!(f, "{}", string)
+    }
+}
+
+impl Hash for Bdd {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.edge.hash(state);
+    }
+}
+
+impl PartialEq for Bdd {
+    fn eq(&self, other: &Self) -> bool {
+        self.cudd.check_same_manager(other);
+        self.edge == other.edge
+    }
+}
+impl Eq for Bdd {}
+
+impl PartialOrd for Bdd {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.cudd.check_same_manager(other);
+        if self.edge == other.edge {
+            Some(Ordering::Equal)
+        } else if (self & !other).is_zero() {
+            Some(Ordering::Less)
+        } else if (other & !self).is_zero() {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
+impl Clone for Bdd {
+    fn clone(&self) -> Self {
+        Self::new(&self.cudd, self.edge)
+    }
+}
+
+macro_rules! not_impl {
+    ($t:ty) => {
+        impl std::ops::Not for $t {
+            type Output = Bdd;
+
+            fn not(self) -> Self::Output {
+                Bdd::new(&self.cudd, complement(self.edge))
+            }
+        }
+    };
+}
+
+not_impl!(Bdd);
+not_impl!(&Bdd);
+
+macro_rules! and_impl {
+    ($t:ty) => {
+        impl<R: Borrow<Bdd>> std::ops::BitAnd<R> for $t {
+            type Output = Bdd;
+
+            fn bitand(self, rhs: R) -> Self::Output {
+                let rhs = rhs.borrow();
+                self.cudd.check_same_manager(rhs);
+                Bdd::apply(&self.cudd, |state| state.and(self.edge, rhs.edge))
+            }
+        }
+    };
+}
+
+and_impl!(Bdd);
+and_impl!(&Bdd);
+
+impl<R: Borrow<Bdd>> std::ops::BitAndAssign<R> for Bdd {
+    fn bitand_assign(&mut self, rhs: R) {
+        *self = &*self & rhs;
+    }
+}
+
+macro_rules! or_impl {
+    ($t:ty) => {
+        impl<R: Borrow<Bdd>> std::ops::BitOr<R> for $t {
+            type Output = Bdd;
+
+            fn bitor(self, rhs: R) -> Self::Output {
+                let rhs = rhs.borrow();
+                self.cudd.check_same_manager(rhs);
+                Bdd::apply(&self.cudd, |state| state.or(self.edge, rhs.edge))
+            }
+        }
+    };
+}
+
+or_impl!(Bdd);
+or_impl!(&Bdd);
+
+impl<R: Borrow<Bdd>> std::ops::BitOrAssign<R> for Bdd {
+    fn bitor_assign(&mut self, rhs: R) {
+        *self = &*self | rhs;
+    }
+}
+
+/// Tests for the BDD package implemented in Rust.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that nodes without references are reclaimed and BDDs with references are preserved.
+    #[test]
+    fn test_collect_garbage() {
+        let mut cudd = Cudd::with_vars(8).unwrap();
+        let vars: Vec<_> = (0..8).map(|var| cudd.bdd_var(var)).collect();
+        let xor = |f: Bdd, x: &Bdd| (&f & !x) | (!&f & x);
+        let parity = vars.iter().fold(cudd.bdd_zero(), xor);
+        // the constant, one node per variable for the parity, and the other variables
+        cudd.reduce_heap(ReorderingMethod::None, 0);
+        assert_eq!(cudd.manager.state.borrow().live_nodes(), 16);
+        for (x, y) in vars.iter().zip(&vars[1..]) {
+            let _ = (x | y) & (&parity | x);
+        }
+        cudd.reduce_heap(ReorderingMethod::None, 0);
+        assert_eq!(cudd.manager.state.borrow().live_nodes(), 16);
+        // reordering preserves the nodes of the parity
+        cudd.shuffle_heap(&[7, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(vars.iter().rev().fold(cudd.bdd_zero(), xor), parity);
+        assert_eq!(cudd.manager.state.borrow().dag_size(parity.edge), 9);
+    }
+}
@@ -9,7 +9,7 @@ use ordered_float::NotNan;
 
 use crate::bindings::*;
 use crate::formula::Ltl;
-use crate::graal::Vm;
+use crate::graal::{OwlError, Vm};
 use crate::tree::{Node, TreeIndex, ValuationTree};
 
 /// An index for a state of an automaton.
@@ -52,6 +52,18 @@ impl StateIndex {
         self == Self::TOP || self == Self::BOTTOM
     }
 
+    /// Returns the number of this state in the order in which states are discovered,
+    /// starting with 0 for the initial state, or `None` for the sink states.
+    pub fn index(self) -> Option<usize> {
+        usize::try_from(self.0).ok()
+    }
+
+    /// Returns the index of the state with the given number, which is the inverse
+    /// of [`Self::index`], or `None` if the number is too large for a state index.
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::try_from(index).ok()
+    }
+
     /// Trys conversion of a value into a state index.
     ///
     /// Note: due to the blanket implementation for `TryFrom` in the standard
@@ -130,6 +142,10 @@ pub trait MaxEvenDpa {
         &self,
         state_iter: I,
     ) -> HashMap<StateIndex, ZielonkaNormalFormState>;
+    /// Returns the score of the state with the given index, i.e. the heuristical
+    /// value in `0.0..=1.0` that is assigned to the edges leading to the state,
+    /// if the successors of a state with an edge to it have been computed.
+    fn state_score(&self, state: StateIndex) -> Option<f64>;
 }
 
 /// The parity acceptance condition of an automaton returned by Owl.
@@ -160,25 +176,25 @@ struct AutomatonInfo {
 
 impl AutomatonInfo {
     /// Creates the automaton information from the values given by Owl.
-    fn from_owl(acc: acceptance_t, acc_sets: c_int) -> Self {
-        let acceptance = Self::convert_acceptance(acc);
+    fn from_owl(acc: acceptance_t, acc_sets: c_int) -> Result<Self, OwlError> {
+        let acceptance = Self::convert_acceptance(acc)?;
         let num_colors = Self::init_num_colors(acceptance, acc_sets);
         assert!(num_colors >= 1);
-        Self {
+        Ok(Self {
             acceptance,
             num_colors,
-        }
+        })
     }
 
     /// Converts the acceptance condition from the Owl enum.
-    fn convert_acceptance(acc: acceptance_t) -> ParityAcceptance {
+    fn convert_acceptance(acc: acceptance_t) -> Result<ParityAcceptance, OwlError> {
         #![allow(non_upper_case_globals)]
         match acc {
-            acceptance_t_PARITY_MAX_EVEN => ParityAcceptance::MaxEven,
-            acceptance_t_PARITY_MAX_ODD => ParityAcceptance::MaxOdd,
-            acceptance_t_PARITY_MIN_EVEN => ParityAcceptance::MinEven,
-            acceptance_t_PARITY_MIN_ODD => ParityAcceptance::MinOdd,
-            _ => panic!("Unsupported acceptance condition: {}", acc),
+            acceptance_t_PARITY_MAX_EVEN => Ok(ParityAcceptance::MaxEven),
+            acceptance_t_PARITY_MAX_ODD => Ok(ParityAcceptance::MaxOdd),
+            acceptance_t_PARITY_MIN_EVEN => Ok(ParityAcceptance::MinEven),
+            acceptance_t_PARITY_MIN_ODD => Ok(ParityAcceptance::MinOdd),
+            _ => Err(OwlError::UnsupportedAcceptance(acc as i64)),
         }
     }
 
@@ -210,6 +226,8 @@ pub struct Automaton<'a> {
     info: AutomatonInfo,
     /// The successors of the automaton and whether they are already computed.
     successors: Vec<Option<EdgeTree<Score>>>,
+    /// The scores of the states reached by the computed successors.
+    scores: HashMap<StateIndex, Score>,
 }
 
 impl<'a> Drop for Automaton<'a> {
@@ -241,12 +259,40 @@ impl<'a> Automaton<'a> {
         successors
     }
 
+    /// Initializes the scores of the fixed top and bottom sink states.
+    fn init_scores() -> HashMap<StateIndex, Score> {
+        let mut scores = HashMap::with_capacity(4096);
+        scores.insert(StateIndex::TOP, Score::new(1.0).unwrap());
+        scores.insert(StateIndex::BOTTOM, Score::new(0.0).unwrap());
+        scores
+    }
+
     /// Creates an automaton for the given LTL formula, with optional simplification and lookahead.
     ///
     /// If the lookahead is set to `-1`, then the ACD constrution is always used.
     /// If the lookahead is set to `0`, then the Zielonka tree is always used.
     /// Otherwise, the given number of states is explored before either the ACD or Zielonka tree is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the automaton can not be constructed, see [`Automaton::try_of`].
     pub fn of(vm: &'a Vm, formula: &Ltl, simplify_formula: bool, lookahead: i32) -> Self {
+        Self::try_of(vm, formula, simplify_formula, lookahead).unwrap()
+    }
+
+    /// Creates an automaton for the given LTL formula as for [`Automaton::of`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Owl raises an exception during the construction, e.g. if
+    /// the heap of the GraalVM is exhausted, or if the automaton does not have a
+    /// parity acceptance condition.
+    pub fn try_of(
+        vm: &'a Vm,
+        formula: &Ltl,
+        simplify_formula: bool,
+        lookahead: i32,
+    ) -> Result<Self, OwlError> {
         let automaton = unsafe {
             if simplify_formula {
                 automaton_of1(
@@ -265,16 +311,27 @@ impl<'a> Automaton<'a> {
                 )
             }
         };
+        if automaton.is_null() {
+            return Err(OwlError::Translation);
+        }
         let acc = unsafe { automaton_acceptance_condition(vm.thread, automaton) };
         let acc_sets = unsafe { automaton_acceptance_condition_sets(vm.thread, automaton) };
-        let info = AutomatonInfo::from_owl(acc, acc_sets);
+        let info = match AutomatonInfo::from_owl(acc, acc_sets) {
+            Ok(info) => info,
+            Err(err) => {
+                unsafe { destroy_object_handle(vm.thread, automaton) };
+                return Err(err);
+            }
+        };
         let successors = Self::init_successors();
-        Automaton {
+        let scores = Self::init_scores();
+        Ok(Automaton {
             vm,
             automaton,
             info,
             successors,
-        }
+            scores,
+        })
     }
 }
 
@@ -401,10 +458,20 @@ impl<'a> MaxEvenDpa for Automaton<'a> {
 
         // split up self for correct borrows
         let successors = &mut self.successors;
+        let scores = &mut self.scores;
         let vm = self.vm;
         let automaton = self.automaton;
         let info = self.info;
-        successors[state_index].get_or_insert_with(|| compute_edge_tree(vm, automaton, info, state))
+        successors[state_index].get_or_insert_with(|| {
+            let edge_tree = compute_edge_tree(vm, automaton, info, state);
+            // the score of an edge is the score of its successor
+            for index in edge_tree.index_iter(TreeIndex::ROOT, None) {
+                if let Node::Leaf(edge) = &edge_tree[index] {
+                    scores.entry(edge.successor()).or_insert(edge.label);
+                }
+            }
+            edge_tree
+        })
     }
 
     fn edge_tree(&self, state: StateIndex) -> Option<&EdgeTree<Score>> {
@@ -446,6 +513,10 @@ impl<'a> MaxEvenDpa for Automaton<'a> {
         }
         features_map
     }
+
+    fn state_score(&self, state: StateIndex) -> Option<f64> {
+        self.scores.get(&state).map(|&score| score.into_inner())
+    }
 }
 
 /// Helper function to convert the vector used in the Owl C interface
@@ -6,7 +6,7 @@ use std::fmt;
 use std::os::raw::{c_char, c_int, c_void};
 
 use crate::bindings::*;
-use crate::graal::Vm;
+use crate::graal::{OwlError, Vm};
 
 /// An LTL formula object from Owl.
 pub struct Ltl<'a> {
@@ -24,13 +24,31 @@ impl<'a> Drop for Ltl<'a> {
 
 impl<'a> Ltl<'a> {
     /// Parses the given formula with the list of atomic propositions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the formula can not be parsed, see [`Ltl::try_parse`].
     pub fn parse<S: AsRef<str>>(vm: &'a Vm, formula: &str, propositions: &[S]) -> Self {
-        let formula_c_string = CString::new(formula).unwrap();
+        Self::try_parse(vm, formula, propositions).unwrap()
+    }
+
+    /// Parses the given formula with the list of atomic propositions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the formula or a proposition contains a nul byte, or if
+    /// Owl raises an exception while parsing the formula.
+    pub fn try_parse<S: AsRef<str>>(
+        vm: &'a Vm,
+        formula: &str,
+        propositions: &[S],
+    ) -> Result<Self, OwlError> {
+        let formula_c_string = CString::new(formula).map_err(|e| OwlError::Parse(e.to_string()))?;
 
-        let p_cstring: Vec<_> = propositions
+        let p_cstring = propositions
             .iter()
-            .map(|p| CString::new(p.as_ref()).unwrap())
-            .collect();
+            .map(|p| CString::new(p.as_ref()).map_err(|e| OwlError::Parse(e.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
 
         let p_ptr: Vec<_> = p_cstring
             .iter() // do NOT into_iter()
@@ -45,10 +63,15 @@ impl<'a> Ltl<'a> {
                 c_int::try_from(propositions.len()).unwrap(),
             )
         };
-        Ltl {
+        if formula_ptr.is_null() {
+            return Err(OwlError::Parse(
+                "syntax error or undeclared proposition".to_string(),
+            ));
+        }
+        Ok(Ltl {
             vm,
             formula: formula_ptr,
-        }
+        })
     }
 
     /// Simplifies the formula with the realizability simplifier,
@@ -1,5 +1,6 @@
 //! The GraalVM for interaction with the Owl library.
 
+use std::fmt;
 use std::ptr;
 
 use crate::bindings::*;
@@ -28,13 +29,32 @@ impl Vm {
     ///
     /// Returns an error if the VM could not be intitialized.
     pub fn new() -> Result<Self, String> {
+        Self::create(ptr::null_mut())
+    }
+
+    /// Creates a new instance of the GraalVM with a heap of at most the given size in bytes,
+    /// which is the size of the address space reserved for the isolate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the VM could not be intitialized, e.g. if the heap size
+    /// is too small for the Owl library.
+    pub fn with_heap_size(heap_size: usize) -> Result<Self, String> {
+        // only the first version of the parameters is used, such that the
+        // remaining fields are ignored and may stay zero
+        let mut params: graal_create_isolate_params_t = unsafe { std::mem::zeroed() };
+        params.version = 1;
+        params.reserved_address_space_size = heap_size as _;
+        Self::create(&mut params)
+    }
+
+    fn create(params: *mut graal_create_isolate_params_t) -> Result<Self, String> {
         let mut vm = Self {
             isolate: std::ptr::null_mut(),
             thread: std::ptr::null_mut(),
         };
 
-        let result =
-            unsafe { graal_create_isolate(ptr::null_mut(), &mut vm.isolate, &mut vm.thread) };
+        let result = unsafe { graal_create_isolate(params, &mut vm.isolate, &mut vm.thread) };
         if result == 0 {
             Ok(vm)
         } else {
@@ -42,3 +62,37 @@ impl Vm {
         }
     }
 }
+
+/// An error raised by the Owl library inside of the GraalVM.
+///
+/// An exception thrown by Owl in an entry point is caught by the GraalVM, which then
+/// returns a null handle instead of an object. The exception itself is not available,
+/// so the error only records the operation that failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OwlError {
+    /// The formula could not be parsed, with a description of the problem.
+    Parse(String),
+    /// The automaton could not be constructed for the formula, e.g. because the formula
+    /// is not supported by the translation or the heap of the GraalVM is exhausted.
+    Translation,
+    /// The automaton uses an acceptance condition that is not a parity condition,
+    /// given by its value in Owl.
+    UnsupportedAcceptance(i64),
+}
+
+impl fmt::Display for OwlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(msg) => write!(f, "could not parse formula: {}", msg),
+            Self::Translation => write!(
+                f,
+                "could not translate formula to an automaton, the formula may not be supported or the heap of the GraalVM may be exhausted"
+            ),
+            Self::UnsupportedAcceptance(acc) => {
+                write!(f, "unsupported acceptance condition: {}", acc)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OwlError {}
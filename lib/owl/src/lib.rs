@@ -3,10 +3,14 @@
 //! All entry points to the Owl library first require an instance of the GraalVM in [`graal::Vm`].
 //! Afterwards, LTL formulas can be parsed by [`formula::Ltl`] and automata can be created by [`automaton::Automaton`].
 //!
+//! The automaton construction is part of the default feature `automaton`. Without this feature,
+//! only formulas can be parsed and simplified, which does not require the CUDD library.
+//!
 //! # Examples
 //!
 //! A max-even DPA for the LTL formula "G (r -> F g)" can be created and queried as follows:
 //! ```
+//! # #[cfg(feature = "automaton")] {
 //! # use owl::{graal, formula, automaton};
 //! use automaton::MaxEvenDpa;
 //!
@@ -25,12 +29,15 @@
 //! assert_ne!(edge1.successor(), q0);
 //! assert_eq!(edge0.color() % 2, 0);
 //! assert_eq!(edge1.color() % 2, 1);
+//! # }
 //! ```
 
 #[doc(hidden)]
 mod bindings;
 
+#[cfg(feature = "automaton")]
 pub mod automaton;
 pub mod formula;
 pub mod graal;
+#[cfg(feature = "automaton")]
 pub mod tree;
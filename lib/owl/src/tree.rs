@@ -21,6 +21,19 @@ impl std::fmt::Display for TreeIndex {
 impl TreeIndex {
     /// The index for the root node of any tree.
     pub const ROOT: Self = Self(0);
+    /// An index that does not refer to a node of any tree.
+    pub const NONE: Self = Self(usize::MAX);
+
+    /// Returns the number of this node in its tree.
+    pub const fn index(self) -> usize {
+        self.0
+    }
+
+    /// Returns the index for the node with the given number in its tree,
+    /// which is the inverse of [`Self::index`].
+    pub const fn from_index(index: usize) -> Self {
+        Self(index)
+    }
 }
 
 /// An inner node of a tree.
@@ -0,0 +1,612 @@
+//! Analysis of LTL specifications without synthesis.
+//!
+//! The analysis of the formula only parses and simplifies the formula, and the analysis
+//! of the automaton explores the automaton without constructing or solving a game.
+//! Both are used by the command `strix analyze`.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use owl::automaton::{Color, MaxEvenDpa};
+use owl::formula::AtomicPropositionStatus;
+use owl::tree::{Node as TreeNode, TreeIndex};
+
+use crate::crosscheck::components;
+use crate::ltl::Ltl;
+pub use crate::ltl::ParseLtlError;
+use crate::options::{Simplification, SynthesisOptions};
+use crate::parity::Parity;
+use crate::{shared_vm, with_fairness, SynthesisError, TranslationError};
+
+/// A syntactic fragment of LTL.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Fragment {
+    /// Formulas without temporal operators.
+    Propositional,
+    /// Formulas that only contain the temporal operators `X`, `G`, `W` and `R` in negation
+    /// normal form. This includes formulas whose only temporal operator is `X`.
+    Safety,
+    /// Formulas that only contain the temporal operators `X`, `F`, `U` and `M` in negation
+    /// normal form.
+    CoSafety,
+    /// All other formulas.
+    General,
+}
+
+impl Fragment {
+    fn of(formula: &Ltl) -> Self {
+        if formula.temporal_operators() == 0 {
+            Self::Propositional
+        } else if formula.is_safety(true) {
+            Self::Safety
+        } else if formula.is_cosafety(true) {
+            Self::CoSafety
+        } else {
+            Self::General
+        }
+    }
+}
+
+impl fmt::Display for Fragment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Propositional => "propositional",
+            Self::Safety => "safety",
+            Self::CoSafety => "co-safety",
+            Self::General => "general",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A class of the temporal hierarchy of Manna and Pnueli, as determined syntactically
+/// from the formula.
+///
+/// The class is an upper bound, as a formula may be equivalent to a formula of a lower
+/// class. Formulas that are both safety and co-safety, such as formulas whose only
+/// temporal operator is `X`, are classified as safety formulas.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TemporalClass {
+    /// Properties that are violated by a finite prefix, such as `G (r -> X g)`.
+    Safety,
+    /// Properties that are satisfied by a finite prefix, such as `F g`.
+    CoSafety,
+    /// Boolean combinations of safety and co-safety properties, such as `G a | F b`.
+    Obligation,
+    /// Properties that require something to happen infinitely often, such as
+    /// `G (r -> F g)`, whose games have a Büchi condition.
+    Recurrence,
+    /// Properties that require something to hold eventually forever, such as `F G a`,
+    /// whose games have a co-Büchi condition.
+    Persistence,
+    /// All other properties, i.e. boolean combinations of recurrence and persistence
+    /// properties, such as GR(1) specifications `G F a -> G F b`.
+    Reactivity,
+}
+
+impl TemporalClass {
+    /// Returns the class of the given formula.
+    pub(crate) fn of(formula: &Ltl) -> Self {
+        match formula.hierarchy_levels(true) {
+            (_, 0..=1) => Self::Safety,
+            (0..=1, _) => Self::CoSafety,
+            (2, 2) => Self::Obligation,
+            (_, 2) => Self::Recurrence,
+            (2, _) => Self::Persistence,
+            _ => Self::Reactivity,
+        }
+    }
+}
+
+impl fmt::Display for TemporalClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Safety => "safety",
+            Self::CoSafety => "co-safety",
+            Self::Obligation => "obligation",
+            Self::Recurrence => "recurrence",
+            Self::Persistence => "persistence",
+            Self::Reactivity => "reactivity",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The analysis of a declared atomic proposition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropositionAnalysis {
+    /// The name of the proposition.
+    pub name: String,
+    /// Whether the proposition is an input or an output.
+    pub is_input: bool,
+    /// The status of the proposition after realizability simplification.
+    pub status: AtomicPropositionStatus,
+}
+
+impl fmt::Display for PropositionAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            AtomicPropositionStatus::Used => "used",
+            AtomicPropositionStatus::Unused => "unused",
+            AtomicPropositionStatus::True => "only positive, may be replaced with true",
+            AtomicPropositionStatus::False => "only negative, may be replaced with false",
+        };
+        let kind = if self.is_input { "input" } else { "output" };
+        write!(f, "{} ({}): {}", self.name, kind, status)
+    }
+}
+
+/// Metrics and properties of an LTL formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormulaAnalysis {
+    /// The number of operators, constants and propositions in the formula.
+    pub size: usize,
+    /// The number of temporal operators in the formula.
+    pub temporal_operators: usize,
+    /// The maximal nesting depth of temporal operators.
+    pub temporal_depth: usize,
+    /// The number of top-level conjuncts.
+    pub conjuncts: usize,
+    /// The syntactic fragment of the formula.
+    pub fragment: Fragment,
+    /// The class of the formula in the temporal hierarchy.
+    pub class: TemporalClass,
+    /// The declared inputs and outputs with their status after simplification,
+    /// which are empty for an analysis with [`analyze_syntax`].
+    pub propositions: Vec<PropositionAnalysis>,
+    /// The propositions of the formula that are neither declared as input nor as output.
+    pub undeclared: Vec<String>,
+}
+
+impl fmt::Display for FormulaAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Size: {}", self.size)?;
+        writeln!(f, "Temporal operators: {}", self.temporal_operators)?;
+        writeln!(f, "Temporal depth: {}", self.temporal_depth)?;
+        writeln!(f, "Top-level conjuncts: {}", self.conjuncts)?;
+        writeln!(f, "Fragment: {}", self.fragment)?;
+        writeln!(f, "Temporal class: {}", self.class)?;
+        if !self.propositions.is_empty() {
+            writeln!(f, "Propositions:")?;
+            for proposition in &self.propositions {
+                writeln!(f, "  {}", proposition)?;
+            }
+        }
+        if !self.undeclared.is_empty() {
+            writeln!(f, "Undeclared propositions: {}", self.undeclared.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Analyzes the LTL formula with the given inputs and outputs without synthesizing
+/// a controller.
+///
+/// The statuses of the propositions are obtained from the realizability simplification
+/// of Owl, which only requires the formula parser of the Owl library and no automaton
+/// construction. Undeclared propositions are treated as outputs for the simplification.
+///
+/// # Errors
+///
+/// Returns an error if the formula can not be parsed.
+pub fn analyze_formula<S: AsRef<str>>(
+    ltl: &str,
+    ins: &[S],
+    outs: &[S],
+) -> Result<FormulaAnalysis, ParseLtlError> {
+    let mut analysis = analyze_syntax(ltl, ins, outs)?;
+    let mut ap: Vec<&str> = ins.iter().chain(outs).map(|a| a.as_ref()).collect();
+    let num_declared = ap.len();
+    ap.extend(analysis.undeclared.iter().map(String::as_str));
+
+    let vm = shared_vm(None);
+    let mut owl_formula = owl::formula::Ltl::parse(&vm, ltl, &ap);
+    let statuses = owl_formula.simplify(ins.len(), ap.len() - ins.len());
+    analysis.propositions = ap[..num_declared]
+        .iter()
+        .zip(statuses)
+        .enumerate()
+        .map(|(i, (&name, status))| PropositionAnalysis {
+            name: name.to_owned(),
+            is_input: i < ins.len(),
+            status,
+        })
+        .collect();
+    Ok(analysis)
+}
+
+/// Analyzes the LTL formula with the given inputs and outputs as for [`analyze_formula`],
+/// but only reports the syntactic metrics of the formula and no statuses of the
+/// propositions, such that the GraalVM for Owl is not started.
+///
+/// # Errors
+///
+/// Returns an error if the formula can not be parsed.
+pub fn analyze_syntax<S: AsRef<str>>(
+    ltl: &str,
+    ins: &[S],
+    outs: &[S],
+) -> Result<FormulaAnalysis, ParseLtlError> {
+    let formula: Ltl = ltl.parse()?;
+    let ap: Vec<&str> = ins.iter().chain(outs).map(|a| a.as_ref()).collect();
+    let undeclared = formula
+        .propositions()
+        .into_iter()
+        .filter(|a| !ap.contains(a))
+        .map(str::to_owned)
+        .collect();
+    Ok(FormulaAnalysis {
+        size: formula.size(),
+        temporal_operators: formula.temporal_operators(),
+        temporal_depth: formula.temporal_depth(),
+        conjuncts: formula.conjuncts().len(),
+        fragment: Fragment::of(&formula),
+        class: TemporalClass::of(&formula),
+        propositions: Vec::new(),
+        undeclared,
+    })
+}
+
+/// The acceptance condition to which the parity condition of an automaton degenerates,
+/// judged by the colors of the edges within each strongly connected component.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Acceptance {
+    /// Each component is either accepting or rejecting.
+    Weak,
+    /// Each component accepts if an even color is seen infinitely often, which
+    /// is larger than all odd colors in the component.
+    Buchi,
+    /// Each component rejects if an odd color is seen infinitely often, which
+    /// is larger than all even colors in the component.
+    CoBuchi,
+    /// A parity condition, with the maximal number of alternations between even
+    /// and odd colors within a component.
+    Parity(usize),
+}
+
+impl Acceptance {
+    /// Returns the acceptance of a component from the sorted distinct colors of the
+    /// edges within the component.
+    fn of_colors(colors: &[Color]) -> Self {
+        let mut blocks: Vec<Parity> = colors.iter().map(|&c| Parity::of(c)).collect();
+        blocks.dedup();
+        match blocks.as_slice() {
+            [] | [_] => Self::Weak,
+            [Parity::Odd, Parity::Even] => Self::Buchi,
+            [Parity::Even, Parity::Odd] => Self::CoBuchi,
+            _ => Self::Parity(blocks.len()),
+        }
+    }
+
+    /// Returns the weakest acceptance condition that covers both conditions.
+    fn join(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Weak, a) | (a, Self::Weak) => a,
+            (Self::Buchi, Self::Buchi) => Self::Buchi,
+            (Self::CoBuchi, Self::CoBuchi) => Self::CoBuchi,
+            (Self::Parity(i), Self::Parity(j)) => Self::Parity(i.max(j)),
+            (Self::Parity(i), _) | (_, Self::Parity(i)) => Self::Parity(i.max(2)),
+            // Büchi and co-Büchi components together need three colors
+            _ => Self::Parity(3),
+        }
+    }
+}
+
+impl fmt::Display for Acceptance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Weak => write!(f, "weak"),
+            Self::Buchi => write!(f, "Büchi"),
+            Self::CoBuchi => write!(f, "co-Büchi"),
+            Self::Parity(index) => write!(f, "parity with index {}", index),
+        }
+    }
+}
+
+/// Statistics of the deterministic parity automaton of a specification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutomatonAnalysis {
+    /// The number of explored states, excluding the sink states.
+    pub states: usize,
+    /// Whether all reachable states were explored, or the exploration was bounded.
+    pub complete: bool,
+    /// The number of sink states reached from the explored states.
+    pub sinks: usize,
+    /// The number of edges of the explored states, i.e. of leaves of their edge trees.
+    pub edges: usize,
+    /// The number of colors of the automaton.
+    pub colors: Color,
+    /// The number of edges with each color.
+    pub color_histogram: Vec<usize>,
+    /// The number of strongly connected components of the explored states.
+    pub components: usize,
+    /// The number of components with an edge within the component.
+    pub nontrivial_components: usize,
+    /// The number of states of the largest component.
+    pub largest_component: usize,
+    /// The acceptance condition to which the parity condition degenerates on the
+    /// explored states.
+    pub acceptance: Acceptance,
+}
+
+impl fmt::Display for AutomatonAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let exploration = if self.complete { "complete" } else { "bounded" };
+        writeln!(f, "Automaton states: {} ({})", self.states, exploration)?;
+        writeln!(f, "Sink states: {}", self.sinks)?;
+        writeln!(f, "Edges: {}", self.edges)?;
+        writeln!(f, "Colors: {}", self.colors)?;
+        let histogram: Vec<_> = self
+            .color_histogram
+            .iter()
+            .enumerate()
+            .map(|(color, count)| format!("{}: {}", color, count))
+            .collect();
+        writeln!(f, "Edges per color: {}", histogram.join(", "))?;
+        writeln!(
+            f,
+            "Components: {} (nontrivial: {}, largest: {})",
+            self.components, self.nontrivial_components, self.largest_component
+        )?;
+        writeln!(f, "Acceptance: {}", self.acceptance)
+    }
+}
+
+/// Explores the automaton from its initial state, up to the given number of states.
+fn explore_automaton<A: MaxEvenDpa>(
+    automaton: &mut A,
+    max_states: Option<usize>,
+) -> AutomatonAnalysis {
+    let colors = automaton.num_colors();
+    let mut color_histogram = vec![0; colors];
+    let mut indices = HashMap::new();
+    let mut states = Vec::new();
+    let mut sinks = HashSet::new();
+    let initial_state = automaton.initial_state();
+    if initial_state.is_sink() {
+        sinks.insert(initial_state);
+    } else {
+        indices.insert(initial_state, 0);
+        states.push(initial_state);
+    }
+    // the successors of the explored states, without edges to sinks
+    let mut edges: Vec<Vec<(usize, Color)>> = Vec::new();
+    let mut num_edges = 0;
+    while edges.len() < states.len() && max_states.map_or(true, |max| edges.len() < max) {
+        let tree = automaton.successors(states[edges.len()]);
+        let mut successors = Vec::new();
+        for leaf in tree.index_iter(TreeIndex::ROOT, None) {
+            if let TreeNode::Leaf(edge) = &tree[leaf] {
+                num_edges += 1;
+                color_histogram[edge.color()] += 1;
+                let successor = edge.successor();
+                if successor.is_sink() {
+                    sinks.insert(successor);
+                } else {
+                    let next = indices.len();
+                    let index = *indices.entry(successor).or_insert(next);
+                    if index == states.len() {
+                        states.push(successor);
+                    }
+                    successors.push((index, edge.color()));
+                }
+            }
+        }
+        edges.push(successors);
+    }
+    let explored = edges.len();
+    let complete = explored == states.len();
+    // only edges between explored states are considered for the components
+    for successors in &mut edges {
+        successors.retain(|&(j, _)| j < explored);
+    }
+
+    let component = components(&edges, Color::MAX);
+    let num_components = component.iter().max().map_or(0, |&c| c + 1);
+    let mut sizes = vec![0; num_components];
+    let mut component_colors = vec![Vec::new(); num_components];
+    for (i, successors) in edges.iter().enumerate() {
+        sizes[component[i]] += 1;
+        for &(j, color) in successors {
+            if component[i] == component[j] {
+                component_colors[component[i]].push(color);
+            }
+        }
+    }
+    let mut acceptance = Acceptance::Weak;
+    for colors in &mut component_colors {
+        colors.sort_unstable();
+        colors.dedup();
+        acceptance = acceptance.join(Acceptance::of_colors(colors));
+    }
+    AutomatonAnalysis {
+        states: explored,
+        complete,
+        sinks: sinks.len(),
+        edges: num_edges,
+        colors,
+        color_histogram,
+        components: num_components,
+        nontrivial_components: component_colors.iter().filter(|c| !c.is_empty()).count(),
+        largest_component: sizes.into_iter().max().unwrap_or(0),
+        acceptance,
+    }
+}
+
+/// Constructs the automaton of the LTL formula with the given inputs and outputs as for
+/// synthesis with the given options, and explores it up to the given number of states
+/// without constructing or solving a game.
+///
+/// # Errors
+///
+/// Returns an error if Owl fails to parse the formula or to construct the automaton.
+pub fn analyze_automaton<S: AsRef<str>>(
+    ltl: &str,
+    ins: &[S],
+    outs: &[S],
+    max_states: Option<usize>,
+    options: &SynthesisOptions,
+) -> Result<AutomatonAnalysis, SynthesisError> {
+    let ap: Vec<&str> = ins.iter().chain(outs).map(|a| a.as_ref()).collect();
+    let translation_error = |cause| {
+        SynthesisError::Translation(TranslationError {
+            cause,
+            subformula: None,
+        })
+    };
+    let fair_ltl;
+    let ltl = if options.fair_inputs.is_empty() {
+        ltl
+    } else {
+        fair_ltl = with_fairness(ltl, &options.fair_inputs);
+        fair_ltl.as_str()
+    };
+    let vm = shared_vm(options.vm_heap_size);
+    let mut formula = owl::formula::Ltl::try_parse(&vm, ltl, &ap).map_err(translation_error)?;
+    if options.ltl_simplification == Simplification::Realizability {
+        formula.simplify(ins.len(), outs.len());
+    }
+    let mut automaton = owl::automaton::Automaton::try_of(
+        &vm,
+        &formula,
+        options.ltl_simplification == Simplification::Language,
+        options.lookahead,
+    )
+    .map_err(translation_error)?;
+    Ok(explore_automaton(&mut automaton, max_states))
+}
+
+/// Tests for the analysis of formulas.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the fragments of formulas.
+    #[test]
+    fn test_fragment() {
+        let fragment = |ltl: &str| Fragment::of(&ltl.parse().unwrap());
+        assert_eq!(fragment("a & !b -> c"), Fragment::Propositional);
+        assert_eq!(fragment("G (r -> X g) & a W b"), Fragment::Safety);
+        assert_eq!(fragment("X a"), Fragment::Safety);
+        assert_eq!(fragment("F (a & X b) | a U b"), Fragment::CoSafety);
+        assert_eq!(fragment("!G a"), Fragment::CoSafety);
+        assert_eq!(fragment("G (r -> F g)"), Fragment::General);
+    }
+
+    /// Test the classes of formulas in the temporal hierarchy.
+    #[test]
+    fn test_temporal_class() {
+        let class = |ltl: &str| TemporalClass::of(&ltl.parse().unwrap());
+        assert_eq!(class("a & !b -> X c"), TemporalClass::Safety);
+        assert_eq!(class("G (r -> X g) & a W b"), TemporalClass::Safety);
+        assert_eq!(class("!G a"), TemporalClass::CoSafety);
+        assert_eq!(class("G a | F b"), TemporalClass::Obligation);
+        assert_eq!(class("G (r -> F g)"), TemporalClass::Recurrence);
+        assert_eq!(class("F G a & b U c"), TemporalClass::Persistence);
+        assert_eq!(class("G F a -> G F b"), TemporalClass::Reactivity);
+    }
+
+    /// Test the analysis of a formula with unused and undeclared propositions.
+    #[test]
+    fn test_analyze_formula() {
+        let analysis = analyze_formula(
+            "G (r -> g | X g) & G (c -> F h) & F d",
+            &["r", "c", "u"],
+            &["g", "h"],
+        )
+        .unwrap();
+        assert_eq!(analysis.size, 16);
+        assert_eq!(analysis.temporal_operators, 5);
+        assert_eq!(analysis.temporal_depth, 2);
+        assert_eq!(analysis.conjuncts, 3);
+        assert_eq!(analysis.fragment, Fragment::General);
+        assert_eq!(analysis.class, TemporalClass::Recurrence);
+        assert_eq!(analysis.undeclared, vec!["d"]);
+        let propositions: Vec<_> = analysis
+            .propositions
+            .iter()
+            .map(|p| (p.name.as_str(), p.is_input))
+            .collect();
+        assert_eq!(
+            propositions,
+            vec![
+                ("r", true),
+                ("c", true),
+                ("u", true),
+                ("g", false),
+                ("h", false)
+            ]
+        );
+        for proposition in &analysis.propositions {
+            assert_eq!(
+                proposition.status == AtomicPropositionStatus::Unused,
+                proposition.name == "u"
+            );
+        }
+    }
+
+    /// Test that the syntactic analysis agrees with the full analysis without statuses.
+    #[test]
+    fn test_analyze_syntax() {
+        let ltl = "G (r -> g | X g) & F d";
+        let analysis = analyze_syntax(ltl, &["r"], &["g"]).unwrap();
+        assert!(analysis.propositions.is_empty());
+        assert_eq!(analysis.undeclared, vec!["d"]);
+        assert!(!analysis.to_string().contains("Propositions:"));
+        let full = analyze_formula(ltl, &["r"], &["g"]).unwrap();
+        assert_eq!(
+            FormulaAnalysis {
+                propositions: Vec::new(),
+                ..full
+            },
+            analysis
+        );
+    }
+
+    /// Test the acceptance conditions of components with the given colors.
+    #[test]
+    fn test_acceptance() {
+        assert_eq!(Acceptance::of_colors(&[]), Acceptance::Weak);
+        assert_eq!(Acceptance::of_colors(&[1, 3]), Acceptance::Weak);
+        assert_eq!(Acceptance::of_colors(&[1, 2]), Acceptance::Buchi);
+        assert_eq!(Acceptance::of_colors(&[0, 2, 3]), Acceptance::CoBuchi);
+        assert_eq!(Acceptance::of_colors(&[0, 1, 2]), Acceptance::Parity(3));
+        assert_eq!(Acceptance::Buchi.join(Acceptance::Weak), Acceptance::Buchi);
+        assert_eq!(
+            Acceptance::Buchi.join(Acceptance::CoBuchi),
+            Acceptance::Parity(3)
+        );
+    }
+
+    /// Test the exploration of the automaton of a response formula.
+    #[test]
+    fn test_analyze_automaton() {
+        let options = SynthesisOptions::default();
+        let analysis = analyze_automaton("G (r -> F g)", &["r"], &["g"], None, &options).unwrap();
+        assert!(analysis.complete);
+        assert!(analysis.states >= 1);
+        assert_eq!(analysis.sinks, 0);
+        assert_eq!(
+            analysis.color_histogram.iter().sum::<usize>(),
+            analysis.edges
+        );
+        assert_eq!(analysis.nontrivial_components, 1);
+        assert_eq!(analysis.acceptance, Acceptance::Buchi);
+
+        let analysis =
+            analyze_automaton("G (r -> F g)", &["r"], &["g"], Some(0), &options).unwrap();
+        assert!(!analysis.complete);
+        assert_eq!(analysis.states, 0);
+        assert_eq!(analysis.edges, 0);
+
+        let analysis = analyze_automaton("r -> g", &["r"], &["g"], None, &options).unwrap();
+        assert!(analysis.sinks > 0);
+        assert_eq!(analysis.acceptance, Acceptance::Weak);
+    }
+
+    /// Test that malformed formulas are rejected before calling Owl.
+    #[test]
+    fn test_analyze_malformed() {
+        assert!(analyze_formula::<&str>("G (r ->", &[], &[]).is_err());
+    }
+}
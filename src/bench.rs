@@ -0,0 +1,358 @@
+//! Deterministic benchmarks for the performance-critical parts of the synthesis procedure.
+//!
+//! Each benchmark is split into a setup, which creates the input of the benchmark
+//! from bundled specifications or from deterministic random generation, and a routine
+//! that is measured with [`Benchmark::run`]. This allows to use the benchmarks from
+//! external harnesses, e.g. with [criterion](https://crates.io/crates/criterion):
+//!
+//! ```ignore
+//! let mut benchmark = SolverBenchmark::new(Solver::Fpi, 10000, 0);
+//! c.bench_function(&benchmark.name(), |b| b.iter(|| benchmark.run()));
+//! ```
+//!
+//! Without an external harness, the benchmarks can be measured with [`run_all`]
+//! or with `strix bench`.
+//!
+//! This module is only available with the `bench` feature.
+
+use std::time::{Duration, Instant};
+
+use cudd::{Bdd, Cudd};
+use log::info;
+
+use crate::controller::labelling::{SimpleLabelling, StructuredLabel};
+use crate::controller::machine::{LabelledMachine, LabelledMachineConstructor, Transition};
+use crate::options::{OnTheFlyLimit, Solver, SynthesisOptions};
+use crate::parity::game::generator::{GameGenerator, GeneratorParameters, SplitMix64};
+use crate::parity::game::{Game, LabelledGame, NodeIndex, Player, Region};
+use crate::parity::solver::{FpiSolver, OinkSolver, ParityGameSolver, SiSolver, ZlkSolver};
+use crate::{synthesize_with, Status};
+
+/// A benchmark with a prepared input.
+pub trait Benchmark {
+    /// Returns the name of the benchmark.
+    fn name(&self) -> String;
+
+    /// Runs the measured routine of the benchmark once.
+    ///
+    /// Returns a checksum of the result, e.g. the size of a winning region,
+    /// which is the same for every run of the benchmark.
+    fn run(&mut self) -> usize;
+}
+
+/// A benchmark for solving a random parity game with a parity game solver.
+pub struct SolverBenchmark {
+    solver: Solver,
+    game: LabelledGame<NodeIndex>,
+    disabled: Region,
+    seed: u64,
+}
+
+impl SolverBenchmark {
+    /// Creates a new benchmark for the given solver on a random game with the
+    /// given number of nodes, generated from the given seed.
+    pub fn new(solver: Solver, num_nodes: usize, seed: u64) -> Self {
+        let parameters = GeneratorParameters {
+            num_nodes,
+            max_degree: 3,
+            num_colors: 16,
+        };
+        let game = GameGenerator::new(parameters, seed).generate();
+        let disabled = Region::with_capacity(num_nodes);
+        Self {
+            solver,
+            game,
+            disabled,
+            seed,
+        }
+    }
+
+    fn solve<S: ParityGameSolver>(&self, mut solver: S, compute_strategy: bool) -> usize {
+        let (region, _) = solver.solve(&self.game, &self.disabled, Player::Even, compute_strategy);
+        region.size()
+    }
+}
+
+impl Benchmark for SolverBenchmark {
+    fn name(&self) -> String {
+        format!(
+            "solve-{}-{}-{}",
+            self.solver,
+            self.game.num_nodes(),
+            self.seed
+        )
+    }
+
+    fn run(&mut self) -> usize {
+        match self.solver {
+            Solver::Fpi => self.solve(FpiSolver::new(), true),
+            Solver::Zlk => self.solve(ZlkSolver::new(), false),
+            Solver::Si => self.solve(SiSolver::new(), true),
+            Solver::Oink => self.solve(OinkSolver::new(None), true),
+        }
+    }
+}
+
+/// A bundled specification for benchmarking the exploration.
+#[derive(Debug, Clone)]
+struct Specification {
+    name: String,
+    formula: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+}
+
+impl Specification {
+    /// A full arbiter for the given number of clients, where every request
+    /// needs to be eventually granted and grants are mutually exclusive.
+    fn full_arbiter(clients: usize) -> Self {
+        let inputs: Vec<_> = (0..clients).map(|i| format!("r{}", i)).collect();
+        let outputs: Vec<_> = (0..clients).map(|i| format!("g{}", i)).collect();
+        let mut conjuncts = Vec::new();
+        for i in 0..clients {
+            conjuncts.push(format!("G ({} -> F {})", inputs[i], outputs[i]));
+            for j in (i + 1)..clients {
+                conjuncts.push(format!("G !({} & {})", outputs[i], outputs[j]));
+            }
+        }
+        Self {
+            name: format!("full-arbiter-{}", clients),
+            formula: conjuncts.join(" & "),
+            inputs,
+            outputs,
+        }
+    }
+
+    /// A specification where the output needs to hold infinitely often
+    /// exactly if all of the given number of inputs hold infinitely often.
+    fn generalized_buchi(conjuncts: usize) -> Self {
+        let inputs: Vec<_> = (0..conjuncts).map(|i| format!("p{}", i)).collect();
+        let premise: Vec<_> = inputs.iter().map(|p| format!("(G F {})", p)).collect();
+        Self {
+            name: format!("generalized-buchi-{}", conjuncts),
+            formula: format!("({}) <-> G F acc", premise.join(" & ")),
+            inputs,
+            outputs: vec!["acc".to_string()],
+        }
+    }
+}
+
+/// A benchmark for exploring the automaton of a bundled specification
+/// and solving the resulting game.
+///
+/// The benchmark performs complete exploration without on-the-fly solving
+/// and only determines realizability.
+pub struct ExplorationBenchmark {
+    specification: Specification,
+    options: SynthesisOptions,
+}
+
+impl ExplorationBenchmark {
+    fn new(specification: Specification) -> Self {
+        let options = SynthesisOptions {
+            only_realizability: true,
+            exploration_on_the_fly: OnTheFlyLimit::None,
+            ..SynthesisOptions::default()
+        };
+        Self {
+            specification,
+            options,
+        }
+    }
+
+    /// Creates a new benchmark for a full arbiter with the given number of clients.
+    pub fn full_arbiter(clients: usize) -> Self {
+        Self::new(Specification::full_arbiter(clients))
+    }
+
+    /// Creates a new benchmark for a specification with a conjunction of
+    /// the given number of Büchi conditions.
+    pub fn generalized_buchi(conjuncts: usize) -> Self {
+        Self::new(Specification::generalized_buchi(conjuncts))
+    }
+}
+
+impl Benchmark for ExplorationBenchmark {
+    fn name(&self) -> String {
+        format!("explore-{}", self.specification.name)
+    }
+
+    fn run(&mut self) -> usize {
+        let spec = &self.specification;
+        let ins: Vec<_> = spec.inputs.iter().map(String::as_str).collect();
+        let outs: Vec<_> = spec.outputs.iter().map(String::as_str).collect();
+        let result = synthesize_with(&spec.formula, &ins, &outs, &self.options);
+        (result.status() == Status::Realizable) as usize
+    }
+}
+
+/// Returns the cube over the given number of variables of the given manager,
+/// starting at the given offset, that corresponds to the bits of the given value.
+fn cube(manager: &Cudd, offset: usize, num_vars: usize, value: usize) -> Bdd {
+    let mut bdd = manager.bdd_one();
+    for var in 0..num_vars {
+        if value & (1 << var) == 0 {
+            bdd &= !manager.bdd_var(offset + var);
+        } else {
+            bdd &= manager.bdd_var(offset + var);
+        }
+    }
+    bdd
+}
+
+/// A benchmark for constructing the BDDs of a random deterministic Mealy machine.
+pub struct BddBenchmark {
+    machine: LabelledMachine<StructuredLabel>,
+    seed: u64,
+}
+
+impl BddBenchmark {
+    /// The number of inputs of the machine.
+    const NUM_INPUTS: usize = 3;
+    /// The number of outputs of the machine.
+    const NUM_OUTPUTS: usize = 4;
+
+    /// Creates a new benchmark for a random machine with the given number of states,
+    /// generated from the given seed.
+    ///
+    /// The machine has a transition for every valuation of the inputs in each state,
+    /// and all states are reachable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of states is zero.
+    pub fn new(num_states: usize, seed: u64) -> Self {
+        assert!(num_states > 0, "machine without states");
+        let mut random = SplitMix64::new(seed);
+        let manager = Cudd::with_vars(Self::NUM_INPUTS + Self::NUM_OUTPUTS).unwrap();
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let states: Vec<_> = (0..num_states)
+            .map(|i| constructor.add_state(i).0)
+            .collect();
+        for (i, &state) in states.iter().enumerate() {
+            for input in 0..(1 << Self::NUM_INPUTS) {
+                let mut transition = Transition::new(cube(&manager, 0, Self::NUM_INPUTS, input));
+                let output = random.below(1 << Self::NUM_OUTPUTS);
+                // connect states in a cycle to ensure reachability
+                let successor = if input == 0 {
+                    states[(i + 1) % num_states]
+                } else {
+                    states[random.below(num_states)]
+                };
+                let output_bdd = cube(&manager, Self::NUM_INPUTS, Self::NUM_OUTPUTS, output);
+                transition.add_output(output_bdd, successor);
+                constructor.add_transition(state, transition);
+            }
+        }
+        let inputs = (0..Self::NUM_INPUTS).map(|i| format!("i{}", i)).collect();
+        let outputs = (0..Self::NUM_OUTPUTS).map(|i| format!("o{}", i)).collect();
+        let machine = constructor
+            .into_machine(vec![states[0]], inputs, outputs, true)
+            .with_structured_labels(&mut SimpleLabelling::default());
+        Self { machine, seed }
+    }
+}
+
+impl Benchmark for BddBenchmark {
+    fn name(&self) -> String {
+        format!("bdd-{}-{}", self.machine.num_states(), self.seed)
+    }
+
+    fn run(&mut self) -> usize {
+        self.machine.create_bdds(None, false).num_bdd_vars()
+    }
+}
+
+/// Returns the default set of benchmarks on mid-size inputs.
+pub fn default_benchmarks() -> Vec<Box<dyn Benchmark>> {
+    vec![
+        Box::new(SolverBenchmark::new(Solver::Fpi, 20000, 0)),
+        Box::new(SolverBenchmark::new(Solver::Zlk, 20000, 0)),
+        Box::new(SolverBenchmark::new(Solver::Si, 2000, 0)),
+        Box::new(ExplorationBenchmark::full_arbiter(4)),
+        Box::new(ExplorationBenchmark::generalized_buchi(6)),
+        Box::new(BddBenchmark::new(2000, 0)),
+    ]
+}
+
+/// The measurement of a benchmark.
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    /// The name of the benchmark.
+    pub name: String,
+    /// The minimal time of all runs.
+    pub min: Duration,
+    /// The mean time of all runs.
+    pub mean: Duration,
+    /// The checksum of the result of the benchmark.
+    pub checksum: usize,
+}
+
+/// Measures the given benchmark with the given number of runs.
+///
+/// # Panics
+///
+/// Panics if the number of runs is zero, or if the checksum differs between runs.
+pub fn measure(benchmark: &mut dyn Benchmark, runs: u32) -> Measurement {
+    assert!(runs > 0, "no runs for benchmark");
+    let name = benchmark.name();
+    info!("Running benchmark {}", name);
+    let mut min: Option<Duration> = None;
+    let mut total = Duration::from_secs(0);
+    let mut checksum = None;
+    for _ in 0..runs {
+        let start = Instant::now();
+        let result = benchmark.run();
+        let time = start.elapsed();
+        min = Some(min.map_or(time, |m| m.min(time)));
+        total += time;
+        assert_eq!(
+            *checksum.get_or_insert(result),
+            result,
+            "non-deterministic result for benchmark {}",
+            name
+        );
+    }
+    Measurement {
+        name,
+        min: min.unwrap(),
+        mean: total / runs,
+        checksum: checksum.unwrap(),
+    }
+}
+
+/// Measures all benchmarks of [`default_benchmarks`] with the given number of runs.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`measure`].
+pub fn run_all(runs: u32) -> Vec<Measurement> {
+    default_benchmarks()
+        .iter_mut()
+        .map(|benchmark| measure(benchmark.as_mut(), runs))
+        .collect()
+}
+
+/// Tests for the benchmarks.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that the solver benchmarks agree on the winning region.
+    #[test]
+    fn test_solver_benchmarks() {
+        let checksums: Vec<_> = [Solver::Fpi, Solver::Zlk, Solver::Si]
+            .iter()
+            .map(|&solver| measure(&mut SolverBenchmark::new(solver, 200, 1), 2).checksum)
+            .collect();
+        assert!(checksums.iter().all(|&c| c == checksums[0]));
+    }
+
+    /// Test that the BDD benchmark is deterministic.
+    #[test]
+    fn test_bdd_benchmark() {
+        let measurement = measure(&mut BddBenchmark::new(100, 1), 2);
+        assert_eq!(measurement.name, "bdd-100-1");
+    }
+}
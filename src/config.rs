@@ -0,0 +1,486 @@
+//! Configuration files for the options of the command-line interface for synthesis,
+//! which allow to share and reproduce complex setups of the synthesis procedure.
+//!
+//! A configuration file is written in a subset of the TOML format. Each line is
+//! either empty, a comment starting with `#` or an assignment `key = value`, where
+//! the key is the long name of an option without the leading dashes, in which
+//! underscores may be used instead of dashes. The value is a boolean for flags,
+//! and a string in double quotes, a number or an array of these for options
+//! taking values, e.g.
+//!
+//! ```toml
+//! # shared setup for a family of specifications
+//! ins = ["r1", "r2"]
+//! exploration = "bfs"
+//! minimize = "both"
+//! border-free = true
+//! ```
+//!
+//! Options given on the command line take precedence over the options of the
+//! configuration file, see [`Config::arguments`].
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use clap::{App, Arg, ArgMatches, ArgSettings};
+
+/// The name of the configuration file read from the current directory if no
+/// configuration file is given explicitly.
+pub const DEFAULT_CONFIG_FILE: &str = "strix.toml";
+
+/// The names of the options of the command-line interface that cannot be set
+/// in a configuration file.
+const RESERVED_OPTIONS: [&str; 4] = ["help", "version", "config-file", "print-config"];
+
+/// An error when parsing a configuration file or when matching its keys to the
+/// options of the command-line interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseConfigError {
+    /// The line of the configuration file at which the error occurred, starting at 1.
+    line: usize,
+    /// The description of the error.
+    message: String,
+}
+
+impl ParseConfigError {
+    fn new<S: Into<String>>(line: usize, message: S) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the line of the configuration file at which the error occurred,
+    /// starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for ParseConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in line {}", self.message, self.line)
+    }
+}
+
+impl Error for ParseConfigError {}
+
+/// The value of an entry of a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigValue {
+    /// A boolean for a flag.
+    Flag(bool),
+    /// A single value of an option.
+    Value(String),
+    /// A list of values of an option.
+    Values(Vec<String>),
+}
+
+/// An entry `key = value` of a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    /// The key of the entry with underscores replaced by dashes.
+    pub key: String,
+    /// The value of the entry.
+    pub value: ConfigValue,
+    /// The line of the entry in the configuration file, or 0 if the entry
+    /// was not read from a file.
+    pub line: usize,
+}
+
+/// The entries of a configuration file in the order in which they occur.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    entries: Vec<ConfigEntry>,
+}
+
+fn is_number(value: &str) -> bool {
+    let digits = value.strip_prefix(&['+', '-'][..]).unwrap_or(value);
+    digits.starts_with(|c: char| c.is_ascii_digit())
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '_')
+}
+
+/// Parses a string in double quotes at the start of the given text and returns
+/// the string together with the remaining text.
+fn parse_string(text: &str) -> Result<(String, &str), String> {
+    let mut chars = text.char_indices().skip(1);
+    let mut value = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &text[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\\')) => value.push('\\'),
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, c)) => return Err(format!("unsupported escape sequence '\\{}'", c)),
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+/// Parses a scalar value, i.e. a string, a number or a boolean, at the start of the
+/// given text and returns the value together with the remaining text.
+fn parse_scalar(text: &str) -> Result<(ConfigValue, &str), String> {
+    if text.starts_with('"') {
+        let (value, rest) = parse_string(text)?;
+        return Ok((ConfigValue::Value(value), rest));
+    }
+    let end = text
+        .find(|c: char| c == ',' || c == ']' || c.is_whitespace())
+        .unwrap_or_else(|| text.len());
+    let (token, rest) = text.split_at(end);
+    match token {
+        "true" => Ok((ConfigValue::Flag(true), rest)),
+        "false" => Ok((ConfigValue::Flag(false), rest)),
+        _ if is_number(token) => Ok((ConfigValue::Value(token.replace('_', "")), rest)),
+        _ => Err(format!("invalid value '{}'", token)),
+    }
+}
+
+/// Parses the value of an entry, which is either a scalar or an array of scalars.
+fn parse_value(text: &str) -> Result<ConfigValue, String> {
+    let (value, rest) = if let Some(mut rest) = text.strip_prefix('[') {
+        let mut values = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                break (ConfigValue::Values(values), after);
+            }
+            let (value, after) = parse_scalar(rest)?;
+            values.push(match value {
+                ConfigValue::Flag(flag) => flag.to_string(),
+                ConfigValue::Value(value) => value,
+                ConfigValue::Values(_) => unreachable!(),
+            });
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                return Err("expected ',' or ']' in array".to_string());
+            }
+        }
+    } else {
+        parse_scalar(text)?
+    };
+    let rest = rest.trim_start();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(value)
+    } else {
+        Err(format!("unexpected '{}' after value", rest))
+    }
+}
+
+impl FromStr for Config {
+    type Err = ParseConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Self::default();
+        for (index, line) in s.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                return Err(ParseConfigError::new(
+                    line_number,
+                    "tables are not supported",
+                ));
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| ParseConfigError::new(line_number, "expected 'key = value'"))?;
+            let key = key.trim();
+            if key.is_empty()
+                || !key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(ParseConfigError::new(
+                    line_number,
+                    format!("invalid key '{}'", key),
+                ));
+            }
+            let key = key.replace('_', "-");
+            if config.get(&key).is_some() {
+                return Err(ParseConfigError::new(
+                    line_number,
+                    format!("duplicate key '{}'", key),
+                ));
+            }
+            let value =
+                parse_value(value.trim()).map_err(|e| ParseConfigError::new(line_number, e))?;
+            config.entries.push(ConfigEntry {
+                key,
+                value,
+                line: line_number,
+            });
+        }
+        Ok(config)
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    if is_number(value) {
+        return write!(f, "{}", value);
+    }
+    write!(f, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\t' => write!(f, "\\t")?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            write!(f, "{} = ", entry.key)?;
+            match &entry.value {
+                ConfigValue::Flag(flag) => write!(f, "{}", flag)?,
+                ConfigValue::Value(value) => write_string(f, value)?,
+                ConfigValue::Values(values) => {
+                    write!(f, "[")?;
+                    for (i, value) in values.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write_string(f, value)?;
+                    }
+                    write!(f, "]")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the options of the application that may be set in a configuration file.
+fn config_options<'a, 'help>(app: &'a App<'help>) -> impl Iterator<Item = &'a Arg<'help>> {
+    app.get_arguments()
+        .filter(|arg| arg.get_long().is_some() && !RESERVED_OPTIONS.contains(&arg.get_name()))
+}
+
+/// Returns true if the option is set by the given command-line arguments.
+fn is_given(arg: &Arg<'_>, args: &[String]) -> bool {
+    args.iter()
+        .take_while(|token| token.as_str() != "--")
+        .any(|token| {
+            if let Some(long) = token.strip_prefix("--") {
+                let name = long.split('=').next().unwrap_or(long);
+                arg.get_long() == Some(name)
+            } else if let Some(short) = token.strip_prefix('-') {
+                short.chars().next().is_some() && arg.get_short() == short.chars().next()
+            } else {
+                false
+            }
+        })
+}
+
+impl Config {
+    /// Returns the entries of the configuration.
+    pub fn entries(&self) -> &[ConfigEntry] {
+        &self.entries
+    }
+
+    /// Returns the value of the entry with the given key, where underscores
+    /// and dashes are treated as equal.
+    pub fn get(&self, key: &str) -> Option<&ConfigValue> {
+        let key = key.replace('_', "-");
+        self.entries
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| &entry.value)
+    }
+
+    /// Returns the command-line arguments for the options of the given application
+    /// that are set by this configuration and not by the given command-line
+    /// arguments, such that options on the command line take precedence.
+    ///
+    /// The arguments should be inserted before the arguments given on the command line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key is not the long name of an option of the application,
+    /// or if the value does not match the kind of the option.
+    pub fn arguments(
+        &self,
+        app: &App<'_>,
+        args: &[String],
+    ) -> Result<Vec<String>, ParseConfigError> {
+        let mut arguments = Vec::new();
+        for entry in &self.entries {
+            let arg = config_options(app)
+                .find(|arg| arg.get_long() == Some(entry.key.as_str()))
+                .ok_or_else(|| {
+                    ParseConfigError::new(entry.line, format!("unknown option '{}'", entry.key))
+                })?;
+            let long = arg.get_long().unwrap();
+            let takes_value = arg.is_set(ArgSettings::TakesValue);
+            let multiple = arg.is_set(ArgSettings::MultipleOccurrences);
+            let values = match (&entry.value, takes_value) {
+                (ConfigValue::Flag(flag), false) => {
+                    if *flag {
+                        Vec::new()
+                    } else {
+                        continue;
+                    }
+                }
+                (ConfigValue::Flag(flag), true) => vec![flag.to_string()],
+                (ConfigValue::Value(value), true) => vec![value.clone()],
+                (ConfigValue::Values(values), true) if multiple => values.clone(),
+                (ConfigValue::Values(_), true) => {
+                    return Err(ParseConfigError::new(
+                        entry.line,
+                        format!("option '{}' takes a single value", long),
+                    ))
+                }
+                (_, false) => {
+                    return Err(ParseConfigError::new(
+                        entry.line,
+                        format!("option '{}' is a flag and expects a boolean", long),
+                    ))
+                }
+            };
+            if is_given(arg, args) {
+                continue;
+            }
+            if takes_value {
+                arguments.extend(values.iter().map(|value| format!("--{}={}", long, value)));
+            } else {
+                arguments.push(format!("--{}", long));
+            }
+        }
+        Ok(arguments)
+    }
+
+    /// Returns the effective configuration of the options of the given application
+    /// after parsing, including the default values of options not given.
+    ///
+    /// Options without a value are omitted.
+    pub fn from_matches(app: &App<'_>, matches: &ArgMatches) -> Self {
+        let entries = config_options(app)
+            .filter_map(|arg| {
+                let value = if arg.is_set(ArgSettings::TakesValue) {
+                    let values: Vec<_> = matches
+                        .values_of(arg.get_name())?
+                        .map(str::to_string)
+                        .collect();
+                    if arg.is_set(ArgSettings::MultipleOccurrences) {
+                        ConfigValue::Values(values)
+                    } else {
+                        ConfigValue::Value(values.into_iter().next()?)
+                    }
+                } else {
+                    ConfigValue::Flag(matches.is_present(arg.get_name()))
+                };
+                Some(ConfigEntry {
+                    key: arg.get_long().unwrap().to_string(),
+                    value,
+                    line: 0,
+                })
+            })
+            .collect();
+        Self { entries }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clap::{FromArgMatches, IntoApp};
+
+    use crate::options::{CliOptions, ExplorationStrategy};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// Test that configuration files are parsed and invalid files are rejected.
+    #[test]
+    fn test_parse_config() {
+        let config: Config = "# comment\n\nins = [\"a\", \"b\"]\nborder_free = true # flag\nlookahead = 10\nexploration = \"min\"\n"
+            .parse()
+            .unwrap();
+        assert_eq!(config.entries().len(), 4);
+        assert_eq!(
+            config.get("ins"),
+            Some(&ConfigValue::Values(args(&["a", "b"])))
+        );
+        assert_eq!(config.get("border-free"), Some(&ConfigValue::Flag(true)));
+        assert_eq!(
+            config.get("lookahead"),
+            Some(&ConfigValue::Value("10".to_string()))
+        );
+        assert_eq!(
+            config.get("exploration"),
+            Some(&ConfigValue::Value("min".to_string()))
+        );
+        assert_eq!(config.to_string().parse::<Config>().unwrap(), config);
+
+        assert_eq!("a = b".parse::<Config>().unwrap_err().line(), 1);
+        assert_eq!("\na".parse::<Config>().unwrap_err().line(), 2);
+        assert!("a = \"b".parse::<Config>().is_err());
+        assert!("a = [1, 2".parse::<Config>().is_err());
+        assert!("a = 1\na = 2".parse::<Config>().is_err());
+        assert!("[synthesis]".parse::<Config>().is_err());
+    }
+
+    /// Test that options on the command line take precedence over the configuration.
+    #[test]
+    fn test_config_arguments() {
+        let config: Config = "formula = \"G (r -> F g)\"\nins = [\"r\"]\nouts = [\"g\"]\nexploration = \"min\"\nborder-free = true\n"
+            .parse()
+            .unwrap();
+        let cli = args(&["strix", "--exploration", "dfs", "--outs", "h"]);
+        let mut merged = vec![cli[0].clone()];
+        merged.extend(
+            config
+                .arguments(&CliOptions::into_app(), &cli[1..])
+                .unwrap(),
+        );
+        merged.extend_from_slice(&cli[1..]);
+        let matches = CliOptions::into_app().get_matches_from(&merged);
+        let options = CliOptions::from_arg_matches(&matches);
+        assert_eq!(options.formula.as_deref(), Some("G (r -> F g)"));
+        assert_eq!(options.inputs, args(&["r"]));
+        assert_eq!(options.outputs, args(&["h"]));
+        assert_eq!(
+            options.synthesis_options.exploration_strategy,
+            ExplorationStrategy::Dfs
+        );
+        assert!(options.synthesis_options.exploration_border_free);
+
+        let effective = Config::from_matches(&CliOptions::into_app(), &matches);
+        assert_eq!(
+            effective.get("outs"),
+            Some(&ConfigValue::Values(args(&["h"])))
+        );
+        assert_eq!(effective.get("border-free"), Some(&ConfigValue::Flag(true)));
+        assert_eq!(
+            effective.get("exploration"),
+            Some(&ConfigValue::Value("dfs".to_string()))
+        );
+
+        let unknown: Config = "no-such-option = 1".parse().unwrap();
+        assert!(unknown.arguments(&CliOptions::into_app(), &[]).is_err());
+        let reserved: Config = "config = \"other.toml\"".parse().unwrap();
+        assert!(reserved.arguments(&CliOptions::into_app(), &[]).is_err());
+        let flag: Config = "border-free = \"yes\"".parse().unwrap();
+        assert!(flag.arguments(&CliOptions::into_app(), &[]).is_err());
+    }
+}
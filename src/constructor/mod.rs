@@ -1,19 +1,97 @@
 pub(crate) mod queue;
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::time::{Duration, Instant};
 
 use cudd::{Bdd, Cudd};
+use log::{info, warn};
 use owl::automaton::{Color, MaxEvenDpa, StateIndex};
 use owl::formula::AtomicPropositionStatus;
 use owl::tree::{Node as TreeNode, TreeIndex};
 
-use crate::controller::labelling::AutomatonTreeLabel;
+use crate::controller::labelling::{AutomatonTreeLabel, LabelHandle, LabelTable};
 use crate::controller::machine::{LabelledMachine, LabelledMachineConstructor, Transition};
-use crate::parity::game::{Game, LabelledGame, Node, NodeIndex, Player};
-use crate::parity::solver::Strategy;
-use queue::ExplorationQueue;
+use crate::parity::game::generator::SplitMix64;
+use crate::parity::game::{Game, LabelledGame, Node, NodeIndex, Player, Region};
+use crate::parity::solver::{Abstraction, Strategy};
+use queue::{ExplorationQueue, QueueStats};
+
+/// The maximum number of automaton edges taken in each random simulation of the
+/// automaton for prioritizing the exploration.
+const SIMULATION_LENGTH: usize = 64;
+
+/// A summary of the scores of explored automaton states, as assigned by the automaton.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct ScoreStats {
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl ScoreStats {
+    fn add(&mut self, score: f64) {
+        if self.count == 0 {
+            self.min = score;
+            self.max = score;
+        } else {
+            self.min = self.min.min(score);
+            self.max = self.max.max(score);
+        }
+        self.count += 1;
+        self.sum += score;
+    }
+
+    fn merge(&mut self, other: Self) {
+        if other.count > 0 {
+            if self.count == 0 {
+                *self = other;
+            } else {
+                self.count += other.count;
+                self.sum += other.sum;
+                self.min = self.min.min(other.min);
+                self.max = self.max.max(other.max);
+            }
+        }
+    }
+
+    /// Returns the number of explored states with a score.
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the minimum score, if any state has a score.
+    pub(crate) fn min(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.min)
+    }
+
+    /// Returns the maximum score, if any state has a score.
+    pub(crate) fn max(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.max)
+    }
+
+    /// Returns the mean score, if any state has a score.
+    pub(crate) fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+impl fmt::Display for ScoreStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min(), self.mean(), self.max()) {
+            (Some(min), Some(mean), Some(max)) => write!(
+                f,
+                "scores: {} states, min {:.3}, mean {:.3}, max {:.3}",
+                self.count(),
+                min,
+                mean,
+                max
+            ),
+            _ => write!(f, "scores: none"),
+        }
+    }
+}
 
 #[derive(Debug, Default, Clone)]
 pub(crate) struct ExplorationStats {
@@ -21,15 +99,28 @@ pub(crate) struct ExplorationStats {
     edges: usize,
     nodes: usize,
     time: Duration,
+    queue: QueueStats,
+    pruned: usize,
+    scores: ScoreStats,
 }
 
 impl ExplorationStats {
-    fn new(states: usize, edges: usize, nodes: usize, time: Duration) -> Self {
+    fn new(
+        states: usize,
+        edges: usize,
+        nodes: usize,
+        time: Duration,
+        queue: QueueStats,
+        scores: ScoreStats,
+    ) -> Self {
         Self {
             states,
             edges,
             nodes,
             time,
+            queue,
+            pruned: 0,
+            scores,
         }
     }
 
@@ -48,6 +139,19 @@ impl ExplorationStats {
     pub(crate) fn time(&self) -> Duration {
         self.time
     }
+
+    pub(crate) fn queue(&self) -> QueueStats {
+        self.queue
+    }
+
+    pub(crate) fn pruned(&self) -> usize {
+        self.pruned
+    }
+
+    /// Returns the summary of the scores of the explored automaton states.
+    pub(crate) fn scores(&self) -> ScoreStats {
+        self.scores
+    }
 }
 
 impl std::ops::AddAssign for ExplorationStats {
@@ -56,6 +160,10 @@ impl std::ops::AddAssign for ExplorationStats {
         self.edges += rhs.edges;
         self.nodes += rhs.nodes;
         self.time += rhs.time;
+        self.queue.fair_pops += rhs.queue.fair_pops;
+        self.queue.max_wait = self.queue.max_wait.max(rhs.queue.max_wait);
+        self.pruned += rhs.pruned;
+        self.scores.merge(rhs.scores);
     }
 }
 
@@ -63,11 +171,14 @@ impl fmt::Display for ExplorationStats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "|Q| = {}, |E| = {}, |V| = {}, exploration time: {:.2}",
+            "|Q| = {}, |E| = {}, |V| = {}, exploration time: {:.2}, {}, pruned: {}, {}",
             self.states(),
             self.edges(),
             self.nodes(),
             self.time().as_secs_f32(),
+            self.queue(),
+            self.pruned(),
+            self.scores(),
         )
     }
 }
@@ -85,6 +196,8 @@ pub(crate) struct AutomatonSpecification<A> {
     inputs: Vec<String>,
     outputs: Vec<String>,
     statuses: Vec<AtomicPropositionStatus>,
+    assumption: Option<A>,
+    initial_states: Vec<StateIndex>,
 }
 
 impl<A: MaxEvenDpa> AutomatonSpecification<A>
@@ -102,18 +215,117 @@ where
             inputs: inputs.iter().map(|s| s.as_ref().to_owned()).collect(),
             outputs: outputs.iter().map(|s| s.as_ref().to_owned()).collect(),
             statuses,
+            assumption: None,
+            initial_states: Vec::new(),
+        }
+    }
+
+    /// Sets an automaton for the assumptions on the environment, which is used
+    /// to obtain don't cares for a controller of the specification.
+    pub(crate) fn with_assumption(mut self, assumption: A) -> Self {
+        self.assumption = Some(assumption);
+        self
+    }
+
+    /// Sets the states of the automaton with the given numbers as the possible initial
+    /// states, among which the environment chooses at the initial node of the game.
+    ///
+    /// The states are found by exploring the automaton from its initial state, where
+    /// states that are not reachable are ignored with a warning.
+    pub(crate) fn with_initial_states(mut self, numbers: &[usize]) -> Self {
+        let mut remaining = numbers.to_vec();
+        remaining.sort_unstable();
+        remaining.dedup();
+        let mut found = Vec::new();
+        let initial_state = self.automaton.initial_state();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(initial_state);
+        queue.push_back(initial_state);
+        while let Some(state) = queue.pop_front() {
+            if remaining.is_empty() {
+                break;
+            }
+            if let Some(position) = state
+                .index()
+                .and_then(|number| remaining.binary_search(&number).ok())
+            {
+                remaining.remove(position);
+                found.push(state);
+            }
+            let tree = self.automaton.successors(state);
+            for leaf in tree.index_iter(TreeIndex::ROOT, None) {
+                if let TreeNode::Leaf(edge) = &tree[leaf] {
+                    if visited.insert(edge.successor()) {
+                        queue.push_back(edge.successor());
+                    }
+                }
+            }
+        }
+        for number in remaining {
+            warn!(
+                "Automaton state {} is not reachable from the initial state and is ignored",
+                number
+            );
+        }
+        found.sort();
+        self.initial_states = found;
+        self
+    }
+}
+
+/// A bound on the depth of explored nodes, where the depth of a node is the number
+/// of automaton edges on the path through which it was first reached.
+struct DepthBound<S> {
+    /// The maximum depth of explored nodes, if the exploration is bounded.
+    bound: Option<usize>,
+    /// The depths of the nodes of the game.
+    depths: Vec<usize>,
+    /// The nodes beyond the bound together with their score, which are
+    /// queued once the bound is raised.
+    held: Vec<(NodeIndex, Option<S>)>,
+}
+
+impl<S> DepthBound<S> {
+    fn new() -> Self {
+        Self {
+            bound: None,
+            depths: Vec::new(),
+            held: Vec::new(),
+        }
+    }
+
+    fn depth(&self, node_index: NodeIndex) -> usize {
+        self.depths.get(node_index).copied().unwrap_or(0)
+    }
+
+    fn set_depth(&mut self, node_index: NodeIndex, depth: usize) {
+        if node_index >= self.depths.len() {
+            self.depths.resize(node_index + 1, 0);
         }
+        self.depths[node_index] = depth;
+    }
+
+    fn exceeds(&self, depth: usize) -> bool {
+        self.bound.map_or(false, |bound| depth > bound)
     }
 }
 
-pub(crate) struct GameConstructor<A, Q> {
+pub(crate) struct GameConstructor<A: MaxEvenDpa, Q> {
     automaton: A,
     inputs: Vec<String>,
     outputs: Vec<String>,
     statuses: Vec<AtomicPropositionStatus>,
-    game: LabelledGame<AutomatonTreeLabel>,
+    assumption: Option<A>,
+    labels: LabelTable,
+    game: LabelledGame<LabelHandle>,
     queue: Q,
+    depth_bound: DepthBound<A::EdgeLabel>,
     stats: ExplorationStats,
+    /// The possible initial states of the automaton.
+    initial_states: Vec<StateIndex>,
+    /// The number of times each automaton state was hit by random simulations.
+    hits: HashMap<StateIndex, usize>,
 }
 
 impl<A: MaxEvenDpa, Q: ExplorationQueue<NodeIndex, A::EdgeLabel>> GameConstructor<A, Q>
@@ -125,39 +337,88 @@ where
     const LEAF_OWNER: Player = Self::SYS_OWNER;
 
     pub(crate) fn new(automaton_spec: AutomatonSpecification<A>, mut queue: Q) -> Self {
-        let initial_label =
-            AutomatonTreeLabel::new(automaton_spec.automaton.initial_state(), TreeIndex::ROOT);
+        let mut labels = LabelTable::new();
         let mut game = LabelledGame::default();
-        let (initial_node, _) = game.add_border_node(initial_label);
-        game.set_initial_node(initial_node);
-        queue.push(initial_node);
+        let mut depth_bound = DepthBound::new();
+        if automaton_spec.initial_states.len() > 1 {
+            // the environment chooses the initial state at a node with the neutral color
+            let initial_label = labels.intern(AutomatonTreeLabel::INITIAL_CHOICE);
+            let (initial_node, _) = game.add_border_node(initial_label);
+            game.update_node(initial_node, Self::ENV_OWNER, Color::default());
+            game.set_initial_node(initial_node);
+            for &state in &automaton_spec.initial_states {
+                Self::add_successor(
+                    &mut queue,
+                    &mut game,
+                    &mut depth_bound,
+                    initial_node,
+                    labels.intern(AutomatonTreeLabel::new(state, TreeIndex::ROOT)),
+                    None,
+                    0,
+                );
+            }
+        } else {
+            let initial_state = automaton_spec
+                .initial_states
+                .first()
+                .copied()
+                .unwrap_or_else(|| automaton_spec.automaton.initial_state());
+            let initial_label =
+                labels.intern(AutomatonTreeLabel::new(initial_state, TreeIndex::ROOT));
+            let (initial_node, _) = game.add_border_node(initial_label);
+            game.set_initial_node(initial_node);
+            queue.push(initial_node);
+        }
 
+        let initial_states = if automaton_spec.initial_states.is_empty() {
+            vec![automaton_spec.automaton.initial_state()]
+        } else {
+            automaton_spec.initial_states
+        };
         Self {
             automaton: automaton_spec.automaton,
             inputs: automaton_spec.inputs,
             outputs: automaton_spec.outputs,
             statuses: automaton_spec.statuses,
+            assumption: automaton_spec.assumption,
+            labels,
             game,
             queue,
+            depth_bound,
             stats: ExplorationStats::default(),
+            initial_states,
+            hits: HashMap::new(),
         }
     }
 
     fn add_successor(
         queue: &mut Q,
-        game: &mut LabelledGame<AutomatonTreeLabel>,
+        game: &mut LabelledGame<LabelHandle>,
+        depth_bound: &mut DepthBound<A::EdgeLabel>,
         node_index: NodeIndex,
-        label: AutomatonTreeLabel,
+        label: LabelHandle,
         score_option: Option<A::EdgeLabel>,
+        priority: usize,
     ) {
         let (successor_index, new_node) = game.add_border_node(label);
         game.add_edge(node_index, successor_index);
         if new_node {
-            if let Some(score) = score_option {
+            queue.set_priority(&successor_index, priority);
+            let mut depth = depth_bound.depth(node_index);
+            if score_option.is_some() {
+                depth += 1;
+            }
+            depth_bound.set_depth(successor_index, depth);
+            if depth_bound.exceeds(depth) {
+                depth_bound.held.push((successor_index, score_option));
+            } else if let Some(score) = score_option {
                 queue.push_scored(successor_index, score);
             } else {
                 queue.push(successor_index);
             }
+        } else {
+            // queue filtered nodes again, as they may now be reachable through the new edge
+            queue.restore(&successor_index, score_option);
         }
     }
 
@@ -167,14 +428,18 @@ where
         let mut explored_states = 0;
         let mut explored_edges = 0;
         let mut explored_nodes = 0;
+        let mut explored_scores = ScoreStats::default();
         while let Some(node_index) = self.queue.pop() {
-            let label = self.game[node_index].label();
+            let label = self.labels[*self.game[node_index].label()];
             let state = label.automaton_state();
             let tree_index = label.tree_index();
-            let tree = self.automaton.successors(state);
             if tree_index == TreeIndex::ROOT {
                 explored_states += 1;
+                if let Some(score) = self.automaton.state_score(state) {
+                    explored_scores.add(score);
+                }
             }
+            let tree = self.automaton.successors(state);
             explored_nodes += 1;
 
             // update node information and add successors
@@ -188,13 +453,17 @@ where
                         Self::SYS_OWNER
                     };
                     self.game.update_node(node_index, owner, Color::default());
+                    let priority = self.hits.get(&state).copied().unwrap_or(0);
                     for tree_succ_index in tree.index_iter(tree_index, target_var) {
                         Self::add_successor(
                             &mut self.queue,
                             &mut self.game,
+                            &mut self.depth_bound,
                             node_index,
-                            AutomatonTreeLabel::new(state, tree_succ_index),
+                            self.labels
+                                .intern(AutomatonTreeLabel::new(state, tree_succ_index)),
                             None,
+                            priority,
                         );
                     }
                 }
@@ -203,12 +472,16 @@ where
                     self.game
                         .update_node(node_index, Self::LEAF_OWNER, edge.color());
                     let successor_state = edge.successor();
+                    let priority = self.hits.get(&successor_state).copied().unwrap_or(0);
                     Self::add_successor(
                         &mut self.queue,
                         &mut self.game,
+                        &mut self.depth_bound,
                         node_index,
-                        AutomatonTreeLabel::new(successor_state, TreeIndex::ROOT),
+                        self.labels
+                            .intern(AutomatonTreeLabel::new(successor_state, TreeIndex::ROOT)),
                         Some(edge.label().clone()),
+                        priority,
                     );
                 }
             };
@@ -223,54 +496,188 @@ where
                 break;
             }
         }
+        // queue statistics are cumulative, so only count the new fair pops
+        let mut queue_stats = self.queue.stats();
+        queue_stats.fair_pops -= self.stats.queue.fair_pops;
         let new_stats = ExplorationStats::new(
             explored_states,
             explored_edges,
             explored_nodes,
             start.elapsed(),
+            queue_stats,
+            explored_scores,
         );
         self.stats += new_stats;
     }
+
+    /// Runs the given number of random simulations of the automaton from the initial
+    /// states, and prioritizes the nodes for automaton states by how often they were
+    /// hit, if the queue orders nodes by priority.
+    ///
+    /// In each step of a simulation, the environment chooses a random branch for the
+    /// inputs of the edge tree of the current state, and the system responds with an
+    /// edge with the best color for it, i.e. the highest even or lowest odd color,
+    /// avoiding the rejecting sink and breaking ties at random. A simulation ends after
+    /// [`SIMULATION_LENGTH`] steps or when reaching a sink. The simulations use a fixed
+    /// seed, such that the exploration is deterministic.
+    pub(crate) fn simulate(&mut self, runs: usize) {
+        let split = self.inputs.len();
+        let start = Instant::now();
+        let mut random = SplitMix64::new(0);
+        let preference = |color: Color, successor: StateIndex| {
+            let even = color % 2 == 0;
+            let rank = if even { color } else { Color::MAX - color };
+            (successor != StateIndex::BOTTOM, even, rank)
+        };
+        for _ in 0..runs {
+            let mut state = self.initial_states[random.below(self.initial_states.len())];
+            for _ in 0..SIMULATION_LENGTH {
+                if state.is_sink() {
+                    break;
+                }
+                *self.hits.entry(state).or_insert(0) += 1;
+                let tree = self.automaton.successors(state);
+                let branches: Vec<_> = tree.index_iter(TreeIndex::ROOT, Some(split)).collect();
+                let branch = branches[random.below(branches.len())];
+                let edges: Vec<_> = tree
+                    .index_iter(branch, None)
+                    .filter_map(|index| match &tree[index] {
+                        TreeNode::Leaf(edge) => Some(edge),
+                        TreeNode::Inner(_) => None,
+                    })
+                    .collect();
+                let best = edges
+                    .iter()
+                    .map(|edge| preference(edge.color(), edge.successor()))
+                    .max()
+                    .unwrap();
+                let candidates: Vec<_> = edges
+                    .iter()
+                    .filter(|edge| preference(edge.color(), edge.successor()) == best)
+                    .collect();
+                state = candidates[random.below(candidates.len())].successor();
+            }
+        }
+        info!(
+            "Simulated {} runs of the automaton in {:.2}s, hitting {} states",
+            runs,
+            start.elapsed().as_secs_f32(),
+            self.hits.len()
+        );
+    }
+
+    /// Bounds the exploration to nodes up to the given depth, or removes the bound
+    /// if `None` is given, where the depth of a node is the number of automaton
+    /// edges on the path through which it was first reached.
+    ///
+    /// Nodes beyond the bound stay in the border of the game and are queued once
+    /// the bound is raised or removed.
+    pub(crate) fn set_depth_bound(&mut self, bound: Option<usize>) {
+        self.depth_bound.bound = bound;
+        let held = std::mem::take(&mut self.depth_bound.held);
+        for (node_index, score_option) in held {
+            if self.depth_bound.exceeds(self.depth_bound.depth(node_index)) {
+                self.depth_bound.held.push((node_index, score_option));
+            } else if let Some(score) = score_option {
+                self.queue.push_scored(node_index, score);
+            } else {
+                self.queue.push(node_index);
+            }
+        }
+    }
+
+    /// Filters the nodes from the queue that are only reachable from the initial node
+    /// through nodes in the given region of decided nodes, as exploring them can not
+    /// change the winner of the initial node. Returns the number of filtered nodes.
+    ///
+    /// Filtered nodes keep their score and are queued again if an edge to them is
+    /// added by further exploration.
+    pub(crate) fn prune(&mut self, decided: &Region) -> usize {
+        let reachable = Region::reachable(&self.game, decided);
+        let num_pruned = self.queue.filter(|&node_index| reachable[node_index]);
+        self.stats.pruned += num_pruned;
+        num_pruned
+    }
 }
 
 impl<A: MaxEvenDpa, Q> GameConstructor<A, Q> {
-    pub(crate) fn get_game(&self) -> &LabelledGame<AutomatonTreeLabel> {
+    pub(crate) fn get_game(&self) -> &LabelledGame<LabelHandle> {
         &self.game
     }
 
+    /// Assigns the explored nodes of the game that are not yet assigned to the given
+    /// abstraction, where the nodes for automaton states with the same state formula
+    /// are grouped into one class.
+    pub(crate) fn assign_abstraction(&self, abstraction: &mut Abstraction) {
+        let new_nodes: Vec<_> = self
+            .game
+            .nodes()
+            .filter(|&i| !self.game.border()[i] && !abstraction.is_assigned(i))
+            .collect();
+        let states: Vec<_> = new_nodes
+            .iter()
+            .map(|&i| self.labels[*self.game[i].label()])
+            .filter(|label| label.tree_index() == TreeIndex::ROOT)
+            .map(|label| label.automaton_state())
+            .filter(|&state| state != StateIndex::TOP && state != StateIndex::BOTTOM)
+            .collect();
+        let features = self.automaton.extract_features(states.iter());
+        for i in new_nodes {
+            let label = self.labels[*self.game[i].label()];
+            let key = if label.tree_index() == TreeIndex::ROOT {
+                features
+                    .get(&label.automaton_state())
+                    .map(|state| state.state_formula())
+            } else {
+                None
+            };
+            abstraction.assign(i, key);
+        }
+    }
+
     pub(crate) fn stats(&self) -> &ExplorationStats {
         &self.stats
     }
 
+    /// Returns the explored game with the interned labels of its nodes resolved.
     pub(crate) fn into_game(self) -> LabelledGame<AutomatonTreeLabel> {
-        self.game
+        let labels = self.labels;
+        self.game.map_labels(|&handle| labels[handle])
     }
 
     pub(crate) fn into_mealy_machine(
         self,
         winner: Player,
         strategy: Strategy,
+        propagate_outputs: bool,
+        tie_breaking_seed: Option<u64>,
     ) -> (LabelledMachine<StateIndex>, A) {
-        let machine = MealyConstructor::construct(
+        let mut machine = MealyConstructor::construct(
             &self.automaton,
             self.inputs,
             self.outputs,
             self.statuses,
+            self.labels,
             self.game,
             strategy,
             winner,
+            propagate_outputs,
+            tie_breaking_seed,
         );
+        if let (Player::Even, Some(mut assumption)) = (winner, self.assumption) {
+            machine.add_assumption_dontcares(&mut assumption);
+        }
         (machine, self.automaton)
     }
 }
 
 pub(crate) struct MealyConstructor<'a, A: MaxEvenDpa + 'a> {
-    input_manager: Cudd,
-    output_manager: Cudd,
+    manager: Cudd,
     automaton: &'a A,
     inputs: Vec<String>,
     outputs: Vec<String>,
-    game: LabelledGame<AutomatonTreeLabel>,
+    labels: LabelTable,
+    game: LabelledGame<LabelHandle>,
     strategy: Strategy,
     mealy: bool,
     input_status_bdd: Bdd,
@@ -278,10 +685,39 @@ pub(crate) struct MealyConstructor<'a, A: MaxEvenDpa + 'a> {
 }
 
 impl<'a, A: MaxEvenDpa + 'a> MealyConstructor<'a, A> {
+    /// Returns the label of the node with the given index.
+    fn label(&self, node_index: NodeIndex) -> AutomatonTreeLabel {
+        self.labels[*self.game[node_index].label()]
+    }
+
     fn leaf_successor(&self, node_index: NodeIndex) -> NodeIndex {
         self.game[node_index].successors()[0]
     }
 
+    /// Returns the nodes for the initial states of the machine, which are the nodes for
+    /// all initial states of the automaton the environment may choose for a controller,
+    /// and the node chosen by the strategy for a counter-strategy.
+    fn initial_nodes(&self) -> Vec<NodeIndex> {
+        let initial_node = self.game.initial_node();
+        if !self.label(initial_node).is_initial_choice() {
+            vec![initial_node]
+        } else if self.mealy {
+            self.game[initial_node].successors().to_vec()
+        } else {
+            self.strategy[initial_node].clone()
+        }
+    }
+
+    /// Returns whether the given player has a choice at the given node, i.e. whether
+    /// the node belongs to the player and is not a leaf of an edge tree.
+    fn has_choice(&self, node_index: NodeIndex, player: Player) -> bool {
+        let label = self.label(node_index);
+        let state_index = label.automaton_state();
+        let tree_index = label.tree_index();
+        self.game[node_index].owner() == player
+            && !self.automaton.edge_tree(state_index).unwrap()[tree_index].is_leaf()
+    }
+
     fn successors(
         &'a self,
         node_index_arr: &'a [NodeIndex; 1],
@@ -289,101 +725,222 @@ impl<'a, A: MaxEvenDpa + 'a> MealyConstructor<'a, A> {
         player: Player,
     ) -> &'a [NodeIndex] {
         let node_index = node_index_arr[0];
-        let node = &self.game[node_index];
-        let state_index = node.label().automaton_state();
-        let tree_index = node.label().tree_index();
-        if node.owner() != player
-            || self.automaton.edge_tree(state_index).unwrap()[tree_index].is_leaf()
-        {
+        if !self.has_choice(node_index, player) {
             node_index_arr
         } else if use_strategy {
             &self.strategy[node_index]
         } else {
-            node.successors()
+            self.game[node_index].successors()
         }
     }
 
+    /// Returns whether all edges below the given node of an edge tree lead to the
+    /// accepting sink of the automaton, so that the choice of the system at this
+    /// node does not influence the outcome of the play.
+    fn leads_to_top(&self, node_index: NodeIndex) -> bool {
+        let label = self.label(node_index);
+        let edge_tree = self.automaton.edge_tree(label.automaton_state()).unwrap();
+        edge_tree
+            .index_iter(label.tree_index(), None)
+            .all(|leaf| match &edge_tree[leaf] {
+                TreeNode::Leaf(edge) => edge.successor() == StateIndex::TOP,
+                TreeNode::Inner(_) => unreachable!(),
+            })
+    }
+
     fn get_bdd(&self, source: NodeIndex, target: NodeIndex, input: bool) -> Bdd {
-        let source_node = &self.game[source];
-        let target_node = &self.game[target];
-        let source_state_index = source_node.label().automaton_state();
-        let target_state_index = target_node.label().automaton_state();
+        let source_label = self.label(source);
+        let target_label = self.label(target);
+        let source_state_index = source_label.automaton_state();
+        let target_state_index = target_label.automaton_state();
         assert_eq!(source_state_index, target_state_index);
-        let source_tree_index = source_node.label().tree_index();
-        let target_tree_index = target_node.label().tree_index();
+        let source_tree_index = source_label.tree_index();
+        let target_tree_index = target_label.tree_index();
 
+        // inputs and outputs share the variables of the manager, so no shift is needed
         let edge_tree = self.automaton.edge_tree(source_state_index).unwrap();
         if input {
             edge_tree.bdd_for_paths(
-                &self.input_manager,
+                &self.manager,
                 source_tree_index,
                 target_tree_index,
                 Some(self.inputs.len()),
                 0,
             ) & &self.input_status_bdd
         } else {
-            edge_tree.bdd_for_paths(
-                &self.output_manager,
-                source_tree_index,
-                target_tree_index,
-                None,
-                -(self.inputs.len() as isize),
-            ) & &self.output_status_bdd
+            edge_tree.bdd_for_paths(&self.manager, source_tree_index, target_tree_index, None, 0)
+                & &self.output_status_bdd
+        }
+    }
+
+    /// Restricts the strategy of the system so that different inputs of a state
+    /// share the same output decisions where possible, and returns the number of
+    /// removed choices.
+    ///
+    /// For each state reachable under the strategy, the choices for the inputs are
+    /// grouped by their output and successor state. The group allowed for the most
+    /// inputs without a decision is then greedily selected for all these inputs,
+    /// until each input has a decision. As every play consistent with the strategy
+    /// is winning, this also holds for any restriction of the strategy.
+    ///
+    /// Ties between groups are broken in favor of the first group, or uniformly at random
+    /// with a pseudo-random number generator if a seed is given.
+    fn propagate_outputs(&mut self, tie_breaking_seed: Option<u64>) -> usize {
+        if !self.mealy {
+            return 0;
+        }
+        let mut random = tie_breaking_seed.map(SplitMix64::new);
+        let mut decisions = Vec::new();
+        let mut visited = Region::with_capacity(self.game.num_nodes());
+        let mut queue = VecDeque::new();
+        for initial_node in self.initial_nodes() {
+            visited.insert(initial_node);
+            queue.push_back(initial_node);
+        }
+        while let Some(node_index) = queue.pop_front() {
+            let mut successors = Vec::new();
+            let mut choices = Vec::new();
+            for &input_successor in self.successors(&[node_index], false, Player::Odd) {
+                let output_successors = self.successors(&[input_successor], true, Player::Even);
+                if self.has_choice(input_successor, Player::Even)
+                    && !output_successors.is_empty()
+                    && !self.leads_to_top(input_successor)
+                {
+                    let keyed: Vec<_> = output_successors
+                        .iter()
+                        .map(|&output_successor| {
+                            let output = self.get_bdd(input_successor, output_successor, false);
+                            let successor = self.leaf_successor(output_successor);
+                            ((output, successor), output_successor)
+                        })
+                        .collect();
+                    choices.push((input_successor, keyed));
+                } else {
+                    successors.extend(output_successors.iter().map(|&o| self.leaf_successor(o)));
+                }
+            }
+
+            // greedily select the group of choices shared by the most undecided inputs
+            let mut undecided: Vec<_> = (0..choices.len()).collect();
+            while !undecided.is_empty() {
+                let mut groups: Vec<(&(Bdd, NodeIndex), usize)> = Vec::new();
+                let mut group_index = HashMap::new();
+                for &k in &undecided {
+                    let (_, keyed) = &choices[k];
+                    for (l, (key, _)) in keyed.iter().enumerate() {
+                        // count each group only once per input
+                        if keyed[..l].iter().all(|(other, _)| other != key) {
+                            let index = *group_index.entry(key).or_insert_with(|| {
+                                groups.push((key, 0));
+                                groups.len() - 1
+                            });
+                            groups[index].1 += 1;
+                        }
+                    }
+                }
+                // without a seed, the first group with the maximum count is chosen for a
+                // deterministic result
+                let max_count = groups.iter().map(|&(_, count)| count).max().unwrap();
+                let mut best_groups = groups
+                    .into_iter()
+                    .filter(|&(_, count)| count == max_count)
+                    .map(|(key, _)| key);
+                let best = match &mut random {
+                    Some(random) => {
+                        let best_groups: Vec<_> = best_groups.collect();
+                        best_groups[random.below(best_groups.len())]
+                    }
+                    None => best_groups.next().unwrap(),
+                };
+                undecided.retain(|&k| {
+                    let (input_successor, keyed) = &choices[k];
+                    if keyed.iter().any(|(key, _)| key == best) {
+                        let selected: Vec<_> = keyed
+                            .iter()
+                            .filter(|(key, _)| key == best)
+                            .map(|&(_, output_successor)| output_successor)
+                            .collect();
+                        successors.extend(selected.iter().map(|&o| self.leaf_successor(o)));
+                        decisions.push((*input_successor, keyed.len(), selected));
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+
+            for successor in successors {
+                if !visited[successor] {
+                    visited.insert(successor);
+                    queue.push_back(successor);
+                }
+            }
+        }
+
+        let mut removed = 0;
+        for (input_successor, num_choices, selected) in decisions {
+            removed += num_choices - selected.len();
+            self.strategy[input_successor] = selected;
         }
+        removed
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn construct(
         automaton: &A,
         inputs: Vec<String>,
         outputs: Vec<String>,
         statuses: Vec<AtomicPropositionStatus>,
-        game: LabelledGame<AutomatonTreeLabel>,
+        labels: LabelTable,
+        game: LabelledGame<LabelHandle>,
         strategy: Strategy,
         winner: Player,
+        propagate_outputs: bool,
+        tie_breaking_seed: Option<u64>,
     ) -> LabelledMachine<StateIndex> {
         let mealy = winner == Player::Even;
         let num_inputs = inputs.len();
         let num_outputs = outputs.len();
 
-        let input_manager = Cudd::with_vars(num_inputs).unwrap();
-        let output_manager = Cudd::with_vars(num_outputs).unwrap();
+        // one manager for inputs and outputs with a partitioned variable space
+        let manager = Cudd::with_vars(num_inputs + num_outputs).unwrap();
         // compute status BDDs
-        let mut input_status_bdd = input_manager.bdd_one();
-        let mut output_status_bdd = output_manager.bdd_one();
+        let mut input_status_bdd = manager.bdd_one();
+        let mut output_status_bdd = manager.bdd_one();
         for (var, status) in statuses.into_iter().enumerate() {
-            if !mealy && var < num_inputs {
-                match status {
-                    AtomicPropositionStatus::True => input_status_bdd &= input_manager.bdd_var(var),
-                    AtomicPropositionStatus::False => {
-                        input_status_bdd &= !input_manager.bdd_var(var)
-                    }
-                    _ => (),
-                }
+            let status_bdd = if !mealy && var < num_inputs {
+                &mut input_status_bdd
             } else if mealy && var >= num_inputs {
-                match status {
-                    AtomicPropositionStatus::True => {
-                        output_status_bdd &= output_manager.bdd_var(var - num_inputs)
-                    }
-                    AtomicPropositionStatus::False => {
-                        output_status_bdd &= !output_manager.bdd_var(var - num_inputs)
-                    }
-                    _ => (),
-                }
+                &mut output_status_bdd
+            } else {
+                continue;
+            };
+            match status {
+                AtomicPropositionStatus::True => *status_bdd &= manager.bdd_var(var),
+                AtomicPropositionStatus::False => *status_bdd &= !manager.bdd_var(var),
+                _ => (),
             }
         }
 
-        let constructor = MealyConstructor {
-            input_manager,
-            output_manager,
+        let mut constructor = MealyConstructor {
+            manager,
             automaton,
             inputs,
             outputs,
+            labels,
             game,
             strategy,
             mealy,
             input_status_bdd,
             output_status_bdd,
         };
+        if propagate_outputs {
+            let removed = constructor.propagate_outputs(tie_breaking_seed);
+            info!(
+                "Removed {} choices from the strategy by propagating outputs",
+                removed
+            );
+        }
         constructor.construct_internal()
     }
 
@@ -391,25 +948,56 @@ impl<'a, A: MaxEvenDpa + 'a> MealyConstructor<'a, A> {
         let mut m = LabelledMachineConstructor::new();
 
         let mut queue = VecDeque::new();
-        let initial_node = self.game.initial_node();
-        let initial_label = self.game[initial_node].label().automaton_state();
-        let (initial_state, _) = m.add_state(initial_label);
-        queue.push_back((initial_node, initial_state));
+        let mut initial_states = Vec::new();
+        for initial_node in self.initial_nodes() {
+            let initial_label = self.label(initial_node).automaton_state();
+            let (initial_state, new_state) = m.add_state(initial_label);
+            initial_states.push(initial_state);
+            if new_state {
+                queue.push_back((initial_node, initial_state));
+            }
+        }
 
+        let mut num_dontcares = 0;
         while let Some((node_index, state_index)) = queue.pop_front() {
+            let mut transitions = Vec::new();
             for &input_successor in self.successors(&[node_index], !self.mealy, Player::Odd) {
                 let input = self.get_bdd(node_index, input_successor, true);
-                let mut transition = Transition::new(input);
-                for &output_successor in
-                    self.successors(&[input_successor], self.mealy, Player::Even)
-                {
-                    let output = self.get_bdd(input_successor, output_successor, false);
-                    let successor_index = self.leaf_successor(output_successor);
+                // inputs after which the specification is satisfied for any output
+                // allow any output, while all other inputs are treated as possible in
+                // this state, as the support of the inputs is not tracked per state
+                let dontcare = self.mealy
+                    && self.label(node_index).automaton_state() != StateIndex::TOP
+                    && self.leads_to_top(input_successor);
+                if dontcare {
+                    num_dontcares += 1;
+                }
+                let mut outputs: Vec<_> = self
+                    .successors(&[input_successor], self.mealy, Player::Even)
+                    .iter()
+                    .map(|&output_successor| {
+                        let output = if dontcare {
+                            self.output_status_bdd.clone()
+                        } else {
+                            self.get_bdd(input_successor, output_successor, false)
+                        };
+                        (output, self.leaf_successor(output_successor))
+                    })
+                    .collect();
+                outputs.sort_by(|(o1, _), (o2, _)| o1.canonical_cmp(o2));
+                transitions.push((input, outputs));
+            }
+            // order transitions by their functions, so that the machine and the numbering
+            // of its states do not depend on the order of the edges in the game
+            transitions.sort_by(|(i1, _), (i2, _)| i1.canonical_cmp(i2));
 
-                    let successor_node = &self.game[successor_index];
-                    assert_eq!(successor_node.label().tree_index(), TreeIndex::ROOT);
+            for (input, outputs) in transitions {
+                let mut transition = Transition::new(input);
+                for (output, successor_index) in outputs {
+                    let successor_label = self.label(successor_index);
+                    assert_eq!(successor_label.tree_index(), TreeIndex::ROOT);
                     let (successor_state, new_state) =
-                        m.add_state(successor_node.label().automaton_state());
+                        m.add_state(successor_label.automaton_state());
 
                     transition.add_output(output, successor_state);
 
@@ -420,6 +1008,12 @@ impl<'a, A: MaxEvenDpa + 'a> MealyConstructor<'a, A> {
                 m.add_transition(state_index, transition);
             }
         }
-        m.into_machine(initial_state, self.inputs, self.outputs, self.mealy)
+        if num_dontcares > 0 {
+            info!(
+                "Added don't cares for {} transitions with inputs that satisfy the specification",
+                num_dontcares
+            );
+        }
+        m.into_machine(initial_states, self.inputs, self.outputs, self.mealy)
     }
 }
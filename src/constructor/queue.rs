@@ -1,26 +1,97 @@
-use std::collections::VecDeque;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
 
+use fixedbitset::FixedBitSet;
 use min_max_heap::MinMaxHeap;
 
+/// The number of pops from the scored queue after which the oldest item is popped
+/// instead of the item with the best score, so that no item is starved.
+const FAIRNESS_INTERVAL: usize = 64;
+
+/// Statistics on the fairness of an exploration queue.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct QueueStats {
+    /// The number of items popped by age instead of score to prevent starvation.
+    pub(crate) fair_pops: usize,
+    /// The maximum number of pops an item waited in the queue before being popped.
+    pub(crate) max_wait: usize,
+}
+
+impl fmt::Display for QueueStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "fair pops: {}, max wait: {}",
+            self.fair_pops, self.max_wait
+        )
+    }
+}
+
 pub(crate) trait ExplorationQueue<I, S> {
     fn push_scored(&mut self, item: I, score: S);
     fn push(&mut self, item: I);
     fn pop(&mut self) -> Option<I>;
+
+    /// Filters out all items from the queue for which the predicate returns `false`,
+    /// and returns the number of filtered items.
+    ///
+    /// Filtered items are not popped until they are restored, and keep their score
+    /// in queues that order items by score.
+    fn filter<F: FnMut(&I) -> bool>(&mut self, f: F) -> usize;
+
+    /// Queues the given item again if it was filtered, and returns whether it was filtered.
+    ///
+    /// The item is queued as if it was pushed with the given score, e.g. when it is
+    /// reached through a new edge. If no score is given, queues that order items
+    /// by score use the score the item had before it was filtered.
+    fn restore(&mut self, item: &I, score: Option<S>) -> bool;
+
+    /// Returns the fairness statistics of the queue, which are only
+    /// collected by queues that order items by score.
+    fn stats(&self) -> QueueStats {
+        QueueStats::default()
+    }
+
+    /// Sets the priority of an item before it is pushed, which is only used by
+    /// queues that order items by priority instead of score.
+    fn set_priority(&mut self, _item: &I, _priority: usize) {}
+}
+
+/// Returns a predicate for retaining the items for which `f` returns `true`,
+/// which inserts all other items into the set of filtered items and counts them.
+fn keep_or_filter<'a, I: Ord + Clone, F: FnMut(&I) -> bool + 'a>(
+    mut f: F,
+    filtered: &'a mut BTreeSet<I>,
+    num_filtered: &'a mut usize,
+) -> impl FnMut(&I) -> bool + 'a {
+    move |item| {
+        let keep = f(item);
+        if !keep {
+            filtered.insert(item.clone());
+            *num_filtered += 1;
+        }
+        keep
+    }
 }
 
 pub(crate) struct BfsQueue<I> {
     queue: VecDeque<I>,
+    /// The filtered items.
+    filtered: BTreeSet<I>,
 }
 
-impl<I> BfsQueue<I> {
+impl<I: Ord> BfsQueue<I> {
     pub(crate) fn with_capacity(capacity: usize) -> Self {
         Self {
             queue: VecDeque::with_capacity(capacity),
+            filtered: BTreeSet::new(),
         }
     }
 }
 
-impl<I, S> ExplorationQueue<I, S> for BfsQueue<I> {
+impl<I: Ord + Clone, S> ExplorationQueue<I, S> for BfsQueue<I> {
     fn push_scored(&mut self, item: I, _: S) {
         self.queue.push_back(item);
     }
@@ -32,21 +103,43 @@ impl<I, S> ExplorationQueue<I, S> for BfsQueue<I> {
     fn pop(&mut self) -> Option<I> {
         self.queue.pop_front()
     }
+
+    fn filter<F: FnMut(&I) -> bool>(&mut self, f: F) -> usize {
+        let mut num_filtered = 0;
+        self.queue
+            .retain(keep_or_filter(f, &mut self.filtered, &mut num_filtered));
+        num_filtered
+    }
+
+    fn restore(&mut self, item: &I, score: Option<S>) -> bool {
+        let restored = self.filtered.remove(item);
+        if restored {
+            if score.is_some() {
+                self.queue.push_back(item.clone());
+            } else {
+                self.queue.push_front(item.clone());
+            }
+        }
+        restored
+    }
 }
 
 pub(crate) struct DfsQueue<I> {
     queue: Vec<I>,
+    /// The filtered items.
+    filtered: BTreeSet<I>,
 }
 
-impl<I> DfsQueue<I> {
+impl<I: Ord> DfsQueue<I> {
     pub(crate) fn with_capacity(capacity: usize) -> Self {
         Self {
             queue: Vec::with_capacity(capacity),
+            filtered: BTreeSet::new(),
         }
     }
 }
 
-impl<I, S> ExplorationQueue<I, S> for DfsQueue<I> {
+impl<I: Ord + Clone, S> ExplorationQueue<I, S> for DfsQueue<I> {
     fn push_scored(&mut self, item: I, _: S) {
         self.queue.push(item);
     }
@@ -58,17 +151,116 @@ impl<I, S> ExplorationQueue<I, S> for DfsQueue<I> {
     fn pop(&mut self) -> Option<I> {
         self.queue.pop()
     }
+
+    fn filter<F: FnMut(&I) -> bool>(&mut self, f: F) -> usize {
+        let mut num_filtered = 0;
+        self.queue
+            .retain(keep_or_filter(f, &mut self.filtered, &mut num_filtered));
+        num_filtered
+    }
+
+    fn restore(&mut self, item: &I, _: Option<S>) -> bool {
+        let restored = self.filtered.remove(item);
+        if restored {
+            self.queue.push(item.clone());
+        }
+        restored
+    }
+}
+
+/// A queue for a depth-first search up to a bounded depth, where deeper items
+/// are deferred to a breadth-first queue.
+///
+/// The depth only increases for scored items, i.e. for successors of edges of the
+/// automaton. A deferred item starts a new depth-first search at depth zero once
+/// the depth-first search is exhausted.
+pub(crate) struct BdfsQueue<I> {
+    /// The items of the current depth-first search together with their depths.
+    stack: Vec<(I, usize)>,
+    /// The deferred items in the order they were pushed.
+    deferred: VecDeque<I>,
+    /// The filtered items.
+    filtered: BTreeSet<I>,
+    /// The depth of the item that was popped last.
+    depth: usize,
+    /// The maximum depth of the depth-first search.
+    max_depth: usize,
+}
+
+impl<I: Ord> BdfsQueue<I> {
+    pub(crate) fn with_capacity(capacity: usize, max_depth: usize) -> Self {
+        Self {
+            stack: Vec::with_capacity(capacity),
+            deferred: VecDeque::with_capacity(capacity),
+            filtered: BTreeSet::new(),
+            depth: 0,
+            max_depth,
+        }
+    }
+}
+
+impl<I: Ord + Clone, S> ExplorationQueue<I, S> for BdfsQueue<I> {
+    fn push_scored(&mut self, item: I, _: S) {
+        if self.depth < self.max_depth {
+            self.stack.push((item, self.depth + 1));
+        } else {
+            self.deferred.push_back(item);
+        }
+    }
+
+    fn push(&mut self, item: I) {
+        self.stack.push((item, self.depth));
+    }
+
+    fn pop(&mut self) -> Option<I> {
+        if let Some((item, depth)) = self.stack.pop() {
+            self.depth = depth;
+            Some(item)
+        } else {
+            self.depth = 0;
+            self.deferred.pop_front()
+        }
+    }
+
+    fn filter<F: FnMut(&I) -> bool>(&mut self, f: F) -> usize {
+        let mut num_filtered = 0;
+        let mut keep = keep_or_filter(f, &mut self.filtered, &mut num_filtered);
+        self.stack.retain(|(item, _)| keep(item));
+        self.deferred.retain(keep);
+        num_filtered
+    }
+
+    fn restore(&mut self, item: &I, score: Option<S>) -> bool {
+        let restored = self.filtered.remove(item);
+        if restored {
+            match score {
+                Some(_) if self.depth >= self.max_depth => self.deferred.push_back(item.clone()),
+                Some(_) => self.stack.push((item.clone(), self.depth + 1)),
+                None => self.stack.push((item.clone(), self.depth)),
+            }
+        }
+        restored
+    }
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 struct ScoredItem<I, S> {
     item: I,
     score: S,
+    /// The sequence number of the push of the item.
+    seq: usize,
+    /// The number of pops from the queue before the item was pushed.
+    pushed_at: usize,
 }
 
 impl<I, S> ScoredItem<I, S> {
-    const fn new(item: I, score: S) -> Self {
-        Self { item, score }
+    const fn new(item: I, score: S, seq: usize, pushed_at: usize) -> Self {
+        Self {
+            item,
+            score,
+            seq,
+            pushed_at,
+        }
     }
 }
 
@@ -79,27 +271,89 @@ pub(crate) enum MinMaxMode {
     MinMax,
 }
 
+/// A queue popping scored items by minimum or maximum score.
+///
+/// To prevent starvation of items with a bad score, every [`FAIRNESS_INTERVAL`]-th
+/// pop from the scored items returns the oldest remaining item instead. Items are
+/// removed lazily from the structure they are not popped from.
 pub(crate) struct MinMaxQueue<I, S> {
     direct_queue: Vec<I>,
     scored_queue: MinMaxHeap<ScoredItem<I, S>>,
+    /// The scored items in the order they were pushed.
+    age_queue: VecDeque<ScoredItem<I, ()>>,
+    /// The sequence numbers of the scored items that were already popped.
+    popped: FixedBitSet,
+    /// The filtered items together with their score, if they were scored.
+    filtered: BTreeMap<I, Option<S>>,
+    /// The number of pushes of scored items.
+    num_pushes: usize,
+    /// The number of pops of scored items.
+    num_pops: usize,
     mode: MinMaxMode,
     next_max: bool,
+    stats: QueueStats,
 }
 
-impl<I: Ord, S: Ord> MinMaxQueue<I, S> {
+impl<I: Ord + Clone, S: Ord> MinMaxQueue<I, S> {
     pub(crate) fn with_capacity(capacity: usize, mode: MinMaxMode) -> Self {
         Self {
             direct_queue: Vec::with_capacity(capacity),
             scored_queue: MinMaxHeap::with_capacity(capacity),
+            age_queue: VecDeque::with_capacity(capacity),
+            popped: FixedBitSet::with_capacity(capacity),
+            filtered: BTreeMap::new(),
+            num_pushes: 0,
+            num_pops: 0,
             mode,
             next_max: matches!(mode, MinMaxMode::Max | MinMaxMode::MinMax),
+            stats: QueueStats::default(),
+        }
+    }
+
+    /// Pops the item with the best score that was not yet popped by age.
+    fn pop_by_score(&mut self) -> Option<ScoredItem<I, S>> {
+        loop {
+            let next = if self.next_max {
+                self.scored_queue.pop_max()
+            } else {
+                self.scored_queue.pop_min()
+            }?;
+            if !self.popped.contains(next.seq) {
+                if self.mode == MinMaxMode::MinMax {
+                    self.next_max = !self.next_max;
+                }
+                return Some(next);
+            }
+        }
+    }
+
+    /// Pops the oldest item that was not yet popped by score.
+    fn pop_by_age(&mut self) -> Option<ScoredItem<I, ()>> {
+        while let Some(next) = self.age_queue.pop_front() {
+            if !self.popped.contains(next.seq) {
+                return Some(next);
+            }
+        }
+        None
+    }
+
+    /// Removes items that were already popped by score from the front of the age queue.
+    fn trim_age_queue(&mut self) {
+        while matches!(self.age_queue.front(), Some(next) if self.popped.contains(next.seq)) {
+            self.age_queue.pop_front();
         }
     }
 }
 
-impl<I: Ord, S: Ord> ExplorationQueue<I, S> for MinMaxQueue<I, S> {
+impl<I: Ord + Clone, S: Ord> ExplorationQueue<I, S> for MinMaxQueue<I, S> {
     fn push_scored(&mut self, item: I, score: S) {
-        self.scored_queue.push(ScoredItem::new(item, score))
+        let seq = self.num_pushes;
+        self.num_pushes += 1;
+        self.popped.grow(self.num_pushes);
+        self.age_queue
+            .push_back(ScoredItem::new(item.clone(), (), seq, self.num_pops));
+        self.scored_queue
+            .push(ScoredItem::new(item, score, seq, self.num_pops))
     }
 
     fn push(&mut self, item: I) {
@@ -108,15 +362,294 @@ impl<I: Ord, S: Ord> ExplorationQueue<I, S> for MinMaxQueue<I, S> {
 
     fn pop(&mut self) -> Option<I> {
         self.direct_queue.pop().or_else(|| {
-            let next = if self.next_max {
-                self.scored_queue.pop_max()
+            let (item, seq, pushed_at) = if (self.num_pops + 1) % FAIRNESS_INTERVAL == 0 {
+                let next = self.pop_by_age()?;
+                self.stats.fair_pops += 1;
+                (next.item, next.seq, next.pushed_at)
             } else {
-                self.scored_queue.pop_min()
+                let next = self.pop_by_score()?;
+                (next.item, next.seq, next.pushed_at)
             };
-            if self.mode == MinMaxMode::MinMax {
-                self.next_max = !self.next_max;
-            }
-            next.map(|s| s.item)
+            self.popped.insert(seq);
+            self.trim_age_queue();
+            self.stats.max_wait = self.stats.max_wait.max(self.num_pops - pushed_at);
+            self.num_pops += 1;
+            Some(item)
         })
     }
+
+    fn filter<F: FnMut(&I) -> bool>(&mut self, mut f: F) -> usize {
+        let mut num_filtered = 0;
+        let filtered = &mut self.filtered;
+        self.direct_queue.retain(|item| {
+            let keep = f(item);
+            if !keep {
+                filtered.insert(item.clone(), None);
+                num_filtered += 1;
+            }
+            keep
+        });
+        // rebuild the heap without the filtered items and the items already popped by age,
+        // and mark the filtered items as popped for the age queue
+        let mut items = Vec::new();
+        for next in std::mem::replace(&mut self.scored_queue, MinMaxHeap::new()).into_vec() {
+            if self.popped.contains(next.seq) {
+                continue;
+            }
+            if f(&next.item) {
+                items.push(next);
+            } else {
+                self.popped.insert(next.seq);
+                self.filtered.insert(next.item, Some(next.score));
+                num_filtered += 1;
+            }
+        }
+        self.scored_queue = MinMaxHeap::from(items);
+        self.trim_age_queue();
+        num_filtered
+    }
+
+    fn restore(&mut self, item: &I, score: Option<S>) -> bool {
+        match self.filtered.remove(item) {
+            Some(old_score) => {
+                match score.or(old_score) {
+                    Some(score) => self.push_scored(item.clone(), score),
+                    None => self.direct_queue.push(item.clone()),
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn stats(&self) -> QueueStats {
+        self.stats
+    }
+}
+
+/// A queue popping scored items by maximum priority, where items with the same
+/// priority are popped in the order they were pushed, e.g. for prioritizing nodes
+/// by how often random simulations hit their automaton states.
+///
+/// Items pushed without score are popped first in depth-first order, and items
+/// without priority have priority zero.
+pub(crate) struct PriorityQueue<I> {
+    direct_queue: Vec<I>,
+    /// The scored items with their priority and the reversed sequence number of the push.
+    scored_queue: BinaryHeap<(usize, Reverse<usize>, I)>,
+    /// The priorities of the items.
+    priorities: HashMap<I, usize>,
+    /// The filtered items together with whether they were scored.
+    filtered: BTreeMap<I, bool>,
+    /// The number of pushes of scored items.
+    num_pushes: usize,
+}
+
+impl<I: Ord + Hash> PriorityQueue<I> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            direct_queue: Vec::with_capacity(capacity),
+            scored_queue: BinaryHeap::with_capacity(capacity),
+            priorities: HashMap::with_capacity(capacity),
+            filtered: BTreeMap::new(),
+            num_pushes: 0,
+        }
+    }
+
+    /// Pushes the item with its priority as a scored item.
+    fn push_prioritized(&mut self, item: I) {
+        let priority = self.priorities.get(&item).copied().unwrap_or(0);
+        self.scored_queue
+            .push((priority, Reverse(self.num_pushes), item));
+        self.num_pushes += 1;
+    }
+}
+
+impl<I: Ord + Hash + Clone, S> ExplorationQueue<I, S> for PriorityQueue<I> {
+    fn push_scored(&mut self, item: I, _: S) {
+        self.push_prioritized(item);
+    }
+
+    fn push(&mut self, item: I) {
+        self.direct_queue.push(item);
+    }
+
+    fn pop(&mut self) -> Option<I> {
+        self.direct_queue
+            .pop()
+            .or_else(|| self.scored_queue.pop().map(|(_, _, item)| item))
+    }
+
+    fn filter<F: FnMut(&I) -> bool>(&mut self, mut f: F) -> usize {
+        let mut num_filtered = 0;
+        let filtered = &mut self.filtered;
+        let mut keep = |item: &I, scored| {
+            let keep = f(item);
+            if !keep {
+                filtered.insert(item.clone(), scored);
+                num_filtered += 1;
+            }
+            keep
+        };
+        self.direct_queue.retain(|item| keep(item, false));
+        let items = std::mem::take(&mut self.scored_queue).into_vec();
+        self.scored_queue = items
+            .into_iter()
+            .filter(|(_, _, item)| keep(item, true))
+            .collect();
+        num_filtered
+    }
+
+    fn restore(&mut self, item: &I, score: Option<S>) -> bool {
+        match self.filtered.remove(item) {
+            Some(scored) => {
+                if scored || score.is_some() {
+                    self.push_prioritized(item.clone());
+                } else {
+                    self.direct_queue.push(item.clone());
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn set_priority(&mut self, item: &I, priority: usize) {
+        self.priorities.insert(item.clone(), priority);
+    }
+}
+
+/// Tests for exploration queues.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that an old item is popped by age although newer items are always preferred.
+    #[test]
+    fn test_min_max_queue_fairness() {
+        let mut queue = MinMaxQueue::with_capacity(16, MinMaxMode::Max);
+        queue.push_scored(0, 0);
+        for i in 1..FAIRNESS_INTERVAL {
+            queue.push_scored(i, 0);
+            assert_eq!(queue.pop(), Some(i));
+        }
+        queue.push_scored(FAIRNESS_INTERVAL, 0);
+        assert_eq!(queue.pop(), Some(0));
+        assert_eq!(queue.pop(), Some(FAIRNESS_INTERVAL));
+        assert_eq!(queue.pop(), None);
+        let stats = queue.stats();
+        assert_eq!(stats.fair_pops, 1);
+        assert_eq!(stats.max_wait, FAIRNESS_INTERVAL - 1);
+    }
+
+    /// Test that every item is popped exactly once in all modes.
+    #[test]
+    fn test_min_max_queue_complete() {
+        for &mode in &[MinMaxMode::Min, MinMaxMode::Max, MinMaxMode::MinMax] {
+            let mut queue = MinMaxQueue::with_capacity(16, mode);
+            let mut popped = Vec::new();
+            for i in 0..10 * FAIRNESS_INTERVAL {
+                queue.push_scored(i, i % 7);
+                if i % 3 == 0 {
+                    popped.extend(queue.pop());
+                }
+            }
+            while let Some(item) = queue.pop() {
+                popped.push(item);
+            }
+            popped.sort_unstable();
+            assert_eq!(popped, (0..10 * FAIRNESS_INTERVAL).collect::<Vec<_>>());
+        }
+    }
+
+    /// Test that filtered items are neither popped by score nor by age until restored.
+    #[test]
+    fn test_min_max_queue_filter() {
+        let mut queue = MinMaxQueue::with_capacity(16, MinMaxMode::Min);
+        for i in 0..2 * FAIRNESS_INTERVAL {
+            queue.push_scored(i, 1);
+        }
+        queue.push(2 * FAIRNESS_INTERVAL);
+        assert_eq!(queue.filter(|&i| i % 2 == 0), FAIRNESS_INTERVAL);
+        let mut popped = Vec::new();
+        while let Some(item) = queue.pop() {
+            popped.push(item);
+        }
+        popped.sort_unstable();
+        let expected: Vec<_> = (0..=2 * FAIRNESS_INTERVAL).step_by(2).collect();
+        assert_eq!(popped, expected);
+    }
+
+    /// Test that restored items are scored again unless they were pushed without score.
+    #[test]
+    fn test_min_max_queue_restore() {
+        let mut queue = MinMaxQueue::with_capacity(16, MinMaxMode::Min);
+        queue.push(5);
+        queue.push_scored(0, 2);
+        queue.push_scored(1, 3);
+        queue.push_scored(2, 4);
+        assert_eq!(queue.filter(|&i| i == 1), 3);
+        assert!(!queue.restore(&1, None));
+        assert!(queue.restore(&5, None));
+        assert!(queue.restore(&0, None));
+        assert!(queue.restore(&2, Some(1)));
+        assert!(!queue.restore(&2, None));
+        assert_eq!(queue.pop(), Some(5));
+        assert_eq!(queue.pop(), Some(0));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), None);
+    }
+
+    /// Test that items are popped by priority and then in the order they were pushed,
+    /// also after being filtered and restored.
+    #[test]
+    fn test_priority_queue() {
+        let mut queue = PriorityQueue::with_capacity(16);
+        queue.set_priority(&2, 3);
+        queue.set_priority(&4, 1);
+        queue.set_priority(&5, 3);
+        for i in 1..=5 {
+            queue.push_scored(i, ());
+        }
+        queue.push(0);
+        assert_eq!(queue.pop(), Some(0));
+        assert_eq!(queue.filter(|&i| i != 5), 1);
+        assert_eq!(queue.pop(), Some(2));
+        assert!(queue.restore(&5, None));
+        assert_eq!(queue.pop(), Some(5));
+        assert_eq!(queue.pop(), Some(4));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), None);
+    }
+
+    /// Test that the bounded depth-first search defers deeper items to a breadth-first search.
+    #[test]
+    fn test_bdfs_queue() {
+        let mut queue = BdfsQueue::with_capacity(16, 2);
+        queue.push(0);
+        // depth 0
+        assert_eq!(queue.pop(), Some(0));
+        queue.push_scored(1, ());
+        queue.push_scored(2, ());
+        // depth 1
+        assert_eq!(queue.pop(), Some(2));
+        queue.push_scored(3, ());
+        // depth 2, so successors are deferred
+        assert_eq!(queue.pop(), Some(3));
+        queue.push(4);
+        assert_eq!(queue.pop(), Some(4));
+        queue.push_scored(5, ());
+        queue.push_scored(6, ());
+        assert_eq!(queue.pop(), Some(1));
+        queue.push_scored(7, ());
+        assert_eq!(queue.pop(), Some(7));
+        // restart from deferred items in breadth-first order
+        assert_eq!(queue.pop(), Some(5));
+        queue.push_scored(8, ());
+        assert_eq!(queue.pop(), Some(8));
+        assert_eq!(queue.pop(), Some(6));
+        assert_eq!(queue.pop(), None);
+    }
 }
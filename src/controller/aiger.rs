@@ -1,20 +1,76 @@
+use std::collections::HashMap;
 use std::fmt;
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
+#[cfg(not(feature = "abc-native"))]
+use std::sync::Once;
+use std::time::Instant;
 
+#[cfg(feature = "abc-native")]
 use abc::Abc;
-use aiger::{Aiger, AigerMode};
-use log::{info, trace};
+use aiger::{
+    aiger_lit2var, aiger_sign, aiger_var2lit, Aiger, AigerConstructor, AigerMode, AndCache,
+    Literal, SymbolKind, AIGER_FALSE, AIGER_TRUE,
+};
+#[cfg(not(feature = "abc-native"))]
+use cudd::{Bdd, ReorderingMethod};
+use log::info;
+#[cfg(feature = "abc-native")]
+use log::trace;
+
+#[cfg(not(feature = "abc-native"))]
+use super::bdd::{BddController, ManagerPool};
+use super::exact::{self, MAX_EXACT_VARS};
+use super::interface::{InterfaceReport, SignalReport, SignalUsage};
+use super::lut::LutController;
+use super::machine::MAX_EXPLICIT_INPUTS;
+use super::{check_composition, CompositionError};
+use crate::options::AndCacheCapacity;
+
+/// The maximal number of reachable valuations of the latches of a controller that is
+/// converted to a transition table.
+const MAX_EXPLICIT_STATES: usize = 4096;
+
+impl From<AndCacheCapacity> for AndCache {
+    fn from(capacity: AndCacheCapacity) -> Self {
+        match capacity {
+            AndCacheCapacity::Unbounded => Self::Unbounded,
+            AndCacheCapacity::Bounded(capacity) => Self::Bounded(capacity),
+            AndCacheCapacity::Disabled => Self::Disabled,
+        }
+    }
+}
 
 /// A controller as an and-inverter-graph / aiger circuit.
+#[derive(Clone)]
 pub struct AigerController {
-    aig: Aiger,
+    pub(super) aig: Aiger,
+}
+
+impl fmt::Debug for AigerController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AigerController")
+            .field("inputs", &self.aig.num_inputs())
+            .field("latches", &self.aig.num_latches())
+            .field("outputs", &self.aig.num_outputs())
+            .field("ands", &self.aig.num_ands())
+            .finish()
+    }
 }
 
 impl AigerController {
-    pub(super) fn new(aig: Aiger) -> Self {
+    pub(crate) fn new(aig: Aiger) -> Self {
         Self { aig }
     }
 
+    /// Reads an aiger controller in ASCII or binary format from the given reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during reading or the circuit is malformed.
+    pub fn read<R: Read>(reader: R) -> io::Result<Self> {
+        Aiger::read(reader).map(Self::new)
+    }
+
     /// Writes the aiger controller to the given writer. The controller
     /// is written in binary mode if the binary flag is true, and otherwise
     /// in ASCII mode.
@@ -33,7 +89,26 @@ impl AigerController {
         )
     }
 
-    fn execute_compress_commands(abc: &mut Abc, all_methods: bool) {
+    /// Adds a line of comments to the circuit, which is written after the symbol table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comment contains a new line.
+    pub fn add_comment(&mut self, comment: &str) {
+        self.aig.add_comment(comment);
+    }
+
+    /// Removes the names of all inputs, latches and outputs as well as all comments
+    /// from the circuit.
+    pub fn strip_symbols(&mut self) {
+        self.aig = self.aig.stripped().unwrap();
+    }
+
+    #[cfg(feature = "abc-native")]
+    fn execute_compress_commands(abc: &mut Abc, all_methods: bool, sequential: bool) {
+        if sequential {
+            abc.sequential_sweep(true);
+        }
         abc.balance(false, false);
         abc.resubstitute(8, 1);
         abc.rewrite(false, false);
@@ -65,7 +140,14 @@ impl AigerController {
         }
     }
 
-    pub(crate) fn compress(&mut self, all_methods: bool) {
+    /// Compresses the circuit with ABC until the size does not decrease anymore
+    /// or the given deadline passes.
+    ///
+    /// If `all_methods` is set, newer rewrite methods of ABC are applied in addition
+    /// to the basic methods. If `sequential` is set, latches are additionally removed
+    /// or merged while preserving sequential equivalence.
+    #[cfg(feature = "abc-native")]
+    pub fn compress(&mut self, all_methods: bool, sequential: bool, deadline: Option<Instant>) {
         info!("Compressing aiger circuit of size {}", self.size());
 
         let mut abc = Abc::new().unwrap();
@@ -74,22 +156,915 @@ impl AigerController {
         let mut size = abc.network_size();
         let mut old_size = size + 1;
         while size > 0 && size < old_size {
-            Self::execute_compress_commands(&mut abc, all_methods);
+            Self::execute_compress_commands(&mut abc, all_methods, sequential);
             old_size = size;
             size = abc.network_size();
             trace!("Compression size now at {}", size);
+            if deadline.map_or(false, |d| Instant::now() >= d) {
+                info!("Time budget for compression exhausted");
+                break;
+            }
         }
         let aig = abc.get_aiger();
         self.aig = aig;
         info!("Compressed aiger circuit has size {}", self.size());
     }
 
+    /// Compresses the circuit without the native ABC library, which is not available.
+    ///
+    /// The outputs and next-state functions of the latches are rebuilt from their BDDs
+    /// after variable reordering, and the rebuilt circuit is used if it has fewer and
+    /// gates. If `sequential` is set, latches are first removed or merged while
+    /// preserving sequential equivalence.
+    #[cfg(not(feature = "abc-native"))]
+    pub fn compress(&mut self, _all_methods: bool, sequential: bool, deadline: Option<Instant>) {
+        static UNAVAILABLE: Once = Once::new();
+        UNAVAILABLE.call_once(|| {
+            log::warn!(
+                "The native ABC library is not available, compressing aiger circuits with BDDs"
+            );
+        });
+        info!("Compressing aiger circuit of size {}", self.size());
+        if sequential {
+            self.aig = abc::sweep_latches(&self.aig, true);
+        }
+        if let Some(aig) = self.rebuild_from_bdds(deadline) {
+            if aig.num_ands() < self.aig.num_ands() {
+                self.aig = aig;
+            }
+        }
+        info!("Compressed aiger circuit has size {}", self.size());
+    }
+
+    /// Rebuilds the circuit from the BDDs of its outputs and next-state functions,
+    /// which are reduced by variable reordering.
+    ///
+    /// Returns `None` if the circuit has uninitialized latches or if the deadline passes
+    /// while constructing the BDDs.
+    #[cfg(not(feature = "abc-native"))]
+    fn rebuild_from_bdds(&self, deadline: Option<Instant>) -> Option<Aiger> {
+        let input_lits = self.aig.input_literals();
+        let latches = self.aig.latch_literals();
+        let output_lits = self.aig.output_literals();
+        if latches
+            .iter()
+            .any(|&(_, _, reset)| reset != AIGER_FALSE && reset != AIGER_TRUE)
+        {
+            return None;
+        }
+        let mut pool = ManagerPool::new(
+            input_lits.len() + latches.len(),
+            output_lits.len() + latches.len(),
+            None,
+        );
+        pool.autodyn_enable(ReorderingMethod::Sift);
+        let manager = &pool.managers()[0];
+
+        let num_vars = self.aig.maxvar() as usize + 1;
+        let mut gates = vec![None; num_vars];
+        for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+        let mut bdds = vec![None; num_vars];
+        bdds[0] = Some(manager.bdd_zero());
+        let var_lits = input_lits
+            .iter()
+            .chain(latches.iter().map(|(lit, _, _)| lit));
+        for (var, &lit) in var_lits.enumerate() {
+            bdds[aiger_lit2var(lit) as usize] = Some(manager.bdd_var(var));
+        }
+        let bdd_for = |bdds: &[Option<Bdd>], lit: u32| {
+            let bdd = bdds[aiger_lit2var(lit) as usize].as_ref().unwrap();
+            if aiger_sign(lit) != 0 {
+                !bdd
+            } else {
+                bdd.clone()
+            }
+        };
+        for var in topological_order(&gates) {
+            if deadline.map_or(false, |d| Instant::now() >= d) {
+                info!("Time budget for compression exhausted");
+                return None;
+            }
+            let (rhs0, rhs1) = gates[var].unwrap();
+            bdds[var] = Some(bdd_for(&bdds, rhs0) & bdd_for(&bdds, rhs1));
+        }
+        let output_bdds = output_lits.iter().map(|&lit| bdd_for(&bdds, lit)).collect();
+        let state_bdds = latches
+            .iter()
+            .map(|&(_, next, _)| bdd_for(&bdds, next))
+            .collect();
+
+        let names = |kind: SymbolKind, prefix: &str| -> Vec<String> {
+            self.aig
+                .symbol_names(kind)
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| name.unwrap_or_else(|| format!("{}{}", prefix, i)))
+                .collect()
+        };
+        let mut controller = BddController::new(
+            names(SymbolKind::Input, "i"),
+            names(SymbolKind::Output, "o"),
+            vec![self.reset_values()],
+            state_bdds,
+            output_bdds,
+            pool,
+        );
+        controller.reduce(false);
+        Some(controller.create_aiger(AndCache::default(), false).aig)
+    }
+
+    /// Replaces the circuit by one with a minimum number of and gates found by exact
+    /// synthesis, if the controller has at most `max_vars` inputs and latches.
+    ///
+    /// The truth tables of the outputs and next-state functions of the latches are
+    /// computed by simulation, and a SAT solver searches for the smallest and-inverter
+    /// graph implementing them, keeping the latches of the current circuit.
+    /// Returns `true` if a smaller circuit was found. Circuits with uninitialized
+    /// latches or more than eight inputs and latches are left unchanged, and only
+    /// circuits with at most twelve and gates are searched for ten seconds or until
+    /// the deadline passes.
+    pub fn exact_synthesis(&mut self, max_vars: usize, deadline: Option<Instant>) -> bool {
+        let input_lits = self.aig.input_literals();
+        let latches = self.aig.latch_literals();
+        let output_lits = self.aig.output_literals();
+        let num_vars = input_lits.len() + latches.len();
+        let num_ands = self.aig.num_ands() as usize;
+        if num_vars > max_vars.min(MAX_EXACT_VARS)
+            || num_ands == 0
+            || latches
+                .iter()
+                .any(|&(_, _, reset)| reset != AIGER_FALSE && reset != AIGER_TRUE)
+        {
+            return false;
+        }
+        info!("Exact synthesis of aiger circuit of size {}", self.size());
+
+        let mut gates = vec![None; self.aig.maxvar() as usize + 1];
+        for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+        let var_lits: Vec<_> = input_lits
+            .iter()
+            .copied()
+            .chain(latches.iter().map(|&(lit, _, _)| lit))
+            .collect();
+        let function_lits: Vec<_> = output_lits
+            .iter()
+            .copied()
+            .chain(latches.iter().map(|&(_, next, _)| next))
+            .collect();
+        let mut functions = vec![Vec::with_capacity(1 << num_vars); function_lits.len()];
+        for row in 0..(1 << num_vars) {
+            let mut values = vec![None; gates.len()];
+            values[0] = Some(false);
+            for (i, &lit) in var_lits.iter().enumerate() {
+                values[aiger_lit2var(lit) as usize] = Some((row >> i) & 1 == 1);
+            }
+            for (function, &lit) in functions.iter_mut().zip(&function_lits) {
+                function.push(evaluate(lit, &gates, &mut values));
+            }
+        }
+
+        let circuit = match exact::synthesize(num_vars, &functions, num_ands, deadline) {
+            Some(circuit) => circuit,
+            None => {
+                info!("Exact synthesis found no smaller circuit");
+                return false;
+            }
+        };
+
+        let name = |names: &[Option<String>], i: usize, prefix: &str| {
+            names[i]
+                .clone()
+                .unwrap_or_else(|| format!("{}{}", prefix, i))
+        };
+        let input_names = self.aig.symbol_names(SymbolKind::Input);
+        let latch_names = self.aig.symbol_names(SymbolKind::Latch);
+        let output_names = self.aig.symbol_names(SymbolKind::Output);
+        let mut aig = AigerConstructor::new(input_lits.len(), latches.len()).unwrap();
+        let mut lits = Vec::with_capacity(1 + num_vars + circuit.gates.len());
+        lits.push(Literal::FALSE);
+        for i in 0..input_lits.len() {
+            lits.push(aig.add_input(&name(&input_names, i, "i")));
+        }
+        for i in 0..latches.len() {
+            lits.push(aig.add_latch(&name(&latch_names, i, "l")));
+        }
+        let signal = |lits: &[Literal], signal: exact::Signal| {
+            let lit = lits[signal.node];
+            if signal.negated {
+                !lit
+            } else {
+                lit
+            }
+        };
+        for &(a, b) in &circuit.gates {
+            let lit = aig.add_and(signal(&lits, a), signal(&lits, b));
+            lits.push(lit);
+        }
+        let (outputs, nexts) = circuit.outputs.split_at(output_lits.len());
+        for (i, &output) in outputs.iter().enumerate() {
+            aig.add_output(&name(&output_names, i, "o"), signal(&lits, output));
+        }
+        for ((&next, &(_, _, reset)), &latch) in nexts
+            .iter()
+            .zip(&latches)
+            .zip(&lits[1 + input_lits.len()..])
+        {
+            aig.set_latch_next(latch, signal(&lits, next));
+            aig.set_latch_reset(latch, Literal::from_bool(reset == AIGER_TRUE));
+        }
+        self.aig = aig.into_aiger();
+        info!(
+            "Exact synthesis found aiger circuit of size {}",
+            self.size()
+        );
+        true
+    }
+
+    /// Composes this circuit in parallel with the other circuit.
+    ///
+    /// The composed circuit has the inputs of both circuits, where inputs with the
+    /// same name are shared and unnamed inputs are never shared, followed by the
+    /// latches and the outputs of this circuit and then of the other circuit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if both circuits have an output with the same name, or if
+    /// an output of one circuit is an input of the other circuit.
+    pub fn compose(&self, other: &Self) -> Result<Self, CompositionError> {
+        let named = |aig: &Aiger, kind| -> Vec<String> {
+            aig.symbol_names(kind).into_iter().flatten().collect()
+        };
+        check_composition(
+            &named(&self.aig, SymbolKind::Input),
+            &named(&self.aig, SymbolKind::Output),
+            &named(&other.aig, SymbolKind::Input),
+            &named(&other.aig, SymbolKind::Output),
+        )?;
+
+        let mut aig = Aiger::new().unwrap();
+        // map the input variables of both circuits to consecutive new variables
+        let mut shared_inputs = HashMap::new();
+        let mut input_maps = [HashMap::new(), HashMap::new()];
+        let mut num_inputs = 0;
+        for (aiger, input_map) in [&self.aig, &other.aig].iter().zip(&mut input_maps) {
+            let names = aiger.symbol_names(SymbolKind::Input);
+            for (lit, name) in aiger.input_literals().into_iter().zip(names) {
+                let new_var = match name.as_ref().and_then(|n| shared_inputs.get(n)) {
+                    Some(&var) => var,
+                    None => {
+                        num_inputs += 1;
+                        aig.add_input(aiger_var2lit(num_inputs), name.as_deref());
+                        if let Some(name) = name {
+                            shared_inputs.insert(name, num_inputs);
+                        }
+                        num_inputs
+                    }
+                };
+                input_map.insert(aiger_lit2var(lit), new_var);
+            }
+        }
+        // all other variables are shifted to distinct ranges after the inputs
+        let offsets = [num_inputs, num_inputs + self.aig.maxvar()];
+        let mut outputs = Vec::new();
+        for ((aiger, input_map), &offset) in [&self.aig, &other.aig]
+            .iter()
+            .zip(&input_maps)
+            .zip(&offsets)
+        {
+            let map = |lit: u32| {
+                let var = aiger_lit2var(lit);
+                if var == 0 {
+                    lit
+                } else {
+                    let new_var = input_map.get(&var).copied().unwrap_or(offset + var);
+                    aiger_var2lit(new_var) | aiger_sign(lit)
+                }
+            };
+            let latch_names = aiger.symbol_names(SymbolKind::Latch);
+            for ((lit, next, reset), name) in aiger.latch_literals().into_iter().zip(latch_names) {
+                aig.add_latch(map(lit), map(next), name.as_deref());
+                aig.add_reset(map(lit), map(reset));
+            }
+            for (lhs, rhs0, rhs1) in aiger.and_literals() {
+                aig.add_and(map(lhs), map(rhs0), map(rhs1));
+            }
+            let output_names = aiger.symbol_names(SymbolKind::Output);
+            for (lit, name) in aiger.output_literals().into_iter().zip(output_names) {
+                outputs.push((map(lit), name));
+            }
+        }
+        for (lit, name) in outputs {
+            aig.add_output(lit, name.as_deref());
+        }
+        let composed = Self::new(aig);
+        info!("Composed aiger circuit has size {}", composed.size());
+        Ok(composed)
+    }
+
+    /// Composes this controller in sequence with the given circuit, where each input of
+    /// the circuit with the same name as an output of this controller is driven by that
+    /// output, e.g. for closing the loop between a controller and a safety monitor.
+    ///
+    /// The composed circuit has the inputs of this controller followed by the inputs of
+    /// the circuit that are not driven, where inputs with the same name are shared, then
+    /// the latches of this controller and of the circuit, and only the outputs of the
+    /// circuit.
+    pub fn drive(&self, circuit: &Self) -> Self {
+        let mut aig = Aiger::new().unwrap();
+        let output_names = self.aig.symbol_names(SymbolKind::Output);
+        // map the input variables of both circuits to consecutive new variables,
+        // except for the inputs of the circuit driven by an output of the controller
+        let mut shared_inputs = HashMap::new();
+        let mut num_inputs = 0;
+        let mut input_var = |aig: &mut Aiger, name: Option<String>| match name
+            .as_ref()
+            .and_then(|n| shared_inputs.get(n))
+        {
+            Some(&var) => var,
+            None => {
+                num_inputs += 1;
+                aig.add_input(aiger_var2lit(num_inputs), name.as_deref());
+                if let Some(name) = name {
+                    shared_inputs.insert(name, num_inputs);
+                }
+                num_inputs
+            }
+        };
+        let mut input_maps = [HashMap::new(), HashMap::new()];
+        let names = self.aig.symbol_names(SymbolKind::Input);
+        for (lit, name) in self.aig.input_literals().into_iter().zip(names) {
+            input_maps[0].insert(aiger_lit2var(lit), input_var(&mut aig, name));
+        }
+        let mut driven_inputs = HashMap::new();
+        let names = circuit.aig.symbol_names(SymbolKind::Input);
+        for (lit, name) in circuit.aig.input_literals().into_iter().zip(names) {
+            let driver = name
+                .as_ref()
+                .and_then(|name| output_names.iter().position(|o| o.as_ref() == Some(name)));
+            if let Some(output) = driver {
+                driven_inputs.insert(aiger_lit2var(lit), output);
+            } else {
+                input_maps[1].insert(aiger_lit2var(lit), input_var(&mut aig, name));
+            }
+        }
+        // all other variables are shifted to distinct ranges after the inputs
+        let offsets = [num_inputs, num_inputs + self.aig.maxvar()];
+        let map_with = |input_map: &HashMap<u32, u32>, offset: u32, lit: u32| {
+            let var = aiger_lit2var(lit);
+            if var == 0 {
+                lit
+            } else {
+                let new_var = input_map.get(&var).copied().unwrap_or(offset + var);
+                aiger_var2lit(new_var) | aiger_sign(lit)
+            }
+        };
+        let controller_outputs: Vec<_> = self
+            .aig
+            .output_literals()
+            .into_iter()
+            .map(|lit| map_with(&input_maps[0], offsets[0], lit))
+            .collect();
+        let map_circuit = |lit: u32| match driven_inputs.get(&aiger_lit2var(lit)) {
+            Some(&output) => controller_outputs[output] ^ aiger_sign(lit),
+            None => map_with(&input_maps[1], offsets[1], lit),
+        };
+        let map_controller = |lit: u32| map_with(&input_maps[0], offsets[0], lit);
+
+        let latch_names = self.aig.symbol_names(SymbolKind::Latch);
+        for ((lit, next, reset), name) in self.aig.latch_literals().into_iter().zip(latch_names) {
+            aig.add_latch(map_controller(lit), map_controller(next), name.as_deref());
+            aig.add_reset(map_controller(lit), map_controller(reset));
+        }
+        for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+            aig.add_and(
+                map_controller(lhs),
+                map_controller(rhs0),
+                map_controller(rhs1),
+            );
+        }
+        let latch_names = circuit.aig.symbol_names(SymbolKind::Latch);
+        for ((lit, next, reset), name) in circuit.aig.latch_literals().into_iter().zip(latch_names)
+        {
+            aig.add_latch(map_circuit(lit), map_circuit(next), name.as_deref());
+            aig.add_reset(map_circuit(lit), map_circuit(reset));
+        }
+        for (lhs, rhs0, rhs1) in circuit.aig.and_literals() {
+            aig.add_and(map_circuit(lhs), map_circuit(rhs0), map_circuit(rhs1));
+        }
+        let output_names = circuit.aig.symbol_names(SymbolKind::Output);
+        for (lit, name) in circuit.aig.output_literals().into_iter().zip(output_names) {
+            aig.add_output(map_circuit(lit), name.as_deref());
+        }
+        let driven = Self::new(aig);
+        info!(
+            "Aiger circuit driven by controller has size {}",
+            driven.size()
+        );
+        driven
+    }
+
+    /// Returns for each variable whether an output or the next value of a latch depends
+    /// on the variable.
+    fn cone_of_influence(&self) -> Vec<bool> {
+        let num_vars = self.aig.maxvar() as usize + 1;
+        let mut gates = vec![None; num_vars];
+        for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+        let mut reached = vec![false; num_vars];
+        let mut stack: Vec<_> = self
+            .aig
+            .output_literals()
+            .into_iter()
+            .chain(
+                self.aig
+                    .latch_literals()
+                    .into_iter()
+                    .map(|(_, next, _)| next),
+            )
+            .map(|lit| aiger_lit2var(lit) as usize)
+            .collect();
+        while let Some(var) = stack.pop() {
+            if reached[var] {
+                continue;
+            }
+            reached[var] = true;
+            if let Some((rhs0, rhs1)) = gates[var] {
+                stack.push(aiger_lit2var(rhs0) as usize);
+                stack.push(aiger_lit2var(rhs1) as usize);
+            }
+        }
+        reached
+    }
+
+    /// Returns for each input whether it is read by the controller.
+    fn read_inputs(&self) -> Vec<bool> {
+        let reached = self.cone_of_influence();
+        self.aig
+            .input_literals()
+            .into_iter()
+            .map(|lit| reached[aiger_lit2var(lit) as usize])
+            .collect()
+    }
+
+    /// Returns a report on the inputs and outputs needed by the controller, where inputs
+    /// are needed if an output or latch depends on them, and outputs are needed if they
+    /// are not driven by a constant, together with the mapping of the interface of the
+    /// circuit returned by [`Self::reduce_interface`] to the interface of this circuit.
+    pub fn interface_report(&self) -> InterfaceReport {
+        let mut signals = Vec::new();
+        let mut num_reduced = 0;
+        let names = self.aig.symbol_names(SymbolKind::Input);
+        for (i, (read, name)) in self.read_inputs().into_iter().zip(names).enumerate() {
+            let reduced_index = read.then(|| num_reduced);
+            num_reduced += usize::from(read);
+            signals.push(SignalReport {
+                name: name.unwrap_or_else(|| format!("i{}", i)),
+                is_input: true,
+                status: None,
+                usage: if read {
+                    SignalUsage::Used
+                } else {
+                    SignalUsage::Unread
+                },
+                reduced_index,
+            });
+        }
+        let mut num_reduced = 0;
+        let names = self.aig.symbol_names(SymbolKind::Output);
+        for (i, (lit, name)) in self
+            .aig
+            .output_literals()
+            .into_iter()
+            .zip(names)
+            .enumerate()
+        {
+            let usage = match lit {
+                AIGER_FALSE => SignalUsage::Constant(false),
+                AIGER_TRUE => SignalUsage::Constant(true),
+                _ => SignalUsage::Used,
+            };
+            let used = usage == SignalUsage::Used;
+            signals.push(SignalReport {
+                name: name.unwrap_or_else(|| format!("o{}", i)),
+                is_input: false,
+                status: None,
+                usage,
+                reduced_index: used.then(|| num_reduced),
+            });
+            num_reduced += usize::from(used);
+        }
+        InterfaceReport::new(signals)
+    }
+
+    /// Returns a circuit with the same behavior on the remaining inputs and outputs,
+    /// where the inputs that are not read and the outputs that are driven by a constant
+    /// are removed, see [`Self::interface_report`] for the mapping of the interfaces.
+    /// And gates on which no output or latch depends are removed as well.
+    pub fn reduce_interface(&self) -> Self {
+        let reached = self.cone_of_influence();
+        let reached = |lit: u32| reached[aiger_lit2var(lit) as usize];
+        let mut aig = Aiger::new().unwrap();
+        let names = self.aig.symbol_names(SymbolKind::Input);
+        for (lit, name) in self.aig.input_literals().into_iter().zip(names) {
+            if reached(lit) {
+                aig.add_input(lit, name.as_deref());
+            }
+        }
+        let names = self.aig.symbol_names(SymbolKind::Latch);
+        for ((lit, next, reset), name) in self.aig.latch_literals().into_iter().zip(names) {
+            aig.add_latch(lit, next, name.as_deref());
+            aig.add_reset(lit, reset);
+        }
+        for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+            if reached(lhs) {
+                aig.add_and(lhs, rhs0, rhs1);
+            }
+        }
+        let names = self.aig.symbol_names(SymbolKind::Output);
+        for (lit, name) in self.aig.output_literals().into_iter().zip(names) {
+            if lit != AIGER_FALSE && lit != AIGER_TRUE {
+                aig.add_output(lit, name.as_deref());
+            }
+        }
+        for comment in self.aig.comments() {
+            aig.add_comment(&comment);
+        }
+        let reduced = Self::new(aig);
+        info!(
+            "Reduced interface of aiger circuit from {} to {} inputs and from {} to {} outputs",
+            self.num_inputs(),
+            reduced.num_inputs(),
+            self.num_outputs(),
+            reduced.num_outputs()
+        );
+        reduced
+    }
+
+    /// Maps the circuit to a network of lookup tables with at most `lut_size` inputs
+    /// for FPGAs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lut_size` is not in the range `2..=6`.
+    pub fn map_luts(&self, lut_size: usize) -> LutController {
+        let luts = LutController::new(abc::map_luts(&self.aig, lut_size));
+        info!(
+            "Mapped aiger circuit to {} lookup tables with {} latches and depth {}",
+            luts.num_luts(),
+            luts.num_latches(),
+            luts.depth()
+        );
+        luts
+    }
+
+    /// Simulates the controller on the given sequence of input valuations, and
+    /// returns the sequence of output valuations.
+    ///
+    /// The simulation starts with the reset values of the latches, where
+    /// uninitialized latches start with value false.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a valuation does not match the number of inputs of the controller.
+    pub fn simulate(&self, inputs: &[Vec<bool>]) -> Vec<Vec<bool>> {
+        let mut latch_values = self.reset_values();
+        inputs
+            .iter()
+            .map(|valuation| {
+                let (outputs, next) = self.step(&latch_values, valuation);
+                latch_values = next;
+                outputs
+            })
+            .collect()
+    }
+
+    /// Returns the reset values of the latches, where uninitialized latches
+    /// start with value false.
+    pub(crate) fn reset_values(&self) -> Vec<bool> {
+        self.aig
+            .latch_literals()
+            .into_iter()
+            .map(|(_, _, reset)| reset == AIGER_TRUE)
+            .collect()
+    }
+
+    /// Evaluates the controller for the given values of the latches and the inputs, and
+    /// returns the values of the outputs together with the next values of the latches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a valuation does not match the number of inputs or latches of the
+    /// controller.
+    pub(crate) fn step(&self, latch_values: &[bool], inputs: &[bool]) -> (Vec<bool>, Vec<bool>) {
+        let num_vars = self.aig.maxvar() as usize + 1;
+        let input_lits = self.aig.input_literals();
+        let latches = self.aig.latch_literals();
+        assert_eq!(inputs.len(), input_lits.len(), "invalid input valuation");
+        assert_eq!(latch_values.len(), latches.len(), "invalid latch valuation");
+        let mut gates = vec![None; num_vars];
+        for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+
+        let mut values = vec![None; num_vars];
+        values[0] = Some(false);
+        for (&lit, &value) in input_lits.iter().zip(inputs) {
+            values[aiger_lit2var(lit) as usize] = Some(value);
+        }
+        for (&(lit, _, _), &value) in latches.iter().zip(latch_values) {
+            values[aiger_lit2var(lit) as usize] = Some(value);
+        }
+        let outputs = self
+            .aig
+            .output_literals()
+            .into_iter()
+            .map(|lit| evaluate(lit, &gates, &mut values))
+            .collect();
+        let next = latches
+            .iter()
+            .map(|&(_, next, _)| evaluate(next, &gates, &mut values))
+            .collect();
+        (outputs, next)
+    }
+
+    /// Returns the transition table of the controller for the given inputs and outputs,
+    /// which are matched by name with the inputs and outputs of the controller, in the
+    /// same format as the transition table of a machine.
+    ///
+    /// The states are the valuations of the latches reachable from the reset values,
+    /// where the reset values are state 0. Returns `None` if the inputs or outputs do
+    /// not match, or if there are too many inputs or reachable states.
+    pub(crate) fn explicit_transitions(
+        &self,
+        inputs: &[&str],
+        outputs: &[&str],
+    ) -> Option<Vec<Option<(usize, usize)>>> {
+        let position = |kind, names: &[&str]| -> Option<Vec<usize>> {
+            let symbols = self.aig.symbol_names(kind);
+            if symbols.len() != names.len() {
+                return None;
+            }
+            symbols
+                .iter()
+                .map(|symbol| {
+                    let symbol = symbol.as_deref()?;
+                    names.iter().position(|&name| name == symbol)
+                })
+                .collect()
+        };
+        let input_map = position(SymbolKind::Input, inputs)?;
+        let output_map = position(SymbolKind::Output, outputs)?;
+        let num_inputs = inputs.len();
+        if num_inputs > MAX_EXPLICIT_INPUTS {
+            return None;
+        }
+
+        let mut indices = HashMap::new();
+        let mut states = vec![self.reset_values()];
+        indices.insert(states[0].clone(), 0);
+        let mut table = Vec::new();
+        while table.len() < states.len() << num_inputs {
+            let latches = states[table.len() >> num_inputs].clone();
+            for valuation in 0..1_usize << num_inputs {
+                let values: Vec<_> = input_map.iter().map(|&i| valuation >> i & 1 == 1).collect();
+                let (values, next) = self.step(&latches, &values);
+                let output = output_map
+                    .iter()
+                    .zip(values)
+                    .filter(|&(_, value)| value)
+                    .fold(0, |v, (&o, _)| v | 1 << o);
+                let next_index = states.len();
+                let successor = *indices.entry(next.clone()).or_insert(next_index);
+                if successor == next_index {
+                    states.push(next);
+                }
+                table.push(Some((output, successor)));
+            }
+            if states.len() > MAX_EXPLICIT_STATES {
+                return None;
+            }
+        }
+        Some(table)
+    }
+
+    /// Returns the number of inputs of the controller.
+    pub fn num_inputs(&self) -> usize {
+        self.aig.num_inputs() as usize
+    }
+
+    /// Returns the number of outputs of the controller.
+    pub fn num_outputs(&self) -> usize {
+        self.aig.num_outputs() as usize
+    }
+
+    /// Returns the depth of the controller, i.e. the maximum number of and gates on a path
+    /// from an input or a latch to an output or to the next value of a latch.
+    pub fn depth(&self) -> u32 {
+        let num_vars = self.aig.maxvar() as usize + 1;
+        let mut gates = vec![None; num_vars];
+        for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+        let mut levels: Vec<_> = gates
+            .iter()
+            .map(|gate| if gate.is_none() { Some(0) } else { None })
+            .collect();
+        self.aig
+            .output_literals()
+            .into_iter()
+            .chain(
+                self.aig
+                    .latch_literals()
+                    .into_iter()
+                    .map(|(_, next, _)| next),
+            )
+            .map(|lit| level(lit, &gates, &mut levels))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the latency of the controller, i.e. the maximum number of and gates on a
+    /// combinational path from an input or from a latch to an output.
+    pub fn latency(&self) -> AigerLatency {
+        let num_vars = self.aig.maxvar() as usize + 1;
+        let mut gates = vec![None; num_vars];
+        for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+        let order = topological_order(&gates);
+        let var = |lit: u32| aiger_lit2var(lit) as usize;
+        let input_levels = levels_from(
+            self.aig.input_literals().into_iter().map(var),
+            &gates,
+            &order,
+        );
+        let latch_levels = levels_from(
+            self.aig
+                .latch_literals()
+                .into_iter()
+                .map(|(lit, _, _)| var(lit)),
+            &gates,
+            &order,
+        );
+        let output_vars: Vec<_> = self.aig.output_literals().into_iter().map(var).collect();
+        let max_level = |levels: &[Option<u32>]| {
+            output_vars
+                .iter()
+                .filter_map(|&v| levels[v])
+                .max()
+                .unwrap_or(0)
+        };
+        AigerLatency {
+            input_to_output: max_level(&input_levels),
+            latch_to_output: max_level(&latch_levels),
+        }
+    }
+
     pub(crate) fn size(&self) -> AigerSize {
+        let latency = self.latency();
         AigerSize {
             num_ands: self.aig.num_ands() as u32,
             num_latches: self.aig.num_latches() as u32,
+            num_inputs: self.aig.num_inputs() as u32,
+            depth: self.depth(),
+            io_delay: latency.input_to_output,
+            latch_delay: latency.latch_to_output,
+        }
+    }
+}
+
+/// The latency of an aiger circuit, as the maximum number of and gates on combinational
+/// paths ending in an output, where a metric is zero if no output depends on the sources.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct AigerLatency {
+    /// The maximum number of and gates on a path from an input to an output.
+    pub input_to_output: u32,
+    /// The maximum number of and gates on a path from a latch to an output.
+    pub latch_to_output: u32,
+}
+
+impl fmt::Display for AigerLatency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(input to output: {}, latch to output: {})",
+            self.input_to_output, self.latch_to_output
+        )
+    }
+}
+
+/// Returns the variables of all and gates in topological order, such that the inputs
+/// of each and gate occur before the gate.
+pub(super) fn topological_order(gates: &[Option<(u32, u32)>]) -> Vec<usize> {
+    let var = |lit: u32| aiger_lit2var(lit) as usize;
+    let mut visited = vec![false; gates.len()];
+    let mut order = Vec::new();
+    let mut stack = Vec::new();
+    for root in (0..gates.len()).filter(|&v| gates[v].is_some()) {
+        stack.push((root, false));
+        while let Some((top, expanded)) = stack.pop() {
+            if expanded {
+                order.push(top);
+                continue;
+            }
+            if visited[top] {
+                continue;
+            }
+            visited[top] = true;
+            stack.push((top, true));
+            let (rhs0, rhs1) = gates[top].unwrap();
+            for &v in &[var(rhs0), var(rhs1)] {
+                if gates[v].is_some() && !visited[v] {
+                    stack.push((v, false));
+                }
+            }
         }
     }
+    order
+}
+
+/// Returns for each variable the maximum number of and gates on a path from one of the
+/// given source variables to the variable, or `None` if it does not depend on a source.
+fn levels_from<I: IntoIterator<Item = usize>>(
+    sources: I,
+    gates: &[Option<(u32, u32)>],
+    order: &[usize],
+) -> Vec<Option<u32>> {
+    let mut levels = vec![None; gates.len()];
+    for source in sources {
+        levels[source] = Some(0);
+    }
+    for &v in order {
+        let (rhs0, rhs1) = gates[v].unwrap();
+        let l0 = levels[aiger_lit2var(rhs0) as usize];
+        let l1 = levels[aiger_lit2var(rhs1) as usize];
+        levels[v] = l0.max(l1).map(|l| l + 1);
+    }
+    levels
+}
+
+/// Returns the level of the given literal, i.e. the maximum number of and gates on a path
+/// from an input or a latch to the literal, where the levels of and gates are computed on
+/// demand from the levels of their inputs.
+fn level(lit: u32, gates: &[Option<(u32, u32)>], levels: &mut [Option<u32>]) -> u32 {
+    let var = |lit: u32| aiger_lit2var(lit) as usize;
+    let mut stack = vec![var(lit)];
+    while let Some(&top) = stack.last() {
+        if levels[top].is_some() {
+            stack.pop();
+            continue;
+        }
+        let (rhs0, rhs1) = gates[top].expect("undefined literal");
+        match (levels[var(rhs0)], levels[var(rhs1)]) {
+            (Some(l0), Some(l1)) => {
+                levels[top] = Some(1 + l0.max(l1));
+                stack.pop();
+            }
+            (l0, l1) => {
+                assert!(stack.len() <= 2 * gates.len(), "cyclic and gates");
+                stack.extend(l0.is_none().then(|| var(rhs0)));
+                stack.extend(l1.is_none().then(|| var(rhs1)));
+            }
+        }
+    }
+    levels[var(lit)].unwrap()
+}
+
+/// Evaluates the given literal with the given values of variables, where the values
+/// of and gates are computed on demand from the values of their inputs.
+pub(crate) fn evaluate(
+    lit: u32,
+    gates: &[Option<(u32, u32)>],
+    values: &mut [Option<bool>],
+) -> bool {
+    let value = |values: &[Option<bool>], lit: u32| {
+        values[aiger_lit2var(lit) as usize].map(|v| v ^ (aiger_sign(lit) == 1))
+    };
+    let mut stack = vec![lit];
+    while let Some(&top) = stack.last() {
+        if value(values, top).is_some() {
+            stack.pop();
+            continue;
+        }
+        let (rhs0, rhs1) = gates[aiger_lit2var(top) as usize].expect("undefined literal");
+        match (value(values, rhs0), value(values, rhs1)) {
+            (Some(v0), Some(v1)) => {
+                values[aiger_lit2var(top) as usize] = Some(v0 && v1);
+                stack.pop();
+            }
+            (v0, v1) => {
+                assert!(stack.len() <= 2 * gates.len(), "cyclic and gates");
+                stack.extend(v0.is_none().then(|| rhs0));
+                stack.extend(v1.is_none().then(|| rhs1));
+            }
+        }
+    }
+    value(values, lit).unwrap()
 }
 
 impl fmt::Display for AigerController {
@@ -98,13 +1073,42 @@ impl fmt::Display for AigerController {
     }
 }
 
+/// The metrics of the size of an aiger circuit.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct AigerSize {
     num_ands: u32,
     num_latches: u32,
+    num_inputs: u32,
+    depth: u32,
+    io_delay: u32,
+    latch_delay: u32,
 }
 
 impl AigerSize {
+    pub(crate) fn num_ands(&self) -> u32 {
+        self.num_ands
+    }
+
+    pub(crate) fn num_latches(&self) -> u32 {
+        self.num_latches
+    }
+
+    pub(crate) fn num_inputs(&self) -> u32 {
+        self.num_inputs
+    }
+
+    pub(crate) fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub(crate) fn io_delay(&self) -> u32 {
+        self.io_delay
+    }
+
+    pub(crate) fn latch_delay(&self) -> u32 {
+        self.latch_delay
+    }
+
     pub(crate) fn total(&self) -> u32 {
         self.num_ands + self.num_latches
     }
@@ -117,6 +1121,7 @@ impl std::ops::Mul<u32> for AigerSize {
         Self {
             num_ands: self.num_ands * rhs,
             num_latches: self.num_latches * rhs,
+            ..self
         }
     }
 }
@@ -125,8 +1130,195 @@ impl fmt::Display for AigerSize {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "(#ands: {}, #latches: {})",
-            self.num_ands, self.num_latches
+            "(#ands: {}, #latches: {}, #inputs: {}, depth: {}, io delay: {}, latch delay: {})",
+            self.num_ands,
+            self.num_latches,
+            self.num_inputs,
+            self.depth,
+            self.io_delay,
+            self.latch_delay
         )
     }
 }
+
+/// Tests for aiger controllers.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test simulation of a controller with a latch.
+    #[test]
+    fn test_simulate() {
+        let aag = "aag 3 1 1 1 1\n2\n4 6\n6\n6 2 5\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        let inputs = vec![vec![true], vec![true], vec![false]];
+        let outputs = controller.simulate(&inputs);
+        assert_eq!(outputs, vec![vec![true], vec![false], vec![false]]);
+    }
+
+    /// Test that the reachable valuations of the latches form the transition table.
+    #[test]
+    fn test_explicit_transitions() {
+        // the latch toggles in every step, and the output c is a if the latch is set
+        let aag = "aag 3 1 1 1 1\n2\n4 5\n6\n6 2 4\ni0 a\no0 c\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        assert_eq!(
+            controller.explicit_transitions(&["a"], &["c"]),
+            Some(vec![Some((0, 1)), Some((0, 1)), Some((0, 0)), Some((1, 0))])
+        );
+        assert_eq!(controller.explicit_transitions(&["b"], &["c"]), None);
+    }
+
+    /// Test that exact synthesis removes redundant gates without changing the behavior.
+    #[test]
+    fn test_exact_synthesis() {
+        let aag = "aag 6 2 1 1 3\n2\n4\n12 10\n10\n6 2 4\n8 2 5\n10 6 9\n";
+        let mut controller = AigerController::read(aag.as_bytes()).unwrap();
+        let inputs = vec![
+            vec![true, true],
+            vec![true, false],
+            vec![false, true],
+            vec![true, true],
+        ];
+        let outputs = controller.simulate(&inputs);
+        assert!(!controller.exact_synthesis(1, None));
+        assert!(controller.exact_synthesis(MAX_EXACT_VARS, None));
+        assert_eq!(controller.size().num_ands, 1);
+        assert_eq!(controller.size().num_latches, 1);
+        assert_eq!(controller.simulate(&inputs), outputs);
+    }
+
+    /// Test that compression removes redundant gates without changing the behavior,
+    /// with or without the native ABC library.
+    #[test]
+    fn test_compress() {
+        let aag = "aag 6 2 1 1 3\n2\n4\n12 10\n10\n6 2 4\n8 2 5\n10 6 9\n";
+        let mut controller = AigerController::read(aag.as_bytes()).unwrap();
+        let inputs = vec![
+            vec![true, true],
+            vec![true, false],
+            vec![false, true],
+            vec![true, true],
+        ];
+        let outputs = controller.simulate(&inputs);
+        controller.compress(false, false, None);
+        assert_eq!(controller.size().num_ands, 1);
+        assert_eq!(controller.size().num_latches, 1);
+        assert_eq!(controller.simulate(&inputs), outputs);
+    }
+
+    /// Test the depth of a circuit with and gates in series and in parallel.
+    #[test]
+    fn test_depth() {
+        let aag = "aag 7 3 1 2 3\n2\n4\n6\n8 12\n14\n9\n10 2 4\n12 10 7\n14 3 8\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        assert_eq!(controller.depth(), 2);
+        let size = controller.size();
+        assert_eq!(size.num_inputs(), 3);
+        assert_eq!(size.total(), 4);
+    }
+
+    /// Test the latency of a circuit from inputs and from latches to outputs.
+    #[test]
+    fn test_latency() {
+        let aag = "aag 5 2 1 3 2\n2\n4\n6 8\n10\n6\n0\n8 2 4\n10 8 6\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        let latency = controller.latency();
+        assert_eq!(latency.input_to_output, 2);
+        assert_eq!(latency.latch_to_output, 1);
+        let size = controller.size();
+        assert_eq!(size.io_delay(), 2);
+        assert_eq!(size.latch_delay(), 1);
+
+        let aag = "aag 3 1 1 1 1\n2\n4 6\n5\n6 2 5\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        let latency = controller.latency();
+        assert_eq!(latency.input_to_output, 0);
+        assert_eq!(latency.latch_to_output, 0);
+    }
+
+    /// Test the report of the needed inputs and outputs and the reduced interface.
+    #[test]
+    fn test_interface() {
+        let aag = "aag 5 2 1 3 2\n2\n4\n6 8\n8\n1\n6\n8 2 6\n10 2 4\n\
+                   i0 a\ni1 b\nl0 l\no0 c\no1 d\no2 e\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        let report = controller.interface_report();
+        assert_eq!(
+            report.to_string(),
+            "input a: used -> input 0\n\
+             input b: unread -> removed\n\
+             output c: used -> output 0\n\
+             output d: constant 1 -> removed\n\
+             output e: used -> output 1\n"
+        );
+        assert_eq!(report.unread_inputs().count(), 1);
+        assert_eq!(report.constant_outputs().count(), 1);
+
+        let reduced = controller.reduce_interface();
+        assert_eq!(reduced.num_inputs(), 1);
+        assert_eq!(reduced.num_outputs(), 2);
+        assert_eq!(reduced.size().num_ands(), 1);
+        let inputs = vec![vec![true, false], vec![true, true], vec![false, true]];
+        let reduced_inputs: Vec<_> = inputs.iter().map(|v| vec![v[0]]).collect();
+        let outputs: Vec<_> = controller
+            .simulate(&inputs)
+            .into_iter()
+            .map(|v| vec![v[0], v[2]])
+            .collect();
+        assert_eq!(reduced.simulate(&reduced_inputs), outputs);
+    }
+
+    /// Test that the inputs of a circuit are driven by the outputs of a controller.
+    #[test]
+    fn test_drive() {
+        let aag = "aag 1 1 0 1 0\n2\n2\ni0 a\no0 controllable_b\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        let monitor_aag = "aag 4 2 1 1 1\n2\n4\n6 8\n6\n8 2 5\ni0 a\ni1 controllable_b\no0 bad\n";
+        let monitor = AigerController::read(monitor_aag.as_bytes()).unwrap();
+
+        let driven = controller.drive(&monitor);
+        assert_eq!(driven.num_inputs(), 1);
+        assert_eq!(driven.num_outputs(), 1);
+        let inputs = vec![vec![true], vec![false], vec![true]];
+        assert_eq!(
+            driven.simulate(&inputs),
+            vec![vec![false], vec![false], vec![false]]
+        );
+        let inputs = vec![vec![true, true], vec![true, false], vec![true, true]];
+        assert_eq!(
+            monitor.simulate(&inputs),
+            vec![vec![false], vec![false], vec![true]]
+        );
+    }
+
+    /// Test that composed circuits share inputs and check their interfaces.
+    #[test]
+    fn test_compose() {
+        let aag = "aag 3 1 1 1 1\n2\n4 6\n6\n6 2 5\ni0 a\no0 b\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        let other_aag = "aag 3 2 0 1 1\n2\n4\n7\n6 2 4\ni0 a\ni1 c\no0 d\n";
+        let other = AigerController::read(other_aag.as_bytes()).unwrap();
+
+        let composed = controller.compose(&other).unwrap();
+        assert_eq!(composed.num_inputs(), 2);
+        assert_eq!(composed.num_outputs(), 2);
+        let inputs = vec![vec![true, true], vec![true, false], vec![false, true]];
+        let outputs = composed.simulate(&inputs);
+        assert_eq!(
+            outputs,
+            vec![vec![true, false], vec![false, true], vec![false, true]]
+        );
+
+        assert_eq!(
+            controller.compose(&controller).err(),
+            Some(CompositionError::SharedOutput("b".to_string()))
+        );
+        let feedback_aag = "aag 1 1 0 1 0\n2\n2\ni0 b\no0 e\n";
+        let feedback = AigerController::read(feedback_aag.as_bytes()).unwrap();
+        assert_eq!(
+            controller.compose(&feedback).err(),
+            Some(CompositionError::Feedback("b".to_string()))
+        );
+    }
+}
@@ -1,69 +1,406 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::io;
+use std::thread;
 
-use aiger::{AigerConstructor, Literal};
-use cudd::{Bdd, BddView, Cudd, ReorderingMethod};
-use log::info;
+use aiger::{AigerConstructor, AndCache, Literal};
+use cudd::{Bdd, BddView, Cudd, ReorderingMethod, SendableBdds};
+use log::{debug, info};
 
 use super::aiger::AigerController;
 
+/// A pool of BDD managers over the same variables for constructing the BDDs
+/// of a controller.
+///
+/// The roots of the controller, that is the outputs followed by the bits of the
+/// state space, are partitioned into consecutive blocks with a separate manager
+/// for each block. This keeps each manager small and allows to reorder the
+/// variables of the managers independently and in parallel.
+pub(crate) struct ManagerPool {
+    managers: Vec<Cudd>,
+    num_roots: usize,
+}
+
+impl ManagerPool {
+    /// Creates a pool for `num_roots` BDDs over `num_vars` variables.
+    ///
+    /// A single manager is used if no threshold is given or the number of variables
+    /// is at most the threshold, and otherwise one manager for each started block of
+    /// `threshold` variables, but at most one manager per root.
+    pub(crate) fn new(num_vars: usize, num_roots: usize, threshold: Option<usize>) -> Self {
+        let num_managers = match threshold.map(|t| t.max(1)) {
+            Some(threshold) if num_vars > threshold => ((num_vars + threshold - 1) / threshold)
+                .min(num_roots)
+                .max(1),
+            _ => 1,
+        };
+        if num_managers > 1 {
+            info!(
+                "Partitioning {} BDDs over {} variables across {} managers",
+                num_roots, num_vars, num_managers
+            );
+        }
+        let managers = (0..num_managers)
+            .map(|_| Cudd::with_vars(num_vars).unwrap())
+            .collect();
+        Self {
+            managers,
+            num_roots,
+        }
+    }
+
+    /// Returns the managers of this pool.
+    pub(crate) fn managers(&self) -> &[Cudd] {
+        &self.managers
+    }
+
+    /// Returns the index of the manager for the given root.
+    pub(crate) fn partition(&self, root: usize) -> usize {
+        root * self.managers.len() / self.num_roots.max(1)
+    }
+
+    /// Enables dynamic reordering with the given method in all managers.
+    pub(crate) fn autodyn_enable(&mut self, method: ReorderingMethod) {
+        for manager in &mut self.managers {
+            manager.autodyn_enable(method);
+        }
+    }
+
+    /// Disables dynamic reordering in all managers.
+    pub(crate) fn autodyn_disable(&mut self) {
+        for manager in &mut self.managers {
+            manager.autodyn_disable();
+        }
+    }
+
+    /// Keeps the `size` variables starting at index `low` adjacent during
+    /// reordering in all managers.
+    pub(crate) fn make_group(&mut self, low: usize, size: usize) {
+        for manager in &mut self.managers {
+            manager.make_group(low, size, false);
+        }
+    }
+}
+
+/// The BDDs of a controller that share a manager.
+struct BddPartition {
+    manager: Cudd,
+    /// The indices of the outputs with a BDD in this partition.
+    outputs: Vec<usize>,
+    /// The indices of the bits of the state space with a BDD in this partition.
+    states: Vec<usize>,
+    /// The output BDDs followed by the state BDDs.
+    bdds: Vec<Bdd>,
+}
+
+impl BddPartition {
+    /// Returns the output BDDs and the state BDDs of this partition.
+    fn output_and_state_bdds(&self) -> (&[Bdd], &[Bdd]) {
+        self.bdds.split_at(self.outputs.len())
+    }
+
+    fn into_sendable(self) -> Result<SendableBddPartition, Self> {
+        match SendableBdds::new(self.manager, self.bdds) {
+            Ok(bdds) => Ok(SendableBddPartition {
+                outputs: self.outputs,
+                states: self.states,
+                bdds,
+            }),
+            Err((manager, bdds)) => Err(Self {
+                manager,
+                outputs: self.outputs,
+                states: self.states,
+                bdds,
+            }),
+        }
+    }
+}
+
+/// A [`BddPartition`] together with its BDD manager, which can be sent to another thread.
+struct SendableBddPartition {
+    outputs: Vec<usize>,
+    states: Vec<usize>,
+    bdds: SendableBdds,
+}
+
+impl From<SendableBddPartition> for BddPartition {
+    fn from(partition: SendableBddPartition) -> Self {
+        let (manager, bdds) = partition.bdds.into_inner();
+        Self {
+            manager,
+            outputs: partition.outputs,
+            states: partition.states,
+            bdds,
+        }
+    }
+}
+
+/// The subfunctions of the BDDs of a partitioned controller, which are identified across
+/// managers by their functions in a common manager.
+///
+/// The BDDs of each partition are swept before they are translated, which computes the
+/// function of each node in the common manager. A node is then translated only if no
+/// node of a previous partition with the same function or its negation has been
+/// translated, and otherwise the literal of that node is reused. The common manager
+/// uses the variable order of the first partition.
+struct SharedSubfunctions {
+    manager: Option<Cudd>,
+    /// The functions of the regular nodes of the current partition in the common manager.
+    functions: HashMap<Bdd, Bdd>,
+    /// The literals of the regular functions in the common manager translated so far.
+    literals: HashMap<Bdd, Literal>,
+    /// The number of nodes whose function was already translated for another partition.
+    num_shared: usize,
+}
+
+impl SharedSubfunctions {
+    /// Creates subfunctions with a common manager with the variable order of the given
+    /// manager.
+    fn new(manager: &Cudd) -> Self {
+        let order = manager.variable_order();
+        let mut common = Cudd::with_vars(order.len()).unwrap();
+        common.shuffle_heap(&order);
+        Self {
+            manager: Some(common),
+            ..Self::disabled()
+        }
+    }
+
+    /// Creates subfunctions without a common manager, which share no nodes.
+    fn disabled() -> Self {
+        Self {
+            manager: None,
+            functions: HashMap::new(),
+            literals: HashMap::new(),
+            num_shared: 0,
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.manager.is_some()
+    }
+
+    /// Computes the functions of the nodes of the given BDDs of a partition.
+    fn sweep(&mut self, bdds: &[Bdd]) {
+        if let Some(manager) = &self.manager {
+            self.functions.clear();
+            for bdd in bdds {
+                Self::function(manager, bdd, &mut self.functions);
+            }
+        }
+    }
+
+    /// Returns the function of the given BDD in the common manager.
+    fn function(manager: &Cudd, bdd: &Bdd, functions: &mut HashMap<Bdd, Bdd>) -> Bdd {
+        let node = bdd.regular();
+        let function = functions.get(&node).cloned().unwrap_or_else(|| {
+            let function = match bdd.view() {
+                BddView::Constant => manager.bdd_one(),
+                BddView::InnerNode {
+                    var,
+                    bdd_then,
+                    bdd_else,
+                } => {
+                    let function_then = Self::function(manager, &bdd_then, functions);
+                    let function_else = Self::function(manager, &bdd_else, functions);
+                    manager.bdd_var(var).ite(&function_then, &function_else)
+                }
+            };
+            functions.insert(node, function.clone());
+            function
+        });
+        if bdd.is_complement() {
+            !function
+        } else {
+            function
+        }
+    }
+
+    /// Returns the literal of a translated node of another partition with the same
+    /// function as the given regular node, if any.
+    fn literal(&mut self, node: &Bdd) -> Option<Literal> {
+        let function = self.functions.get(node)?;
+        let literal = *self.literals.get(&function.regular())?;
+        self.num_shared += 1;
+        Some(if function.is_complement() {
+            !literal
+        } else {
+            literal
+        })
+    }
+
+    /// Records the literal of the given translated regular node.
+    fn insert(&mut self, node: &Bdd, literal: Literal) {
+        if let Some(function) = self.functions.get(node) {
+            let literal = if function.is_complement() {
+                !literal
+            } else {
+                literal
+            };
+            self.literals.entry(function.regular()).or_insert(literal);
+        }
+    }
+}
+
 /// A controller as a BDD.
 ///
 /// More specifically, a controller in this form is a forest of BDDs with shared
 /// nodes, having a root for each output and each bit of the state space.
 /// The input variables of the BDDs are the inputs and the bits of current state.
+/// For controllers with many variables, the roots may be partitioned across
+/// several managers, see [`ManagerPool`].
+///
+/// The controller may have several allowed initial states, of which one is chosen
+/// as the reset value of the latches when creating an aiger circuit.
 pub struct BddController {
     inputs: Vec<String>,
     outputs: Vec<String>,
     state_names: Vec<String>,
-    initial_state: Vec<bool>,
-    state_bdds: Vec<Bdd>,
-    output_bdds: Vec<Bdd>,
-    manager: Cudd,
+    initial_states: Vec<Vec<bool>>,
+    partitions: Vec<BddPartition>,
 }
 
 impl BddController {
     pub(super) fn new(
         inputs: Vec<String>,
         outputs: Vec<String>,
-        initial_state: Vec<bool>,
+        initial_states: Vec<Vec<bool>>,
         state_bdds: Vec<Bdd>,
         output_bdds: Vec<Bdd>,
-        mut manager: Cudd,
+        mut pool: ManagerPool,
     ) -> Self {
-        let state_names = (0..initial_state.len())
-            .map(|i| format!("l{}", i))
-            .collect();
+        assert!(
+            !initial_states.is_empty(),
+            "controller without initial state"
+        );
+        assert!(initial_states.iter().all(|s| s.len() == state_bdds.len()));
+        assert_eq!(pool.num_roots, output_bdds.len() + state_bdds.len());
+        let state_names = (0..state_bdds.len()).map(|i| format!("l{}", i)).collect();
         // ensure that dynamic reordering is disabled for a later consistent traversal of the BDDs
-        manager.autodyn_disable();
+        pool.autodyn_disable();
+        let num_outputs = output_bdds.len();
+        let roots: Vec<_> = output_bdds
+            .into_iter()
+            .chain(state_bdds)
+            .enumerate()
+            .map(|(root, bdd)| (pool.partition(root), root, bdd))
+            .collect();
+        let mut partitions: Vec<_> = pool
+            .managers
+            .into_iter()
+            .map(|manager| BddPartition {
+                manager,
+                outputs: Vec::new(),
+                states: Vec::new(),
+                bdds: Vec::new(),
+            })
+            .collect();
+        // roots are partitioned into consecutive blocks, so outputs come first in each partition
+        for (partition, root, bdd) in roots {
+            let partition = &mut partitions[partition];
+            if root < num_outputs {
+                partition.outputs.push(root);
+            } else {
+                partition.states.push(root - num_outputs);
+            }
+            partition.bdds.push(bdd);
+        }
         Self {
             inputs,
             outputs,
             state_names,
-            initial_state,
-            state_bdds,
-            output_bdds,
-            manager,
+            initial_states,
+            partitions,
         }
     }
 
     fn num_state_vars(&self) -> usize {
-        self.initial_state.len()
+        self.state_names.len()
+    }
+
+    /// Chooses the reset value of the latches among the allowed initial states.
+    ///
+    /// Prefers the initial state with the least number of latches initialized to one,
+    /// as these need an additional inverter once the circuit is normalized to
+    /// latches initialized to zero.
+    fn reset_value(&self) -> &[bool] {
+        self.initial_states
+            .iter()
+            .min_by_key(|bits| bits.iter().filter(|&&b| b).count())
+            .unwrap()
     }
 
     pub(crate) fn num_bdd_vars(&self) -> usize {
         self.inputs.len() + self.num_state_vars()
     }
 
+    /// Returns the names of the BDD variables, that is the inputs followed
+    /// by the bits of the current state.
+    fn var_names(&self) -> impl Iterator<Item = &String> {
+        self.inputs.iter().chain(&self.state_names)
+    }
+
+    /// Reorders the variables of all managers according to the given order
+    /// of input and state bit names, and returns the number of names applied.
+    ///
+    /// Unknown and repeated names are ignored, and variables not named in the
+    /// order are placed after the named variables in their current relative order.
+    pub(crate) fn apply_variable_order(&mut self, order: &[String]) -> usize {
+        let index: HashMap<_, _> = self
+            .var_names()
+            .enumerate()
+            .map(|(var, name)| (name.as_str(), var))
+            .collect();
+        let mut vars = Vec::with_capacity(order.len());
+        for name in order {
+            if let Some(&var) = index.get(name.as_str()) {
+                if !vars.contains(&var) {
+                    vars.push(var);
+                }
+            }
+        }
+        info!(
+            "Applying variable order for {} of {} BDD variables",
+            vars.len(),
+            self.num_bdd_vars()
+        );
+        for partition in &mut self.partitions {
+            let mut permutation = vars.clone();
+            permutation.extend(
+                partition
+                    .manager
+                    .variable_order()
+                    .into_iter()
+                    .filter(|var| !vars.contains(var)),
+            );
+            partition.manager.shuffle_heap(&permutation);
+        }
+        vars.len()
+    }
+
+    /// Returns the current order of the names of the inputs and state bits.
+    ///
+    /// For a partitioned controller, this is the order of the first manager.
+    pub(crate) fn variable_order(&self) -> Vec<String> {
+        let names: Vec<_> = self.var_names().collect();
+        self.partitions[0]
+            .manager
+            .variable_order()
+            .into_iter()
+            .filter_map(|var| names.get(var).map(|&name| name.clone()))
+            .collect()
+    }
+
     fn bdd_to_aig(
         mut aig: &mut AigerConstructor,
         bdd: &Bdd,
         mut bdd_cache: &mut HashMap<Bdd, Literal>,
+        mut shared: &mut SharedSubfunctions,
         input_state_lits: &[Literal],
     ) -> Literal {
         let node = bdd.regular();
         let literal = bdd_cache.get(&node).cloned().unwrap_or_else(|| {
-            let lit = match bdd.view() {
+            let lit = shared.literal(&node).unwrap_or_else(|| match bdd.view() {
                 BddView::Constant => Literal::TRUE,
                 BddView::InnerNode {
                     var,
@@ -71,13 +408,25 @@ impl BddController {
                     bdd_else,
                 } => {
                     let lit_var = input_state_lits[var];
-                    let lit_then =
-                        Self::bdd_to_aig(&mut aig, &bdd_then, &mut bdd_cache, input_state_lits);
-                    let lit_else =
-                        Self::bdd_to_aig(&mut aig, &bdd_else, &mut bdd_cache, input_state_lits);
-                    aig.add_ite(lit_var, lit_then, lit_else)
+                    let lit_then = Self::bdd_to_aig(
+                        &mut aig,
+                        &bdd_then,
+                        &mut bdd_cache,
+                        &mut shared,
+                        input_state_lits,
+                    );
+                    let lit_else = Self::bdd_to_aig(
+                        &mut aig,
+                        &bdd_else,
+                        &mut bdd_cache,
+                        &mut shared,
+                        input_state_lits,
+                    );
+                    let lit = aig.add_ite(lit_var, lit_then, lit_else);
+                    shared.insert(&node, lit);
+                    lit
                 }
-            };
+            });
             bdd_cache.insert(node, lit);
             lit
         });
@@ -88,10 +437,22 @@ impl BddController {
         }
     }
 
-    pub(crate) fn create_aiger(&self) -> AigerController {
+    /// Creates an aiger circuit for this controller, where and gates are cached
+    /// according to the given configuration.
+    ///
+    /// If `share_subfunctions` is set and the BDDs are partitioned across several
+    /// managers, nodes of different managers with the same function or its negation
+    /// are translated only once, see [`SharedSubfunctions`].
+    pub(crate) fn create_aiger(
+        &self,
+        and_cache: AndCache,
+        share_subfunctions: bool,
+    ) -> AigerController {
         info!("Creating aiger circuit from BDD");
 
-        let mut aig = AigerConstructor::new(self.inputs.len(), self.num_state_vars()).unwrap();
+        let mut aig =
+            AigerConstructor::with_and_cache(self.inputs.len(), self.num_state_vars(), and_cache)
+                .unwrap();
         let mut input_state_lits = Vec::with_capacity(self.num_bdd_vars());
         for i in &self.inputs {
             input_state_lits.push(aig.add_input(i));
@@ -100,26 +461,63 @@ impl BddController {
             input_state_lits.push(aig.add_latch(s));
         }
 
-        let mut cache = HashMap::new();
-        for (o, output_bdd) in self.outputs.iter().zip(self.output_bdds.iter()) {
-            let lit = Self::bdd_to_aig(&mut aig, output_bdd, &mut cache, &input_state_lits);
+        let mut shared = if share_subfunctions && self.partitions.len() > 1 {
+            SharedSubfunctions::new(&self.partitions[0].manager)
+        } else {
+            SharedSubfunctions::disabled()
+        };
+        let mut output_lits = vec![Literal::FALSE; self.outputs.len()];
+        let mut next_state_lits = vec![Literal::FALSE; self.num_state_vars()];
+        for partition in &self.partitions {
+            // nodes are only shared within a manager
+            let mut cache = HashMap::new();
+            shared.sweep(&partition.bdds);
+            let (output_bdds, state_bdds) = partition.output_and_state_bdds();
+            for (&o, output_bdd) in partition.outputs.iter().zip(output_bdds) {
+                output_lits[o] = Self::bdd_to_aig(
+                    &mut aig,
+                    output_bdd,
+                    &mut cache,
+                    &mut shared,
+                    &input_state_lits,
+                );
+            }
+            for (&s, state_bdd) in partition.states.iter().zip(state_bdds) {
+                next_state_lits[s] = Self::bdd_to_aig(
+                    &mut aig,
+                    state_bdd,
+                    &mut cache,
+                    &mut shared,
+                    &input_state_lits,
+                );
+            }
+        }
+        for (o, lit) in self.outputs.iter().zip(output_lits) {
             aig.add_output(o, lit);
         }
         let state_lits = &input_state_lits[self.inputs.len()..];
-        for ((&state_init, state_bdd), &state_lit) in self
-            .initial_state
+        for ((&state_init, lit), &state_lit) in self
+            .reset_value()
             .iter()
-            .zip(self.state_bdds.iter())
+            .zip(next_state_lits)
             .zip(state_lits.iter())
         {
-            let lit = Self::bdd_to_aig(&mut aig, state_bdd, &mut cache, &input_state_lits);
             aig.set_latch_next(state_lit, lit);
             aig.set_latch_reset(state_lit, Literal::from_bool(state_init));
         }
 
+        if shared.enabled() {
+            debug!(
+                "Shared {} subfunctions across BDD managers",
+                shared.num_shared
+            );
+        }
+        debug!("And gate cache: {}", aig.and_cache_stats());
         AigerController::new(aig.into_aiger())
     }
 
+    /// Reduces the BDDs by variable reordering, where the managers of
+    /// a partitioned controller are reordered in parallel.
     pub(crate) fn reduce(&mut self, exact: bool) {
         info!("Reducing BDD by variable reordering");
         let reordering_type = if exact {
@@ -127,25 +525,200 @@ impl BddController {
         } else {
             ReorderingMethod::SiftConverge
         };
-        self.manager.reduce_heap(reordering_type, 0);
+        if self.partitions.len() == 1 {
+            self.partitions[0].manager.reduce_heap(reordering_type, 0);
+            return;
+        }
+        let handles: Vec<_> = self
+            .partitions
+            .drain(..)
+            .map(|partition| match partition.into_sendable() {
+                Ok(sendable) => Ok(thread::spawn(move || {
+                    let mut partition = BddPartition::from(sendable);
+                    partition.manager.reduce_heap(reordering_type, 0);
+                    partition
+                        .into_sendable()
+                        .unwrap_or_else(|_| panic!("BDD manager referenced after reordering"))
+                })),
+                Err(mut partition) => {
+                    partition.manager.reduce_heap(reordering_type, 0);
+                    Err(partition)
+                }
+            })
+            .collect();
+        self.partitions = handles
+            .into_iter()
+            .map(|handle| match handle {
+                Ok(handle) => BddPartition::from(handle.join().unwrap()),
+                Err(partition) => partition,
+            })
+            .collect();
     }
 }
 
-impl fmt::Display for BddController {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut bdds = Vec::with_capacity(self.output_bdds.len() + self.state_bdds.len());
-        bdds.extend(self.output_bdds.iter().cloned());
-        bdds.extend(self.state_bdds.iter().cloned());
+impl BddController {
+    /// Converts this controller into a value that can be sent to another thread,
+    /// where it can be converted back into a controller with [`From`].
+    ///
+    /// # Errors
+    ///
+    /// Returns this controller if any BDD outside of this controller still
+    /// references a manager of this controller.
+    pub fn into_sendable(self) -> Result<SendableBddController, Self> {
+        let partitions: Vec<_> = self
+            .partitions
+            .into_iter()
+            .map(BddPartition::into_sendable)
+            .collect();
+        if partitions.iter().all(Result::is_ok) {
+            Ok(SendableBddController {
+                inputs: self.inputs,
+                outputs: self.outputs,
+                state_names: self.state_names,
+                initial_states: self.initial_states,
+                partitions: partitions.into_iter().filter_map(Result::ok).collect(),
+            })
+        } else {
+            let partitions = partitions
+                .into_iter()
+                .map(|partition| match partition {
+                    Ok(sendable) => BddPartition::from(sendable),
+                    Err(partition) => partition,
+                })
+                .collect();
+            Err(Self {
+                inputs: self.inputs,
+                outputs: self.outputs,
+                state_names: self.state_names,
+                initial_states: self.initial_states,
+                partitions,
+            })
+        }
+    }
 
-        let mut in_names = Vec::with_capacity(self.inputs.len() + self.num_state_vars());
+    /// Returns the names of variables and BDDs of a partition for the DOT representation.
+    fn dot_arguments(&self, partition: &BddPartition) -> (Vec<String>, Vec<String>) {
+        let mut in_names = Vec::with_capacity(self.num_bdd_vars());
         in_names.extend(self.inputs.iter().cloned());
         in_names.extend(self.state_names.iter().cloned());
 
-        let mut out_names = Vec::with_capacity(self.outputs.len() + self.num_state_vars());
-        out_names.extend(self.outputs.iter().cloned());
-        out_names.extend(self.state_names.iter().cloned());
+        let mut out_names = Vec::with_capacity(partition.bdds.len());
+        out_names.extend(partition.outputs.iter().map(|&o| self.outputs[o].clone()));
+        out_names.extend(
+            partition
+                .states
+                .iter()
+                .map(|&s| self.state_names[s].clone()),
+        );
+
+        (in_names, out_names)
+    }
+
+    /// Writes the BDD controller in DOT format to the given writer.
+    ///
+    /// A controller partitioned across several managers is written as one
+    /// graph for each manager.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during writing.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        for partition in &self.partitions {
+            let (in_names, out_names) = self.dot_arguments(partition);
+            partition
+                .manager
+                .write_dot(&mut writer, &partition.bdds, &in_names, &out_names)?;
+        }
+        Ok(())
+    }
+}
+
+/// A [`BddController`] together with its BDD managers, which can be sent to another thread.
+///
+/// This value is obtained from [`BddController::into_sendable`] and can be
+/// converted back into a controller on the receiving thread.
+pub struct SendableBddController {
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    state_names: Vec<String>,
+    initial_states: Vec<Vec<bool>>,
+    partitions: Vec<SendableBddPartition>,
+}
+
+impl From<SendableBddController> for BddController {
+    fn from(controller: SendableBddController) -> Self {
+        Self {
+            inputs: controller.inputs,
+            outputs: controller.outputs,
+            state_names: controller.state_names,
+            initial_states: controller.initial_states,
+            partitions: controller
+                .partitions
+                .into_iter()
+                .map(BddPartition::from)
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for BddController {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for partition in &self.partitions {
+            let (in_names, out_names) = self.dot_arguments(partition);
+            let dot = partition
+                .manager
+                .dump_dot(&partition.bdds, &in_names, &out_names);
+            write!(f, "{}", dot)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let dot = self.manager.dump_dot(&bdds, &in_names, &out_names);
-        write!(f, "{}", dot)
+    /// Test that BDDs are only partitioned with a threshold below the number of variables.
+    #[test]
+    fn test_partition_opt_in() {
+        assert_eq!(ManagerPool::new(100, 10, None).managers().len(), 1);
+        assert_eq!(ManagerPool::new(100, 10, Some(100)).managers().len(), 1);
+        assert_eq!(ManagerPool::new(100, 10, Some(30)).managers().len(), 4);
+        assert_eq!(ManagerPool::new(100, 2, Some(30)).managers().len(), 2);
+    }
+
+    /// Test that a function in managers with different variable orders is translated once.
+    #[test]
+    fn test_share_subfunctions() {
+        let pool = ManagerPool::new(3, 2, Some(1));
+        let mut roots = Vec::new();
+        for (i, manager) in pool.managers().iter().enumerate() {
+            if i == 1 {
+                manager.bdd_one().manager().shuffle_heap(&[2, 1, 0]);
+            }
+            let (a, b, c) = (manager.bdd_var(0), manager.bdd_var(1), manager.bdd_var(2));
+            let function = (a & b) | c;
+            roots.push(if i == 0 { function } else { !function });
+        }
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        let controller = BddController::new(
+            names(&["a", "b", "c"]),
+            names(&["x", "y"]),
+            vec![Vec::new()],
+            Vec::new(),
+            roots,
+            pool,
+        );
+        let aig = controller.create_aiger(AndCache::Disabled, false);
+        let shared = controller.create_aiger(AndCache::Disabled, true);
+        assert!(shared.num_ands() < aig.num_ands());
+        let inputs: Vec<_> = (0..8)
+            .map(|i| (0..3).map(|v| i >> v & 1 == 1).collect())
+            .collect();
+        assert_eq!(shared.simulate(&inputs), aig.simulate(&inputs));
+        for (input, output) in inputs.iter().zip(shared.simulate(&inputs)) {
+            let expected = (input[0] && input[1]) || input[2];
+            assert_eq!(output, vec![expected, !expected]);
+        }
     }
 }
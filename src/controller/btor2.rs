@@ -0,0 +1,211 @@
+//! Export of aiger circuits as word-level sequential models in BTOR2 format, which can
+//! be read directly by hardware model checkers such as btormc or Pono.
+//!
+//! The translation is structural: inputs and latches of the circuit become inputs and
+//! states with bit-vectors of width one, and gates become `and` nodes, where negated
+//! literals are given by negative node ids. An optional assumption monitor, i.e. an
+//! aiger circuit whose outputs signal violations of assumptions on the environment,
+//! is added to the model, where its inputs named like an input or output of the
+//! controller are connected to it, and the negations of its outputs are added as
+//! constraints. Properties to verify can then be added as `bad` nodes over the
+//! outputs of the controller.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+use aiger::{aiger_lit2var, aiger_sign, Aiger, SymbolKind};
+use log::info;
+
+use super::aiger::{topological_order, AigerController};
+
+/// The node id of the sort of bit-vectors of width one.
+const SORT: i64 = 1;
+/// The node id of the constant false.
+const FALSE: i64 = 2;
+/// The node id of the constant true.
+const TRUE: i64 = 3;
+
+/// A controller as a sequential model in BTOR2 format, obtained by structural
+/// translation of an aiger circuit together with an optional assumption monitor.
+///
+/// The outputs of the controller are written as `output` nodes, and the negated
+/// outputs of the assumption monitor as `constraint` nodes, such that a model checker
+/// only considers traces on which no assumption is violated.
+#[derive(Clone)]
+pub struct Btor2Controller {
+    controller: AigerController,
+    assumption: Option<AigerController>,
+}
+
+impl AigerController {
+    /// Returns the model of this controller in BTOR2 format, where the outputs of the
+    /// given assumption monitor signal violations of assumptions on the environment and
+    /// the inputs of the monitor are connected to the inputs and outputs of this
+    /// controller with the same name.
+    pub fn to_btor2(&self, assumption: Option<&Self>) -> Btor2Controller {
+        Btor2Controller {
+            controller: self.clone(),
+            assumption: assumption.cloned(),
+        }
+    }
+}
+
+/// The nodes of a BTOR2 model written so far.
+struct Model<W> {
+    writer: W,
+    next_id: i64,
+}
+
+impl<W: Write> Model<W> {
+    /// Writes a node with the given operation and returns its id.
+    fn node(&mut self, operation: fmt::Arguments<'_>) -> io::Result<i64> {
+        let id = self.next_id;
+        self.next_id += 1;
+        writeln!(self.writer, "{} {}", id, operation)?;
+        Ok(id)
+    }
+
+    /// Writes the inputs, latches and gates of the given circuit and returns the node ids
+    /// of its variables, where an input with a name for which the given function returns
+    /// a node is connected to that node.
+    fn add_circuit<F: Fn(&str) -> Option<i64>>(
+        &mut self,
+        aig: &Aiger,
+        connect: F,
+    ) -> io::Result<HashMap<u32, i64>> {
+        let mut ids = HashMap::new();
+        let names = aig.symbol_names(SymbolKind::Input);
+        for (i, (lit, name)) in aig.input_literals().into_iter().zip(names).enumerate() {
+            let id = match name.as_deref().and_then(&connect) {
+                Some(id) => id,
+                None => self.node(format_args!("input {} {}", SORT, symbol(name, "i", i)))?,
+            };
+            ids.insert(aiger_lit2var(lit), id);
+        }
+        let latches = aig.latch_literals();
+        let names = aig.symbol_names(SymbolKind::Latch);
+        for (i, (&(lit, _, _), name)) in latches.iter().zip(names).enumerate() {
+            let id = self.node(format_args!("state {} {}", SORT, symbol(name, "l", i)))?;
+            ids.insert(aiger_lit2var(lit), id);
+        }
+        let mut gates = vec![None; aig.maxvar() as usize + 1];
+        for (lhs, rhs0, rhs1) in aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+        for v in topological_order(&gates) {
+            let (rhs0, rhs1) = gates[v].unwrap();
+            let (id0, id1) = (literal(&ids, rhs0), literal(&ids, rhs1));
+            let id = self.node(format_args!("and {} {} {}", SORT, id0, id1))?;
+            ids.insert(v as u32, id);
+        }
+        for (lit, next, reset) in latches {
+            let state = ids[&aiger_lit2var(lit)];
+            // latches with themselves as reset value are uninitialized
+            if reset != lit {
+                let value = literal(&ids, reset);
+                self.node(format_args!("init {} {} {}", SORT, state, value))?;
+            }
+            let next = literal(&ids, next);
+            self.node(format_args!("next {} {} {}", SORT, state, next))?;
+        }
+        Ok(ids)
+    }
+}
+
+/// Returns the node id for the given literal, where negated literals are given by
+/// negative node ids.
+fn literal(ids: &HashMap<u32, i64>, lit: u32) -> i64 {
+    let negated = aiger_sign(lit) == 1;
+    match aiger_lit2var(lit) {
+        0 if negated => TRUE,
+        0 => FALSE,
+        var if negated => -ids[&var],
+        var => ids[&var],
+    }
+}
+
+/// Returns the symbol for a node, which is the given name with whitespace replaced,
+/// or the prefix followed by the index if the name is missing.
+fn symbol(name: Option<String>, prefix: &str, index: usize) -> String {
+    match name {
+        Some(name) => name.replace(char::is_whitespace, "_"),
+        None => format!("{}{}", prefix, index),
+    }
+}
+
+impl Btor2Controller {
+    /// Writes the model in BTOR2 format to the given writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during writing.
+    pub fn write<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut model = Model {
+            writer,
+            next_id: SORT,
+        };
+        model.node(format_args!("sort bitvec 1"))?;
+        model.node(format_args!("zero {}", SORT))?;
+        model.node(format_args!("one {}", SORT))?;
+
+        let aig = &self.controller.aig;
+        let ids = model.add_circuit(aig, |_| None)?;
+        let mut signals = HashMap::new();
+        let names = aig.symbol_names(SymbolKind::Input);
+        for (lit, name) in aig.input_literals().into_iter().zip(names) {
+            if let Some(name) = name {
+                signals.insert(name, literal(&ids, lit));
+            }
+        }
+        let names = aig.symbol_names(SymbolKind::Output);
+        for (i, (lit, name)) in aig.output_literals().into_iter().zip(names).enumerate() {
+            let id = literal(&ids, lit);
+            let name = symbol(name, "o", i);
+            model.node(format_args!("output {} {}", id, name))?;
+            signals.insert(name, id);
+        }
+
+        if let Some(assumption) = &self.assumption {
+            let aig = &assumption.aig;
+            let ids = model.add_circuit(aig, |name| signals.get(name).copied())?;
+            let names = aig.symbol_names(SymbolKind::Output);
+            for (i, (lit, name)) in aig.output_literals().into_iter().zip(names).enumerate() {
+                let id = -literal(&ids, lit);
+                let name = symbol(name, "a", i);
+                model.node(format_args!("constraint {} {}", id, name))?;
+            }
+        }
+        info!("Written BTOR2 model with {} nodes", model.next_id - 1);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a controller with an assumption monitor is translated to BTOR2.
+    #[test]
+    fn test_write_btor2() {
+        let aag = "aag 3 1 1 1 1\n2\n4 6\n6\n6 2 5\ni0 a\no0 b\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        controller.to_btor2(None).write(&mut output).unwrap();
+        let expected = "1 sort bitvec 1\n2 zero 1\n3 one 1\n4 input 1 a\n5 state 1 l0\n6 and 1 4 -5\n7 init 1 5 2\n8 next 1 5 6\n9 output 6 b\n";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        let monitor_aag = "aag 4 3 0 1 1\n2\n4\n6\n8 2 5\ni0 a\ni1 b\ni2 c\no0 violation\n";
+        let monitor = AigerController::read(monitor_aag.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        controller
+            .to_btor2(Some(&monitor))
+            .write(&mut output)
+            .unwrap();
+        let expected = format!(
+            "{}10 input 1 c\n11 and 1 4 -6\n12 constraint -11 violation\n",
+            expected
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
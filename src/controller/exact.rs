@@ -0,0 +1,280 @@
+//! Exact synthesis of and-inverter graphs with a minimum number of and gates
+//! for boolean functions with few variables.
+
+use std::time::{Duration, Instant};
+
+use log::debug;
+use varisat::{CnfFormula, ExtendFormula, Lit};
+
+use super::sat::{self, SatResult};
+
+/// The maximum number of variables of functions for exact synthesis.
+pub(crate) const MAX_EXACT_VARS: usize = 8;
+
+/// The maximum number of and gates of the circuits searched by exact synthesis.
+const MAX_EXACT_GATES: usize = 12;
+
+/// The time after which exact synthesis gives up if no earlier deadline is given.
+const EXACT_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+/// A possibly negated node in a synthesized and-inverter graph.
+///
+/// Node `0` is the constant false, nodes `1` to `n` are the variables
+/// and the remaining nodes are the and gates.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct Signal {
+    pub(super) node: usize,
+    pub(super) negated: bool,
+}
+
+/// An and-inverter graph with a minimum number of and gates for a list of functions.
+#[derive(Debug, Clone)]
+pub(super) struct ExactCircuit {
+    /// The inputs of the and gates, which only refer to variables and earlier gates.
+    pub(super) gates: Vec<(Signal, Signal)>,
+    /// The signal implementing each function.
+    pub(super) outputs: Vec<Signal>,
+}
+
+/// The value of a node at a row of the truth table, which is either known
+/// or given by a variable of the SAT solver.
+#[derive(Debug, Copy, Clone)]
+enum Value {
+    Const(bool),
+    Var(Lit),
+}
+
+impl Value {
+    /// Returns the condition that this value is equal to `value`.
+    fn equals(self, value: bool) -> Self {
+        match self {
+            Self::Const(b) => Self::Const(b == value),
+            Self::Var(lit) => Self::Var(if value { lit } else { !lit }),
+        }
+    }
+}
+
+/// Adds the disjunction of the given conditions as a clause, skipping clauses
+/// that are already satisfied by a constant.
+fn add_clause(formula: &mut CnfFormula, conditions: &[Value]) {
+    let mut clause = Vec::with_capacity(conditions.len());
+    for &condition in conditions {
+        match condition {
+            Value::Const(true) => return,
+            Value::Const(false) => (),
+            Value::Var(lit) => clause.push(lit),
+        }
+    }
+    formula.add_clause(&clause);
+}
+
+/// Searches for an and-inverter graph with the minimum number of and gates,
+/// which is less than `max_gates` and at most [`MAX_EXACT_GATES`], implementing
+/// the given functions over `num_vars` variables.
+///
+/// Each function is given by its truth table, where the entry at index `t` is
+/// the value for the valuation in which variable `i` has the value of bit `i` of `t`.
+/// Returns `None` if there is no such graph, or if the deadline or the time limit
+/// of [`EXACT_TIME_LIMIT`] passes, which also abandons a running search of the solver.
+///
+/// # Panics
+///
+/// Panics if the number of variables exceeds [`MAX_EXACT_VARS`] or a truth table
+/// has the wrong size.
+pub(super) fn synthesize(
+    num_vars: usize,
+    functions: &[Vec<bool>],
+    max_gates: usize,
+    deadline: Option<Instant>,
+) -> Option<ExactCircuit> {
+    assert!(num_vars <= MAX_EXACT_VARS);
+    assert!(functions.iter().all(|f| f.len() == 1 << num_vars));
+    let limit = Instant::now() + EXACT_TIME_LIMIT;
+    let deadline = deadline.map_or(limit, |d| d.min(limit));
+    for num_gates in 0..max_gates.min(MAX_EXACT_GATES + 1) {
+        debug!("Searching for circuit with {} and gates", num_gates);
+        match solve(num_vars, functions, num_gates, deadline) {
+            Ok(Some(circuit)) => return Some(circuit),
+            Ok(None) => (),
+            Err(()) => return None,
+        }
+    }
+    None
+}
+
+/// Encodes the existence of an and-inverter graph with exactly `num_gates`
+/// and gates for the given functions and solves it.
+///
+/// Each and gate selects two distinct earlier nodes and a truth table with exactly
+/// one true entry, which covers all negations of its inputs. Each function selects
+/// a node and whether it is negated. The values of the gates at each row of the
+/// truth table are given by additional variables. Returns an error if the
+/// deadline passes or the solver fails.
+fn solve(
+    num_vars: usize,
+    functions: &[Vec<bool>],
+    num_gates: usize,
+    deadline: Instant,
+) -> Result<Option<ExactCircuit>, ()> {
+    let num_rows = 1 << num_vars;
+    let num_nodes = 1 + num_vars + num_gates;
+    let mut formula = CnfFormula::new();
+
+    let gate_values: Vec<Vec<Lit>> = (0..num_gates)
+        .map(|_| (0..num_rows).map(|_| formula.new_lit()).collect())
+        .collect();
+    let value = |node: usize, row: usize| {
+        if node == 0 {
+            Value::Const(false)
+        } else if node <= num_vars {
+            Value::Const((row >> (node - 1)) & 1 == 1)
+        } else {
+            Value::Var(gate_values[node - num_vars - 1][row])
+        }
+    };
+
+    let mut selections = Vec::with_capacity(num_gates);
+    let mut tables = Vec::with_capacity(num_gates);
+    for gate in 0..num_gates {
+        let node = 1 + num_vars + gate;
+        // truth table of the gate with exactly one true entry
+        let table: Vec<Lit> = (0..4).map(|_| formula.new_lit()).collect();
+        formula.add_clause(&table);
+        for k in 0..4 {
+            for l in (k + 1)..4 {
+                formula.add_clause(&[!table[k], !table[l]]);
+            }
+        }
+        // selection of the inputs of the gate
+        let mut selection = Vec::new();
+        for j in 1..node {
+            for l in (j + 1)..node {
+                let sel = formula.new_lit();
+                selection.push((j, l, sel));
+                for row in 0..num_rows {
+                    for (k, &entry) in table.iter().enumerate() {
+                        let (vj, vl) = (k & 1 == 1, k & 2 == 2);
+                        let premise = [
+                            Value::Var(!sel),
+                            value(j, row).equals(!vj),
+                            value(l, row).equals(!vl),
+                        ];
+                        let out = value(node, row);
+                        let mut clause = premise.to_vec();
+                        clause.extend(&[out.equals(true), Value::Var(!entry)]);
+                        add_clause(&mut formula, &clause);
+                        let mut clause = premise.to_vec();
+                        clause.extend(&[out.equals(false), Value::Var(entry)]);
+                        add_clause(&mut formula, &clause);
+                    }
+                }
+            }
+        }
+        let clause: Vec<_> = selection.iter().map(|&(_, _, sel)| sel).collect();
+        formula.add_clause(&clause);
+        selections.push(selection);
+        tables.push(table);
+    }
+
+    let mut outputs = Vec::with_capacity(functions.len());
+    for function in functions {
+        let negated = formula.new_lit();
+        let choices: Vec<Lit> = (0..num_nodes).map(|_| formula.new_lit()).collect();
+        formula.add_clause(&choices);
+        for (node, &choice) in choices.iter().enumerate() {
+            for (row, &target) in function.iter().enumerate() {
+                add_clause(
+                    &mut formula,
+                    &[
+                        Value::Var(!choice),
+                        Value::Var(negated),
+                        value(node, row).equals(target),
+                    ],
+                );
+                add_clause(
+                    &mut formula,
+                    &[
+                        Value::Var(!choice),
+                        Value::Var(!negated),
+                        value(node, row).equals(!target),
+                    ],
+                );
+            }
+        }
+        outputs.push((choices, negated));
+    }
+
+    match sat::solve(formula, Some(deadline)) {
+        SatResult::Sat(model) => {
+            let is_true = |lit: Lit| model[lit.index()] == lit;
+            let gates = selections
+                .iter()
+                .zip(&tables)
+                .map(|(selection, table)| {
+                    let &(j, l, _) = selection.iter().find(|&&(_, _, sel)| is_true(sel)).unwrap();
+                    let k = table.iter().position(|&entry| is_true(entry)).unwrap();
+                    (
+                        Signal {
+                            node: j,
+                            negated: k & 1 == 0,
+                        },
+                        Signal {
+                            node: l,
+                            negated: k & 2 == 0,
+                        },
+                    )
+                })
+                .collect();
+            let outputs = outputs
+                .iter()
+                .map(|(choices, negated)| Signal {
+                    node: choices.iter().position(|&choice| is_true(choice)).unwrap(),
+                    negated: is_true(*negated),
+                })
+                .collect();
+            Ok(Some(ExactCircuit { gates, outputs }))
+        }
+        SatResult::Unsat => Ok(None),
+        SatResult::Unknown => {
+            debug!("Time budget for exact synthesis exhausted");
+            Err(())
+        }
+    }
+}
+
+/// Tests for exact synthesis.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evaluates the signal of a circuit for the valuation given by the row of a truth table.
+    fn evaluate(circuit: &ExactCircuit, num_vars: usize, signal: Signal, row: usize) -> bool {
+        let value = if signal.node == 0 {
+            false
+        } else if signal.node <= num_vars {
+            (row >> (signal.node - 1)) & 1 == 1
+        } else {
+            let (a, b) = circuit.gates[signal.node - num_vars - 1];
+            evaluate(circuit, num_vars, a, row) && evaluate(circuit, num_vars, b, row)
+        };
+        value ^ signal.negated
+    }
+
+    /// Test that exact synthesis finds minimum circuits for small functions.
+    #[test]
+    fn test_synthesize() {
+        let xor = vec![false, true, true, false];
+        let nand = vec![true, true, true, false];
+        let var = vec![false, true, false, true];
+        let functions = vec![xor, nand, var];
+        let circuit = synthesize(2, &functions, 10, None).unwrap();
+        assert_eq!(circuit.gates.len(), 3);
+        for (function, &signal) in functions.iter().zip(&circuit.outputs) {
+            for (row, &expected) in function.iter().enumerate() {
+                assert_eq!(evaluate(&circuit, 2, signal, row), expected);
+            }
+        }
+        assert!(synthesize(2, &functions, 3, None).is_none());
+        assert!(synthesize(2, &functions, 10, Some(Instant::now())).is_none());
+    }
+}
@@ -0,0 +1,561 @@
+//! Factoring of aiger circuits into hierarchical controllers, which consist of a state
+//! module with the latches of the circuit and a combinational module for each output.
+//!
+//! The state module reads the inputs on which the next values of the latches depend
+//! and provides the values of all latches, and the module of an output reads the
+//! inputs and latches on which the output depends. The modules are connected by the
+//! names of the signals, where inputs and outputs keep the names of the circuit and
+//! latches are named after the latches of the circuit, made distinct from the inputs
+//! and outputs. Gates are not shared between the modules, such that each module can
+//! be reviewed and placed on its own.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use aiger::{aiger_lit2var, aiger_sign, Aiger, SymbolKind};
+use log::info;
+
+use super::aiger::{topological_order, AigerController};
+
+/// The keywords of Verilog, which are escaped when used as the name of a signal.
+const KEYWORDS: &[&str] = &[
+    "always",
+    "and",
+    "assign",
+    "begin",
+    "buf",
+    "case",
+    "default",
+    "else",
+    "end",
+    "endcase",
+    "endfunction",
+    "endmodule",
+    "for",
+    "function",
+    "if",
+    "initial",
+    "inout",
+    "input",
+    "integer",
+    "module",
+    "nand",
+    "negedge",
+    "nor",
+    "not",
+    "or",
+    "output",
+    "parameter",
+    "posedge",
+    "reg",
+    "signed",
+    "supply0",
+    "supply1",
+    "task",
+    "tri",
+    "wire",
+    "xnor",
+    "xor",
+];
+
+/// A controller as a hierarchical netlist of a state module with the latches and a
+/// combinational module for each output, obtained by factoring an aiger circuit.
+///
+/// Each module is an aiger circuit on its own, which keeps the literals of the original
+/// circuit, where the latches are inputs of the output modules and outputs of the state
+/// module. The controller is written as Verilog modules with a top module connecting
+/// them.
+#[derive(Clone)]
+pub struct HierarchicalController {
+    inputs: Vec<String>,
+    state: AigerController,
+    outputs: Vec<AigerController>,
+}
+
+/// Returns for each variable whether one of the given literals depends on the variable.
+fn cone<I: IntoIterator<Item = u32>>(gates: &[Option<(u32, u32)>], roots: I) -> Vec<bool> {
+    let mut reached = vec![false; gates.len()];
+    let mut stack: Vec<_> = roots
+        .into_iter()
+        .map(|lit| aiger_lit2var(lit) as usize)
+        .collect();
+    while let Some(var) = stack.pop() {
+        if reached[var] {
+            continue;
+        }
+        reached[var] = true;
+        if let Some((rhs0, rhs1)) = gates[var] {
+            stack.push(aiger_lit2var(rhs0) as usize);
+            stack.push(aiger_lit2var(rhs1) as usize);
+        }
+    }
+    reached
+}
+
+/// Returns the given names, where missing names are replaced by the prefix followed
+/// by the index.
+fn names_or_default(names: Vec<Option<String>>, prefix: &str) -> Vec<String> {
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| name.unwrap_or_else(|| format!("{}{}", prefix, i)))
+        .collect()
+}
+
+impl AigerController {
+    /// Factors this controller into a hierarchical controller with a state module
+    /// for the latches and a combinational module for each output.
+    pub fn factor(&self) -> HierarchicalController {
+        let inputs: Vec<_> = self
+            .aig
+            .input_literals()
+            .into_iter()
+            .zip(names_or_default(
+                self.aig.symbol_names(SymbolKind::Input),
+                "i",
+            ))
+            .collect();
+        let outputs: Vec<_> = self
+            .aig
+            .output_literals()
+            .into_iter()
+            .zip(names_or_default(
+                self.aig.symbol_names(SymbolKind::Output),
+                "o",
+            ))
+            .collect();
+        // latches are signals between the modules and need names distinct from all others
+        let mut used: HashSet<_> = inputs
+            .iter()
+            .chain(&outputs)
+            .map(|(_, name)| name.clone())
+            .collect();
+        let latch_names = names_or_default(self.aig.symbol_names(SymbolKind::Latch), "l");
+        let latches: Vec<_> = self
+            .aig
+            .latch_literals()
+            .into_iter()
+            .zip(latch_names)
+            .map(|(latch, mut name)| {
+                while used.contains(&name) {
+                    name.push('_');
+                }
+                used.insert(name.clone());
+                (latch, name)
+            })
+            .collect();
+        let mut gates = vec![None; self.aig.maxvar() as usize + 1];
+        for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+        let add_gates = |aig: &mut Aiger, reached: &[bool]| {
+            for (lhs, rhs0, rhs1) in self.aig.and_literals() {
+                if reached[aiger_lit2var(lhs) as usize] {
+                    aig.add_and(lhs, rhs0, rhs1);
+                }
+            }
+        };
+
+        let reached = cone(&gates, latches.iter().map(|&((_, next, _), _)| next));
+        let mut aig = Aiger::new().unwrap();
+        for (lit, name) in &inputs {
+            if reached[aiger_lit2var(*lit) as usize] {
+                aig.add_input(*lit, Some(name.as_str()));
+            }
+        }
+        for &((lit, next, reset), ref name) in &latches {
+            aig.add_latch(lit, next, Some(name.as_str()));
+            aig.add_reset(lit, reset);
+        }
+        add_gates(&mut aig, &reached);
+        for &((lit, _, _), ref name) in &latches {
+            aig.add_output(lit, Some(name.as_str()));
+        }
+        let state = AigerController::new(aig);
+
+        let outputs: Vec<_> = outputs
+            .iter()
+            .map(|(output, output_name)| {
+                let reached = cone(&gates, Some(*output));
+                let mut aig = Aiger::new().unwrap();
+                let signals = inputs
+                    .iter()
+                    .map(|(lit, name)| (*lit, name))
+                    .chain(latches.iter().map(|((lit, _, _), name)| (*lit, name)));
+                for (lit, name) in signals {
+                    if reached[aiger_lit2var(lit) as usize] {
+                        aig.add_input(lit, Some(name.as_str()));
+                    }
+                }
+                add_gates(&mut aig, &reached);
+                aig.add_output(*output, Some(output_name.as_str()));
+                AigerController::new(aig)
+            })
+            .collect();
+        info!(
+            "Factored aiger circuit into state module with {} gates and {} output modules \
+            with {} gates",
+            state.aig.num_ands(),
+            outputs.len(),
+            outputs.iter().map(|o| o.aig.num_ands()).sum::<u32>()
+        );
+        HierarchicalController {
+            inputs: inputs.into_iter().map(|(_, name)| name).collect(),
+            state,
+            outputs,
+        }
+    }
+}
+
+/// Returns the Verilog identifier for the given name, which is escaped if the name is
+/// not a simple identifier or a keyword.
+fn identifier(name: &str) -> String {
+    let simple = name
+        .chars()
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if simple && !KEYWORDS.contains(&name) {
+        name.to_string()
+    } else {
+        format!("\\{} ", name.replace(char::is_whitespace, "_"))
+    }
+}
+
+/// The signals of a Verilog module, where inputs and latches have identifiers and and
+/// gates are wires named by a prefix followed by their variable.
+struct Signals<'a> {
+    names: HashMap<u32, String>,
+    prefix: &'a str,
+}
+
+impl Signals<'_> {
+    /// Returns the expression for the given literal.
+    fn expression(&self, lit: u32) -> String {
+        let negation = if aiger_sign(lit) == 1 { "~" } else { "" };
+        match aiger_lit2var(lit) {
+            0 if aiger_sign(lit) == 1 => "1'b1".to_string(),
+            0 => "1'b0".to_string(),
+            var => match self.names.get(&var) {
+                Some(name) => format!("{}{}", negation, name),
+                None => format!("{}{}{}", negation, self.prefix, var),
+            },
+        }
+    }
+
+    /// Writes the declarations and assignments of the wires for the gates of the circuit.
+    fn write_gates<W: Write>(&self, writer: &mut W, aig: &Aiger) -> io::Result<()> {
+        let mut gates = vec![None; aig.maxvar() as usize + 1];
+        for (lhs, rhs0, rhs1) in aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+        let order = topological_order(&gates);
+        for v in &order {
+            writeln!(writer, "  wire {}{};", self.prefix, v)?;
+        }
+        for v in order {
+            let (rhs0, rhs1) = gates[v].unwrap();
+            writeln!(
+                writer,
+                "  assign {}{} = {} & {};",
+                self.prefix,
+                v,
+                self.expression(rhs0),
+                self.expression(rhs1)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the identifiers of the signals of the given kind of the circuit.
+fn identifiers(aig: &Aiger, kind: SymbolKind) -> Vec<String> {
+    aig.symbol_names(kind)
+        .into_iter()
+        .map(|name| identifier(&name.expect("signal of module without name")))
+        .collect()
+}
+
+impl HierarchicalController {
+    /// Returns the state module, whose inputs are the inputs of the controller on which
+    /// the next values of the latches depend and whose outputs are the latches.
+    pub fn state_module(&self) -> &AigerController {
+        &self.state
+    }
+
+    /// Returns the combinational module for each output, whose inputs are the inputs
+    /// and latches of the controller on which the output depends.
+    pub fn output_modules(&self) -> &[AigerController] {
+        &self.outputs
+    }
+
+    /// Writes the controller as Verilog modules to the given writer.
+    ///
+    /// The state module `controller_state` and the output modules `controller_output_<k>`
+    /// are instantiated by the top module `controller`, whose ports are a clock if the
+    /// controller has latches, followed by the inputs and outputs of the controller.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during writing.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        // the prefix for gates and instances is not the start of any signal name
+        let names: Vec<_> = self
+            .inputs
+            .iter()
+            .cloned()
+            .chain(
+                self.state
+                    .aig
+                    .symbol_names(SymbolKind::Latch)
+                    .into_iter()
+                    .flatten(),
+            )
+            .chain(
+                self.outputs
+                    .iter()
+                    .flat_map(|o| o.aig.symbol_names(SymbolKind::Output))
+                    .flatten(),
+            )
+            .collect();
+        let mut prefix = "n".to_string();
+        while names.iter().any(|name| name.starts_with(&prefix)) {
+            prefix.push('_');
+        }
+        let mut clock = "clk".to_string();
+        while names.contains(&clock) {
+            clock.push('_');
+        }
+        let has_latches = self.state.aig.num_latches() > 0;
+
+        if has_latches {
+            let aig = &self.state.aig;
+            let inputs = identifiers(aig, SymbolKind::Input);
+            let latches = identifiers(aig, SymbolKind::Latch);
+            let ports: Vec<_> = Some(&clock)
+                .into_iter()
+                .chain(&inputs)
+                .chain(&latches)
+                .collect();
+            writeln!(writer, "module controller_state({});", join(&ports))?;
+            for port in Some(&clock).into_iter().chain(&inputs) {
+                writeln!(writer, "  input {};", port)?;
+            }
+            for latch in &latches {
+                writeln!(writer, "  output reg {};", latch)?;
+            }
+            let literals = aig.input_literals().into_iter();
+            let literals = literals.chain(aig.latch_literals().into_iter().map(|(lit, _, _)| lit));
+            let signals = Signals {
+                names: literals
+                    .map(aiger_lit2var)
+                    .zip(inputs.iter().chain(&latches).cloned())
+                    .collect(),
+                prefix: &prefix,
+            };
+            signals.write_gates(&mut writer, aig)?;
+            for ((lit, _, reset), latch) in aig.latch_literals().into_iter().zip(&latches) {
+                // latches with themselves as reset value are uninitialized
+                if reset != lit {
+                    writeln!(
+                        writer,
+                        "  initial {} = {};",
+                        latch,
+                        signals.expression(reset)
+                    )?;
+                }
+            }
+            writeln!(writer, "  always @(posedge {}) begin", clock)?;
+            for ((_, next, _), latch) in aig.latch_literals().into_iter().zip(&latches) {
+                writeln!(writer, "    {} <= {};", latch, signals.expression(next))?;
+            }
+            writeln!(writer, "  end")?;
+            writeln!(writer, "endmodule")?;
+            writeln!(writer)?;
+        }
+
+        for (k, output) in self.outputs.iter().enumerate() {
+            let aig = &output.aig;
+            let inputs = identifiers(aig, SymbolKind::Input);
+            let outputs = identifiers(aig, SymbolKind::Output);
+            let ports: Vec<_> = inputs.iter().chain(&outputs).collect();
+            writeln!(writer, "module controller_output_{}({});", k, join(&ports))?;
+            for input in &inputs {
+                writeln!(writer, "  input {};", input)?;
+            }
+            writeln!(writer, "  output {};", outputs[0])?;
+            let signals = Signals {
+                names: aig
+                    .input_literals()
+                    .into_iter()
+                    .map(aiger_lit2var)
+                    .zip(inputs.iter().cloned())
+                    .collect(),
+                prefix: &prefix,
+            };
+            signals.write_gates(&mut writer, aig)?;
+            let lit = aig.output_literals()[0];
+            writeln!(
+                writer,
+                "  assign {} = {};",
+                outputs[0],
+                signals.expression(lit)
+            )?;
+            writeln!(writer, "endmodule")?;
+            writeln!(writer)?;
+        }
+
+        let clock = identifier(&clock);
+        let inputs: Vec<_> = self.inputs.iter().map(|name| identifier(name)).collect();
+        let outputs: Vec<_> = self
+            .outputs
+            .iter()
+            .map(|o| identifiers(&o.aig, SymbolKind::Output).remove(0))
+            .collect();
+        let clock_port = Some(&clock).filter(|_| has_latches);
+        let ports: Vec<_> = clock_port
+            .into_iter()
+            .chain(&inputs)
+            .chain(&outputs)
+            .collect();
+        writeln!(writer, "module controller({});", join(&ports))?;
+        for port in clock_port.into_iter().chain(&inputs) {
+            writeln!(writer, "  input {};", port)?;
+        }
+        for output in &outputs {
+            writeln!(writer, "  output {};", output)?;
+        }
+        let latches = identifiers(&self.state.aig, SymbolKind::Latch);
+        for latch in &latches {
+            writeln!(writer, "  wire {};", latch)?;
+        }
+        if has_latches {
+            let inputs = identifiers(&self.state.aig, SymbolKind::Input);
+            let ports: Vec<_> = Some(&clock)
+                .into_iter()
+                .chain(&inputs)
+                .chain(&latches)
+                .collect();
+            writeln!(
+                writer,
+                "  controller_state {}_state({});",
+                prefix,
+                connections(&ports)
+            )?;
+        }
+        for (k, output) in self.outputs.iter().enumerate() {
+            let aig = &output.aig;
+            let inputs = identifiers(aig, SymbolKind::Input);
+            let outputs = identifiers(aig, SymbolKind::Output);
+            let ports: Vec<_> = inputs.iter().chain(&outputs).collect();
+            writeln!(
+                writer,
+                "  controller_output_{} {}_output_{}({});",
+                k,
+                prefix,
+                k,
+                connections(&ports)
+            )?;
+        }
+        writeln!(writer, "endmodule")?;
+        info!(
+            "Written hierarchical controller with {} latches and {} output modules",
+            latches.len(),
+            self.outputs.len()
+        );
+        Ok(())
+    }
+}
+
+/// Returns the given ports separated by commas.
+fn join(ports: &[&String]) -> String {
+    ports
+        .iter()
+        .map(|port| port.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns the connections of the given ports of an instance to the signals with the
+/// same name.
+fn connections(ports: &[&String]) -> String {
+    ports
+        .iter()
+        .map(|port| format!(".{}({})", port, port))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a factored controller behaves like the circuit and is written as Verilog.
+    #[test]
+    fn test_factor() {
+        let aag = "aag 3 1 1 1 1\n2\n4 6\n6\n6 2 5\ni0 a\no0 b\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        let hierarchy = controller.factor();
+        let state = hierarchy.state_module();
+        let outputs = hierarchy.output_modules();
+        assert_eq!(state.num_inputs(), 1);
+        assert_eq!(state.num_outputs(), 1);
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].num_inputs(), 2);
+        for &latch in &[false, true] {
+            for &input in &[false, true] {
+                let (expected_outputs, expected_next) = controller.step(&[latch], &[input]);
+                let (_, next) = state.step(&[latch], &[input]);
+                let (outputs, _) = outputs[0].step(&[], &[input, latch]);
+                assert_eq!(next, expected_next);
+                assert_eq!(outputs, expected_outputs);
+            }
+        }
+
+        let mut output = Vec::new();
+        hierarchy.write(&mut output).unwrap();
+        let expected = "\
+module controller_state(clk, a, l0);
+  input clk;
+  input a;
+  output reg l0;
+  wire n3;
+  assign n3 = a & ~l0;
+  initial l0 = 1'b0;
+  always @(posedge clk) begin
+    l0 <= n3;
+  end
+endmodule
+
+module controller_output_0(a, l0, b);
+  input a;
+  input l0;
+  output b;
+  wire n3;
+  assign n3 = a & ~l0;
+  assign b = n3;
+endmodule
+
+module controller(clk, a, b);
+  input clk;
+  input a;
+  output b;
+  wire l0;
+  controller_state n_state(.clk(clk), .a(a), .l0(l0));
+  controller_output_0 n_output_0(.a(a), .l0(l0), .b(b));
+endmodule
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    /// Test that names which are not simple identifiers are escaped.
+    #[test]
+    fn test_identifier() {
+        assert_eq!(identifier("a_1"), "a_1");
+        assert_eq!(identifier("input"), "\\input ");
+        assert_eq!(identifier("x[0]"), "\\x[0] ");
+        assert_eq!(identifier("0a"), "\\0a ");
+    }
+}
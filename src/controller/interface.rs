@@ -0,0 +1,116 @@
+//! Reports on the inputs and outputs of the specification that are actually needed by
+//! a controller, together with the mapping of a reduced interface of an aiger circuit,
+//! which omits inputs not read and outputs driven by constants, to the original interface.
+
+use std::fmt;
+
+use owl::formula::AtomicPropositionStatus;
+
+/// The usage of an input or output by a controller.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignalUsage {
+    /// The input is read or the output is driven by the controller.
+    Used,
+    /// The input is not read by the controller, i.e. no output or latch depends on it.
+    Unread,
+    /// The output is driven by a constant with the given value.
+    Constant(bool),
+}
+
+impl fmt::Display for SignalUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Used => write!(f, "used"),
+            Self::Unread => write!(f, "unread"),
+            Self::Constant(value) => write!(f, "constant {}", u8::from(*value)),
+        }
+    }
+}
+
+/// An entry of an [`InterfaceReport`] for one input or output of a controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalReport {
+    /// The name of the input or output.
+    pub name: String,
+    /// Whether the signal is an input or an output.
+    pub is_input: bool,
+    /// The status of the proposition after realizability simplification, if the signal
+    /// is a proposition of the specification and the status is known.
+    pub status: Option<AtomicPropositionStatus>,
+    /// The usage of the signal by the controller.
+    pub usage: SignalUsage,
+    /// The index of the signal among the inputs or outputs of the reduced interface,
+    /// or `None` if it is omitted from the reduced interface.
+    pub reduced_index: Option<usize>,
+}
+
+impl fmt::Display for SignalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.is_input { "input" } else { "output" };
+        write!(f, "{} {}: {}", kind, self.name, self.usage)?;
+        match self.status {
+            Some(AtomicPropositionStatus::Unused) => write!(f, ", unused in specification")?,
+            Some(AtomicPropositionStatus::True) => write!(f, ", only positive in specification")?,
+            Some(AtomicPropositionStatus::False) => write!(f, ", only negative in specification")?,
+            Some(AtomicPropositionStatus::Used) | None => (),
+        }
+        match self.reduced_index {
+            Some(index) => write!(f, " -> {} {}", kind, index),
+            None => write!(f, " -> removed"),
+        }
+    }
+}
+
+/// A report on the inputs and outputs needed by a controller in the form of an aiger
+/// circuit, which also maps the reduced interface to the original interface,
+/// see [`AigerController::interface_report`](super::AigerController::interface_report).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceReport {
+    signals: Vec<SignalReport>,
+}
+
+impl InterfaceReport {
+    pub(crate) fn new(signals: Vec<SignalReport>) -> Self {
+        Self { signals }
+    }
+
+    /// Returns the entries for all inputs followed by all outputs of the original interface.
+    pub fn signals(&self) -> &[SignalReport] {
+        &self.signals
+    }
+
+    /// Returns the entries for the inputs that are not read by the controller.
+    pub fn unread_inputs(&self) -> impl Iterator<Item = &SignalReport> {
+        self.signals
+            .iter()
+            .filter(|signal| signal.is_input && signal.usage == SignalUsage::Unread)
+    }
+
+    /// Returns the entries for the outputs that are driven by constants.
+    pub fn constant_outputs(&self) -> impl Iterator<Item = &SignalReport> {
+        self.signals
+            .iter()
+            .filter(|signal| !signal.is_input && matches!(signal.usage, SignalUsage::Constant(_)))
+    }
+
+    /// Sets the statuses of the signals after realizability simplification from the
+    /// statuses of the propositions with the given names.
+    pub(crate) fn set_statuses(&mut self, names: &[&str], statuses: &[AtomicPropositionStatus]) {
+        for signal in &mut self.signals {
+            signal.status = names
+                .iter()
+                .zip(statuses)
+                .find(|(name, _)| **name == signal.name)
+                .map(|(_, &status)| status);
+        }
+    }
+}
+
+impl fmt::Display for InterfaceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for signal in &self.signals {
+            writeln!(f, "{}", signal)?;
+        }
+        Ok(())
+    }
+}
@@ -2,9 +2,12 @@
 
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt;
 use std::hash::Hash;
+use std::io;
 use std::iter;
+use std::num::NonZeroU32;
 use std::ops::Index;
 
 use log::debug;
@@ -12,6 +15,8 @@ use log::debug;
 use owl::automaton::{MaxEvenDpa, StateIndex, ZielonkaNormalFormState};
 use owl::tree::TreeIndex;
 
+use crate::parity::game::binary::{read_number, write_number, BinaryLabel};
+
 /// A label referencing a state in an automaton
 /// and a node in the edge tree of that state.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -24,11 +29,19 @@ pub struct AutomatonTreeLabel {
 
 impl std::fmt::Display for AutomatonTreeLabel {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "({}, {})", self.automaton_state, self.tree_index)
+        if self.is_initial_choice() {
+            write!(f, "initial")
+        } else {
+            write!(f, "({}, {})", self.automaton_state, self.tree_index)
+        }
     }
 }
 
 impl AutomatonTreeLabel {
+    /// The label of the node at which the environment chooses among several initial
+    /// states of the automaton, which does not refer to a node of an edge tree.
+    pub(crate) const INITIAL_CHOICE: Self = Self::new(StateIndex::BOTTOM, TreeIndex::NONE);
+
     pub(crate) const fn new(automaton_state: StateIndex, tree_index: TreeIndex) -> Self {
         Self {
             automaton_state,
@@ -36,6 +49,12 @@ impl AutomatonTreeLabel {
         }
     }
 
+    /// Returns whether this is the label of the node at which the environment chooses
+    /// among several initial states of the automaton.
+    pub fn is_initial_choice(&self) -> bool {
+        *self == Self::INITIAL_CHOICE
+    }
+
     /// Returns the index of the state of the automaton in this label.
     pub const fn automaton_state(&self) -> StateIndex {
         self.automaton_state
@@ -45,6 +64,120 @@ impl AutomatonTreeLabel {
     pub const fn tree_index(&self) -> TreeIndex {
         self.tree_index
     }
+
+    /// Returns the number of the state in this label, which is `0` for the top sink,
+    /// `1` for the bottom sink and the index of the state plus two otherwise.
+    fn state_number(&self) -> usize {
+        match self.automaton_state.index() {
+            Some(index) => index + 2,
+            None if self.automaton_state == StateIndex::TOP => 0,
+            None => 1,
+        }
+    }
+
+    /// Returns the number of the tree node in this label, which is `0` for no node
+    /// and the index of the node plus one otherwise.
+    fn tree_number(&self) -> usize {
+        if self.tree_index == TreeIndex::NONE {
+            0
+        } else {
+            self.tree_index.index() + 1
+        }
+    }
+}
+
+/// Labels are written as the state with `0` and `1` for the top and bottom sink states
+/// and the number of any other state plus two, followed by the tree index plus one,
+/// or `0` for the initial choice.
+impl BinaryLabel for AutomatonTreeLabel {
+    fn write_label<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_number(writer, self.state_number() as u64)?;
+        write_number(writer, self.tree_number() as u64)
+    }
+
+    fn read_label<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidData, "label too large");
+        let automaton_state = match read_number(reader)? {
+            0 => StateIndex::TOP,
+            1 => StateIndex::BOTTOM,
+            state => usize::try_from(state - 2)
+                .ok()
+                .and_then(StateIndex::from_index)
+                .ok_or_else(too_large)?,
+        };
+        let tree_index = match read_number(reader)? {
+            0 => TreeIndex::NONE,
+            index => usize::try_from(index - 1)
+                .map(TreeIndex::from_index)
+                .map_err(|_| too_large())?,
+        };
+        Ok(Self::new(automaton_state, tree_index))
+    }
+}
+
+/// A handle for an [`AutomatonTreeLabel`] interned in a [`LabelTable`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub(crate) struct LabelHandle(NonZeroU32);
+
+impl LabelHandle {
+    /// Returns the index of the label in the table.
+    fn index(self) -> usize {
+        self.0.get() as usize - 1
+    }
+}
+
+/// A table of interned automaton tree labels, which are referenced by handles.
+///
+/// Each label is stored once in the table, and labels are looked up by the numbers
+/// of their state and tree node instead of by hashing, so that games can store and
+/// hash the four-byte handles instead of the labels.
+#[derive(Debug, Default)]
+pub(crate) struct LabelTable {
+    labels: Vec<AutomatonTreeLabel>,
+    /// The handles of the labels, indexed by the number of the state and then
+    /// by the number of the tree node.
+    handles: Vec<Vec<Option<LabelHandle>>>,
+}
+
+impl LabelTable {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the handle of the given label, and interns the label if it is not
+    /// yet in the table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table already contains `u32::MAX` labels.
+    pub(crate) fn intern(&mut self, label: AutomatonTreeLabel) -> LabelHandle {
+        let state = label.state_number();
+        let tree = label.tree_number();
+        if state >= self.handles.len() {
+            self.handles.resize(state + 1, Vec::new());
+        }
+        let state_handles = &mut self.handles[state];
+        if tree >= state_handles.len() {
+            state_handles.resize(tree + 1, None);
+        }
+        let labels = &mut self.labels;
+        *state_handles[tree].get_or_insert_with(|| {
+            let handle = u32::try_from(labels.len() + 1)
+                .ok()
+                .and_then(NonZeroU32::new)
+                .expect("too many labels");
+            labels.push(label);
+            LabelHandle(handle)
+        })
+    }
+}
+
+impl Index<LabelHandle> for LabelTable {
+    type Output = AutomatonTreeLabel;
+
+    fn index(&self, handle: LabelHandle) -> &Self::Output {
+        &self.labels[handle.index()]
+    }
 }
 
 /// The type for the concrete value of a component in a [`StructuredLabel`].
@@ -114,7 +247,7 @@ pub struct StructuredLabel {
 }
 
 impl StructuredLabel {
-    fn new(label: Vec<LabelValue>) -> Self {
+    pub(crate) fn new(label: Vec<LabelValue>) -> Self {
         Self { label }
     }
 
@@ -158,6 +291,45 @@ impl fmt::Display for StructuredLabel {
     }
 }
 
+/// An error which can be returned when parsing a structured label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLabelError(String);
+
+impl fmt::Display for ParseLabelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid structured label '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParseLabelError {}
+
+impl std::str::FromStr for StructuredLabel {
+    type Err = ParseLabelError;
+
+    /// Parses a structured label in the format in which it is displayed, i.e. the values
+    /// of its components separated by commas between brackets, where `-` is a don't care
+    /// value, e.g. `[0,-,3]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseLabelError(s.to_string());
+        let inner = s
+            .trim()
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or_else(error)?;
+        if inner.trim().is_empty() {
+            return Ok(Self::new(Vec::new()));
+        }
+        inner
+            .split(',')
+            .map(|value| match value.trim() {
+                "-" => Ok(LabelValue::DontCare),
+                value => value.parse().map(LabelValue::Value).map_err(|_| error()),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::new)
+    }
+}
+
 pub(crate) trait Labelling<L> {
     /// Prepare the labels for the state indices in the given iterator.
     fn prepare_labels<'a, I: Iterator<Item = &'a L>>(&'a mut self, label_iter: I)
@@ -196,21 +368,70 @@ impl<L: Clone + Eq + Hash> Labelling<L> for SimpleLabelling<L> {
     }
 }
 
+/// The function to obtain structured labels from the features of automaton states.
+type FeatureLabelling =
+    fn(&HashMap<StateIndex, ZielonkaNormalFormState>) -> HashMap<StateIndex, StructuredLabel>;
+
 pub(crate) struct AutomatonLabelling<'a, A> {
     automaton: &'a A,
+    labelling: FeatureLabelling,
+    /// Whether the score of each state is added as the last label component.
+    scores: bool,
     feature_map: HashMap<StateIndex, StructuredLabel>,
 }
 
 impl<'a, A> AutomatonLabelling<'a, A> {
+    /// Creates a labelling where each component of the normal form of a state,
+    /// including each bit of the temporal profiles, is a separate label component.
     pub(crate) fn new(automaton: &'a A) -> Self {
         AutomatonLabelling {
             automaton,
+            labelling: zielonka_normal_form_to_labelling,
+            scores: false,
+            feature_map: HashMap::new(),
+        }
+    }
+
+    /// Creates a labelling as [`AutomatonLabelling::new`], where the score of each state
+    /// in percent is added as an additional last label component, or a don't care
+    /// for states without a score.
+    pub(crate) fn with_scores(automaton: &'a A) -> Self {
+        AutomatonLabelling {
+            scores: true,
+            ..Self::new(automaton)
+        }
+    }
+
+    /// Creates a labelling that follows the product structure of the automaton,
+    /// where the entry for each subformula in the normal form of a state
+    /// is enumerated as a single label component.
+    pub(crate) fn with_inner_structure(automaton: &'a A) -> Self {
+        AutomatonLabelling {
+            automaton,
+            labelling: zielonka_normal_form_to_inner_labelling,
+            scores: false,
             feature_map: HashMap::new(),
         }
     }
 }
 
 impl<'a, A: MaxEvenDpa> AutomatonLabelling<'a, A> {
+    /// Computes the labels of the states from their features.
+    fn compute_feature_map(&mut self, features: &HashMap<StateIndex, ZielonkaNormalFormState>) {
+        self.feature_map = (self.labelling)(features);
+        if self.scores {
+            for (&state, label) in &mut self.feature_map {
+                let score = self
+                    .automaton
+                    .state_score(state)
+                    .map_or(LabelValue::DontCare, |score| {
+                        LabelValue::Value((score * 100.0).round() as LabelInnerValue)
+                    });
+                label.label.push(score);
+            }
+        }
+    }
+
     fn get_label(&self, states: &[StateIndex]) -> StructuredLabel {
         let mut values = Vec::new();
         for index in states {
@@ -223,7 +444,7 @@ impl<'a, A: MaxEvenDpa> AutomatonLabelling<'a, A> {
 impl<'a, A: MaxEvenDpa> Labelling<StateIndex> for AutomatonLabelling<'a, A> {
     fn prepare_labels<'b, I: Iterator<Item = &'b StateIndex>>(&'b mut self, iter: I) {
         let features = self.automaton.extract_features(iter);
-        self.feature_map = zielonka_normal_form_to_labelling(&features);
+        self.compute_feature_map(&features);
     }
 
     fn get_label(&self, index: &StateIndex) -> StructuredLabel {
@@ -234,7 +455,7 @@ impl<'a, A: MaxEvenDpa> Labelling<StateIndex> for AutomatonLabelling<'a, A> {
 impl<'a, A: MaxEvenDpa> Labelling<Vec<StateIndex>> for AutomatonLabelling<'a, A> {
     fn prepare_labels<'b, I: Iterator<Item = &'b Vec<StateIndex>>>(&'b mut self, iter: I) {
         let features = self.automaton.extract_features(iter.flat_map(|s| s.iter()));
-        self.feature_map = zielonka_normal_form_to_labelling(&features);
+        self.compute_feature_map(&features);
     }
 
     fn get_label(&self, indices: &Vec<StateIndex>) -> StructuredLabel {
@@ -350,3 +571,102 @@ fn zielonka_normal_form_to_labelling(
     }
     map
 }
+
+/// Transforms a list of states in normal from into a list of states with
+/// a structured label reflecting the product structure of the states.
+///
+/// In contrast to [`zielonka_normal_form_to_labelling`], the entry of each
+/// subformula in the state map is not split into its temporal profiles,
+/// but the distinct entries for each subformula are enumerated and
+/// form a single component of the label.
+fn zielonka_normal_form_to_inner_labelling(
+    state_features: &HashMap<StateIndex, ZielonkaNormalFormState>,
+) -> HashMap<StateIndex, StructuredLabel> {
+    // process states in a fixed order for deterministic labels
+    let mut states: Vec<_> = state_features.keys().copied().collect();
+    states.sort();
+
+    // compute widths and enumerate entries for each subformula
+    let mut round_robin_counters_width = 0;
+    let mut zielonka_path_width = 0;
+    let mut state_map_width = 0;
+    let mut entry_values: Vec<HashMap<(i32, Vec<i32>, Vec<i32>), LabelInnerValue>> = Vec::new();
+    let mut state_entries = HashMap::with_capacity(states.len());
+    for &state in &states {
+        let feature = &state_features[&state];
+        round_robin_counters_width = std::cmp::max(
+            round_robin_counters_width,
+            feature.round_robin_counters().len(),
+        );
+        zielonka_path_width = std::cmp::max(zielonka_path_width, feature.zielonka_path().len());
+        let mut keys: Vec<_> = feature.state_map().keys().copied().collect();
+        keys.sort_unstable();
+        let mut entries = Vec::with_capacity(keys.len());
+        for key in keys {
+            let key = key as usize;
+            state_map_width = std::cmp::max(state_map_width, key + 1);
+            if entry_values.len() <= key {
+                entry_values.resize_with(key + 1, HashMap::new);
+            }
+            let entry = &feature.state_map()[&(key as i32)];
+            let mut all_profile: Vec<_> = entry.all_profile().iter().copied().collect();
+            all_profile.sort_unstable();
+            let mut rejecting_profile: Vec<_> = entry.rejecting_profile().iter().copied().collect();
+            rejecting_profile.sort_unstable();
+            let values = &mut entry_values[key];
+            let next_value = values.len() as LabelInnerValue;
+            let value = *values
+                .entry((entry.disambiguation(), all_profile, rejecting_profile))
+                .or_insert(next_value);
+            entries.push((key, value));
+        }
+        state_entries.insert(state, entries);
+    }
+    let width = 1 // state formula
+        + round_robin_counters_width
+        + zielonka_path_width
+        + state_map_width;
+
+    debug!("Inner state feature space has dimension {}", width);
+
+    let mut map = HashMap::with_capacity(states.len());
+    for state in states {
+        let features = &state_features[&state];
+        let mut vec: Vec<LabelValue> = Vec::with_capacity(width);
+        // add state formula
+        vec.push(LabelValue::Value(
+            features.state_formula() as LabelInnerValue
+        ));
+        // add round-robin counter
+        vec.extend(
+            features
+                .round_robin_counters()
+                .iter()
+                .map(|&v| LabelValue::Value(v as LabelInnerValue)),
+        );
+        vec.extend(
+            iter::repeat(LabelValue::DontCare)
+                .take(round_robin_counters_width - features.round_robin_counters().len()),
+        );
+        // add Zielonka path
+        vec.extend(
+            features
+                .zielonka_path()
+                .iter()
+                .map(|&v| LabelValue::Value(v as LabelInnerValue)),
+        );
+        vec.extend(
+            iter::repeat(LabelValue::DontCare)
+                .take(zielonka_path_width - features.zielonka_path().len()),
+        );
+        // add one component per subformula with the enumerated entry
+        let mut entries = vec![LabelValue::DontCare; state_map_width];
+        for &(key, value) in &state_entries[&state] {
+            entries[key] = LabelValue::Value(value);
+        }
+        vec.extend(entries);
+        assert_eq!(vec.len(), width);
+        map.insert(state, StructuredLabel::new(vec));
+    }
+    map
+}
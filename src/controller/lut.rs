@@ -0,0 +1,40 @@
+use std::io;
+
+use abc::LutNetwork;
+
+/// A controller as a network of lookup tables for FPGAs,
+/// obtained by technology mapping of an aiger circuit.
+pub struct LutController {
+    luts: LutNetwork,
+}
+
+impl LutController {
+    pub(super) fn new(luts: LutNetwork) -> Self {
+        Self { luts }
+    }
+
+    /// Writes the controller in BLIF format to the given writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during writing.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "{}", self.luts)
+    }
+
+    /// Returns the number of lookup tables of the controller.
+    pub fn num_luts(&self) -> usize {
+        self.luts.num_luts()
+    }
+
+    /// Returns the number of latches of the controller.
+    pub fn num_latches(&self) -> usize {
+        self.luts.num_latches()
+    }
+
+    /// Returns the maximum number of lookup tables on a path between
+    /// inputs, latches and outputs of the controller.
+    pub fn depth(&self) -> u32 {
+        self.luts.depth()
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use cudd::{Bdd, Cudd};
+use log::info;
+use owl::automaton::{Color, MaxEvenDpa, StateIndex as AutomatonStateIndex};
+use owl::tree::{Node as TreeNode, TreeIndex};
+
+use super::{LabelledMachine, StateIndex, Transition};
+use crate::parity::game::{Game, LabelledGame, Player, Region};
+use crate::parity::solver::{ParityGameSolver, ZlkSolver};
+
+/// The successors of a state of an assumption automaton, restricted
+/// to the successors from which the assumption can still be satisfied.
+struct AssumptionSuccessors {
+    /// The inputs after which the assumption can no longer be satisfied
+    /// for any output.
+    violating_inputs: Bdd,
+    /// The successors together with a BDD over the inputs and outputs leading to them.
+    successors: Vec<(Bdd, AutomatonStateIndex)>,
+}
+
+impl AssumptionSuccessors {
+    fn new<A: MaxEvenDpa>(
+        assumption: &mut A,
+        state: AutomatonStateIndex,
+        manager: &Cudd,
+        num_inputs: usize,
+    ) -> Self {
+        let tree = assumption.successors(state);
+        let violating = |index| {
+            tree.index_iter(index, None).all(|leaf| match &tree[leaf] {
+                TreeNode::Leaf(edge) => edge.successor() == AutomatonStateIndex::BOTTOM,
+                TreeNode::Inner(_) => unreachable!(),
+            })
+        };
+
+        let mut violating_inputs = manager.bdd_zero();
+        for index in tree.index_iter(TreeIndex::ROOT, Some(num_inputs)) {
+            if violating(index) {
+                violating_inputs |=
+                    tree.bdd_for_paths(manager, TreeIndex::ROOT, index, Some(num_inputs), 0);
+            }
+        }
+        let successors = tree
+            .index_iter(TreeIndex::ROOT, None)
+            .filter_map(|index| match &tree[index] {
+                TreeNode::Leaf(edge) if edge.successor() != AutomatonStateIndex::BOTTOM => {
+                    let bdd = tree.bdd_for_paths(manager, TreeIndex::ROOT, index, None, 0);
+                    Some((bdd, edge.successor()))
+                }
+                _ => None,
+            })
+            .collect();
+        Self {
+            violating_inputs,
+            successors,
+        }
+    }
+}
+
+/// A node of the product of a machine with an assumption automaton, in which a play
+/// is won by the odd player if it violates the assumption.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ProductNode {
+    /// A pair of a state of the machine and a state of the automaton.
+    State(StateIndex, AutomatonStateIndex),
+    /// An edge of the automaton with the given color to a pair of states.
+    Edge(StateIndex, AutomatonStateIndex, Color),
+    /// A sink reached once the assumption is violated, or the behavior of the
+    /// machine is not specified.
+    Violated,
+}
+
+impl<L> LabelledMachine<L> {
+    /// Returns whether some run of this machine violates the given assumption
+    /// automaton from each of the initial states.
+    ///
+    /// The product of this machine with the assumption automaton is explored as a
+    /// game in which the odd player resolves all choices, and thus wins if some run
+    /// is rejected by the automaton. Inputs of a Mealy machine and outputs of a Moore
+    /// machine without a transition are treated as violating the assumption, as the
+    /// runs of the machine are not known for them.
+    ///
+    /// The assumption automaton needs to use the inputs followed by the outputs
+    /// of this machine as atomic propositions.
+    pub(crate) fn violates_assumption<A: MaxEvenDpa>(&self, assumption: &mut A) -> bool {
+        let manager = match self.states().flat_map(|s| &s.transitions).next() {
+            Some(transition) => transition.input.manager(),
+            None => return true,
+        };
+
+        let mut game = LabelledGame::default();
+        let (violated, _) = game.add_border_node(ProductNode::Violated);
+        game.update_node(violated, Player::Odd, 1);
+        game.add_edge(violated, violated);
+        let mut automaton_successors = HashMap::new();
+        let initial_assumption = assumption.initial_state();
+        let mut initial_nodes = Vec::with_capacity(self.initial_states.len());
+        let mut queue = VecDeque::new();
+        for &state in &self.initial_states {
+            let (index, new_node) =
+                game.add_border_node(ProductNode::State(state, initial_assumption));
+            if new_node {
+                queue.push_back((index, state, initial_assumption));
+            }
+            initial_nodes.push(index);
+        }
+        game.set_initial_node(initial_nodes[0]);
+        while let Some((index, state, assumption_state)) = queue.pop_front() {
+            game.update_node(index, Player::Odd, 0);
+            let successors = automaton_successors
+                .entry(assumption_state)
+                .or_insert_with(|| {
+                    let tree = assumption.successors(assumption_state);
+                    tree.index_iter(TreeIndex::ROOT, None)
+                        .filter_map(|leaf| match &tree[leaf] {
+                            TreeNode::Leaf(edge)
+                                if edge.successor() != AutomatonStateIndex::TOP =>
+                            {
+                                let bdd =
+                                    tree.bdd_for_paths(&manager, TreeIndex::ROOT, leaf, None, 0);
+                                Some((bdd, edge.successor(), edge.color()))
+                            }
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                });
+            let transitions = &self.states[state.0].transitions;
+            let mut targets = Vec::new();
+            let mut unspecified = if self.mealy || transitions.is_empty() {
+                !transitions
+                    .iter()
+                    .fold(manager.bdd_zero(), |inputs, t| inputs | &t.input)
+            } else {
+                manager.bdd_zero()
+            };
+            for transition in transitions {
+                if !self.mealy {
+                    unspecified |= !transition
+                        .outputs
+                        .iter()
+                        .fold(manager.bdd_zero(), |outputs, o| outputs | &o.output);
+                }
+                for output in &transition.outputs {
+                    let step = &transition.input & &output.output;
+                    for (bdd, successor, color) in successors.iter() {
+                        if (&step & bdd).is_zero() {
+                            continue;
+                        }
+                        if *successor == AutomatonStateIndex::BOTTOM {
+                            targets.push(violated);
+                            continue;
+                        }
+                        let (edge_index, new_edge) = game.add_border_node(ProductNode::Edge(
+                            output.successor,
+                            *successor,
+                            *color,
+                        ));
+                        if new_edge {
+                            game.update_node(edge_index, Player::Odd, *color);
+                            let (target_index, new_target) = game
+                                .add_border_node(ProductNode::State(output.successor, *successor));
+                            if new_target {
+                                queue.push_back((target_index, output.successor, *successor));
+                            }
+                            game.add_edge(edge_index, target_index);
+                        }
+                        targets.push(edge_index);
+                    }
+                }
+            }
+            if !unspecified.is_zero() {
+                targets.push(violated);
+            }
+            targets.sort_unstable();
+            targets.dedup();
+            for target in targets {
+                game.add_edge(index, target);
+            }
+        }
+        // runs staying in the accepting sink of the automaton satisfy the assumption
+        game.complete_deadlocks(Player::Even);
+
+        let disabled = Region::with_capacity(game.num_nodes());
+        let (violating, _) = ZlkSolver::new().solve(&game, &disabled, Player::Odd, false);
+        initial_nodes.iter().all(|&index| violating[index])
+    }
+
+    /// Adds don't cares to this machine for inputs after which the given
+    /// assumption automaton can no longer be satisfied.
+    ///
+    /// The product of this machine with the assumption automaton is explored from
+    /// the initial states, where runs are not continued once the assumption is violated.
+    /// An input is a don't care for a state if it violates the assumption in all
+    /// automaton states paired with the state in the product. The outputs for
+    /// don't care inputs are replaced with arbitrary outputs, while the successors
+    /// are kept to preserve the structure of the machine for minimization.
+    /// The don't care inputs are additionally recorded for each state, so that
+    /// both outputs and successors are arbitrary for them when creating BDDs.
+    ///
+    /// The assumption automaton needs to use the inputs followed by the outputs
+    /// of this machine as atomic propositions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this machine is not a Mealy machine.
+    pub(crate) fn add_assumption_dontcares<A: MaxEvenDpa>(&mut self, assumption: &mut A) {
+        assert!(self.mealy, "assumption don't cares require a mealy machine");
+        let manager = match self.states().flat_map(|s| &s.transitions).next() {
+            Some(transition) => transition.input.manager(),
+            None => return,
+        };
+        let num_inputs = self.num_inputs();
+
+        // explore product of machine and assumption automaton
+        let mut care_inputs = vec![manager.bdd_zero(); self.num_states()];
+        let mut assumption_successors = HashMap::new();
+        let initial_assumption = assumption.initial_state();
+        let mut queue: VecDeque<_> = self
+            .initial_states
+            .iter()
+            .map(|&state| (state, initial_assumption))
+            .collect();
+        let mut visited: HashSet<_> = queue.iter().copied().collect();
+        while let Some((state, assumption_state)) = queue.pop_front() {
+            let successors = assumption_successors
+                .entry(assumption_state)
+                .or_insert_with(|| {
+                    AssumptionSuccessors::new(assumption, assumption_state, &manager, num_inputs)
+                });
+            care_inputs[state.0] |= !&successors.violating_inputs;
+            for transition in &self.states[state.0].transitions {
+                for output in &transition.outputs {
+                    let step = &transition.input & &output.output;
+                    for (bdd, successor) in &successors.successors {
+                        let pair = (output.successor, *successor);
+                        if !visited.contains(&pair) && !(&step & bdd).is_zero() {
+                            visited.insert(pair);
+                            queue.push_back(pair);
+                        }
+                    }
+                }
+            }
+        }
+
+        // replace outputs for don't care inputs
+        let mut num_dontcares = 0;
+        for (state, care) in self.states.iter_mut().zip(care_inputs) {
+            state.dontcare_inputs = Some(!&care);
+            let mut transitions = Vec::with_capacity(state.transitions.len());
+            for transition in state.transitions.drain(..) {
+                let dontcare_input = &transition.input & !&care;
+                if dontcare_input.is_zero() {
+                    transitions.push(transition);
+                    continue;
+                }
+                num_dontcares += 1;
+                let mut dontcare_transition = Transition::new(dontcare_input);
+                for output in &transition.outputs {
+                    dontcare_transition.add_output(manager.bdd_one(), output.successor);
+                }
+                let care_input = &transition.input & &care;
+                if !care_input.is_zero() {
+                    transitions.push(Transition::with_outputs(care_input, transition.outputs));
+                }
+                transitions.push(dontcare_transition);
+            }
+            state.transitions = transitions;
+        }
+        info!(
+            "Added don't cares from assumption to {} transitions using {} product states",
+            num_dontcares,
+            visited.len()
+        );
+    }
+}
+
+/// Tests for don't cares from assumptions.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use owl::automaton::Automaton;
+    use owl::formula::Ltl;
+    use owl::graal::Vm;
+
+    use aiger::AndCache;
+
+    use crate::controller::machine::LabelledMachineConstructor;
+
+    /// Test that assumptions are violated by a Moore machine if some run violates them.
+    #[test]
+    fn test_violates_assumption() {
+        let vm = Vm::new().unwrap();
+        let ap = ["a", "c"];
+        let manager = Cudd::with_vars(2).unwrap();
+        let a = manager.bdd_var(0);
+        let c = manager.bdd_var(1);
+        // the machine sets input a in the first step, and afterwards repeats output c
+        let mut constructor = LabelledMachineConstructor::new();
+        let (first, _) = constructor.add_state(0);
+        let (low, _) = constructor.add_state(1);
+        let (high, _) = constructor.add_state(2);
+        let mut t0 = Transition::new(a.clone());
+        t0.add_output(!&c, low);
+        t0.add_output(c.clone(), high);
+        let mut t1 = Transition::new(!&a);
+        t1.add_output(!&c, low);
+        t1.add_output(c.clone(), high);
+        let mut t2 = Transition::new(a.clone());
+        t2.add_output(!&c, low);
+        t2.add_output(c.clone(), high);
+        constructor.add_transition(first, t0);
+        constructor.add_transition(low, t1);
+        constructor.add_transition(high, t2);
+        let inputs = vec!["a".to_string()];
+        let outputs = vec!["c".to_string()];
+        let machine = constructor.into_machine(vec![first], inputs, outputs, false);
+
+        let violates = |ltl: &str| {
+            let formula = Ltl::parse(&vm, ltl, &ap);
+            machine.violates_assumption(&mut Automaton::of(&vm, &formula, false, 0))
+        };
+        assert!(violates("G !a"));
+        assert!(violates("G F a"));
+        assert!(violates("F G a"));
+        assert!(!violates("a"));
+        assert!(!violates("G (c <-> X a)"));
+        assert!(!violates("G F c -> G F a"));
+        assert!(!violates("G (!c -> X !a)"));
+    }
+
+    /// Test that inputs violating a safety assumption become don't cares.
+    #[test]
+    fn test_assumption_dontcares() {
+        let vm = Vm::new().unwrap();
+        let ap = ["a", "b", "c"];
+        let formula = Ltl::parse(&vm, "G !a", &ap);
+        let mut assumption = Automaton::of(&vm, &formula, false, 0);
+
+        let manager = Cudd::with_vars(3).unwrap();
+        let a = manager.bdd_var(0);
+        let b = manager.bdd_var(1);
+        let c = manager.bdd_var(2);
+        let mut constructor = LabelledMachineConstructor::new();
+        let (state, _) = constructor.add_state(0);
+        let mut t0 = Transition::new(&a & &b);
+        t0.add_output(c.clone(), state);
+        let mut t1 = Transition::new(!&a | !&b);
+        t1.add_output(!&c, state);
+        constructor.add_transition(state, t0);
+        constructor.add_transition(state, t1);
+        let inputs = vec!["a".to_string(), "b".to_string()];
+        let outputs = vec!["c".to_string()];
+        let mut machine = constructor.into_machine(vec![state], inputs, outputs, true);
+
+        machine.add_assumption_dontcares(&mut assumption);
+        let transitions = &machine.states[0].transitions;
+        assert_eq!(transitions.len(), 3);
+        assert!(transitions[0].input == &a & &b && transitions[0].outputs[0].output.is_one());
+        assert!(transitions[1].input == !&a && transitions[1].outputs[0].output == !&c);
+        assert!(transitions[2].input == &a & !&b && transitions[2].outputs[0].output.is_one());
+        assert_eq!(machine.states[0].dontcare_inputs, Some(&a & !&b));
+    }
+
+    /// Test that a recovery input added after don't cares from an assumption produces
+    /// the fallback output also for inputs that violate the assumption.
+    #[test]
+    fn test_assumption_dontcares_with_recovery() {
+        let vm = Vm::new().unwrap();
+        let ap = ["a", "b", "c"];
+        let formula = Ltl::parse(&vm, "G !a", &ap);
+        let mut assumption = Automaton::of(&vm, &formula, false, 0);
+
+        let manager = Cudd::with_vars(3).unwrap();
+        let a = manager.bdd_var(0);
+        let b = manager.bdd_var(1);
+        let c = manager.bdd_var(2);
+        let mut constructor = LabelledMachineConstructor::new();
+        let (state, _) = constructor.add_state(0);
+        let mut t0 = Transition::new(a.clone());
+        t0.add_output(c.clone(), state);
+        let mut t1 = Transition::new(!&a);
+        t1.add_output(!&c, state);
+        constructor.add_transition(state, t1);
+        constructor.add_transition(state, t0);
+        let inputs = vec!["a".to_string(), "b".to_string()];
+        let outputs = vec!["c".to_string()];
+        let mut machine = constructor.into_machine(vec![state], inputs, outputs, true);
+
+        machine.add_assumption_dontcares(&mut assumption);
+        assert!(machine.add_recovery_input("v"));
+        // inputs violating the assumption are no longer don't cares while v is set
+        let recovery_manager = Cudd::with_vars(4).unwrap();
+        let dontcare_inputs = machine.states[0].dontcare_inputs.as_ref().unwrap();
+        let dontcare_inputs = dontcare_inputs.transfer(&recovery_manager);
+        assert!(!dontcare_inputs.is_zero());
+        assert!((dontcare_inputs & recovery_manager.bdd_var(2)).is_zero());
+        // the fallback output of !a is used while v is set, also for input a
+        let aig = machine
+            .create_bdds(None, false)
+            .create_aiger(AndCache::default(), false);
+        for a in &[false, true] {
+            for b in &[false, true] {
+                assert_eq!(aig.simulate(&[vec![*a, *b, true]]), vec![vec![false]]);
+            }
+        }
+    }
+}
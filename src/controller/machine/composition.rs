@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use cudd::Cudd;
+use log::info;
+
+use super::super::labelling::{LabelValue, StructuredLabel};
+use super::super::{check_composition, CompositionError};
+use super::{map_bdd, LabelledMachine, LabelledMachineConstructor, State, StateIndex, Transition};
+
+/// Returns the values of the label padded with don't cares to the given number of components.
+fn padded(label: &StructuredLabel, components: usize) -> impl Iterator<Item = LabelValue> + '_ {
+    let padding = components - label.components();
+    label
+        .iter()
+        .copied()
+        .chain(std::iter::repeat(LabelValue::DontCare).take(padding))
+}
+
+impl LabelledMachine<StructuredLabel> {
+    /// Returns the mapping of the variables of this machine to the variables of
+    /// a composed machine with the given inputs, where the outputs of this machine
+    /// start at the given offset.
+    fn composed_vars(&self, inputs: &[String], output_offset: usize) -> Vec<usize> {
+        self.inputs
+            .iter()
+            .map(|i| inputs.iter().position(|n| n == i).unwrap())
+            .chain(output_offset..output_offset + self.num_outputs())
+            .collect()
+    }
+
+    /// Composes this machine in parallel with the other machine.
+    ///
+    /// The composed machine reads the inputs of both machines, where inputs with
+    /// the same name are shared, and produces the outputs of this machine followed
+    /// by the outputs of the other machine. Its states are the reachable pairs of
+    /// states of both machines, labelled by the components of the label of this
+    /// machine followed by the components of the label of the other machine, where
+    /// both are padded with don't cares to the same number of components for all
+    /// states of the respective machine.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the machines is a Moore machine, if both machines
+    /// have an output with the same name, or if an output of one machine is an
+    /// input of the other machine.
+    pub fn compose(&self, other: &Self) -> Result<Self, CompositionError> {
+        if !self.mealy || !other.mealy {
+            return Err(CompositionError::NotMealy);
+        }
+        check_composition(&self.inputs, &self.outputs, &other.inputs, &other.outputs)?;
+        info!(
+            "Composing machines with {} and {} states",
+            self.num_states(),
+            other.num_states()
+        );
+
+        let mut inputs = self.inputs.clone();
+        inputs.extend(
+            other
+                .inputs
+                .iter()
+                .filter(|&i| !self.inputs.contains(i))
+                .cloned(),
+        );
+        let mut outputs = self.outputs.clone();
+        outputs.extend(other.outputs.iter().cloned());
+        let manager = Cudd::with_vars(inputs.len() + outputs.len()).unwrap();
+        let vars = self.composed_vars(&inputs, inputs.len());
+        let other_vars = other.composed_vars(&inputs, inputs.len() + self.num_outputs());
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let mut queue = VecDeque::new();
+        let mut initial_states = Vec::new();
+        for &state in &self.initial_states {
+            for &other_state in &other.initial_states {
+                let (index, _) = constructor.add_state((state, other_state));
+                initial_states.push(index);
+                queue.push_back((index, state, other_state));
+            }
+        }
+        while let Some((index, state, other_state)) = queue.pop_front() {
+            for transition in &self[state].transitions {
+                let input = map_bdd(&transition.input, self.num_vars(), &vars, &manager);
+                for other_transition in &other[other_state].transitions {
+                    let other_input = map_bdd(
+                        &other_transition.input,
+                        other.num_vars(),
+                        &other_vars,
+                        &manager,
+                    );
+                    let combined_input = &input & other_input;
+                    if combined_input.is_zero() {
+                        continue;
+                    }
+                    let mut combined_transition = Transition::new(combined_input);
+                    for output in &transition.outputs {
+                        let output_bdd = map_bdd(&output.output, self.num_vars(), &vars, &manager);
+                        for other_output in &other_transition.outputs {
+                            let other_output_bdd = map_bdd(
+                                &other_output.output,
+                                other.num_vars(),
+                                &other_vars,
+                                &manager,
+                            );
+                            let successors = (output.successor, other_output.successor);
+                            let (successor, new) = constructor.add_state(successors);
+                            if new {
+                                queue.push_back((successor, successors.0, successors.1));
+                            }
+                            combined_transition
+                                .add_output(&output_bdd & other_output_bdd, successor);
+                        }
+                    }
+                    constructor.add_transition(index, combined_transition);
+                }
+            }
+        }
+        let machine = constructor.into_machine(initial_states, inputs, outputs, true);
+
+        let num_components = |m: &Self| {
+            m.labels()
+                .map(StructuredLabel::components)
+                .max()
+                .unwrap_or(0)
+        };
+        let (components, other_components) = (num_components(self), num_components(other));
+        let states = machine
+            .states
+            .into_iter()
+            .map(|state| {
+                let (index, other_index) = *state.label();
+                let values = padded(self[index].label(), components)
+                    .chain(padded(other[other_index].label(), other_components))
+                    .collect();
+                State::with_transitions(StructuredLabel::new(values), state.transitions)
+            })
+            .collect();
+        let composed = LabelledMachine {
+            states,
+            inputs: machine.inputs,
+            outputs: machine.outputs,
+            initial_states: machine.initial_states,
+            mealy: true,
+            outputs_first: machine.outputs_first,
+            symmetry: None,
+        };
+        info!("Composed machine has {} states", composed.num_states());
+        Ok(composed)
+    }
+}
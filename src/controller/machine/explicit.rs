@@ -0,0 +1,153 @@
+//! Conversion of Mealy machines to and from explicit transition tables, which give the
+//! output and successor for each state and valuation of the inputs, as used by bounded
+//! synthesis.
+
+use cudd::{CubeValue, Cudd};
+
+use super::{LabelledMachine, LabelledMachineConstructor, StateIndex, Transition};
+
+/// The maximal number of inputs of a machine that is converted to a transition table,
+/// as all valuations of the inputs are enumerated for each state.
+pub(crate) const MAX_EXPLICIT_INPUTS: usize = 16;
+
+impl<L> LabelledMachine<L> {
+    /// Returns the transition table of this machine, which gives the output valuation and
+    /// successor for the state and input valuation at index `state << num_inputs | input`,
+    /// where the initial state has index 0 and unspecified outputs are false.
+    ///
+    /// The table has no entry for inputs without a transition. Returns `None` if the
+    /// inputs or outputs of this machine differ from the given ones, if this machine is
+    /// not a Mealy machine with a single initial state, or if it has more than
+    /// [`MAX_EXPLICIT_INPUTS`] inputs.
+    pub(crate) fn explicit_transitions(
+        &self,
+        inputs: &[&str],
+        outputs: &[&str],
+    ) -> Option<Vec<Option<(usize, usize)>>> {
+        if self.inputs != inputs || self.outputs != outputs {
+            return None;
+        }
+        let num_inputs = self.num_inputs();
+        if !self.mealy || self.initial_states.len() != 1 || num_inputs > MAX_EXPLICIT_INPUTS {
+            return None;
+        }
+        let manager = match self.states().flat_map(|s| &s.transitions).next() {
+            Some(transition) => transition.input.manager(),
+            None => Cudd::with_vars(self.num_vars()).unwrap(),
+        };
+        // swap the initial state with the first state
+        let initial = self.initial_states[0].0;
+        let index = |state: usize| match state {
+            s if s == initial => 0,
+            0 => initial,
+            s => s,
+        };
+        let mut table = vec![None; self.num_states() << num_inputs];
+        for (state, s) in self.states.iter().enumerate() {
+            for valuation in 0..1_usize << num_inputs {
+                let values: Vec<_> = (0..num_inputs)
+                    .map(|i| (i, valuation >> i & 1 == 1))
+                    .collect();
+                let input = manager.bdd_cube_with_values(&values);
+                let transition = match s
+                    .transitions
+                    .iter()
+                    .find(|t| !(&t.input & &input).is_zero())
+                {
+                    Some(transition) => transition,
+                    None => continue,
+                };
+                let output = &transition.outputs[0];
+                let cube = output.output.cube_iter(self.num_vars()).next().unwrap();
+                let output_valuation = cube
+                    .iter()
+                    .skip(num_inputs)
+                    .enumerate()
+                    .filter(|(_, value)| **value == CubeValue::Set)
+                    .fold(0, |v, (o, _)| v | 1 << o);
+                table[index(state) << num_inputs | valuation] =
+                    Some((output_valuation, index(output.successor.0)));
+            }
+        }
+        Some(table)
+    }
+}
+
+impl LabelledMachine<usize> {
+    /// Creates a deterministic Mealy machine from the given transition table, see
+    /// [`LabelledMachine::explicit_transitions`], where the states are labelled with
+    /// their index in the table.
+    ///
+    /// Inputs with the same output and successor in a state share a transition.
+    pub(crate) fn from_explicit_transitions(
+        table: &[Option<(usize, usize)>],
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+    ) -> Self {
+        let num_inputs = inputs.len();
+        let num_outputs = outputs.len();
+        let manager = Cudd::with_vars(num_inputs + num_outputs).unwrap();
+        let num_states = table.len() >> num_inputs;
+        let mut constructor = LabelledMachineConstructor::new();
+        for state in 0..num_states {
+            constructor.add_state(state);
+        }
+        for state in 0..num_states {
+            let mut transitions: Vec<(usize, usize, Transition)> = Vec::new();
+            for valuation in 0..1_usize << num_inputs {
+                let (output, successor) = match table[state << num_inputs | valuation] {
+                    Some(entry) => entry,
+                    None => continue,
+                };
+                let values: Vec<_> = (0..num_inputs)
+                    .map(|i| (i, valuation >> i & 1 == 1))
+                    .collect();
+                let input = manager.bdd_cube_with_values(&values);
+                match transitions
+                    .iter_mut()
+                    .find(|(o, s, _)| *o == output && *s == successor)
+                {
+                    Some((_, _, transition)) => transition.input |= input,
+                    None => {
+                        let values: Vec<_> = (0..num_outputs)
+                            .map(|o| (num_inputs + o, output >> o & 1 == 1))
+                            .collect();
+                        let mut transition = Transition::new(input);
+                        transition.add_output(
+                            manager.bdd_cube_with_values(&values),
+                            StateIndex(successor),
+                        );
+                        transitions.push((output, successor, transition));
+                    }
+                }
+            }
+            for (_, _, transition) in transitions {
+                constructor.add_transition(StateIndex(state), transition);
+            }
+        }
+        constructor.into_machine(vec![StateIndex(0)], inputs, outputs, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a machine is converted to a transition table and back.
+    #[test]
+    fn test_explicit_transitions() {
+        // the machine outputs the input of the previous step
+        let table = vec![Some((0, 0)), Some((0, 1)), Some((1, 0)), Some((1, 1))];
+        let inputs = vec!["a".to_string()];
+        let outputs = vec!["b".to_string()];
+        let machine = LabelledMachine::from_explicit_transitions(&table, inputs, outputs);
+        assert_eq!(machine.num_states(), 2);
+        assert!(machine.is_deterministic());
+        assert_eq!(machine.explicit_transitions(&["a"], &["b"]), Some(table));
+        assert_eq!(machine.explicit_transitions(&["b"], &["a"]), None);
+
+        let mut moore = machine;
+        moore.mealy = false;
+        assert_eq!(moore.explicit_transitions(&["a"], &["b"]), None);
+    }
+}
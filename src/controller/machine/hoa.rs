@@ -0,0 +1,421 @@
+use std::fmt;
+
+use cudd::{Bdd, Cudd};
+
+use super::{LabelledMachine, LabelledMachineConstructor, State, StateIndex, Transition};
+use crate::controller::labelling::StructuredLabel;
+
+/// An error which can be returned when parsing a machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMachineError(pub(super) String);
+
+impl fmt::Display for ParseMachineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid machine: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseMachineError {}
+
+/// Parses a non-negative integer, using the given description of the value for errors.
+pub(super) fn parse_number(token: Option<&str>, what: &str) -> Result<usize, ParseMachineError> {
+    token
+        .and_then(|t| t.parse().ok())
+        .ok_or_else(|| ParseMachineError(format!("invalid {}", what)))
+}
+
+/// Parses the quoted names following the number of atomic propositions in an `AP` header.
+fn parse_names(s: &str) -> Result<Vec<String>, ParseMachineError> {
+    let mut parts = s.split('"');
+    if !parts.next().map_or(false, |p| p.trim().is_empty()) {
+        return Err(ParseMachineError("invalid atomic propositions".to_string()));
+    }
+    let mut names = Vec::new();
+    while let Some(name) = parts.next() {
+        names.push(name.to_string());
+        match parts.next() {
+            Some(separator) if separator.trim().is_empty() => (),
+            _ => return Err(ParseMachineError("invalid atomic propositions".to_string())),
+        }
+    }
+    Ok(names)
+}
+
+/// A parser for the boolean expressions of edge labels in HOA format,
+/// where variables are given by the index of their atomic proposition.
+struct LabelParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    label: &'a str,
+    manager: &'a Cudd,
+    num_vars: usize,
+}
+
+impl<'a> LabelParser<'a> {
+    fn parse(label: &'a str, manager: &'a Cudd, num_vars: usize) -> Result<Bdd, ParseMachineError> {
+        let mut parser = Self {
+            chars: label.chars().peekable(),
+            label,
+            manager,
+            num_vars,
+        };
+        let bdd = parser.disjunction()?;
+        match parser.next_char() {
+            None => Ok(bdd),
+            Some(_) => Err(parser.error()),
+        }
+    }
+
+    fn error(&self) -> ParseMachineError {
+        ParseMachineError(format!("invalid label '{}'", self.label))
+    }
+
+    fn peek_char(&mut self) -> Option<char> {
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_whitespace() {
+                return Some(c);
+            }
+            self.chars.next();
+        }
+        None
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek_char();
+        self.chars.next();
+        c
+    }
+
+    fn disjunction(&mut self) -> Result<Bdd, ParseMachineError> {
+        let mut bdd = self.conjunction()?;
+        while self.peek_char() == Some('|') {
+            self.next_char();
+            bdd |= self.conjunction()?;
+        }
+        Ok(bdd)
+    }
+
+    fn conjunction(&mut self) -> Result<Bdd, ParseMachineError> {
+        let mut bdd = self.literal()?;
+        while self.peek_char() == Some('&') {
+            self.next_char();
+            bdd &= self.literal()?;
+        }
+        Ok(bdd)
+    }
+
+    fn literal(&mut self) -> Result<Bdd, ParseMachineError> {
+        match self.next_char() {
+            Some('!') => Ok(!self.literal()?),
+            Some('(') => {
+                let bdd = self.disjunction()?;
+                match self.next_char() {
+                    Some(')') => Ok(bdd),
+                    _ => Err(self.error()),
+                }
+            }
+            Some('t') => Ok(self.manager.bdd_one()),
+            Some('f') => Ok(self.manager.bdd_zero()),
+            Some(c) if c.is_ascii_digit() => {
+                let mut var = c.to_digit(10).unwrap() as usize;
+                while let Some(&c) = self.chars.peek() {
+                    match c.to_digit(10) {
+                        Some(d) => var = var * 10 + d as usize,
+                        None => break,
+                    }
+                    self.chars.next();
+                }
+                if var < self.num_vars {
+                    Ok(self.manager.bdd_var(var))
+                } else {
+                    Err(ParseMachineError(format!(
+                        "atomic proposition {} out of range",
+                        var
+                    )))
+                }
+            }
+            _ => Err(self.error()),
+        }
+    }
+}
+
+/// Parses a machine in HOA format, where each state is labelled with its number,
+/// and returns it together with the names of the states, if given.
+fn parse_hoa(s: &str) -> Result<(LabelledMachine<usize>, Vec<Option<String>>), ParseMachineError> {
+    let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+    if lines.next() != Some("HOA: v1") {
+        return Err(ParseMachineError("missing header".to_string()));
+    }
+
+    // header
+    let mut num_states = None;
+    let mut initial_states = Vec::new();
+    let mut names = None;
+    let mut controllable = None;
+    let mut machine_type = None;
+    for line in &mut lines {
+        if line == "--BODY--" {
+            break;
+        }
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| ParseMachineError(format!("invalid header line '{}'", line)))?;
+        let value = value.trim();
+        match key {
+            "States" => num_states = Some(parse_number(Some(value), "number of states")?),
+            "Start" => initial_states.push(parse_number(Some(value), "initial state")?),
+            "AP" => {
+                let (count, rest) = value.split_once(' ').unwrap_or((value, ""));
+                let ap_names = parse_names(rest)?;
+                if parse_number(Some(count), "number of atomic propositions")? != ap_names.len() {
+                    return Err(ParseMachineError(
+                        "wrong number of atomic propositions".to_string(),
+                    ));
+                }
+                names = Some(ap_names);
+            }
+            "controllable-AP" => {
+                controllable = Some(
+                    value
+                        .split_whitespace()
+                        .map(|t| parse_number(Some(t), "controllable atomic proposition"))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            }
+            "machine-type" => {
+                machine_type = match value {
+                    "mealy" => Some(true),
+                    "moore" => Some(false),
+                    _ => {
+                        return Err(ParseMachineError(format!(
+                            "unknown machine type '{}'",
+                            value
+                        )))
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    let num_states =
+        num_states.ok_or_else(|| ParseMachineError("missing number of states".to_string()))?;
+    let mut names =
+        names.ok_or_else(|| ParseMachineError("missing atomic propositions".to_string()))?;
+    let mut controllable = controllable
+        .ok_or_else(|| ParseMachineError("missing controllable atomic propositions".to_string()))?;
+    let num_vars = names.len();
+    controllable.sort_unstable();
+    controllable.dedup();
+    if let Some(ap) = controllable.iter().find(|&&ap| ap >= num_vars) {
+        return Err(ParseMachineError(format!(
+            "atomic proposition {} out of range",
+            ap
+        )));
+    }
+    let num_controllable = controllable.len();
+    let controllable_last = controllable
+        .iter()
+        .copied()
+        .eq(num_vars - num_controllable..num_vars);
+    let controllable_first = controllable.iter().copied().eq(0..num_controllable);
+    // the inputs are the uncontrollable propositions of a Mealy machine
+    // and the controllable propositions of a Moore machine
+    let (mealy, outputs_first) = match machine_type {
+        None | Some(true) if controllable_last => (true, false),
+        None | Some(false) if controllable_first => (false, false),
+        Some(true) if controllable_first => (true, true),
+        Some(false) if controllable_last => (false, true),
+        _ => {
+            return Err(ParseMachineError(
+                "controllable atomic propositions are neither the inputs nor the outputs"
+                    .to_string(),
+            ))
+        }
+    };
+    let num_inputs = if mealy {
+        num_vars - num_controllable
+    } else {
+        num_controllable
+    };
+    // position of each proposition in the variables, where the inputs come first
+    let num_outputs = num_vars - num_inputs;
+    let permutation: Vec<usize> = (0..num_vars)
+        .map(|ap| {
+            if !outputs_first {
+                ap
+            } else if ap < num_outputs {
+                num_inputs + ap
+            } else {
+                ap - num_outputs
+            }
+        })
+        .collect();
+    if outputs_first {
+        names.rotate_left(num_outputs);
+    }
+    if initial_states.is_empty() {
+        return Err(ParseMachineError("missing initial state".to_string()));
+    }
+    if let Some(state) = initial_states.iter().find(|&&state| state >= num_states) {
+        return Err(ParseMachineError(format!("state {} out of range", state)));
+    }
+
+    // body
+    let manager = Cudd::with_vars(num_vars).unwrap();
+    let input_cube = manager.bdd_cube(&(0..num_inputs).collect::<Vec<_>>());
+    let output_cube = manager.bdd_cube(&(num_inputs..num_vars).collect::<Vec<_>>());
+    let mut constructor = LabelledMachineConstructor::new();
+    let states: Vec<StateIndex> = (0..num_states)
+        .map(|state| constructor.add_state(state).0)
+        .collect();
+    let mut transitions: Vec<Vec<Transition>> = vec![Vec::new(); num_states];
+    let mut state_names = vec![None; num_states];
+    let mut defined = vec![false; num_states];
+    let mut current = None;
+    let mut ended = false;
+    for line in &mut lines {
+        if line == "--END--" {
+            ended = true;
+            break;
+        }
+        if let Some(state) = line.strip_prefix("State:") {
+            let name = state.split('"').nth(1);
+            let state = parse_number(state.split_whitespace().next(), "state")?;
+            if state >= num_states {
+                return Err(ParseMachineError(format!("state {} out of range", state)));
+            }
+            if defined[state] {
+                return Err(ParseMachineError(format!("state {} defined twice", state)));
+            }
+            defined[state] = true;
+            state_names[state] = name.map(str::to_string);
+            current = Some(state);
+            continue;
+        }
+        let state = current
+            .ok_or_else(|| ParseMachineError(format!("edge '{}' outside of state", line)))?;
+        let (label, successor) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.rsplit_once(']'))
+            .ok_or_else(|| ParseMachineError(format!("invalid edge '{}'", line)))?;
+        let successor = parse_number(Some(successor.trim()), "successor")?;
+        if successor >= num_states {
+            return Err(ParseMachineError(format!(
+                "state {} out of range",
+                successor
+            )));
+        }
+        let mut bdd = LabelParser::parse(label, &manager, num_vars)?;
+        if outputs_first {
+            bdd = bdd.permute(&permutation);
+        }
+        if bdd.is_zero() {
+            continue;
+        }
+        let input = bdd.exist_abstract(&output_cube);
+        let output = bdd.exist_abstract(&input_cube);
+        if (&input & &output) != bdd {
+            return Err(ParseMachineError(format!(
+                "label '{}' is not a conjunction of inputs and outputs",
+                label
+            )));
+        }
+        let state_transitions = &mut transitions[state];
+        match state_transitions.last_mut() {
+            Some(transition) if transition.input == input => {
+                transition.add_output(output, states[successor])
+            }
+            _ => {
+                let mut transition = Transition::new(input);
+                transition.add_output(output, states[successor]);
+                state_transitions.push(transition);
+            }
+        }
+    }
+    if !ended {
+        return Err(ParseMachineError("missing end of body".to_string()));
+    }
+    if let Some(state) = defined.iter().position(|&d| !d) {
+        return Err(ParseMachineError(format!(
+            "state {} has no definition",
+            state
+        )));
+    }
+
+    for (state, state_transitions) in states.iter().zip(transitions) {
+        for transition in state_transitions {
+            constructor.add_transition(*state, transition);
+        }
+    }
+    let outputs = names.split_off(num_inputs);
+    let mut machine = constructor.into_machine(
+        initial_states.into_iter().map(|s| states[s]).collect(),
+        names,
+        outputs,
+        mealy,
+    );
+    machine.outputs_first = outputs_first;
+    Ok((machine, state_names))
+}
+
+impl std::str::FromStr for LabelledMachine<usize> {
+    type Err = ParseMachineError;
+
+    /// Parses a machine in the HOA format in which machines are written, where
+    /// each state is labelled with its number in the HOA format.
+    ///
+    /// The atomic propositions are the inputs followed by the outputs. The machine
+    /// is a Mealy machine if the controllable propositions are the last propositions,
+    /// and a Moore machine if they are the first propositions. If the header states
+    /// the type of the machine with `machine-type: mealy` or `machine-type: moore`,
+    /// the outputs may also be listed before the inputs. Each edge label needs
+    /// to be a conjunction of a formula over the inputs and a formula over the outputs,
+    /// and consecutive edges of a state with the same inputs form one transition.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_hoa(s).map(|(machine, _)| machine)
+    }
+}
+
+impl std::str::FromStr for LabelledMachine<StructuredLabel> {
+    type Err = ParseMachineError;
+
+    /// Parses a machine in HOA format as for [`LabelledMachine<usize>`], where each state
+    /// is labelled with the structured label given as its name, as written for machines
+    /// with structured labels, e.g. `State: 0 "[1,-,3]"`.
+    ///
+    /// All states need to be named by structured labels with the same number of
+    /// components, and different states need to have different labels, such that
+    /// the labels can be used as encoding of the states for BDDs and circuits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (machine, names) = parse_hoa(s)?;
+        let mut labels: Vec<StructuredLabel> = Vec::with_capacity(names.len());
+        for (state, name) in names.into_iter().enumerate() {
+            let label: StructuredLabel = name
+                .ok_or_else(|| ParseMachineError(format!("state {} has no name", state)))?
+                .parse()
+                .map_err(|e| ParseMachineError(format!("{} of state {}", e, state)))?;
+            if let Some(first) = labels.first() {
+                if label.components() != first.components() {
+                    return Err(ParseMachineError(format!(
+                        "label of state {} has {} instead of {} components",
+                        state,
+                        label.components(),
+                        first.components()
+                    )));
+                }
+            }
+            if let Some(other) = labels.iter().position(|l| *l == label) {
+                return Err(ParseMachineError(format!(
+                    "states {} and {} have the same label",
+                    other, state
+                )));
+            }
+            labels.push(label);
+        }
+        let states = machine
+            .states()
+            .zip(labels)
+            .map(|(state, label)| State::with_transitions(label, state.transitions.clone()))
+            .collect();
+        Ok(machine.clone_with(states, machine.initial_states.clone()))
+    }
+}
@@ -0,0 +1,172 @@
+//! Mapping the states of a deployed machine to the states of a machine re-synthesized
+//! after a small change of the specification, such that a running controller can
+//! continue in a corresponding state of the new controller.
+
+use std::fmt;
+
+use cudd::Cudd;
+use log::info;
+
+use super::simulation::simulation_relation;
+use super::{LabelValue, LabelledMachine, StructuredLabel};
+
+/// An entry of a [`StateMapping`] for one state of the old machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateMappingEntry {
+    /// The index of the state of the old machine.
+    pub old_state: usize,
+    /// The values of the latches of the old controller in this state.
+    pub old_bits: Vec<bool>,
+    /// The index of the state of the new machine and the values of the latches of the
+    /// new controller in this state, if the label of a state of the new machine
+    /// overlaps with the label of the old state.
+    pub new_state: Option<(usize, Vec<bool>)>,
+    /// Whether every behavior of the old machine from the old state is also a
+    /// behavior of the new machine from the new state.
+    pub simulated: bool,
+}
+
+/// A table mapping the states of a deployed machine to the states of a re-synthesized
+/// machine, together with the values of the latches of the controllers in these
+/// states, see [`LabelledMachine::state_mapping`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateMapping {
+    entries: Vec<StateMappingEntry>,
+}
+
+impl StateMapping {
+    /// Returns the entries of the mapping, one for each state of the old machine.
+    pub fn entries(&self) -> &[StateMappingEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of states of the old machine that are mapped to a state
+    /// of the new machine.
+    pub fn num_mapped(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.new_state.is_some())
+            .count()
+    }
+
+    /// Returns the values of the latches of the new controller for the given values
+    /// of the latches of the old controller, or `None` if they do not belong to a
+    /// state of the old machine that is mapped to a state of the new machine.
+    pub fn map_bits(&self, old_bits: &[bool]) -> Option<&[bool]> {
+        self.entries
+            .iter()
+            .find(|entry| entry.old_bits == old_bits)
+            .and_then(|entry| entry.new_state.as_ref())
+            .map(|(_, new_bits)| new_bits.as_slice())
+    }
+}
+
+fn write_bits(f: &mut fmt::Formatter<'_>, bits: &[bool]) -> fmt::Result {
+    for &bit in bits {
+        write!(f, "{}", if bit { '1' } else { '0' })?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for StateMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            write!(f, "{} [", entry.old_state)?;
+            write_bits(f, &entry.old_bits)?;
+            match &entry.new_state {
+                Some((new_state, new_bits)) => {
+                    write!(f, "] -> {} [", new_state)?;
+                    write_bits(f, new_bits)?;
+                    write!(f, "]")?;
+                    if entry.simulated {
+                        write!(f, " simulated")?;
+                    }
+                    writeln!(f)?;
+                }
+                None => writeln!(f, "] -> none")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the number of components with the same value in both labels if the labels
+/// overlap, i.e. if each component present in both labels has the same value or a
+/// don't care value in one of them, and `None` otherwise.
+fn overlap(label: &StructuredLabel, other: &StructuredLabel) -> Option<usize> {
+    let mut equal = 0;
+    for (&v, &w) in label.iter().zip(other.iter()) {
+        match (v, w) {
+            (LabelValue::Value(v), LabelValue::Value(w)) if v != w => return None,
+            (LabelValue::Value(_), LabelValue::Value(_)) => equal += 1,
+            _ => (),
+        }
+    }
+    Some(equal)
+}
+
+impl LabelledMachine<StructuredLabel> {
+    /// Computes a table that maps each state of this machine, which is the deployed
+    /// machine, to a state of the given new machine obtained by re-synthesis after
+    /// a change of the specification, together with the values of the latches of both
+    /// controllers, such that a running controller can continue in the new state.
+    ///
+    /// A state is mapped to the state of the new machine whose label overlaps with its
+    /// label in the most components with the same value, where ties are broken in favor
+    /// of states that simulate the old state and then of states with a smaller index.
+    /// Simulation is only checked if both machines have the same inputs, outputs and
+    /// type. States without any overlapping label are not mapped.
+    ///
+    /// Labels only carry information on the specification if the machines are
+    /// constructed with a label structure based on the automaton, e.g. with option
+    /// `--label structured`, and the labels need to be compressed in the same way
+    /// for both machines.
+    pub fn state_mapping(&self, new: &Self) -> StateMapping {
+        info!(
+            "Mapping {} states of the old machine to {} states of the new machine",
+            self.num_states(),
+            new.num_states()
+        );
+        let related = if self.inputs == new.inputs
+            && self.outputs == new.outputs
+            && self.mealy == new.mealy
+        {
+            let manager = Cudd::with_vars(self.num_vars()).unwrap();
+            let moves = self.moves(&manager);
+            let new_moves = new.moves(&manager);
+            Some(simulation_relation(&manager, &moves, &new_moves))
+        } else {
+            None
+        };
+        let simulated =
+            |s: usize, t: usize| related.as_ref().map_or(false, |related| related[s][t]);
+
+        let old_bits = self.state_bits();
+        let new_bits = new.state_bits();
+        let entries: Vec<_> = self
+            .labels()
+            .zip(old_bits)
+            .enumerate()
+            .map(|(s, (label, old_bits))| {
+                let best = new
+                    .labels()
+                    .enumerate()
+                    .filter_map(|(t, new_label)| overlap(label, new_label).map(|equal| (t, equal)))
+                    .max_by_key(|&(t, equal)| (equal, simulated(s, t), std::cmp::Reverse(t)));
+                StateMappingEntry {
+                    old_state: s,
+                    old_bits,
+                    new_state: best.map(|(t, _)| (t, new_bits[t].clone())),
+                    simulated: best.map_or(false, |(t, _)| simulated(s, t)),
+                }
+            })
+            .collect();
+        let mapping = StateMapping { entries };
+        info!(
+            "Mapped {} of {} states to the new machine",
+            mapping.num_mapped(),
+            self.num_states()
+        );
+        mapping
+    }
+}
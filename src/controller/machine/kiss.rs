@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
+
+use cudd::{Bdd, Cube, CubeValue, Cudd};
+
+use super::hoa::parse_number;
+use super::{LabelledMachine, LabelledMachineConstructor, ParseMachineError, Transition};
+
+/// Returns the cube of the given non-empty BDD with the most unspecified variables
+/// in the given range, which keeps as many don't cares of the BDD as possible.
+fn largest_cube(bdd: &Bdd, num_vars: usize, vars: &Range<usize>) -> Cube {
+    bdd.cube_iter(num_vars)
+        .max_by_key(|cube| {
+            vars.clone()
+                .filter(|&var| cube[var] == CubeValue::Unspecified)
+                .count()
+        })
+        .expect("empty set of values")
+}
+
+/// Returns the values of the given cube for the variables in the given range
+/// as a string of `0`, `1` and `-`.
+fn cube_string(cube: &Cube, vars: &Range<usize>) -> String {
+    vars.clone().map(|var| cube[var].to_string()).collect()
+}
+
+impl<L> LabelledMachine<L> {
+    /// Writes the machine as an incompletely specified machine in the KISS2 format
+    /// to the given writer, with one line for each cube of inputs in each state.
+    ///
+    /// The output of each line is a cube of the set of outputs of the transition, where
+    /// unspecified outputs are written as don't cares. Inputs that are impossible in a
+    /// state of a Mealy machine under the assumptions are left unspecified. As the format
+    /// requires a unique successor, the first successor of each transition is kept for a
+    /// Mealy machine. A Moore machine is written with its observed outputs as the inputs
+    /// of the lines, keeping the first choice of its inputs for each state. Only the first
+    /// initial state is written as the reset state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during writing.
+    pub(crate) fn write_kiss2<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        let num_vars = self.num_vars();
+        let inputs = 0..self.num_inputs();
+        let outputs = self.num_inputs()..num_vars;
+        // the variables read and written by the machine
+        let (read, written) = if self.mealy {
+            (inputs, outputs)
+        } else {
+            (outputs, inputs)
+        };
+
+        let mut lines = Vec::new();
+        for (index, state) in self.states_with_index() {
+            if self.mealy {
+                for transition in &state.transitions {
+                    let output = match transition.outputs.first() {
+                        Some(output) => output,
+                        None => continue,
+                    };
+                    let written_cube = largest_cube(&output.output, num_vars, &written);
+                    let input = match &state.dontcare_inputs {
+                        Some(dontcare) => &transition.input & !dontcare,
+                        None => transition.input.clone(),
+                    };
+                    for read_cube in input.cube_iter(num_vars) {
+                        lines.push((
+                            cube_string(&read_cube, &read),
+                            index,
+                            output.successor,
+                            cube_string(&written_cube, &written),
+                        ));
+                    }
+                }
+            } else if let Some(transition) = state.transitions.first() {
+                let written_cube = largest_cube(&transition.input, num_vars, &written);
+                // observed outputs already covered by a previous successor are skipped
+                let mut covered = transition.input.manager().bdd_zero();
+                for output in &transition.outputs {
+                    let observed = &output.output & !&covered;
+                    for read_cube in observed.cube_iter(num_vars) {
+                        lines.push((
+                            cube_string(&read_cube, &read),
+                            index,
+                            output.successor,
+                            cube_string(&written_cube, &written),
+                        ));
+                    }
+                    covered |= &output.output;
+                }
+            }
+        }
+
+        writeln!(writer, ".i {}", read.len())?;
+        writeln!(writer, ".o {}", written.len())?;
+        writeln!(writer, ".p {}", lines.len())?;
+        writeln!(writer, ".s {}", self.num_states())?;
+        writeln!(writer, ".r s{}", self.initial_states[0])?;
+        for (read, state, successor, written) in lines {
+            writeln!(writer, "{} s{} s{} {}", read, state, successor, written)?;
+        }
+        writeln!(writer, ".e")
+    }
+}
+
+/// Parses a cube of `0`, `1` and `-` for the variables starting at the given offset,
+/// where `~` is also accepted for an unspecified value.
+fn parse_cube(
+    token: &str,
+    manager: &Cudd,
+    offset: usize,
+    len: usize,
+) -> Result<Bdd, ParseMachineError> {
+    if token.chars().count() != len {
+        return Err(ParseMachineError(format!(
+            "cube '{}' does not have {} values",
+            token, len
+        )));
+    }
+    let mut bdd = manager.bdd_one();
+    for (i, c) in token.chars().enumerate() {
+        match c {
+            '0' => bdd &= !manager.bdd_var(offset + i),
+            '1' => bdd &= manager.bdd_var(offset + i),
+            '-' | '~' => (),
+            _ => return Err(ParseMachineError(format!("invalid cube '{}'", token))),
+        }
+    }
+    Ok(bdd)
+}
+
+impl LabelledMachine<usize> {
+    /// Parses a Mealy machine in the KISS2 format, where each state is labelled with
+    /// its position in the order in which the states first occur.
+    ///
+    /// The names of the inputs and outputs are taken from `.ilb` and `.ob` lines if
+    /// present, and are `i0`, `i1`, ... and `o0`, `o1`, ... otherwise. The reset state
+    /// is given by `.r` or is the state of the first line. Lines of a state with the
+    /// same inputs form one transition, where unspecified outputs are don't cares and
+    /// the unspecified successor `*` is taken as the state itself. Inputs not covered
+    /// in a state are don't cares with a transition to the state itself.
+    pub(crate) fn from_kiss2(s: &str) -> Result<Self, ParseMachineError> {
+        let mut num_inputs = None;
+        let mut num_outputs = None;
+        let mut input_names = None;
+        let mut output_names = None;
+        let mut reset = None;
+        let mut rows = Vec::new();
+        for line in s.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut tokens = line.split_whitespace();
+            let first = tokens.next().unwrap();
+            match first {
+                ".i" => num_inputs = Some(parse_number(tokens.next(), "number of inputs")?),
+                ".o" => num_outputs = Some(parse_number(tokens.next(), "number of outputs")?),
+                ".ilb" => input_names = Some(tokens.map(String::from).collect::<Vec<_>>()),
+                ".ob" => output_names = Some(tokens.map(String::from).collect::<Vec<_>>()),
+                ".r" => {
+                    reset = Some(
+                        tokens
+                            .next()
+                            .ok_or_else(|| ParseMachineError("missing reset state".to_string()))?
+                            .to_string(),
+                    )
+                }
+                ".e" | ".end" => break,
+                // the number of lines and states are only hints
+                ".p" | ".s" => (),
+                _ if first.starts_with('.') => {
+                    return Err(ParseMachineError(format!("unknown directive '{}'", first)))
+                }
+                _ => rows.push(line),
+            }
+        }
+        let num_inputs =
+            num_inputs.ok_or_else(|| ParseMachineError("missing number of inputs".to_string()))?;
+        let num_outputs = num_outputs
+            .ok_or_else(|| ParseMachineError("missing number of outputs".to_string()))?;
+        let inputs =
+            input_names.unwrap_or_else(|| (0..num_inputs).map(|i| format!("i{}", i)).collect());
+        let outputs =
+            output_names.unwrap_or_else(|| (0..num_outputs).map(|i| format!("o{}", i)).collect());
+        if inputs.len() != num_inputs || outputs.len() != num_outputs {
+            return Err(ParseMachineError(
+                "wrong number of input or output names".to_string(),
+            ));
+        }
+
+        let num_vars = num_inputs + num_outputs;
+        let manager = Cudd::with_vars(num_vars).unwrap();
+        let mut constructor = LabelledMachineConstructor::new();
+        let mut state_names = HashMap::new();
+        let mut transitions: Vec<Vec<Transition>> = Vec::new();
+        let mut add_state = |name: &str, transitions: &mut Vec<Vec<Transition>>| {
+            let next = state_names.len();
+            let index = *state_names.entry(name.to_string()).or_insert(next);
+            if index == transitions.len() {
+                transitions.push(Vec::new());
+            }
+            constructor.add_state(index).0
+        };
+        let mut initial_state = reset
+            .as_deref()
+            .map(|name| add_state(name, &mut transitions));
+        for row in rows {
+            let tokens: Vec<_> = row.split_whitespace().collect();
+            // cubes over no variables are omitted
+            let expected = 2 + usize::from(num_inputs > 0) + usize::from(num_outputs > 0);
+            if tokens.len() != expected {
+                return Err(ParseMachineError(format!("invalid line '{}'", row)));
+            }
+            let (input, rest) = if num_inputs > 0 {
+                (tokens[0], &tokens[1..])
+            } else {
+                ("", &tokens[..])
+            };
+            let output = if num_outputs > 0 { rest[2] } else { "" };
+            if rest[0] == "*" {
+                return Err(ParseMachineError(format!(
+                    "invalid state in line '{}'",
+                    row
+                )));
+            }
+            let state = add_state(rest[0], &mut transitions);
+            let successor = if rest[1] == "*" {
+                state
+            } else {
+                add_state(rest[1], &mut transitions)
+            };
+            initial_state.get_or_insert(state);
+            let input = parse_cube(input, &manager, 0, num_inputs)?;
+            let output = parse_cube(output, &manager, num_inputs, num_outputs)?;
+            let state_transitions = &mut transitions[state.0];
+            if let Some(transition) = state_transitions.iter_mut().find(|t| t.input == input) {
+                transition.add_output(output, successor);
+            } else if state_transitions
+                .iter()
+                .any(|t| !(&t.input & &input).is_zero())
+            {
+                return Err(ParseMachineError(format!(
+                    "overlapping inputs in line '{}'",
+                    row
+                )));
+            } else {
+                let mut transition = Transition::new(input);
+                transition.add_output(output, successor);
+                state_transitions.push(transition);
+            }
+        }
+        let initial_state =
+            initial_state.ok_or_else(|| ParseMachineError("missing initial state".to_string()))?;
+
+        let mut dontcare_inputs = Vec::with_capacity(transitions.len());
+        for (index, state_transitions) in transitions.into_iter().enumerate() {
+            let state = constructor.add_state(index).0;
+            let mut uncovered = manager.bdd_one();
+            for transition in state_transitions {
+                uncovered &= !&transition.input;
+                constructor.add_transition(state, transition);
+            }
+            if uncovered.is_zero() {
+                dontcare_inputs.push(None);
+            } else {
+                let mut transition = Transition::new(uncovered.clone());
+                transition.add_output(manager.bdd_one(), state);
+                constructor.add_transition(state, transition);
+                dontcare_inputs.push(Some(uncovered));
+            }
+        }
+        let mut machine = constructor.into_machine(vec![initial_state], inputs, outputs, true);
+        for (state, dontcare) in machine.states.iter_mut().zip(dontcare_inputs) {
+            state.dontcare_inputs = dontcare;
+        }
+        Ok(machine)
+    }
+}
+
+/// Tests for writing machines in the KISS2 format.
+#[cfg(test)]
+mod tests {
+    use cudd::Cudd;
+
+    use super::super::{LabelledMachine, LabelledMachineConstructor, Transition};
+
+    /// Test that the outputs of a non-deterministic Mealy machine are written
+    /// as don't cares and that impossible inputs are left unspecified.
+    #[test]
+    fn test_write_kiss2_dontcares() {
+        let manager = Cudd::with_vars(3).unwrap();
+        let a = manager.bdd_var(0);
+        let b = manager.bdd_var(1);
+        let c = manager.bdd_var(2);
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let (s0, _) = constructor.add_state(0);
+        let (s1, _) = constructor.add_state(1);
+        let mut t0 = Transition::new(a.clone());
+        t0.add_output(manager.bdd_one(), s1);
+        constructor.add_transition(s0, t0);
+        let mut t1 = Transition::new(!&a);
+        t1.add_output(&b & !&c, s0);
+        constructor.add_transition(s0, t1);
+        let mut t2 = Transition::new(manager.bdd_one());
+        t2.add_output(c, s0);
+        constructor.add_transition(s1, t2);
+        let mut machine = constructor.into_machine(
+            vec![s0],
+            vec!["a".to_string()],
+            vec!["b".to_string(), "c".to_string()],
+            true,
+        );
+        machine.states[1].dontcare_inputs = Some(a);
+
+        let mut kiss = Vec::new();
+        machine.write_kiss2(&mut kiss).unwrap();
+        assert_eq!(
+            String::from_utf8(kiss).unwrap(),
+            ".i 1\n.o 2\n.p 3\n.s 2\n.r s0\n1 s0 s1 --\n0 s0 s0 10\n0 s1 s0 -1\n.e\n"
+        );
+    }
+
+    /// Test that a machine in KISS2 format is parsed with its don't cares and
+    /// written again with the same lines.
+    #[test]
+    fn test_parse_kiss2() {
+        let kiss = ".i 1\n.o 2\n.p 3\n.s 2\n.r s0\n1 s0 s1 --\n0 s0 s0 10\n0 s1 s0 -1\n.e\n";
+        let machine = LabelledMachine::<usize>::from_kiss2(kiss).unwrap();
+        assert_eq!(machine.num_states(), 2);
+        assert_eq!(machine.inputs, vec!["i0"]);
+        assert_eq!(machine.outputs, vec!["o0", "o1"]);
+        let a = machine.states[1].transitions[0].input.manager().bdd_var(0);
+        assert_eq!(machine.states[0].dontcare_inputs, None);
+        assert_eq!(machine.states[1].dontcare_inputs, Some(a));
+
+        let mut written = Vec::new();
+        machine.write_kiss2(&mut written).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), kiss);
+
+        assert!(
+            LabelledMachine::<usize>::from_kiss2(".i 1\n.o 1\n1 s0 s0 1\n- s0 s1 0\n").is_err()
+        );
+        assert!(LabelledMachine::<usize>::from_kiss2(".i 1\n.o 1\n10 s0 s0 1\n").is_err());
+        assert!(LabelledMachine::<usize>::from_kiss2(".o 1\n1 s0 s0 1\n").is_err());
+    }
+}
@@ -1,11 +1,13 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::ops::Index;
+use std::time::Instant;
 
 use cudd::Bdd;
 use log::{debug, error};
-use varisat::{ExtendFormula, Lit, Solver};
+use varisat::{CnfFormula, ExtendFormula, Lit, Solver};
 
 use super::{LabelledMachine, State, StateIndex, Transition, TransitionOutput};
+use crate::controller::sat::{self, SatResult};
 
 /// Obtain a model for the constraints already in solver where the minimal
 /// number of given vars are set to true.
@@ -69,12 +71,29 @@ fn minimal_model(solver: &mut Solver, vars: &[Lit]) -> Vec<Lit> {
     model
 }
 
+/// The result of the search for a covering machine with a given number of states.
+pub(super) enum Covering<L> {
+    /// A covering machine was found.
+    Found(LabelledMachine<Vec<L>>),
+    /// There is no covering machine with the given number of states.
+    None,
+    /// The SAT encoding exceeds the maximum number of clauses with the given number of clauses.
+    ClauseLimit(usize),
+    /// The deadline passed or the solver failed before the encoding was solved.
+    Unknown,
+}
+
 impl<L> LabelledMachine<L> {
     pub(super) fn minimal_reachable_states(&self) -> Vec<bool> {
         let mut solver = Solver::new();
         let state_vars: Vec<_> = self.state_indices().map(|_| solver.new_lit()).collect();
-        // initial state is reachable
-        solver.add_clause(&[state_vars[self.initial_state.0]]);
+        // some initial state is reachable
+        let initial_clause: Vec<_> = self
+            .initial_states
+            .iter()
+            .map(|s| state_vars[s.0])
+            .collect();
+        solver.add_clause(&initial_clause);
         for (index, state) in self.states_with_index() {
             let state_var = state_vars[index.0];
             if self.mealy {
@@ -141,7 +160,7 @@ impl<L> LabelledMachine<L> {
     /// Computes a list of actions such that all actions in the list are pairwise disjoint
     /// and their union is equal to the union of the actions in the given class.
     fn disjoint_action_set(&self, class: &[StateIndex]) -> Vec<Bdd> {
-        let mut disjoint_set: HashSet<Bdd> = HashSet::new();
+        let mut disjoint_set: Vec<Bdd> = Vec::new();
         let mut queue = VecDeque::new();
         for &i in class {
             for transition in &self[i].transitions {
@@ -158,33 +177,38 @@ impl<L> LabelledMachine<L> {
             if disjoint_set.contains(&action) {
                 continue;
             }
-            let intersection_match = disjoint_set.iter().find_map(|disjoint_action| {
-                let intersection = disjoint_action & &action;
-                (!intersection.is_zero()).then(|| (intersection, disjoint_action.clone()))
-            });
+            let intersection_match =
+                disjoint_set
+                    .iter()
+                    .enumerate()
+                    .find_map(|(index, disjoint_action)| {
+                        let intersection = disjoint_action & &action;
+                        (!intersection.is_zero()).then(|| (index, intersection))
+                    });
             match intersection_match {
-                Some((intersection, disjoint_action)) => {
+                Some((index, intersection)) => {
+                    let disjoint_action = &disjoint_set[index];
                     let diff0 = &action & !&intersection;
-                    let diff1 = &disjoint_action & !&intersection;
+                    let diff1 = disjoint_action & !&intersection;
                     if diff0.is_zero() {
-                        disjoint_set.remove(&disjoint_action);
-                        disjoint_set.insert(intersection);
-                        disjoint_set.insert(diff1);
+                        disjoint_set[index] = intersection;
+                        disjoint_set.push(diff1);
                     } else if diff1.is_zero() {
                         queue.push_back(diff0);
                     } else {
-                        disjoint_set.remove(&disjoint_action);
                         queue.push_back(diff0);
-                        disjoint_set.insert(intersection);
-                        disjoint_set.insert(diff1);
+                        disjoint_set[index] = intersection;
+                        disjoint_set.push(diff1);
                     }
                 }
                 None => {
-                    disjoint_set.insert(action.clone());
+                    disjoint_set.push(action);
                 }
             };
         }
-        disjoint_set.into_iter().collect()
+        // sort by the functions for an order independent of the manager
+        disjoint_set.sort_by(Bdd::canonical_cmp);
+        disjoint_set
     }
 }
 
@@ -200,7 +224,10 @@ impl<L: Clone> LabelledMachine<L> {
         debug!("Splitting action sets");
         let mut new_states: Vec<State<L>> = self
             .states()
-            .map(|state| State::new(state.label().clone()))
+            .map(|state| {
+                State::new(state.label().clone())
+                    .with_dontcare_inputs(state.dontcare_inputs.clone())
+            })
             .collect();
         for class in &classes.classes {
             let disjoint_set = self.disjoint_action_set(class);
@@ -221,7 +248,9 @@ impl<L: Clone> LabelledMachine<L> {
                                 })
                             }));
                     }
-                    new_state.transitions.sort_by_key(|t| t.input.node_id());
+                    new_state
+                        .transitions
+                        .sort_by(|t1, t2| t1.input.canonical_cmp(&t2.input));
                 } else {
                     let transition = &state.transitions[0];
                     let mut new_transition = Transition::new(transition.input.clone());
@@ -235,12 +264,14 @@ impl<L: Clone> LabelledMachine<L> {
                                     .then(|| TransitionOutput::new(new_output.clone(), successor))
                             }));
                     }
-                    new_transition.outputs.sort_by_key(|to| to.output.node_id());
+                    new_transition
+                        .outputs
+                        .sort_by(|to1, to2| to1.output.canonical_cmp(&to2.output));
                     new_state.add_transition(new_transition);
                 }
             }
         }
-        self.clone_with(new_states, self.initial_state)
+        self.clone_with(new_states, self.initial_states.clone())
     }
 
     fn state_num_actions(&self, state: &State<L>) -> usize {
@@ -267,6 +298,10 @@ impl<L: Clone> LabelledMachine<L> {
 
     /// Find a machine with `num_states` states that covers the current machine.
     ///
+    /// If a maximum number of clauses is given and the SAT encoding exceeds it,
+    /// the encoding is not solved. If the deadline passes while solving the encoding,
+    /// the search is abandoned.
+    ///
     /// Uses approach described in Abel and Reineke:
     /// ["MeMin: SAT-based Exact Minimization of Incompletely Specified Mealy Machines"](http://embedded.cs.uni-saarland.de/MeMin.php)
     pub(super) fn find_covering_machine(
@@ -274,23 +309,25 @@ impl<L: Clone> LabelledMachine<L> {
         num_states: usize,
         matrix: &IncompatabilityMatrix,
         pairwise_incompatible_states: &[StateIndex],
-    ) -> Option<LabelledMachine<Vec<L>>> {
-        let mut solver = Solver::new();
+        max_clauses: Option<usize>,
+        deadline: Option<Instant>,
+    ) -> Covering<L> {
+        let mut formula = CnfFormula::new();
 
         // class_state_vars[i][s] should be true if class i contains state s
         let class_state_vars: Vec<Vec<_>> = (0..num_states)
-            .map(|_| self.state_indices().map(|_| solver.new_lit()).collect())
+            .map(|_| self.state_indices().map(|_| formula.new_lit()).collect())
             .collect();
 
         // every state is in some class
         for s in self.state_indices() {
             let class_vars: Vec<_> = (0..num_states).map(|i| class_state_vars[i][s.0]).collect();
-            solver.add_clause(&class_vars);
+            formula.add_clause(&class_vars);
         }
 
         // assign pairwise incompatible states to different classes
         for (i, s) in pairwise_incompatible_states.iter().enumerate() {
-            solver.add_clause(&[class_state_vars[i][s.0]]);
+            formula.add_clause(&[class_state_vars[i][s.0]]);
         }
 
         // compute list of states that could be in each class
@@ -310,13 +347,13 @@ impl<L: Clone> LabelledMachine<L> {
         for (i, state_vars) in class_state_vars.iter().enumerate() {
             for s1 in self.state_indices() {
                 match pairwise_incompatible_states.get(i) {
-                    Some(&s2) if matrix[(s1, s2)] => solver.add_clause(&[!state_vars[s1.0]]),
+                    Some(&s2) if matrix[(s1, s2)] => formula.add_clause(&[!state_vars[s1.0]]),
                     _ => {
                         for s2 in ((s1.0 + 1)..self.num_states())
                             .map(StateIndex)
                             .filter(|&s2| matrix[(s1, s2)])
                         {
-                            solver.add_clause(&[!state_vars[s1.0], !state_vars[s2.0]]);
+                            formula.add_clause(&[!state_vars[s1.0], !state_vars[s2.0]]);
                         }
                     }
                 }
@@ -341,7 +378,7 @@ impl<L: Clone> LabelledMachine<L> {
             let mut class_successor_mapping = Vec::with_capacity(num_actions);
             for a in 0..num_actions {
                 // compute possible successor classes
-                let mut successor_classes = HashSet::with_capacity(num_states);
+                let mut successor_classes = BTreeSet::new();
                 for &s in possible_states {
                     if let Some(successor) = self.successor_under_action(s, a) {
                         successor_classes.extend((0..num_states).filter(|&j| {
@@ -357,18 +394,18 @@ impl<L: Clone> LabelledMachine<L> {
                 } else {
                     successor_mapping = successor_classes
                         .into_iter()
-                        .map(|j| (j, solver.new_lit()))
+                        .map(|j| (j, formula.new_lit()))
                         .collect();
 
                     // clause for disjunction over successor
                     let successor_vars: Vec<_> =
                         successor_mapping.iter().map(|(_, var)| *var).collect();
-                    solver.add_clause(&successor_vars);
+                    formula.add_clause(&successor_vars);
 
                     for &s in possible_states {
                         if let Some(successor) = self.successor_under_action(s, a) {
                             for &(j, var) in &successor_mapping {
-                                solver.add_clause(&[
+                                formula.add_clause(&[
                                     !var,
                                     !class_state_vars[i][s.0],
                                     class_state_vars[j][successor.0],
@@ -382,19 +419,18 @@ impl<L: Clone> LabelledMachine<L> {
             class_successors.push(class_successor_mapping);
         }
 
-        match solver.solve() {
-            Ok(true) => {
+        if max_clauses.map_or(false, |max| formula.len() > max) {
+            return Covering::ClauseLimit(formula.len());
+        }
+        match sat::solve(formula, deadline) {
+            SatResult::Sat(model) => {
                 // obtain class covering and successors
-                let model = solver.model().unwrap();
                 let (classes, successors) =
                     Self::extract_class_model(&model, class_state_vars, class_successors);
-                Some(self.build_machine_from_classes(classes, successors))
-            }
-            Ok(false) => None,
-            Err(err) => {
-                error!("Sat solver error: {}", err);
-                None
+                Covering::Found(self.build_machine_from_classes(classes, successors))
             }
+            SatResult::Unsat => Covering::None,
+            SatResult::Unknown => Covering::Unknown,
         }
     }
 
@@ -439,11 +475,14 @@ impl<L: Clone> LabelledMachine<L> {
         classes: Vec<Vec<StateIndex>>,
         class_successors: Vec<Vec<Vec<StateIndex>>>,
     ) -> LabelledMachine<Vec<L>> {
-        let initial_state = classes
+        // every class containing an initial state may be used as an initial state
+        let initial_states: Vec<_> = classes
             .iter()
             .enumerate()
-            .find_map(|(i, class)| class.contains(&self.initial_state).then(|| StateIndex(i)))
-            .unwrap();
+            .filter(|(_, class)| self.initial_states.iter().any(|s| class.contains(s)))
+            .map(|(i, _)| StateIndex(i))
+            .collect();
+        assert!(!initial_states.is_empty());
 
         let new_states = classes
             .into_iter()
@@ -510,11 +549,23 @@ impl<L: Clone> LabelledMachine<L> {
                     vec![Transition::with_outputs(input, new_transition_outputs)]
                 };
 
+                // inputs are only don't cares if they are for all states of the class
+                let dontcare_inputs = class_states
+                    .iter()
+                    .map(|s| s.dontcare_inputs.as_ref())
+                    .collect::<Option<Vec<_>>>()
+                    .map(|inputs| {
+                        inputs
+                            .iter()
+                            .skip(1)
+                            .fold(inputs[0].clone(), |d1, &d2| d1 & d2)
+                    });
                 State::with_transitions(new_label, new_transitions)
+                    .with_dontcare_inputs(dontcare_inputs)
             })
             .collect();
 
-        self.clone_with(new_states, initial_state)
+        self.clone_with(new_states, initial_states)
     }
 }
 
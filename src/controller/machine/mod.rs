@@ -1,16 +1,33 @@
+mod assumption;
+mod composition;
+mod explicit;
+mod hoa;
+mod hotswap;
+mod kiss;
 mod minimization;
+mod recovery;
+mod repair;
+mod simulation;
+mod symmetry;
 
 use std::collections::{hash_map::Entry, HashMap, VecDeque};
 use std::fmt;
 use std::hash::Hash;
 use std::iter;
-use std::ops::Index;
+use std::ops::{Index, Range};
+use std::time::Instant;
 
 use cudd::{Bdd, CubeValue, Cudd, ReorderingMethod};
-use log::info;
+use log::{debug, info, warn};
 
-use super::bdd::BddController;
+use super::bdd::{BddController, ManagerPool};
 use super::labelling::{LabelInnerValue, LabelValue, Labelling, StructuredLabel};
+use crate::parity::game::generator::SplitMix64;
+pub(crate) use explicit::MAX_EXPLICIT_INPUTS;
+pub use hoa::ParseMachineError;
+pub use hotswap::{StateMapping, StateMappingEntry};
+use minimization::Covering;
+pub(crate) use repair::check_repair_interface;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct StateIndex(usize);
@@ -58,6 +75,10 @@ impl Transition {
 pub(crate) struct State<L> {
     label: L,
     transitions: Vec<Transition>,
+    /// The inputs that are impossible in this state under the assumptions on the
+    /// environment, for which both the outputs and the successor are arbitrary,
+    /// if known.
+    dontcare_inputs: Option<Bdd>,
 }
 
 impl<L> State<L> {
@@ -66,7 +87,16 @@ impl<L> State<L> {
     }
 
     fn with_transitions(label: L, transitions: Vec<Transition>) -> Self {
-        Self { label, transitions }
+        Self {
+            label,
+            transitions,
+            dontcare_inputs: None,
+        }
+    }
+
+    fn with_dontcare_inputs(mut self, dontcare_inputs: Option<Bdd>) -> Self {
+        self.dontcare_inputs = dontcare_inputs;
+        self
     }
 
     fn add_transition(&mut self, transition: Transition) {
@@ -108,19 +138,24 @@ impl<L: Hash + Eq + Clone> LabelledMachineConstructor<L> {
         self.states[state.0].add_transition(transition);
     }
 
+    /// Creates the machine from the added states, where the machine may start
+    /// in any of the given non-empty set of initial states.
     pub(crate) fn into_machine(
         self,
-        initial_state: StateIndex,
+        initial_states: Vec<StateIndex>,
         inputs: Vec<String>,
         outputs: Vec<String>,
         mealy: bool,
     ) -> LabelledMachine<L> {
+        assert!(!initial_states.is_empty(), "machine without initial state");
         LabelledMachine {
             states: self.states,
             inputs,
             outputs,
-            initial_state,
+            initial_states,
             mealy,
+            outputs_first: false,
+            symmetry: None,
         }
     }
 }
@@ -141,13 +176,27 @@ impl<L: Hash + Eq + Clone> LabelledMachineConstructor<L> {
 ///
 /// The machine may also be non-deterministic or deterministic, both in the
 /// possible outputs and successors (for Mealy) and possible inputs (for Moore).
+///
+/// The inputs and outputs of all transitions are BDDs over a common variable space,
+/// where the first variables correspond to the inputs, followed by variables
+/// for the outputs.
+///
+/// The machine has a non-empty set of initial states, where the controller may
+/// start in any of them. The choice of the initial state is deferred until the
+/// reset values of the latches are chosen for a BDD or aiger circuit.
 #[derive(Debug, Clone)]
 pub struct LabelledMachine<L> {
     states: Vec<State<L>>,
     inputs: Vec<String>,
     outputs: Vec<String>,
-    initial_state: StateIndex,
+    initial_states: Vec<StateIndex>,
     mealy: bool,
+    /// Whether the outputs are listed before the inputs in the HOA format.
+    outputs_first: bool,
+    /// The pairs of inputs and outputs that are swapped while the swap latch of
+    /// a machine quotiented by symmetry is set, where the last output toggles
+    /// the latch, see [`Self::quotient_by_symmetry`].
+    symmetry: Option<Vec<(String, String)>>,
 }
 
 impl<L> LabelledMachine<L> {
@@ -155,6 +204,10 @@ impl<L> LabelledMachine<L> {
         self.states.len()
     }
 
+    pub(crate) fn is_mealy(&self) -> bool {
+        self.mealy
+    }
+
     fn num_inputs(&self) -> usize {
         self.inputs.len()
     }
@@ -187,7 +240,7 @@ impl<L> LabelledMachine<L> {
                     }
                     if transition.outputs[0]
                         .output
-                        .cube_iter(self.num_outputs())
+                        .cube_iter(self.num_vars())
                         .count()
                         != 1
                     {
@@ -200,7 +253,7 @@ impl<L> LabelledMachine<L> {
                 }
                 if state.transitions[0]
                     .input
-                    .cube_iter(self.num_inputs())
+                    .cube_iter(self.num_vars())
                     .count()
                     != 1
                 {
@@ -214,14 +267,16 @@ impl<L> LabelledMachine<L> {
     fn clone_with<Lnew>(
         &self,
         new_states: Vec<State<Lnew>>,
-        new_initial_state: StateIndex,
+        new_initial_states: Vec<StateIndex>,
     ) -> LabelledMachine<Lnew> {
         LabelledMachine {
             states: new_states,
             inputs: self.inputs.clone(),
             outputs: self.outputs.clone(),
-            initial_state: new_initial_state,
+            initial_states: new_initial_states,
             mealy: self.mealy,
+            outputs_first: self.outputs_first,
+            symmetry: self.symmetry.clone(),
         }
     }
 
@@ -237,9 +292,80 @@ impl<L> LabelledMachine<L> {
             .map(|s| State {
                 label: labelling.get_label(s.label()),
                 transitions: s.transitions.clone(),
+                dontcare_inputs: s.dontcare_inputs.clone(),
             })
             .collect();
-        self.clone_with(new_states, self.initial_state)
+        self.clone_with(new_states, self.initial_states.clone())
+    }
+
+    /// Widens the input of one transition in each state of a Mealy machine to all inputs
+    /// not covered by the other transitions of the state, if this shortens its label
+    /// in the HOA output, and returns the number of widened transitions.
+    ///
+    /// Inputs without a transition in a state are don't cares, so the widened transition
+    /// acts as an implicit else edge for these inputs. Moore machines are left unchanged,
+    /// as their inputs are chosen by the strategy.
+    pub(crate) fn add_implicit_else(&mut self) -> usize {
+        if !self.mealy {
+            return 0;
+        }
+        let names = self.var_names();
+        let mut num_widened = 0;
+        for state in &mut self.states {
+            let mut best: Option<(usize, usize, Bdd)> = None;
+            for (k, transition) in state.transitions.iter().enumerate() {
+                let mut others = transition.input.manager().bdd_zero();
+                for (l, other) in state.transitions.iter().enumerate() {
+                    if l != k {
+                        others |= &other.input;
+                    }
+                }
+                let widened = !others;
+                let length = transition.input.factored_form_string(&names).len();
+                let widened_length = widened.factored_form_string(&names).len();
+                if widened_length < length
+                    && best
+                        .as_ref()
+                        .map_or(true, |&(_, saving, _)| length - widened_length > saving)
+                {
+                    best = Some((k, length - widened_length, widened));
+                }
+            }
+            if let Some((k, _, widened)) = best {
+                state.transitions[k].input = widened;
+                num_widened += 1;
+            }
+        }
+        num_widened
+    }
+
+    fn var_names(&self) -> Vec<String> {
+        (0..self.num_vars()).map(|i| format!("{}", i)).collect()
+    }
+
+    /// Sets whether the outputs are listed before the inputs in the atomic
+    /// propositions of the HOA format.
+    pub(crate) fn set_outputs_first(&mut self, outputs_first: bool) {
+        self.outputs_first = outputs_first;
+    }
+
+    /// Returns the position of the variable in the atomic propositions of the HOA format.
+    fn ap_position(&self, var: usize) -> usize {
+        if !self.outputs_first {
+            var
+        } else if var < self.num_inputs() {
+            self.num_outputs() + var
+        } else {
+            var - self.num_inputs()
+        }
+    }
+
+    /// Returns the names of the variables in the labels of the HOA format,
+    /// which are their positions in the atomic propositions.
+    fn ap_names(&self) -> Vec<String> {
+        (0..self.num_vars())
+            .map(|var| format!("{}", self.ap_position(var)))
+            .collect()
     }
 
     fn state_indices(&self) -> impl Iterator<Item = StateIndex> {
@@ -266,22 +392,25 @@ where
 }
 
 impl<L: Clone> LabelledMachine<L> {
-    pub(crate) fn determinize(&mut self) {
+    /// Determinizes the machine by keeping the most used successors, outputs and inputs.
+    ///
+    /// If `low_toggling` is set, transitions staying in the same state are preferred
+    /// over the most used successors, reducing the switching activity of the machine.
+    pub(crate) fn determinize(&mut self, low_toggling: bool) {
         info!("Determinizing machine with {} states", self.num_states());
-        let num_inputs = self.num_inputs();
-        let num_outputs = self.num_outputs();
+        let num_vars = self.num_vars();
         // count how often each input, output and successor state is used
         let mut input_count = HashMap::new();
         let mut output_count = HashMap::new();
         let mut successor_count = HashMap::new();
         for state in &self.states {
             for transition in &state.transitions {
-                for input in transition.input.bdd_cube_iter(num_inputs) {
+                for input in transition.input.bdd_cube_iter(num_vars) {
                     *input_count.entry(input).or_insert(0_usize) += 1;
                 }
                 for output in &transition.outputs {
                     *successor_count.entry(output.successor).or_insert(0_usize) += 1;
-                    for output_bdd in output.output.bdd_cube_iter(num_outputs) {
+                    for output_bdd in output.output.bdd_cube_iter(num_vars) {
                         *output_count.entry(output_bdd).or_insert(0_usize) += 1;
                     }
                 }
@@ -289,12 +418,18 @@ impl<L: Clone> LabelledMachine<L> {
         }
         if self.mealy {
             // keep most used successor and then most used output in each transition
-            for state in &mut self.states {
+            for (index, state) in self.states.iter_mut().enumerate() {
+                let index = StateIndex(index);
                 for transition in &mut state.transitions {
-                    keep_max_by_key(&mut transition.outputs, |o| successor_count[&o.successor]);
+                    keep_max_by_key(&mut transition.outputs, |o| {
+                        (
+                            low_toggling && o.successor == index,
+                            successor_count[&o.successor],
+                        )
+                    });
                     let output_bdd = transition.outputs[0]
                         .output
-                        .bdd_cube_iter(num_outputs)
+                        .bdd_cube_iter(num_vars)
                         .max_by_key(|o| output_count[o])
                         .unwrap();
                     transition.outputs[0].output = output_bdd;
@@ -302,36 +437,102 @@ impl<L: Clone> LabelledMachine<L> {
             }
         } else {
             // keep inputs with most used successors and most used input
-            for state in &mut self.states {
+            for (index, state) in self.states.iter_mut().enumerate() {
+                let index = StateIndex(index);
                 keep_max_by_key(&mut state.transitions, |t| {
-                    t.outputs
+                    let self_loops = if low_toggling {
+                        t.outputs.iter().filter(|o| o.successor == index).count()
+                    } else {
+                        0
+                    };
+                    let successors = t
+                        .outputs
                         .iter()
                         .map(|o| successor_count[&o.successor])
-                        .sum::<usize>()
+                        .sum::<usize>();
+                    (self_loops, successors)
                 });
                 let input_bdd = state.transitions[0]
                     .input
-                    .bdd_cube_iter(num_inputs)
+                    .bdd_cube_iter(num_vars)
                     .max_by_key(|i| input_count[i])
                     .unwrap();
                 state.transitions[0].input = input_bdd;
             }
         }
 
-        // remove unreachable states
+        self.remove_unreachable_states();
+        info!("Determinized machine has {} states", self.num_states());
+    }
+
+    /// Determinizes the machine by choosing uniformly at random among the choices of
+    /// each transition, using a pseudo-random number generator with the given seed.
+    ///
+    /// For a Mealy machine, the successor and output cube of each transition are chosen
+    /// among all pairs of a successor and a cube of its outputs, and otherwise the input
+    /// cube of each state is chosen among all cubes of its transitions. Different seeds
+    /// thus yield different winning strategies wherever the machine is non-deterministic.
+    pub(crate) fn determinize_randomly(&mut self, seed: u64) {
+        info!(
+            "Determinizing machine with {} states randomly with seed {}",
+            self.num_states(),
+            seed
+        );
+        let num_vars = self.num_vars();
+        let mut random = SplitMix64::new(seed);
+        if self.mealy {
+            for state in &mut self.states {
+                for transition in &mut state.transitions {
+                    let mut choices: Vec<_> = transition
+                        .outputs
+                        .iter()
+                        .flat_map(|o| {
+                            o.output
+                                .bdd_cube_iter(num_vars)
+                                .map(move |cube| TransitionOutput::new(cube, o.successor))
+                        })
+                        .collect();
+                    let choice = choices.swap_remove(random.below(choices.len()));
+                    transition.outputs = vec![choice];
+                }
+            }
+        } else {
+            for state in &mut self.states {
+                let mut choices: Vec<_> = state
+                    .transitions
+                    .iter()
+                    .flat_map(|t| {
+                        t.input
+                            .bdd_cube_iter(num_vars)
+                            .map(move |cube| Transition::with_outputs(cube, t.outputs.clone()))
+                    })
+                    .collect();
+                let choice = choices.swap_remove(random.below(choices.len()));
+                state.transitions = vec![choice];
+            }
+        }
+
+        self.remove_unreachable_states();
+        info!("Determinized machine has {} states", self.num_states());
+    }
+
+    fn remove_unreachable_states(&mut self) {
         let keep = self.reachable_states();
         if keep.iter().any(std::ops::Not::not) {
             *self = self.remove_states(&keep);
         }
-        info!("Determinized machine has {} states", self.num_states());
     }
 
     fn reachable_states(&self) -> Vec<bool> {
         let n = self.num_states();
         let mut reachable = vec![false; n];
         let mut queue = VecDeque::with_capacity(n);
-        reachable[self.initial_state.0] = true;
-        queue.push_back(self.initial_state);
+        for &initial_state in &self.initial_states {
+            if !reachable[initial_state.0] {
+                reachable[initial_state.0] = true;
+                queue.push_back(initial_state);
+            }
+        }
         while let Some(state_index) = queue.pop_front() {
             let state = &self[state_index];
             for transition in &state.transitions {
@@ -356,7 +557,10 @@ impl<L: Clone> LabelledMachine<L> {
         for (index, state) in self.states_with_index() {
             if keep[index.0] {
                 let new_index = new_states.len();
-                new_states.push(State::new(state.label().clone()));
+                new_states.push(
+                    State::new(state.label().clone())
+                        .with_dontcare_inputs(state.dontcare_inputs.clone()),
+                );
                 state_mapping.push(new_index);
             } else {
                 state_mapping.push(0);
@@ -388,9 +592,14 @@ impl<L: Clone> LabelledMachine<L> {
             }
         }
         // create new machine
-        assert!(keep[self.initial_state.0]);
-        let new_initial_state = StateIndex(state_mapping[self.initial_state.0]);
-        self.clone_with(new_states, new_initial_state)
+        let new_initial_states: Vec<_> = self
+            .initial_states
+            .iter()
+            .filter(|s| keep[s.0])
+            .map(|s| StateIndex(state_mapping[s.0]))
+            .collect();
+        assert!(!new_initial_states.is_empty());
+        self.clone_with(new_states, new_initial_states)
     }
 
     pub(crate) fn minimize_with_nondeterminism(&self) -> Self {
@@ -407,7 +616,23 @@ impl<L: Clone> LabelledMachine<L> {
 }
 
 impl<L: Clone + Eq + Hash + Ord> LabelledMachine<L> {
-    pub(crate) fn minimize_with_dontcares(&self, compress_labels: bool) -> LabelledMachine<Vec<L>> {
+    /// Minimizes the machine using don't cares by a binary search for covering machines
+    /// between a lower bound on the size and the size of the smallest covering machine
+    /// found so far, starting with the size of this machine.
+    ///
+    /// The search is anytime: if the given deadline passes, the search stops and the
+    /// smallest machine found so far is used, or this machine if none was found. Sizes
+    /// for which the SAT encoding exceeds the given maximum number of clauses are not
+    /// tried, and neither are larger sizes, as the encoding only grows with the size.
+    /// The given function is called with the lower and upper bound on the minimal size
+    /// after each size that was tried.
+    pub(crate) fn minimize_with_dontcares(
+        &self,
+        compress_labels: bool,
+        deadline: Option<Instant>,
+        max_clauses: Option<usize>,
+        on_bounds: &mut dyn FnMut(usize, usize),
+    ) -> LabelledMachine<Vec<L>> {
         info!(
             "Minimizing machine with {} states using don't cares",
             self.num_states()
@@ -421,38 +646,121 @@ impl<L: Clone + Eq + Hash + Ord> LabelledMachine<L> {
         let matrix = self.compute_incompatability_matrix();
         let classes = matrix.compute_transitively_compatible_states();
         let pairwise_incompatible_states = self.find_pairwise_incompatible_states(&matrix);
-        let lower_bound = pairwise_incompatible_states.len();
-        assert!((1..=n).contains(&lower_bound));
-
-        if lower_bound < n {
+        let mut lower = pairwise_incompatible_states.len();
+        assert!((1..=n).contains(&lower));
+
+        // the size of the smallest machine found so far, and the size from which on
+        // the encoding exceeds the maximum number of clauses
+        let mut upper = n;
+        let mut limit = n;
+        let mut best = None;
+        if lower < n {
             let split_machine = self.split_actions(&classes);
-            for num_states in lower_bound..n {
-                if let Some(mut min_machine) = split_machine.find_covering_machine(
+            while lower < upper.min(limit) {
+                let num_states = lower + (upper.min(limit) - lower) / 2;
+                if deadline.map_or(false, |d| Instant::now() >= d) {
+                    info!(
+                        "Time budget for minimization exhausted before trying {} states, \
+                        minimal size is between {} and {}",
+                        num_states, lower, upper
+                    );
+                    break;
+                }
+                match split_machine.find_covering_machine(
                     num_states,
                     &matrix,
                     &pairwise_incompatible_states,
+                    max_clauses,
+                    deadline,
                 ) {
-                    info!(
-                        "Minimized machine to {} states using don't cares",
-                        min_machine.num_states()
-                    );
-                    if compress_labels {
-                        min_machine.compress_label_features();
+                    Covering::Found(min_machine) => {
+                        upper = min_machine.num_states();
+                        best = Some(min_machine);
+                    }
+                    Covering::None => lower = num_states + 1,
+                    Covering::ClauseLimit(clauses) => {
+                        warn!(
+                            "Encoding for minimization to {} states has {} clauses, exceeding the limit of {}",
+                            num_states,
+                            clauses,
+                            max_clauses.unwrap()
+                        );
+                        limit = num_states;
+                    }
+                    Covering::Unknown => {
+                        info!(
+                            "Search for machine with {} states abandoned, \
+                            minimal size is between {} and {}",
+                            num_states, lower, upper
+                        );
+                        break;
                     }
-                    return min_machine;
                 }
+                debug!(
+                    "Minimal size of machine using don't cares is between {} and {}",
+                    lower, upper
+                );
+                on_bounds(lower, upper);
             }
         }
+        if let Some(mut min_machine) = best {
+            info!(
+                "Minimized machine to {} states using don't cares",
+                min_machine.num_states()
+            );
+            if compress_labels {
+                min_machine.compress_label_features();
+            }
+            return min_machine;
+        }
         // no further minimization possible, return copy of current machine
         let new_states = self
             .states()
             .map(|state| {
                 State::with_transitions(vec![state.label().clone()], state.transitions.clone())
+                    .with_dontcare_inputs(state.dontcare_inputs.clone())
             })
             .collect();
         info!("No further minimization using don't cares possible");
-        self.clone_with(new_states, self.initial_state)
+        self.clone_with(new_states, self.initial_states.clone())
+    }
+}
+
+/// Copies the given BDD to the given manager, keeping only the variables in the
+/// given range and shifting them to start at the given offset.
+fn copy_bdd(bdd: &Bdd, vars: Range<usize>, manager: &Cudd, offset: usize) -> Bdd {
+    let mut copy = manager.bdd_zero();
+    for cube in bdd.cube_iter(vars.end) {
+        let mut cube_bdd = manager.bdd_one();
+        for (var, value) in cube.iter().enumerate().skip(vars.start).take(vars.len()) {
+            let new_var = offset + var - vars.start;
+            match value {
+                CubeValue::Set => cube_bdd &= manager.bdd_var(new_var),
+                CubeValue::Unset => cube_bdd &= !manager.bdd_var(new_var),
+                CubeValue::Unspecified => (),
+            }
+        }
+        copy |= cube_bdd;
+    }
+    copy
+}
+
+/// Copies the given BDD over `num_vars` variables to the given manager,
+/// replacing each variable by the variable given in the mapping.
+fn map_bdd(bdd: &Bdd, num_vars: usize, mapping: &[usize], manager: &Cudd) -> Bdd {
+    let mut copy = manager.bdd_zero();
+    for cube in bdd.cube_iter(num_vars) {
+        let mut cube_bdd = manager.bdd_one();
+        for (var, value) in cube.iter().enumerate() {
+            match value {
+                CubeValue::Set => cube_bdd &= manager.bdd_var(mapping[var]),
+                CubeValue::Unset => cube_bdd &= !manager.bdd_var(mapping[var]),
+                CubeValue::Unspecified => (),
+            }
+        }
+        copy |= cube_bdd;
     }
+    copy
 }
 
 fn bdd_for_label(
@@ -551,19 +859,130 @@ impl LabelledMachine<StructuredLabel> {
         values
     }
 
-    pub(crate) fn compress_label_values(&mut self) {
-        let mut component_values = self.component_values();
-        for vals in &mut component_values {
-            vals.sort();
-        }
-        let mapping: Vec<HashMap<LabelValue, LabelInnerValue>> = component_values
+    /// Returns the number of state variables for each component of the labels, which
+    /// is the maximal number of bits of the values of the component.
+    fn state_widths(&self) -> Vec<u32> {
+        self.component_values()
             .into_iter()
-            .map(|mut vals| {
+            .map(|vals| vals.into_iter().map(|v| v.num_bits()).max().unwrap())
+            .collect()
+    }
+
+    /// Returns the values of the state variables for each state, which are the values
+    /// of the latches of BDD and aiger controllers created from this machine, where
+    /// don't care values of the labels are set to false.
+    pub fn state_bits(&self) -> Vec<Vec<bool>> {
+        let widths = self.state_widths();
+        self.labels()
+            .map(|label| bits_for_label(label, &widths))
+            .collect()
+    }
+
+    /// Computes a mapping of the values of each component to new values, such that
+    /// the values of states with many transitions between them are mapped to values
+    /// differing in few bits, reducing the toggling of latches in a circuit.
+    ///
+    /// Values are assigned greedily in order of their number of transitions,
+    /// each to the free value with the least Hamming distance to the values
+    /// of already assigned neighbours, weighted by the number of transitions.
+    /// The number of bits needed for each component is the same as for
+    /// a dense assignment of values.
+    fn low_toggling_value_mapping(&self) -> Vec<HashMap<LabelValue, LabelInnerValue>> {
+        // greedy assignment is quadratic, so skip it for components with many values
+        const MAX_VALUES: usize = 4096;
+
+        let mut component_values = self.component_values();
+        component_values
+            .iter_mut()
+            .enumerate()
+            .map(|(c, vals)| {
                 vals.sort();
                 vals.dedup();
-                vals.into_iter().filter(|v| v.is_value()).zip(0..).collect()
+                vals.retain(|v| v.is_value());
+                let n = vals.len();
+                let index: HashMap<LabelValue, usize> = vals.iter().copied().zip(0..).collect();
+                let value_index = |label: &StructuredLabel| {
+                    label.iter().nth(c).and_then(|v| index.get(v).copied())
+                };
+
+                // count transitions between states with different values
+                let mut weights = HashMap::new();
+                for state in self.states() {
+                    if let Some(from) = value_index(state.label()) {
+                        for transition in &state.transitions {
+                            for output in &transition.outputs {
+                                if let Some(to) = value_index(self[output.successor].label()) {
+                                    if from != to {
+                                        let edge = (from.min(to), from.max(to));
+                                        *weights.entry(edge).or_insert(0_usize) += 1;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                let mut neighbours = vec![Vec::new(); n];
+                let mut total_weight = vec![0; n];
+                for (&(a, b), &w) in &weights {
+                    neighbours[a].push((b, w));
+                    neighbours[b].push((a, w));
+                    total_weight[a] += w;
+                    total_weight[b] += w;
+                }
+
+                let mut codes: Vec<Option<LabelInnerValue>> = vec![None; n];
+                if n > MAX_VALUES {
+                    for (i, code) in codes.iter_mut().enumerate() {
+                        *code = Some(i as LabelInnerValue);
+                    }
+                } else {
+                    let mut order: Vec<_> = (0..n).collect();
+                    order.sort_by_key(|&i| std::cmp::Reverse(total_weight[i]));
+                    let num_codes = n.next_power_of_two();
+                    let mut used = vec![false; num_codes];
+                    for i in order {
+                        let cost = |code: LabelInnerValue| {
+                            neighbours[i]
+                                .iter()
+                                .filter_map(|&(j, w)| {
+                                    codes[j].map(|c| w * (c ^ code).count_ones() as usize)
+                                })
+                                .sum::<usize>()
+                        };
+                        let best = (0..num_codes)
+                            .filter(|&code| !used[code])
+                            .min_by_key(|&code| cost(code as LabelInnerValue))
+                            .unwrap();
+                        used[best] = true;
+                        codes[i] = Some(best as LabelInnerValue);
+                    }
+                }
+                vals.iter()
+                    .copied()
+                    .zip(codes.into_iter().map(Option::unwrap))
+                    .collect()
             })
-            .collect();
+            .collect()
+    }
+
+    /// Compresses the values of each component of the labels to a range of
+    /// consecutive values starting at zero.
+    ///
+    /// If `low_toggling` is set, the values are instead assigned to reduce
+    /// the toggling of latches, see [`Self::low_toggling_value_mapping`].
+    pub(crate) fn compress_label_values(&mut self, low_toggling: bool) {
+        let mapping: Vec<HashMap<LabelValue, LabelInnerValue>> = if low_toggling {
+            self.low_toggling_value_mapping()
+        } else {
+            self.component_values()
+                .into_iter()
+                .map(|mut vals| {
+                    vals.sort();
+                    vals.dedup();
+                    vals.into_iter().filter(|v| v.is_value()).zip(0..).collect()
+                })
+                .collect()
+        };
 
         for state in &mut self.states {
             for (v, m) in state.label.iter_mut().zip(mapping.iter()) {
@@ -574,106 +993,343 @@ impl LabelledMachine<StructuredLabel> {
         }
     }
 
-    pub(crate) fn create_bdds(&self) -> BddController {
+    /// Creates BDDs for the outputs and the next-state functions of this machine.
+    ///
+    /// If a partition threshold is given and the number of BDD variables exceeds it,
+    /// the BDDs are partitioned across several managers, and otherwise a single
+    /// manager is used.
+    ///
+    /// If `group_components` is set, the bits of each component of the labels
+    /// are kept adjacent during reordering, instead of being interleaved with
+    /// the bits of other components.
+    pub(crate) fn create_bdds(
+        &self,
+        partition_threshold: Option<usize>,
+        group_components: bool,
+    ) -> BddController {
         info!("Constructing BDD from machine");
         assert!(
             self.is_deterministic(),
             "can only create BDDs from deterministic machine"
         );
 
-        // compute bit widths of each label
-        let initial_label = self[self.initial_state].label();
-        let component_values = self.component_values();
-        let widths: Vec<u32> = component_values
-            .into_iter()
-            .map(|vals| vals.into_iter().map(|v| v.num_bits()).max().unwrap())
-            .collect();
-
-        let num_state_vars = widths.iter().sum::<u32>() as usize;
+        let widths = self.state_widths();
+        // a machine quotiented by symmetry has a swap latch after the bits of the labels,
+        // which is toggled by the last output
+        let swap = self.symmetry.as_ref().map(|swaps| {
+            self.swap_permutation(swaps)
+                .expect("invalid symmetry of machine")
+        });
+        assert!(
+            self.mealy || swap.is_none(),
+            "moore machine can not be quotiented by symmetry"
+        );
+        let num_swap_vars = usize::from(swap.is_some());
+        let num_label_vars = widths.iter().sum::<u32>() as usize;
+        let num_state_vars = num_label_vars + num_swap_vars;
         let num_controllable_vars = if self.mealy {
-            self.num_outputs()
+            self.num_outputs() - num_swap_vars
         } else {
             self.num_inputs()
         };
-        let num_uncontrollable_vars = self.num_vars() - num_controllable_vars;
+        let num_uncontrollable_vars = self.num_vars() - num_controllable_vars - num_swap_vars;
         let num_vars = num_uncontrollable_vars + num_state_vars;
 
-        let mut manager = Cudd::with_vars(num_vars).unwrap();
-        manager.autodyn_enable(ReorderingMethod::Sift);
+        let mut pool = ManagerPool::new(
+            num_vars,
+            num_controllable_vars + num_state_vars,
+            partition_threshold,
+        );
+        if group_components {
+            let mut offset = num_uncontrollable_vars;
+            for &width in &widths {
+                let width = width as usize;
+                if width > 1 {
+                    pool.make_group(offset, width);
+                }
+                offset += width;
+            }
+        }
+        pool.autodyn_enable(ReorderingMethod::Sift);
+        // the roots are the controlled variables followed by the successor bits
+        let partitions: Vec<_> = (0..num_controllable_vars + num_state_vars)
+            .map(|root| pool.partition(root))
+            .collect();
+        let controlled_partition = |var: usize| partitions[var];
+        let successor_partition = |var: usize| partitions[num_controllable_vars + var];
+        let managers = pool.managers();
 
-        let mut successor_bdds = vec![manager.bdd_zero(); num_state_vars];
-        let mut controlled_bdds = vec![manager.bdd_zero(); num_controllable_vars];
+        let mut successor_bdds: Vec<_> = (0..num_state_vars)
+            .map(|var| managers[successor_partition(var)].bdd_zero())
+            .collect();
+        let mut controlled_bdds: Vec<_> = (0..num_controllable_vars)
+            .map(|var| managers[controlled_partition(var)].bdd_zero())
+            .collect();
+        // inputs with arbitrary outputs, e.g. from assumptions on the environment
+        let mut dontcare_bdds: Vec<_> = managers.iter().map(Cudd::bdd_zero).collect();
+        // inputs with arbitrary outputs and successors, as they violate the assumptions
+        let mut impossible_bdds: Vec<_> = managers.iter().map(Cudd::bdd_zero).collect();
+        // states with arbitrary values of a controlled variable of a moore machine
+        let mut latched_dontcare_bdds: Vec<_> = (0..num_controllable_vars)
+            .map(|var| managers[controlled_partition(var)].bdd_zero())
+            .collect();
+        let mut num_dontcare_states = 0;
+
+        // the inputs and outputs are swapped while the swap latch is set, where input
+        // variables come first in all managers
+        let swap_latch = num_uncontrollable_vars + num_label_vars;
+        let swapped_var = |var: usize, swapped: bool| match &swap {
+            Some(permutation) if swapped => permutation[var],
+            _ => var,
+        };
+        let swapped_input = |input: &Bdd, manager: &Cudd, swapped: bool| match &swap {
+            Some(permutation) if swapped => input
+                .transfer(manager)
+                .permute(&permutation[..self.num_inputs()]),
+            _ => input.transfer(manager),
+        };
 
         for state in &self.states {
-            let state_bdd =
-                bdd_for_label(state.label(), &manager, num_uncontrollable_vars, &widths);
-            if self.mealy {
-                for transition in &state.transitions {
-                    let input_bdd = transition.input.transfer(&manager);
-                    let combined_bdd = input_bdd & &state_bdd;
-                    // get first cube and successor of first output
-                    let transition_output = &transition.outputs[0];
-                    let cube_out = transition_output
-                        .output
-                        .cube_iter(self.num_outputs())
-                        .next()
-                        .unwrap();
-                    let successor_label = self[transition_output.successor].label();
-                    let successor_bits = bits_for_label(successor_label, &widths);
-                    for (bdd, v) in controlled_bdds.iter_mut().zip(cube_out.iter()) {
-                        if *v == CubeValue::Set {
-                            *bdd |= &combined_bdd;
+            let state_bdds: Vec<_> = managers
+                .iter()
+                .map(|manager| {
+                    bdd_for_label(state.label(), manager, num_uncontrollable_vars, &widths)
+                })
+                .collect();
+            // each state of a machine quotiented by symmetry is also entered with swapped
+            // inputs and outputs, i.e. with the swap latch set
+            let frames: Vec<(bool, Vec<Bdd>)> = if swap.is_some() {
+                [false, true]
+                    .iter()
+                    .map(|&swapped| {
+                        let frame_bdds = managers
+                            .iter()
+                            .zip(&state_bdds)
+                            .map(|(manager, state_bdd)| {
+                                let latch = manager.bdd_var(swap_latch);
+                                state_bdd & if swapped { latch } else { !latch }
+                            })
+                            .collect();
+                        (swapped, frame_bdds)
+                    })
+                    .collect()
+            } else {
+                vec![(false, state_bdds.clone())]
+            };
+            if let Some(dontcare_inputs) = &state.dontcare_inputs {
+                if !dontcare_inputs.is_zero() {
+                    num_dontcare_states += 1;
+                    for (swapped, frame_bdds) in &frames {
+                        for ((impossible_bdd, manager), frame_bdd) in
+                            impossible_bdds.iter_mut().zip(managers).zip(frame_bdds)
+                        {
+                            *impossible_bdd |=
+                                swapped_input(dontcare_inputs, manager, *swapped) & frame_bdd;
                         }
                     }
-                    for (var, bdd) in successor_bdds.iter_mut().enumerate() {
-                        if successor_bits[var] {
-                            *bdd |= &combined_bdd;
+                }
+            }
+            if self.mealy {
+                for (swapped, frame_bdds) in &frames {
+                    for transition in &state.transitions {
+                        let combined_bdds: Vec<_> = managers
+                            .iter()
+                            .zip(frame_bdds)
+                            .map(|(manager, frame_bdd)| {
+                                swapped_input(&transition.input, manager, *swapped) & frame_bdd
+                            })
+                            .collect();
+                        // get first cube and successor of first output
+                        let transition_output = &transition.outputs[0];
+                        let cube_out = transition_output
+                            .output
+                            .cube_iter(self.num_vars())
+                            .next()
+                            .unwrap();
+                        let output_values: Vec<_> = cube_out
+                            .iter()
+                            .skip(self.num_inputs())
+                            .take(num_controllable_vars)
+                            .copied()
+                            .collect();
+                        if output_values.iter().all(|&v| v == CubeValue::Unspecified) {
+                            for (dontcare_bdd, combined_bdd) in
+                                dontcare_bdds.iter_mut().zip(&combined_bdds)
+                            {
+                                *dontcare_bdd |= combined_bdd;
+                            }
+                        }
+                        let successor_label = self[transition_output.successor].label();
+                        let mut successor_bits = bits_for_label(successor_label, &widths);
+                        if swap.is_some() {
+                            let toggled = cube_out[self.num_vars() - 1] == CubeValue::Set;
+                            successor_bits.push(*swapped != toggled);
+                        }
+                        for (var, &v) in output_values.iter().enumerate() {
+                            if v == CubeValue::Set {
+                                let var = swapped_var(self.num_inputs() + var, *swapped)
+                                    - self.num_inputs();
+                                controlled_bdds[var] |= &combined_bdds[controlled_partition(var)];
+                            }
+                        }
+                        for (var, bdd) in successor_bdds.iter_mut().enumerate() {
+                            if successor_bits[var] {
+                                *bdd |= &combined_bdds[successor_partition(var)];
+                            }
                         }
                     }
                 }
             } else {
-                // get first cube of first input
-                let transition = &state.transitions[0];
-                let cube_in = transition
-                    .input
-                    .cube_iter(self.num_inputs())
-                    .next()
-                    .unwrap();
-                for (bdd, v) in controlled_bdds.iter_mut().zip(cube_in.iter()) {
-                    if *v == CubeValue::Set {
-                        *bdd |= &state_bdd;
+                // the controlled values are latched in the state, and responses
+                // without a transition lead to an arbitrary successor
+                for (var, value) in self.latched_values(state).into_iter().enumerate() {
+                    let partition = controlled_partition(var);
+                    match value {
+                        Some(true) => controlled_bdds[var] |= &state_bdds[partition],
+                        Some(false) => (),
+                        None => latched_dontcare_bdds[var] |= &state_bdds[partition],
                     }
                 }
-                for transition_output in &transition.outputs {
-                    let output_bdd = transition_output.output.transfer(&manager);
-                    let combined_bdd = output_bdd & &state_bdd;
+                let mut uncovered_bdds: Vec<_> = state_bdds.clone();
+                for transition_output in &state.transitions[0].outputs {
+                    let combined_bdds: Vec<_> = managers
+                        .iter()
+                        .zip(&state_bdds)
+                        .map(|(manager, state_bdd)| {
+                            copy_bdd(
+                                &transition_output.output,
+                                self.num_inputs()..self.num_vars(),
+                                manager,
+                                0,
+                            ) & state_bdd
+                        })
+                        .collect();
                     let successor_label = self[transition_output.successor].label();
                     let successor_bits = bits_for_label(successor_label, &widths);
                     for (var, bdd) in successor_bdds.iter_mut().enumerate() {
                         if successor_bits[var] {
-                            *bdd |= &combined_bdd;
+                            *bdd |= &combined_bdds[successor_partition(var)];
                         }
                     }
+                    for (uncovered_bdd, combined_bdd) in
+                        uncovered_bdds.iter_mut().zip(&combined_bdds)
+                    {
+                        *uncovered_bdd &= !combined_bdd;
+                    }
                 }
+                for (impossible_bdd, uncovered_bdd) in
+                    impossible_bdds.iter_mut().zip(uncovered_bdds)
+                {
+                    *impossible_bdd |= uncovered_bdd;
+                }
+            }
+        }
+        pool.autodyn_disable();
+        if num_dontcare_states > 0 {
+            info!(
+                "Using impossible inputs under assumptions as don't cares in {} states",
+                num_dontcare_states
+            );
+        }
+        // the controlled values of a moore machine may not depend on the responses
+        if self.mealy {
+            for (dontcare_bdd, impossible_bdd) in dontcare_bdds.iter_mut().zip(&impossible_bdds) {
+                *dontcare_bdd |= impossible_bdd;
+            }
+        }
+        for (var, bdd) in controlled_bdds.iter_mut().enumerate() {
+            let dontcare_bdd =
+                &dontcare_bdds[controlled_partition(var)] | &latched_dontcare_bdds[var];
+            if !dontcare_bdd.is_zero() {
+                *bdd = bdd.restrict(&!dontcare_bdd);
+            }
+        }
+        for (var, bdd) in successor_bdds.iter_mut().enumerate() {
+            let impossible_bdd = &impossible_bdds[successor_partition(var)];
+            if !impossible_bdd.is_zero() {
+                *bdd = bdd.restrict(&!impossible_bdd);
             }
         }
-        manager.autodyn_disable();
 
-        let initial_bits = bits_for_label(initial_label, &widths);
+        // the controller starts with the swap latch unset
+        let initial_bits = self
+            .initial_states
+            .iter()
+            .map(|&s| {
+                let mut bits = bits_for_label(self[s].label(), &widths);
+                bits.extend(iter::repeat(false).take(num_swap_vars));
+                bits
+            })
+            .collect();
+        let outputs = self.outputs[..self.num_outputs() - num_swap_vars].to_vec();
         let (bdd_inputs, bdd_outputs) = if self.mealy {
-            (&self.inputs, &self.outputs)
+            (self.inputs.clone(), outputs)
         } else {
-            (&self.outputs, &self.inputs)
+            (outputs, self.inputs.clone())
         };
         BddController::new(
-            bdd_inputs.clone(),
-            bdd_outputs.clone(),
+            bdd_inputs,
+            bdd_outputs,
             initial_bits,
             successor_bdds,
             controlled_bdds,
-            manager,
+            pool,
         )
     }
+
+    /// Returns the values of the controlled variables of a Moore machine in the given
+    /// state, where `None` is an arbitrary value.
+    ///
+    /// The values are latched in the state, i.e. they are given by the single input
+    /// cube of the state and independent of the responses, which in turn need to lead
+    /// to a unique successor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state does not have a single transition with a single input cube
+    /// over the inputs, if the outputs of the transition overlap, or if the state has
+    /// don't care inputs.
+    fn latched_values(&self, state: &State<L>) -> Vec<Option<bool>> {
+        assert!(
+            state.dontcare_inputs.as_ref().map_or(true, Bdd::is_zero),
+            "moore machine can not have don't care inputs"
+        );
+        assert_eq!(
+            state.transitions.len(),
+            1,
+            "moore machine needs a single transition in each state"
+        );
+        let transition = &state.transitions[0];
+        let mut cubes = transition.input.cube_iter(self.num_vars());
+        let cube = cubes
+            .next()
+            .expect("moore machine needs a non-empty input in each state");
+        assert!(
+            cubes.next().is_none(),
+            "moore machine needs a single input cube in each state"
+        );
+        let values: Vec<_> = cube
+            .iter()
+            .map(|value| match value {
+                CubeValue::Set => Some(true),
+                CubeValue::Unset => Some(false),
+                CubeValue::Unspecified => None,
+            })
+            .collect();
+        assert!(
+            values[self.num_inputs()..].iter().all(Option::is_none),
+            "inputs of moore machine can not depend on the outputs"
+        );
+        for (k, output) in transition.outputs.iter().enumerate() {
+            assert!(
+                transition.outputs[..k]
+                    .iter()
+                    .all(|other| (&other.output & &output.output).is_zero()),
+                "outputs of moore machine need to have a unique successor"
+            );
+        }
+        values[..self.num_inputs()].to_vec()
+    }
 }
 
 impl<L> Index<StateIndex> for LabelledMachine<L> {
@@ -710,11 +1366,10 @@ impl<L: fmt::Display> fmt::Display for State<L> {
 }
 
 impl<L: fmt::Display> fmt::Display for LabelledMachine<L> {
+    /// Writes the machine in HOA format, where each state is named by its label,
+    /// such that a machine with structured labels can be read back with its labels.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let input_names: Vec<_> = (0..self.num_inputs()).map(|i| format!("{}", i)).collect();
-        let output_names: Vec<_> = (self.num_inputs()..self.num_vars())
-            .map(|i| format!("{}", i))
-            .collect();
+        let names = self.ap_names();
 
         // write header
         writeln!(f, "HOA: v1")?;
@@ -725,26 +1380,34 @@ impl<L: fmt::Display> fmt::Display for LabelledMachine<L> {
             env!("CARGO_PKG_VERSION")
         )?;
         writeln!(f, "States: {}", self.num_states())?;
-        writeln!(f, "Start: {}", self.initial_state)?;
-        write!(f, "AP: {}", self.num_vars())?;
-        for input in &self.inputs {
-            write!(f, " \"{}\"", input)?;
+        for initial_state in &self.initial_states {
+            writeln!(f, "Start: {}", initial_state)?;
         }
-        for output in &self.outputs {
-            write!(f, " \"{}\"", output)?;
+        write!(f, "AP: {}", self.num_vars())?;
+        let aps = if self.outputs_first {
+            self.outputs.iter().chain(&self.inputs)
+        } else {
+            self.inputs.iter().chain(&self.outputs)
+        };
+        for ap in aps {
+            write!(f, " \"{}\"", ap)?;
         }
         writeln!(f)?;
         write!(f, "controllable-AP:")?;
-        if self.mealy {
-            for o in self.num_inputs()..self.num_vars() {
-                write!(f, " {}", o)?;
-            }
+        let controllable = if self.mealy {
+            self.num_inputs()..self.num_vars()
         } else {
-            for i in 0..self.num_inputs() {
-                write!(f, " {}", i)?;
-            }
+            0..self.num_inputs()
+        };
+        for var in controllable {
+            write!(f, " {}", self.ap_position(var))?;
         }
         writeln!(f)?;
+        if self.outputs_first {
+            // the type is otherwise determined by the position of the controllable propositions
+            let machine_type = if self.mealy { "mealy" } else { "moore" };
+            writeln!(f, "machine-type: {}", machine_type)?;
+        }
         writeln!(f, "acc-name: all")?;
         writeln!(f, "Acceptance: 0 t")?;
 
@@ -753,9 +1416,9 @@ impl<L: fmt::Display> fmt::Display for LabelledMachine<L> {
         for (index, state) in self.states_with_index() {
             writeln!(f, "State: {} \"{}\"", index, state.label())?;
             for t in &state.transitions {
-                let input = t.input.factored_form_string(&input_names);
+                let input = t.input.factored_form_string(&names);
                 for out in &t.outputs {
-                    let output = out.output.factored_form_string(&output_names);
+                    let output = out.output.factored_form_string(&names);
                     writeln!(f, "[({}) & ({})] {}", input, output, out.successor)?;
                 }
             }
@@ -764,3 +1427,643 @@ impl<L: fmt::Display> fmt::Display for LabelledMachine<L> {
         Ok(())
     }
 }
+
+/// Tests for machines.
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    use aiger::AndCache;
+
+    use super::super::CompositionError;
+    use super::*;
+
+    /// Creates a non-deterministic Mealy machine with one input and one output.
+    fn nondeterministic_machine() -> LabelledMachine<usize> {
+        let manager = Cudd::with_vars(2).unwrap();
+        let a = manager.bdd_var(0);
+        let b = manager.bdd_var(1);
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let (s0, _) = constructor.add_state(0);
+        let (s1, _) = constructor.add_state(1);
+        let mut t0 = Transition::new(a.clone());
+        t0.add_output(b.clone(), s0);
+        t0.add_output(!&b, s1);
+        constructor.add_transition(s0, t0);
+        let mut t1 = Transition::new(!&a);
+        t1.add_output(!&b, s1);
+        constructor.add_transition(s0, t1);
+        let mut t2 = Transition::new(manager.bdd_one());
+        t2.add_output(b, s0);
+        constructor.add_transition(s1, t2);
+        constructor.into_machine(vec![s0], vec!["a".to_string()], vec!["b".to_string()], true)
+    }
+
+    /// Test that an input not covered in a state is used as an implicit else edge.
+    #[test]
+    fn test_implicit_else() {
+        let manager = Cudd::with_vars(3).unwrap();
+        let a = manager.bdd_var(0);
+        let b = manager.bdd_var(1);
+        let c = manager.bdd_var(2);
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let (s0, _) = constructor.add_state(0);
+        let mut t0 = Transition::new(&a & &b);
+        t0.add_output(c.clone(), s0);
+        constructor.add_transition(s0, t0);
+        let mut t1 = Transition::new(!&a);
+        t1.add_output(!&c, s0);
+        constructor.add_transition(s0, t1);
+        let mut machine = constructor.into_machine(
+            vec![s0],
+            vec!["a".to_string(), "b".to_string()],
+            vec!["c".to_string()],
+            true,
+        );
+
+        assert_eq!(machine.add_implicit_else(), 1);
+        let transitions = &machine[s0].transitions;
+        assert_eq!(transitions[0].input, a);
+        assert_eq!(transitions[1].input, !&a);
+        assert_eq!(machine.add_implicit_else(), 0);
+    }
+
+    /// Creates a deterministic Mealy machine with one state, where the output
+    /// is the given function of the input.
+    fn single_state_machine(
+        input: &str,
+        output: &str,
+        negated: bool,
+    ) -> LabelledMachine<StructuredLabel> {
+        let manager = Cudd::with_vars(2).unwrap();
+        let a = manager.bdd_var(0);
+        let b = manager.bdd_var(1);
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let (s0, _) = constructor.add_state(StructuredLabel::new(vec![LabelValue::Value(0)]));
+        let mut t0 = Transition::new(a.clone());
+        t0.add_output(if negated { !&b } else { b.clone() }, s0);
+        constructor.add_transition(s0, t0);
+        let mut t1 = Transition::new(!&a);
+        t1.add_output(if negated { b } else { !&b }, s0);
+        constructor.add_transition(s0, t1);
+        constructor.into_machine(
+            vec![s0],
+            vec![input.to_string()],
+            vec![output.to_string()],
+            true,
+        )
+    }
+
+    /// Test that machines with shared inputs are composed and their interfaces are checked.
+    #[test]
+    fn test_compose() {
+        let machine = single_state_machine("a", "b", false);
+        let other = single_state_machine("a", "c", true);
+        let composed = machine.compose(&other).unwrap();
+        assert_eq!(composed.inputs, vec!["a".to_string()]);
+        assert_eq!(composed.outputs, vec!["b".to_string(), "c".to_string()]);
+        assert_eq!(composed.num_states(), 1);
+        assert!(composed.is_deterministic());
+        assert_eq!(composed.states[0].label.components(), 2);
+
+        let independent = single_state_machine("d", "e", false);
+        let composed = machine.compose(&independent).unwrap();
+        assert_eq!(composed.inputs, vec!["a".to_string(), "d".to_string()]);
+        assert_eq!(composed.states[0].transitions.len(), 4);
+
+        assert_eq!(
+            machine.compose(&machine).err(),
+            Some(CompositionError::SharedOutput("b".to_string()))
+        );
+        let feedback = single_state_machine("b", "f", false);
+        assert_eq!(
+            machine.compose(&feedback).err(),
+            Some(CompositionError::Feedback("b".to_string()))
+        );
+
+        // labels of both machines are padded to the same number of components
+        use LabelValue::{DontCare, Value};
+        let cyclic = cyclic_machine(vec![vec![Value(0)], vec![Value(1), Value(2)]]);
+        let independent = single_state_machine("c", "d", false);
+        let composed = independent.compose(&cyclic).unwrap();
+        assert_eq!(composed.num_states(), 2);
+        let labels: Vec<_> = composed
+            .labels()
+            .map(|l| l.iter().copied().collect::<Vec<_>>())
+            .collect();
+        assert_eq!(labels[0][1..], [Value(0), DontCare]);
+        assert_eq!(labels[1][1..], [Value(1), Value(2)]);
+    }
+
+    /// Creates a deterministic Mealy machine with states with the given labels in a cycle,
+    /// where the output equals the input.
+    fn cyclic_machine(labels: Vec<Vec<LabelValue>>) -> LabelledMachine<StructuredLabel> {
+        let manager = Cudd::with_vars(2).unwrap();
+        let a = manager.bdd_var(0);
+        let b = manager.bdd_var(1);
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let states: Vec<_> = labels
+            .into_iter()
+            .map(|label| constructor.add_state(StructuredLabel::new(label)).0)
+            .collect();
+        for (i, &state) in states.iter().enumerate() {
+            let successor = states[(i + 1) % states.len()];
+            let mut t0 = Transition::new(a.clone());
+            t0.add_output(b.clone(), successor);
+            constructor.add_transition(state, t0);
+            let mut t1 = Transition::new(!&a);
+            t1.add_output(!&b, successor);
+            constructor.add_transition(state, t1);
+        }
+        constructor.into_machine(
+            vec![states[0]],
+            vec!["a".to_string()],
+            vec!["b".to_string()],
+            true,
+        )
+    }
+
+    /// Test that states are mapped to the new state with the most overlapping label
+    /// together with the values of the latches.
+    #[test]
+    fn test_state_mapping() {
+        use LabelValue::{DontCare, Value};
+        let old = cyclic_machine(vec![vec![Value(0), Value(1)], vec![Value(1), Value(1)]]);
+        let new = cyclic_machine(vec![
+            vec![Value(1), DontCare],
+            vec![Value(0), Value(1)],
+            vec![Value(2), Value(2)],
+        ]);
+        assert_eq!(old.state_bits(), vec![vec![false, true], vec![true, true]]);
+
+        let mapping = old.state_mapping(&new);
+        let entries = mapping.entries();
+        assert_eq!(mapping.num_mapped(), 2);
+        assert_eq!(
+            entries[0].new_state,
+            Some((1, vec![false, false, true, false]))
+        );
+        assert_eq!(
+            entries[1].new_state,
+            Some((0, vec![true, false, false, false]))
+        );
+        assert!(entries.iter().all(|entry| entry.simulated));
+        assert_eq!(
+            mapping.map_bits(&[true, true]),
+            Some(&[true, false, false, false][..])
+        );
+        assert_eq!(mapping.map_bits(&[false, false]), None);
+        assert_eq!(
+            mapping.to_string(),
+            "0 [01] -> 1 [0010] simulated\n1 [11] -> 0 [1000] simulated\n"
+        );
+
+        let unrelated = cyclic_machine(vec![vec![Value(3), Value(3)]]);
+        let mapping = old.state_mapping(&unrelated);
+        assert_eq!(mapping.num_mapped(), 0);
+        assert_eq!(mapping.map_bits(&[false, true]), None);
+    }
+
+    /// Test that the recovery input produces the fallback output and keeps the state.
+    #[test]
+    fn test_recovery_input() {
+        let mut machine = single_state_machine("a", "b", false);
+        assert!(!machine.add_recovery_input("b"));
+        assert!(machine.add_recovery_input("v"));
+        assert_eq!(machine.inputs, vec!["a".to_string(), "v".to_string()]);
+        assert_eq!(machine.states[0].transitions.len(), 3);
+        assert!(machine.is_deterministic());
+
+        let aig = machine
+            .create_bdds(None, false)
+            .create_aiger(AndCache::default(), false);
+        let inputs = vec![
+            vec![false, false],
+            vec![false, true],
+            vec![true, false],
+            vec![true, true],
+        ];
+        let outputs = aig.simulate(&inputs);
+        assert_eq!(
+            outputs,
+            vec![vec![false], vec![true], vec![true], vec![true]]
+        );
+    }
+
+    /// Test that states without transitions keep their state with all outputs unset
+    /// while the recovery input is set.
+    #[test]
+    fn test_recovery_input_without_transitions() {
+        let mut constructor = LabelledMachineConstructor::new();
+        let (s0, _) = constructor.add_state(0);
+        let inputs = vec!["a".to_string()];
+        let outputs = vec!["b".to_string()];
+        let mut machine = constructor.into_machine(vec![s0], inputs, outputs, true);
+        assert!(machine.add_recovery_input("v"));
+
+        let manager = Cudd::with_vars(3).unwrap();
+        let transitions = &machine.states[0].transitions;
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].input.transfer(&manager), manager.bdd_var(1));
+        let output = &transitions[0].outputs[0];
+        assert_eq!(output.output.transfer(&manager), !manager.bdd_var(2));
+        assert_eq!(output.successor, s0);
+    }
+
+    /// Test that the outputs of a circuit for a Moore machine only depend on the state.
+    #[test]
+    fn test_moore_circuit() {
+        let manager = Cudd::with_vars(3).unwrap();
+        let a = manager.bdd_var(0);
+        let c = manager.bdd_var(1);
+        let b = manager.bdd_var(2);
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let (s0, _) = constructor.add_state(0);
+        let (s1, _) = constructor.add_state(1);
+        let mut t0 = Transition::new(&a & &c);
+        t0.add_output(b.clone(), s1);
+        t0.add_output(!&b, s0);
+        constructor.add_transition(s0, t0);
+        // c is arbitrary in s1
+        let mut t1 = Transition::new(!&a);
+        t1.add_output(manager.bdd_one(), s0);
+        constructor.add_transition(s1, t1);
+        let inputs = vec!["a".to_string(), "c".to_string()];
+        let outputs = vec!["b".to_string()];
+        let machine = constructor.into_machine(vec![s0], inputs, outputs, false);
+        assert_eq!(
+            machine.latched_values(&machine.states[0]),
+            vec![Some(true); 2]
+        );
+        assert_eq!(
+            machine.latched_values(&machine.states[1]),
+            vec![Some(false), None]
+        );
+
+        let aig = machine
+            .create_bdds(None, false)
+            .create_aiger(AndCache::default(), false);
+        let responses = vec![vec![true], vec![true], vec![false], vec![false]];
+        let values = aig.simulate(&responses);
+        let a_values: Vec<_> = values.iter().map(|v| v[0]).collect();
+        assert_eq!(a_values, vec![true, false, true, true]);
+        assert!(values[0][1] && values[2][1] && values[3][1]);
+    }
+
+    /// Test that the bits of each label component stay adjacent during reordering.
+    #[test]
+    fn test_group_components() {
+        use LabelValue::Value;
+        let machine = cyclic_machine(vec![
+            vec![Value(0), Value(0)],
+            vec![Value(1), Value(3)],
+            vec![Value(2), Value(1)],
+            vec![Value(3), Value(2)],
+        ]);
+        assert_eq!(machine.state_widths(), vec![2, 2]);
+        let mut bdd = machine.create_bdds(None, true);
+        bdd.reduce(false);
+        let order = bdd.variable_order();
+        let position = |name: &str| order.iter().position(|n| n == name).unwrap();
+        assert_eq!((position("l0") as isize - position("l1") as isize).abs(), 1);
+        assert_eq!((position("l2") as isize - position("l3") as isize).abs(), 1);
+
+        let aig = bdd.create_aiger(AndCache::default(), false);
+        let outputs = aig.simulate(&[vec![true], vec![false]]);
+        assert_eq!(outputs, vec![vec![true], vec![false]]);
+    }
+
+    /// Test that a variable order is applied to and read back from the BDDs of a machine.
+    #[test]
+    fn test_variable_order() {
+        let machine = single_state_machine("a", "b", false);
+        let independent = single_state_machine("d", "e", false);
+        let mut bdd = machine
+            .compose(&independent)
+            .unwrap()
+            .create_bdds(None, false);
+        assert_eq!(bdd.variable_order(), vec!["a".to_string(), "d".to_string()]);
+        let order = vec!["x".to_string(), "d".to_string(), "d".to_string()];
+        assert_eq!(bdd.apply_variable_order(&order), 1);
+        assert_eq!(bdd.variable_order(), vec!["d".to_string(), "a".to_string()]);
+
+        let aig = bdd.create_aiger(AndCache::default(), false);
+        let outputs = aig.simulate(&[vec![false, true], vec![true, false]]);
+        assert_eq!(outputs, vec![vec![false, true], vec![true, false]]);
+    }
+
+    /// Creates a Mealy machine for two clients with requests `a` and `b` and grants `x`
+    /// and `y`, which alternates between granting the request of either client.
+    fn symmetric_machine() -> LabelledMachine<StructuredLabel> {
+        let manager = Cudd::with_vars(4).unwrap();
+        let a = manager.bdd_var(0);
+        let b = manager.bdd_var(1);
+        let x = manager.bdd_var(2);
+        let y = manager.bdd_var(3);
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let (s0, _) = constructor.add_state(StructuredLabel::new(vec![LabelValue::Value(0)]));
+        let (s1, _) = constructor.add_state(StructuredLabel::new(vec![LabelValue::Value(1)]));
+        for &(state, successor, input, output, other_output) in
+            &[(s0, s1, &a, &x, &y), (s1, s0, &b, &y, &x)]
+        {
+            let mut t0 = Transition::new(input.clone());
+            t0.add_output(output & !other_output, successor);
+            constructor.add_transition(state, t0);
+            let mut t1 = Transition::new(!input);
+            t1.add_output(!output & !other_output, successor);
+            constructor.add_transition(state, t1);
+        }
+        let names = |names: &[&str]| -> Vec<_> { names.iter().map(|n| n.to_string()).collect() };
+        constructor.into_machine(vec![s0], names(&["a", "b"]), names(&["x", "y"]), true)
+    }
+
+    /// Returns the given pairs of names as owned strings.
+    fn swaps(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(p, q)| (p.to_string(), q.to_string()))
+            .collect()
+    }
+
+    /// Test that states are found symmetric under swapping inputs and outputs.
+    #[test]
+    fn test_symmetric_states() {
+        let machine = symmetric_machine();
+        assert_eq!(
+            machine.symmetric_states(&swaps(&[("a", "b"), ("x", "y")])),
+            Some(vec![(StateIndex(0), StateIndex(1))])
+        );
+        assert_eq!(
+            machine.symmetric_states(&swaps(&[("a", "b")])),
+            Some(vec![])
+        );
+        assert_eq!(machine.symmetric_states(&swaps(&[("a", "x")])), None);
+        assert_eq!(
+            machine.symmetric_states(&swaps(&[("a", "b"), ("b", "a")])),
+            None
+        );
+    }
+
+    /// Test that the circuit of a machine quotiented by symmetry swaps the inputs
+    /// and outputs with a latch.
+    #[test]
+    fn test_quotient_by_symmetry() {
+        let mut machine = symmetric_machine();
+        assert_eq!(machine.quotient_by_symmetry(&swaps(&[("a", "x")])), None);
+        assert_eq!(
+            machine.quotient_by_symmetry(&swaps(&[("a", "b"), ("x", "y")])),
+            Some(1)
+        );
+        assert_eq!(machine.num_states(), 1);
+        assert_eq!(machine.outputs, vec!["x", "y", "swap"]);
+
+        let aig = machine
+            .create_bdds(None, false)
+            .create_aiger(AndCache::default(), false);
+        let inputs = vec![
+            vec![true, false],
+            vec![true, true],
+            vec![false, true],
+            vec![false, true],
+            vec![true, true],
+        ];
+        let outputs = aig.simulate(&inputs);
+        assert_eq!(
+            outputs,
+            vec![
+                vec![true, false],
+                vec![false, true],
+                vec![false, false],
+                vec![false, true],
+                vec![true, false],
+            ]
+        );
+    }
+
+    /// Test that a machine simulates itself.
+    #[test]
+    fn test_simulation_reflexive() {
+        let machine = nondeterministic_machine();
+        assert!(machine.is_simulated_by(&machine));
+        assert!(machine.is_simulation_equivalent(&machine));
+    }
+
+    /// Test that determinization and minimization preserve behavior of the machine.
+    #[test]
+    fn test_simulation_determinization() {
+        let machine = nondeterministic_machine();
+        let mut deterministic = machine.clone();
+        deterministic.determinize(false);
+        assert!(deterministic.is_simulated_by(&machine));
+        assert!(!machine.is_simulated_by(&deterministic));
+
+        let minimized = deterministic.minimize_with_dontcares(false, None, None, &mut |_, _| ());
+        assert!(minimized.is_simulated_by(&deterministic));
+        assert!(minimized.is_simulated_by(&machine));
+    }
+
+    /// Test that random determinization samples different deterministic machines
+    /// that preserve behavior, and is reproducible for the same seed.
+    #[test]
+    fn test_random_determinization() {
+        let machine = nondeterministic_machine();
+        let mut samples = HashSet::new();
+        for seed in 0..16 {
+            let mut deterministic = machine.clone();
+            deterministic.determinize_randomly(seed);
+            assert!(deterministic.is_deterministic());
+            assert!(deterministic.is_simulated_by(&machine));
+            let mut again = machine.clone();
+            again.determinize_randomly(seed);
+            assert_eq!(again.to_string(), deterministic.to_string());
+            samples.insert(deterministic.to_string());
+        }
+        assert_eq!(samples.len(), 2);
+    }
+
+    /// A cycle of three compatible states, which can be minimized to one state.
+    fn compatible_cycle() -> LabelledMachine<usize> {
+        let manager = Cudd::with_vars(2).unwrap();
+        let b = manager.bdd_var(1);
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let states: Vec<_> = (0..3).map(|i| constructor.add_state(i).0).collect();
+        for i in 0..3 {
+            let mut transition = Transition::new(manager.bdd_one());
+            transition.add_output(b.clone(), states[(i + 1) % 3]);
+            constructor.add_transition(states[i], transition);
+        }
+        constructor.into_machine(
+            vec![states[0]],
+            vec!["a".to_string()],
+            vec!["b".to_string()],
+            true,
+        )
+    }
+
+    /// Test that minimization narrows the bounds on the minimal size, and falls back
+    /// to the unminimized machine if the SAT encoding exceeds the maximum number of
+    /// clauses.
+    #[test]
+    fn test_minimization_clause_limit() {
+        let machine = compatible_cycle();
+        let mut bounds = Vec::new();
+        let minimized = machine.minimize_with_dontcares(false, None, None, &mut |lower, upper| {
+            bounds.push((lower, upper))
+        });
+        assert_eq!(minimized.num_states(), 1);
+        assert_eq!(bounds, vec![(1, 2), (1, 1)]);
+        let limited = machine.minimize_with_dontcares(false, None, Some(0), &mut |_, _| ());
+        assert_eq!(limited.num_states(), 3);
+        assert!(limited.is_simulation_equivalent(&machine));
+    }
+
+    /// Test that minimization returns the smallest machine found so far once the
+    /// deadline has passed.
+    #[test]
+    fn test_minimization_deadline() {
+        let machine = compatible_cycle();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut bounds = Vec::new();
+        // let the deadline pass after the first size that was tried
+        let minimized =
+            machine.minimize_with_dontcares(false, Some(deadline), None, &mut |lower, upper| {
+                bounds.push((lower, upper));
+                std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+            });
+        assert_eq!(bounds, vec![(1, 2)]);
+        assert_eq!(minimized.num_states(), 2);
+        assert!(minimized.is_simulation_equivalent(&machine));
+
+        let expired =
+            machine.minimize_with_dontcares(false, Some(Instant::now()), None, &mut |_, _| {
+                panic!("no size should be tried after the deadline")
+            });
+        assert_eq!(expired.num_states(), 3);
+    }
+
+    /// Test that machines are parsed from the HOA format in which they are written.
+    #[test]
+    fn test_hoa_format() {
+        let machine = nondeterministic_machine();
+        let parsed: LabelledMachine<usize> = machine.to_string().parse().unwrap();
+        assert_eq!(parsed.inputs, machine.inputs);
+        assert_eq!(parsed.outputs, machine.outputs);
+        assert!(parsed.mealy);
+        assert_eq!(parsed.num_states(), 2);
+        assert_eq!(parsed.states[0].transitions.len(), 2);
+        assert!(parsed.is_simulation_equivalent(&machine));
+
+        let mut moore = nondeterministic_machine();
+        moore.mealy = false;
+        let parsed: LabelledMachine<usize> = moore.to_string().parse().unwrap();
+        assert!(!parsed.mealy);
+
+        let text = machine.to_string();
+        assert!(text
+            .replace("controllable-AP: 1", "controllable-AP: 2")
+            .parse::<LabelledMachine<usize>>()
+            .is_err());
+        assert!(text
+            .replace("--END--", "")
+            .parse::<LabelledMachine<usize>>()
+            .is_err());
+        assert!(text
+            .replace("] 1", "] 2")
+            .parse::<LabelledMachine<usize>>()
+            .is_err());
+    }
+
+    /// Test that machines with structured labels are parsed from the HOA format with
+    /// their labels.
+    #[test]
+    fn test_hoa_structured_labels() {
+        use LabelValue::{DontCare, Value};
+        let machine = cyclic_machine(vec![
+            vec![Value(0), Value(2)],
+            vec![Value(1), DontCare],
+            vec![Value(2), Value(0)],
+        ]);
+        let text = machine.to_string();
+        assert!(text.contains("State: 1 \"[1,-]\"\n"));
+        let parsed: LabelledMachine<StructuredLabel> = text.parse().unwrap();
+        assert_eq!(
+            parsed.labels().collect::<Vec<_>>(),
+            machine.labels().collect::<Vec<_>>()
+        );
+        assert!(parsed.is_simulation_equivalent(&machine));
+        assert_eq!(parsed.state_bits(), machine.state_bits());
+        assert_eq!(parsed.to_string(), text);
+
+        assert!(nondeterministic_machine()
+            .to_string()
+            .parse::<LabelledMachine<StructuredLabel>>()
+            .is_err());
+        assert!(text
+            .replace("[1,-]", "[0,2]")
+            .parse::<LabelledMachine<StructuredLabel>>()
+            .is_err());
+        assert!(text
+            .replace("[1,-]", "[1]")
+            .parse::<LabelledMachine<StructuredLabel>>()
+            .is_err());
+        assert!(text
+            .replace("[1,-]", "[1,x]")
+            .parse::<LabelledMachine<StructuredLabel>>()
+            .is_err());
+    }
+
+    /// Test that machines with the outputs listed first are written with their type
+    /// and parsed with the inputs first.
+    #[test]
+    fn test_hoa_outputs_first() {
+        let mut machine = nondeterministic_machine();
+        machine.set_outputs_first(true);
+        let text = machine.to_string();
+        assert!(text.contains("AP: 2 \"b\" \"a\"\ncontrollable-AP: 0\nmachine-type: mealy\n"));
+        let parsed: LabelledMachine<usize> = text.parse().unwrap();
+        assert_eq!(parsed.inputs, machine.inputs);
+        assert_eq!(parsed.outputs, machine.outputs);
+        assert!(parsed.mealy);
+        assert!(parsed.is_simulation_equivalent(&machine));
+        assert_eq!(parsed.to_string(), text);
+
+        // without the type, the outputs are the inputs of a Moore machine
+        let parsed: LabelledMachine<usize> =
+            text.replace("machine-type: mealy\n", "").parse().unwrap();
+        assert!(!parsed.mealy);
+        assert_eq!(parsed.inputs, machine.outputs);
+
+        let mut moore = nondeterministic_machine();
+        moore.mealy = false;
+        moore.set_outputs_first(true);
+        let parsed: LabelledMachine<usize> = moore.to_string().parse().unwrap();
+        assert!(!parsed.mealy);
+        assert_eq!(parsed.inputs, moore.inputs);
+        assert!(text
+            .replace("machine-type: mealy", "machine-type: other")
+            .parse::<LabelledMachine<usize>>()
+            .is_err());
+    }
+
+    /// Test that additional initial states are kept and add behaviors to the machine.
+    #[test]
+    fn test_multiple_initial_states() {
+        let machine = nondeterministic_machine();
+        let mut multi_initial = machine.clone();
+        multi_initial.initial_states.push(StateIndex(1));
+        assert!(machine.is_simulated_by(&multi_initial));
+        assert!(!multi_initial.is_simulated_by(&machine));
+
+        multi_initial.determinize(false);
+        assert_eq!(multi_initial.initial_states.len(), 2);
+        assert!(multi_initial.to_string().contains("Start: 1"));
+    }
+}
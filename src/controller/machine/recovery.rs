@@ -0,0 +1,78 @@
+use cudd::{Bdd, CubeValue, Cudd};
+use log::info;
+
+use super::{map_bdd, LabelledMachine, StateIndex, Transition};
+
+/// Returns the number of valuations of the first `num_inputs` variables
+/// in the given BDD over `num_vars` variables.
+fn num_input_valuations(bdd: &Bdd, num_inputs: usize, num_vars: usize) -> f64 {
+    bdd.cube_iter(num_vars)
+        .map(|cube| {
+            let unspecified = cube
+                .iter()
+                .take(num_inputs)
+                .filter(|&&v| v == CubeValue::Unspecified)
+                .count();
+            2_f64.powi(unspecified as i32)
+        })
+        .sum()
+}
+
+impl<L> LabelledMachine<L> {
+    /// Adds an input with the given name to a Mealy machine, which signals that the
+    /// environment has violated its assumptions, and returns whether it was added.
+    ///
+    /// While the input is set, each state produces a fixed fallback output and keeps
+    /// its state, so that the controller resumes from the same state once the input
+    /// is cleared. The fallback output of a state is the first output of the transition
+    /// covering the most inputs of that state, or all outputs unset for states without
+    /// transitions. All other transitions and the don't care inputs of each state are
+    /// restricted to inputs where the new input is not set.
+    ///
+    /// Moore machines and names of existing inputs or outputs are rejected.
+    pub(crate) fn add_recovery_input(&mut self, name: &str) -> bool {
+        if !self.mealy || self.inputs.iter().chain(&self.outputs).any(|n| n == name) {
+            return false;
+        }
+        let num_inputs = self.num_inputs();
+        let num_vars = self.num_vars();
+        // the new input is the last input, so all outputs are shifted by one
+        let mapping: Vec<usize> = (0..num_vars)
+            .map(|var| if var < num_inputs { var } else { var + 1 })
+            .collect();
+        let manager = Cudd::with_vars(num_vars + 1).unwrap();
+        let violation = manager.bdd_var(num_inputs);
+        let unset_outputs = (num_inputs + 1..=num_vars)
+            .fold(manager.bdd_one(), |bdd, var| bdd & !manager.bdd_var(var));
+        for (index, state) in self.states.iter_mut().enumerate() {
+            let mut fallback: Option<(f64, Bdd)> = None;
+            for transition in &mut state.transitions {
+                let size = num_input_valuations(&transition.input, num_inputs, num_vars);
+                if fallback
+                    .as_ref()
+                    .map_or(true, |&(max_size, _)| size > max_size)
+                {
+                    let output = &transition.outputs[0].output;
+                    let cube = output.bdd_cube_iter(num_vars).next().unwrap();
+                    fallback = Some((size, map_bdd(&cube, num_vars, &mapping, &manager)));
+                }
+                transition.input =
+                    map_bdd(&transition.input, num_vars, &mapping, &manager) & !&violation;
+                for output in &mut transition.outputs {
+                    output.output = map_bdd(&output.output, num_vars, &mapping, &manager);
+                }
+            }
+            if let Some(dontcare_inputs) = &mut state.dontcare_inputs {
+                *dontcare_inputs =
+                    map_bdd(dontcare_inputs, num_vars, &mapping, &manager) & !&violation;
+            }
+            let output = fallback.map_or_else(|| unset_outputs.clone(), |(_, output)| output);
+            let mut recovery = Transition::new(violation.clone());
+            recovery.add_output(output, StateIndex(index));
+            state.add_transition(recovery);
+        }
+        self.inputs.push(name.to_string());
+        info!("Added recovery input {} with fallback outputs", name);
+        true
+    }
+}
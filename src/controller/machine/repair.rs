@@ -0,0 +1,312 @@
+//! Repair of a deployed controller after a change of the specification, where a machine
+//! synthesized for the new specification only overrides the outputs of the old controller
+//! where they are no longer allowed, such that the latches of the old controller and
+//! thus its state encoding are kept.
+
+use std::collections::HashMap;
+
+use aiger::SymbolKind;
+use cudd::{Bdd, CubeValue, Cudd};
+use log::info;
+
+use super::{LabelledMachine, LabelledMachineConstructor, State, StateIndex, Transition};
+use crate::controller::aiger::AigerController;
+use crate::controller::labelling::{LabelInnerValue, LabelValue, StructuredLabel};
+
+/// The maximal number of inputs of a specification for repairing a controller, as all
+/// valuations of the inputs are enumerated for each state of the repaired machine.
+pub(crate) const MAX_REPAIR_INPUTS: usize = 16;
+
+/// Checks that the given controller has exactly the given inputs and outputs by name,
+/// and that the number of inputs is at most [`MAX_REPAIR_INPUTS`], and otherwise returns
+/// a description of the mismatch.
+pub(crate) fn check_repair_interface(
+    controller: &AigerController,
+    inputs: &[&str],
+    outputs: &[&str],
+) -> Result<(), String> {
+    if inputs.len() > MAX_REPAIR_INPUTS {
+        return Err(format!(
+            "specification has {} inputs, but at most {} are supported",
+            inputs.len(),
+            MAX_REPAIR_INPUTS
+        ));
+    }
+    for &(kind, expected, signal) in &[
+        (SymbolKind::Input, inputs, "input"),
+        (SymbolKind::Output, outputs, "output"),
+    ] {
+        let names = controller.aig.symbol_names(kind);
+        if names.len() != expected.len() {
+            return Err(format!(
+                "controller has {} {}s instead of {}",
+                names.len(),
+                signal,
+                expected.len()
+            ));
+        }
+        if let Some(name) = expected
+            .iter()
+            .find(|&&name| !names.iter().any(|n| n.as_deref() == Some(name)))
+        {
+            return Err(format!(
+                "{} {} of the specification is not an {} of the controller",
+                signal, name, signal
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the cube of the given valuation of consecutive variables starting at the offset.
+fn valuation_cube(manager: &Cudd, offset: usize, values: &[bool]) -> Bdd {
+    let mut cube = manager.bdd_one();
+    for (i, &value) in values.iter().enumerate() {
+        let var = manager.bdd_var(offset + i);
+        if value {
+            cube &= var;
+        } else {
+            cube &= !var;
+        }
+    }
+    cube
+}
+
+impl<L> LabelledMachine<L> {
+    /// Returns the outputs allowed in the given state for the given input cube that differ
+    /// from the given outputs in the fewest values, together with the successor, or `None`
+    /// if no transition of the state allows the input.
+    fn closest_outputs(
+        &self,
+        state: StateIndex,
+        input: &Bdd,
+        outputs: &[bool],
+    ) -> Option<(Vec<bool>, StateIndex)> {
+        let mut best: Option<(usize, Vec<bool>, StateIndex)> = None;
+        for transition in &self[state].transitions {
+            if (&transition.input & input).is_zero() {
+                continue;
+            }
+            for output in &transition.outputs {
+                for cube in output.output.cube_iter(self.num_vars()) {
+                    let values: Vec<_> = cube
+                        .iter()
+                        .skip(self.num_inputs())
+                        .zip(outputs)
+                        .map(|(value, &old)| match value {
+                            CubeValue::Set => true,
+                            CubeValue::Unset => false,
+                            CubeValue::Unspecified => old,
+                        })
+                        .collect();
+                    let distance = values.iter().zip(outputs).filter(|(v, o)| v != o).count();
+                    if best.as_ref().map_or(true, |&(d, _, _)| distance < d) {
+                        best = Some((distance, values, output.successor));
+                    }
+                }
+            }
+        }
+        best.map(|(_, values, successor)| (values, successor))
+    }
+
+    /// Repairs the given old controller with this machine for a modified specification,
+    /// and returns a machine that keeps the outputs of the old controller wherever this
+    /// machine allows them.
+    ///
+    /// The repaired machine is the product of the old controller and this machine,
+    /// explored from the reset values of the latches and the initial states. For each
+    /// input, the outputs of the old controller are kept if a transition of this machine
+    /// allows them, and are otherwise replaced by allowed outputs that differ from them
+    /// in the fewest values. The labels of the states have a component with the value of
+    /// each latch of the old controller, so that a circuit for the repaired machine has
+    /// the latches of the old controller with their next-state functions, and a further
+    /// component for the state of this machine only if it is not determined by the
+    /// values of the latches.
+    ///
+    /// The old controller needs to have the inputs and outputs of this machine,
+    /// see [`check_repair_interface`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this machine is not a Mealy machine.
+    pub(crate) fn repair(&self, old: &AigerController) -> LabelledMachine<StructuredLabel> {
+        assert!(self.mealy, "only mealy machines can repair a controller");
+        info!(
+            "Repairing controller with {} latches by machine with {} states",
+            old.aig.num_latches(),
+            self.num_states()
+        );
+        let num_inputs = self.num_inputs();
+        let manager = match self.states().flat_map(|s| &s.transitions).next() {
+            Some(transition) => transition.input.manager(),
+            None => Cudd::with_vars(self.num_vars()).unwrap(),
+        };
+        // positions of the inputs of the old controller among the inputs of this machine,
+        // and of the outputs of this machine among the outputs of the old controller
+        let input_map: Vec<_> = old
+            .aig
+            .symbol_names(SymbolKind::Input)
+            .into_iter()
+            .map(|name| {
+                self.inputs
+                    .iter()
+                    .position(|n| Some(n) == name.as_ref())
+                    .expect("interface of controller not checked")
+            })
+            .collect();
+        let old_outputs = old.aig.symbol_names(SymbolKind::Output);
+        let output_map: Vec<_> = self
+            .outputs
+            .iter()
+            .map(|name| {
+                old_outputs
+                    .iter()
+                    .position(|n| n.as_ref() == Some(name))
+                    .expect("interface of controller not checked")
+            })
+            .collect();
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let mut queue = Vec::new();
+        let reset = old.reset_values();
+        let initial_states: Vec<_> = self
+            .initial_states
+            .iter()
+            .map(|&state| {
+                let (index, new_state) = constructor.add_state((reset.clone(), state));
+                if new_state {
+                    queue.push((reset.clone(), state));
+                }
+                index
+            })
+            .collect();
+        let mut num_kept = 0;
+        let mut num_changed = 0;
+        while let Some((latches, state)) = queue.pop() {
+            let index = constructor.add_state((latches.clone(), state)).0;
+            // inputs with the same outputs and successor share a transition
+            let mut groups: Vec<(Bdd, StateIndex, Bdd)> = Vec::new();
+            for valuation in 0..1_usize << num_inputs {
+                let inputs: Vec<_> = (0..num_inputs).map(|i| valuation >> i & 1 == 1).collect();
+                let old_inputs: Vec<_> = input_map.iter().map(|&i| inputs[i]).collect();
+                let (old_values, next_latches) = old.step(&latches, &old_inputs);
+                let old_values: Vec<_> = output_map.iter().map(|&o| old_values[o]).collect();
+                let input = valuation_cube(&manager, 0, &inputs);
+                // inputs without transition are don't cares
+                let (values, successor) = match self.closest_outputs(state, &input, &old_values) {
+                    Some(choice) => choice,
+                    None => continue,
+                };
+                if values == old_values {
+                    num_kept += 1;
+                } else {
+                    num_changed += 1;
+                }
+                let (successor_index, new_state) =
+                    constructor.add_state((next_latches.clone(), successor));
+                if new_state {
+                    queue.push((next_latches, successor));
+                }
+                let output = valuation_cube(&manager, num_inputs, &values);
+                match groups
+                    .iter_mut()
+                    .find(|(o, s, _)| *o == output && *s == successor_index)
+                {
+                    Some((_, _, group_input)) => *group_input |= input,
+                    None => groups.push((output, successor_index, input)),
+                }
+            }
+            for (output, successor, input) in groups {
+                let mut transition = Transition::new(input);
+                transition.add_output(output, successor);
+                constructor.add_transition(index, transition);
+            }
+        }
+        let machine = constructor.into_machine(
+            initial_states,
+            self.inputs.clone(),
+            self.outputs.clone(),
+            true,
+        );
+        info!(
+            "Repaired machine has {} states and changes the outputs of the old controller \
+            for {} of {} inputs in these states",
+            machine.num_states(),
+            num_changed,
+            num_kept + num_changed
+        );
+
+        // the state of this machine is only kept if not determined by the latches
+        let mut determined = HashMap::new();
+        let keep_state = machine
+            .labels()
+            .any(|(latches, state)| *determined.entry(latches).or_insert(state) != state);
+        let mut state_values = HashMap::new();
+        let new_states = machine
+            .states()
+            .map(|s| {
+                let (latches, state) = s.label();
+                let mut label: Vec<_> = latches
+                    .iter()
+                    .map(|&value| LabelValue::Value(value as LabelInnerValue))
+                    .collect();
+                if keep_state {
+                    let next_value = state_values.len() as LabelInnerValue;
+                    label.push(LabelValue::Value(
+                        *state_values.entry(*state).or_insert(next_value),
+                    ));
+                }
+                State::with_transitions(StructuredLabel::new(label), s.transitions.clone())
+            })
+            .collect();
+        machine.clone_with(new_states, machine.initial_states.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a controller keeps its latches and outputs where they are still allowed.
+    #[test]
+    fn test_repair() {
+        // the old controller sets c to a only in every second step
+        let aag = "aag 3 1 1 1 1\n2\n4 5\n6\n6 2 4\ni0 a\no0 c\n";
+        let old = AigerController::read(aag.as_bytes()).unwrap();
+
+        // the new specification requires c to be equal to a
+        let manager = Cudd::with_vars(2).unwrap();
+        let a = manager.bdd_var(0);
+        let c = manager.bdd_var(1);
+        let mut constructor = LabelledMachineConstructor::new();
+        let (state, _) = constructor.add_state(0);
+        let mut t0 = Transition::new(a.clone());
+        t0.add_output(c.clone(), state);
+        let mut t1 = Transition::new(!&a);
+        t1.add_output(!&c, state);
+        constructor.add_transition(state, t0);
+        constructor.add_transition(state, t1);
+        let inputs = vec!["a".to_string()];
+        let outputs = vec!["c".to_string()];
+        let machine = constructor.into_machine(vec![state], inputs, outputs, true);
+
+        assert_eq!(check_repair_interface(&old, &["a"], &["c"]), Ok(()));
+        assert!(check_repair_interface(&old, &["a"], &["d"]).is_err());
+        assert!(check_repair_interface(&old, &["a", "b"], &["c"]).is_err());
+
+        let repaired = machine.repair(&old);
+        assert_eq!(repaired.num_states(), 2);
+        assert_eq!(repaired.state_bits(), vec![vec![false], vec![true]]);
+        for state in repaired.states() {
+            assert_eq!(state.transitions.len(), 2);
+            for transition in &state.transitions {
+                let expected = if transition.input == a {
+                    c.clone()
+                } else {
+                    !&c
+                };
+                assert!(transition.outputs[0].output == expected);
+            }
+        }
+    }
+}
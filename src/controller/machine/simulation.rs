@@ -0,0 +1,119 @@
+use cudd::{Bdd, Cudd};
+use log::{debug, info};
+
+use super::{copy_bdd, LabelledMachine, StateIndex};
+
+/// Moves of the states of a machine, where a move is a BDD over the combined
+/// input and output variables together with a successor.
+pub(super) type Moves = Vec<Vec<(Bdd, StateIndex)>>;
+
+/// Computes the largest simulation relation between the states with the given moves
+/// and the states with the other moves, where `related[s][t]` is set if state `s`
+/// is simulated by other state `t`.
+pub(super) fn simulation_relation(
+    manager: &Cudd,
+    moves: &Moves,
+    other_moves: &Moves,
+) -> Vec<Vec<bool>> {
+    let mut related = vec![vec![true; other_moves.len()]; moves.len()];
+    let mut changed = true;
+    let mut iterations = 0;
+    while changed {
+        changed = false;
+        iterations += 1;
+        for (state, state_moves) in moves.iter().enumerate() {
+            for (other_state, other_state_moves) in other_moves.iter().enumerate() {
+                if !related[state][other_state] {
+                    continue;
+                }
+                let matched = state_moves.iter().all(|(bdd, successor)| {
+                    let mut covered = manager.bdd_zero();
+                    for (other_bdd, other_successor) in other_state_moves {
+                        if related[successor.0][other_successor.0] {
+                            covered |= other_bdd;
+                        }
+                    }
+                    (bdd & !covered).is_zero()
+                });
+                if !matched {
+                    related[state][other_state] = false;
+                    changed = true;
+                }
+            }
+        }
+    }
+    debug!("Computed simulation relation in {} iterations", iterations);
+    related
+}
+
+impl<L> LabelledMachine<L> {
+    /// Returns the moves of each state.
+    pub(super) fn moves(&self, manager: &Cudd) -> Moves {
+        let vars = 0..self.num_vars();
+        self.states()
+            .map(|state| {
+                let mut moves = Vec::new();
+                for transition in &state.transitions {
+                    let input = copy_bdd(&transition.input, vars.clone(), manager, 0);
+                    for output in &transition.outputs {
+                        let output_bdd = copy_bdd(&output.output, vars.clone(), manager, 0);
+                        moves.push((&input & output_bdd, output.successor));
+                    }
+                }
+                moves
+            })
+            .collect()
+    }
+
+    /// Checks whether this machine is simulated by the other machine.
+    ///
+    /// A machine is simulated by another machine if every step of the first machine
+    /// from a state can be matched by a step with the same inputs and outputs of
+    /// the other machine from a related state. Every behavior of this machine
+    /// is then also a behavior of the other machine, so this machine implements
+    /// every specification that the other machine implements.
+    ///
+    /// The check computes the largest simulation relation between the states
+    /// of both machines, and tests if it relates each initial state of this machine
+    /// to some initial state of the other machine.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machines have different inputs or outputs, or if one machine
+    /// is a Mealy machine and the other one a Moore machine.
+    pub fn is_simulated_by<L2>(&self, other: &LabelledMachine<L2>) -> bool {
+        assert_eq!(self.inputs, other.inputs, "machines have different inputs");
+        assert_eq!(
+            self.outputs, other.outputs,
+            "machines have different outputs"
+        );
+        assert_eq!(self.mealy, other.mealy, "machines have different types");
+        info!(
+            "Checking simulation of machine with {} states by machine with {} states",
+            self.num_states(),
+            other.num_states()
+        );
+
+        let manager = Cudd::with_vars(self.num_vars()).unwrap();
+        let moves = self.moves(&manager);
+        let other_moves = other.moves(&manager);
+
+        let related = simulation_relation(&manager, &moves, &other_moves);
+        self.initial_states.iter().all(|s| {
+            other
+                .initial_states
+                .iter()
+                .any(|other_s| related[s.0][other_s.0])
+        })
+    }
+
+    /// Checks whether this machine and the other machine simulate each other,
+    /// i.e. whether both machines have the same behaviors.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Self::is_simulated_by`].
+    pub fn is_simulation_equivalent<L2>(&self, other: &LabelledMachine<L2>) -> bool {
+        self.is_simulated_by(other) && other.is_simulated_by(self)
+    }
+}
@@ -0,0 +1,182 @@
+use cudd::Cudd;
+use log::info;
+
+use super::simulation::simulation_relation;
+use super::{LabelledMachine, State, StateIndex, Transition};
+
+impl<L> LabelledMachine<L> {
+    /// Returns the permutation of the variables that swaps the inputs or outputs
+    /// in each of the given pairs, or `None` if a name is unknown, a pair consists
+    /// of an input and an output, or a variable occurs in several pairs.
+    pub(super) fn swap_permutation(&self, swaps: &[(String, String)]) -> Option<Vec<usize>> {
+        let num_inputs = self.num_inputs();
+        let var = |name: &str| {
+            self.inputs
+                .iter()
+                .chain(&self.outputs)
+                .position(|n| n == name)
+        };
+        let mut permutation: Vec<_> = (0..self.num_vars()).collect();
+        for (first, second) in swaps {
+            let (i, j) = (var(first)?, var(second)?);
+            if (i < num_inputs) != (j < num_inputs) || permutation[i] != i || permutation[j] != j {
+                return None;
+            }
+            permutation.swap(i, j);
+        }
+        Some(permutation)
+    }
+
+    /// Returns the pairs of distinct states that are symmetric under swapping the
+    /// inputs and outputs in each of the given pairs, e.g. the requests and grants
+    /// of two clients of an arbiter, or `None` if the swaps are invalid.
+    ///
+    /// Two states are symmetric if the first state simulates the second state with
+    /// swapped inputs and outputs and vice versa, so that one of them could be
+    /// replaced by the other one if the swap is applied to the inputs and outputs
+    /// of the controller while in the replaced state.
+    pub(crate) fn symmetric_states(
+        &self,
+        swaps: &[(String, String)],
+    ) -> Option<Vec<(StateIndex, StateIndex)>> {
+        let permutation = self.swap_permutation(swaps)?;
+        let manager = Cudd::with_vars(self.num_vars()).unwrap();
+        let moves = self.moves(&manager);
+        let swapped_moves = moves
+            .iter()
+            .map(|state_moves| {
+                state_moves
+                    .iter()
+                    .map(|(bdd, successor)| (bdd.permute(&permutation), *successor))
+                    .collect()
+            })
+            .collect();
+        let simulated = simulation_relation(&manager, &moves, &swapped_moves);
+        let simulating = simulation_relation(&manager, &swapped_moves, &moves);
+        let mut pairs = Vec::new();
+        for state in 0..self.num_states() {
+            for other_state in state + 1..self.num_states() {
+                if simulated[state][other_state] && simulating[other_state][state] {
+                    pairs.push((StateIndex(state), StateIndex(other_state)));
+                }
+            }
+        }
+        info!(
+            "Found {} pairs of symmetric states in machine with {} states",
+            pairs.len(),
+            self.num_states()
+        );
+        Some(pairs)
+    }
+
+    /// Quotients a Mealy machine by the states that are symmetric under swapping the
+    /// inputs and outputs in each of the given pairs, and returns the number of removed
+    /// states, or `None` if the swaps are invalid.
+    ///
+    /// Each removed state is replaced by a symmetric state that is entered with swapped
+    /// inputs and outputs. Controllers created from the quotient keep whether the inputs
+    /// and outputs are swapped in a swap latch, which is toggled by a new last output of
+    /// the machine on the transitions into replaced states. Initial states are never
+    /// replaced, and a state replacing another one only keeps the don't care inputs that
+    /// are also don't cares of the replaced state with swapped inputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine is a Moore machine or already quotiented by symmetry.
+    pub(crate) fn quotient_by_symmetry(&mut self, swaps: &[(String, String)]) -> Option<usize> {
+        assert!(self.mealy, "can only quotient mealy machine by symmetry");
+        assert!(
+            self.symmetry.is_none(),
+            "machine already quotiented by symmetry"
+        );
+        let permutation = self.swap_permutation(swaps)?;
+        let pairs = self.symmetric_states(swaps)?;
+
+        // each kept state replaces at most one other state
+        let num_states = self.num_states();
+        let mut replacement: Vec<Option<StateIndex>> = vec![None; num_states];
+        let mut replaced: Vec<Option<StateIndex>> = vec![None; num_states];
+        for (state, other_state) in pairs {
+            if replacement[state.0].is_none()
+                && replaced[state.0].is_none()
+                && replacement[other_state.0].is_none()
+                && replaced[other_state.0].is_none()
+                && !self.initial_states.contains(&other_state)
+            {
+                replacement[other_state.0] = Some(state);
+                replaced[state.0] = Some(other_state);
+            }
+        }
+        let mut indices = vec![None; num_states];
+        let mut num_kept = 0;
+        for (index, _) in replacement.iter().enumerate().filter(|(_, r)| r.is_none()) {
+            indices[index] = Some(StateIndex(num_kept));
+            num_kept += 1;
+        }
+
+        let mut toggle_name = "swap".to_string();
+        while self
+            .inputs
+            .iter()
+            .chain(&self.outputs)
+            .any(|n| *n == toggle_name)
+        {
+            toggle_name.insert(0, '_');
+        }
+        let manager = Cudd::with_vars(self.num_vars() + 1).unwrap();
+        let toggle = manager.bdd_var(self.num_vars());
+        let dontcare_inputs: Vec<_> = self
+            .states()
+            .map(|state| state.dontcare_inputs.as_ref().map(|d| d.transfer(&manager)))
+            .collect();
+        let states = std::mem::take(&mut self.states);
+        for (index, state) in states.into_iter().enumerate() {
+            if replacement[index].is_some() {
+                continue;
+            }
+            let transitions = state
+                .transitions
+                .iter()
+                .map(|transition| {
+                    let mut new_transition = Transition::new(transition.input.transfer(&manager));
+                    for transition_output in &transition.outputs {
+                        let successor = transition_output.successor;
+                        let output = transition_output.output.transfer(&manager);
+                        match replacement[successor.0] {
+                            Some(kept) => new_transition
+                                .add_output(output & &toggle, indices[kept.0].unwrap()),
+                            None => new_transition
+                                .add_output(output & !&toggle, indices[successor.0].unwrap()),
+                        }
+                    }
+                    new_transition
+                })
+                .collect();
+            let dontcare = match replaced[index] {
+                Some(other_state) => {
+                    match (&dontcare_inputs[index], &dontcare_inputs[other_state.0]) {
+                        (Some(dontcare), Some(other_dontcare)) => {
+                            Some(dontcare & other_dontcare.permute(&permutation))
+                        }
+                        _ => None,
+                    }
+                }
+                None => dontcare_inputs[index].clone(),
+            };
+            self.states.push(
+                State::with_transitions(state.label, transitions).with_dontcare_inputs(dontcare),
+            );
+        }
+        for initial_state in &mut self.initial_states {
+            *initial_state = indices[initial_state.0].unwrap();
+        }
+        self.outputs.push(toggle_name);
+        self.symmetry = Some(swaps.to_vec());
+        info!(
+            "Quotient by symmetry removed {} of {} states",
+            num_states - num_kept,
+            num_states
+        );
+        Some(num_states - num_kept)
+    }
+}
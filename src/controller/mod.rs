@@ -2,9 +2,110 @@
 
 pub(crate) mod aiger;
 pub(crate) mod bdd;
+mod btor2;
+mod exact;
+mod hierarchy;
+mod interface;
 pub mod labelling;
+pub(crate) mod lut;
 pub(crate) mod machine;
+mod sat;
 
-pub use self::aiger::AigerController;
-pub use bdd::BddController;
-pub use machine::LabelledMachine;
+pub use self::aiger::{AigerController, AigerLatency};
+pub use bdd::{BddController, SendableBddController};
+pub use btor2::Btor2Controller;
+pub use hierarchy::HierarchicalController;
+pub use interface::{InterfaceReport, SignalReport, SignalUsage};
+pub use lut::LutController;
+pub use machine::{LabelledMachine, ParseMachineError, StateMapping, StateMappingEntry};
+
+use std::fmt;
+use std::io::{self, Write};
+
+use log::info;
+
+/// The number of bytes after which the progress of writing a controller is reported.
+const PROGRESS_INTERVAL: u64 = 64 * 1024 * 1024;
+
+/// A writer that reports the progress of writing large controllers.
+pub(crate) struct ProgressWriter<W> {
+    writer: W,
+    written: u64,
+    next_report: u64,
+}
+
+impl<W: Write> ProgressWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        Self {
+            writer,
+            written: 0,
+            next_report: PROGRESS_INTERVAL,
+        }
+    }
+}
+
+impl<W: Write> Write for ProgressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writer.write(buf)?;
+        self.written += n as u64;
+        if self.written >= self.next_report {
+            info!("Written {} MiB of controller", self.written / (1024 * 1024));
+            self.next_report += PROGRESS_INTERVAL;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// An error from composing two controllers with incompatible interfaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompositionError {
+    /// Both controllers have an output with the given name.
+    SharedOutput(String),
+    /// An output of one controller with the given name is an input of the other controller.
+    Feedback(String),
+    /// One of the controllers is a Moore machine.
+    NotMealy,
+}
+
+impl fmt::Display for CompositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SharedOutput(name) => write!(f, "output {} is shared by both controllers", name),
+            Self::Feedback(name) => write!(
+                f,
+                "output {} of one controller is an input of the other controller",
+                name
+            ),
+            Self::NotMealy => write!(f, "only Mealy machines can be composed"),
+        }
+    }
+}
+
+impl std::error::Error for CompositionError {}
+
+/// Checks that two controllers with the given inputs and outputs can be composed
+/// in parallel, i.e. that their outputs are disjoint and no output of one
+/// controller is an input of the other controller.
+fn check_composition<S: AsRef<str>>(
+    inputs: &[S],
+    outputs: &[S],
+    other_inputs: &[S],
+    other_outputs: &[S],
+) -> Result<(), CompositionError> {
+    let contains = |names: &[S], name: &S| names.iter().any(|n| n.as_ref() == name.as_ref());
+    if let Some(output) = outputs.iter().find(|o| contains(other_outputs, *o)) {
+        return Err(CompositionError::SharedOutput(output.as_ref().to_string()));
+    }
+    if let Some(output) = outputs
+        .iter()
+        .find(|o| contains(other_inputs, *o))
+        .or_else(|| other_outputs.iter().find(|o| contains(inputs, *o)))
+    {
+        return Err(CompositionError::Feedback(output.as_ref().to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Solving of SAT formulas within a deadline.
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+use log::error;
+use varisat::{CnfFormula, Lit, Solver};
+
+/// The result of solving a formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SatResult {
+    /// The formula is satisfiable with the given model.
+    Sat(Vec<Lit>),
+    /// The formula is unsatisfiable.
+    Unsat,
+    /// The deadline passed or the solver failed before the formula was solved.
+    Unknown,
+}
+
+/// Solves the formula, where the search is abandoned once the deadline passes.
+///
+/// The solver can not be interrupted, so with a deadline it runs on a separate
+/// thread whose result is only awaited until the deadline. An abandoned search
+/// finishes in the background and its result is discarded.
+pub(crate) fn solve(formula: CnfFormula, deadline: Option<Instant>) -> SatResult {
+    let deadline = match deadline {
+        Some(deadline) => deadline,
+        None => return solve_formula(&formula),
+    };
+    let now = Instant::now();
+    if now >= deadline {
+        return SatResult::Unknown;
+    }
+    let (sender, receiver) = mpsc::channel();
+    let spawned = thread::Builder::new()
+        .name("sat-solver".to_string())
+        .spawn(move || {
+            // the result is not received any more if the deadline has passed
+            let _ = sender.send(solve_formula(&formula));
+        });
+    if let Err(err) = spawned {
+        error!("Could not start thread for sat solver: {}", err);
+        return SatResult::Unknown;
+    }
+    receiver
+        .recv_timeout(deadline - now)
+        .unwrap_or(SatResult::Unknown)
+}
+
+/// Solves the formula on the current thread.
+fn solve_formula(formula: &CnfFormula) -> SatResult {
+    let mut solver = Solver::new();
+    solver.add_formula(formula);
+    match solver.solve() {
+        Ok(true) => SatResult::Sat(solver.model().unwrap()),
+        Ok(false) => SatResult::Unsat,
+        Err(err) => {
+            error!("Sat solver error: {}", err);
+            SatResult::Unknown
+        }
+    }
+}
+
+/// Tests for solving formulas within a deadline.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use varisat::ExtendFormula;
+
+    /// Returns a formula stating that `n + 1` pigeons sit in `n` holes, which is
+    /// unsatisfiable and hard to refute for a resolution-based solver.
+    fn pigeonhole(n: usize) -> CnfFormula {
+        let mut formula = CnfFormula::new();
+        let sits: Vec<Vec<Lit>> = (0..=n)
+            .map(|_| (0..n).map(|_| formula.new_lit()).collect())
+            .collect();
+        for pigeon in &sits {
+            formula.add_clause(pigeon);
+        }
+        for hole in 0..n {
+            for p1 in 0..=n {
+                for p2 in (p1 + 1)..=n {
+                    formula.add_clause(&[!sits[p1][hole], !sits[p2][hole]]);
+                }
+            }
+        }
+        formula
+    }
+
+    /// Test that formulas are solved and the search is abandoned at the deadline.
+    #[test]
+    fn test_solve_with_deadline() {
+        let deadline = Instant::now() + Duration::from_secs(60);
+        for &deadline in &[None, Some(deadline)] {
+            let mut formula = CnfFormula::new();
+            let (a, b) = (formula.new_lit(), formula.new_lit());
+            formula.add_clause(&[a, b]);
+            formula.add_clause(&[!a]);
+            assert_eq!(solve(formula, deadline), SatResult::Sat(vec![!a, b]));
+        }
+        assert_eq!(solve(pigeonhole(3), Some(deadline)), SatResult::Unsat);
+
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(100);
+        assert_eq!(solve(pigeonhole(14), Some(deadline)), SatResult::Unknown);
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(solve(pigeonhole(3), Some(start)), SatResult::Unknown);
+    }
+}
@@ -0,0 +1,599 @@
+//! Cross-validation of the realizability status by bounded synthesis.
+//!
+//! For small specifications, the automaton is explored completely and a search
+//! enumerates Mealy machines for the system and counter-strategies for the environment
+//! up to a bound on the number of states, in order of increasing size. A machine is
+//! accepted if all cycles of its product with the automaton have an even maximal color,
+//! and a counter-strategy is accepted if all these cycles have an odd maximal color.
+//!
+//! The search is independent of the exploration, the game construction and the parity
+//! game solvers, and thus provides a second opinion on the status determined by them.
+//! As the search is bounded, it can only confirm or contradict a status if it finds a
+//! machine or a counter-strategy, and is inconclusive otherwise.
+//!
+//! The same product construction checks whether a machine is robust to glitches of the
+//! inputs, where the machine reads a corrupted value of a single input at most once in
+//! every window of a given number of steps, while the automaton reads the actual inputs.
+//! The search for machines can include these glitches to find a robust machine.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use log::debug;
+use owl::automaton::{Color, MaxEvenDpa, StateIndex};
+
+use crate::Status;
+
+/// The maximal number of inputs and outputs of a specification that is cross-checked.
+const MAX_PROPOSITIONS: usize = 8;
+/// The maximal number of states of an automaton that is cross-checked.
+const MAX_AUTOMATON_STATES: usize = 1000;
+/// The maximal number of partial machines and counter-strategies considered by the search.
+const MAX_CANDIDATES: usize = 1_000_000;
+
+/// The outcome of cross-checking the realizability status.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CrossCheck {
+    /// A machine for a realizable or a counter-strategy for an unrealizable status
+    /// was found, with the given number of states.
+    Confirmed(usize),
+    /// A machine for an unrealizable or a counter-strategy for a realizable status
+    /// was found, with the given number of states.
+    Contradicted(usize),
+    /// Neither a machine nor a counter-strategy was found within the bounds.
+    Inconclusive,
+}
+
+impl CrossCheck {
+    /// Compares the status of the synthesis procedure with the status found by
+    /// bounded synthesis with the number of states of the witness, if any.
+    pub(crate) fn compare(status: Status, bounded: Option<(Status, usize)>) -> Self {
+        match bounded {
+            Some((s, states)) if s == status => Self::Confirmed(states),
+            Some((_, states)) => Self::Contradicted(states),
+            None => Self::Inconclusive,
+        }
+    }
+}
+
+/// The outcome of analyzing the robustness of a controller to glitches of the inputs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Robustness {
+    /// The controller satisfies the specification under all glitches.
+    Robust,
+    /// The controller violates the specification under some glitches.
+    Violated,
+    /// The controller violates the specification under some glitches, and was replaced
+    /// by a robust controller with the given number of states.
+    Resynthesized(usize),
+    /// The controller or the automaton is too large for the analysis.
+    Unknown,
+}
+
+impl fmt::Display for Robustness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Robust => write!(f, "robust"),
+            Self::Violated => write!(f, "violated"),
+            Self::Resynthesized(_) => write!(f, "resynthesized"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// A completely explored automaton with explicit successors for each valuation.
+#[derive(Debug, Clone)]
+pub(crate) struct ExplicitDpa {
+    num_inputs: usize,
+    num_outputs: usize,
+    /// The successor and color for each state and valuation, where the valuation
+    /// has the inputs in the low bits and the outputs in the high bits, and the
+    /// initial state has index 0.
+    transitions: Vec<Vec<(usize, Color)>>,
+}
+
+impl ExplicitDpa {
+    /// Explores the automaton completely for the given number of inputs and outputs,
+    /// or returns `None` if it has more states than allowed for cross-checking.
+    pub(crate) fn explore<A: MaxEvenDpa>(
+        automaton: &mut A,
+        num_inputs: usize,
+        num_outputs: usize,
+    ) -> Option<Self> {
+        let num_vars = num_inputs + num_outputs;
+        if num_vars > MAX_PROPOSITIONS {
+            return None;
+        }
+        let mut indices = HashMap::new();
+        let mut states = vec![automaton.initial_state()];
+        indices.insert(automaton.initial_state(), 0);
+        let mut transitions = Vec::new();
+        let mut valuation = vec![false; num_vars];
+        while transitions.len() < states.len() {
+            let state: StateIndex = states[transitions.len()];
+            let edges = automaton.successors(state);
+            let mut successors = Vec::with_capacity(1 << num_vars);
+            for v in 0..(1_usize << num_vars) {
+                for (var, value) in valuation.iter_mut().enumerate() {
+                    *value = (v >> var) & 1 == 1;
+                }
+                let edge = edges.lookup(&valuation);
+                let next = indices.len();
+                let index = *indices.entry(edge.successor()).or_insert(next);
+                if index == states.len() {
+                    states.push(edge.successor());
+                }
+                successors.push((index, edge.color()));
+            }
+            transitions.push(successors);
+            if states.len() > MAX_AUTOMATON_STATES {
+                return None;
+            }
+        }
+        Some(Self {
+            num_inputs,
+            num_outputs,
+            transitions,
+        })
+    }
+
+    /// Returns the successor and color for the given state, input and output valuation.
+    fn step(&self, state: usize, input: usize, output: usize) -> (usize, Color) {
+        self.transitions[state][input | (output << self.num_inputs)]
+    }
+
+    /// Searches for a machine or counter-strategy with at most the given number of states,
+    /// and returns the status it shows together with its number of states.
+    pub(crate) fn bounded_synthesis(&self, max_states: usize) -> Option<(Status, usize)> {
+        let mut budget = MAX_CANDIDATES;
+        for states in 1..=max_states {
+            let mut system = SystemSearch {
+                dpa: self,
+                max_states: states,
+                machine: vec![None; states << self.num_inputs],
+                glitch_window: None,
+                budget: &mut budget,
+            };
+            match system.search(0, 1) {
+                Some(true) => return Some((Status::Realizable, states)),
+                Some(false) => (),
+                None => break,
+            }
+            let mut environment = EnvironmentSearch {
+                dpa: self,
+                max_states: states,
+                inputs: vec![None; states],
+                successors: vec![None; states << self.num_outputs],
+                budget: &mut budget,
+            };
+            match environment.search(0, 1) {
+                Some(true) => return Some((Status::Unrealizable, states)),
+                Some(false) => (),
+                None => break,
+            }
+            debug!("No machine or counter-strategy with {} states", states);
+        }
+        None
+    }
+
+    /// Adds the successors of the given node of the product of this automaton with a
+    /// partial machine, where the machine reads a corrupted input if glitches with the
+    /// given window are enabled.
+    ///
+    /// The machine gives the output and successor for each state and input, and a node
+    /// consists of the state of the machine combined with the number of steps until the
+    /// next glitch is possible, and the state of the automaton. A glitch flips the value
+    /// of a single input read by the machine, while the automaton reads the actual input.
+    fn product_successors(
+        &self,
+        machine: &[Option<(usize, usize)>],
+        glitch_window: Option<usize>,
+        (x, s): (usize, usize),
+        successors: &mut Vec<((usize, usize), Color)>,
+    ) {
+        let num_inputs = 1 << self.num_inputs;
+        let window = glitch_window.unwrap_or(1);
+        let (q, cooldown) = (x / window, x % window);
+        let num_glitches = match glitch_window {
+            Some(_) if cooldown == 0 => self.num_inputs,
+            _ => 0,
+        };
+        for input in 0..num_inputs {
+            for glitch in 0..=num_glitches {
+                let (reading, next_cooldown) = if glitch == 0 {
+                    (input, cooldown.saturating_sub(1))
+                } else {
+                    (input ^ (1 << (glitch - 1)), window - 1)
+                };
+                if let Some((output, next)) = machine[q * num_inputs + reading] {
+                    let (s_next, color) = self.step(s, input, output);
+                    successors.push(((next * window + next_cooldown, s_next), color));
+                }
+            }
+        }
+    }
+
+    /// Returns whether the given machine satisfies the specification of this automaton
+    /// if the machine reads a corrupted value of a single input at most once in every
+    /// window of the given number of steps.
+    ///
+    /// The machine gives the output and successor for each state and input, where
+    /// state 0 is the initial state. Readings without a transition are not followed.
+    pub(crate) fn is_robust(&self, machine: &[Option<(usize, usize)>], window: usize) -> bool {
+        !has_cycle_with_parity(1, |node, successors| {
+            self.product_successors(machine, Some(window), node, successors)
+        })
+    }
+
+    /// Searches for a machine with at most the given number of states that is robust to
+    /// glitches with the given window, see [`ExplicitDpa::is_robust`].
+    ///
+    /// Returns the output and successor for each state and input of the machine, or
+    /// `None` if no such machine is found.
+    pub(crate) fn robust_synthesis(
+        &self,
+        window: usize,
+        max_states: usize,
+    ) -> Option<Vec<Option<(usize, usize)>>> {
+        let mut budget = MAX_CANDIDATES;
+        for states in 1..=max_states {
+            let mut system = SystemSearch {
+                dpa: self,
+                max_states: states,
+                machine: vec![None; states << self.num_inputs],
+                glitch_window: Some(window),
+                budget: &mut budget,
+            };
+            match system.search(0, 1) {
+                Some(true) => {
+                    // the transitions of the used states are chosen, all others are not
+                    let mut machine = system.machine;
+                    machine.retain(Option::is_some);
+                    return Some(machine);
+                }
+                Some(false) => (),
+                None => break,
+            }
+            debug!("No robust machine with {} states", states);
+        }
+        None
+    }
+}
+
+/// Checks the realizability of a specification with the given automaton by bounded
+/// synthesis up to the given number of states.
+///
+/// Returns the status shown by a machine or counter-strategy together with its number
+/// of states, or `None` if the specification is too large or no such machine or
+/// counter-strategy is found.
+pub(crate) fn check_realizability<A: MaxEvenDpa>(
+    automaton: &mut A,
+    num_inputs: usize,
+    num_outputs: usize,
+    max_states: usize,
+) -> Option<(Status, usize)> {
+    ExplicitDpa::explore(automaton, num_inputs, num_outputs)?.bounded_synthesis(max_states)
+}
+
+/// Analyzes the robustness of the given machine to glitches of the inputs with the given
+/// window, see [`ExplicitDpa::is_robust`], against the specification with the given
+/// automaton. If the machine is not robust and a bound is given, a robust machine with
+/// at most that number of states is searched.
+///
+/// Returns the outcome together with the robust machine, if one was found.
+pub(crate) fn analyze_robustness<A: MaxEvenDpa>(
+    automaton: &mut A,
+    num_inputs: usize,
+    num_outputs: usize,
+    machine: &[Option<(usize, usize)>],
+    window: usize,
+    max_states: Option<usize>,
+) -> (Robustness, Option<Vec<Option<(usize, usize)>>>) {
+    let dpa = match ExplicitDpa::explore(automaton, num_inputs, num_outputs) {
+        Some(dpa) => dpa,
+        None => return (Robustness::Unknown, None),
+    };
+    if dpa.is_robust(machine, window) {
+        return (Robustness::Robust, None);
+    }
+    match max_states.and_then(|max_states| dpa.robust_synthesis(window, max_states)) {
+        Some(robust) => (
+            Robustness::Resynthesized(robust.len() >> num_inputs),
+            Some(robust),
+        ),
+        None => (Robustness::Violated, None),
+    }
+}
+
+/// Returns whether the product graph reachable from node `(0, 0)` with the given
+/// successor function contains a cycle whose maximal color has the given parity.
+fn has_cycle_with_parity<F>(parity: Color, mut successors: F) -> bool
+where
+    F: FnMut((usize, usize), &mut Vec<((usize, usize), Color)>),
+{
+    let mut indices = HashMap::new();
+    let mut nodes = vec![(0, 0)];
+    indices.insert((0, 0), 0);
+    let mut edges: Vec<Vec<(usize, Color)>> = Vec::new();
+    let mut buffer = Vec::new();
+    while edges.len() < nodes.len() {
+        buffer.clear();
+        successors(nodes[edges.len()], &mut buffer);
+        let mut node_edges = Vec::with_capacity(buffer.len());
+        for &(next, color) in &buffer {
+            let index = *indices.entry(next).or_insert(nodes.len());
+            if index == nodes.len() {
+                nodes.push(next);
+            }
+            node_edges.push((index, color));
+        }
+        edges.push(node_edges);
+    }
+
+    let max_color = edges.iter().flatten().map(|&(_, c)| c).max();
+    let max_color = match max_color {
+        Some(c) => c,
+        None => return false,
+    };
+    (0..=max_color).filter(|c| c % 2 == parity).any(|color| {
+        let components = components(&edges, color);
+        edges.iter().enumerate().any(|(u, node_edges)| {
+            node_edges
+                .iter()
+                .any(|&(v, c)| c == color && components[u] == components[v])
+        })
+    })
+}
+
+/// Returns the strongly connected component of each node in the graph restricted to
+/// edges with at most the given color, using an iterative version of Tarjan's algorithm.
+pub(crate) fn components(edges: &[Vec<(usize, Color)>], max_color: Color) -> Vec<usize> {
+    const UNVISITED: usize = usize::MAX;
+    let n = edges.len();
+    let mut index = vec![UNVISITED; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut component = vec![UNVISITED; n];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut num_components = 0;
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        let mut call_stack = vec![(root, 0)];
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some(&mut (u, ref mut edge)) = call_stack.last_mut() {
+            if let Some(&(v, color)) = edges[u].get(*edge) {
+                *edge += 1;
+                if color > max_color {
+                    continue;
+                }
+                if index[v] == UNVISITED {
+                    index[v] = next_index;
+                    lowlink[v] = next_index;
+                    next_index += 1;
+                    stack.push(v);
+                    on_stack[v] = true;
+                    call_stack.push((v, 0));
+                } else if on_stack[v] {
+                    lowlink[u] = lowlink[u].min(index[v]);
+                }
+            } else {
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[u]);
+                }
+                if lowlink[u] == index[u] {
+                    while let Some(w) = stack.pop() {
+                        on_stack[w] = false;
+                        component[w] = num_components;
+                        if w == u {
+                            break;
+                        }
+                    }
+                    num_components += 1;
+                }
+            }
+        }
+    }
+    component
+}
+
+/// A search for a Mealy machine of the system, which chooses an output and a successor
+/// for each state and input.
+struct SystemSearch<'a> {
+    dpa: &'a ExplicitDpa,
+    max_states: usize,
+    /// The output and successor for each state and input, if already chosen.
+    machine: Vec<Option<(usize, usize)>>,
+    /// The window for glitches of the inputs read by the machine, if enabled.
+    glitch_window: Option<usize>,
+    budget: &'a mut usize,
+}
+
+impl SystemSearch<'_> {
+    /// Returns whether the partial machine has a cycle with an odd maximal color,
+    /// which is also present in every completion of the machine.
+    fn rejected(&self) -> bool {
+        has_cycle_with_parity(1, |node, successors| {
+            self.dpa
+                .product_successors(&self.machine, self.glitch_window, node, successors)
+        })
+    }
+
+    /// Chooses the transition of the given slot with the given number of used states,
+    /// and returns whether an accepted machine was found, or `None` if the budget
+    /// of candidates is exhausted.
+    fn search(&mut self, slot: usize, used: usize) -> Option<bool> {
+        let num_inputs = 1 << self.dpa.num_inputs;
+        if slot >= used * num_inputs {
+            // all transitions of reachable states are chosen
+            return Some(true);
+        }
+        for output in 0..(1 << self.dpa.num_outputs) {
+            // break symmetries by using a new state only as the next unused state
+            for next in 0..used.saturating_add(1).min(self.max_states) {
+                if *self.budget == 0 {
+                    return None;
+                }
+                *self.budget -= 1;
+                self.machine[slot] = Some((output, next));
+                if !self.rejected() && self.search(slot + 1, used.max(next + 1))? {
+                    return Some(true);
+                }
+            }
+        }
+        self.machine[slot] = None;
+        Some(false)
+    }
+}
+
+/// A search for a counter-strategy of the environment, which chooses an input for
+/// each state and a successor for each state and output.
+struct EnvironmentSearch<'a> {
+    dpa: &'a ExplicitDpa,
+    max_states: usize,
+    /// The input for each state, if already chosen.
+    inputs: Vec<Option<usize>>,
+    /// The successor for each state and output, if already chosen.
+    successors: Vec<Option<usize>>,
+    budget: &'a mut usize,
+}
+
+impl EnvironmentSearch<'_> {
+    /// Returns whether the partial counter-strategy has a cycle with an even maximal
+    /// color, which is also present in every completion of the counter-strategy.
+    fn rejected(&self) -> bool {
+        let num_outputs = 1 << self.dpa.num_outputs;
+        has_cycle_with_parity(0, |(p, s), successors| {
+            if let Some(input) = self.inputs[p] {
+                for output in 0..num_outputs {
+                    if let Some(next) = self.successors[p * num_outputs + output] {
+                        let (s_next, color) = self.dpa.step(s, input, output);
+                        successors.push(((next, s_next), color));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Chooses the input or a successor of the given slot with the given number of used
+    /// states, and returns whether an accepted counter-strategy was found, or `None` if
+    /// the budget of candidates is exhausted.
+    fn search(&mut self, slot: usize, used: usize) -> Option<bool> {
+        let num_outputs = 1 << self.dpa.num_outputs;
+        let slots_per_state = num_outputs + 1;
+        if slot >= used * slots_per_state {
+            return Some(true);
+        }
+        let state = slot / slots_per_state;
+        let offset = slot % slots_per_state;
+        if offset == 0 {
+            for input in 0..(1 << self.dpa.num_inputs) {
+                if *self.budget == 0 {
+                    return None;
+                }
+                *self.budget -= 1;
+                self.inputs[state] = Some(input);
+                if self.search(slot + 1, used)? {
+                    return Some(true);
+                }
+            }
+            self.inputs[state] = None;
+        } else {
+            let index = state * num_outputs + offset - 1;
+            for next in 0..used.saturating_add(1).min(self.max_states) {
+                if *self.budget == 0 {
+                    return None;
+                }
+                *self.budget -= 1;
+                self.successors[index] = Some(next);
+                if !self.rejected() && self.search(slot + 1, used.max(next + 1))? {
+                    return Some(true);
+                }
+            }
+            self.successors[index] = None;
+        }
+        Some(false)
+    }
+}
+
+/// Tests for bounded synthesis on explicit automata.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a machine is found for a realizable specification.
+    #[test]
+    fn test_realizable() {
+        // G (r <-> g) with input r in bit 0 and output g in bit 1
+        let dpa = ExplicitDpa {
+            num_inputs: 1,
+            num_outputs: 1,
+            transitions: vec![
+                vec![(0, 0), (1, 1), (1, 1), (0, 0)],
+                vec![(1, 1), (1, 1), (1, 1), (1, 1)],
+            ],
+        };
+        assert_eq!(dpa.bounded_synthesis(2), Some((Status::Realizable, 1)));
+    }
+
+    /// Test that glitches of the inputs break a machine that copies its input, while a
+    /// robust machine is found if the specification does not depend on the readings.
+    #[test]
+    fn test_robustness() {
+        // G (r <-> g) with input r in bit 0 and output g in bit 1
+        let dpa = ExplicitDpa {
+            num_inputs: 1,
+            num_outputs: 1,
+            transitions: vec![
+                vec![(0, 0), (1, 1), (1, 1), (0, 0)],
+                vec![(1, 1), (1, 1), (1, 1), (1, 1)],
+            ],
+        };
+        let copy = vec![Some((0, 0)), Some((1, 0))];
+        assert!(!dpa.is_robust(&copy, 3));
+        assert_eq!(dpa.robust_synthesis(3, 2), None);
+
+        // G F r -> G F g, which is satisfied by any machine that outputs g infinitely often
+        let dpa = ExplicitDpa {
+            num_inputs: 1,
+            num_outputs: 1,
+            transitions: vec![vec![(0, 0), (0, 1), (0, 2), (0, 2)]],
+        };
+        assert!(dpa.is_robust(&[Some((1, 0)), Some((1, 0))], 1));
+        // glitches can hide every request from the machine that copies its input
+        assert!(!dpa.is_robust(&copy, 1));
+        assert!(!dpa.is_robust(&copy, 2));
+        let robust = dpa.robust_synthesis(1, 2).unwrap();
+        assert!(dpa.is_robust(&robust, 1));
+    }
+
+    /// Test that a counter-strategy is found for an unrealizable specification.
+    #[test]
+    fn test_unrealizable() {
+        // G (g <-> X r) with states for the initial step, the previous value of g,
+        // and the rejecting sink
+        let dpa = ExplicitDpa {
+            num_inputs: 1,
+            num_outputs: 1,
+            transitions: vec![
+                vec![(1, 0), (1, 0), (2, 0), (2, 0)],
+                vec![(1, 0), (3, 1), (2, 0), (3, 1)],
+                vec![(3, 1), (1, 0), (3, 1), (2, 0)],
+                vec![(3, 1), (3, 1), (3, 1), (3, 1)],
+            ],
+        };
+        assert_eq!(dpa.bounded_synthesis(1), None);
+        assert_eq!(dpa.bounded_synthesis(2), Some((Status::Unrealizable, 2)));
+        assert_eq!(
+            CrossCheck::compare(Status::Realizable, dpa.bounded_synthesis(2)),
+            CrossCheck::Contradicted(2)
+        );
+    }
+}
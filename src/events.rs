@@ -0,0 +1,432 @@
+//! Structured events emitted at the stage boundaries of the synthesis procedure.
+//!
+//! Each event is recorded together with the time elapsed since the start of the
+//! synthesis procedure. Events can be received with a callback implementing
+//! [`EventSink`], or written as JSON lines with [`JsonLinesSink`].
+
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use log::trace;
+
+use crate::analysis::TemporalClass;
+use crate::crosscheck::Robustness;
+use crate::Status;
+
+/// An event at a stage boundary of the synthesis procedure, together with
+/// the key metrics of the finished stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineEvent {
+    /// The GraalVM for Owl has been initialized, which is reported separately as its
+    /// startup may dominate the runtime for small specifications.
+    VmInitialized {
+        /// The time for creating the GraalVM in milliseconds, which is zero if reused.
+        millis: u64,
+        /// Whether the GraalVM of a previous synthesis call in the same thread was reused.
+        reused: bool,
+    },
+    /// Atomic propositions of the formula that are neither declared as inputs nor as
+    /// outputs have been added to the inputs or outputs.
+    UndeclaredPropositions {
+        /// The added propositions.
+        propositions: Vec<String>,
+        /// Whether the propositions were added to the inputs instead of the outputs.
+        inputs: bool,
+    },
+    /// The formula has been classified in the temporal hierarchy, independently of
+    /// how the specification is synthesized.
+    FormulaClassified {
+        /// The class of the formula.
+        class: TemporalClass,
+    },
+    /// The automaton for the specification has been created.
+    AutomatonCreated,
+    /// The game has been solved with the given number of nodes, including the border.
+    GameSolved {
+        /// The number of nodes of the game.
+        nodes: usize,
+        /// The realizability status determined by the game.
+        status: Status,
+    },
+    /// The machine has been constructed from the winning strategy.
+    MachineConstructed {
+        /// The number of states of the machine.
+        states: usize,
+    },
+    /// The bounds on the minimal size of the machine have been narrowed during the
+    /// minimization using don't cares, which stops at the upper bound if its time
+    /// budget is exhausted.
+    MinimizationBounds {
+        /// The lower bound on the number of states.
+        lower: usize,
+        /// The number of states of the smallest machine found so far.
+        upper: usize,
+    },
+    /// The machine has been minimized.
+    MachineMinimized {
+        /// The number of states of the minimized machine.
+        states: usize,
+    },
+    /// The BDDs of the controller have been constructed and reordered.
+    BddConstructed {
+        /// The number of BDD variables, i.e. inputs and state bits.
+        variables: usize,
+    },
+    /// The aiger circuit of the controller has been constructed and compressed.
+    AigerConstructed {
+        /// The number of and gates of the circuit.
+        ands: u32,
+        /// The number of latches of the circuit.
+        latches: u32,
+        /// The depth of the circuit in and gates.
+        depth: u32,
+        /// The maximum number of and gates on a path from an input to an output.
+        io_delay: u32,
+        /// The maximum number of and gates on a path from a latch to an output.
+        latch_delay: u32,
+    },
+    /// The robustness of the controller to glitches of the inputs has been analyzed.
+    RobustnessAnalyzed {
+        /// The number of steps in which at most one glitch occurs.
+        window: usize,
+        /// The outcome of the analysis.
+        robustness: Robustness,
+    },
+    /// The synthesis procedure has finished.
+    Finished {
+        /// The realizability status of the specification.
+        status: Status,
+    },
+}
+
+impl PipelineEvent {
+    /// Returns the name of the event in snake case, as used in the JSON output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::VmInitialized { .. } => "vm_initialized",
+            Self::UndeclaredPropositions { .. } => "undeclared_propositions",
+            Self::FormulaClassified { .. } => "formula_classified",
+            Self::AutomatonCreated => "automaton_created",
+            Self::GameSolved { .. } => "game_solved",
+            Self::MachineConstructed { .. } => "machine_constructed",
+            Self::MinimizationBounds { .. } => "minimization_bounds",
+            Self::MachineMinimized { .. } => "machine_minimized",
+            Self::BddConstructed { .. } => "bdd_constructed",
+            Self::AigerConstructed { .. } => "aiger_constructed",
+            Self::RobustnessAnalyzed { .. } => "robustness_analyzed",
+            Self::Finished { .. } => "finished",
+        }
+    }
+
+    /// Writes the metrics of the event as JSON members, each preceded by a comma.
+    fn write_metrics<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        match self {
+            Self::VmInitialized { millis, reused } => {
+                write!(writer, ",\"millis\":{},\"reused\":{}", millis, reused)
+            }
+            Self::UndeclaredPropositions {
+                propositions,
+                inputs,
+            } => {
+                write!(writer, ",\"propositions\":[")?;
+                for (i, proposition) in propositions.iter().enumerate() {
+                    if i > 0 {
+                        write!(writer, ",")?;
+                    }
+                    write_json_string(&mut writer, proposition)?;
+                }
+                write!(writer, "],\"inputs\":{}", inputs)
+            }
+            Self::FormulaClassified { class } => write!(writer, ",\"class\":\"{}\"", class),
+            Self::AutomatonCreated => Ok(()),
+            Self::GameSolved { nodes, status } => {
+                write!(writer, ",\"nodes\":{},\"status\":\"{}\"", nodes, status)
+            }
+            Self::MachineConstructed { states } | Self::MachineMinimized { states } => {
+                write!(writer, ",\"states\":{}", states)
+            }
+            Self::MinimizationBounds { lower, upper } => {
+                write!(writer, ",\"lower\":{},\"upper\":{}", lower, upper)
+            }
+            Self::BddConstructed { variables } => write!(writer, ",\"variables\":{}", variables),
+            Self::AigerConstructed {
+                ands,
+                latches,
+                depth,
+                io_delay,
+                latch_delay,
+            } => write!(
+                writer,
+                ",\"ands\":{},\"latches\":{},\"depth\":{},\"io_delay\":{},\"latch_delay\":{}",
+                ands, latches, depth, io_delay, latch_delay
+            ),
+            Self::RobustnessAnalyzed { window, robustness } => write!(
+                writer,
+                ",\"window\":{},\"robustness\":\"{}\"",
+                window, robustness
+            ),
+            Self::Finished { status } => write!(writer, ",\"status\":\"{}\"", status),
+        }
+    }
+}
+
+/// Writes the given string as JSON string with quotes, escaping quotes, backslashes
+/// and control characters.
+fn write_json_string<W: Write>(mut writer: W, s: &str) -> io::Result<()> {
+    write!(writer, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(writer, "\\\"")?,
+            '\\' => write!(writer, "\\\\")?,
+            c if c.is_control() => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{}", c)?,
+        }
+    }
+    write!(writer, "\"")
+}
+
+impl fmt::Display for PipelineEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        match self {
+            Self::VmInitialized { millis, reused } => {
+                write!(f, " (millis: {}, reused: {})", millis, reused)
+            }
+            Self::UndeclaredPropositions {
+                propositions,
+                inputs,
+            } => write!(
+                f,
+                " (propositions: {}, inputs: {})",
+                propositions.join(", "),
+                inputs
+            ),
+            Self::FormulaClassified { class } => write!(f, " (class: {})", class),
+            Self::AutomatonCreated => Ok(()),
+            Self::GameSolved { nodes, status } => {
+                write!(f, " (nodes: {}, status: {})", nodes, status)
+            }
+            Self::MachineConstructed { states } | Self::MachineMinimized { states } => {
+                write!(f, " (states: {})", states)
+            }
+            Self::MinimizationBounds { lower, upper } => {
+                write!(f, " (lower: {}, upper: {})", lower, upper)
+            }
+            Self::BddConstructed { variables } => write!(f, " (variables: {})", variables),
+            Self::AigerConstructed {
+                ands,
+                latches,
+                depth,
+                io_delay,
+                latch_delay,
+            } => write!(
+                f,
+                " (ands: {}, latches: {}, depth: {}, io delay: {}, latch delay: {})",
+                ands, latches, depth, io_delay, latch_delay
+            ),
+            Self::RobustnessAnalyzed { window, robustness } => {
+                write!(f, " (window: {}, robustness: {})", window, robustness)
+            }
+            Self::Finished { status } => write!(f, " (status: {})", status),
+        }
+    }
+}
+
+/// A receiver for the events of the synthesis procedure.
+pub trait EventSink {
+    /// Records an event, which happened after the given time elapsed since the
+    /// start of the synthesis procedure.
+    fn record(&mut self, elapsed: Duration, event: &PipelineEvent);
+}
+
+impl<F: FnMut(Duration, &PipelineEvent)> EventSink for F {
+    fn record(&mut self, elapsed: Duration, event: &PipelineEvent) {
+        self(elapsed, event)
+    }
+}
+
+/// A sink that writes each event as one line of JSON to a writer.
+///
+/// Each line is an object with the elapsed time in seconds as member `time`,
+/// the [name](PipelineEvent::name) of the event as member `event` and one member
+/// for each metric of the event, e.g.
+/// `{"time":0.812,"event":"game_solved","nodes":42,"status":"REALIZABLE"}`.
+///
+/// Writing stops at the first I/O error, which is returned by [`JsonLinesSink::finish`].
+pub struct JsonLinesSink<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    /// Creates a new sink writing to the given writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Flushes the writer and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error that occurred while writing an event or flushing.
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => self.writer.flush().map(|_| self.writer),
+        }
+    }
+
+    fn write_event(&mut self, elapsed: Duration, event: &PipelineEvent) -> io::Result<()> {
+        write!(
+            self.writer,
+            "{{\"time\":{:.3},\"event\":\"{}\"",
+            elapsed.as_secs_f64(),
+            event.name()
+        )?;
+        event.write_metrics(&mut self.writer)?;
+        writeln!(self.writer, "}}")
+    }
+}
+
+impl<W: Write> EventSink for JsonLinesSink<W> {
+    fn record(&mut self, elapsed: Duration, event: &PipelineEvent) {
+        if self.error.is_none() {
+            if let Err(error) = self.write_event(elapsed, event) {
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+/// The log of events of one run of the synthesis procedure, which forwards
+/// each event to the registered sinks.
+pub(crate) struct EventLog<'e> {
+    start: Instant,
+    sinks: Vec<&'e mut dyn EventSink>,
+}
+
+impl<'e> EventLog<'e> {
+    /// Creates a new log without sinks, starting the time for events now.
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            sinks: Vec::new(),
+        }
+    }
+
+    /// Registers a sink that receives all further events.
+    pub(crate) fn register(&mut self, sink: &'e mut dyn EventSink) {
+        self.sinks.push(sink);
+    }
+
+    /// Records the given event in all registered sinks.
+    pub(crate) fn emit(&mut self, event: PipelineEvent) {
+        let elapsed = self.start.elapsed();
+        trace!("Event after {:.3}s: {}", elapsed.as_secs_f64(), event);
+        for sink in &mut self.sinks {
+            sink.record(elapsed, &event);
+        }
+    }
+}
+
+/// Tests for pipeline events.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that events are written as JSON lines and formatted with their metrics.
+    #[test]
+    fn test_json_lines() {
+        let mut sink = JsonLinesSink::new(Vec::new());
+        sink.record(
+            Duration::from_millis(1500),
+            &PipelineEvent::AutomatonCreated,
+        );
+        sink.record(
+            Duration::from_millis(2250),
+            &PipelineEvent::AigerConstructed {
+                ands: 12,
+                latches: 3,
+                depth: 5,
+                io_delay: 4,
+                latch_delay: 2,
+            },
+        );
+        let output = String::from_utf8(sink.finish().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            "{\"time\":1.500,\"event\":\"automaton_created\"}\n\
+             {\"time\":2.250,\"event\":\"aiger_constructed\",\"ands\":12,\"latches\":3,\"depth\":5,\
+             \"io_delay\":4,\"latch_delay\":2}\n"
+        );
+
+        let event = PipelineEvent::GameSolved {
+            nodes: 42,
+            status: Status::Realizable,
+        };
+        assert_eq!(
+            event.to_string(),
+            "game_solved (nodes: 42, status: REALIZABLE)"
+        );
+        assert_eq!(
+            PipelineEvent::AutomatonCreated.to_string(),
+            "automaton_created"
+        );
+        let event = PipelineEvent::VmInitialized {
+            millis: 350,
+            reused: false,
+        };
+        assert_eq!(
+            event.to_string(),
+            "vm_initialized (millis: 350, reused: false)"
+        );
+    }
+
+    /// Test that the names of undeclared propositions are written as escaped JSON strings.
+    #[test]
+    fn test_json_undeclared_propositions() {
+        let event = PipelineEvent::UndeclaredPropositions {
+            propositions: vec!["d".to_string(), "e\"f".to_string()],
+            inputs: true,
+        };
+        let mut sink = JsonLinesSink::new(Vec::new());
+        sink.record(Duration::from_millis(10), &event);
+        let output = String::from_utf8(sink.finish().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            "{\"time\":0.010,\"event\":\"undeclared_propositions\",\
+             \"propositions\":[\"d\",\"e\\\"f\"],\"inputs\":true}\n"
+        );
+        assert_eq!(
+            event.to_string(),
+            "undeclared_propositions (propositions: d, e\"f, inputs: true)"
+        );
+    }
+
+    /// Test that the event log forwards events to all sinks.
+    #[test]
+    fn test_event_log() {
+        let mut first = Vec::new();
+        let mut second = 0;
+        let mut record_first = |_: Duration, event: &PipelineEvent| first.push(event.clone());
+        let mut record_second = |_: Duration, _: &PipelineEvent| second += 1;
+        let mut log = EventLog::new();
+        log.register(&mut record_first);
+        log.register(&mut record_second);
+        log.emit(PipelineEvent::Finished {
+            status: Status::Unrealizable,
+        });
+        drop(log);
+        assert_eq!(
+            first,
+            vec![PipelineEvent::Finished {
+                status: Status::Unrealizable
+            }]
+        );
+        assert_eq!(second, 1);
+    }
+}
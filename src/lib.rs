@@ -1,34 +1,73 @@
 //! Strix library crate for reactive synthesis of controllers from LTL specifications.
+//!
+//! # Thread safety
+//!
+//! The synthesis functions may be called concurrently from several threads. Each thread
+//! uses its own instance of the GraalVM for Owl, see [`release_shared_vm`], and BDD
+//! managers are never shared between calls. The ABC framework for compressing aiger
+//! circuits keeps global state and thus only supports one instance per process, so
+//! compression of circuits is serialized across threads.
 
+pub mod analysis;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod config;
 mod constructor;
 pub mod controller;
+pub mod crosscheck;
+pub mod events;
+mod ltl;
+pub mod monitor;
 pub mod options;
+pub mod params;
 pub mod parity;
+pub mod portfolio;
+pub mod selftest;
+pub mod synthesizer;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+use std::cell::RefCell;
 use std::fmt::{self, Display};
-use std::time::Duration;
+use std::fs;
+use std::hash::Hash;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use log::{debug, info, trace, warn};
 use owl::automaton::{MaxEvenDpa, StateIndex};
 use owl::formula::AtomicPropositionStatus;
+use owl::graal::OwlError;
 
-use constructor::queue::{BfsQueue, DfsQueue, ExplorationQueue, MinMaxMode, MinMaxQueue};
+use analysis::TemporalClass;
+use constructor::queue::{
+    BdfsQueue, BfsQueue, DfsQueue, ExplorationQueue, MinMaxMode, MinMaxQueue, PriorityQueue,
+};
 use constructor::{AutomatonSpecification, ExplorationLimit, GameConstructor};
 use controller::aiger::AigerController;
 use controller::bdd::BddController;
 use controller::labelling::{
-    AutomatonLabelling, AutomatonTreeLabel, SimpleLabelling, StructuredLabel,
+    AutomatonLabelling, AutomatonTreeLabel, Labelling, SimpleLabelling, StructuredLabel,
 };
-use controller::machine::LabelledMachine;
+use controller::lut::LutController;
+use controller::machine::{check_repair_interface, LabelledMachine};
+use controller::{Btor2Controller, HierarchicalController, InterfaceReport, ProgressWriter};
+use crosscheck::{CrossCheck, Robustness};
+use events::{EventLog, EventSink, JsonLinesSink, PipelineEvent};
 use options::{
-    AigerCompression, BddReordering, ExplorationStrategy, LabelCompression, LabelStructure,
-    MinimizationMethod, OnTheFlyLimit, OutputFormat, Simplification, Solver, SynthesisOptions,
+    AigerCompression, AigerMetric, ApOrder, BddReordering, DeterminizationPolicy,
+    ExplorationStrategy, Fairness, LabelCompression, LabelStructure, MinimizationMethod,
+    OnTheFlyLimit, OutputFormat, OutputTarget, Simplification, Solver, SynthesisOptions,
+    Undeclared,
 };
-use parity::game::{LabelledGame, NodeIndex, Player};
+use parity::game::{Game, LabelledGame, NodeIndex, Player};
+use parity::solution::{solve_game, GameSolution};
 use parity::solver::{
-    FpiSolver, IncrementalParityGameSolver, IncrementalSolver, ParityGameSolver, SiSolver,
-    ZlkSolver,
+    Abstraction, FpiSolver, IncrementalParityGameSolver, IncrementalSolver, OinkSolver,
+    ParityGameSolver, SafetySolver, SiSolver, Strategy, ZlkSolver,
 };
+use parity::TelemetrySink;
+use portfolio::{pareto_frontier, Candidate, Provenance};
 
 /// The realizability status for a specification.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -70,6 +109,94 @@ impl Display for Status {
     }
 }
 
+/// An error detected before the synthesis procedure is started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SynthesisError {
+    /// No LTL formula was given.
+    MissingFormula,
+    /// The atomic proposition was given more than once as input or output.
+    DuplicateProposition(String),
+    /// The atomic propositions of the formula are neither declared as inputs nor as
+    /// outputs, see [`SynthesisOptions::undeclared`].
+    UndeclaredPropositions(Vec<String>),
+    /// The combination of options is not supported, with a description of the conflict.
+    IncompatibleOptions(String),
+    /// The atomic proposition of a fairness assumption is not an input.
+    UnknownFairInput(String),
+    /// Owl failed to parse the specification or to translate it to an automaton.
+    Translation(TranslationError),
+    /// The safety monitor could not be read, with a description of the problem.
+    InvalidMonitor(String),
+    /// The interface of the controller to repair does not match the specification,
+    /// with a description of the mismatch.
+    RepairInterface(String),
+}
+
+impl Display for SynthesisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingFormula => write!(f, "no LTL formula given"),
+            Self::DuplicateProposition(ap) => {
+                write!(f, "atomic proposition {} given more than once", ap)
+            }
+            Self::UndeclaredPropositions(aps) => write!(
+                f,
+                "atomic propositions {} of the formula are neither inputs nor outputs",
+                aps.join(", ")
+            ),
+            Self::IncompatibleOptions(conflict) => write!(f, "incompatible options: {}", conflict),
+            Self::UnknownFairInput(ap) => {
+                write!(f, "fairness assumption on {} which is not an input", ap)
+            }
+            Self::Translation(err) => write!(f, "{}", err),
+            Self::InvalidMonitor(err) => write!(f, "invalid safety monitor: {}", err),
+            Self::RepairInterface(err) => {
+                write!(
+                    f,
+                    "controller to repair does not match specification: {}",
+                    err
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SynthesisError {}
+
+/// An error of Owl when parsing the specification or translating it to an automaton.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslationError {
+    /// The error raised by Owl.
+    cause: OwlError,
+    /// The top-level conjunct of the formula for which the error is raised on its own.
+    subformula: Option<String>,
+}
+
+impl TranslationError {
+    /// Returns the error raised by Owl.
+    pub fn cause(&self) -> &OwlError {
+        &self.cause
+    }
+
+    /// Returns the top-level conjunct of the formula that can not be translated on its own,
+    /// if the formula has several conjuncts and such a conjunct has been identified.
+    pub fn subformula(&self) -> Option<&str> {
+        self.subformula.as_deref()
+    }
+}
+
+impl Display for TranslationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.cause)?;
+        if let Some(subformula) = &self.subformula {
+            write!(f, " in subformula {}", subformula)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TranslationError {}
+
 /// Synthesize an LTL specification with the given LTL formula, list of input
 /// atomic propositions and list of atomic output propositions.
 ///
@@ -90,6 +217,627 @@ pub fn synthesize_with(
     outs: &[&str],
     options: &SynthesisOptions,
 ) -> SynthesisResult {
+    synthesize_with_callback(ltl, ins, outs, options, |_| ())
+}
+
+/// Synthesize an LTL specification with the given LTL formula, list of input
+/// atomic propositions and list of atomic output propositions, using the
+/// given synthesis options.
+///
+/// The callback `on_status` is invoked exactly once with the realizability status
+/// as soon as the game is solved, before the controller is constructed, minimized
+/// and compressed. This allows to report the verdict early, e.g. by writing and
+/// flushing it to the output, while the construction of the controller continues.
+///
+/// Returns the result of the synthesis procedure.
+pub fn synthesize_with_callback<F: FnMut(Status)>(
+    ltl: &str,
+    ins: &[&str],
+    outs: &[&str],
+    options: &SynthesisOptions,
+    on_status: F,
+) -> SynthesisResult {
+    try_synthesize_with_callback(ltl, ins, outs, options, on_status)
+        .unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Synthesize an LTL specification as for [`synthesize_with_callback`], where a failure
+/// of Owl to parse the formula or translate it to an automaton is returned as an error.
+///
+/// # Errors
+///
+/// Returns a [`SynthesisError::Translation`] if Owl raises an exception while parsing
+/// the formula or constructing the automaton, e.g. if the heap of the GraalVM set with
+/// [`SynthesisOptions::vm_heap_size`] is exhausted. For a formula with several top-level
+/// conjuncts, the error identifies the first conjunct that fails on its own.
+/// Returns a [`SynthesisError::UnknownFairInput`] if a proposition of a fairness
+/// assumption in [`SynthesisOptions::fair_inputs`] is not an input, and a
+/// [`SynthesisError::UndeclaredPropositions`] if propositions of the formula are
+/// neither inputs nor outputs and [`SynthesisOptions::undeclared`] is
+/// [`Undeclared::Error`].
+pub fn try_synthesize_with_callback<F: FnMut(Status)>(
+    ltl: &str,
+    ins: &[&str],
+    outs: &[&str],
+    options: &SynthesisOptions,
+    mut on_status: F,
+) -> Result<SynthesisResult, SynthesisError> {
+    synthesize_with_sinks(ltl, ins, outs, options, &mut on_status, None, None)
+}
+
+/// Synthesize an LTL specification with the given LTL formula, list of input
+/// atomic propositions and list of atomic output propositions, using the
+/// given synthesis options.
+///
+/// The callback `on_status` is invoked as for [`synthesize_with_callback`].
+/// Additionally, the sink `telemetry` is notified with a [`parity::SolverTelemetry`] record
+/// after each time the parity game is solved during on-the-fly exploration.
+///
+/// Returns the result of the synthesis procedure.
+pub fn synthesize_with_telemetry<F: FnMut(Status), T: TelemetrySink>(
+    ltl: &str,
+    ins: &[&str],
+    outs: &[&str],
+    options: &SynthesisOptions,
+    mut on_status: F,
+    mut telemetry: T,
+) -> SynthesisResult {
+    synthesize_with_sinks(
+        ltl,
+        ins,
+        outs,
+        options,
+        &mut on_status,
+        Some(&mut telemetry),
+        None,
+    )
+    .unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Synthesize an LTL specification with the given LTL formula, list of input
+/// atomic propositions and list of atomic output propositions, using the
+/// given synthesis options.
+///
+/// The callback `on_status` is invoked as for [`synthesize_with_callback`].
+/// Additionally, the sink `events` receives a [`events::PipelineEvent`] with the
+/// elapsed time and key metrics at each stage boundary of the synthesis procedure.
+///
+/// Returns the result of the synthesis procedure.
+pub fn synthesize_with_events<F: FnMut(Status), E: EventSink>(
+    ltl: &str,
+    ins: &[&str],
+    outs: &[&str],
+    options: &SynthesisOptions,
+    mut on_status: F,
+    mut events: E,
+) -> SynthesisResult {
+    synthesize_with_sinks(
+        ltl,
+        ins,
+        outs,
+        options,
+        &mut on_status,
+        None,
+        Some(&mut events),
+    )
+    .unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Searches for a minimal set of assumptions on the environment that makes
+/// an unrealizable LTL specification realizable.
+///
+/// The candidate assumptions are instantiated from the given templates, where
+/// each occurrence of `{}` in a template is replaced by one of the inputs, and
+/// templates without `{}` are used as they are. Candidates that can not be
+/// translated to an automaton are skipped with a warning. Sets of candidates of
+/// increasing size up to `max_assumptions` are added as an assumption `A -> G` to
+/// the specification `G` until it becomes realizable, with the given options
+/// otherwise.
+///
+/// The search is guided by the counter-strategies of the unrealizable sets. A
+/// counter-strategy remains winning for every set of assumptions that is satisfied
+/// by all its runs, so a set is only checked if it contains, for each counter-strategy
+/// found so far, a candidate that is violated by one of its runs.
+///
+/// Returns the assumptions of the first realizable specification, which are empty
+/// if the specification is already realizable, or `None` if no such set is found.
+pub fn weaken_assumptions(
+    ltl: &str,
+    ins: &[&str],
+    outs: &[&str],
+    templates: &[&str],
+    max_assumptions: usize,
+    options: &SynthesisOptions,
+) -> Option<Vec<String>> {
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Hoa,
+        only_realizability: false,
+        ..options.clone()
+    };
+    let vm = shared_vm(options.vm_heap_size);
+    let mut ap = Vec::with_capacity(ins.len() + outs.len());
+    ap.extend_from_slice(ins);
+    ap.extend_from_slice(outs);
+    let mut candidates: Vec<String> = Vec::new();
+    let mut automata = Vec::new();
+    for template in templates {
+        let instances: Vec<String> = if template.contains("{}") {
+            ins.iter()
+                .map(|input| template.replace("{}", input))
+                .collect()
+        } else {
+            vec![template.to_string()]
+        };
+        for candidate in instances {
+            if candidates.contains(&candidate) {
+                continue;
+            }
+            let automaton =
+                owl::formula::Ltl::try_parse(&vm, &candidate, &ap).and_then(|formula| {
+                    owl::automaton::Automaton::try_of(
+                        &vm,
+                        &formula,
+                        options.ltl_simplification == Simplification::Language,
+                        options.lookahead,
+                    )
+                });
+            match automaton {
+                Ok(automaton) => {
+                    candidates.push(candidate);
+                    automata.push(automaton);
+                }
+                Err(err) => warn!("Skipping candidate assumption {}: {}", candidate, err),
+            }
+        }
+    }
+
+    // the candidates violated by some run of each counter-strategy found so far
+    let mut violated: Vec<Vec<bool>> = Vec::new();
+    for size in 0..=max_assumptions.min(candidates.len()) {
+        info!(
+            "Searching for {} assumptions among {} candidates",
+            size,
+            candidates.len()
+        );
+        let mut indices: Vec<usize> = (0..size).collect();
+        loop {
+            if violated.iter().all(|v| indices.iter().any(|&i| v[i])) {
+                let assumptions: Vec<_> = indices.iter().map(|&i| candidates[i].clone()).collect();
+                let weakened = if assumptions.is_empty() {
+                    ltl.to_string()
+                } else {
+                    format!("({}) -> ({})", assumptions.join(") & ("), ltl)
+                };
+                debug!("Checking weakened specification {}", weakened);
+                let result = synthesize_with(&weakened, ins, outs, &options);
+                if result.status() == Status::Realizable {
+                    info!("Found assumptions for realizability: {:?}", assumptions);
+                    return Some(assumptions);
+                }
+                if let Some(Controller::Machine(counter_strategy)) = result.controller() {
+                    let violated_candidates: Vec<bool> = automata
+                        .iter_mut()
+                        .map(|automaton| counter_strategy.violates_assumption(automaton))
+                        .collect();
+                    debug!(
+                        "Counter-strategy with {} states violates {} candidates",
+                        counter_strategy.num_states(),
+                        violated_candidates.iter().filter(|&&v| v).count()
+                    );
+                    violated.push(violated_candidates);
+                }
+            }
+            if !next_combination(&mut indices, candidates.len()) {
+                break;
+            }
+        }
+    }
+    info!("No assumptions for realizability found");
+    None
+}
+
+/// Advances the given sorted indices to the next combination of distinct indices
+/// below `n` in lexicographic order, and returns `false` if there is none.
+fn next_combination(indices: &mut [usize], n: usize) -> bool {
+    let k = indices.len();
+    for i in (0..k).rev() {
+        if indices[i] < n - k + i {
+            let start = indices[i] + 1;
+            for (offset, index) in indices[i..].iter_mut().enumerate() {
+                *index = start + offset;
+            }
+            return true;
+        }
+    }
+    false
+}
+
+/// Converts a controller previously produced by the synthesis procedure into the
+/// output format of the given options, without synthesizing it again.
+///
+/// The controller is either a machine in HOA or KISS2 format or an aiger circuit in ASCII
+/// or binary format. A machine passes through the minimization, determinization, BDD and
+/// aiger stages as configured by the options, where each state is labelled by its
+/// number in the HOA format or by its position in the KISS2 format. An aiger circuit is compressed and either kept as a
+/// circuit or mapped to lookup tables.
+///
+/// If all states of a machine in HOA format are named by structured labels, as written
+/// for machines with structured labels, e.g. after external editing, the labels are kept
+/// as the encoding of the states in the BDD and aiger stages, unless the machine is
+/// minimized.
+///
+/// # Errors
+///
+/// Returns an error if the controller is malformed, if it is a BDD in DOT format,
+/// which does not contain the initial state, or if it can not be converted to the
+/// output format.
+pub fn convert_controller(input: &[u8], options: &SynthesisOptions) -> std::io::Result<Controller> {
+    use std::io::{Error, ErrorKind};
+
+    if options.output_format == OutputFormat::Pg {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "a controller can not be converted to a parity game",
+        ));
+    }
+    let start = input
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(input.len());
+    let text = &input[start..];
+    if text.starts_with(b"HOA:") {
+        let text = std::str::from_utf8(text).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        if options.machine_minimization == MinimizationMethod::None {
+            if let Ok(machine) = text.parse::<LabelledMachine<StructuredLabel>>() {
+                info!(
+                    "Converting machine with {} states and structured labels",
+                    machine.num_states()
+                );
+                return Ok(convert_structured_machine(machine, options));
+            }
+        }
+        let machine: LabelledMachine<usize> = text
+            .parse()
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        info!("Converting machine with {} states", machine.num_states());
+        Ok(convert_machine(machine, options))
+    } else if text.starts_with(b".") || text.starts_with(b"#") {
+        let text = std::str::from_utf8(text).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        let machine = LabelledMachine::<usize>::from_kiss2(text)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        info!(
+            "Converting KISS2 machine with {} states",
+            machine.num_states()
+        );
+        Ok(convert_machine(machine, options))
+    } else if text.starts_with(b"aag") || text.starts_with(b"aig") {
+        if !matches!(
+            options.output_format,
+            OutputFormat::Aag
+                | OutputFormat::Aig
+                | OutputFormat::Blif
+                | OutputFormat::Btor2
+                | OutputFormat::Verilog
+        ) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "an aiger circuit can not be converted to output format {}",
+                    options.output_format
+                ),
+            ));
+        }
+        let mut aig = AigerController::read(text)?;
+        info!("Converting aiger circuit");
+        let deadline = stage_deadline(options.budget_compression);
+        match options.aiger_compression {
+            AigerCompression::Basic => aig.compress(false, false, deadline),
+            AigerCompression::More => aig.compress(true, false, deadline),
+            AigerCompression::Sequential => aig.compress(true, true, deadline),
+            AigerCompression::None => (),
+        };
+        if let Some(max_vars) = options.aiger_exact_synthesis {
+            aig.exact_synthesis(max_vars, deadline);
+        }
+        if options.output_format == OutputFormat::Blif {
+            Ok(Controller::Lut(aig.map_luts(abc::MAX_LUT_SIZE)))
+        } else if options.output_format == OutputFormat::Btor2 {
+            let assumption = options.btor2_assumption.as_ref();
+            Ok(Controller::Btor2(aig.to_btor2(assumption)))
+        } else if options.output_format == OutputFormat::Verilog {
+            Ok(Controller::Verilog(aig.factor()))
+        } else {
+            Ok(Controller::Aiger(aig))
+        }
+    } else if text.starts_with(b"digraph") {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            "a BDD in DOT format does not contain the initial state and can not be converted",
+        ))
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            "controller is neither a machine in HOA format nor an aiger circuit",
+        ))
+    }
+}
+
+/// Synthesizes a controller for a safety monitor given as an aiger circuit in ASCII
+/// or binary format, see [`monitor`] for the interpretation of the circuit.
+///
+/// The safety game is constructed directly over the valuations of the latches of the
+/// monitor and solved with the parity game solver of the options. The callback
+/// `on_status` is invoked as for [`synthesize_with_callback`]. For a realizable monitor,
+/// a machine for the system passes through the minimization, determinization, BDD and
+/// aiger stages as configured by the options, where each state is labelled by its
+/// number in the exploration of the monitor. An aiger circuit is returned as the monitor
+/// with its controllable inputs driven by the controller, as in the safety track of
+/// SYNTCOMP. No controller is constructed for an unrealizable monitor.
+///
+/// # Errors
+///
+/// Returns a [`SynthesisError::InvalidMonitor`] if the circuit can not be read as
+/// a [`monitor::SafetyMonitor`] or its safety game has more than
+/// [`monitor::MAX_MONITOR_MOVES`] moves, and a [`SynthesisError::IncompatibleOptions`] if
+/// a parity game is requested as output format.
+pub fn synthesize_monitor_with_callback<F: FnMut(Status)>(
+    input: &[u8],
+    options: &SynthesisOptions,
+    mut on_status: F,
+) -> Result<SynthesisResult, SynthesisError> {
+    if options.requires_format(OutputFormat::Pg) {
+        return Err(SynthesisError::IncompatibleOptions(
+            "a parity game can not be written for a safety monitor".to_string(),
+        ));
+    }
+    let monitor = monitor::SafetyMonitor::read(input)
+        .map_err(|err| SynthesisError::InvalidMonitor(err.to_string()))?;
+    info!(
+        "Read safety monitor with {} uncontrollable inputs, {} controllable inputs and {} latches",
+        monitor.uncontrollable_inputs().len(),
+        monitor.controllable_inputs().len(),
+        monitor.num_latches()
+    );
+    let safety_game = monitor
+        .explore()
+        .map_err(|err| SynthesisError::InvalidMonitor(err.to_string()))?;
+    let game = safety_game.game();
+    let solution = solve_game(game, options.parity_solver, options.oink_solver.clone());
+    let status = Status::from(
+        solution
+            .winner(game.initial_node())
+            .expect("complete game is solved"),
+    );
+    on_status(status);
+    if status == Status::Unrealizable || options.only_realizability {
+        return Ok(SynthesisResult::only_status(status));
+    }
+
+    let machine = monitor.machine(&safety_game, &solution);
+    info!("Constructed machine with {} states", machine.num_states());
+    let controller = match convert_machine(machine, options) {
+        Controller::Aiger(aiger) => Controller::Aiger(aiger.drive(monitor.circuit())),
+        controller => controller,
+    };
+    Ok(SynthesisResult::with_controller(status, controller))
+}
+
+/// Determinizes the machine with the determinization policy of the options.
+fn determinize_machine<L: Clone>(machine: &mut LabelledMachine<L>, options: &SynthesisOptions) {
+    match options.machine_determinization_policy {
+        DeterminizationPolicy::Frequency => machine.determinize(false),
+        DeterminizationPolicy::LowToggle => machine.determinize(true),
+        DeterminizationPolicy::Random => machine.determinize_randomly(options.determinization_seed),
+    }
+}
+
+/// Passes a machine read from its HOA format through the stages of the synthesis
+/// procedure following the construction of the machine.
+fn convert_machine(mut machine: LabelledMachine<usize>, options: &SynthesisOptions) -> Controller {
+    let compress_features = matches!(
+        options.label_compression,
+        LabelCompression::Features | LabelCompression::Both
+    );
+
+    let minimize = options.machine_minimization != MinimizationMethod::None
+        && !exceeds_minimization_limit(&machine, options);
+    if minimize
+        && matches!(
+            options.machine_minimization,
+            MinimizationMethod::NonDeterminism | MinimizationMethod::Both
+        )
+    {
+        machine = machine.minimize_with_nondeterminism();
+    }
+    let minimize_dontcares = minimize
+        && matches!(
+            options.machine_minimization,
+            MinimizationMethod::DontCares | MinimizationMethod::Both
+        );
+    let incomplete_machine = options
+        .requires_format(OutputFormat::Kiss2)
+        .then(|| machine.with_structured_labels(&mut SimpleLabelling::default()));
+    let mut json_events = create_event_log_sink(options);
+    let mut events = EventLog::new();
+    if let Some(sink) = &mut json_events {
+        events.register(sink);
+    }
+    let structured_machine = if minimize_dontcares {
+        determinize_machine(&mut machine, options);
+        machine
+            .minimize_with_dontcares(
+                compress_features,
+                stage_deadline(options.budget_minimization),
+                options.minimization_max_clauses,
+                &mut |lower, upper| events.emit(PipelineEvent::MinimizationBounds { lower, upper }),
+            )
+            .with_structured_labels(&mut SimpleLabelling::default())
+    } else {
+        // machines need to be deterministic for other output formats
+        if options.machine_determinization || !options.only_machines() {
+            determinize_machine(&mut machine, options);
+        }
+        machine.with_structured_labels(&mut SimpleLabelling::default())
+    };
+
+    // the status is only used for writing parity games
+    let result = construct_result_from_structured_machines(
+        Status::Realizable,
+        vec![(
+            Provenance::new(minimize_dontcares, LabelStructure::None),
+            structured_machine,
+        )],
+        incomplete_machine,
+        options,
+        &mut events,
+    );
+    drop(events);
+    finish_event_log_sink(json_events);
+    result.controller.expect("no controller constructed")
+}
+
+/// Passes a machine with structured labels read from its HOA format through the stages
+/// of the synthesis procedure following the construction of the machine, where the
+/// labels are kept as the encoding of the states.
+fn convert_structured_machine(
+    mut machine: LabelledMachine<StructuredLabel>,
+    options: &SynthesisOptions,
+) -> Controller {
+    let incomplete_machine = options
+        .requires_format(OutputFormat::Kiss2)
+        .then(|| machine.clone());
+    // machines need to be deterministic for other output formats
+    if options.machine_determinization || !options.only_machines() {
+        determinize_machine(&mut machine, options);
+    }
+
+    // the status is only used for writing parity games
+    let mut json_events = create_event_log_sink(options);
+    let mut events = EventLog::new();
+    if let Some(sink) = &mut json_events {
+        events.register(sink);
+    }
+    let result = construct_result_from_structured_machines(
+        Status::Realizable,
+        vec![(Provenance::new(false, LabelStructure::None), machine)],
+        incomplete_machine,
+        options,
+        &mut events,
+    );
+    drop(events);
+    finish_event_log_sink(json_events);
+    result.controller.expect("no controller constructed")
+}
+
+thread_local! {
+    /// The GraalVM shared by the synthesis calls of the current thread, with its heap size.
+    static SHARED_VM: RefCell<Option<(Option<usize>, Rc<owl::graal::Vm>)>> = RefCell::new(None);
+}
+
+/// Returns the GraalVM shared by the synthesis calls of the current thread with the given
+/// maximum heap size in megabytes, which is created on first use or if the heap size changed.
+fn shared_vm(heap_size: Option<usize>) -> Rc<owl::graal::Vm> {
+    shared_vm_with_time(heap_size).0
+}
+
+/// Returns the GraalVM shared by the synthesis calls of the current thread as for
+/// [`shared_vm`], together with the time for creating it, or `None` if an existing
+/// instance is reused.
+fn shared_vm_with_time(heap_size: Option<usize>) -> (Rc<owl::graal::Vm>, Option<Duration>) {
+    SHARED_VM.with(|shared| {
+        let mut shared = shared.borrow_mut();
+        if let Some((size, vm)) = shared.as_ref() {
+            if *size == heap_size {
+                return (Rc::clone(vm), None);
+            }
+        }
+        // tear down the previous instance before creating a new one
+        *shared = None;
+        let start = Instant::now();
+        let vm = Rc::new(
+            match heap_size {
+                Some(megabytes) => owl::graal::Vm::with_heap_size(megabytes << 20),
+                None => owl::graal::Vm::new(),
+            }
+            .unwrap(),
+        );
+        let time = start.elapsed();
+        info!("Initialized GraalVM in {:.3}s", time.as_secs_f64());
+        *shared = Some((heap_size, Rc::clone(&vm)));
+        (vm, Some(time))
+    })
+}
+
+/// Releases the GraalVM shared by the synthesis calls of the current thread.
+///
+/// The GraalVM is created by the first synthesis call of a thread and afterwards
+/// reused until the thread exits, as its startup dominates the runtime for small
+/// specifications. This function allows to release its resources earlier, after
+/// which the next synthesis call creates a new instance.
+pub fn release_shared_vm() {
+    SHARED_VM.with(|shared| *shared.borrow_mut() = None);
+}
+
+fn synthesize_with_sinks(
+    ltl: &str,
+    ins: &[&str],
+    outs: &[&str],
+    options: &SynthesisOptions,
+    on_status: &mut dyn FnMut(Status),
+    telemetry: Option<&mut dyn TelemetrySink>,
+    events: Option<&mut dyn EventSink>,
+) -> Result<SynthesisResult, SynthesisError> {
+    let mut json_events = create_event_log_sink(options);
+    let mut event_log = EventLog::new();
+    if let Some(sink) = &mut json_events {
+        event_log.register(sink);
+    }
+    if let Some(sink) = events {
+        event_log.register(sink);
+    }
+
+    // the GraalVM is only started once the sinks for the events have been set up,
+    // as the undeclared propositions are obtained from the formula parsed by Owl
+    let (vm, vm_time) = shared_vm_with_time(options.vm_heap_size);
+    event_log.emit(PipelineEvent::VmInitialized {
+        millis: vm_time.map_or(0, |time| time.as_millis() as u64),
+        reused: vm_time.is_none(),
+    });
+    let undeclared = undeclared_propositions(&vm, ltl, ins, outs).map_err(|cause| {
+        SynthesisError::Translation(TranslationError {
+            cause,
+            subformula: None,
+        })
+    })?;
+    let mut declared_ins = ins.to_vec();
+    let mut declared_outs = outs.to_vec();
+    if !undeclared.is_empty() {
+        let (declared, kind) = match options.undeclared {
+            Undeclared::Error => return Err(SynthesisError::UndeclaredPropositions(undeclared)),
+            Undeclared::Inputs => (&mut declared_ins, "inputs"),
+            Undeclared::Outputs => (&mut declared_outs, "outputs"),
+        };
+        declared.extend(undeclared.iter().map(String::as_str));
+        warn!(
+            "Atomic propositions {} of the formula are not declared and added to the {}",
+            undeclared.join(", "),
+            kind
+        );
+        event_log.emit(PipelineEvent::UndeclaredPropositions {
+            propositions: undeclared.clone(),
+            inputs: options.undeclared == Undeclared::Inputs,
+        });
+    }
+    let (ins, outs) = (declared_ins.as_slice(), declared_outs.as_slice());
+    let class = temporal_class(ltl);
+    if let Some(class) = class {
+        info!(
+            "Formula is in the {} class of the temporal hierarchy",
+            class
+        );
+        event_log.emit(PipelineEvent::FormulaClassified { class });
+    }
+
     let num_inputs = ins.len();
     let num_outputs = outs.len();
 
@@ -97,8 +845,32 @@ pub fn synthesize_with(
     ap.extend_from_slice(ins);
     ap.extend_from_slice(outs);
 
-    let vm = owl::graal::Vm::new().unwrap();
-    let mut formula = owl::formula::Ltl::parse(&vm, ltl, &ap);
+    let fair_inputs: Vec<_> = options
+        .fair_inputs
+        .iter()
+        .flat_map(Fairness::inputs)
+        .collect();
+    if let Some(a) = fair_inputs.iter().find(|a| !ins.contains(*a)) {
+        return Err(SynthesisError::UnknownFairInput(a.to_string()));
+    }
+    if let Some(controller) = &options.repair_controller {
+        check_repair_interface(controller, ins, outs).map_err(SynthesisError::RepairInterface)?;
+    }
+    let fair_ltl;
+    let ltl = if options.fair_inputs.is_empty() {
+        ltl
+    } else {
+        fair_ltl = with_fairness(ltl, &options.fair_inputs);
+        debug!("Formula with fairness assumptions: {}", fair_ltl);
+        fair_ltl.as_str()
+    };
+
+    let mut formula = owl::formula::Ltl::try_parse(&vm, ltl, &ap).map_err(|cause| {
+        SynthesisError::Translation(TranslationError {
+            cause,
+            subformula: None,
+        })
+    })?;
     debug!("Parsed formula: {}", formula);
     let statuses = if options.ltl_simplification == Simplification::Realizability {
         info!("Applying realizability simplifications");
@@ -108,6 +880,13 @@ pub fn synthesize_with(
     };
     debug!("Simplified formula: {}", formula);
     for (&status, &a) in statuses.iter().zip(ap.iter()) {
+        if fair_inputs.contains(&a) && status != AtomicPropositionStatus::Used {
+            warn!(
+                "Fairness assumption on input {} has no effect on realizability",
+                a
+            );
+            continue;
+        }
         match status {
             AtomicPropositionStatus::Unused => {
                 warn!("Atomic proposition {} not used in formula", a)
@@ -124,38 +903,409 @@ pub fn synthesize_with(
         }
     }
     info!("Creating automaton");
-    let automaton = owl::automaton::Automaton::of(
+    let automaton = owl::automaton::Automaton::try_of(
         &vm,
         &formula,
         options.ltl_simplification == Simplification::Language,
         options.lookahead,
-    );
+    )
+    .map_err(|cause| {
+        warn!("Owl failed to translate formula: {}", cause);
+        SynthesisError::Translation(TranslationError {
+            cause,
+            subformula: failing_conjunct(&vm, ltl, &ap, options),
+        })
+    })?;
     info!("Finished creating automaton");
+    event_log.emit(PipelineEvent::AutomatonCreated);
 
-    let automaton_spec = AutomatonSpecification::new(automaton, ins, outs, statuses);
-    match options.exploration_strategy {
-        ExplorationStrategy::Bfs => {
-            explore_with(BfsQueue::with_capacity(4096), automaton_spec, options)
-        }
-        ExplorationStrategy::Dfs => {
-            explore_with(DfsQueue::with_capacity(4096), automaton_spec, options)
+    let mut automaton_spec = AutomatonSpecification::new(automaton, ins, outs, statuses.clone());
+    if !options.initial_states.is_empty() {
+        automaton_spec = automaton_spec.with_initial_states(&options.initial_states);
+    }
+    if options.assumption_dontcares && !options.only_realizability {
+        if let Some(assumption) = split_assumption(ltl) {
+            let translation_error = |cause| {
+                SynthesisError::Translation(TranslationError {
+                    cause,
+                    subformula: Some(assumption.to_string()),
+                })
+            };
+            let assumption_formula =
+                owl::formula::Ltl::try_parse(&vm, &assumption, &ap).map_err(translation_error)?;
+            debug!("Parsed assumption: {}", assumption_formula);
+            info!("Creating automaton for assumption");
+            let assumption_automaton = owl::automaton::Automaton::try_of(
+                &vm,
+                &assumption_formula,
+                options.ltl_simplification == Simplification::Language,
+                options.lookahead,
+            )
+            .map_err(translation_error)?;
+            automaton_spec = automaton_spec.with_assumption(assumption_automaton);
+        } else {
+            warn!("Formula has no assumption at the top level, no don't cares are added");
         }
+    }
+    let result = match options.exploration_strategy {
+        ExplorationStrategy::Bfs => explore_with(
+            BfsQueue::with_capacity(4096),
+            automaton_spec,
+            class,
+            options,
+            on_status,
+            telemetry,
+            &mut event_log,
+        ),
+        ExplorationStrategy::Dfs => explore_with(
+            DfsQueue::with_capacity(4096),
+            automaton_spec,
+            class,
+            options,
+            on_status,
+            telemetry,
+            &mut event_log,
+        ),
         ExplorationStrategy::Min => explore_with(
             MinMaxQueue::with_capacity(4096, MinMaxMode::Min),
             automaton_spec,
+            class,
             options,
+            on_status,
+            telemetry,
+            &mut event_log,
         ),
         ExplorationStrategy::Max => explore_with(
             MinMaxQueue::with_capacity(4096, MinMaxMode::Max),
             automaton_spec,
+            class,
             options,
+            on_status,
+            telemetry,
+            &mut event_log,
         ),
         ExplorationStrategy::MinMax => explore_with(
             MinMaxQueue::with_capacity(4096, MinMaxMode::MinMax),
             automaton_spec,
+            class,
+            options,
+            on_status,
+            telemetry,
+            &mut event_log,
+        ),
+        ExplorationStrategy::Bdfs(depth) => explore_with(
+            BdfsQueue::with_capacity(4096, depth),
+            automaton_spec,
+            class,
+            options,
+            on_status,
+            telemetry,
+            &mut event_log,
+        ),
+        ExplorationStrategy::Sim(_) => explore_with(
+            PriorityQueue::with_capacity(4096),
+            automaton_spec,
+            class,
+            options,
+            on_status,
+            telemetry,
+            &mut event_log,
+        ),
+    };
+    let mut result = match options.cross_check {
+        // the automaton of the cross-check starts in its own initial state
+        Some(_) if !options.initial_states.is_empty() => {
+            warn!("Cross-check is skipped as it does not support initial states");
+            result
+        }
+        Some(max_states) => {
+            let check = cross_check(&vm, &formula, num_inputs, num_outputs, max_states, options);
+            let check = CrossCheck::compare(result.status(), check);
+            match check {
+                CrossCheck::Confirmed(states) => info!(
+                    "Bounded synthesis confirmed status {:?} with {} states",
+                    result.status(),
+                    states
+                ),
+                CrossCheck::Contradicted(states) => warn!(
+                    "Bounded synthesis contradicted status {:?} with {} states",
+                    result.status(),
+                    states
+                ),
+                CrossCheck::Inconclusive => info!(
+                    "Bounded synthesis with at most {} states was inconclusive",
+                    max_states
+                ),
+            }
+            result.with_cross_check(Some(check))
+        }
+        None => result,
+    };
+    if options.glitch_window.is_some() && !options.initial_states.is_empty() {
+        // the automaton of the analysis starts in its own initial state
+        warn!("Robustness to glitches is not analyzed as it does not support initial states");
+    } else if let Some(window) = options.glitch_window {
+        result = analyze_robustness(
+            &vm,
+            ltl,
+            &ap,
+            num_inputs,
+            window,
+            result,
             options,
+            &mut event_log,
+        );
+        if let Some(robustness) = result.robustness() {
+            event_log.emit(PipelineEvent::RobustnessAnalyzed { window, robustness });
+        }
+    }
+    if let Some(interface) = &mut result.interface {
+        interface.set_statuses(&ap, &statuses);
+        if let Some(path) = &options.interface_report {
+            if let Err(err) = fs::write(path, interface.to_string()) {
+                warn!("Could not write interface report to {}: {}", path, err);
+            }
+        }
+    }
+    event_log.emit(PipelineEvent::Finished {
+        status: result.status(),
+    });
+    drop(event_log);
+    finish_event_log_sink(json_events);
+    Ok(result)
+}
+
+type EventLogSink = JsonLinesSink<std::io::BufWriter<fs::File>>;
+
+/// Creates the sink writing the events to the event log given in the options, if any.
+fn create_event_log_sink(options: &SynthesisOptions) -> Option<EventLogSink> {
+    options.event_log.as_ref().and_then(|path| {
+        fs::File::create(path)
+            .map(|file| JsonLinesSink::new(std::io::BufWriter::new(file)))
+            .map_err(|err| warn!("Could not write event log to {}: {}", path, err))
+            .ok()
+    })
+}
+
+/// Flushes the sink for the event log, warning about errors while writing the events.
+fn finish_event_log_sink(sink: Option<EventLogSink>) {
+    if let Some(Err(err)) = sink.map(JsonLinesSink::finish) {
+        warn!("Could not write event log: {}", err);
+    }
+}
+
+/// Returns the atomic propositions of the formula parsed by Owl that are neither inputs
+/// nor outputs, in the order of their first occurrence.
+///
+/// As Owl only parses formulas over declared propositions, the candidates are the
+/// propositions read by the syntax tree of the formula, and only the candidates that
+/// are used in the formula parsed by Owl with all candidates declared are returned.
+///
+/// # Errors
+///
+/// Returns an error if Owl can not parse the formula with the inputs, outputs and
+/// candidates as propositions.
+fn undeclared_propositions(
+    vm: &owl::graal::Vm,
+    ltl: &str,
+    ins: &[&str],
+    outs: &[&str],
+) -> Result<Vec<String>, OwlError> {
+    let syntax = ltl.parse::<crate::ltl::Ltl>();
+    let mut ap: Vec<&str> = ins.iter().chain(outs).copied().collect();
+    let num_declared = ap.len();
+    if let Ok(formula) = &syntax {
+        for a in formula.propositions() {
+            if !ap.contains(&a) {
+                ap.push(a);
+            }
+        }
+    }
+    if ap.len() == num_declared {
+        return Ok(Vec::new());
+    }
+    let mut formula = owl::formula::Ltl::try_parse(vm, ltl, &ap)?;
+    let statuses = formula.simplify(ins.len(), ap.len() - ins.len());
+    Ok(ap[num_declared..]
+        .iter()
+        .zip(&statuses[num_declared..])
+        .filter(|&(_, &status)| status != AtomicPropositionStatus::Unused)
+        .map(|(&a, _)| a.to_owned())
+        .collect())
+}
+
+/// Returns the class of the formula in the temporal hierarchy, or `None` if the formula
+/// can not be parsed.
+fn temporal_class(ltl: &str) -> Option<TemporalClass> {
+    ltl.parse::<crate::ltl::Ltl>()
+        .ok()
+        .map(|formula| TemporalClass::of(&formula))
+}
+
+/// Returns the first top-level conjunct of the formula that can not be parsed or
+/// translated to an automaton on its own, if the formula has several conjuncts.
+fn failing_conjunct(
+    vm: &owl::graal::Vm,
+    ltl: &str,
+    ap: &[&str],
+    options: &SynthesisOptions,
+) -> Option<String> {
+    let formula = ltl.parse::<crate::ltl::Ltl>().ok()?;
+    let conjuncts = formula.conjuncts();
+    if conjuncts.len() <= 1 {
+        return None;
+    }
+    info!("Translating conjuncts of formula separately to locate failure");
+    conjuncts
+        .iter()
+        .map(|conjunct| conjunct.to_string())
+        .find(|conjunct| {
+            owl::formula::Ltl::try_parse(vm, conjunct, ap)
+                .and_then(|formula| {
+                    owl::automaton::Automaton::try_of(
+                        vm,
+                        &formula,
+                        options.ltl_simplification == Simplification::Language,
+                        options.lookahead,
+                    )
+                    .map(|_| ())
+                })
+                .is_err()
+        })
+}
+
+/// Checks the realizability of the formula by bounded synthesis with at most the given
+/// number of states, on a separate automaton for the formula.
+///
+/// Returns the status shown by a machine or counter-strategy together with its number
+/// of states, or `None` if the formula is too large or the check is inconclusive.
+fn cross_check(
+    vm: &owl::graal::Vm,
+    formula: &owl::formula::Ltl,
+    num_inputs: usize,
+    num_outputs: usize,
+    max_states: usize,
+    options: &SynthesisOptions,
+) -> Option<(Status, usize)> {
+    info!("Cross-checking status by bounded synthesis");
+    let mut automaton = owl::automaton::Automaton::try_of(
+        vm,
+        formula,
+        options.ltl_simplification == Simplification::Language,
+        options.lookahead,
+    )
+    .map_err(|err| warn!("Could not create automaton for cross-check: {}", err))
+    .ok()?;
+    crosscheck::check_realizability(&mut automaton, num_inputs, num_outputs, max_states)
+}
+
+/// The maximal number of states of a machine synthesized to be robust to glitches of
+/// the inputs, as requested with option `--glitch-resynthesis`.
+const MAX_ROBUST_STATES: usize = 4;
+
+/// Analyzes the robustness of the controller of the result to glitches of the inputs
+/// with the given window, on a separate automaton for the formula, and replaces a
+/// controller that is not robust by a robust machine if requested with
+/// [`SynthesisOptions::glitch_resynthesis`].
+///
+/// The propositions are the inputs followed by the outputs. The result is returned
+/// unchanged if the specification is not realizable.
+fn analyze_robustness(
+    vm: &owl::graal::Vm,
+    ltl: &str,
+    ap: &[&str],
+    num_inputs: usize,
+    window: usize,
+    result: SynthesisResult,
+    options: &SynthesisOptions,
+    events: &mut EventLog<'_>,
+) -> SynthesisResult {
+    if result.status() != Status::Realizable {
+        info!("Specification is unrealizable, robustness to glitches is not analyzed");
+        return result;
+    }
+    info!("Analyzing robustness to glitches of the inputs");
+    let (ins, outs) = ap.split_at(num_inputs);
+    let machine = match &result.controller {
+        Some(Controller::Machine(machine)) => machine.explicit_transitions(ins, outs),
+        Some(Controller::Aiger(aiger)) => aiger.explicit_transitions(ins, outs),
+        _ => None,
+    };
+    let (robustness, robust) = match machine {
+        Some(machine) => {
+            // the original formula, as the controller also has to handle removed propositions
+            let automaton = owl::formula::Ltl::try_parse(vm, ltl, ap)
+                .and_then(|formula| {
+                    owl::automaton::Automaton::try_of(
+                        vm,
+                        &formula,
+                        options.ltl_simplification == Simplification::Language,
+                        options.lookahead,
+                    )
+                })
+                .map_err(|err| {
+                    warn!(
+                        "Could not create automaton for robustness analysis: {}",
+                        err
+                    )
+                });
+            match automaton {
+                Ok(mut automaton) => crosscheck::analyze_robustness(
+                    &mut automaton,
+                    ins.len(),
+                    outs.len(),
+                    &machine,
+                    window,
+                    options.glitch_resynthesis.then(|| MAX_ROBUST_STATES),
+                ),
+                Err(()) => (Robustness::Unknown, None),
+            }
+        }
+        None => (Robustness::Unknown, None),
+    };
+    match robustness {
+        Robustness::Robust => info!(
+            "Controller is robust to a glitch in every window of {} steps",
+            window
+        ),
+        Robustness::Violated => warn!(
+            "Controller is not robust to a glitch in every window of {} steps",
+            window
+        ),
+        Robustness::Resynthesized(states) => warn!(
+            "Controller is not robust to a glitch in every window of {} steps, \
+             replaced by robust machine with {} states",
+            window, states
+        ),
+        Robustness::Unknown => warn!(
+            "Robustness to glitches can only be analyzed for Mealy machines and aiger \
+             circuits of small specifications"
         ),
     }
+    let result = match robust {
+        Some(table) => {
+            let to_strings = |names: &[&str]| names.iter().map(|s| s.to_string()).collect();
+            let machine = LabelledMachine::from_explicit_transitions(
+                &table,
+                to_strings(ins),
+                to_strings(outs),
+            );
+            construct_result_from_structured_machines(
+                result.status,
+                vec![(
+                    Provenance::new(false, LabelStructure::None),
+                    machine.with_structured_labels(&mut SimpleLabelling::default()),
+                )],
+                None,
+                options,
+                events,
+            )
+            .with_solution(result.solution)
+            .with_cross_check(result.cross_check)
+        }
+        None => result,
+    };
+    result.with_robustness(Some(robustness))
 }
 
 /// A controller for a specification.
@@ -166,13 +1316,27 @@ pub enum Controller {
     /// This is not an actual controller, but the template for a controller. The labels
     /// of the nodes of the parity game refer to the indices of nodes in edge trees for
     /// states of the automaton from which the game was constructed.
+    ///
+    /// The game may only be partially explored, where unexplored nodes are in the
+    /// [border](parity::game::Game::border) of the game. See
+    /// [`LabelledGame::write_with_winner`] for how the border is completed when
+    /// writing the game.
     ParityGame(LabelledGame<AutomatonTreeLabel>),
     /// A controller in form of a Mealy or Moore machine for the specification or its negation.
     Machine(LabelledMachine<StructuredLabel>),
+    /// A controller in form of an incompletely specified machine before determinization,
+    /// which is written in KISS2 format with its don't care outputs.
+    IncompleteMachine(LabelledMachine<StructuredLabel>),
     /// A controller in form of a BDD.
     Bdd(BddController),
     /// A controller in form of an aiger circuit.
     Aiger(AigerController),
+    /// A controller in form of a network of lookup tables.
+    Lut(LutController),
+    /// A controller in form of a sequential model in BTOR2 format.
+    Btor2(Btor2Controller),
+    /// A controller in form of a hierarchical netlist written as Verilog modules.
+    Verilog(HierarchicalController),
 }
 
 impl Controller {
@@ -180,21 +1344,50 @@ impl Controller {
     /// The given status is used for completing the border if the controller is a parity game.
     /// The binary flag is used to control the output if the controller is an aiger circuit.
     ///
+    /// The controller is streamed to the writer through a buffer, without first
+    /// constructing its complete representation in memory.
+    ///
     /// # Errors
     ///
     /// Returns an error if an I/O error occurs during the write operation.
     pub fn write<W: std::io::Write>(
         &self,
-        mut writer: W,
+        writer: W,
         status: Status,
         binary: bool,
     ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(ProgressWriter::new(writer));
         match self {
-            Self::ParityGame(game) => game.write_with_winner(writer, Player::from(status)),
+            Self::ParityGame(game) => game.write_with_winner(&mut writer, Player::from(status)),
             Self::Machine(machine) => write!(writer, "{}", machine),
-            Self::Bdd(bdd) => write!(writer, "{}", bdd),
-            Self::Aiger(aiger) => aiger.write(writer, binary),
-        }
+            Self::IncompleteMachine(machine) => machine.write_kiss2(&mut writer),
+            Self::Bdd(bdd) => bdd.write(&mut writer),
+            Self::Aiger(aiger) => aiger.write(&mut writer, binary),
+            Self::Lut(luts) => luts.write(&mut writer),
+            Self::Btor2(model) => model.write(&mut writer),
+            Self::Verilog(hierarchy) => hierarchy.write(&mut writer),
+        }?;
+        writer.flush()
+    }
+}
+
+/// A controller for an additional output of the synthesis procedure.
+pub struct Artifact {
+    target: OutputTarget,
+    controller: Controller,
+}
+
+impl Artifact {
+    /// Returns the output for which the controller was constructed.
+    pub fn target(&self) -> &OutputTarget {
+        &self.target
+    }
+
+    /// Returns the controller in the format of the output.
+    pub fn controller(&self) -> &Controller {
+        &self.controller
     }
 }
 
@@ -204,6 +1397,23 @@ pub struct SynthesisResult {
     status: Status,
     /// A controller for the specification, if a controller has been produced.
     controller: Option<Controller>,
+    /// The winning regions and strategies of both players in the explored game,
+    /// if requested with option `--strategies-output`.
+    solution: Option<GameSolution>,
+    /// The Pareto frontier of the candidate aiger circuits, if requested with
+    /// [`SynthesisOptions::pareto_metrics`].
+    pareto_frontier: Option<Vec<Candidate>>,
+    /// The controllers for the additional outputs in [`SynthesisOptions::outputs`].
+    artifacts: Vec<Artifact>,
+    /// The outcome of cross-checking the status by bounded synthesis, if requested with
+    /// [`SynthesisOptions::cross_check`].
+    cross_check: Option<CrossCheck>,
+    /// The outcome of analyzing the robustness of the controller to glitches of the
+    /// inputs, if requested with [`SynthesisOptions::glitch_window`].
+    robustness: Option<Robustness>,
+    /// The report on the inputs and outputs needed by the aiger circuit, if requested with
+    /// [`SynthesisOptions::interface_report`] or [`SynthesisOptions::reduce_interface`].
+    interface: Option<InterfaceReport>,
 }
 
 impl SynthesisResult {
@@ -218,52 +1428,238 @@ impl SynthesisResult {
         &self.controller
     }
 
+    /// Returns the winning regions and strategies of both players in the explored game,
+    /// if they have been requested with [`SynthesisOptions::strategies_output`].
+    ///
+    /// The node indices refer to the nodes of the game as written with output format
+    /// `pg`. Nodes in the border or not yet decided by the solver are undecided.
+    pub fn solution(&self) -> Option<&GameSolution> {
+        self.solution.as_ref()
+    }
+
+    /// Returns the candidate aiger circuits that are Pareto-optimal for the metrics given
+    /// with [`SynthesisOptions::pareto_metrics`], together with their metrics and provenance.
+    ///
+    /// The controller of the result is one of these candidates, selected by the objective
+    /// [`SynthesisOptions::aiger_objective`], unless compressed further or mapped to
+    /// lookup tables afterwards.
+    pub fn pareto_frontier(&self) -> Option<&[Candidate]> {
+        self.pareto_frontier.as_deref()
+    }
+
+    /// Returns the controllers for the additional outputs given with
+    /// [`SynthesisOptions::outputs`], in the same order as the outputs.
+    ///
+    /// The controllers are constructed from the same machine, BDD and aiger circuit
+    /// as the controller of the result, and are empty if no controller has been produced.
+    pub fn artifacts(&self) -> &[Artifact] {
+        &self.artifacts
+    }
+
+    /// Returns the outcome of cross-checking the realizability status by bounded
+    /// synthesis, if it has been requested with [`SynthesisOptions::cross_check`].
+    pub fn cross_check(&self) -> Option<CrossCheck> {
+        self.cross_check
+    }
+
+    /// Returns the outcome of analyzing the robustness of the controller to glitches of
+    /// the inputs, if it has been requested with [`SynthesisOptions::glitch_window`].
+    ///
+    /// If the controller has been replaced by a robust machine, see
+    /// [`SynthesisOptions::glitch_resynthesis`], the controller of this result and its
+    /// additional outputs are constructed from that machine.
+    pub fn robustness(&self) -> Option<Robustness> {
+        self.robustness
+    }
+
+    /// Returns the report on the inputs and outputs of the specification needed by the
+    /// aiger circuit, if it has been requested with [`SynthesisOptions::interface_report`]
+    /// or [`SynthesisOptions::reduce_interface`].
+    ///
+    /// With [`SynthesisOptions::reduce_interface`], the report maps the inputs and
+    /// outputs of the reduced circuit to those of the specification.
+    pub fn interface(&self) -> Option<&InterfaceReport> {
+        self.interface.as_ref()
+    }
+
     fn only_status(status: Status) -> Self {
         Self {
             status,
             controller: None,
+            solution: None,
+            pareto_frontier: None,
+            artifacts: Vec::new(),
+            cross_check: None,
+            robustness: None,
+            interface: None,
         }
     }
     fn with_game(status: Status, game: LabelledGame<AutomatonTreeLabel>) -> Self {
         Self {
             status,
             controller: Some(Controller::ParityGame(game)),
+            solution: None,
+            pareto_frontier: None,
+            artifacts: Vec::new(),
+            cross_check: None,
+            robustness: None,
+            interface: None,
         }
     }
-    fn with_machine(status: Status, machine: LabelledMachine<StructuredLabel>) -> Self {
+    fn with_controller(status: Status, controller: Controller) -> Self {
         Self {
             status,
-            controller: Some(Controller::Machine(machine)),
+            controller: Some(controller),
+            solution: None,
+            pareto_frontier: None,
+            artifacts: Vec::new(),
+            cross_check: None,
+            robustness: None,
+            interface: None,
         }
     }
-    fn with_bdd(status: Status, bdd: BddController) -> Self {
-        Self {
-            status,
-            controller: Some(Controller::Bdd(bdd)),
+
+    fn with_solution(mut self, solution: Option<GameSolution>) -> Self {
+        self.solution = solution;
+        self
+    }
+
+    fn with_pareto_frontier(mut self, frontier: Option<Vec<Candidate>>) -> Self {
+        self.pareto_frontier = frontier;
+        self
+    }
+
+    fn with_artifacts(mut self, artifacts: Vec<Artifact>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    fn with_cross_check(mut self, cross_check: Option<CrossCheck>) -> Self {
+        self.cross_check = cross_check;
+        self
+    }
+
+    fn with_robustness(mut self, robustness: Option<Robustness>) -> Self {
+        self.robustness = robustness;
+        self
+    }
+
+    fn with_interface(mut self, interface: Option<InterfaceReport>) -> Self {
+        self.interface = interface;
+        self
+    }
+}
+
+/// Returns the assumption `A` of an LTL formula of the form `A -> G`, or `None` if the
+/// formula has another form or can not be parsed.
+///
+/// The assumption is taken from the syntax tree of the formula, whose parser agrees
+/// with the parser of Owl on the precedence and associativity of the operators.
+fn split_assumption(ltl: &str) -> Option<String> {
+    match ltl.parse::<crate::ltl::Ltl>().ok()? {
+        crate::ltl::Ltl::Binary(crate::ltl::BinaryOp::Implies, assumption, _) => {
+            Some(assumption.to_string())
         }
+        _ => None,
     }
-    fn with_aiger(status: Status, aiger: AigerController) -> Self {
-        Self {
-            status,
-            controller: Some(Controller::Aiger(aiger)),
+}
+
+/// Adds the fairness assumptions to the assumption of an LTL formula of the form
+/// `A -> G`, or as the assumption of any other formula.
+///
+/// The formula is split with the same parser as for [`split_assumption`], and taken
+/// as a whole if it can not be parsed, such that the error is reported by Owl.
+fn with_fairness(ltl: &str, fairness: &[Fairness]) -> String {
+    use crate::ltl::{BinaryOp, Ltl};
+    let and = |f1, f2| Ltl::Binary(BinaryOp::And, Box::new(f1), Box::new(f2));
+    let implies = |f1, f2| Ltl::Binary(BinaryOp::Implies, Box::new(f1), Box::new(f2));
+    let fairness = fairness
+        .iter()
+        .map(|f| {
+            f.formula()
+                .parse::<Ltl>()
+                .expect("fairness assumption is a valid formula")
+        })
+        .fold(None, |conjunction, f| {
+            Some(match conjunction {
+                Some(conjunction) => and(conjunction, f),
+                None => f,
+            })
+        })
+        .unwrap_or(Ltl::Const(true));
+    match ltl.parse::<Ltl>() {
+        Ok(Ltl::Binary(BinaryOp::Implies, assumption, guarantee)) => {
+            implies(and(fairness, *assumption), *guarantee).to_string()
         }
+        Ok(formula) => implies(fairness, formula).to_string(),
+        Err(_) => format!("({}) -> ({})", fairness, ltl),
     }
 }
 
+/// Returns the deadline for a stage with the given time budget in seconds starting now.
+fn stage_deadline(budget: Option<u64>) -> Option<Instant> {
+    budget.map(|seconds| Instant::now() + Duration::from_secs(seconds))
+}
+
+/// Checks whether the given deadline of a stage has passed.
+fn deadline_passed(deadline: Option<Instant>) -> bool {
+    deadline.map_or(false, |d| Instant::now() >= d)
+}
+
 fn explore_with<A: MaxEvenDpa, Q: ExplorationQueue<NodeIndex, A::EdgeLabel>>(
     queue: Q,
     automaton_spec: AutomatonSpecification<A>,
+    class: Option<TemporalClass>,
     options: &SynthesisOptions,
+    on_status: &mut dyn FnMut(Status),
+    telemetry: Option<&mut dyn TelemetrySink>,
+    events: &mut EventLog<'_>,
 ) -> SynthesisResult
 where
     A::EdgeLabel: Clone + Eq + Ord,
 {
-    let constructor = GameConstructor::new(automaton_spec, queue);
+    let mut constructor = GameConstructor::new(automaton_spec, queue);
+    if let ExplorationStrategy::Sim(runs) = options.exploration_strategy {
+        constructor.simulate(runs);
+    }
+
+    // games of safety formulas are solved by an attractor computation, as long as
+    // the game has the form of a safety game
+    let safety = class == Some(TemporalClass::Safety);
 
     match options.parity_solver {
-        Solver::Fpi => solve_with(constructor, FpiSolver::new(), options),
-        Solver::Zlk => solve_with(constructor, ZlkSolver::new(), options),
-        Solver::Si => solve_with(constructor, SiSolver::new(), options),
+        Solver::Fpi => solve_with(
+            constructor,
+            SafetySolver::new(FpiSolver::new(), safety),
+            options,
+            on_status,
+            telemetry,
+            events,
+        ),
+        Solver::Zlk => solve_with(
+            constructor,
+            SafetySolver::new(ZlkSolver::new(), safety),
+            options,
+            on_status,
+            telemetry,
+            events,
+        ),
+        Solver::Si => solve_with(
+            constructor,
+            SafetySolver::new(SiSolver::new(), safety),
+            options,
+            on_status,
+            telemetry,
+            events,
+        ),
+        Solver::Oink => solve_with(
+            constructor,
+            SafetySolver::new(OinkSolver::new(options.oink_solver.clone()), safety),
+            options,
+            on_status,
+            telemetry,
+            events,
+        ),
     }
 }
 
@@ -271,6 +1667,9 @@ fn solve_with<A: MaxEvenDpa, Q: ExplorationQueue<NodeIndex, A::EdgeLabel>, S: Pa
     mut constructor: GameConstructor<A, Q>,
     solver: S,
     options: &SynthesisOptions,
+    on_status: &mut dyn FnMut(Status),
+    telemetry: Option<&mut dyn TelemetrySink>,
+    events: &mut EventLog<'_>,
 ) -> SynthesisResult
 where
     A::EdgeLabel: Clone + Eq + Ord,
@@ -285,19 +1684,112 @@ where
         OnTheFlyLimit::TimeMultiple(_) => ExplorationLimit::Time(Duration::from_secs(0)),
     };
 
+    let deadline = stage_deadline(options.budget_solving);
     let mut incremental_solver = IncrementalSolver::new(solver);
+    incremental_solver.restrict_to_reachable(options.solve_reachable);
+    // the full winning regions are only needed for the strategies of both players and
+    // for strategies independent of the border, which may require solving further nodes
+    incremental_solver.stop_at_decided_initial(
+        options.strategies_output.is_none() && !options.exploration_border_free,
+    );
+    incremental_solver.enable_preprocessing(options.game_preprocessing);
+    incremental_solver.enable_merging(options.merge_equivalent);
+    if let Some(hints) = &options.winning_hints {
+        incremental_solver.set_hints(hints.clone());
+    }
+    if let Some(sink) = telemetry {
+        incremental_solver.register_telemetry(sink);
+    }
+    let mut abstraction = options.abstraction_refinement.then(Abstraction::new);
+    let mut status_reported = false;
+    // first explore the game up to increasing depths, where unexplored nodes are won by the system
+    let mut depth_bound = options.depth_bounded_exploration.map(|_| 1);
+    constructor.set_depth_bound(depth_bound);
     loop {
-        constructor.explore(limit);
+        if let Some(depth) = depth_bound {
+            debug!("Exploring game up to depth {}", depth);
+            constructor.explore(ExplorationLimit::None);
+        } else {
+            constructor.explore(limit);
+        }
         let game = constructor.get_game();
+        if let Some(abstraction) = &mut abstraction {
+            constructor.assign_abstraction(abstraction);
+            incremental_solver.solve_abstraction(game, abstraction);
+        }
         let result = incremental_solver.solve(game);
         let construction_stats = constructor.stats();
         let solver_stats = incremental_solver.stats();
 
         trace!("Stats: {}; {}", construction_stats, solver_stats);
+        trace!("Solver telemetry: {}", incremental_solver.telemetry());
+        if let Some(stats) = incremental_solver.preprocessing_stats() {
+            trace!("Preprocessing: {}", stats);
+        }
+        if let Some(stats) = incremental_solver.merging_stats() {
+            trace!("Merging: {}", stats);
+        }
+        if let Some(abstraction) = &abstraction {
+            trace!("Abstraction: {}", abstraction.stats());
+        }
+        if let Some(stats) = incremental_solver.hint_stats() {
+            trace!("Hints: {}", stats);
+        }
 
         if let Some(winner) = result {
-            info!("Game solved, winner is {}", winner);
-            return construct_result(winner, constructor, incremental_solver, options);
+            if let (Some(depth), Player::Odd) = (depth_bound, winner) {
+                info!(
+                    "Unrealizability shown on game explored up to depth {}",
+                    depth
+                );
+            }
+            // the winner of the initial node does not change with further exploration
+            if !status_reported {
+                on_status(Status::from(winner));
+                status_reported = true;
+            }
+            if !options.exploration_border_free
+                || options.only_realizability
+                || options.output_format == OutputFormat::Pg
+            {
+                info!("Game solved, winner is {}", winner);
+                return construct_result(
+                    winner,
+                    constructor,
+                    incremental_solver,
+                    None,
+                    options,
+                    events,
+                );
+            }
+            // only stop exploration once the strategy is independent of the border
+            let strategy = incremental_solver.strategy(game, winner);
+            let dependencies = strategy.border_dependencies(game, winner);
+            if dependencies.is_empty() || deadline_passed(deadline) {
+                if !dependencies.is_empty() {
+                    info!("Time budget for exploration and solving exhausted");
+                }
+                info!("Game solved, winner is {}", winner);
+                return construct_result(
+                    winner,
+                    constructor,
+                    incremental_solver,
+                    Some(strategy),
+                    options,
+                    events,
+                );
+            }
+            debug!(
+                "Strategy for {} depends on {} nodes adjacent to border, continuing exploration",
+                winner,
+                dependencies.size()
+            );
+        }
+
+        if let (Some(depth), Some(max_depth)) = (depth_bound, options.depth_bounded_exploration) {
+            // double the depth, and fall back to unbounded exploration beyond the maximum
+            depth_bound = Some(depth * 2).filter(|&d| d <= max_depth.get());
+            constructor.set_depth_bound(depth_bound);
         }
 
         // dynamically scale exploration limit for time multiple option
@@ -306,6 +1798,16 @@ where
                 (solver_stats.time() * n).saturating_sub(construction_stats.time()),
             );
         }
+
+        if options.exploration_pruning || options.exploration_filter {
+            let pruned = constructor.prune(&incremental_solver.decided());
+            if pruned > 0 {
+                debug!(
+                    "Removed {} nodes only reachable through decided nodes from the queue",
+                    pruned
+                );
+            }
+        }
     }
 }
 
@@ -316,28 +1818,114 @@ fn construct_result<
 >(
     winner: Player,
     constructor: GameConstructor<A, Q>,
-    mut solver: IncrementalSolver<S>,
+    mut solver: IncrementalSolver<'_, S>,
+    strategy: Option<Strategy>,
     options: &SynthesisOptions,
+    events: &mut EventLog<'_>,
 ) -> SynthesisResult
 where
     A::EdgeLabel: Clone + Eq + Ord,
 {
     let status = Status::from(winner);
-    if options.output_format == OutputFormat::Pg {
-        let game = constructor.into_game();
+    events.emit(PipelineEvent::GameSolved {
+        nodes: constructor.get_game().num_nodes(),
+        status,
+    });
+    let solution = options.strategies_output.as_ref().map(|path| {
+        let solution = solve_both_players(constructor.get_game(), &mut solver);
+        info!(
+            "Decided {} of {} nodes for the strategies of both players",
+            solution.num_decided(),
+            constructor.get_game().num_nodes()
+        );
+        if let Err(err) = fs::write(path, solution.to_string()) {
+            warn!("Could not write strategies to {}: {}", path, err);
+        }
+        solution
+    });
+    let result = if options.output_format == OutputFormat::Pg {
+        let mut game = constructor.into_game();
+        if options.pg_explicit_border {
+            let num_border = game.complete_border(!winner);
+            info!("Completed {} border nodes won by {}", num_border, !winner);
+        }
         SynthesisResult::with_game(status, game)
     } else if options.only_realizability {
         SynthesisResult::only_status(status)
     } else {
         info!("Obtaining winning strategy");
-        let strategy = solver.strategy(constructor.get_game(), winner);
+        let game = constructor.get_game();
+        let strategy = strategy.unwrap_or_else(|| solver.strategy(game, winner));
+        let dependencies = strategy.border_dependencies(game, winner);
+        if !dependencies.is_empty() {
+            info!(
+                "Strategy has {} decisions at nodes adjacent to unexplored nodes",
+                dependencies.size()
+            );
+        }
         let construction_stats = constructor.stats();
         let solver_stats = solver.stats();
         trace!("Stats: {}; {}", construction_stats, solver_stats);
 
         info!("Constructing machine");
-        let (machine, automaton) = constructor.into_mealy_machine(winner, strategy);
-        construct_result_from_machine(status, machine, &automaton, options)
+        let tie_breaking_seed = (options.machine_determinization_policy
+            == DeterminizationPolicy::Random)
+            .then(|| options.determinization_seed);
+        let (machine, automaton) = constructor.into_mealy_machine(
+            winner,
+            strategy,
+            options.strategy_propagation,
+            tie_breaking_seed,
+        );
+        construct_result_from_machine(status, machine, &automaton, options, events)
+    };
+    result.with_solution(solution)
+}
+
+/// Returns the winning regions and strategies of both players in the explored game,
+/// where border nodes and nodes not yet decided by the solver are left undecided.
+fn solve_both_players<'a, G: Game<'a>, S: ParityGameSolver>(
+    game: &'a G,
+    solver: &mut IncrementalSolver<'_, S>,
+) -> GameSolution {
+    let mut solution = GameSolution::undecided(game.nodes().collect());
+    for &player in &Player::PLAYERS {
+        let strategy = solver.strategy(game, player);
+        let winning = &solver.winning()[player];
+        solution.add_region(game, player, winning, Some(&strategy));
+    }
+    solution
+}
+
+/// Parses pairs of atomic propositions of the form `a:b` separated by commas,
+/// or returns `None` if a pair is malformed.
+fn parse_swaps(pairs: &str) -> Option<Vec<(String, String)>> {
+    pairs
+        .split(',')
+        .map(|pair| {
+            let (first, second) = pair.split_once(':')?;
+            Some((first.trim().to_string(), second.trim().to_string()))
+        })
+        .collect()
+}
+
+/// The maximum number of states of a machine that is minimized for the aiger portfolio,
+/// unless a different maximum is given with option `--minimize-max-states`.
+const PORTFOLIO_MAX_STATES: usize = 4000;
+
+/// Checks whether the machine has more states than allowed for minimization by the
+/// given options, and warns that the unminimized machine is used in this case.
+fn exceeds_minimization_limit<L>(machine: &LabelledMachine<L>, options: &SynthesisOptions) -> bool {
+    match options.minimization_max_states {
+        Some(max_states) if machine.num_states() > max_states => {
+            warn!(
+                "Machine with {} states exceeds the limit of {} states for minimization, using the unminimized machine",
+                machine.num_states(),
+                max_states
+            );
+            true
+        }
+        _ => false,
     }
 }
 
@@ -346,20 +1934,42 @@ fn construct_result_from_machine<A: MaxEvenDpa>(
     mut machine: LabelledMachine<StateIndex>,
     automaton: &A,
     options: &SynthesisOptions,
+    events: &mut EventLog<'_>,
 ) -> SynthesisResult
 where
     A::EdgeLabel: Clone + Eq + Ord,
 {
     let mut min_machine = None;
+    events.emit(PipelineEvent::MachineConstructed {
+        states: machine.num_states(),
+    });
+
+    if let Some(controller) = &options.repair_controller {
+        if status == Status::Realizable {
+            let repaired = machine.repair(controller);
+            return construct_result_from_structured_machines(
+                status,
+                vec![(Provenance::new(false, LabelStructure::None), repaired)],
+                None,
+                options,
+                events,
+            );
+        }
+        warn!("Specification is unrealizable, controller is not repaired");
+    }
 
     // avoid minimization in portfolio approach for very large machines
-    let min_portfolio = options.aiger_portfolio && machine.num_states() <= 4000;
-    let min_nondet = min_portfolio
+    let min_portfolio = options.aiger_portfolio
+        && machine.num_states()
+            <= options
+                .minimization_max_states
+                .unwrap_or(PORTFOLIO_MAX_STATES);
+    let mut min_nondet = min_portfolio
         || matches!(
             options.machine_minimization,
             MinimizationMethod::NonDeterminism | MinimizationMethod::Both
         );
-    let min_dontcare = min_portfolio
+    let mut min_dontcare = min_portfolio
         || matches!(
             options.machine_minimization,
             MinimizationMethod::DontCares | MinimizationMethod::Both
@@ -371,85 +1981,214 @@ where
             LabelCompression::Features | LabelCompression::Both
         );
 
+    if let Some(symmetry) = &options.symmetry {
+        // the swap latch of the quotient is only available in circuits of Mealy machines
+        let quotient = machine.is_mealy()
+            && !options.requires_format(OutputFormat::Hoa)
+            && !options.requires_format(OutputFormat::Kiss2)
+            && options.recovery_input.is_none();
+        let valid = parse_swaps(symmetry).map_or(false, |swaps| {
+            if quotient {
+                machine.quotient_by_symmetry(&swaps).is_some()
+            } else {
+                machine.symmetric_states(&swaps).is_some()
+            }
+        });
+        if !valid {
+            warn!("Invalid pairs of symmetric propositions: {}", symmetry);
+        }
+    }
+
+    let deadline = stage_deadline(options.budget_minimization);
+    if min_nondet && exceeds_minimization_limit(&machine, options) {
+        min_nondet = false;
+        min_dontcare = false;
+    }
     if min_nondet {
         machine = machine.minimize_with_nondeterminism();
     }
+    // keep the don't cares of the incompletely specified machine before determinization
+    let incomplete_machine = options
+        .requires_format(OutputFormat::Kiss2)
+        .then(|| machine.with_structured_labels(&mut SimpleLabelling::default()));
+    if min_dontcare && exceeds_minimization_limit(&machine, options) {
+        min_dontcare = false;
+    }
     if min_dontcare {
-        machine.determinize();
-        min_machine = Some(machine.minimize_with_dontcares(compress_features));
+        determinize_machine(&mut machine, options);
+        min_machine = Some(machine.minimize_with_dontcares(
+            compress_features,
+            deadline,
+            options.minimization_max_clauses,
+            &mut |lower, upper| events.emit(PipelineEvent::MinimizationBounds { lower, upper }),
+        ));
+    }
+    if min_nondet || min_dontcare {
+        events.emit(PipelineEvent::MachineMinimized {
+            states: min_machine
+                .as_ref()
+                .map_or(machine.num_states(), LabelledMachine::num_states),
+        });
     }
 
     // machines needs to be deterministic for other output formats
-    if options.machine_determinization
-        || (!min_dontcare && options.output_format != OutputFormat::Hoa)
-    {
-        machine.determinize();
+    if options.machine_determinization || (!min_dontcare && !options.only_machines()) {
+        determinize_machine(&mut machine, options);
     }
 
     // add labels
     let mut structured_machines = Vec::new();
     if options.aiger_portfolio {
+        let structures = [
+            LabelStructure::None,
+            LabelStructure::Structured,
+            LabelStructure::Inner,
+        ];
         if let Some(min_machine) = min_machine {
             if min_machine.num_states() < machine.num_states() {
-                let m0 = min_machine.with_structured_labels(&mut SimpleLabelling::default());
-                structured_machines.push(m0);
-                let m1 =
-                    min_machine.with_structured_labels(&mut AutomatonLabelling::new(automaton));
-                structured_machines.push(m1);
+                for &structure in &structures {
+                    let m = with_label_structure(&min_machine, structure, automaton);
+                    structured_machines.push((Provenance::new(true, structure), m));
+                }
             }
         }
-        let m2 = machine.with_structured_labels(&mut SimpleLabelling::default());
-        let m3 = machine.with_structured_labels(&mut AutomatonLabelling::new(automaton));
-        structured_machines.push(m2);
-        structured_machines.push(m3);
+        for &structure in &structures {
+            let m = with_label_structure(&machine, structure, automaton);
+            structured_machines.push((Provenance::new(false, structure), m));
+        }
     } else if let Some(min_machine) = min_machine {
-        let m = match options.label_structure {
-            LabelStructure::None => {
-                min_machine.with_structured_labels(&mut SimpleLabelling::default())
-            }
-            LabelStructure::Structured => {
-                min_machine.with_structured_labels(&mut AutomatonLabelling::new(automaton))
-            }
-        };
-        structured_machines.push(m);
+        let m = with_label_structure(&min_machine, options.label_structure, automaton);
+        structured_machines.push((Provenance::new(true, options.label_structure), m));
     } else {
-        let m = match options.label_structure {
-            LabelStructure::None => machine.with_structured_labels(&mut SimpleLabelling::default()),
-            LabelStructure::Structured => {
-                machine.with_structured_labels(&mut AutomatonLabelling::new(automaton))
-            }
-        };
-        structured_machines.push(m);
+        let m = with_label_structure(&machine, options.label_structure, automaton);
+        structured_machines.push((Provenance::new(false, options.label_structure), m));
     }
 
-    construct_result_from_structured_machines(status, structured_machines, options)
+    construct_result_from_structured_machines(
+        status,
+        structured_machines,
+        incomplete_machine,
+        options,
+        events,
+    )
+}
+
+/// Returns the machine with structured labels of the given structure for its states.
+fn with_label_structure<'a, L: Clone + Eq + Hash, A: MaxEvenDpa>(
+    machine: &LabelledMachine<L>,
+    structure: LabelStructure,
+    automaton: &'a A,
+) -> LabelledMachine<StructuredLabel>
+where
+    AutomatonLabelling<'a, A>: Labelling<L>,
+{
+    match structure {
+        LabelStructure::None => machine.with_structured_labels(&mut SimpleLabelling::default()),
+        LabelStructure::Structured => {
+            machine.with_structured_labels(&mut AutomatonLabelling::new(automaton))
+        }
+        LabelStructure::Inner => {
+            machine.with_structured_labels(&mut AutomatonLabelling::with_inner_structure(automaton))
+        }
+        LabelStructure::Scored => {
+            machine.with_structured_labels(&mut AutomatonLabelling::with_scores(automaton))
+        }
+    }
 }
 
 fn construct_result_from_structured_machines(
     status: Status,
-    mut structured_machines: Vec<LabelledMachine<StructuredLabel>>,
+    mut structured_machines: Vec<(Provenance, LabelledMachine<StructuredLabel>)>,
+    incomplete_machine: Option<LabelledMachine<StructuredLabel>>,
     options: &SynthesisOptions,
+    events: &mut EventLog<'_>,
 ) -> SynthesisResult {
+    let low_toggling = options.machine_determinization_policy == DeterminizationPolicy::LowToggle;
     if options.aiger_portfolio
+        || low_toggling
         || matches!(
             options.label_compression,
             LabelCompression::Values | LabelCompression::Both
         )
     {
-        for m in &mut structured_machines {
-            m.compress_label_values();
+        for (_, m) in &mut structured_machines {
+            m.compress_label_values(low_toggling);
         }
     }
 
-    if options.output_format == OutputFormat::Hoa {
-        SynthesisResult::with_machine(status, structured_machines.remove(0))
-    } else {
+    if let Some(name) = &options.recovery_input {
+        for (_, m) in &mut structured_machines {
+            if !m.add_recovery_input(name) {
+                warn!(
+                    "Recovery input {} could not be added to the controller",
+                    name
+                );
+                break;
+            }
+        }
+    }
+
+    // one controller for each distinct requested format, sharing all intermediate results
+    let mut controllers = Vec::new();
+    if options.requires_format(OutputFormat::Hoa) {
+        let mut machine = if options.only_machines() {
+            structured_machines.remove(0).1
+        } else {
+            structured_machines[0].1.clone()
+        };
+        machine.set_outputs_first(options.ap_order == ApOrder::OutputsFirst);
+        if options.hoa_implicit_else {
+            let num_widened = machine.add_implicit_else();
+            info!(
+                "Widened {} transitions to implicit else edges for don't care inputs",
+                num_widened
+            );
+        }
+        controllers.push((OutputFormat::Hoa, Controller::Machine(machine)));
+    }
+    if let Some(machine) = incomplete_machine {
+        controllers.push((OutputFormat::Kiss2, Controller::IncompleteMachine(machine)));
+    }
+    let mut pareto = None;
+    let mut interface = None;
+    if !options.only_machines() {
+        let origins: Vec<_> = structured_machines
+            .iter()
+            .map(|(provenance, m)| (*provenance, m.num_states()))
+            .collect();
         let mut bdds: Vec<_> = structured_machines
             .into_iter()
-            .map(|m| m.create_bdds())
+            .map(|(_, m)| {
+                m.create_bdds(
+                    options.bdd_partition_threshold,
+                    options.bdd_group_components,
+                )
+            })
             .collect();
 
+        if let Some(path) = &options.bdd_order_input {
+            match fs::read_to_string(path) {
+                Ok(contents) => {
+                    let order: Vec<_> = contents
+                        .lines()
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(String::from)
+                        .collect();
+                    for bdd in &mut bdds {
+                        bdd.apply_variable_order(&order);
+                    }
+                }
+                Err(err) => warn!("Could not read variable order from {}: {}", path, err),
+            }
+        }
+
+        let deadline = stage_deadline(options.budget_reordering);
         for bdd in &mut bdds {
+            if deadline_passed(deadline) {
+                info!("Time budget for reordering exhausted, skipping reordering");
+                break;
+            }
             match options.bdd_reordering {
                 BddReordering::Heuristic => bdd.reduce(false),
                 BddReordering::Mixed => bdd.reduce(bdd.num_bdd_vars() <= 16),
@@ -458,31 +2197,268 @@ fn construct_result_from_structured_machines(
             };
         }
 
-        if options.output_format == OutputFormat::Bdd {
-            SynthesisResult::with_bdd(status, bdds.remove(0))
-        } else {
-            let mut aigs: Vec<_> = bdds.into_iter().map(|bdd| bdd.create_aiger()).collect();
+        events.emit(PipelineEvent::BddConstructed {
+            variables: bdds.iter().map(BddController::num_bdd_vars).min().unwrap(),
+        });
+
+        if let Some(path) = &options.bdd_order_output {
+            let mut contents = String::new();
+            for name in bdds[0].variable_order() {
+                contents.push_str(&name);
+                contents.push('\n');
+            }
+            if let Err(err) = fs::write(path, contents) {
+                warn!("Could not write variable order to {}: {}", path, err);
+            }
+        }
+
+        if options.requires_format(OutputFormat::Aag)
+            || options.requires_format(OutputFormat::Aig)
+            || options.requires_format(OutputFormat::Blif)
+            || options.requires_format(OutputFormat::Btor2)
+            || options.requires_format(OutputFormat::Verilog)
+        {
+            let and_cache = options.aiger_and_cache.into();
+            let mut aigs: Vec<_> = bdds
+                .iter()
+                .map(|bdd| bdd.create_aiger(and_cache, options.bdd_share_subfunctions))
+                .collect();
             // in portfolio approach, skip compressing circuits relatively much larger than old minimum
             let min_size = aigs.iter().map(AigerController::size).min().unwrap();
             let min_size_total = min_size.total() as f32;
             let cmp_size = min_size_total + (min_size_total * 10000.0) / (min_size_total + 1000.0);
+            let deadline = stage_deadline(options.budget_compression);
             for aig in &mut aigs {
+                if deadline_passed(deadline) {
+                    info!("Time budget for compression exhausted, skipping compression");
+                    break;
+                }
                 if !options.aiger_portfolio || (aig.size().total() as f32) <= cmp_size {
                     match options.aiger_compression {
-                        AigerCompression::Basic => aig.compress(false),
-                        AigerCompression::More => aig.compress(true),
+                        AigerCompression::Basic => aig.compress(false, false, deadline),
+                        AigerCompression::More => aig.compress(true, false, deadline),
+                        AigerCompression::Sequential => aig.compress(true, true, deadline),
                         AigerCompression::None => (),
                     };
+                    if let Some(max_vars) = options.aiger_exact_synthesis {
+                        aig.exact_synthesis(max_vars, deadline);
+                    }
+                }
+            }
+            let candidates: Vec<_> = origins
+                .into_iter()
+                .zip(aigs)
+                .map(|((provenance, states), aig)| Candidate::new(provenance, states, aig))
+                .collect();
+            if candidates.len() > 1 {
+                let metrics = [
+                    AigerMetric::Gates,
+                    AigerMetric::Latches,
+                    AigerMetric::Inputs,
+                    AigerMetric::Depth,
+                    AigerMetric::States,
+                ];
+                for c in &candidates {
+                    if !candidates
+                        .iter()
+                        .any(|other| other.metrics().dominates(c.metrics(), &metrics))
+                    {
+                        info!(
+                            "Pareto-optimal aiger circuit from {} has size {}",
+                            c.provenance(),
+                            c.metrics()
+                        );
+                    }
+                }
+            }
+            pareto = (!options.pareto_metrics.is_empty())
+                .then(|| pareto_frontier(candidates.clone(), &options.pareto_metrics));
+            let candidates = match &options.aiger_thresholds {
+                Some(thresholds) if !candidates.iter().any(|c| c.metrics().meets(thresholds)) => {
+                    warn!(
+                        "No aiger circuit meets the thresholds {}, ignoring them",
+                        thresholds
+                    );
+                    candidates
+                }
+                Some(thresholds) => candidates
+                    .into_iter()
+                    .filter(|c| c.metrics().meets(thresholds))
+                    .collect(),
+                None => candidates,
+            };
+            let aig = candidates
+                .into_iter()
+                .min_by_key(|c| c.metrics().objective_key(&options.aiger_objective))
+                .unwrap()
+                .into_controller();
+            interface = (options.interface_report.is_some() || options.reduce_interface)
+                .then(|| aig.interface_report());
+            let aig = if options.reduce_interface {
+                aig.reduce_interface()
+            } else {
+                aig
+            };
+            let size = aig.size();
+            info!("Selected aiger circuit has size {}", size);
+            events.emit(PipelineEvent::AigerConstructed {
+                ands: size.num_ands(),
+                latches: size.num_latches(),
+                depth: size.depth(),
+                io_delay: size.io_delay(),
+                latch_delay: size.latch_delay(),
+            });
+            if options.requires_format(OutputFormat::Blif) {
+                let luts = aig.map_luts(abc::MAX_LUT_SIZE);
+                controllers.push((OutputFormat::Blif, Controller::Lut(luts)));
+            }
+            if options.requires_format(OutputFormat::Btor2) {
+                let assumption = options.btor2_assumption.as_ref();
+                let model = aig.to_btor2(assumption);
+                controllers.push((OutputFormat::Btor2, Controller::Btor2(model)));
+            }
+            if options.requires_format(OutputFormat::Verilog) {
+                controllers.push((OutputFormat::Verilog, Controller::Verilog(aig.factor())));
+            }
+            for &format in &[OutputFormat::Aag, OutputFormat::Aig] {
+                if options.requires_format(format) {
+                    controllers.push((format, Controller::Aiger(aig.clone())));
                 }
             }
-            assert!(matches!(
-                options.output_format,
-                OutputFormat::Aag | OutputFormat::Aig
-            ));
-            SynthesisResult::with_aiger(
-                status,
-                aigs.into_iter().min_by_key(|a| a.size().total()).unwrap(),
-            )
         }
+        if options.requires_format(OutputFormat::Bdd) {
+            controllers.push((OutputFormat::Bdd, Controller::Bdd(bdds.remove(0))));
+        }
+    }
+
+    let mut take_controller = |format| {
+        let index = controllers
+            .iter()
+            .position(|(f, _)| *f == format)
+            .expect("no controller constructed for output format");
+        controllers.swap_remove(index).1
+    };
+    let controller = take_controller(options.output_format);
+    let artifacts = options
+        .outputs
+        .iter()
+        .map(|target| Artifact {
+            target: target.clone(),
+            controller: take_controller(target.format),
+        })
+        .collect();
+    SynthesisResult::with_controller(status, controller)
+        .with_pareto_frontier(pareto)
+        .with_artifacts(artifacts)
+        .with_interface(interface)
+}
+
+/// Tests for the helper functions of the synthesis procedure.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that combinations are enumerated in lexicographic order.
+    #[test]
+    fn test_next_combination() {
+        let mut indices = vec![0, 1];
+        let mut combinations = vec![indices.clone()];
+        while next_combination(&mut indices, 4) {
+            combinations.push(indices.clone());
+        }
+        assert_eq!(
+            combinations,
+            vec![
+                vec![0, 1],
+                vec![0, 2],
+                vec![0, 3],
+                vec![1, 2],
+                vec![1, 3],
+                vec![2, 3]
+            ]
+        );
+        assert!(!next_combination(&mut [], 4));
+    }
+
+    /// Test that the GraalVM is shared between synthesis calls with the same heap size.
+    #[test]
+    fn test_shared_vm() {
+        let vm = shared_vm(None);
+        assert!(Rc::ptr_eq(&vm, &shared_vm(None)));
+        let options = SynthesisOptions {
+            vm_heap_size: Some(256),
+            ..SynthesisOptions::default()
+        };
+        let result = synthesize_with("G (r -> F g)", &["r"], &["g"], &options);
+        assert_eq!(result.status(), Status::Realizable);
+        let large_vm = shared_vm(Some(256));
+        assert!(!Rc::ptr_eq(&vm, &large_vm));
+        release_shared_vm();
+        assert!(!Rc::ptr_eq(&large_vm, &shared_vm(Some(256))));
+    }
+
+    /// Test that the propositions declared neither as inputs nor as outputs are found.
+    #[test]
+    fn test_undeclared_propositions() {
+        let vm = shared_vm(None);
+        assert_eq!(
+            undeclared_propositions(&vm, "G (Req -> F g) & F Req", &[], &["g"]).unwrap(),
+            vec!["Req".to_string()]
+        );
+        assert!(undeclared_propositions(&vm, "G (r -> F g)", &["r"], &["g"])
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Test that fairness assumptions on inputs are added to the assumption of the formula.
+    #[test]
+    fn test_with_fairness() {
+        let fairness: Vec<Fairness> = ["ready", "req->ack", "en ~> tk"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert_eq!(fairness[0], Fairness::Unconditional("ready".to_string()));
+        assert_eq!(fairness[1].inputs(), vec!["req", "ack"]);
+        assert_eq!(fairness[2].to_string(), "en~>tk");
+        assert!("->ack".parse::<Fairness>().is_err());
+        assert_eq!(
+            with_fairness("G F a -> G g", &fairness[..2]),
+            "(((G (F ready)) & ((G (F req)) -> (G (F ack)))) & (G (F a))) -> (G g)"
+        );
+        assert_eq!(
+            with_fairness("G g", &fairness[2..]),
+            "((F (G en)) -> (G (F tk))) -> (G g)"
+        );
+        // the formula is split at the same implication as by Owl
+        assert_eq!(
+            with_fairness("a U b -> c", &fairness[..1]),
+            "(G (F ready)) -> (a U (b -> c))"
+        );
+        assert_eq!(
+            with_fairness("a & b | c -> d", &fairness[..1]),
+            "((G (F ready)) & ((a & b) | c)) -> d"
+        );
+    }
+
+    /// Test that the assumption is only split off at a top-level implication.
+    #[test]
+    fn test_split_assumption() {
+        assert_eq!(split_assumption("G F r -> G F g").unwrap(), "G (F r)");
+        // conjunction and disjunction bind stronger than implication
+        assert_eq!(
+            split_assumption("(a -> b) & G c => G d").unwrap(),
+            "(a -> b) & (G c)"
+        );
+        assert_eq!(split_assumption("a & b | c -> d").unwrap(), "(a & b) | c");
+        // the other binary operators have the same precedence and associate to the right
+        assert_eq!(split_assumption("a -> b -> c").unwrap(), "a");
+        assert_eq!(split_assumption("a U b -> c"), None);
+        assert_eq!(split_assumption("a <-> b"), None);
+        assert_eq!(split_assumption("G (r -> F g)"), None);
+        assert_eq!(split_assumption("Up & R -> g"), None);
+        assert_eq!(
+            split_assumption("Up & \"R\" -> g").unwrap(),
+            "\"Up\" & \"R\""
+        );
     }
 }
@@ -0,0 +1,776 @@
+//! A syntax tree for LTL formulas in the input syntax of Strix, which is used to
+//! inspect the structure of a specification without constructing an automaton.
+//!
+//! The syntax follows the LTL parser of Owl: all binary operators except for
+//! conjunction and disjunction have the same, lowest precedence and associate to
+//! the right, disjunction binds stronger than these operators, conjunction binds
+//! stronger than disjunction, and unary operators bind strongest. As in the lexer
+//! of Owl, words are read by longest match, so an operator has to be separated from
+//! an adjacent proposition: `G F a` is a formula, but `GFa` and `Req` are propositions.
+//!
+//! The parser only accepts a subset of the syntax of Owl and additionally rejects
+//! formulas nested deeper than [`MAX_NESTING_DEPTH`], so that the recursive functions
+//! on the syntax tree can not overflow the stack. For the same reason, sequences of
+//! conjunctions and disjunctions are parsed into balanced trees, which does not change
+//! the meaning of the formula. The formulas accepted by both parsers
+//! are checked to have the same structure on random formulas in the tests.
+
+use std::error::Error;
+use std::fmt;
+
+/// The maximal nesting depth of parentheses and of operators other than conjunction
+/// and disjunction in a formula that is accepted by the parser.
+pub(crate) const MAX_NESTING_DEPTH: usize = 256;
+
+/// A unary temporal operator.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum UnaryOp {
+    /// The next operator `X`.
+    Next,
+    /// The eventually operator `F`.
+    Finally,
+    /// The always operator `G`.
+    Globally,
+}
+
+/// A binary operator.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum BinaryOp {
+    And,
+    Or,
+    Implies,
+    Iff,
+    Xor,
+    /// The until operator `U`.
+    Until,
+    /// The weak until operator `W`.
+    WeakUntil,
+    /// The release operator `R`.
+    Release,
+    /// The strong release operator `M`.
+    StrongRelease,
+}
+
+impl BinaryOp {
+    /// Returns whether this operator is a temporal operator.
+    pub(crate) const fn is_temporal(self) -> bool {
+        matches!(
+            self,
+            Self::Until | Self::WeakUntil | Self::Release | Self::StrongRelease
+        )
+    }
+}
+
+/// An LTL formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Ltl {
+    Const(bool),
+    Atom(String),
+    Not(Box<Ltl>),
+    Unary(UnaryOp, Box<Ltl>),
+    Binary(BinaryOp, Box<Ltl>, Box<Ltl>),
+}
+
+impl Ltl {
+    /// Returns the number of operators, constants and propositions in the formula.
+    pub(crate) fn size(&self) -> usize {
+        match self {
+            Self::Const(_) | Self::Atom(_) => 1,
+            Self::Not(f) | Self::Unary(_, f) => 1 + f.size(),
+            Self::Binary(_, f1, f2) => 1 + f1.size() + f2.size(),
+        }
+    }
+
+    /// Returns the number of temporal operators in the formula.
+    pub(crate) fn temporal_operators(&self) -> usize {
+        match self {
+            Self::Const(_) | Self::Atom(_) => 0,
+            Self::Not(f) => f.temporal_operators(),
+            Self::Unary(_, f) => 1 + f.temporal_operators(),
+            Self::Binary(op, f1, f2) => {
+                op.is_temporal() as usize + f1.temporal_operators() + f2.temporal_operators()
+            }
+        }
+    }
+
+    /// Returns the maximal nesting depth of temporal operators in the formula.
+    pub(crate) fn temporal_depth(&self) -> usize {
+        match self {
+            Self::Const(_) | Self::Atom(_) => 0,
+            Self::Not(f) => f.temporal_depth(),
+            Self::Unary(_, f) => 1 + f.temporal_depth(),
+            Self::Binary(op, f1, f2) => {
+                op.is_temporal() as usize + f1.temporal_depth().max(f2.temporal_depth())
+            }
+        }
+    }
+
+    /// Returns the atomic propositions of the formula in the order of their first occurrence.
+    pub(crate) fn propositions(&self) -> Vec<&str> {
+        let mut propositions = Vec::new();
+        self.collect_propositions(&mut propositions);
+        propositions
+    }
+
+    fn collect_propositions<'a>(&'a self, propositions: &mut Vec<&'a str>) {
+        match self {
+            Self::Const(_) => (),
+            Self::Atom(a) => {
+                if !propositions.contains(&a.as_str()) {
+                    propositions.push(a);
+                }
+            }
+            Self::Not(f) | Self::Unary(_, f) => f.collect_propositions(propositions),
+            Self::Binary(_, f1, f2) => {
+                f1.collect_propositions(propositions);
+                f2.collect_propositions(propositions);
+            }
+        }
+    }
+
+    /// Returns the top-level conjuncts of the formula.
+    pub(crate) fn conjuncts(&self) -> Vec<&Ltl> {
+        match self {
+            Self::Binary(BinaryOp::And, f1, f2) => {
+                let mut conjuncts = f1.conjuncts();
+                conjuncts.extend(f2.conjuncts());
+                conjuncts
+            }
+            _ => vec![self],
+        }
+    }
+
+    /// Returns whether the formula is a safety formula, i.e. in negation normal form
+    /// it only contains the temporal operators `X`, `G`, `W` and `R`, if it occurs
+    /// with the given polarity.
+    pub(crate) fn is_safety(&self, positive: bool) -> bool {
+        self.is_in_fragment(positive, true)
+    }
+
+    /// Returns whether the formula is a co-safety formula, i.e. in negation normal form
+    /// it only contains the temporal operators `X`, `F`, `U` and `M`, if it occurs
+    /// with the given polarity.
+    pub(crate) fn is_cosafety(&self, positive: bool) -> bool {
+        self.is_in_fragment(positive, false)
+    }
+
+    /// Returns whether the formula with the given polarity is in the safety fragment
+    /// if `safety` is set, and in the co-safety fragment otherwise.
+    fn is_in_fragment(&self, positive: bool, safety: bool) -> bool {
+        match self {
+            Self::Const(_) | Self::Atom(_) => true,
+            Self::Not(f) => f.is_in_fragment(!positive, safety),
+            Self::Unary(UnaryOp::Next, f) => f.is_in_fragment(positive, safety),
+            Self::Unary(UnaryOp::Globally, f) => {
+                positive == safety && f.is_in_fragment(positive, safety)
+            }
+            Self::Unary(UnaryOp::Finally, f) => {
+                positive != safety && f.is_in_fragment(positive, safety)
+            }
+            Self::Binary(op, f1, f2) => match op {
+                BinaryOp::And | BinaryOp::Or => {
+                    f1.is_in_fragment(positive, safety) && f2.is_in_fragment(positive, safety)
+                }
+                BinaryOp::Implies => {
+                    f1.is_in_fragment(!positive, safety) && f2.is_in_fragment(positive, safety)
+                }
+                BinaryOp::Iff | BinaryOp::Xor => [f1, f2].iter().all(|f| {
+                    f.is_in_fragment(positive, safety) && f.is_in_fragment(!positive, safety)
+                }),
+                BinaryOp::WeakUntil | BinaryOp::Release => {
+                    positive == safety
+                        && f1.is_in_fragment(positive, safety)
+                        && f2.is_in_fragment(positive, safety)
+                }
+                BinaryOp::Until | BinaryOp::StrongRelease => {
+                    positive != safety
+                        && f1.is_in_fragment(positive, safety)
+                        && f2.is_in_fragment(positive, safety)
+                }
+            },
+        }
+    }
+
+    /// Returns the minimal levels `(i, j)` of the syntactic future hierarchy such that the
+    /// formula with the given polarity is in the classes Σ_i and Π_j.
+    ///
+    /// In negation normal form, Σ_0 and Π_0 contain the formulas whose only temporal
+    /// operator is `X`, Σ_i+1 is the closure of Π_i under `X`, `F`, `U`, `M` and the
+    /// boolean operators, and dually Π_i+1 is the closure of Σ_i under `X`, `G`, `W`,
+    /// `R` and the boolean operators. Thus Π_1 is the safety fragment and Σ_1 the
+    /// co-safety fragment.
+    pub(crate) fn hierarchy_levels(&self, positive: bool) -> (usize, usize) {
+        // the levels (i, j) of subformulas satisfy i <= j + 1 and j <= i + 1
+        let join = |(i1, j1): (usize, usize), (i2, j2): (usize, usize)| (i1.max(i2), j1.max(j2));
+        let eventually = |(i, _): (usize, usize)| (i.max(1), i.max(1) + 1);
+        let always = |(_, j): (usize, usize)| (j.max(1) + 1, j.max(1));
+        match self {
+            Self::Const(_) | Self::Atom(_) => (0, 0),
+            Self::Not(f) => f.hierarchy_levels(!positive),
+            Self::Unary(UnaryOp::Next, f) => f.hierarchy_levels(positive),
+            Self::Unary(op, f) => {
+                let levels = f.hierarchy_levels(positive);
+                if (*op == UnaryOp::Finally) == positive {
+                    eventually(levels)
+                } else {
+                    always(levels)
+                }
+            }
+            Self::Binary(op, f1, f2) => {
+                let levels = match op {
+                    BinaryOp::Implies => join(
+                        f1.hierarchy_levels(!positive),
+                        f2.hierarchy_levels(positive),
+                    ),
+                    BinaryOp::Iff | BinaryOp::Xor => [f1, f2]
+                        .iter()
+                        .flat_map(|f| vec![f.hierarchy_levels(true), f.hierarchy_levels(false)])
+                        .fold((0, 0), join),
+                    _ => join(f1.hierarchy_levels(positive), f2.hierarchy_levels(positive)),
+                };
+                match op {
+                    BinaryOp::Until | BinaryOp::StrongRelease if positive => eventually(levels),
+                    BinaryOp::WeakUntil | BinaryOp::Release if !positive => eventually(levels),
+                    BinaryOp::Until | BinaryOp::StrongRelease => always(levels),
+                    BinaryOp::WeakUntil | BinaryOp::Release => always(levels),
+                    _ => levels,
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Ltl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Const(true) => write!(f, "true"),
+            Self::Const(false) => write!(f, "false"),
+            Self::Atom(a) => {
+                if a.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+                    && !["true", "false", "tt", "ff", "xor"].contains(&a.as_str())
+                {
+                    write!(f, "{}", a)
+                } else {
+                    write!(f, "\"{}\"", a)
+                }
+            }
+            Self::Not(g) => write!(f, "!{}", Parenthesized(g)),
+            Self::Unary(op, g) => {
+                let symbol = match op {
+                    UnaryOp::Next => "X",
+                    UnaryOp::Finally => "F",
+                    UnaryOp::Globally => "G",
+                };
+                write!(f, "{} {}", symbol, Parenthesized(g))
+            }
+            Self::Binary(op, g1, g2) => {
+                let symbol = match op {
+                    BinaryOp::And => "&",
+                    BinaryOp::Or => "|",
+                    BinaryOp::Implies => "->",
+                    BinaryOp::Iff => "<->",
+                    BinaryOp::Xor => "xor",
+                    BinaryOp::Until => "U",
+                    BinaryOp::WeakUntil => "W",
+                    BinaryOp::Release => "R",
+                    BinaryOp::StrongRelease => "M",
+                };
+                write!(f, "{} {} {}", Parenthesized(g1), symbol, Parenthesized(g2))
+            }
+        }
+    }
+}
+
+/// A formula that is enclosed in parentheses when displayed, unless it is atomic.
+struct Parenthesized<'a>(&'a Ltl);
+
+impl fmt::Display for Parenthesized<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Ltl::Const(_) | Ltl::Atom(_) => write!(f, "{}", self.0),
+            formula => write!(f, "({})", formula),
+        }
+    }
+}
+
+/// An error when parsing an LTL formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLtlError {
+    /// The byte position in the formula at which the error occurred.
+    position: usize,
+    /// The description of the error.
+    message: String,
+}
+
+impl ParseLtlError {
+    fn new<S: Into<String>>(position: usize, message: S) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the byte position in the formula at which the error occurred.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for ParseLtlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ParseLtlError {}
+
+/// A token of an LTL formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    Not,
+    Unary(UnaryOp),
+    Binary(BinaryOp),
+    Const(bool),
+    Atom(String),
+}
+
+/// Splits the formula into tokens together with their positions.
+fn tokenize(ltl: &str) -> Result<Vec<(usize, Token)>, ParseLtlError> {
+    let mut tokens = Vec::new();
+    let mut chars = ltl.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let rest = &ltl[i..];
+        let token = match c {
+            _ if c.is_whitespace() => continue,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '!' | '~' => Token::Not,
+            '&' | '|' => {
+                // the operators may be written with single or double symbols
+                if chars.peek().map_or(false, |&(_, next)| next == c) {
+                    chars.next();
+                }
+                Token::Binary(if c == '&' {
+                    BinaryOp::And
+                } else {
+                    BinaryOp::Or
+                })
+            }
+            '^' => Token::Binary(BinaryOp::Xor),
+            '-' | '=' if rest[1..].starts_with('>') => {
+                chars.next();
+                Token::Binary(BinaryOp::Implies)
+            }
+            '<' if rest.starts_with("<->") || rest.starts_with("<=>") => {
+                chars.next();
+                chars.next();
+                Token::Binary(BinaryOp::Iff)
+            }
+            '<' if rest.starts_with("<>") => {
+                chars.next();
+                Token::Unary(UnaryOp::Finally)
+            }
+            '[' if rest.starts_with("[]") => {
+                chars.next();
+                Token::Unary(UnaryOp::Globally)
+            }
+            '"' => {
+                let end = rest[1..]
+                    .find('"')
+                    .ok_or_else(|| ParseLtlError::new(i, "unterminated quoted proposition"))?;
+                for _ in 0..=rest[1..1 + end].chars().count() {
+                    chars.next();
+                }
+                Token::Atom(rest[1..1 + end].to_owned())
+            }
+            '0' => Token::Const(false),
+            '1' => Token::Const(true),
+            _ if c.is_ascii_alphabetic() || c == '_' => {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                let word = &rest[..len];
+                for _ in 1..word.chars().count() {
+                    chars.next();
+                }
+                word_token(word)
+            }
+            _ => {
+                return Err(ParseLtlError::new(
+                    i,
+                    format!("unexpected character '{}'", c),
+                ))
+            }
+        };
+        tokens.push((i, token));
+    }
+    Ok(tokens)
+}
+
+/// Returns the token of a word, which is an operator or a constant if the whole
+/// word is one, and a proposition otherwise.
+fn word_token(word: &str) -> Token {
+    match word {
+        "true" | "tt" => Token::Const(true),
+        "false" | "ff" => Token::Const(false),
+        "xor" => Token::Binary(BinaryOp::Xor),
+        "X" => Token::Unary(UnaryOp::Next),
+        "F" => Token::Unary(UnaryOp::Finally),
+        "G" => Token::Unary(UnaryOp::Globally),
+        "U" => Token::Binary(BinaryOp::Until),
+        "W" => Token::Binary(BinaryOp::WeakUntil),
+        "R" => Token::Binary(BinaryOp::Release),
+        "M" => Token::Binary(BinaryOp::StrongRelease),
+        _ => Token::Atom(word.to_owned()),
+    }
+}
+
+/// A recursive descent parser for LTL formulas.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    /// The length of the formula, which is the position of the end of the input.
+    end: usize,
+    /// The nesting depth of the formula at the current token.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.index)
+            .map_or(self.end, |&(position, _)| position)
+    }
+
+    /// Increases the nesting depth by one for the next operand.
+    fn descend(&mut self) -> Result<(), ParseLtlError> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING_DEPTH {
+            return Err(ParseLtlError::new(
+                self.position(),
+                format!("formula nested deeper than {}", MAX_NESTING_DEPTH),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses a formula with binary operators of the lowest precedence.
+    fn parse_binary(&mut self) -> Result<Ltl, ParseLtlError> {
+        let left = self.parse_disjunction()?;
+        match self.peek() {
+            Some(&Token::Binary(op)) if !matches!(op, BinaryOp::And | BinaryOp::Or) => {
+                self.index += 1;
+                self.descend()?;
+                let right = self.parse_binary()?;
+                self.depth -= 1;
+                Ok(Ltl::Binary(op, Box::new(left), Box::new(right)))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn parse_disjunction(&mut self) -> Result<Ltl, ParseLtlError> {
+        let mut operands = vec![self.parse_conjunction()?];
+        while self.peek() == Some(&Token::Binary(BinaryOp::Or)) {
+            self.index += 1;
+            operands.push(self.parse_conjunction()?);
+        }
+        Ok(balanced(BinaryOp::Or, operands))
+    }
+
+    fn parse_conjunction(&mut self) -> Result<Ltl, ParseLtlError> {
+        let mut operands = vec![self.parse_unary()?];
+        while self.peek() == Some(&Token::Binary(BinaryOp::And)) {
+            self.index += 1;
+            operands.push(self.parse_unary()?);
+        }
+        Ok(balanced(BinaryOp::And, operands))
+    }
+
+    fn parse_unary(&mut self) -> Result<Ltl, ParseLtlError> {
+        let position = self.position();
+        let token = self
+            .tokens
+            .get(self.index)
+            .map(|(_, token)| token.clone())
+            .ok_or_else(|| ParseLtlError::new(position, "unexpected end of formula"))?;
+        self.index += 1;
+        match token {
+            Token::Not | Token::Unary(_) => {
+                self.descend()?;
+                let operand = Box::new(self.parse_unary()?);
+                self.depth -= 1;
+                Ok(match token {
+                    Token::Unary(op) => Ltl::Unary(op, operand),
+                    _ => Ltl::Not(operand),
+                })
+            }
+            Token::Const(value) => Ok(Ltl::Const(value)),
+            Token::Atom(a) => Ok(Ltl::Atom(a)),
+            Token::LeftParen => {
+                self.descend()?;
+                let formula = self.parse_binary()?;
+                self.depth -= 1;
+                if self.peek() == Some(&Token::RightParen) {
+                    self.index += 1;
+                    Ok(formula)
+                } else {
+                    Err(ParseLtlError::new(self.position(), "expected ')'"))
+                }
+            }
+            Token::RightParen | Token::Binary(_) => {
+                Err(ParseLtlError::new(position, "expected a formula"))
+            }
+        }
+    }
+}
+
+/// Combines the non-empty list of operands with the associative operator into a
+/// balanced tree, such that the depth of the tree is logarithmic in the number of operands.
+fn balanced(op: BinaryOp, mut operands: Vec<Ltl>) -> Ltl {
+    if operands.len() == 1 {
+        return operands.pop().unwrap();
+    }
+    let right = operands.split_off(operands.len() / 2);
+    Ltl::Binary(
+        op,
+        Box::new(balanced(op, operands)),
+        Box::new(balanced(op, right)),
+    )
+}
+
+impl std::str::FromStr for Ltl {
+    type Err = ParseLtlError;
+
+    fn from_str(ltl: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(ltl)?,
+            index: 0,
+            end: ltl.len(),
+            depth: 0,
+        };
+        let formula = parser.parse_binary()?;
+        if parser.index < parser.tokens.len() {
+            return Err(ParseLtlError::new(parser.position(), "unexpected token"));
+        }
+        Ok(formula)
+    }
+}
+
+/// Tests for parsing LTL formulas.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use owl::graal::Vm;
+
+    use crate::parity::game::generator::SplitMix64;
+
+    /// Test that operators are parsed with their precedence and associativity.
+    #[test]
+    fn test_parse_precedence() {
+        let formula: Ltl = "G (r -> F g) & a | !b U c W d".parse().unwrap();
+        assert_eq!(
+            formula.to_string(),
+            "(((G (r -> (F g))) & a) | (!b)) U (c W d)"
+        );
+        let formula: Ltl = "G F a && X X\"b c\" || ff".parse().unwrap();
+        assert_eq!(formula.to_string(), "((G (F a)) & (X (X \"b c\"))) | false");
+        let formula: Ltl = "[](a <-> <>b) ^ tt".parse().unwrap();
+        assert_eq!(formula.to_string(), "(G (a <-> (F b))) xor true");
+    }
+
+    /// Test that malformed formulas are rejected with the position of the error.
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("a &".parse::<Ltl>().unwrap_err().position(), 3);
+        assert_eq!("(a | b".parse::<Ltl>().unwrap_err().position(), 6);
+        assert_eq!("a b".parse::<Ltl>().unwrap_err().position(), 2);
+        assert_eq!("a $ b".parse::<Ltl>().unwrap_err().position(), 2);
+        assert_eq!("\"a".parse::<Ltl>().unwrap_err().position(), 0);
+        assert_eq!("G 1a".parse::<Ltl>().unwrap_err().position(), 3);
+        assert_eq!("G 2".parse::<Ltl>().unwrap_err().position(), 2);
+    }
+
+    /// Test that words are only read as operators if the whole word is an operator.
+    #[test]
+    fn test_parse_words() {
+        let formula: Ltl = "Req R eq & GFa".parse().unwrap();
+        assert_eq!(formula.to_string(), "\"Req\" R (eq & \"GFa\")");
+        assert_eq!(formula.propositions(), vec!["Req", "eq", "GFa"]);
+        let formula: Ltl = "G(F_1)".parse().unwrap();
+        assert_eq!(formula.to_string(), "G \"F_1\"");
+    }
+
+    /// Test that deeply nested formulas are rejected instead of overflowing the stack.
+    #[test]
+    fn test_parse_nesting_depth() {
+        let nested = |prefix: &str, infix: &str, suffix: &str, depth: usize| {
+            format!(
+                "{}a{}{}",
+                prefix.repeat(depth),
+                infix.repeat(depth),
+                suffix.repeat(depth)
+            )
+        };
+        for &(prefix, infix, suffix) in &[
+            ("(", "", ")"),
+            ("! ", "", ""),
+            ("X ", "", ""),
+            ("", " U a", ""),
+            ("a U ", "", ""),
+        ] {
+            assert!(nested(prefix, infix, suffix, MAX_NESTING_DEPTH)
+                .parse::<Ltl>()
+                .is_ok());
+            let error = nested(prefix, infix, suffix, 100_000)
+                .parse::<Ltl>()
+                .unwrap_err();
+            assert!(error.to_string().starts_with("formula nested deeper than"));
+        }
+        for infix in &[" & a", " | a"] {
+            let formula: Ltl = nested("", infix, "", 100_000).parse().unwrap();
+            assert_eq!(formula.size(), 200_001);
+            assert_eq!(
+                formula.conjuncts().len(),
+                if *infix == " & a" { 100_001 } else { 1 }
+            );
+        }
+    }
+
+    /// Test the metrics and the fragments of formulas.
+    #[test]
+    fn test_metrics_and_fragments() {
+        let formula: Ltl = "G (r -> X (g W r)) & F a".parse().unwrap();
+        assert_eq!(formula.size(), 10);
+        assert_eq!(formula.temporal_operators(), 4);
+        assert_eq!(formula.temporal_depth(), 3);
+        assert_eq!(formula.propositions(), vec!["r", "g", "a"]);
+        assert_eq!(formula.conjuncts().len(), 2);
+        assert!(!formula.is_safety(true));
+        assert!(!formula.is_cosafety(true));
+
+        let safety: Ltl = "G (r -> X !F g) & !(a U b)".parse().unwrap();
+        assert!(safety.is_safety(true));
+        assert!(!safety.is_cosafety(true));
+        let cosafety: Ltl = "!G (a R b) | F (a M b)".parse().unwrap();
+        assert!(cosafety.is_cosafety(true));
+        assert!(!cosafety.is_safety(true));
+        let both: Ltl = "X a <-> b".parse().unwrap();
+        assert!(both.is_safety(true) && both.is_cosafety(true));
+        let neither: Ltl = "G a <-> b".parse().unwrap();
+        assert!(!neither.is_safety(true) && !neither.is_cosafety(true));
+    }
+
+    /// Test the levels of formulas in the syntactic future hierarchy.
+    #[test]
+    fn test_hierarchy_levels() {
+        let levels = |ltl: &str| ltl.parse::<Ltl>().unwrap().hierarchy_levels(true);
+        assert_eq!(levels("a & X b"), (0, 0));
+        assert_eq!(levels("G (r -> X g)"), (2, 1));
+        assert_eq!(levels("!(a W b)"), (1, 2));
+        assert_eq!(levels("G a | F b"), (2, 2));
+        assert_eq!(levels("G (r -> F g)"), (3, 2));
+        assert_eq!(levels("F G a"), (2, 3));
+        assert_eq!(levels("G F a -> G F b"), (3, 3));
+        assert_eq!(levels("G F a <-> b"), (3, 3));
+    }
+
+    /// Returns one of the given choices at random.
+    fn pick(random: &mut SplitMix64, choices: &[&str]) -> String {
+        choices[random.below(choices.len())].to_owned()
+    }
+
+    /// Appends the tokens of a random formula with at most the given depth, where the
+    /// formula is enclosed in parentheses if it binds weaker than the given level.
+    fn random_tokens(
+        random: &mut SplitMix64,
+        depth: usize,
+        level: usize,
+        tokens: &mut Vec<String>,
+    ) {
+        let kind = if depth == 0 {
+            random.below(2)
+        } else {
+            random.below(7)
+        };
+        // the levels of the operator and of its operands, from weakest to strongest binding
+        let (own, left, right) = match kind {
+            0..=3 => (3, 3, 3),
+            4 => (2, 2, 3),
+            5 => (1, 1, 2),
+            _ => (0, 1, 0),
+        };
+        let parenthesized = own < level || random.below(8) == 0;
+        if parenthesized {
+            tokens.push("(".to_owned());
+        }
+        match kind {
+            0 => tokens.push(pick(random, &["a", "b", "Req", "GFa"])),
+            1 => tokens.push(pick(random, &["true", "tt", "1", "false", "ff", "0"])),
+            2 | 3 => {
+                tokens.push(if kind == 2 {
+                    pick(random, &["!", "~"])
+                } else {
+                    pick(random, &["X", "F", "<>", "G", "[]"])
+                });
+                random_tokens(random, depth - 1, right, tokens);
+            }
+            _ => {
+                random_tokens(random, depth - 1, left, tokens);
+                tokens.push(match kind {
+                    4 => pick(random, &["&", "&&"]),
+                    5 => pick(random, &["|", "||"]),
+                    _ => pick(
+                        random,
+                        &["->", "=>", "<->", "<=>", "^", "xor", "U", "W", "R", "M"],
+                    ),
+                });
+                random_tokens(random, depth - 1, right, tokens);
+            }
+        }
+        if parenthesized {
+            tokens.push(")".to_owned());
+        }
+    }
+
+    /// Test that the parser agrees with the parser of Owl on random formulas, which
+    /// are written with few parentheses and sometimes without spaces between tokens.
+    #[test]
+    fn test_parse_agrees_with_owl() {
+        let vm = Vm::new().unwrap();
+        let mut random = SplitMix64::new(0);
+        for _ in 0..1000 {
+            let mut tokens = Vec::new();
+            random_tokens(&mut random, 4, 0, &mut tokens);
+            let mut text = String::new();
+            for token in tokens {
+                if !text.is_empty() && random.below(4) != 0 {
+                    text.push(' ');
+                }
+                text.push_str(&token);
+            }
+
+            match text.parse::<Ltl>() {
+                Ok(formula) => {
+                    let ap = formula.propositions();
+                    let owl_formula = owl::formula::Ltl::try_parse(&vm, &text, &ap)
+                        .unwrap_or_else(|_| panic!("rejected by Owl: {}", text));
+                    let printed = owl::formula::Ltl::parse(&vm, &formula.to_string(), &ap);
+                    assert_eq!(printed.to_string(), owl_formula.to_string(), "{}", text);
+                }
+                Err(_) => {
+                    let ap = ["a", "b", "Req", "GFa"];
+                    let owl_formula = owl::formula::Ltl::try_parse(&vm, &text, &ap);
+                    assert!(owl_formula.is_err(), "accepted by Owl: {}", text);
+                }
+            }
+        }
+    }
+}
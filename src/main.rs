@@ -1,21 +1,166 @@
 //! Strix binary crate.
 
-use std::io::{self, Write};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::path::Path;
 
-use clap::Clap;
+use clap::{FromArgMatches, IntoApp};
 use fs_err as fs;
+use log::info;
 
-use strix::options::{CliOptions, OutputFormat, SynthesisOptions, TraceLevel};
-use strix::synthesize_with;
+use strix::analysis::{analyze_automaton, analyze_formula, analyze_syntax};
+use strix::config::{Config, DEFAULT_CONFIG_FILE};
+use strix::controller::AigerController;
+use strix::options::{
+    AigerCompression, AnalyzeOptions, CliOptions, Command, CompressOptions, ConvertOptions,
+    Deadlocks, FuzzOptions, InputFormat, OutputFormat, ReduceGameOptions, SelftestOptions,
+    SimulateOptions, SolveGameOptions, SynthesisOptions, TraceLevel, WeakenOptions,
+};
+use strix::params::{expand_parameters, parse_assignment};
+use strix::parity::fuzz::{fuzz_solvers, reduce_failure, FuzzConfig};
+use strix::parity::game::binary::is_binary_game;
+use strix::parity::game::{Game, LabelledGame, NodeIndex};
+use strix::parity::solution::solve_game;
+use strix::parity::WinningRegion;
+use strix::selftest::{selftest, SelftestConfig};
+use strix::{
+    convert_controller, synthesize_monitor_with_callback, try_synthesize_with_callback,
+    weaken_assumptions, Controller, Status, SynthesisResult,
+};
+
+/// The bound on the node indices in a file with hints for the winning regions.
+const MAX_HINT_NODES: usize = 1 << 26;
 
 fn main() {
-    if let Err(error) = strix_main() {
+    let result = parse_command(command_arguments(std::env::args().collect())).and_then(
+        |(command, config)| match command {
+            Command::Synthesize(options) => synthesize_command(options, config, false),
+            Command::Check(options) => synthesize_command(options, config, true),
+            Command::Compress(options) => compress_main(options),
+            Command::Convert(options) => convert_main(options),
+            Command::SolvePg(options) => solve_pg_main(options),
+            Command::Simulate(options) => simulate_main(options),
+            Command::FuzzSolvers(options) => fuzz_main(options),
+            Command::ReducePg(options) => reduce_pg_main(options),
+            Command::Analyze(options) => analyze_main(options),
+            Command::Weaken(options) => weaken_main(options),
+            Command::Selftest(options) => selftest_main(options),
+            #[cfg(feature = "bench")]
+            Command::Bench(options) => bench_main(options),
+        },
+    );
+    if let Err(error) = result {
         // discard result as we cannot further propagate a write error
         let _ = write!(io::stderr(), "Error: {}", error);
         std::process::exit(1);
     }
 }
 
+/// Returns the arguments with the default command for synthesis inserted after the
+/// name of the program, unless they start with a command or ask for help or the version.
+fn command_arguments(mut args: Vec<String>) -> Vec<String> {
+    let app = Command::into_app();
+    let has_command = args.get(1).map_or(false, |arg| {
+        ["help", "-h", "--help", "-V", "--version"].contains(&arg.as_str())
+            || app
+                .get_subcommands()
+                .any(|command| command.get_name() == arg)
+    });
+    if !has_command {
+        args.insert(args.len().min(1), Command::DEFAULT.to_string());
+    }
+    args
+}
+
+/// Parses the command from the given arguments, where the first argument is the name
+/// of the program and the second the name of the command.
+///
+/// The options of the commands for synthesis are merged with the options of the
+/// configuration file given with `--config`, or of the default configuration file
+/// if it exists. For these commands, the effective configuration of all options
+/// is returned together with the command.
+///
+/// # Errors
+///
+/// Returns an error if the configuration file could not be read or is not valid.
+fn parse_command(mut args: Vec<String>) -> io::Result<(Command, Option<Config>)> {
+    // the commands for synthesis are those with the options for a configuration file
+    let synthesis = args.get(1).map_or(false, |name| {
+        Command::into_app().get_subcommands().any(|command| {
+            command.get_name() == name
+                && command
+                    .get_arguments()
+                    .any(|arg| arg.get_name() == "config-file")
+        })
+    });
+    if synthesis {
+        let config_args = configuration_arguments(&args[2..])?;
+        args.splice(2..2, config_args);
+    }
+    let matches = Command::into_app().get_matches_from(&args);
+    let config = if synthesis {
+        matches
+            .subcommand_matches(&args[1])
+            .map(|matches| Config::from_matches(&CliOptions::into_app(), matches))
+    } else {
+        None
+    };
+    Ok((Command::from_arg_matches(&matches), config))
+}
+
+/// Returns the arguments for the options of the configuration file given with `--config`
+/// in the given options for synthesis, or of the default configuration file if it exists.
+///
+/// # Errors
+///
+/// Returns an error if the configuration file could not be read or is not valid.
+fn configuration_arguments(args: &[String]) -> io::Result<Vec<String>> {
+    let mut config_file = None;
+    for (i, arg) in args.iter().enumerate() {
+        if arg == "--" {
+            break;
+        } else if arg == "--config" {
+            config_file = args.get(i + 1).cloned();
+        } else if let Some(file) = arg.strip_prefix("--config=") {
+            config_file = Some(file.to_string());
+        }
+    }
+    if config_file.is_none() && Path::new(DEFAULT_CONFIG_FILE).is_file() {
+        config_file = Some(DEFAULT_CONFIG_FILE.to_string());
+    }
+
+    match config_file {
+        Some(config_file) => {
+            let invalid_config = |e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", config_file, e),
+                )
+            };
+            let config: Config = fs::read_to_string(&config_file)?
+                .parse()
+                .map_err(invalid_config)?;
+            config
+                .arguments(&CliOptions::into_app(), args)
+                .map_err(invalid_config)
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Runs the synthesis with the given options, or prints the effective configuration
+/// if requested.
+fn synthesize_command(
+    options: CliOptions,
+    config: Option<Config>,
+    only_realizability: bool,
+) -> io::Result<()> {
+    match config {
+        Some(config) if options.print_config => write!(io::stdout(), "{}", config),
+        _ => synthesize_main(options, only_realizability),
+    }
+}
+
 /// Initialize the logging framework with the given trace level.
 ///
 /// # Errors
@@ -29,20 +174,313 @@ fn initialize_logging(level: TraceLevel) -> io::Result<()> {
         .map_err(|e| io::Error::new(io::ErrorKind::AlreadyExists, e))
 }
 
-/// Main function that parses the options, reads the input,
-/// calls the synthesis procedure and writes the output.
+/// Reads the contents of the given input file, or of standard input if no file is given.
 ///
 /// # Errors
 ///
-/// Returns an error if an I/O error occurred, e.g. from opening a file.
-fn strix_main() -> io::Result<()> {
-    let options = CliOptions::parse();
+/// Returns an error if the input could not be read.
+fn read_input(input_file: Option<&str>) -> io::Result<String> {
+    match input_file {
+        Some(input_file) => fs::read_to_string(input_file),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+/// Reads a parity game in PGSolver format or in binary format from the given input file,
+/// or from standard input if no file is given, where deadlocks are completed according
+/// to the given interpretation.
+///
+/// # Errors
+///
+/// Returns an error if the input could not be read or is not a valid parity game.
+fn read_game(
+    input_file: Option<&str>,
+    deadlocks: Deadlocks,
+) -> io::Result<LabelledGame<NodeIndex>> {
+    let input = match input_file {
+        Some(input_file) => fs::read(input_file)?,
+        None => {
+            let mut input = Vec::new();
+            io::stdin().read_to_end(&mut input)?;
+            input
+        }
+    };
+    let mut game = if is_binary_game(&input) {
+        LabelledGame::read_binary(input.as_slice())?
+    } else {
+        String::from_utf8(input)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+    };
+    let num_deadlocks = game.complete_deadlocks(deadlocks.winner());
+    if num_deadlocks > 0 {
+        info!(
+            "Completed {} deadlocks as won by the {} player",
+            num_deadlocks,
+            deadlocks.winner()
+        );
+    }
+    Ok(game)
+}
+
+/// Returns a buffered writer to the given output file, or to standard output
+/// if no file is given.
+///
+/// # Errors
+///
+/// Returns an error if the output file could not be created.
+fn output_writer(output_file: Option<&str>) -> io::Result<io::BufWriter<Box<dyn Write>>> {
+    let writer: Box<dyn Write> = match output_file {
+        Some(output_file) => Box::new(fs::File::create(output_file)?),
+        None => Box::new(io::stdout()),
+    };
+    Ok(io::BufWriter::new(writer))
+}
+
+/// Reads an aiger controller from the given file.
+///
+/// # Errors
+///
+/// Returns an error if the file could not be read or does not contain a valid circuit.
+fn read_aiger(input_file: &str) -> io::Result<AigerController> {
+    AigerController::read(io::BufReader::new(fs::File::open(input_file)?))
+}
+
+/// Main function for fuzzing the parity game solvers on random games.
+///
+/// # Errors
+///
+/// Returns an error if the solvers disagree on a game or produce an invalid strategy.
+fn fuzz_main(options: FuzzOptions) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
+
+    let report = fuzz_solvers(&FuzzConfig::from(&options))
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    write!(io::stdout(), "{}", report)
+}
+
+/// Main function for testing the synthesis procedure on random specifications.
+///
+/// # Errors
+///
+/// Returns an error if configurations disagree on a specification, bounded synthesis
+/// contradicts a status or the synthesis fails.
+fn selftest_main(options: SelftestOptions) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
+
+    let config = SelftestConfig::try_from(&options).unwrap_or_else(|err| err.exit());
+    let report = selftest(&config).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    write!(io::stdout(), "{}", report)
+}
+
+/// Main function for measuring the deterministic benchmarks.
+///
+/// # Errors
+///
+/// Returns an error if the output could not be written.
+#[cfg(feature = "bench")]
+fn bench_main(options: strix::options::BenchOptions) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
+
+    let mut stdout = io::stdout();
+    for measurement in strix::bench::run_all(options.runs.get()) {
+        writeln!(
+            stdout,
+            "{}: min {:.3}s, mean {:.3}s, checksum {}",
+            measurement.name,
+            measurement.min.as_secs_f64(),
+            measurement.mean.as_secs_f64(),
+            measurement.checksum
+        )?;
+    }
+    Ok(())
+}
+
+/// Main function for compressing an aiger circuit.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurred or the circuit is malformed.
+fn compress_main(options: CompressOptions) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
+
+    let mut controller = read_aiger(&options.input_file)?;
+    match options.aiger_compression {
+        AigerCompression::None => (),
+        AigerCompression::Basic => controller.compress(false, false, None),
+        AigerCompression::More => controller.compress(true, false, None),
+        AigerCompression::Sequential => controller.compress(true, true, None),
+    }
+    let mut writer = output_writer(options.output_file.as_deref())?;
+    controller.write(&mut writer, options.binary)?;
+    writer.flush()
+}
+
+/// Main function for converting a machine or an aiger circuit to another output format,
+/// optionally removing the symbol table of a circuit or adding comments.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurred, the controller is malformed or can not
+/// be converted to the output format.
+fn convert_main(options: ConvertOptions) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
+
+    let input = fs::read(&options.input_file)?;
+    // keep the format of the input by default
+    let output_format = options.output_format.unwrap_or_else(|| {
+        let text = String::from_utf8_lossy(&input);
+        let text = text.trim_start();
+        if text.starts_with("HOA:") {
+            OutputFormat::Hoa
+        } else if text.starts_with('.') || text.starts_with('#') {
+            OutputFormat::Kiss2
+        } else if options.binary {
+            OutputFormat::Aig
+        } else {
+            OutputFormat::Aag
+        }
+    });
+    let synthesis_options = SynthesisOptions {
+        output_format,
+        machine_minimization: options.machine_minimization,
+        aiger_compression: options.aiger_compression.unwrap_or(AigerCompression::None),
+        ..SynthesisOptions::default()
+    };
+    let mut controller = convert_controller(&input, &synthesis_options)?;
+    if options.strip || !options.comments.is_empty() {
+        let aiger = match &mut controller {
+            Controller::Aiger(aiger) => aiger,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "options strip and comment can only be used for aiger circuits",
+                ))
+            }
+        };
+        if options.strip {
+            aiger.strip_symbols();
+        }
+        for comment in &options.comments {
+            if comment.contains('\n') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "comment must not contain a new line",
+                ));
+            }
+            aiger.add_comment(comment);
+        }
+    }
+    let binary = options.binary || output_format == OutputFormat::Aig;
+    // the status is only used for writing parity games
+    controller.write(
+        output_writer(options.output_file.as_deref())?,
+        Status::Realizable,
+        binary,
+    )
+}
+
+/// Main function for solving a parity game.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurred or the game is malformed.
+fn solve_pg_main(options: SolveGameOptions) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
+
+    let game = read_game(options.input_file.as_deref(), options.deadlocks)?;
+    let solution = solve_game(&game, options.parity_solver, options.oink_solver);
+    let mut writer = output_writer(options.output_file.as_deref())?;
+    write!(writer, "{}", solution)?;
+    writer.flush()
+}
+
+/// Main function for reducing a parity game on which the solvers fail to a small subgame.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurred, the game is malformed or the solvers
+/// do not fail on the game.
+fn reduce_pg_main(options: ReduceGameOptions) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
+
+    let game = read_game(options.input_file.as_deref(), options.deadlocks)?;
+    let (description, reduced) = reduce_failure(&game).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "solvers do not fail on game")
+    })?;
+    writeln!(
+        io::stderr(),
+        "{} on game, reduced from {} to {} nodes",
+        description,
+        game.num_nodes(),
+        reduced.num_nodes()
+    )?;
+    let mut writer = output_writer(options.output_file.as_deref())?;
+    if options.binary {
+        reduced.write_binary(writer)
+    } else {
+        write!(writer, "{}", reduced)?;
+        writer.flush()
+    }
+}
+
+/// Main function for analyzing a formula without synthesis.
+///
+/// # Errors
+///
+/// Returns an error if the formula could not be read or parsed.
+fn analyze_main(options: AnalyzeOptions) -> io::Result<()> {
     initialize_logging(options.trace_level)?;
 
-    // trim inputs and outputs
     let ins: Vec<_> = options.inputs.iter().map(|s| s.trim()).collect();
     let outs: Vec<_> = options.outputs.iter().map(|s| s.trim()).collect();
+    let ltl = if let Some(input_file) = &options.input_file {
+        fs::read_to_string(input_file)?
+    } else if let Some(formula) = &options.formula {
+        formula.clone()
+    } else {
+        unreachable!()
+    };
+    let analysis = if options.syntax_only {
+        analyze_syntax(&ltl, &ins, &outs)
+    } else {
+        analyze_formula(&ltl, &ins, &outs)
+    }
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    write!(io::stdout(), "{}", analysis)?;
+    if options.automaton || options.max_states.is_some() {
+        let analysis = analyze_automaton(
+            &ltl,
+            &ins,
+            &outs,
+            options.max_states,
+            &SynthesisOptions::default(),
+        )
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        write!(io::stdout(), "{}", analysis)?;
+    }
+    Ok(())
+}
+
+/// Main function for searching for assumptions that make a specification realizable.
+///
+/// Writes the found assumptions one per line, or a message if the specification is
+/// already realizable or no set of assumptions was found.
+///
+/// # Errors
+///
+/// Returns an error if the formula could not be read or an I/O error occurred.
+fn weaken_main(options: WeakenOptions) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
 
+    let ins: Vec<_> = options.inputs.iter().map(|s| s.trim()).collect();
+    let outs: Vec<_> = options.outputs.iter().map(|s| s.trim()).collect();
+    let templates: Vec<_> = options.templates.iter().map(|s| s.trim()).collect();
     let ltl = if let Some(input_file) = &options.input_file {
         fs::read_to_string(input_file)?
     } else if let Some(formula) = &options.formula {
@@ -50,28 +488,243 @@ fn strix_main() -> io::Result<()> {
     } else {
         unreachable!()
     };
+    let assumptions = weaken_assumptions(
+        &ltl,
+        &ins,
+        &outs,
+        &templates,
+        options.max_assumptions,
+        &SynthesisOptions::default(),
+    );
+    let mut stdout = io::stdout();
+    match assumptions {
+        Some(assumptions) if assumptions.is_empty() => {
+            writeln!(stdout, "Specification is already realizable")
+        }
+        Some(assumptions) => {
+            for assumption in assumptions {
+                writeln!(stdout, "{}", assumption)?;
+            }
+            Ok(())
+        }
+        None => writeln!(
+            stdout,
+            "No set of at most {} assumptions found",
+            options.max_assumptions
+        ),
+    }
+}
+
+/// Main function for simulating an aiger controller on a sequence of inputs.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurred, the circuit is malformed or an input
+/// does not match the number of inputs of the controller.
+fn simulate_main(options: SimulateOptions) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
+
+    let controller = read_aiger(&options.input_file)?;
+    let stimulus = read_input(options.stimulus_file.as_deref())?;
+    let mut inputs = Vec::new();
+    for line in stimulus.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        let valuation = line
+            .chars()
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid input value '{}'", c),
+                )),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        if valuation.len() != controller.num_inputs() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "expected {} input values, but got '{}'",
+                    controller.num_inputs(),
+                    line
+                ),
+            ));
+        }
+        inputs.push(valuation);
+    }
+
+    let mut writer = output_writer(None)?;
+    for outputs in controller.simulate(&inputs) {
+        let line: String = outputs.iter().map(|&v| if v { '1' } else { '0' }).collect();
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()
+}
+
+/// Main function that parses the options, reads the input,
+/// calls the synthesis procedure and writes the output.
+///
+/// If `only_realizability` is set, only the realizability of
+/// the specification is checked regardless of the options.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurred, e.g. from opening a file,
+/// or if the combination of options is not supported.
+fn synthesize_main(options: CliOptions, only_realizability: bool) -> io::Result<()> {
+    initialize_logging(options.trace_level)?;
+    if options.input_format == InputFormat::Aiger {
+        return synthesize_monitor_main(&options, only_realizability);
+    }
+
+    let source = if let Some(input_file) = &options.input_file {
+        fs::read_to_string(input_file)?
+    } else if let Some(formula) = &options.formula {
+        formula.clone()
+    } else {
+        unreachable!()
+    };
+
+    // expand parameters in the formula and the names of inputs and outputs
+    let invalid_input = |e| io::Error::new(io::ErrorKind::InvalidInput, e);
+    let overrides = options
+        .parameters
+        .iter()
+        .map(|assignment| parse_assignment(assignment))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(invalid_input)?;
+    let expanded = expand_parameters(&source, &overrides).map_err(invalid_input)?;
+    let ltl = expanded.formula();
+    // trim inputs and outputs
+    let ins = expanded
+        .expand_propositions(&options.inputs.iter().map(|s| s.trim()).collect::<Vec<_>>())
+        .map_err(invalid_input)?;
+    let outs = expanded
+        .expand_propositions(&options.outputs.iter().map(|s| s.trim()).collect::<Vec<_>>())
+        .map_err(invalid_input)?;
+    let ins: Vec<_> = ins.iter().map(String::as_str).collect();
+    let outs: Vec<_> = outs.iter().map(String::as_str).collect();
 
     let mut synthesis_options = SynthesisOptions::from(&options);
+    if only_realizability {
+        synthesis_options.only_realizability = true;
+    }
+    if let Some(hints_file) = &options.hints_file {
+        let hints = WinningRegion::parse(&fs::read_to_string(hints_file)?, MAX_HINT_NODES)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        synthesis_options.winning_hints = Some(hints);
+    }
+    if let Some(assumption_file) = &options.btor2_assumption_file {
+        synthesis_options.btor2_assumption = Some(read_aiger(assumption_file)?);
+    }
+    if let Some(repair_file) = &options.repair_file {
+        synthesis_options.repair_controller = Some(read_aiger(repair_file)?);
+    }
     // override output option for aiger portfolio option
     if synthesis_options.aiger_portfolio
         && !matches!(
             synthesis_options.output_format,
-            OutputFormat::Aag | OutputFormat::Aig
+            OutputFormat::Aag
+                | OutputFormat::Aig
+                | OutputFormat::Blif
+                | OutputFormat::Btor2
+                | OutputFormat::Verilog
         )
     {
         synthesis_options.output_format = OutputFormat::Aag;
     }
-    let result = synthesize_with(&ltl, &ins, &outs, &synthesis_options);
+    synthesis_options
+        .validate()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    // write the status as soon as it is known, before the controller is constructed
+    let mut status_written = Ok(());
+    let result = try_synthesize_with_callback(ltl, &ins, &outs, &synthesis_options, |status| {
+        status_written = write_status(status)
+    })
+    .map_err(invalid_input)?;
+    status_written?;
+    write_controllers(&result, &synthesis_options, options.output_file.as_deref())
+}
+
+/// Main function for synthesizing a controller for a safety monitor in aiger format,
+/// which is read from the input file of the options.
+///
+/// If `only_realizability` is set, only the realizability of
+/// the monitor is checked regardless of the options.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurred, no input file is given, the monitor
+/// is malformed or the combination of options is not supported.
+fn synthesize_monitor_main(options: &CliOptions, only_realizability: bool) -> io::Result<()> {
+    let input_file = options.input_file.as_ref().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "a safety monitor has to be read from a file",
+        )
+    })?;
+    let input = fs::read(input_file)?;
+
+    let mut synthesis_options = SynthesisOptions::from(options);
+    if only_realizability {
+        synthesis_options.only_realizability = true;
+    }
+    synthesis_options
+        .validate()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut status_written = Ok(());
+    let result = synthesize_monitor_with_callback(&input, &synthesis_options, |status| {
+        status_written = write_status(status)
+    })
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    status_written?;
+    write_controllers(&result, &synthesis_options, options.output_file.as_deref())
+}
+
+/// Writes the realizability status to standard output and flushes it.
+///
+/// # Errors
+///
+/// Returns an error if the status could not be written.
+fn write_status(status: Status) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    writeln!(stdout, "{}", status)?;
+    stdout.flush()
+}
 
-    writeln!(io::stdout(), "{}", result.status())?;
+/// Writes the controller of the result to the given output file, or to standard output
+/// if no file is given, and the controllers of the additional outputs to their files.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurred.
+fn write_controllers(
+    result: &SynthesisResult,
+    synthesis_options: &SynthesisOptions,
+    output_file: Option<&str>,
+) -> io::Result<()> {
     if let Some(controller) = result.controller() {
         let binary = synthesis_options.output_format == OutputFormat::Aig;
-        if let Some(output_file) = &options.output_file {
+        if let Some(output_file) = output_file {
             let file = fs::File::create(output_file)?;
             controller.write(file, result.status(), binary)?;
         } else {
             controller.write(io::stdout(), result.status(), binary)?;
         }
     }
+    for artifact in result.artifacts() {
+        let target = artifact.target();
+        let binary = target.format == OutputFormat::Aig;
+        if let Some(file) = &target.file {
+            let file = fs::File::create(file)?;
+            artifact.controller().write(file, result.status(), binary)?;
+        } else {
+            artifact
+                .controller()
+                .write(io::stdout(), result.status(), binary)?;
+        }
+    }
     Ok(())
 }
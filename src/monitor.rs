@@ -0,0 +1,460 @@
+//! Synthesis from safety monitors given as aiger circuits, as in the safety track of
+//! SYNTCOMP, where the game is constructed directly over the state space of the circuit
+//! without translating a formula with Owl.
+//!
+//! A monitor is an aiger circuit with a single output that signals a bad state. The
+//! inputs with a name starting with [`CONTROLLABLE_PREFIX`] are controlled by the system
+//! and all other inputs by the environment, and the system wins if the output is never
+//! set. The safety game is explored explicitly over the valuations of the latches that
+//! are reachable from their reset values, where in each step the environment first
+//! chooses the uncontrollable inputs and the system then chooses the controllable inputs.
+//!
+//! The game is solved with the parity game solvers, and a machine for the system that
+//! stays within the winning region is passed through the same stages as a machine for
+//! an LTL specification. An aiger circuit for the controller then drives the controllable
+//! inputs of the monitor, such that the result is again a monitor without controllable
+//! inputs whose output is never set.
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::io;
+
+use aiger::{aiger_lit2var, Aiger, SymbolKind, AIGER_FALSE, AIGER_TRUE};
+use cudd::{Bdd, Cudd};
+use log::info;
+
+use crate::controller::aiger::{evaluate, AigerController};
+use crate::controller::machine::{LabelledMachine, LabelledMachineConstructor, Transition};
+use crate::parity::game::{Game, LabelledGame, NodeIndex, Player};
+use crate::parity::solution::GameSolution;
+
+/// The prefix of the names of the inputs of a monitor that are controlled by the system.
+pub const CONTROLLABLE_PREFIX: &str = "controllable_";
+/// The maximal number of inputs of a monitor, as all valuations of the inputs are
+/// enumerated for each state of the game.
+pub const MAX_MONITOR_INPUTS: usize = 16;
+/// The maximal number of moves in the safety game of a monitor, that is the number of
+/// reachable valuations of the latches times the number of valuations of the inputs,
+/// as the successor of each move is stored explicitly.
+pub const MAX_MONITOR_MOVES: usize = 1 << 24;
+
+/// A safety monitor given as an aiger circuit with a single output for bad states.
+#[derive(Clone)]
+pub struct SafetyMonitor {
+    circuit: AigerController,
+    uncontrollable_inputs: Vec<String>,
+    controllable_inputs: Vec<String>,
+    /// The variables of the uncontrollable and the controllable inputs.
+    input_vars: (Vec<usize>, Vec<usize>),
+    /// The variable, the literal of the next value and the reset value of each latch.
+    latches: Vec<(usize, u32, bool)>,
+    /// The literal of the output for bad states.
+    bad: u32,
+    /// The inputs of the and gate for each variable defined by an and gate.
+    gates: Vec<Option<(u32, u32)>>,
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+impl SafetyMonitor {
+    /// Reads a safety monitor as an aiger circuit in ASCII or binary format from the
+    /// given reader, where unnamed inputs are uncontrollable and named `i<index>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during reading, the circuit is malformed,
+    /// does not have exactly one output, has inputs with the same name, has more than
+    /// [`MAX_MONITOR_INPUTS`] inputs or has latches without a reset value of zero or one.
+    pub fn read<R: io::Read>(reader: R) -> io::Result<Self> {
+        let aig = Aiger::read(reader)?
+            .renamed(|kind, index, name| match name {
+                Some(name) => Some(name.to_string()),
+                None if kind == SymbolKind::Input => Some(format!("i{}", index)),
+                None => None,
+            })
+            .map_err(invalid_data)?;
+        let outputs = aig.output_literals();
+        if outputs.len() != 1 {
+            return Err(invalid_data(format!(
+                "monitor has {} outputs instead of a single output for bad states",
+                outputs.len()
+            )));
+        }
+        let input_lits = aig.input_literals();
+        if input_lits.len() > MAX_MONITOR_INPUTS {
+            return Err(invalid_data(format!(
+                "monitor has {} inputs, but at most {} are supported",
+                input_lits.len(),
+                MAX_MONITOR_INPUTS
+            )));
+        }
+
+        let mut uncontrollable_inputs = Vec::new();
+        let mut controllable_inputs = Vec::new();
+        let mut input_vars = (Vec::new(), Vec::new());
+        let names = aig.symbol_names(SymbolKind::Input);
+        for (lit, name) in input_lits.into_iter().zip(names) {
+            let name = name.expect("inputs are named");
+            if uncontrollable_inputs.contains(&name) || controllable_inputs.contains(&name) {
+                return Err(invalid_data(format!("input {} given more than once", name)));
+            }
+            let var = aiger_lit2var(lit) as usize;
+            if name.starts_with(CONTROLLABLE_PREFIX) {
+                controllable_inputs.push(name);
+                input_vars.1.push(var);
+            } else {
+                uncontrollable_inputs.push(name);
+                input_vars.0.push(var);
+            }
+        }
+        let latches = aig
+            .latch_literals()
+            .into_iter()
+            .map(|(lit, next, reset)| match reset {
+                AIGER_FALSE | AIGER_TRUE => {
+                    Ok((aiger_lit2var(lit) as usize, next, reset == AIGER_TRUE))
+                }
+                _ => Err(invalid_data(format!(
+                    "latch {} has no reset value, but monitors require initialized latches",
+                    lit
+                ))),
+            })
+            .collect::<io::Result<_>>()?;
+        let mut gates = vec![None; aig.maxvar() as usize + 1];
+        for (lhs, rhs0, rhs1) in aig.and_literals() {
+            gates[aiger_lit2var(lhs) as usize] = Some((rhs0, rhs1));
+        }
+        Ok(Self {
+            circuit: AigerController::new(aig),
+            uncontrollable_inputs,
+            controllable_inputs,
+            input_vars,
+            latches,
+            bad: outputs[0],
+            gates,
+        })
+    }
+
+    /// Returns the names of the inputs controlled by the environment.
+    pub fn uncontrollable_inputs(&self) -> &[String] {
+        &self.uncontrollable_inputs
+    }
+
+    /// Returns the names of the inputs controlled by the system.
+    pub fn controllable_inputs(&self) -> &[String] {
+        &self.controllable_inputs
+    }
+
+    /// Returns the number of latches of the monitor.
+    pub fn num_latches(&self) -> usize {
+        self.latches.len()
+    }
+
+    /// Returns the monitor as aiger circuit.
+    pub fn circuit(&self) -> &AigerController {
+        &self.circuit
+    }
+
+    /// Evaluates the monitor for the given valuation of the latches and valuations of the
+    /// uncontrollable and controllable inputs, which have the value of the input with
+    /// index `i` in bit `i`, and returns whether the output is set together with the
+    /// next valuation of the latches.
+    fn step(
+        &self,
+        latch_values: &[bool],
+        uncontrollable: usize,
+        controllable: usize,
+    ) -> (bool, Vec<bool>) {
+        let mut values = vec![None; self.gates.len()];
+        values[0] = Some(false);
+        for (i, &var) in self.input_vars.0.iter().enumerate() {
+            values[var] = Some(uncontrollable >> i & 1 == 1);
+        }
+        for (i, &var) in self.input_vars.1.iter().enumerate() {
+            values[var] = Some(controllable >> i & 1 == 1);
+        }
+        for (&(var, _, _), &value) in self.latches.iter().zip(latch_values) {
+            values[var] = Some(value);
+        }
+        let bad = evaluate(self.bad, &self.gates, &mut values);
+        let next = self
+            .latches
+            .iter()
+            .map(|&(_, next, _)| evaluate(next, &self.gates, &mut values))
+            .collect();
+        (bad, next)
+    }
+
+    /// Explores the safety game of the monitor over the valuations of the latches that
+    /// are reachable from the reset values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the game has more than [`MAX_MONITOR_MOVES`] moves.
+    pub(crate) fn explore(&self) -> io::Result<SafetyGame> {
+        let num_uncontrollable = 1 << self.uncontrollable_inputs.len();
+        let num_controllable = 1 << self.controllable_inputs.len();
+        let max_states = MAX_MONITOR_MOVES / (num_uncontrollable * num_controllable);
+        let reset: Vec<_> = self.latches.iter().map(|&(_, _, reset)| reset).collect();
+        let mut indices = HashMap::new();
+        indices.insert(reset.clone(), 0);
+        let mut states = vec![reset];
+        let mut successors = Vec::new();
+        while successors.len() < states.len() {
+            if states.len() > max_states {
+                return Err(invalid_data(format!(
+                    "monitor has more than {} reachable states with {} inputs",
+                    max_states,
+                    self.uncontrollable_inputs.len() + self.controllable_inputs.len()
+                )));
+            }
+            let latch_values = states[successors.len()].clone();
+            let state_successors: Vec<Vec<_>> = (0..num_uncontrollable)
+                .map(|uncontrollable| {
+                    (0..num_controllable)
+                        .map(|controllable| {
+                            let (bad, next) =
+                                self.step(&latch_values, uncontrollable, controllable);
+                            if bad {
+                                return None;
+                            }
+                            Some(match indices.entry(next) {
+                                Entry::Occupied(entry) => *entry.get(),
+                                Entry::Vacant(entry) => {
+                                    states.push(entry.key().clone());
+                                    *entry.insert(states.len() - 1)
+                                }
+                            })
+                        })
+                        .collect()
+                })
+                .collect();
+            successors.push(state_successors);
+        }
+        info!(
+            "Explored {} states of the monitor with {} latches",
+            states.len(),
+            self.num_latches()
+        );
+        Ok(SafetyGame::new(successors))
+    }
+
+    /// Constructs a Mealy machine for the system from the solution of the safety game,
+    /// which reads the uncontrollable inputs, writes the controllable inputs and allows
+    /// all moves that stay within the winning region of the system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initial state is not won by the system.
+    pub(crate) fn machine(
+        &self,
+        game: &SafetyGame,
+        solution: &GameSolution,
+    ) -> LabelledMachine<usize> {
+        let winning = |state: usize| solution.winner(game.state_nodes[state]) == Some(Player::Even);
+        assert!(winning(0), "initial state not won by the system");
+
+        let num_uncontrollable = self.uncontrollable_inputs.len();
+        let num_controllable = self.controllable_inputs.len();
+        let manager = Cudd::with_vars(num_uncontrollable + num_controllable).unwrap();
+        let cube = |valuation: usize, offset: usize, len: usize| {
+            let mut bdd = manager.bdd_one();
+            for i in 0..len {
+                let var = manager.bdd_var(offset + i);
+                if valuation >> i & 1 == 1 {
+                    bdd &= var;
+                } else {
+                    bdd &= !var;
+                }
+            }
+            bdd
+        };
+
+        let mut constructor = LabelledMachineConstructor::new();
+        let (initial_state, _) = constructor.add_state(0);
+        let mut queue = vec![0];
+        while let Some(state) = queue.pop() {
+            let index = constructor.add_state(state).0;
+            // uncontrollable inputs with the same winning moves share a transition
+            let mut groups: Vec<(Vec<(usize, Vec<usize>)>, Bdd)> = Vec::new();
+            for (uncontrollable, choices) in game.successors[state].iter().enumerate() {
+                let mut moves: Vec<(usize, Vec<usize>)> = Vec::new();
+                for (controllable, &successor) in choices.iter().enumerate() {
+                    match successor {
+                        Some(successor) if winning(successor) => {
+                            match moves.iter_mut().find(|(s, _)| *s == successor) {
+                                Some((_, valuations)) => valuations.push(controllable),
+                                None => moves.push((successor, vec![controllable])),
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                let input = cube(uncontrollable, 0, num_uncontrollable);
+                match groups.iter_mut().find(|(m, _)| *m == moves) {
+                    Some((_, group_input)) => *group_input |= input,
+                    None => groups.push((moves, input)),
+                }
+            }
+            for (moves, input) in groups {
+                let mut transition = Transition::new(input);
+                for (successor, valuations) in moves {
+                    let mut output = manager.bdd_zero();
+                    for valuation in valuations {
+                        output |= cube(valuation, num_uncontrollable, num_controllable);
+                    }
+                    let (successor_index, new_state) = constructor.add_state(successor);
+                    if new_state {
+                        queue.push(successor);
+                    }
+                    transition.add_output(output, successor_index);
+                }
+                constructor.add_transition(index, transition);
+            }
+        }
+        constructor.into_machine(
+            vec![initial_state],
+            self.uncontrollable_inputs.clone(),
+            self.controllable_inputs.clone(),
+            true,
+        )
+    }
+}
+
+/// The explicit safety game of a monitor, where each state is a valuation of the latches
+/// that is owned by the environment, with a successor owned by the system for each
+/// valuation of the uncontrollable inputs, and all moves that set the output lead to a
+/// sink that is won by the environment.
+pub(crate) struct SafetyGame {
+    game: LabelledGame<NodeIndex>,
+    /// The node of the game for each state.
+    state_nodes: Vec<NodeIndex>,
+    /// The successor state for each state, valuation of the uncontrollable inputs and
+    /// valuation of the controllable inputs, or `None` if the output is set, where the
+    /// state with the reset values of the latches has index 0.
+    successors: Vec<Vec<Vec<Option<usize>>>>,
+}
+
+impl SafetyGame {
+    fn new(successors: Vec<Vec<Vec<Option<usize>>>>) -> Self {
+        let mut game = LabelledGame::default();
+        let add_node = |game: &mut LabelledGame<NodeIndex>, owner, color| {
+            let (index, _) = game.add_border_node(game.num_nodes());
+            game.update_node(index, owner, color);
+            index
+        };
+        let sink = add_node(&mut game, Player::Odd, 1);
+        game.add_edge(sink, sink);
+        let state_nodes: Vec<_> = successors
+            .iter()
+            .map(|_| add_node(&mut game, Player::Odd, 0))
+            .collect();
+        for (&state_node, state_successors) in state_nodes.iter().zip(&successors) {
+            for choices in state_successors {
+                let choice_node = add_node(&mut game, Player::Even, 0);
+                game.add_edge(state_node, choice_node);
+                let mut targets = Vec::new();
+                for successor in choices {
+                    let target = successor.map_or(sink, |successor| state_nodes[successor]);
+                    if !targets.contains(&target) {
+                        targets.push(target);
+                        game.add_edge(choice_node, target);
+                    }
+                }
+            }
+        }
+        game.set_initial_node(state_nodes[0]);
+        Self {
+            game,
+            state_nodes,
+            successors,
+        }
+    }
+
+    /// Returns the parity game, which is complete and has the state with the reset
+    /// values of the latches as initial node.
+    pub(crate) fn game(&self) -> &LabelledGame<NodeIndex> {
+        &self.game
+    }
+}
+
+/// Tests for synthesis from safety monitors.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Solver;
+    use crate::parity::solution::solve_game;
+
+    /// A monitor that is set one step after input `a` is set without `controllable_b`.
+    const MONITOR: &str = "aag 4 2 1 1 1\n2\n4\n6 8\n6\n8 2 5\ni0 a\ni1 controllable_b\no0 bad\n";
+
+    /// Test that the safety game of a realizable monitor is won by the system and that
+    /// the machine allows exactly the winning moves.
+    #[test]
+    fn test_realizable_monitor() {
+        let monitor = SafetyMonitor::read(MONITOR.as_bytes()).unwrap();
+        assert_eq!(monitor.uncontrollable_inputs(), &["a".to_string()]);
+        assert_eq!(
+            monitor.controllable_inputs(),
+            &["controllable_b".to_string()]
+        );
+        let game = monitor.explore().unwrap();
+        assert_eq!(game.state_nodes.len(), 2);
+        assert_eq!(game.game().num_nodes(), 7);
+        let solution = solve_game(game.game(), Solver::Fpi, None);
+        assert_eq!(solution.winner(game.state_nodes[0]), Some(Player::Even));
+        assert_eq!(solution.winner(game.state_nodes[1]), Some(Player::Odd));
+        let machine = monitor.machine(&game, &solution);
+        assert_eq!(machine.num_states(), 1);
+    }
+
+    /// Test that a monitor set directly by an uncontrollable input is unrealizable.
+    #[test]
+    fn test_unrealizable_monitor() {
+        let aag = "aag 2 2 0 1 0\n2\n4\n2\ni0 a\ni1 controllable_b\no0 bad\n";
+        let monitor = SafetyMonitor::read(aag.as_bytes()).unwrap();
+        let game = monitor.explore().unwrap();
+        let solution = solve_game(game.game(), Solver::Fpi, None);
+        assert_eq!(solution.winner(game.state_nodes[0]), Some(Player::Odd));
+    }
+
+    /// Test that monitors without a single output, with duplicate inputs or with
+    /// uninitialized latches are rejected.
+    #[test]
+    fn test_invalid_monitor() {
+        let aag = "aag 1 1 0 2 0\n2\n2\n3\n";
+        assert!(SafetyMonitor::read(aag.as_bytes()).is_err());
+        let aag = "aag 2 2 0 1 0\n2\n4\n2\ni0 a\ni1 a\n";
+        assert!(SafetyMonitor::read(aag.as_bytes()).is_err());
+        let aag = "aag 1 1 0 1 0\n2\n2\n";
+        let monitor = SafetyMonitor::read(aag.as_bytes()).unwrap();
+        assert_eq!(monitor.uncontrollable_inputs(), &["i0".to_string()]);
+        // a latch that is its own reset value is uninitialized
+        let aag = "aag 1 0 1 1 0\n2 3 2\n2\n";
+        assert!(SafetyMonitor::read(aag.as_bytes()).is_err());
+    }
+
+    /// Test that the exploration of a monitor with too many reachable states fails.
+    #[test]
+    fn test_monitor_state_limit() {
+        // each latch stores an input, so all valuations of the latches are reachable
+        let num_inputs = MAX_MONITOR_INPUTS;
+        let num_latches = 9;
+        let mut aag = format!(
+            "aag {} {} {} 1 0\n",
+            num_inputs + num_latches,
+            num_inputs,
+            num_latches
+        );
+        for i in 1..=num_inputs {
+            aag.push_str(&format!("{}\n", 2 * i));
+        }
+        for i in 1..=num_latches {
+            aag.push_str(&format!("{} {}\n", 2 * (num_inputs + i), 2 * i));
+        }
+        aag.push_str("0\n");
+        let monitor = SafetyMonitor::read(aag.as_bytes()).unwrap();
+        assert!(monitor.explore().is_err());
+    }
+}
@@ -1,10 +1,16 @@
 //! Options for the synthesis procedure.
 
+use std::convert::TryFrom;
 use std::fmt;
 use std::str::FromStr;
 
 use clap::{ArgGroup, Clap, Error, ErrorKind};
 
+use crate::controller::AigerController;
+use crate::parity::game::Player;
+use crate::parity::WinningRegion;
+use crate::SynthesisError;
+
 /// Implement [`Display`](std::fmt::Display) with the information in [`clap::ArgEnum`].
 ///
 /// This ensures consistent names for parsing of the default argument.
@@ -31,6 +37,8 @@ macro_rules! clap_display {
 pub enum InputFormat {
     /// A specification in linear temporal logic (LTL).
     Ltl,
+    /// A safety monitor as aiger circuit, see [`crate::monitor`].
+    Aiger,
 }
 impl Default for InputFormat {
     fn default() -> Self {
@@ -57,6 +65,22 @@ pub enum OutputFormat {
     /// Controller as an aiger circuit in binary format.
     #[clap(name = "aig")]
     Aig,
+    /// Controller as a network of lookup tables with six inputs for FPGAs,
+    /// mapped from the aiger circuit and written in BLIF format.
+    #[clap(name = "blif")]
+    Blif,
+    /// Machine controller in KISS2 format, written before determinization with
+    /// the don't care outputs of the incompletely specified machine.
+    #[clap(name = "kiss2")]
+    Kiss2,
+    /// Controller as a sequential model in BTOR2 format for hardware model checkers,
+    /// translated structurally from the aiger circuit.
+    #[clap(name = "btor2")]
+    Btor2,
+    /// Controller as a hierarchical netlist of Verilog modules, factored from the aiger
+    /// circuit into a state module with the latches and a module for each output.
+    #[clap(name = "verilog")]
+    Verilog,
 }
 impl Default for OutputFormat {
     fn default() -> Self {
@@ -65,6 +89,213 @@ impl Default for OutputFormat {
 }
 clap_display!(OutputFormat);
 
+/// An additional output of the synthesis procedure, which shares all intermediate
+/// results with the controller in the main output format.
+///
+/// The target is given as `format` or `format:file`, where the file is only used
+/// by the command-line interface to write the controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTarget {
+    /// The output format of the controller.
+    pub format: OutputFormat,
+    /// The file to which the controller is written.
+    pub file: Option<String>,
+}
+impl fmt::Display for OutputTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format)?;
+        if let Some(file) = &self.file {
+            write!(f, ":{}", file)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error which can be returned when parsing an output target.
+#[derive(Debug)]
+pub struct ParseOutputTargetError {
+    msg: String,
+    kind: ErrorKind,
+}
+impl ParseOutputTargetError {
+    fn new(msg: String, kind: ErrorKind) -> Self {
+        Self { msg, kind }
+    }
+    fn to_clap_error(&self) -> Error {
+        Error::with_description(self.msg.clone(), self.kind)
+    }
+}
+impl fmt::Display for ParseOutputTargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_clap_error(), f)
+    }
+}
+impl std::error::Error for ParseOutputTargetError {}
+
+impl FromStr for OutputTarget {
+    type Err = ParseOutputTargetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use clap::ArgEnum as _;
+        let (format, file) = match s.split_once(':') {
+            Some((format, file)) => (format, Some(file.to_string())),
+            None => (s, None),
+        };
+        let format = OutputFormat::from_str(format, false).map_err(|_| {
+            ParseOutputTargetError::new(
+                format!(
+                    "invalid output format '{}' [possible values: {}]",
+                    format,
+                    OutputFormat::VARIANTS.join(", ")
+                ),
+                ErrorKind::InvalidValue,
+            )
+        })?;
+        if file.as_deref() == Some("") {
+            return Err(ParseOutputTargetError::new(
+                format!("missing file name for output target '{}'", s),
+                ErrorKind::EmptyValue,
+            ));
+        }
+        Ok(Self { format, file })
+    }
+}
+
+/// A fairness assumption on inputs of the specification, which is added to the
+/// assumptions of the formula.
+///
+/// The assumption is given as `input` for an input that holds infinitely often,
+/// as `enabled->taken` for strong fairness or as `enabled~>taken` for weak fairness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fairness {
+    /// The input holds infinitely often, i.e. `G F input`.
+    Unconditional(String),
+    /// If the first input holds infinitely often, then the second input holds
+    /// infinitely often, i.e. `G F enabled -> G F taken`.
+    Strong(String, String),
+    /// If the first input eventually holds forever, then the second input holds
+    /// infinitely often, i.e. `F G enabled -> G F taken`.
+    Weak(String, String),
+}
+impl Fairness {
+    /// Returns the inputs constrained by this assumption.
+    pub fn inputs(&self) -> Vec<&str> {
+        match self {
+            Self::Unconditional(input) => vec![input.as_str()],
+            Self::Strong(enabled, taken) | Self::Weak(enabled, taken) => {
+                vec![enabled.as_str(), taken.as_str()]
+            }
+        }
+    }
+
+    /// Returns the assumption as an LTL formula.
+    pub fn formula(&self) -> String {
+        match self {
+            Self::Unconditional(input) => format!("G F \"{}\"", input),
+            Self::Strong(enabled, taken) => format!("G F \"{}\" -> G F \"{}\"", enabled, taken),
+            Self::Weak(enabled, taken) => format!("F G \"{}\" -> G F \"{}\"", enabled, taken),
+        }
+    }
+}
+impl fmt::Display for Fairness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unconditional(input) => write!(f, "{}", input),
+            Self::Strong(enabled, taken) => write!(f, "{}->{}", enabled, taken),
+            Self::Weak(enabled, taken) => write!(f, "{}~>{}", enabled, taken),
+        }
+    }
+}
+
+/// An error which can be returned when parsing a fairness assumption.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFairnessError(String);
+impl fmt::Display for ParseFairnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid fairness assumption '{}'", self.0)
+    }
+}
+impl std::error::Error for ParseFairnessError {}
+
+impl FromStr for Fairness {
+    type Err = ParseFairnessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = |name: &str| {
+            let name = name.trim();
+            if name.is_empty() || name.contains('"') {
+                Err(ParseFairnessError(s.to_string()))
+            } else {
+                Ok(name.to_string())
+            }
+        };
+        if let Some((enabled, taken)) = s.split_once("->") {
+            Ok(Self::Strong(input(enabled)?, input(taken)?))
+        } else if let Some((enabled, taken)) = s.split_once("~>") {
+            Ok(Self::Weak(input(enabled)?, input(taken)?))
+        } else {
+            Ok(Self::Unconditional(input(s)?))
+        }
+    }
+}
+
+/// The capacity of the cache of and gates while constructing an aiger circuit,
+/// which reuses and gates with the same inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AndCacheCapacity {
+    /// Cache all and gates.
+    Unbounded,
+    /// Cache at most the given number of and gates, evicting the least
+    /// recently used gate.
+    Bounded(usize),
+    /// Do not cache and gates, which saves the memory of the cache at the cost
+    /// of a larger circuit before compression.
+    Disabled,
+}
+impl Default for AndCacheCapacity {
+    fn default() -> Self {
+        Self::Unbounded
+    }
+}
+impl fmt::Display for AndCacheCapacity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unbounded => write!(f, "unbounded"),
+            Self::Bounded(capacity) => write!(f, "{}", capacity),
+            Self::Disabled => write!(f, "none"),
+        }
+    }
+}
+
+/// An error which can be returned when parsing the capacity of the and gate cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAndCacheCapacityError(String);
+impl fmt::Display for ParseAndCacheCapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid cache capacity '{}' [possible values: unbounded, none, <num>]",
+            self.0
+        )
+    }
+}
+impl std::error::Error for ParseAndCacheCapacityError {}
+
+impl FromStr for AndCacheCapacity {
+    type Err = ParseAndCacheCapacityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unbounded" => Ok(Self::Unbounded),
+            "none" => Ok(Self::Disabled),
+            _ => s
+                .parse()
+                .map(Self::Bounded)
+                .map_err(|_| ParseAndCacheCapacityError(s.to_string())),
+        }
+    }
+}
+
 /// The type of labels used in the machine controller
 /// for further translation to a BDD or aiger circuit.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Clap)]
@@ -77,6 +308,16 @@ pub enum LabelStructure {
     /// of the parity automaton for the machine.
     #[clap(name = "structured")]
     Structured,
+    /// Structured labels following the product structure
+    /// of the states of the parity automaton, with one
+    /// component for each subformula of a state.
+    #[clap(name = "inner")]
+    Inner,
+    /// Structured labels as for `structured`, with an
+    /// additional component for the score of the state
+    /// of the parity automaton used during exploration.
+    #[clap(name = "scored")]
+    Scored,
 }
 impl Default for LabelStructure {
     fn default() -> Self {
@@ -116,35 +357,115 @@ clap_display!(LabelCompression);
 ///
 /// The min, max and minmax strategies use a scoring
 /// of nodes derived from states of the parity automaton.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Clap)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExplorationStrategy {
     /// Explore nodes in a breadth-first search, i.e.
     /// choose the node that was discovered the earliest as the next node.
-    #[clap(name = "bfs")]
     Bfs,
     /// Explore nodes in a depth-first search, i.e.
     /// choose the node that was discovered the latest as the next node.
-    #[clap(name = "dfs")]
     Dfs,
     /// Explore nodes by choosing the node with the minimum score
     /// as the next node.
-    #[clap(name = "min")]
     Min,
     /// Explore nodes by choosing the node with the maximum score
     /// as the next node.
-    #[clap(name = "max")]
     Max,
     /// Explore nodes by alternatingly choosing the node with the
     /// minimum and maximum score next.
-    #[clap(name = "minmax")]
     MinMax,
+    /// Explore nodes in a depth-first search up to the given depth of
+    /// automaton states, and defer deeper nodes to a breadth-first search,
+    /// from which each node starts a new bounded depth-first search.
+    Bdfs(usize),
+    /// Explore nodes by choosing the node whose automaton state was hit most often
+    /// by the given number of random simulations of the automaton, with random inputs
+    /// and greedy outputs of the system, and in breadth-first order among equally
+    /// often hit nodes.
+    Sim(usize),
 }
 impl Default for ExplorationStrategy {
     fn default() -> Self {
         Self::Bfs
     }
 }
-clap_display!(ExplorationStrategy);
+impl fmt::Display for ExplorationStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bfs => write!(f, "bfs"),
+            Self::Dfs => write!(f, "dfs"),
+            Self::Min => write!(f, "min"),
+            Self::Max => write!(f, "max"),
+            Self::MinMax => write!(f, "minmax"),
+            Self::Bdfs(depth) => write!(f, "bdfs:{}", depth),
+            Self::Sim(runs) => write!(f, "sim:{}", runs),
+        }
+    }
+}
+
+/// An error which can be returned when parsing an exploration strategy.
+#[derive(Debug)]
+pub struct ParseExplorationStrategyError {
+    msg: String,
+    kind: ErrorKind,
+}
+impl ParseExplorationStrategyError {
+    fn new(msg: String, kind: ErrorKind) -> Self {
+        Self { msg, kind }
+    }
+    fn to_clap_error(&self) -> Error {
+        Error::with_description(self.msg.clone(), self.kind)
+    }
+}
+impl fmt::Display for ParseExplorationStrategyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_clap_error(), f)
+    }
+}
+impl std::error::Error for ParseExplorationStrategyError {}
+
+impl FromStr for ExplorationStrategy {
+    type Err = ParseExplorationStrategyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bfs" => Ok(Self::Bfs),
+            "dfs" => Ok(Self::Dfs),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            "minmax" => Ok(Self::MinMax),
+            _ => {
+                let (strategy, name, value): (fn(usize) -> Self, _, _) = match s.split_once(':') {
+                    Some(("bdfs", depth)) => (Self::Bdfs, "depth", depth),
+                    Some(("sim", runs)) => (Self::Sim, "runs", runs),
+                    _ => {
+                        return Err(ParseExplorationStrategyError::new(
+                            format!(
+                                "invalid value '{}' [possible values: bfs, dfs, min, max, minmax, bdfs:<depth>, sim:<runs>]",
+                                s
+                            ),
+                            ErrorKind::InvalidValue,
+                        ))
+                    }
+                };
+                let number = value.parse::<usize>().map_err(|e| {
+                    ParseExplorationStrategyError::new(
+                        format!("could not parse {} '{}': {}", name, value, e),
+                        ErrorKind::ValueValidation,
+                    )
+                })?;
+                if number == 0 {
+                    Err(ParseExplorationStrategyError::new(
+                        format!("{} '0' out of range [must be greater than 0]", name),
+                        ErrorKind::ValueValidation,
+                    ))
+                } else {
+                    Ok(strategy(number))
+                }
+            }
+        }
+    }
+}
 
 /// The scoring function to use during on-the-fly exploration
 /// with an exploration strategy that uses scores.
@@ -322,6 +643,12 @@ pub enum Solver {
     /// M. Luttenberger, 2012.
     #[clap(name = "si")]
     Si,
+    /// Use an external solver of [Oink](https://github.com/trolando/oink),
+    /// which needs to be installed and available as `oink`.
+    ///
+    /// Falls back to FPI if Oink can not be invoked.
+    #[clap(name = "oink")]
+    Oink,
 }
 impl Default for Solver {
     fn default() -> Self {
@@ -330,6 +657,37 @@ impl Default for Solver {
 }
 clap_display!(Solver);
 
+/// The interpretation of deadlocks in parity games read from a file, i.e. of nodes
+/// without successors, where the even player is the system and the odd player is
+/// the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Clap)]
+pub enum Deadlocks {
+    /// A deadlock is a failure of the environment, so plays reaching a deadlock
+    /// are won by the system.
+    #[clap(name = "environment")]
+    Environment,
+    /// A deadlock is a failure of the system, so plays reaching a deadlock
+    /// are won by the environment.
+    #[clap(name = "system")]
+    System,
+}
+impl Default for Deadlocks {
+    fn default() -> Self {
+        Self::System
+    }
+}
+clap_display!(Deadlocks);
+
+impl Deadlocks {
+    /// Returns the player winning plays that reach a deadlock.
+    pub fn winner(self) -> Player {
+        match self {
+            Self::Environment => Player::Even,
+            Self::System => Player::Odd,
+        }
+    }
+}
+
 /// The simplications to apply to an LTL formula of the specification.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Clap)]
 pub enum Simplification {
@@ -350,6 +708,70 @@ impl Default for Simplification {
 }
 clap_display!(Simplification);
 
+/// The handling of atomic propositions that occur in the formula of the specification,
+/// but are neither declared as inputs nor as outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Clap)]
+pub enum Undeclared {
+    /// Reject the specification with an error listing the undeclared propositions.
+    #[clap(name = "error")]
+    Error,
+    /// Add the undeclared propositions to the inputs with a warning.
+    #[clap(name = "inputs")]
+    Inputs,
+    /// Add the undeclared propositions to the outputs with a warning.
+    #[clap(name = "outputs")]
+    Outputs,
+}
+impl Default for Undeclared {
+    fn default() -> Self {
+        Self::Error
+    }
+}
+clap_display!(Undeclared);
+
+/// The order of the inputs and outputs in the list of atomic propositions of a machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Clap)]
+pub enum ApOrder {
+    /// List the inputs before the outputs.
+    #[clap(name = "inputs-first")]
+    InputsFirst,
+    /// List the outputs before the inputs, e.g. for tools that require the
+    /// controllable atomic propositions of a Mealy machine first.
+    #[clap(name = "outputs-first")]
+    OutputsFirst,
+}
+impl Default for ApOrder {
+    fn default() -> Self {
+        Self::InputsFirst
+    }
+}
+clap_display!(ApOrder);
+
+/// The policy for determinizing the controller machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Clap)]
+pub enum DeterminizationPolicy {
+    /// Keep the most frequently used successors and outputs, aiming for
+    /// a small controller.
+    #[clap(name = "frequency")]
+    Frequency,
+    /// Prefer staying in the current state, and encode states such that
+    /// states with many transitions between them differ in few bits,
+    /// aiming for low switching activity of the latches in the circuit.
+    #[clap(name = "low-toggle")]
+    LowToggle,
+    /// Choose uniformly at random among the successors and outputs allowed by the
+    /// winning strategy, with the seed of option `--determinization-seed`, e.g. for
+    /// testing downstream systems against different controllers.
+    #[clap(name = "random")]
+    Random,
+}
+impl Default for DeterminizationPolicy {
+    fn default() -> Self {
+        Self::Frequency
+    }
+}
+clap_display!(DeterminizationPolicy);
+
 /// The minimization method to use on the controller machine.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Clap)]
 pub enum MinimizationMethod {
@@ -396,6 +818,10 @@ pub enum AigerCompression {
     /// is not further reduced.
     #[clap(name = "more")]
     More,
+    /// Apply both basic and newer rewrite methods of the ABC framework together with
+    /// sequential methods that remove or merge latches until the size is not further reduced.
+    #[clap(name = "sequential")]
+    Sequential,
 }
 impl Default for AigerCompression {
     fn default() -> Self {
@@ -404,6 +830,168 @@ impl Default for AigerCompression {
 }
 clap_display!(AigerCompression);
 
+/// A metric of an aiger circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AigerMetric {
+    /// The total number of and gates and latches.
+    Size,
+    /// The number of and gates.
+    Gates,
+    /// The number of latches.
+    Latches,
+    /// The maximum number of and gates on a path from an input or latch
+    /// to an output or latch.
+    Depth,
+    /// The number of inputs.
+    Inputs,
+    /// The number of states of the machine from which the circuit is constructed.
+    States,
+    /// The maximum number of and gates on a path from an input to an output.
+    IoDelay,
+    /// The maximum number of and gates on a path from a latch to an output.
+    LatchDelay,
+}
+impl AigerMetric {
+    const VALUES: [(&'static str, Self); 8] = [
+        ("size", Self::Size),
+        ("gates", Self::Gates),
+        ("latches", Self::Latches),
+        ("depth", Self::Depth),
+        ("inputs", Self::Inputs),
+        ("states", Self::States),
+        ("io-delay", Self::IoDelay),
+        ("latch-delay", Self::LatchDelay),
+    ];
+
+    fn parse(name: &str) -> Result<Self, ParseAigerObjectiveError> {
+        Self::VALUES
+            .iter()
+            .find(|(n, _)| *n == name.trim())
+            .map(|&(_, metric)| metric)
+            .ok_or_else(|| {
+                ParseAigerObjectiveError::new(
+                    format!(
+                        "invalid metric '{}' [possible values: size, gates, latches, depth, inputs, states, io-delay, latch-delay]",
+                        name
+                    ),
+                    ErrorKind::InvalidValue,
+                )
+            })
+    }
+}
+impl fmt::Display for AigerMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, _) = Self::VALUES.iter().find(|(_, m)| m == self).unwrap();
+        write!(f, "{}", name)
+    }
+}
+
+/// The objective for selecting an aiger circuit among the candidates of the portfolio
+/// approach, which minimizes the given metrics in lexicographic order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AigerObjective(Vec<AigerMetric>);
+impl AigerObjective {
+    /// Returns the metrics of the objective in lexicographic order.
+    pub fn metrics(&self) -> &[AigerMetric] {
+        &self.0
+    }
+}
+impl Default for AigerObjective {
+    fn default() -> Self {
+        Self(vec![AigerMetric::Size])
+    }
+}
+impl fmt::Display for AigerObjective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, metric) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", metric)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error which can be returned when parsing an objective for aiger circuits.
+#[derive(Debug)]
+pub struct ParseAigerObjectiveError {
+    msg: String,
+    kind: ErrorKind,
+}
+impl ParseAigerObjectiveError {
+    fn new(msg: String, kind: ErrorKind) -> Self {
+        Self { msg, kind }
+    }
+    fn to_clap_error(&self) -> Error {
+        Error::with_description(self.msg.clone(), self.kind)
+    }
+}
+impl fmt::Display for ParseAigerObjectiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_clap_error(), f)
+    }
+}
+impl std::error::Error for ParseAigerObjectiveError {}
+
+impl FromStr for AigerObjective {
+    type Err = ParseAigerObjectiveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(AigerMetric::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Upper bounds on metrics of the selected aiger circuit, such that candidates of the
+/// portfolio approach exceeding a bound are only selected if no candidate meets all bounds.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AigerThresholds(Vec<(AigerMetric, usize)>);
+impl AigerThresholds {
+    /// Returns the metrics together with their upper bounds.
+    pub fn bounds(&self) -> &[(AigerMetric, usize)] {
+        &self.0
+    }
+}
+impl fmt::Display for AigerThresholds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (metric, bound)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}={}", metric, bound)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AigerThresholds {
+    type Err = ParseAigerObjectiveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|threshold| {
+                let (name, bound) = threshold.split_once('=').ok_or_else(|| {
+                    ParseAigerObjectiveError::new(
+                        format!("invalid threshold '{}', expected metric=bound", threshold),
+                        ErrorKind::InvalidValue,
+                    )
+                })?;
+                let bound = bound.trim().parse().map_err(|_| {
+                    ParseAigerObjectiveError::new(
+                        format!("invalid bound '{}' for metric {}", bound, name.trim()),
+                        ErrorKind::InvalidValue,
+                    )
+                })?;
+                Ok((AigerMetric::parse(name)?, bound))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
 /// The method to use for reordering the BDD controller to reduce its size.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Clap)]
 pub enum BddReordering {
@@ -469,6 +1057,90 @@ impl From<TraceLevel> for log::LevelFilter {
         }
     }
 }
+/// The commands of the command-line interface.
+///
+/// If the arguments do not start with a command, they are the options of the
+/// default command [`Command::DEFAULT`] for synthesis.
+#[derive(Debug, Clone, Clap)]
+#[clap(name = "strix", version, about)]
+pub enum Command {
+    /// Synthesizes a controller for a specification.
+    #[clap(
+        name = "synthesize",
+        about = "Synthesize a controller, which is the default without a command"
+    )]
+    Synthesize(CliOptions),
+    /// Only checks the realizability of a specification.
+    #[clap(
+        name = "check",
+        about = "Only check realizability of the specification"
+    )]
+    Check(CliOptions),
+    /// Compresses an aiger circuit.
+    #[clap(
+        name = "compress",
+        about = "Compress an aiger circuit with the rewrite methods of ABC"
+    )]
+    Compress(CompressOptions),
+    /// Converts a machine or an aiger circuit to another output format.
+    #[clap(
+        name = "convert",
+        about = "Convert a machine or an aiger circuit to another output format"
+    )]
+    Convert(ConvertOptions),
+    /// Solves a parity game.
+    #[clap(name = "solve-pg", about = "Solve a parity game in PGSolver format")]
+    SolvePg(SolveGameOptions),
+    /// Simulates an aiger controller.
+    #[clap(
+        name = "simulate",
+        about = "Simulate an aiger controller on a sequence of inputs"
+    )]
+    Simulate(SimulateOptions),
+    /// Fuzzes the parity game solvers.
+    #[clap(
+        name = "fuzz-solvers",
+        about = "Check parity game solvers for agreement on random games"
+    )]
+    FuzzSolvers(FuzzOptions),
+    /// Reduces a parity game on which the solvers fail.
+    #[clap(
+        name = "reduce-pg",
+        about = "Reduce a parity game in PGSolver format to a subgame on which the solvers still fail"
+    )]
+    ReducePg(ReduceGameOptions),
+    /// Analyzes a formula without synthesis.
+    #[clap(
+        name = "analyze",
+        about = "Report metrics and proposition statuses of an LTL formula without synthesis"
+    )]
+    Analyze(AnalyzeOptions),
+    /// Searches for assumptions that make a specification realizable.
+    #[clap(
+        name = "weaken",
+        about = "Search for a minimal set of assumptions that makes the specification realizable"
+    )]
+    Weaken(WeakenOptions),
+    /// Tests the synthesis procedure on random specifications.
+    #[clap(
+        name = "selftest",
+        about = "Check configurations of the synthesis procedure for agreement on random specifications"
+    )]
+    Selftest(SelftestOptions),
+    /// Measures the deterministic benchmarks.
+    #[cfg(feature = "bench")]
+    #[clap(
+        name = "bench",
+        about = "Measure the deterministic benchmarks of the synthesis procedure"
+    )]
+    Bench(BenchOptions),
+}
+
+impl Command {
+    /// The name of the command used if the arguments do not start with a command.
+    pub const DEFAULT: &'static str = "synthesize";
+}
+
 // Workaround for https://github.com/TeXitoi/structopt/issues/333
 #[cfg_attr(not(doc), allow(missing_docs))]
 #[cfg_attr(
@@ -526,8 +1198,30 @@ pub struct CliOptions {
         display_order = 3
     )]
     pub outputs: Vec<String>,
-    /// The input format of the specification.
-    #[clap(skip)]
+    /// Assignments of the form `name=value` to parameters declared in the formula,
+    /// which take precedence over the values declared with `param`.
+    ///
+    /// See [`params`](crate::params) for the expansion of parameters in the formula
+    /// and in the names of inputs and outputs.
+    #[clap(
+        long = "param",
+        name = "assignment",
+        number_of_values = 1,
+        about = "Override the value of a parameter declared in the formula with name=value",
+        display_order = 4
+    )]
+    pub parameters: Vec<String>,
+    /// The input format of the specification, where a safety monitor in aiger format
+    /// is read from [`CliOptions::input_file`] and the inputs and outputs are given
+    /// by the names of its inputs.
+    #[clap(
+        arg_enum,
+        long = "input-format",
+        name = "input-format",
+        default_value,
+        about = "Input format of the specification (LTL formula or aiger safety monitor read with --formula-file)",
+        display_order = 1
+    )]
     pub input_format: InputFormat,
     /// The output file where the controller should be written to.
     #[clap(
@@ -548,11 +1242,727 @@ pub struct CliOptions {
     )]
     /// The trace level to use for instantiating the logging framework.
     pub trace_level: TraceLevel,
+    /// The file from which hints for the winning regions of both players are read,
+    /// see [`SynthesisOptions::winning_hints`].
+    ///
+    /// The hints are given as two lines `even { <nodes> }` and `odd { <nodes> }`
+    /// in the format of [`WinningRegion`], with node indices below 2^26.
+    #[clap(
+        long = "winning-hints",
+        name = "hints-file",
+        about = "Read hints for the winning regions of the game from the given file",
+        display_order = 6
+    )]
+    pub hints_file: Option<String>,
+    /// The file from which an assumption monitor for the controller in BTOR2 format is
+    /// read as an aiger circuit, see [`SynthesisOptions::btor2_assumption`].
+    #[clap(
+        long = "btor2-assumption",
+        name = "assumption-file",
+        about = "Read an aiger circuit whose outputs signal assumption violations for the BTOR2 model",
+        display_order = 7
+    )]
+    pub btor2_assumption_file: Option<String>,
+    /// The file from which a deployed controller is read as an aiger circuit, which is
+    /// repaired for the specification, see [`SynthesisOptions::repair_controller`].
+    #[clap(
+        long = "repair",
+        name = "controller-file",
+        about = "Repair the aiger circuit in the given file for the specification, keeping its latches",
+        display_order = 8
+    )]
+    pub repair_file: Option<String>,
+    /// The configuration file from which further options are read, where options given
+    /// on the command line take precedence, see [`config`](crate::config).
+    ///
+    /// If not set, the file [`DEFAULT_CONFIG_FILE`](crate::config::DEFAULT_CONFIG_FILE)
+    /// is read from the current directory if it exists.
+    #[clap(
+        long = "config",
+        name = "config-file",
+        about = "Read further options from the given configuration file (default: strix.toml if it exists)",
+        display_order = 57
+    )]
+    pub config_file: Option<String>,
+    /// Whether to print the effective configuration of all options instead of
+    /// running the synthesis.
+    #[clap(
+        long = "print-config",
+        about = "Print the effective configuration in the format of a configuration file and exit",
+        display_order = 58
+    )]
+    pub print_config: bool,
     /// The set of options for the synthesis process.
     #[clap(flatten)]
     pub synthesis_options: SynthesisOptions,
 }
 
+/// Options of the command-line interface for fuzzing the parity game solvers
+/// with `strix fuzz-solvers`.
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix fuzz-solvers",
+    about = "Check parity game solvers for agreement on random games"
+)]
+pub struct FuzzOptions {
+    /// The number of random games to check.
+    #[clap(
+        long = "games",
+        default_value = "1000",
+        about = "Number of random games to check",
+        display_order = 0
+    )]
+    pub num_games: usize,
+    /// The number of nodes of each random game.
+    #[clap(
+        long = "nodes",
+        default_value = "50",
+        about = "Number of nodes of each game",
+        display_order = 1
+    )]
+    pub num_nodes: usize,
+    /// The maximal number of successors of each node.
+    #[clap(
+        long = "degree",
+        default_value = "3",
+        about = "Maximal number of successors of each node",
+        display_order = 2
+    )]
+    pub max_degree: usize,
+    /// The number of colors of each random game.
+    #[clap(
+        long = "colors",
+        default_value = "8",
+        about = "Number of colors of each game",
+        display_order = 3
+    )]
+    pub num_colors: usize,
+    /// The seed for the first random game.
+    #[clap(
+        long = "seed",
+        default_value = "0",
+        about = "Seed for the first game, increased by one for each further game",
+        display_order = 4
+    )]
+    pub seed: u64,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 5
+    )]
+    pub trace_level: TraceLevel,
+}
+
+impl From<&FuzzOptions> for crate::parity::fuzz::FuzzConfig {
+    fn from(options: &FuzzOptions) -> Self {
+        Self {
+            parameters: crate::parity::game::generator::GeneratorParameters {
+                num_nodes: options.num_nodes,
+                max_degree: options.max_degree,
+                num_colors: options.num_colors,
+            },
+            num_games: options.num_games,
+            seed: options.seed,
+        }
+    }
+}
+
+/// Options of the command-line interface for testing the synthesis procedure on
+/// random specifications with `strix selftest`.
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix selftest",
+    about = "Check configurations of the synthesis procedure for agreement on random specifications"
+)]
+pub struct SelftestOptions {
+    /// The number of random specifications to check.
+    #[clap(
+        long = "specs",
+        default_value = "100",
+        about = "Number of random specifications to check",
+        display_order = 0
+    )]
+    pub num_specs: usize,
+    /// The number of input propositions of each specification.
+    #[clap(
+        long = "ins",
+        default_value = "2",
+        about = "Number of input propositions of each specification",
+        display_order = 1
+    )]
+    pub num_inputs: usize,
+    /// The number of output propositions of each specification.
+    #[clap(
+        long = "outs",
+        default_value = "2",
+        about = "Number of output propositions of each specification",
+        display_order = 2
+    )]
+    pub num_outputs: usize,
+    /// The maximal depth of operators in each formula.
+    #[clap(
+        long = "depth",
+        default_value = "4",
+        about = "Maximal depth of operators in each formula",
+        display_order = 3
+    )]
+    pub max_depth: usize,
+    /// The weights of the operators in random formulas, which keep their default
+    /// weight if not given.
+    #[clap(
+        long = "weights",
+        name = "operator=weight",
+        about = "Comma-separated list of weights of the operators prop, const, not, and, or, \
+                 next, finally, globally, until and release",
+        display_order = 4
+    )]
+    pub weights: Option<crate::selftest::OperatorWeights>,
+    /// The maximal number of states for cross-checking the status by bounded synthesis.
+    #[clap(
+        long = "cross-check",
+        name = "max-states",
+        default_value = "2",
+        about = "Check the status by bounded synthesis up to the given number of states",
+        display_order = 5
+    )]
+    pub cross_check_states: usize,
+    /// The seed for the first random specification.
+    #[clap(
+        long = "seed",
+        default_value = "0",
+        about = "Seed for the first specification, increased by one for each further specification",
+        display_order = 6
+    )]
+    pub seed: u64,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 7
+    )]
+    pub trace_level: TraceLevel,
+}
+
+/// Converts the options to a configuration for the self-test.
+///
+/// Returns an error if the options do not allow any formulas to be generated, i.e. if
+/// there are no propositions, or the weights of propositions and constants are both zero.
+impl TryFrom<&SelftestOptions> for crate::selftest::SelftestConfig {
+    type Error = Error;
+
+    fn try_from(options: &SelftestOptions) -> Result<Self, Self::Error> {
+        let weights = options.weights.unwrap_or_default();
+        if options.num_inputs + options.num_outputs == 0 {
+            return Err(Error::with_description(
+                "formulas need at least one input or output proposition".to_string(),
+                ErrorKind::ValueValidation,
+            ));
+        }
+        if weights.proposition == 0 && weights.constant == 0 {
+            return Err(Error::with_description(
+                "formulas need a positive weight for propositions or constants".to_string(),
+                ErrorKind::ValueValidation,
+            ));
+        }
+        Ok(Self {
+            parameters: crate::selftest::FormulaParameters {
+                num_inputs: options.num_inputs,
+                num_outputs: options.num_outputs,
+                max_depth: options.max_depth,
+                weights,
+            },
+            num_specs: options.num_specs,
+            seed: options.seed,
+            cross_check_states: options.cross_check_states,
+        })
+    }
+}
+
+/// Options of the command-line interface for compressing an aiger circuit
+/// with `strix compress`.
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix compress",
+    about = "Compress an aiger circuit with the rewrite methods of ABC"
+)]
+pub struct CompressOptions {
+    /// The file from which the aiger circuit is read.
+    #[clap(name = "input-file", about = "Aiger circuit in ASCII or binary format")]
+    pub input_file: String,
+    /// The output file where the compressed circuit should be written to.
+    #[clap(
+        short = 'O',
+        long = "output-file",
+        about = "Write circuit to the given file",
+        display_order = 0
+    )]
+    pub output_file: Option<String>,
+    /// The method to use for compression.
+    #[clap(
+        arg_enum,
+        long = "compression",
+        name = "method",
+        default_value,
+        about = "Method to use for compression",
+        display_order = 1
+    )]
+    pub aiger_compression: AigerCompression,
+    /// Whether the circuit should be written in binary format.
+    #[clap(
+        long = "binary",
+        about = "Write circuit in binary format",
+        display_order = 2
+    )]
+    pub binary: bool,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 3
+    )]
+    pub trace_level: TraceLevel,
+}
+
+/// Options of the command-line interface for converting a previously produced
+/// controller to another output format with `strix convert`.
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix convert",
+    about = "Convert a machine or an aiger circuit to another output format"
+)]
+pub struct ConvertOptions {
+    /// The file from which the controller is read.
+    #[clap(
+        name = "input-file",
+        about = "Machine in HOA or KISS2 format or aiger circuit in ASCII or binary format"
+    )]
+    pub input_file: String,
+    /// The output file where the converted controller should be written to.
+    #[clap(
+        short = 'O',
+        long = "output-file",
+        about = "Write controller to the given file",
+        display_order = 0
+    )]
+    pub output_file: Option<String>,
+    /// The output format of the converted controller, which is the format
+    /// of the input if not given.
+    #[clap(
+        arg_enum,
+        short = 'o',
+        long = "output-format",
+        name = "format",
+        about = "Output format for controller (HOA automaton, BDD, AAG/AIG circuit, BLIF LUT network, KISS2 machine)",
+        display_order = 1
+    )]
+    pub output_format: Option<OutputFormat>,
+    /// Whether the circuit should be written in binary format.
+    #[clap(
+        long = "binary",
+        about = "Write circuit in binary format",
+        display_order = 2
+    )]
+    pub binary: bool,
+    /// The method for minimization of a machine.
+    #[clap(
+        arg_enum,
+        short = 'm',
+        long = "minimize",
+        name = "method",
+        default_value,
+        about = "Method for minimization of a machine",
+        display_order = 3
+    )]
+    pub machine_minimization: MinimizationMethod,
+    /// The method for compressing the aiger circuit, where the circuit is
+    /// not compressed if not given.
+    #[clap(
+        arg_enum,
+        long = "compression",
+        name = "aig-strategy",
+        about = "Aiger compression strategy",
+        display_order = 4
+    )]
+    pub aiger_compression: Option<AigerCompression>,
+    /// Whether the symbol table and comments should be removed from the circuit.
+    #[clap(
+        long = "strip",
+        about = "Remove the symbol table and comments from the circuit",
+        display_order = 5
+    )]
+    pub strip: bool,
+    /// Lines of comments that should be added to the circuit.
+    #[clap(
+        long = "comment",
+        name = "line",
+        number_of_values = 1,
+        about = "Add a line of comments to the circuit",
+        display_order = 6
+    )]
+    pub comments: Vec<String>,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 7
+    )]
+    pub trace_level: TraceLevel,
+}
+
+/// Options of the command-line interface for solving a parity game
+/// with `strix solve-pg`.
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix solve-pg",
+    about = "Solve a parity game in PGSolver format"
+)]
+pub struct SolveGameOptions {
+    /// The file from which the parity game is read, or standard input if not given.
+    #[clap(
+        name = "input-file",
+        about = "Parity game in PGSolver format or in binary format"
+    )]
+    pub input_file: Option<String>,
+    /// The output file where the solution should be written to.
+    #[clap(
+        short = 'O',
+        long = "output-file",
+        about = "Write solution to the given file",
+        display_order = 0
+    )]
+    pub output_file: Option<String>,
+    /// The parity game solver to use.
+    #[clap(
+        arg_enum,
+        long = "solver",
+        name = "solver",
+        default_value,
+        about = "Parity game solver to use",
+        display_order = 1
+    )]
+    pub parity_solver: Solver,
+    /// The solver of Oink to use if the parity game solver is Oink.
+    #[clap(
+        long = "oink-solver",
+        name = "oink-solver",
+        about = "Solver to use within Oink, e.g. npp, tl, zlk, fpi",
+        display_order = 2
+    )]
+    pub oink_solver: Option<String>,
+    /// The interpretation of nodes without successors in the parity game.
+    #[clap(
+        arg_enum,
+        long = "deadlocks",
+        name = "deadlocks",
+        default_value,
+        about = "Whether nodes without successors are lost by the environment or the system",
+        display_order = 3
+    )]
+    pub deadlocks: Deadlocks,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 4
+    )]
+    pub trace_level: TraceLevel,
+}
+
+/// Options of the command-line interface for reducing a parity game on which
+/// the solvers fail with `strix reduce-pg`.
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix reduce-pg",
+    about = "Reduce a parity game in PGSolver format to a subgame on which the solvers still fail"
+)]
+pub struct ReduceGameOptions {
+    /// The file from which the parity game is read, or standard input if not given.
+    #[clap(
+        name = "input-file",
+        about = "Parity game in PGSolver format or in binary format"
+    )]
+    pub input_file: Option<String>,
+    /// The output file where the reduced game should be written to.
+    #[clap(
+        short = 'O',
+        long = "output-file",
+        about = "Write reduced game to the given file",
+        display_order = 0
+    )]
+    pub output_file: Option<String>,
+    /// Whether the reduced game should be written in binary format.
+    #[clap(
+        long = "binary",
+        about = "Write reduced game in binary format",
+        display_order = 1
+    )]
+    pub binary: bool,
+    /// The interpretation of nodes without successors in the parity game.
+    #[clap(
+        arg_enum,
+        long = "deadlocks",
+        name = "deadlocks",
+        default_value,
+        about = "Whether nodes without successors are lost by the environment or the system",
+        display_order = 2
+    )]
+    pub deadlocks: Deadlocks,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 3
+    )]
+    pub trace_level: TraceLevel,
+}
+
+/// Options of the command-line interface for analyzing a formula with `strix analyze`.
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix analyze",
+    about = "Report metrics and proposition statuses of an LTL formula without synthesis"
+)]
+#[clap(group = ArgGroup::new("input-formula").required(true))]
+pub struct AnalyzeOptions {
+    /// The LTL formula to analyze.
+    #[clap(
+        short = 'f',
+        long = "formula",
+        about = "LTL formula of the specification",
+        group = "input-formula",
+        display_order = 0
+    )]
+    pub formula: Option<String>,
+    /// The input file from which the LTL formula is read.
+    #[clap(
+        short = 'F',
+        long = "formula-file",
+        about = "Read LTL formula from the the given file",
+        group = "input-formula",
+        display_order = 1
+    )]
+    pub input_file: Option<String>,
+    /// The list of input atomic propositions for the specification.
+    #[clap(
+        long = "ins",
+        about = "Comma-separated list of input proposition",
+        use_delimiter = true,
+        min_values = 0,
+        display_order = 2
+    )]
+    pub inputs: Vec<String>,
+    /// The list of output atomic propositions for the specification.
+    #[clap(
+        long = "outs",
+        about = "Comma-separated list of output proposition",
+        use_delimiter = true,
+        min_values = 0,
+        display_order = 3
+    )]
+    pub outputs: Vec<String>,
+    /// Flag whether to also explore the automaton of the specification.
+    #[clap(
+        long = "automaton",
+        about = "Also explore the automaton without solving and report its statistics",
+        display_order = 4
+    )]
+    pub automaton: bool,
+    /// The maximal number of automaton states to explore, if any.
+    #[clap(
+        long = "max-states",
+        name = "states",
+        about = "Explore at most the given number of automaton states, implies --automaton",
+        display_order = 5
+    )]
+    pub max_states: Option<usize>,
+    /// Flag whether to only report the syntactic metrics of the formula without the
+    /// statuses of the propositions, which does not start the GraalVM for Owl.
+    #[clap(
+        long = "syntax-only",
+        about = "Only report syntactic metrics without proposition statuses, which avoids starting Owl",
+        conflicts_with_all = &["automaton", "states"],
+        display_order = 6
+    )]
+    pub syntax_only: bool,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 7
+    )]
+    pub trace_level: TraceLevel,
+}
+
+/// Options of the command-line interface for searching for assumptions with
+/// `strix weaken`.
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix weaken",
+    about = "Search for a minimal set of assumptions that makes the specification realizable"
+)]
+#[clap(group = ArgGroup::new("input-formula").required(true))]
+pub struct WeakenOptions {
+    /// The LTL formula of the specification.
+    #[clap(
+        short = 'f',
+        long = "formula",
+        about = "LTL formula of the specification",
+        group = "input-formula",
+        display_order = 0
+    )]
+    pub formula: Option<String>,
+    /// The input file from which the LTL formula is read.
+    #[clap(
+        short = 'F',
+        long = "formula-file",
+        about = "Read LTL formula from the the given file",
+        group = "input-formula",
+        display_order = 1
+    )]
+    pub input_file: Option<String>,
+    /// The list of input atomic propositions for the specification.
+    #[clap(
+        long = "ins",
+        about = "Comma-separated list of input proposition",
+        use_delimiter = true,
+        min_values = 0,
+        display_order = 2
+    )]
+    pub inputs: Vec<String>,
+    /// The list of output atomic propositions for the specification.
+    #[clap(
+        long = "outs",
+        about = "Comma-separated list of output proposition",
+        use_delimiter = true,
+        min_values = 0,
+        display_order = 3
+    )]
+    pub outputs: Vec<String>,
+    /// The templates from which the candidate assumptions are instantiated.
+    #[clap(
+        long = "template",
+        name = "template",
+        number_of_values = 1,
+        about = "Template of candidate assumptions, where {} is replaced by each input",
+        required = true,
+        display_order = 4
+    )]
+    pub templates: Vec<String>,
+    /// The maximal number of assumptions in a set.
+    #[clap(
+        long = "max-assumptions",
+        name = "count",
+        default_value = "2",
+        about = "Search for sets of at most the given number of assumptions",
+        display_order = 5
+    )]
+    pub max_assumptions: usize,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 6
+    )]
+    pub trace_level: TraceLevel,
+}
+
+/// Options of the command-line interface for simulating an aiger controller
+/// with `strix simulate`.
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix simulate",
+    about = "Simulate an aiger controller on a sequence of inputs"
+)]
+pub struct SimulateOptions {
+    /// The file from which the aiger controller is read.
+    #[clap(name = "input-file", about = "Aiger circuit in ASCII or binary format")]
+    pub input_file: String,
+    /// The file from which the inputs are read, or standard input if not given.
+    ///
+    /// Each line contains the values of all inputs in one step as a string of `0` and `1`.
+    #[clap(
+        short = 's',
+        long = "stimulus",
+        about = "Read inputs from the given file, with one line of 0 and 1 per step",
+        display_order = 0
+    )]
+    pub stimulus_file: Option<String>,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 1
+    )]
+    pub trace_level: TraceLevel,
+}
+
+/// Options of the command-line interface for running the benchmarks
+/// with `strix bench`.
+#[cfg(feature = "bench")]
+#[derive(Debug, Clone, Clap)]
+#[clap(
+    name = "strix bench",
+    about = "Measure the deterministic benchmarks of the synthesis procedure"
+)]
+pub struct BenchOptions {
+    /// The number of runs of each benchmark, which must be positive.
+    #[clap(
+        long = "runs",
+        default_value = "5",
+        about = "Number of runs of each benchmark",
+        display_order = 0
+    )]
+    pub runs: std::num::NonZeroU32,
+    /// The trace level to use for instantiating the logging framework.
+    #[clap(
+        arg_enum,
+        short = 't',
+        long = "trace",
+        name = "trace-level",
+        default_value,
+        about = "Trace level",
+        display_order = 1
+    )]
+    pub trace_level: TraceLevel,
+}
+
 // Workaround for https://github.com/TeXitoi/structopt/issues/333
 #[cfg_attr(not(doc), allow(missing_docs))]
 #[cfg_attr(
@@ -606,7 +2016,7 @@ pub struct SynthesisOptions {
         long = "output-format",
         name = "format",
         default_value,
-        about = "Output format for controller (Parity Game, HOA automaton, BDD, AAG/AIG circuit)",
+        about = "Output format for controller (Parity Game, HOA automaton, BDD, AAG/AIG circuit, BLIF LUT network, KISS2 machine)",
         display_order = 4
     )]
     pub output_format: OutputFormat,
@@ -622,17 +2032,25 @@ pub struct SynthesisOptions {
     pub exploration_scoring: ScoringFunction,
     /// The strategy to use for on-the-fly exploration.
     #[clap(
-        arg_enum,
         short = 'e',
         long = "exploration",
         name = "exp-strategy",
         default_value,
-        about = "On-the-fly exploration strategy",
+        about = "On-the-fly exploration strategy:
+    breadth-first search [bfs]
+    depth-first search [dfs]
+    minimum, maximum or alternating score [min, max, minmax]
+    depth-first search up to <depth> states with breadth-first restarts [bdfs:<depth>]
+    states most often hit by <runs> random simulations [sim:<runs>]\n",
         display_order = 6
     )]
     pub exploration_strategy: ExplorationStrategy,
     /// Filter unexplored states based on reachability from the inital state
     /// through non-winning states.
+    ///
+    /// The filter is supplied by the solver after each exploration increment, in the
+    /// same way as for option `--prune-queue`. Filtered states keep their score in
+    /// scored queues and are queued again once they are reached by a new edge.
     #[clap(skip)]
     pub exploration_filter: bool,
     /// The limit to use for on-the-fly exploration.
@@ -650,6 +2068,18 @@ pub struct SynthesisOptions {
         display_order = 8
     )]
     pub exploration_on_the_fly: OnTheFlyLimit,
+    /// Continue the on-the-fly exploration after the game is solved until the
+    /// strategy for the winner does not make any decisions at nodes adjacent to
+    /// the unexplored part of the game.
+    ///
+    /// This option has no effect if only realizability is checked or
+    /// a parity game is produced.
+    #[clap(
+        long = "border-free",
+        about = "Continue exploration until the strategy does not depend on unexplored nodes",
+        display_order = 18
+    )]
+    pub exploration_border_free: bool,
     #[clap(
         long = "lookahead",
         name = "states",
@@ -672,6 +2102,18 @@ pub struct SynthesisOptions {
         display_order = 10
     )]
     pub parity_solver: Solver,
+    /// The solver of Oink to use if the parity game solver is Oink,
+    /// given as the name of the corresponding command line flag of Oink,
+    /// e.g. `npp` or `zlk`.
+    ///
+    /// If no solver is given, the default solver of Oink is used.
+    #[clap(
+        long = "oink-solver",
+        name = "oink-solver",
+        about = "Solver to use within Oink, e.g. npp, tl, zlk, fpi",
+        display_order = 19
+    )]
+    pub oink_solver: Option<String>,
     /// Determinize the machine, i.e. ensure that there is a unique successor
     /// and a unique output only using don't cares for each input.
     ///
@@ -685,6 +2127,16 @@ pub struct SynthesisOptions {
         display_order = 2
     )]
     pub machine_determinization: bool,
+    /// The policy to use for determinization of the machine.
+    #[clap(
+        arg_enum,
+        long = "determinization-policy",
+        name = "policy",
+        default_value,
+        about = "Policy for determinization of the controller automaton",
+        display_order = 20
+    )]
+    pub machine_determinization_policy: DeterminizationPolicy,
     /// The minimization method to use for the machine.
     #[clap(
         arg_enum,
@@ -747,6 +2199,749 @@ pub struct SynthesisOptions {
         display_order = 16
     )]
     pub aiger_compression: AigerCompression,
+    /// The time budget in seconds for exploring the automaton and solving the game.
+    ///
+    /// The game is always solved until the winner is determined, so the budget
+    /// only bounds further exploration for a border-free strategy, after which
+    /// the current strategy is used.
+    #[clap(
+        long = "budget-solving",
+        name = "solving-seconds",
+        about = "Time budget in seconds for exploration after the winner is determined",
+        display_order = 21
+    )]
+    pub budget_solving: Option<u64>,
+    /// The time budget in seconds for minimizing the machine.
+    ///
+    /// If the budget expires, the smallest machine found so far is used. The
+    /// budget also bounds a running search of the SAT solver, which is abandoned
+    /// once the budget expires. Only the construction of the SAT encoding for a
+    /// size is not interrupted, which can be bounded with the maximum number of
+    /// clauses.
+    #[clap(
+        long = "budget-minimization",
+        name = "minimization-seconds",
+        about = "Time budget in seconds for machine minimization",
+        display_order = 22
+    )]
+    pub budget_minimization: Option<u64>,
+    /// The time budget in seconds for reordering the BDDs.
+    ///
+    /// A running reordering is not interrupted, but no further BDDs are
+    /// reordered once the budget expires.
+    #[clap(
+        long = "budget-reordering",
+        name = "reordering-seconds",
+        about = "Time budget in seconds for BDD reordering",
+        display_order = 23
+    )]
+    pub budget_reordering: Option<u64>,
+    /// The time budget in seconds for compressing the aiger circuits.
+    ///
+    /// If the budget expires, the smallest circuit found so far is used.
+    #[clap(
+        long = "budget-compression",
+        name = "compression-seconds",
+        about = "Time budget in seconds for aiger compression",
+        display_order = 24
+    )]
+    pub budget_compression: Option<u64>,
+    /// The maximum number of states of a machine that is minimized.
+    ///
+    /// Larger machines are not minimized, and the unminimized machine is used instead.
+    /// Without this option, only the machines of the aiger portfolio are limited
+    /// to 4000 states.
+    #[clap(
+        long = "minimize-max-states",
+        name = "max-states",
+        about = "Maximum number of states of a machine that is minimized",
+        display_order = 40
+    )]
+    pub minimization_max_states: Option<usize>,
+    /// The maximum number of clauses of the SAT encoding for minimizing a machine
+    /// with don't cares.
+    ///
+    /// If the encoding for a candidate size exceeds this number, this and larger sizes
+    /// are not tried, and the smallest machine found for a smaller size is used, or
+    /// the unminimized machine if there is none.
+    #[clap(
+        long = "minimize-max-clauses",
+        name = "max-clauses",
+        about = "Maximum number of clauses of the SAT encoding for minimization",
+        display_order = 41
+    )]
+    pub minimization_max_clauses: Option<usize>,
+    /// Use the assumptions of a specification of the form `A -> G` to simplify
+    /// the controller.
+    ///
+    /// Inputs after which the assumption `A` can no longer be satisfied are treated
+    /// as don't cares for the outputs of the controller during minimization and
+    /// construction of BDDs. For BDDs, these inputs are also don't cares for the
+    /// successor state. This option only has an effect for realizable
+    /// specifications whose formula has an implication at the top level.
+    #[clap(
+        long = "assumption-dontcares",
+        about = "Use environment assumptions to obtain don't cares for the controller",
+        display_order = 25
+    )]
+    pub assumption_dontcares: bool,
+    /// Restrict solving and strategy computation to the part of the game
+    /// reachable from the initial node.
+    ///
+    /// The reachable part is recomputed after each exploration increment,
+    /// skipping nodes that are only reachable through nodes that are already won
+    /// by one of the players.
+    #[clap(
+        long = "solve-reachable",
+        about = "Only solve the part of the game reachable from the initial node",
+        display_order = 26
+    )]
+    pub solve_reachable: bool,
+    /// Remove nodes from the exploration queue that are only reachable from the
+    /// initial node through nodes that are already won by one of the players.
+    ///
+    /// The analysis runs after each exploration increment and reclaims the memory
+    /// of stale queue entries. Removed nodes are queued again if further
+    /// exploration adds an edge to them.
+    #[clap(
+        long = "prune-queue",
+        about = "Remove queued nodes only reachable through already won nodes",
+        display_order = 27
+    )]
+    pub exploration_pruning: bool,
+    /// Restrict the winning strategy before constructing the machine, such that
+    /// different inputs in a state share the same output and successor where possible.
+    ///
+    /// This reduces the number of distinct transitions of the machine, which
+    /// benefits the minimization and the size of BDDs and circuits.
+    #[clap(
+        long = "propagate-outputs",
+        about = "Share output decisions of the strategy across inputs",
+        display_order = 28
+    )]
+    pub strategy_propagation: bool,
+    /// Use an implicit else edge in each state of a controller in HOA format,
+    /// which also covers the inputs without a transition in that state.
+    ///
+    /// The input label of one transition per state is replaced by the negation of
+    /// the other input labels if this results in a shorter label.
+    #[clap(
+        long = "hoa-else",
+        about = "Use implicit else edges for don't care inputs in HOA output",
+        display_order = 29
+    )]
+    pub hoa_implicit_else: bool,
+    /// Use exact synthesis for aiger circuits with at most the given number of
+    /// inputs and latches.
+    ///
+    /// A SAT solver searches for a circuit with a minimum number of and gates
+    /// implementing the outputs and latch updates after compression. As the cost
+    /// grows quickly with the number of inputs and latches, circuits with more
+    /// than eight inputs and latches are never synthesized exactly, and the search
+    /// is limited to circuits with at most twelve and gates and to ten seconds or
+    /// the budget for compression.
+    #[clap(
+        long = "exact-synthesis",
+        name = "max-vars",
+        about = "Use exact synthesis for aiger circuits with at most this many inputs and latches",
+        display_order = 30
+    )]
+    pub aiger_exact_synthesis: Option<usize>,
+    /// The number of BDD variables above which the BDDs of the controller are
+    /// partitioned across several managers. Without a threshold, a single manager
+    /// is used.
+    ///
+    /// Each manager holds the BDDs of a block of outputs and latches, and the
+    /// managers are reordered independently in parallel, before the BDDs are
+    /// merged again when constructing the aiger circuit.
+    #[clap(
+        long = "bdd-partition",
+        name = "partition-vars",
+        about = "Number of BDD variables above which BDDs are partitioned across managers",
+        display_order = 31
+    )]
+    pub bdd_partition_threshold: Option<usize>,
+    /// Add an input with the given name to the controller that signals a violation
+    /// of the assumptions on the environment.
+    ///
+    /// While the input is set, the controller produces a fixed fallback output for
+    /// its current state and keeps its state, resuming normal operation once the
+    /// input is cleared. This option only has an effect for realizable specifications.
+    #[clap(
+        long = "recovery",
+        name = "input-name",
+        about = "Add input for assumption violations with fallback outputs in each state",
+        display_order = 32
+    )]
+    pub recovery_input: Option<String>,
+    /// Complete the border of the parity game explicitly before writing it.
+    ///
+    /// Nodes in the border of a partially explored game are turned into nodes won
+    /// by the losing player, which is the pessimistic assumption for the winner,
+    /// so that the written game contains no border nodes. This option only has
+    /// an effect for parity game output.
+    #[clap(
+        long = "pg-explicit-border",
+        about = "Complete the border of parity game output with nodes won by the loser",
+        display_order = 33
+    )]
+    pub pg_explicit_border: bool,
+    /// Read an initial variable order for the BDDs of the controller from the given file.
+    ///
+    /// The file lists the names of inputs and bits of the state, such as `l0`,
+    /// one per line. Unknown names are ignored, and variables not listed in the
+    /// file are placed after the listed variables. The order is applied before
+    /// reordering, so it can be used as a starting point for further reordering.
+    #[clap(
+        long = "bdd-order",
+        name = "order-file",
+        about = "Read initial variable order for BDDs from file",
+        display_order = 34
+    )]
+    pub bdd_order_input: Option<String>,
+    /// Write the final variable order of the BDDs of the controller to the given file
+    /// in the format read by option `--bdd-order`, to reuse it in related synthesis runs.
+    #[clap(
+        long = "bdd-order-output",
+        name = "order-output-file",
+        about = "Write final variable order of BDDs to file",
+        display_order = 35
+    )]
+    pub bdd_order_output: Option<String>,
+    /// Detect states of the machine that are symmetric under swapping the given
+    /// pairs of atomic propositions, e.g. `r0:r1,g0:g1` for the requests and grants
+    /// of two clients of an arbiter.
+    ///
+    /// Pairs consist either of two inputs or of two outputs. Before the machine is
+    /// minimized, it is quotiented by its symmetric states, where a latch of the
+    /// circuit records whether the inputs and outputs are currently swapped. For
+    /// machines in HOA or KISS2 format, for Moore machines or with a recovery input,
+    /// the number of pairs of symmetric states is only reported.
+    #[clap(
+        long = "symmetry",
+        name = "ap-pairs",
+        about = "Merge states symmetric under swapping the given pairs of propositions",
+        display_order = 36
+    )]
+    pub symmetry: Option<String>,
+    /// Explore the game only up to a bounded depth of automaton states, starting
+    /// with depth one and doubling the depth up to the given maximum of at least one,
+    /// before continuing with the general exploration.
+    ///
+    /// Unexplored nodes are assumed to be won by the system, so the environment
+    /// winning a bounded game shows that the specification is unrealizable.
+    #[clap(
+        long = "depth-bounded-exploration",
+        name = "max-depth",
+        about = "Explore the game up to doubling depths before the general exploration",
+        display_order = 37
+    )]
+    pub depth_bounded_exploration: Option<std::num::NonZeroUsize>,
+    /// Decide nodes of the game by cheap reductions before each call to the
+    /// parity game solver.
+    ///
+    /// Nodes with a self-loop that is forced or chosen by the owner for a color of
+    /// the owner's parity, cycles of nodes with a single successor and the attractors
+    /// of these nodes are decided directly. The number of decided nodes is reported
+    /// with the solving statistics.
+    #[clap(
+        long = "preprocess",
+        about = "Decide nodes by cheap reductions before solving the game",
+        display_order = 38
+    )]
+    pub game_preprocessing: bool,
+    /// Write the events at the stage boundaries of the synthesis procedure,
+    /// with the elapsed time and key metrics of each stage, as JSON lines to the
+    /// given file. When converting a machine, only the stages following the
+    /// construction of the machine are logged.
+    #[clap(
+        long = "event-log",
+        name = "event-file",
+        about = "Write events of the synthesis stages as JSON lines to the given file",
+        display_order = 39
+    )]
+    pub event_log: Option<String>,
+    /// The maximum heap size of the GraalVM running Owl in megabytes.
+    ///
+    /// The GraalVM is shared by all synthesis calls of a thread that use the same
+    /// heap size, and is only created again if the heap size changes.
+    #[clap(
+        long = "vm-heap-size",
+        name = "heap-megabytes",
+        about = "Maximum heap size of the GraalVM in megabytes",
+        display_order = 42
+    )]
+    pub vm_heap_size: Option<usize>,
+    /// The order of the inputs and outputs in the atomic propositions of a machine
+    /// in HOA format.
+    ///
+    /// The labels and the controllable atomic propositions refer to the positions in
+    /// this order. If the outputs are listed first, the header additionally states the
+    /// type of the machine, as the controllable atomic propositions are then not
+    /// sufficient to distinguish Mealy and Moore machines. Aiger circuits and BDDs
+    /// are not affected, as only their inputs are variables, while their outputs are
+    /// functions of the inputs and the state.
+    #[clap(
+        arg_enum,
+        long = "ap-order",
+        name = "ap-order",
+        default_value,
+        about = "Order of inputs and outputs in the atomic propositions of a machine",
+        display_order = 43
+    )]
+    pub ap_order: ApOrder,
+    /// Write the winning regions and strategies of both players in the explored game
+    /// to the given file in the PGSolver format for solutions.
+    ///
+    /// The nodes refer to the game as written with output format `pg`, where each node
+    /// owned by its winner is annotated with the successor chosen by the winner. Border
+    /// nodes and nodes not yet decided by the solver are omitted, such that the file
+    /// also shows the parts of the game won by the environment for realizable
+    /// specifications and the parts won by the system for unrealizable specifications.
+    #[clap(
+        long = "strategies-output",
+        name = "strategies-file",
+        about = "Write the winning regions and strategies of both players to the given file",
+        display_order = 44
+    )]
+    pub strategies_output: Option<String>,
+    /// The objective for selecting the aiger circuit among the candidates of the
+    /// portfolio approach, given as a comma-separated list of the metrics `size`,
+    /// `gates`, `latches`, `depth`, `inputs`, `states`, `io-delay` and `latch-delay`,
+    /// which are minimized in
+    /// lexicographic order, e.g. `depth,size` for the smallest circuit of minimum depth.
+    ///
+    /// The candidates that are Pareto-optimal with respect to the number of and gates,
+    /// latches, inputs, states and the depth are logged before the selection.
+    #[clap(
+        long = "aiger-objective",
+        name = "metrics",
+        default_value,
+        about = "Metrics minimized in lexicographic order for selecting the aiger circuit",
+        display_order = 45
+    )]
+    pub aiger_objective: AigerObjective,
+    /// Merge equivalent nodes of the game before each call to the parity game solver.
+    ///
+    /// Nodes are equivalent if they have the same owner and color and their successors
+    /// are equivalent, even if they are reached through different paths in the edge
+    /// trees of the automaton. The solver is called on the game of the classes of
+    /// equivalent nodes, and the number of classes is reported with the solving statistics.
+    #[clap(
+        long = "merge-equivalent",
+        about = "Merge equivalent nodes of the game before solving",
+        display_order = 46
+    )]
+    pub merge_equivalent: bool,
+    /// The metrics for which the Pareto frontier of the candidate aiger circuits is kept
+    /// in the result, which is available with
+    /// [`SynthesisResult::pareto_frontier`](crate::SynthesisResult::pareto_frontier).
+    ///
+    /// The candidates are the circuits of the portfolio approach with option `--aiger`,
+    /// or the single constructed circuit otherwise. No frontier is kept if no metrics
+    /// are given or the output format is not an aiger circuit.
+    #[clap(skip)]
+    pub pareto_metrics: Vec<AigerMetric>,
+    /// Additional outputs of the controller in other formats, which are constructed from
+    /// the same game, machine, BDD or aiger circuit as the controller in the main output
+    /// format, and are available with
+    /// [`SynthesisResult::artifacts`](crate::SynthesisResult::artifacts).
+    ///
+    /// The formats must differ from each other and from the main output format, and
+    /// parity games can not be an additional output.
+    #[clap(
+        long = "also-output",
+        name = "format[:file]",
+        number_of_values = 1,
+        about = "Additionally write the controller in the given format to the given file",
+        display_order = 47
+    )]
+    pub outputs: Vec<OutputTarget>,
+    /// Cross-check the realizability status by bounded synthesis with Mealy machines
+    /// and counter-strategies of at most the given number of states.
+    ///
+    /// The check is independent of the game construction and solving, and only applies
+    /// to specifications with few propositions and automaton states. A status contradicted
+    /// by the check is reported as a warning, and the outcome is available with
+    /// [`SynthesisResult::cross_check`](crate::SynthesisResult::cross_check).
+    #[clap(
+        long = "cross-check",
+        name = "max-states",
+        about = "Check the status by bounded synthesis up to the given number of states",
+        display_order = 48
+    )]
+    pub cross_check: Option<usize>,
+    /// Decide nodes of the game on abstract games before each call to the parity game solver.
+    ///
+    /// The abstraction groups the nodes for automaton states with the same state formula,
+    /// which only differ in their round-robin counters and permutations, and lets the
+    /// opponent choose the node within a class. Nodes won in the abstract games are also
+    /// won in the game, and classes that do not decide are refined by splitting nodes
+    /// that are not equivalent in the abstract game.
+    #[clap(
+        long = "abstraction-refinement",
+        about = "Decide nodes on refined abstract games before solving",
+        display_order = 49
+    )]
+    pub abstraction_refinement: bool,
+    /// Numbers of states of the automaton that are possible initial states, among which
+    /// the environment chooses at the initial node of the game.
+    ///
+    /// The states are numbered in the order in which they are discovered, starting with 0
+    /// for the initial state, as in the labels of the parity game output. A controller
+    /// has to win from each of the states, and its machine has an initial state for each
+    /// of them, where a BDD or aiger circuit starts in one of them. States that are not
+    /// reachable from the initial state are ignored with a warning.
+    #[clap(
+        long = "initial-states",
+        name = "states",
+        about = "Comma-separated list of automaton states chosen by the environment as initial state",
+        use_delimiter = true,
+        display_order = 50
+    )]
+    pub initial_states: Vec<usize>,
+    /// Fairness assumptions on inputs, which are added as a conjunction to the
+    /// assumption of a formula of the form `A -> G`, or as the assumption of any
+    /// other formula.
+    ///
+    /// Each assumption is given as `input` for `G F input`, as `enabled->taken`
+    /// for strong fairness `G F enabled -> G F taken` or as `enabled~>taken`
+    /// for weak fairness `F G enabled -> G F taken`, see [`Fairness`]. All
+    /// propositions of an assumption need to be inputs.
+    #[clap(
+        long = "fair-ins",
+        name = "fairness",
+        about = "Comma-separated list of fairness assumptions input, enabled->taken or enabled~>taken",
+        use_delimiter = true,
+        display_order = 51
+    )]
+    pub fair_inputs: Vec<Fairness>,
+    /// The capacity of the cache of and gates while constructing aiger circuits.
+    ///
+    /// The cache reuses and gates with the same inputs, and can grow as large as the
+    /// circuit itself. A bounded cache evicts the least recently used gates, and
+    /// without a cache no inputs of and gates are hashed at all. Statistics of the
+    /// cache are logged on the debug level.
+    #[clap(
+        long = "aiger-and-cache",
+        name = "capacity",
+        default_value,
+        about = "Capacity of the cache of and gates for aiger circuits: unbounded, none or a number",
+        display_order = 52
+    )]
+    pub aiger_and_cache: AndCacheCapacity,
+    /// Upper bounds on metrics of the selected aiger circuit, given as a comma-separated
+    /// list of `metric=bound` with the metrics of option `--aiger-objective`, e.g.
+    /// `io-delay=4,latch-delay=6` for the latency of the circuit.
+    ///
+    /// Among the candidates of the portfolio approach, the circuit is selected by the
+    /// objective from the candidates that meet all bounds. If no candidate meets them,
+    /// the circuit is selected from all candidates and a warning is logged.
+    #[clap(
+        long = "aiger-thresholds",
+        name = "metric=bound",
+        about = "Comma-separated upper bounds on metrics of the selected aiger circuit",
+        display_order = 53
+    )]
+    pub aiger_thresholds: Option<AigerThresholds>,
+    /// The seed for the random determinization policy.
+    ///
+    /// With determinization policy `random`, the seed determines the choices among the
+    /// successors and outputs of the machine and the tie-breaking when propagating outputs
+    /// of the strategy with option `--propagate-outputs`, so that the same seed yields
+    /// the same controller and different seeds sample different winning controllers.
+    #[clap(
+        long = "determinization-seed",
+        name = "seed",
+        default_value = "0",
+        about = "Seed for the random determinization policy",
+        display_order = 54
+    )]
+    pub determinization_seed: u64,
+    /// Write a report on the inputs and outputs needed by the aiger circuit to the given
+    /// file, which is also available with
+    /// [`SynthesisResult::interface`](crate::SynthesisResult::interface).
+    ///
+    /// An input is needed if an output or latch of the circuit depends on it, and an
+    /// output is needed if it is not driven by a constant. The report also shows the
+    /// status of each proposition after realizability simplification, and the mapping
+    /// of the interface reduced with option `--reduce-interface` to the original one.
+    #[clap(
+        long = "interface-report",
+        name = "report-file",
+        about = "Write a report on the inputs and outputs needed by the aiger circuit to the given file",
+        display_order = 55
+    )]
+    pub interface_report: Option<String>,
+    /// Remove the inputs not read and the outputs driven by constants from the interface
+    /// of the aiger circuit, e.g. for signals of specifications copied from templates.
+    ///
+    /// The removed signals and the mapping of the remaining signals to the original
+    /// interface are given in the report of option `--interface-report`.
+    #[clap(
+        long = "reduce-interface",
+        about = "Remove unread inputs and constant outputs from the aiger circuit",
+        display_order = 56
+    )]
+    pub reduce_interface: bool,
+    /// Keep the bits of each component of structured labels adjacent in the variable
+    /// order of the BDDs during reordering.
+    ///
+    /// Otherwise, reordering may interleave bits of unrelated components, which can
+    /// increase the size of the BDDs and makes the resulting circuits harder to read.
+    #[clap(
+        long = "bdd-group-labels",
+        about = "Keep bits of each label component adjacent when reordering BDD variables",
+        display_order = 59
+    )]
+    pub bdd_group_components: bool,
+    /// The handling of atomic propositions of the formula that are neither declared
+    /// as inputs nor as outputs.
+    ///
+    /// By default, such propositions are rejected, as a specification in which they are
+    /// silently treated as either inputs or outputs may have a different realizability
+    /// status than intended. Otherwise, they are added to the inputs or outputs with a
+    /// warning, which is also emitted as [event](crate::events::PipelineEvent).
+    #[clap(
+        arg_enum,
+        long = "undeclared",
+        name = "undeclared",
+        default_value,
+        about = "Handling of propositions of the formula that are not declared",
+        display_order = 60
+    )]
+    pub undeclared: Undeclared,
+    /// Share subfunctions of the BDDs of different managers in the aiger circuit.
+    ///
+    /// If the BDDs of the controller are partitioned across several managers, see
+    /// [`SynthesisOptions::bdd_partition_threshold`], nodes are only shared within each
+    /// manager. With this option, the functions of all nodes are identified in a common
+    /// manager before constructing the aiger circuit, and nodes of different managers
+    /// with the same function or its negation are translated only once.
+    #[clap(
+        long = "bdd-share",
+        about = "Share subfunctions of BDDs across managers in aiger circuits",
+        display_order = 61
+    )]
+    pub bdd_share_subfunctions: bool,
+    /// Analyze the robustness of the controller to glitches of the inputs, where the
+    /// controller reads a corrupted value of a single input at most once in every window
+    /// of the given number of steps.
+    ///
+    /// The analysis checks whether the controller still satisfies the specification under
+    /// all such glitches, on the product of the controller with a separate automaton for
+    /// the formula. It only applies to Mealy machines and aiger circuits for realizable
+    /// specifications with few propositions, automaton states and controller states. A
+    /// violation is reported as a warning, and the outcome is available with
+    /// [`SynthesisResult::robustness`](crate::SynthesisResult::robustness).
+    #[clap(
+        long = "glitch-window",
+        name = "steps",
+        about = "Check robustness of the controller to a corrupted input in every window of \
+                 the given number of steps",
+        display_order = 62
+    )]
+    pub glitch_window: Option<usize>,
+    /// Replace a controller that is not robust to glitches of the inputs, see
+    /// [`SynthesisOptions::glitch_window`], by a robust Mealy machine found by bounded
+    /// synthesis with the glitches, if one exists with few states.
+    #[clap(
+        long = "glitch-resynthesis",
+        about = "Synthesize a robust controller if the controller is not robust to glitches",
+        display_order = 63
+    )]
+    pub glitch_resynthesis: bool,
+    /// Hints for the winning regions of both players in the game, e.g. from the solution
+    /// of a previous run on a related specification, see
+    /// [`GameSolution::winning_region`](crate::parity::solution::GameSolution::winning_region).
+    ///
+    /// The nodes refer to the game as written with output format `pg`. Hinted nodes are
+    /// only added to the winning regions once the solver verifies that the player wins
+    /// them, and incorrect hints are rejected, so hints may only speed up solving.
+    #[clap(skip)]
+    pub winning_hints: Option<WinningRegion>,
+    /// An assumption monitor added to the controller in BTOR2 format, whose outputs signal
+    /// violations of assumptions on the environment and whose inputs are connected to
+    /// the inputs and outputs of the controller with the same name, see
+    /// [`AigerController::to_btor2`](crate::controller::AigerController::to_btor2).
+    #[clap(skip)]
+    pub btor2_assumption: Option<AigerController>,
+    /// A deployed controller for a previous version of the specification, which is
+    /// repaired instead of constructing a new controller.
+    ///
+    /// The repaired controller keeps the latches of the old controller and its outputs
+    /// wherever they are allowed by a machine for the new specification, and only
+    /// replaces the outputs where necessary. The old controller needs to have the same
+    /// inputs and outputs as the specification by name.
+    #[clap(skip)]
+    pub repair_controller: Option<AigerController>,
+}
+
+impl SynthesisOptions {
+    /// Checks whether this combination of options is supported by the synthesis procedure.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the conflict if the combination of options is
+    /// not supported, or if an option would be silently ignored.
+    pub fn validate(&self) -> Result<(), SynthesisError> {
+        if self.oink_solver.is_some() && self.parity_solver != Solver::Oink {
+            return Err(SynthesisError::IncompatibleOptions(
+                "option oink-solver can only be used with solver oink".to_string(),
+            ));
+        }
+        if self.budget_solving.is_some() && !self.exploration_border_free {
+            return Err(SynthesisError::IncompatibleOptions(
+                "option budget-solving can only be used with option border-free".to_string(),
+            ));
+        }
+        let circuit = self.requires_format(OutputFormat::Aag)
+            || self.requires_format(OutputFormat::Aig)
+            || self.requires_format(OutputFormat::Blif)
+            || self.requires_format(OutputFormat::Btor2)
+            || self.requires_format(OutputFormat::Verilog);
+        if !circuit {
+            let conflicting = [
+                ("interface-report", self.interface_report.is_some()),
+                ("reduce-interface", self.reduce_interface),
+            ];
+            if let Some((option, _)) = conflicting.iter().find(|(_, set)| *set) {
+                return Err(SynthesisError::IncompatibleOptions(format!(
+                    "option {} can only be used with an aiger circuit or lookup tables as output",
+                    option
+                )));
+            }
+        }
+        if self.btor2_assumption.is_some() && !self.requires_format(OutputFormat::Btor2) {
+            return Err(SynthesisError::IncompatibleOptions(
+                "option btor2-assumption can only be used with output format btor2".to_string(),
+            ));
+        }
+        if self.repair_controller.is_some() {
+            // the labels of the repaired machine encode the latches of the old controller
+            let conflicting = [
+                ("aiger", self.aiger_portfolio),
+                (
+                    "minimize",
+                    self.machine_minimization != MinimizationMethod::None,
+                ),
+                ("label", self.label_structure != LabelStructure::None),
+                (
+                    "label-compression",
+                    self.label_compression != LabelCompression::None,
+                ),
+                (
+                    "determinization-policy",
+                    self.machine_determinization_policy == DeterminizationPolicy::LowToggle,
+                ),
+                ("output-format pg", self.output_format == OutputFormat::Pg),
+                (
+                    "also-output kiss2",
+                    self.requires_format(OutputFormat::Kiss2),
+                ),
+            ];
+            if let Some((option, _)) = conflicting.iter().find(|(_, set)| *set) {
+                return Err(SynthesisError::IncompatibleOptions(format!(
+                    "option {} can not be used with option repair",
+                    option
+                )));
+            }
+        }
+        if self.determinization_seed != 0
+            && self.machine_determinization_policy != DeterminizationPolicy::Random
+        {
+            return Err(SynthesisError::IncompatibleOptions(
+                "option determinization-seed can only be used with determinization policy random"
+                    .to_string(),
+            ));
+        }
+        if self.aiger_objective != AigerObjective::default() && !self.aiger_portfolio {
+            return Err(SynthesisError::IncompatibleOptions(
+                "option aiger-objective can only be used with option aiger".to_string(),
+            ));
+        }
+        if self.glitch_resynthesis && self.glitch_window.is_none() {
+            return Err(SynthesisError::IncompatibleOptions(
+                "option glitch-resynthesis can only be used with option glitch-window".to_string(),
+            ));
+        }
+        if self.glitch_window == Some(0) {
+            return Err(SynthesisError::IncompatibleOptions(
+                "option glitch-window requires a window of at least one step".to_string(),
+            ));
+        }
+        if !self.initial_states.is_empty() {
+            // all check the specification from the initial state of its automaton
+            let conflicting = [
+                ("cross-check", self.cross_check.is_some()),
+                ("glitch-window", self.glitch_window.is_some()),
+                ("assumption-dontcares", self.assumption_dontcares),
+            ];
+            if let Some((option, _)) = conflicting.iter().find(|(_, set)| *set) {
+                return Err(SynthesisError::IncompatibleOptions(format!(
+                    "option {} can not be used with option initial-states",
+                    option
+                )));
+            }
+        }
+        for (i, target) in self.outputs.iter().enumerate() {
+            if target.format == OutputFormat::Pg {
+                return Err(SynthesisError::IncompatibleOptions(
+                    "a parity game can not be an additional output".to_string(),
+                ));
+            }
+            if target.format == self.output_format
+                || self.outputs[..i].iter().any(|t| t.format == target.format)
+            {
+                return Err(SynthesisError::IncompatibleOptions(format!(
+                    "output format {} given more than once",
+                    target.format
+                )));
+            }
+        }
+        if self.output_format == OutputFormat::Pg {
+            let ignored = [
+                ("aiger", self.aiger_portfolio),
+                ("determinize", self.machine_determinization),
+                (
+                    "determinization-policy",
+                    self.machine_determinization_policy != DeterminizationPolicy::default(),
+                ),
+                (
+                    "minimize",
+                    self.machine_minimization != MinimizationMethod::None,
+                ),
+                ("label", self.label_structure != LabelStructure::None),
+                (
+                    "label-compression",
+                    self.label_compression != LabelCompression::None,
+                ),
+                ("assumption-dontcares", self.assumption_dontcares),
+                ("ap-order", self.ap_order != ApOrder::default()),
+                ("also-output", !self.outputs.is_empty()),
+                ("glitch-window", self.glitch_window.is_some()),
+            ];
+            if let Some((option, _)) = ignored.iter().find(|(_, set)| *set) {
+                return Err(SynthesisError::IncompatibleOptions(format!(
+                    "option {} can not be used with parity game output",
+                    option
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether a controller in the given format is requested, either as the
+    /// main output format or as an additional output.
+    pub(crate) fn requires_format(&self, format: OutputFormat) -> bool {
+        self.output_format == format || self.outputs.iter().any(|t| t.format == format)
+    }
+
+    /// Returns whether only machines in HOA or KISS2 format are requested.
+    pub(crate) fn only_machines(&self) -> bool {
+        let is_machine = |format| matches!(format, OutputFormat::Hoa | OutputFormat::Kiss2);
+        is_machine(self.output_format) && self.outputs.iter().all(|t| is_machine(t.format))
+    }
 }
 
 impl From<&CliOptions> for SynthesisOptions {
@@ -0,0 +1,627 @@
+//! Expansion of integer parameters in LTL formulas, which allows to describe a
+//! family of specifications by a single source file.
+//!
+//! A parameter is declared on a line of its own with `param N = 3`, where the value
+//! may be an integer expression over previously declared parameters and an optional
+//! `;` may terminate the line. The declarations are removed from the formula, and the
+//! following constructs are expanded in the remaining formula:
+//!
+//! - `{expr}` is replaced by the value of the integer expression, e.g. `r{i+1}`
+//!   becomes `r3` for `i = 2`. Expressions support `+`, `-`, `*`, `/`, `%`,
+//!   parentheses, integer literals and the names of parameters and bound variables.
+//! - `forall i in a..b { body }` is replaced by the conjunction of the body for all
+//!   values of `i` from `a` to `b` inclusively, and `exists i in a..b { body }` by the
+//!   disjunction. An empty range yields `true` and `false`, respectively.
+//! - `X[expr] f` is replaced by the formula `f` preceded by `expr` next operators.
+//!
+//! Plain identifiers are never replaced, so a parameter named like an atomic
+//! proposition does not change the formula outside of braces.
+
+use std::error::Error;
+use std::fmt;
+
+/// An error when expanding the parameters of a formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandError {
+    /// The byte position in the source at which the error occurred.
+    position: usize,
+    /// The description of the error.
+    message: String,
+}
+
+impl ExpandError {
+    fn new<S: Into<String>>(position: usize, message: S) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the byte position in the source at which the error occurred.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ExpandError {}
+
+/// A formula with all parameters expanded, together with the values of the parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandedFormula {
+    formula: String,
+    parameters: Vec<(String, i64)>,
+}
+
+impl ExpandedFormula {
+    /// Returns the expanded formula.
+    pub fn formula(&self) -> &str {
+        &self.formula
+    }
+
+    /// Returns the declared parameters with their values in the order of declaration.
+    pub fn parameters(&self) -> &[(String, i64)] {
+        &self.parameters
+    }
+
+    /// Expands the given names of atomic propositions with the parameters of the formula.
+    ///
+    /// A name may contain `{expr}`, which is replaced by the value of the expression,
+    /// and at most one range `{a..b}`, for which the name is replaced by one name for
+    /// each value of the range, e.g. `r{1..N}` becomes `r1`, `r2` and `r3` for `N = 3`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an expression in a name is malformed, where the position
+    /// refers to the name.
+    pub fn expand_propositions<S: AsRef<str>>(
+        &self,
+        names: &[S],
+    ) -> Result<Vec<String>, ExpandError> {
+        let mut expanded = Vec::new();
+        for name in names {
+            let name = name.as_ref();
+            let expander = Expander::new(name, self.parameters.clone());
+            match find_range(name) {
+                Some((open, close)) => {
+                    let (from, to) = expander.range(open + 1, close)?;
+                    for value in from..=to {
+                        let mut result = String::new();
+                        expander.substitute(0, open, &mut result)?;
+                        result.push_str(&value.to_string());
+                        expander.substitute(close + 1, name.len(), &mut result)?;
+                        expanded.push(result);
+                    }
+                }
+                None => {
+                    let mut result = String::new();
+                    expander.substitute(0, name.len(), &mut result)?;
+                    expanded.push(result);
+                }
+            }
+        }
+        Ok(expanded)
+    }
+}
+
+/// Returns the positions of the braces of the first range `{a..b}` in a name.
+fn find_range(name: &str) -> Option<(usize, usize)> {
+    let mut start = 0;
+    while let Some(open) = name[start..].find('{').map(|i| start + i) {
+        let close = name[open..].find('}').map(|i| open + i)?;
+        if name[open..close].contains("..") {
+            return Some((open, close));
+        }
+        start = close;
+    }
+    None
+}
+
+/// Parses an assignment of a value to a parameter of the form `N=3`, as given
+/// on the command line to override the value declared in a formula.
+///
+/// # Errors
+///
+/// Returns an error if the assignment is malformed.
+pub fn parse_assignment(assignment: &str) -> Result<(String, i64), ExpandError> {
+    let (name, value) = assignment.split_once('=').ok_or_else(|| {
+        ExpandError::new(0, format!("expected 'name=value', got '{}'", assignment))
+    })?;
+    let name = name.trim();
+    if !is_identifier(name) {
+        return Err(ExpandError::new(
+            0,
+            format!("invalid parameter name '{}'", name),
+        ));
+    }
+    let value = value.trim().parse().map_err(|_| {
+        ExpandError::new(
+            name.len() + 1,
+            format!("invalid value '{}' for parameter {}", value.trim(), name),
+        )
+    })?;
+    Ok((name.to_string(), value))
+}
+
+/// Expands the parameters of the given formula, where the values in `overrides`
+/// take precedence over the values declared in the formula.
+///
+/// A formula without declarations, braces and replications is returned unchanged,
+/// except for surrounding whitespace.
+///
+/// # Errors
+///
+/// Returns an error with the position in the formula if a declaration or expression
+/// is malformed, a parameter is declared twice or is unknown, or an override is
+/// given for a parameter that is not declared.
+pub fn expand_parameters(
+    source: &str,
+    overrides: &[(String, i64)],
+) -> Result<ExpandedFormula, ExpandError> {
+    let mut parameters: Vec<(String, i64)> = Vec::new();
+    // declarations are blanked out to keep the positions of the remaining formula
+    let mut body = String::with_capacity(source.len());
+    let mut line_start = 0;
+    for line in source.split_inclusive('\n') {
+        match declaration(line) {
+            Some((name_offset, rest_offset)) => {
+                let name_start = line_start + name_offset;
+                let rest = &line[rest_offset..];
+                let name = &line[name_offset..rest_offset];
+                let value_start = rest.find('=').ok_or_else(|| {
+                    ExpandError::new(line_start + rest_offset, "expected '=' in declaration")
+                })? + 1;
+                let value_end = rest.trim_end().trim_end_matches(';').len();
+                if parameters.iter().any(|(p, _)| p == name) {
+                    return Err(ExpandError::new(
+                        name_start,
+                        format!("parameter {} declared more than once", name),
+                    ));
+                }
+                let value = Expander::new(source, parameters.clone()).evaluate(
+                    line_start + rest_offset + value_start,
+                    line_start + rest_offset + value_end.max(value_start),
+                )?;
+                let value = overrides
+                    .iter()
+                    .find(|(p, _)| p == name)
+                    .map_or(value, |&(_, v)| v);
+                parameters.push((name.to_string(), value));
+                body.extend(line.bytes().map(|b| if b == b'\n' { '\n' } else { ' ' }));
+            }
+            None => body.push_str(line),
+        }
+        line_start += line.len();
+    }
+    if let Some((name, _)) = overrides
+        .iter()
+        .find(|(name, _)| !parameters.iter().any(|(p, _)| p == name))
+    {
+        return Err(ExpandError::new(
+            0,
+            format!("parameter {} is not declared in the formula", name),
+        ));
+    }
+
+    let mut formula = String::with_capacity(body.len());
+    Expander::new(&body, parameters.clone()).expand(0, body.len(), &mut formula)?;
+    Ok(ExpandedFormula {
+        formula: formula.trim().to_string(),
+        parameters,
+    })
+}
+
+/// Returns the offsets of the name and the rest of a declaration `param N = ...`,
+/// or `None` if the line is not a declaration.
+fn declaration(line: &str) -> Option<(usize, usize)> {
+    let trimmed = line.trim_start();
+    let rest = trimmed.strip_prefix("param")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let name_offset = line.len() - rest.trim_start().len();
+    let name_len = line[name_offset..]
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(line.len() - name_offset);
+    if name_len == 0 {
+        return None;
+    }
+    Some((name_offset, name_offset + name_len))
+}
+
+/// Returns whether the string is a valid name of a parameter.
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Expands a part of a source, with the values of the parameters and bound variables
+/// in scope, where inner scopes are at the end.
+struct Expander<'a> {
+    source: &'a str,
+    scope: Vec<(String, i64)>,
+}
+
+impl<'a> Expander<'a> {
+    fn new(source: &'a str, scope: Vec<(String, i64)>) -> Self {
+        Self { source, scope }
+    }
+
+    fn char_at(&self, i: usize) -> Option<char> {
+        self.source.get(i..).and_then(|s| s.chars().next())
+    }
+
+    fn skip_whitespace(&self, mut i: usize, end: usize) -> usize {
+        while i < end && self.char_at(i).map_or(false, char::is_whitespace) {
+            i += 1;
+        }
+        i
+    }
+
+    fn identifier_end(&self, i: usize, end: usize) -> usize {
+        self.source[i..end]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(end, |len| i + len)
+    }
+
+    /// Returns the position of the closing delimiter matching the opening one at `open`.
+    fn matching(
+        &self,
+        open: usize,
+        end: usize,
+        delimiters: (char, char),
+    ) -> Result<usize, ExpandError> {
+        let mut depth = 0_usize;
+        for (i, c) in self.source[open..end].char_indices() {
+            if c == delimiters.0 {
+                depth += 1;
+            } else if c == delimiters.1 {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(open + i);
+                }
+            }
+        }
+        Err(ExpandError::new(
+            open,
+            format!("unmatched '{}'", delimiters.0),
+        ))
+    }
+
+    /// Appends the source between `start` and `end` with all constructs expanded.
+    fn expand(&mut self, start: usize, end: usize, out: &mut String) -> Result<(), ExpandError> {
+        let mut quoted = false;
+        let mut i = start;
+        while i < end {
+            let c = self.char_at(i).unwrap();
+            match c {
+                '{' => {
+                    let close = self.matching(i, end, ('{', '}'))?;
+                    out.push_str(&self.evaluate(i + 1, close)?.to_string());
+                    i = close + 1;
+                }
+                '}' => return Err(ExpandError::new(i, "unmatched '}'")),
+                '"' => {
+                    quoted = !quoted;
+                    out.push(c);
+                    i += 1;
+                }
+                _ if !quoted && (c.is_alphanumeric() || c == '_') => {
+                    let word_end = self.identifier_end(i, end);
+                    let word = &self.source[i..word_end];
+                    i = if word == "forall" || word == "exists" {
+                        match self.replication(word == "forall", word_end, end, out)? {
+                            Some(next) => next,
+                            None => {
+                                out.push_str(word);
+                                word_end
+                            }
+                        }
+                    } else if self.char_at(word_end) == Some('[')
+                        && word.ends_with('X')
+                        && word.chars().all(|c| matches!(c, 'X' | 'F' | 'G'))
+                    {
+                        let close = self.matching(word_end, end, ('[', ']'))?;
+                        let count = self.evaluate(word_end + 1, close)?;
+                        if count < 0 {
+                            return Err(ExpandError::new(
+                                word_end + 1,
+                                format!("negative number {} of next operators", count),
+                            ));
+                        }
+                        let mut operators = word[..word.len() - 1].to_string();
+                        for _ in 0..count {
+                            if !operators.is_empty() {
+                                operators.push(' ');
+                            }
+                            operators.push('X');
+                        }
+                        out.push_str(&operators);
+                        if !operators.is_empty()
+                            && !self.char_at(close + 1).map_or(true, char::is_whitespace)
+                        {
+                            out.push(' ');
+                        }
+                        close + 1
+                    } else {
+                        out.push_str(word);
+                        word_end
+                    };
+                }
+                _ => {
+                    out.push(c);
+                    i += c.len_utf8();
+                }
+            }
+        }
+        if quoted {
+            return Err(ExpandError::new(end, "unterminated quoted proposition"));
+        }
+        Ok(())
+    }
+
+    /// Expands a replication `forall i in a..b { body }` or `exists i in a..b { body }`
+    /// following the keyword, and returns the position after it, or `None` if the
+    /// keyword is not followed by a variable and `in`.
+    fn replication(
+        &mut self,
+        conjunction: bool,
+        start: usize,
+        end: usize,
+        out: &mut String,
+    ) -> Result<Option<usize>, ExpandError> {
+        let var_start = self.skip_whitespace(start, end);
+        let var_end = self.identifier_end(var_start, end);
+        let in_start = self.skip_whitespace(var_end, end);
+        let in_end = self.identifier_end(in_start, end);
+        if var_start == start || var_end == var_start || &self.source[in_start..in_end] != "in" {
+            return Ok(None);
+        }
+        let variable = self.source[var_start..var_end].to_string();
+        if !is_identifier(&variable) {
+            return Err(ExpandError::new(
+                var_start,
+                format!("invalid variable name '{}'", variable),
+            ));
+        }
+        let open = self.source[in_end..end]
+            .find('{')
+            .map(|i| in_end + i)
+            .ok_or_else(|| ExpandError::new(in_end, "expected '{' after range"))?;
+        let close = self.matching(open, end, ('{', '}'))?;
+        let (from, to) = self.range(in_end, open)?;
+
+        if from > to {
+            out.push_str(if conjunction { "true" } else { "false" });
+        }
+        for value in from..=to {
+            if value > from {
+                out.push_str(if conjunction { " & " } else { " | " });
+            }
+            let mut body = String::new();
+            self.scope.push((variable.clone(), value));
+            let result = self.expand(open + 1, close, &mut body);
+            self.scope.pop();
+            result?;
+            out.push('(');
+            out.push_str(body.trim());
+            out.push(')');
+        }
+        Ok(Some(close + 1))
+    }
+
+    /// Evaluates a range `a..b` between `start` and `end`.
+    fn range(&self, start: usize, end: usize) -> Result<(i64, i64), ExpandError> {
+        let separator = self.source[start..end]
+            .find("..")
+            .map(|i| start + i)
+            .ok_or_else(|| ExpandError::new(start, "expected a range 'a..b'"))?;
+        Ok((
+            self.evaluate(start, separator)?,
+            self.evaluate(separator + 2, end)?,
+        ))
+    }
+
+    /// Appends the source between `start` and `end`, where only `{expr}` is replaced.
+    fn substitute(&self, start: usize, end: usize, out: &mut String) -> Result<(), ExpandError> {
+        let mut i = start;
+        while let Some(open) = self.source[i..end].find('{').map(|o| i + o) {
+            let close = self.matching(open, end, ('{', '}'))?;
+            out.push_str(&self.source[i..open]);
+            out.push_str(&self.evaluate(open + 1, close)?.to_string());
+            i = close + 1;
+        }
+        out.push_str(&self.source[i..end]);
+        Ok(())
+    }
+
+    /// Evaluates the integer expression between `start` and `end`.
+    fn evaluate(&self, start: usize, end: usize) -> Result<i64, ExpandError> {
+        let mut parser = ExpressionParser {
+            expander: self,
+            index: start,
+            end,
+        };
+        let value = parser.parse_sum()?;
+        parser.skip_whitespace();
+        if parser.index < end {
+            return Err(ExpandError::new(
+                parser.index,
+                "unexpected token in expression",
+            ));
+        }
+        Ok(value)
+    }
+}
+
+/// A recursive descent parser and evaluator for integer expressions.
+struct ExpressionParser<'e, 'a> {
+    expander: &'e Expander<'a>,
+    index: usize,
+    end: usize,
+}
+
+impl ExpressionParser<'_, '_> {
+    fn skip_whitespace(&mut self) {
+        self.index = self.expander.skip_whitespace(self.index, self.end);
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        Some(self.expander.char_at(self.index)?).filter(|_| self.index < self.end)
+    }
+
+    fn overflow(position: usize) -> ExpandError {
+        ExpandError::new(position, "integer overflow in expression")
+    }
+
+    fn parse_sum(&mut self) -> Result<i64, ExpandError> {
+        let mut value = self.parse_product()?;
+        while let Some(op) = self.peek().filter(|&c| c == '+' || c == '-') {
+            let position = self.index;
+            self.index += 1;
+            let right = self.parse_product()?;
+            value = if op == '+' {
+                value.checked_add(right)
+            } else {
+                value.checked_sub(right)
+            }
+            .ok_or_else(|| Self::overflow(position))?;
+        }
+        Ok(value)
+    }
+
+    fn parse_product(&mut self) -> Result<i64, ExpandError> {
+        let mut value = self.parse_atom()?;
+        while let Some(op) = self.peek().filter(|c| "*/%".contains(*c)) {
+            let position = self.index;
+            self.index += 1;
+            let right = self.parse_atom()?;
+            if op != '*' && right == 0 {
+                return Err(ExpandError::new(position, "division by zero"));
+            }
+            value = match op {
+                '*' => value.checked_mul(right),
+                '/' => value.checked_div(right),
+                _ => value.checked_rem(right),
+            }
+            .ok_or_else(|| Self::overflow(position))?;
+        }
+        Ok(value)
+    }
+
+    fn parse_atom(&mut self) -> Result<i64, ExpandError> {
+        let position = self.index;
+        match self.peek() {
+            Some('-') => {
+                self.index += 1;
+                self.parse_atom()?
+                    .checked_neg()
+                    .ok_or_else(|| Self::overflow(position))
+            }
+            Some('(') => {
+                self.index += 1;
+                let value = self.parse_sum()?;
+                if self.peek() != Some(')') {
+                    return Err(ExpandError::new(self.index, "expected ')'"));
+                }
+                self.index += 1;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.index;
+                let end = self.expander.source[start..self.end]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .map_or(self.end, |len| start + len);
+                self.index = end;
+                self.expander.source[start..end]
+                    .parse()
+                    .map_err(|_| Self::overflow(start))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let start = self.index;
+                let end = self.expander.identifier_end(start, self.end);
+                self.index = end;
+                let name = &self.expander.source[start..end];
+                self.expander
+                    .scope
+                    .iter()
+                    .rev()
+                    .find(|(p, _)| p == name)
+                    .map(|&(_, value)| value)
+                    .ok_or_else(|| ExpandError::new(start, format!("unknown parameter {}", name)))
+            }
+            Some(c) => Err(ExpandError::new(
+                self.index,
+                format!("unexpected character '{}' in expression", c),
+            )),
+            None => Err(ExpandError::new(self.index, "expected an expression")),
+        }
+    }
+}
+
+/// Tests for expanding parameters.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that declarations, substitutions, replications and next chains are expanded.
+    #[test]
+    fn test_expand_parameters() {
+        let source = "param N = 2;\nparam M = N + 1\nforall i in 1..N { G (r{i} -> X[M-i] g{i}) }";
+        let expanded = expand_parameters(source, &[]).unwrap();
+        assert_eq!(expanded.formula(), "(G (r1 -> X X g1)) & (G (r2 -> X g2))");
+        assert_eq!(
+            expanded.parameters(),
+            &[("N".to_string(), 2), ("M".to_string(), 3)]
+        );
+
+        let expanded = expand_parameters(
+            "param N = 2\nG (r -> exists k in 0..N { X[k] g })",
+            &[("N".to_string(), 0)],
+        )
+        .unwrap();
+        assert_eq!(expanded.formula(), "G (r -> (g))");
+        let empty = expand_parameters("param N = 0\nforall i in 1..N { a{i} }", &[]).unwrap();
+        assert_eq!(empty.formula(), "true");
+
+        let plain = "G (\"forall\" -> F N) & forall";
+        assert_eq!(expand_parameters(plain, &[]).unwrap().formula(), plain);
+    }
+
+    /// Test that names of propositions are expanded with ranges.
+    #[test]
+    fn test_expand_propositions() {
+        let expanded = expand_parameters("param N = 3\nG F a", &[]).unwrap();
+        assert_eq!(
+            expanded
+                .expand_propositions(&["r{1..N}", "g{N-1}", "x"])
+                .unwrap(),
+            vec!["r1", "r2", "r3", "g2", "x"]
+        );
+    }
+
+    /// Test that malformed declarations and expressions are rejected with their position.
+    #[test]
+    fn test_expand_errors() {
+        assert_eq!(expand_parameters("a{M}", &[]).unwrap_err().position(), 2);
+        assert_eq!(
+            expand_parameters("param N = 1\nparam N = 2\na", &[])
+                .unwrap_err()
+                .position(),
+            18
+        );
+        assert_eq!(expand_parameters("a{1/0}", &[]).unwrap_err().position(), 3);
+        assert_eq!(expand_parameters("a{1", &[]).unwrap_err().position(), 1);
+        assert_eq!(expand_parameters("X[-1] a", &[]).unwrap_err().position(), 2);
+        assert!(expand_parameters("a", &[("N".to_string(), 1)]).is_err());
+        assert_eq!(parse_assignment("N = 4"), Ok(("N".to_string(), 4)));
+        assert!(parse_assignment("N").is_err());
+        assert!(parse_assignment("1=2").is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! Fuzzing of the parity game solvers on random parity games.
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use log::{debug, info};
+
+use super::game::generator::{GameGenerator, GeneratorParameters};
+use super::game::{Game, LabelledGame, Node, NodeIndex, Player, Region};
+use super::reduce::reduce_game;
+use super::solver::{AlgorithmStats, FpiSolver, ParityGameSolver, SiSolver, Strategy, ZlkSolver};
+
+/// The configuration for fuzzing the parity game solvers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuzzConfig {
+    /// The parameters for generating random games.
+    pub parameters: GeneratorParameters,
+    /// The number of random games to check.
+    pub num_games: usize,
+    /// The seed of the first game, where the seed is increased by one for each further game.
+    pub seed: u64,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self {
+            parameters: GeneratorParameters::default(),
+            num_games: 1000,
+            seed: 0,
+        }
+    }
+}
+
+/// A failure found during fuzzing, i.e. a disagreement between solvers,
+/// an invalid strategy or a panic of a solver.
+#[derive(Debug, Clone)]
+pub struct FuzzFailure {
+    /// The seed from which the game can be generated again.
+    pub seed: u64,
+    /// The description of the failure.
+    pub description: String,
+    /// The game in PGSolver format.
+    pub game: String,
+    /// A subgame of the game in PGSolver format on which the solvers still fail,
+    /// see [`reduce_failure`].
+    pub reduced_game: String,
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} on game with seed {}:", self.description, self.seed)?;
+        write!(f, "{}", self.game)?;
+        writeln!(f, "Reduced game with failure:")?;
+        write!(f, "{}", self.reduced_game)
+    }
+}
+
+impl std::error::Error for FuzzFailure {}
+
+/// The metrics of a parity game solver accumulated over the checked games.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolverMetrics {
+    /// The name of the solver.
+    pub name: &'static str,
+    /// The time spent by the solver on all games.
+    pub time: Duration,
+    /// The metrics of the algorithm of the solver on all games.
+    pub stats: AlgorithmStats,
+}
+
+impl SolverMetrics {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            time: Duration::default(),
+            stats: AlgorithmStats::default(),
+        }
+    }
+}
+
+/// The report of fuzzing without failures, which compares the metrics of the
+/// solvers on the checked games to guide the selection of an algorithm.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuzzReport {
+    /// The number of checked games.
+    pub num_games: usize,
+    /// The metrics of each solver.
+    pub solvers: Vec<SolverMetrics>,
+}
+
+impl FuzzReport {
+    /// Adds the metrics of the solvers on one further game.
+    fn record(&mut self, solvers: Vec<SolverMetrics>) {
+        self.num_games += 1;
+        if self.solvers.is_empty() {
+            self.solvers = solvers;
+        } else {
+            for (total, metrics) in self.solvers.iter_mut().zip(&solvers) {
+                total.time += metrics.time;
+                total.stats.add(&metrics.stats);
+            }
+        }
+    }
+}
+
+impl fmt::Display for FuzzReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Checked {} games", self.num_games)?;
+        for metrics in &self.solvers {
+            writeln!(
+                f,
+                "{}: time: {:.3}, {}",
+                metrics.name,
+                metrics.time.as_secs_f32(),
+                metrics.stats
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the parity game solvers on random games generated with the given configuration.
+///
+/// For each game, the winning regions computed by all solvers are compared, it is
+/// checked that they partition the game, and the strategies of all solvers for
+/// both players are checked for validity.
+///
+/// Returns the report with the metrics of all solvers on the checked games.
+///
+/// # Errors
+///
+/// Returns the first failure found.
+pub fn fuzz_solvers(config: &FuzzConfig) -> Result<FuzzReport, FuzzFailure> {
+    info!(
+        "Fuzzing solvers on {} games with {} nodes",
+        config.num_games, config.parameters.num_nodes
+    );
+    let mut report = FuzzReport::default();
+    for i in 0..config.num_games {
+        let seed = config.seed.wrapping_add(i as u64);
+        let game = GameGenerator::new(config.parameters, seed).generate();
+        debug!("Checking game with seed {}", seed);
+        match check_game_unwind(&game) {
+            Ok(solvers) => report.record(solvers),
+            Err(description) => {
+                let reduced_game = reduce_with_failure(&game, &description);
+                return Err(FuzzFailure {
+                    seed,
+                    description,
+                    game: game.to_string(),
+                    reduced_game: reduced_game.to_string(),
+                });
+            }
+        }
+    }
+    info!("Solver comparison:\n{}", report);
+    Ok(report)
+}
+
+/// Checks the parity game solvers on the given complete game, and reduces the game
+/// to a small subgame on which the solvers still fail if they fail on the game.
+///
+/// The solvers fail on a game if they disagree on the winning regions, produce an
+/// invalid strategy or panic, and the reduced subgame may show a different failure
+/// than the original game. See [`reduce_game`] for the reduction.
+///
+/// Returns `None` if the solvers do not fail, and otherwise the description of the
+/// failure on the given game together with the reduced subgame.
+///
+/// # Panics
+///
+/// Panics if the game is not complete, i.e. if its border is not empty.
+pub fn reduce_failure(game: &LabelledGame<NodeIndex>) -> Option<(String, LabelledGame<NodeIndex>)> {
+    let description = check_game_unwind(game).err()?;
+    let reduced = reduce_with_failure(game, &description);
+    Some((description, reduced))
+}
+
+/// Reduces the game with the given failure to a small subgame on which the solvers still fail.
+fn reduce_with_failure(
+    game: &LabelledGame<NodeIndex>,
+    description: &str,
+) -> LabelledGame<NodeIndex> {
+    info!("Reducing game with failure: {}", description);
+    reduce_game(game, |subgame| check_game_unwind(subgame).is_err())
+}
+
+/// Checks the given game as [`check_game`], but turns a panic of a solver into a failure.
+fn check_game_unwind(game: &LabelledGame<NodeIndex>) -> Result<Vec<SolverMetrics>, String> {
+    panic::catch_unwind(AssertUnwindSafe(|| check_game(game))).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown cause");
+        Err(format!("solver panicked: {}", message))
+    })
+}
+
+/// Solves the game for the player with the given solver, and adds the solving time
+/// to the metrics of the solver.
+fn solve_measured<S: ParityGameSolver>(
+    solver: &mut S,
+    metrics: &mut SolverMetrics,
+    game: &LabelledGame<NodeIndex>,
+    disabled: &Region,
+    player: Player,
+) -> (Region, Option<Strategy>) {
+    let start = Instant::now();
+    let result = solver.solve(game, disabled, player, true);
+    metrics.time += start.elapsed();
+    result
+}
+
+/// Checks that all solvers agree on the given complete game and produce valid strategies.
+///
+/// Returns the metrics of the solvers for both players.
+fn check_game(game: &LabelledGame<NodeIndex>) -> Result<Vec<SolverMetrics>, String> {
+    let n = game.num_nodes();
+    let disabled = Region::with_capacity(n);
+    let mut winning = Vec::with_capacity(2);
+    let mut fpi_solver = FpiSolver::new();
+    let mut si_solver = SiSolver::new();
+    let mut zlk_solver = ZlkSolver::new();
+    let mut metrics = [
+        SolverMetrics::new("FPI"),
+        SolverMetrics::new("SI"),
+        SolverMetrics::new("ZLK"),
+    ];
+    for &player in &Player::PLAYERS {
+        let (fpi, fpi_strategy) =
+            solve_measured(&mut fpi_solver, &mut metrics[0], game, &disabled, player);
+        let (si, si_strategy) =
+            solve_measured(&mut si_solver, &mut metrics[1], game, &disabled, player);
+        let (zlk, zlk_strategy) =
+            solve_measured(&mut zlk_solver, &mut metrics[2], game, &disabled, player);
+        if fpi != si {
+            return Err(format!(
+                "FPI and SI disagree on winning region for {}: {} vs. {}",
+                player, fpi, si
+            ));
+        }
+        if fpi != zlk {
+            return Err(format!(
+                "FPI and ZLK disagree on winning region for {}: {} vs. {}",
+                player, fpi, zlk
+            ));
+        }
+        check_strategy(game, &fpi, &fpi_strategy.expect("no strategy"), player)
+            .map_err(|e| format!("invalid strategy of FPI for {}: {}", player, e))?;
+        check_strategy(game, &si, &si_strategy.expect("no strategy"), player)
+            .map_err(|e| format!("invalid strategy of SI for {}: {}", player, e))?;
+        check_strategy(game, &zlk, &zlk_strategy.expect("no strategy"), player)
+            .map_err(|e| format!("invalid strategy of ZLK for {}: {}", player, e))?;
+        winning.push(fpi);
+    }
+    if let Some(i) = (0..n).find(|&i| winning[0][i] == winning[1][i]) {
+        return Err(format!(
+            "winning regions do not partition the game at node {}",
+            i
+        ));
+    }
+    metrics[0].stats = fpi_solver.algorithm_stats();
+    metrics[1].stats = si_solver.algorithm_stats();
+    metrics[2].stats = zlk_solver.algorithm_stats();
+    Ok(metrics.to_vec())
+}
+
+/// Checks that the given strategy is winning for the given player from all nodes
+/// in the given region.
+///
+/// The strategy needs to stay within the region, and the game restricted to the region
+/// and to the moves of the strategy needs to be won by the player from all nodes.
+/// As strategies may be non-deterministic and any of the moves may be taken,
+/// nodes of the player are given to the opponent in the restricted game.
+fn check_strategy(
+    game: &LabelledGame<NodeIndex>,
+    region: &Region,
+    strategy: &Strategy,
+    player: Player,
+) -> Result<(), String> {
+    let n = game.num_nodes();
+    let mut restricted = LabelledGame::default();
+    for i in game.nodes() {
+        let (index, _) = restricted.add_border_node(i);
+        restricted.update_node(index, !player, game[i].color());
+    }
+    for i in region.nodes() {
+        let node = &game[i];
+        let successors: &[NodeIndex] = if node.owner() == player {
+            &strategy[i]
+        } else {
+            node.successors()
+        };
+        if successors.is_empty() {
+            return Err(format!("no move at node {}", i));
+        }
+        for &j in successors {
+            if !node.successors().contains(&j) {
+                return Err(format!("move from node {} to non-successor {}", i, j));
+            }
+            if !region[j] {
+                return Err(format!("move from node {} leaves region to {}", i, j));
+            }
+            restricted.add_edge(i, j);
+        }
+    }
+    let mut outside = Region::with_capacity(n);
+    outside.extend(game.nodes().filter(|&i| !region[i]));
+    let (won, _) = FpiSolver::new().solve(&restricted, &outside, player, false);
+    if won != *region {
+        return Err(format!("strategy only wins {} instead of {}", won, region));
+    }
+    Ok(())
+}
+
+/// Tests for the parity game solvers on random games.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test agreement of solvers and validity of strategies on small random games.
+    #[test]
+    fn test_fuzz_small_games() {
+        let config = FuzzConfig {
+            parameters: GeneratorParameters {
+                num_nodes: 10,
+                max_degree: 3,
+                num_colors: 4,
+            },
+            num_games: 200,
+            seed: 0,
+        };
+        let report = fuzz_solvers(&config).unwrap_or_else(|failure| panic!("{}", failure));
+        assert_eq!(report.num_games, 200);
+        let names: Vec<_> = report.solvers.iter().map(|metrics| metrics.name).collect();
+        assert_eq!(names, vec!["FPI", "SI", "ZLK"]);
+        for metrics in &report.solvers {
+            assert!(metrics.stats.iterations() > 0);
+            assert!(metrics.stats.strategy_edges() > 0);
+        }
+        assert!(report.solvers[2].stats.attractors() > 0);
+        assert!(report.solvers[2].stats.max_depth() > 0);
+    }
+
+    /// Test agreement of solvers and validity of strategies on larger random games.
+    #[test]
+    fn test_fuzz_large_games() {
+        let config = FuzzConfig {
+            parameters: GeneratorParameters {
+                num_nodes: 200,
+                max_degree: 4,
+                num_colors: 12,
+            },
+            num_games: 20,
+            seed: 1000,
+        };
+        if let Err(failure) = fuzz_solvers(&config) {
+            panic!("{}", failure);
+        }
+    }
+
+    /// Test that the generator is deterministic for a fixed seed.
+    #[test]
+    fn test_generator_deterministic() {
+        let parameters = GeneratorParameters::default();
+        let game1 = GameGenerator::new(parameters, 42).generate();
+        let game2 = GameGenerator::new(parameters, 42).generate();
+        assert_eq!(game1.to_string(), game2.to_string());
+        assert_eq!(game1.num_nodes(), parameters.num_nodes);
+        assert!(game1.border().is_empty());
+    }
+
+    /// Test that no reduction happens if the solvers agree on a game.
+    #[test]
+    fn test_reduce_without_failure() {
+        let game = GameGenerator::new(GeneratorParameters::default(), 7).generate();
+        assert!(reduce_failure(&game).is_none());
+    }
+}
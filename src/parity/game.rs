@@ -1,5 +1,8 @@
 //! Parity games.
 
+pub mod binary;
+pub mod generator;
+
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::collections::VecDeque;
@@ -134,14 +137,23 @@ pub trait Game<'a>: Index<NodeIndex, Output = <Self as Game<'a>>::Node> {
     /// Nodes in the border have an owner and a color, which are however implementation-defined
     /// and should not be used. Once a node is updated and removed from the border,
     /// the owner and color can change to their proper value.
+    ///
+    /// Nodes outside the border without successors are deadlocks, which only occur in
+    /// games read from a file and are not supported by the solvers. They need to be
+    /// completed with [`LabelledGame::complete_deadlocks`] before solving.
     fn border(&self) -> &Region;
 }
 
 /// A region of a parity game, defining a set of nodes of the game in this region.
 ///
 /// A region can be indexed by the index of a game node, which returns `true` if
-/// the node is in that region.
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// the node is in that region. Indices beyond the capacity of the region are not
+/// in the region, and two regions are equal if they contain the same nodes,
+/// independent of their capacity.
+///
+/// A region is formatted as the indices of its nodes in increasing order between
+/// braces, e.g. `{ 0 3 4 }`, and can be parsed from this format with [`Region::parse`].
+#[derive(Debug, Clone, Default)]
 pub struct Region {
     data: FixedBitSet,
 }
@@ -154,6 +166,14 @@ impl Index<NodeIndex> for Region {
     }
 }
 
+impl PartialEq for Region {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes().eq(other.nodes())
+    }
+}
+
+impl Eq for Region {}
+
 impl std::fmt::Display for Region {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{{")?;
@@ -165,47 +185,185 @@ impl std::fmt::Display for Region {
     }
 }
 
+/// An error which can be returned when parsing a region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRegionError(pub(crate) String);
+
+impl fmt::Display for ParseRegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid region: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseRegionError {}
+
 impl Region {
-    pub(crate) fn new() -> Self {
+    /// Creates a new empty region.
+    pub fn new() -> Self {
         Self {
             data: FixedBitSet::default(),
         }
     }
 
-    pub(crate) fn with_capacity(n: usize) -> Self {
+    /// Creates a new empty region with capacity for nodes with indices below `n`.
+    pub fn with_capacity(n: usize) -> Self {
         Self {
             data: FixedBitSet::with_capacity(n),
         }
     }
 
-    pub(crate) fn nodes(&self) -> fixedbitset::Ones {
+    /// Parses a region of a game with `n` nodes from the indices of its nodes separated
+    /// by whitespace between braces, rejecting indices of `n` or above.
+    pub fn parse(s: &str, n: usize) -> Result<Self, ParseRegionError> {
+        let inner = s
+            .trim()
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .ok_or_else(|| ParseRegionError("missing braces".to_string()))?;
+        let mut region = Self::new();
+        for token in inner.split_whitespace() {
+            let index = token
+                .parse()
+                .map_err(|_| ParseRegionError(format!("invalid node index '{}'", token)))?;
+            if index >= n {
+                return Err(ParseRegionError(format!("node {} out of range", index)));
+            }
+            region.insert(index);
+        }
+        Ok(region)
+    }
+
+    /// Returns an iterator over the indices of the nodes in this region in increasing order.
+    pub fn nodes(&self) -> impl Iterator<Item = NodeIndex> + '_ {
         self.data.ones()
     }
 
-    pub(crate) fn grow(&mut self, n: usize) {
+    /// Returns whether the node with the given index is in this region.
+    pub fn contains(&self, index: NodeIndex) -> bool {
+        self.data[index]
+    }
+
+    /// Grows the capacity of this region to nodes with indices below `n`.
+    pub fn grow(&mut self, n: usize) {
         self.data.grow(n);
     }
 
-    pub(crate) fn union_with(&mut self, other: &Self) {
+    /// Adds the node with the given index to this region, growing the capacity if needed.
+    pub fn insert(&mut self, index: NodeIndex) {
+        self.data.grow(index + 1);
+        self.data.insert(index);
+    }
+
+    /// Removes the node with the given index from this region.
+    pub fn remove(&mut self, index: NodeIndex) {
+        if index < self.data.len() {
+            self.data.set(index, false);
+        }
+    }
+
+    /// Adds the node with the given index to this region if `value` is `true`,
+    /// and removes it otherwise.
+    pub fn set(&mut self, index: NodeIndex, value: bool) {
+        if value {
+            self.insert(index);
+        } else {
+            self.remove(index);
+        }
+    }
+
+    /// Returns the number of nodes in this region.
+    pub fn size(&self) -> usize {
+        self.data.count_ones(..)
+    }
+
+    /// Returns whether this region contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.data.ones().next().is_none()
+    }
+
+    /// Removes all nodes from this region while keeping its capacity.
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Adds all nodes of the other region to this region.
+    pub fn union_with(&mut self, other: &Self) {
         self.data.union_with(&other.data);
     }
 
-    pub(crate) fn union(&self, other: &Self) -> Self {
+    /// Returns the region of nodes in this or the other region.
+    pub fn union(&self, other: &Self) -> Self {
         let mut new_region = self.clone();
         new_region.union_with(other);
         new_region
     }
 
-    pub(crate) fn insert(&mut self, index: NodeIndex) {
-        self.data.insert(index);
+    /// Removes all nodes from this region that are not in the other region.
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.data.intersect_with(&other.data);
     }
 
-    pub(crate) fn set(&mut self, index: NodeIndex, value: bool) {
-        self.data.set(index, value);
+    /// Returns the region of nodes in both this and the other region.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut new_region = self.clone();
+        new_region.intersect_with(other);
+        new_region
     }
 
-    pub(crate) fn size(&self) -> usize {
-        self.data.count_ones(..)
+    /// Removes all nodes of the other region from this region.
+    pub fn difference_with(&mut self, other: &Self) {
+        self.data.difference_with(&other.data);
+    }
+
+    /// Returns the region of nodes in this region but not in the other region.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut new_region = self.clone();
+        new_region.difference_with(other);
+        new_region
+    }
+
+    /// Returns whether all nodes of this region are in the other region.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.nodes().all(|index| other[index])
+    }
+
+    /// Returns whether this region and the other region have no node in common.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.nodes().all(|index| !other[index])
+    }
+
+    /// Returns the region of nodes that are not in this region,
+    /// for a game with the given number of nodes.
+    pub fn complement(&self, n: usize) -> Self {
+        let mut region = Self::with_capacity(n);
+        region.data.insert_range(..);
+        region.data.difference_with(&self.data);
+        region
+    }
+
+    /// Returns the region of nodes reachable from the initial node of the given game.
+    ///
+    /// Nodes in the `blocked` region are included in the result if they are reachable,
+    /// but their successors are not explored further.
+    pub(crate) fn reachable<'a, G: Game<'a>>(game: &'a G, blocked: &Self) -> Self {
+        let n = game.num_nodes();
+        let mut region = Self::with_capacity(n);
+        let mut queue = VecDeque::with_capacity(n);
+        let initial_node = game.initial_node();
+        region.insert(initial_node);
+        queue.push_back(initial_node);
+        while let Some(i) = queue.pop_front() {
+            if blocked[i] {
+                continue;
+            }
+            for &j in game[i].successors() {
+                if !region[j] {
+                    region.insert(j);
+                    queue.push_back(j);
+                }
+            }
+        }
+        region
     }
 
     pub(crate) fn attract<'a, G: Game<'a>>(&self, game: &'a G, player: Player) -> Self {
@@ -281,6 +439,14 @@ impl std::iter::Extend<NodeIndex> for Region {
     }
 }
 
+impl std::iter::FromIterator<NodeIndex> for Region {
+    fn from_iter<T: IntoIterator<Item = NodeIndex>>(iter: T) -> Self {
+        Self {
+            data: iter.into_iter().collect(),
+        }
+    }
+}
+
 /// A labelled node of [`LabelledGame<L>`].
 #[derive(Debug)]
 pub struct LabelledNode<L> {
@@ -387,6 +553,10 @@ impl<L> LabelledGame<L> {
     pub(crate) fn update_node(&mut self, index: NodeIndex, owner: Player, color: Color) {
         assert!(self.border[index]);
         self.border.set(index, false);
+        self.set_owner_and_color(index, owner, color);
+    }
+
+    fn set_owner_and_color(&mut self, index: NodeIndex, owner: Player, color: Color) {
         let node = &mut self[index];
         node.owner = owner;
         node.color = color;
@@ -400,6 +570,81 @@ impl<L> LabelledGame<L> {
         self[from].successors.push(to);
         self[to].predecessors.push(from);
     }
+
+    /// Turns each node in the border into a node won by the given player, and returns
+    /// the number of such nodes.
+    ///
+    /// Each border node becomes a node owned by the player with a color of the parity
+    /// of the player and a single edge to itself, which is the same completion that
+    /// [`Self::write_with_winner`] uses for the losing player. Afterwards the border
+    /// is empty and the game can be written in PGSolver format without a winner.
+    pub fn complete_border(&mut self, player: Player) -> usize {
+        let border: Vec<_> = self.border.nodes().collect();
+        for &index in &border {
+            self.update_node(index, player, Color::from(Parity::from(player)));
+            self.add_edge(index, index);
+        }
+        border.len()
+    }
+
+    /// Returns the region of deadlocks, which are the nodes outside the border
+    /// without successors.
+    pub fn deadlocks(&self) -> Region {
+        self.nodes()
+            .filter(|&i| !self.border[i] && self[i].successors.is_empty())
+            .collect()
+    }
+
+    /// Turns each deadlock into a node won by the given player, and returns the number
+    /// of deadlocks.
+    ///
+    /// Each deadlock becomes a node owned by the player with a color of the parity of
+    /// the player and a single edge to itself, as for [`Self::complete_border`]. Plays
+    /// reaching a deadlock are then won by the player, whose strategy stays at the
+    /// deadlock, so the solvers need no special treatment of deadlocks.
+    pub fn complete_deadlocks(&mut self, player: Player) -> usize {
+        let deadlocks: Vec<_> = self.deadlocks().nodes().collect();
+        let color = Color::from(Parity::from(player));
+        for &index in &deadlocks {
+            let old_color = self[index].color;
+            self.color_map[old_color].retain(|&i| i != index);
+            self.set_owner_and_color(index, player, color);
+            self.add_edge(index, index);
+        }
+        deadlocks.len()
+    }
+
+    /// Returns this game with the label of each node replaced by the result of the given
+    /// function, which has to map different labels of the game to different labels.
+    pub(crate) fn map_labels<M: Hash + Eq + Clone, F: FnMut(&L) -> M>(
+        self,
+        mut f: F,
+    ) -> LabelledGame<M> {
+        let nodes: Vec<_> = self
+            .nodes
+            .into_iter()
+            .map(|node| LabelledNode {
+                successors: node.successors,
+                predecessors: node.predecessors,
+                owner: node.owner,
+                color: node.color,
+                label: f(&node.label),
+            })
+            .collect();
+        let mapping: HashMap<_, _> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.label.clone(), index))
+            .collect();
+        assert_eq!(mapping.len(), nodes.len(), "labels are not distinct");
+        LabelledGame {
+            nodes,
+            mapping,
+            border: self.border,
+            color_map: self.color_map,
+            initial_node: self.initial_node,
+        }
+    }
 }
 
 impl<'a, L> Game<'a> for LabelledGame<L> {
@@ -491,11 +736,20 @@ where
 }
 
 impl<L: fmt::Display> LabelledGame<L> {
-    pub(crate) fn write_with_winner<W: io::Write>(
-        &self,
-        mut writer: W,
-        winner: Player,
-    ) -> io::Result<()> {
+    /// Writes this game in PGSolver format, where the border is completed
+    /// in favor of the player losing the game.
+    ///
+    /// Nodes in the [border](Game::border) have not been explored yet and have no
+    /// successors. Each such node is written as a node owned by the losing player
+    /// with a color of the parity of that player and a single edge to itself,
+    /// and its label is suffixed with `(border)`. Plays reaching the border are
+    /// then won by the losing player, which is the pessimistic assumption for the
+    /// winner, so the winner of the initial node is the same in the written game.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during writing.
+    pub fn write_with_winner<W: io::Write>(&self, mut writer: W, winner: Player) -> io::Result<()> {
         write!(
             writer,
             "{}",
@@ -520,11 +774,181 @@ impl<L: fmt::Display> fmt::Display for LabelledGame<L> {
     }
 }
 
+/// An error from parsing a parity game in PGSolver format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGameError(String);
+
+impl fmt::Display for ParseGameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid parity game: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseGameError {}
+
+/// Splits a parity game in PGSolver format into its statements separated
+/// by semicolons, ignoring semicolons in names of nodes.
+fn statements(s: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                statements.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    statements.push(&s[start..]);
+    statements
+}
+
+fn parse_node_id(token: Option<&str>) -> Result<NodeIndex, ParseGameError> {
+    let token = token.unwrap_or_default();
+    token
+        .parse()
+        .map_err(|_| ParseGameError(format!("invalid node identifier '{}'", token)))
+}
+
+impl std::str::FromStr for LabelledGame<NodeIndex> {
+    type Err = ParseGameError;
+
+    /// Parses a parity game in PGSolver format, where each node is labelled
+    /// with its identifier in the game and names of nodes are ignored.
+    ///
+    /// The initial node is given by the `start` statement, or is the first node
+    /// of the game if there is no such statement. Nodes without successors are
+    /// read as deadlocks, see [`LabelledGame::complete_deadlocks`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut game = Self::default();
+        let mut start = None;
+        let mut first_node = None;
+        for statement in statements(s) {
+            // strip name of node
+            let mut tokens = statement.split('"').next().unwrap().split_whitespace();
+            match tokens.next() {
+                None | Some("parity") => (),
+                Some("start") => start = Some(parse_node_id(tokens.next())?),
+                id_token => {
+                    let id = parse_node_id(id_token)?;
+                    let color = match tokens.next().map(str::parse) {
+                        Some(Ok(color)) => color,
+                        _ => return Err(ParseGameError(format!("invalid color of node {}", id))),
+                    };
+                    let owner = match tokens.next() {
+                        Some("0") => Player::Even,
+                        Some("1") => Player::Odd,
+                        _ => return Err(ParseGameError(format!("invalid owner of node {}", id))),
+                    };
+                    let successors: String = tokens.collect();
+
+                    let (index, _) = game.add_border_node(id);
+                    if !game.border()[index] {
+                        return Err(ParseGameError(format!("node {} defined twice", id)));
+                    }
+                    game.update_node(index, owner, color);
+                    first_node.get_or_insert(index);
+                    for successor in successors.split(',').filter(|s| !s.is_empty()) {
+                        let successor_id = parse_node_id(Some(successor))?;
+                        let (successor_index, _) = game.add_border_node(successor_id);
+                        game.add_edge(index, successor_index);
+                    }
+                }
+            }
+        }
+        if let Some(index) = game.border().nodes().next() {
+            return Err(ParseGameError(format!(
+                "node {} has no definition",
+                game[index].label()
+            )));
+        }
+        let initial_node = match start {
+            Some(id) => game.mapping.get(&id).copied(),
+            None => first_node,
+        };
+        match initial_node {
+            Some(index) => game.set_initial_node(index),
+            None => return Err(ParseGameError("no initial node".to_string())),
+        }
+        Ok(game)
+    }
+}
+
 /// Tests for parity games.
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Test that the border is completed with self loops won by the given player.
+    #[test]
+    fn test_complete_border() {
+        let mut game = LabelledGame::default();
+        let n0 = game.add_node(0, Player::Even, 0);
+        let (n1, _) = game.add_border_node(1);
+        game.add_edge(n0, n1);
+        game.set_initial_node(n0);
+
+        let mut output = Vec::new();
+        game.write_with_winner(&mut output, Player::Even).unwrap();
+        let written = String::from_utf8(output).unwrap();
+        assert_eq!(
+            written,
+            "parity 2;\n0 0 0 1 \"0\";\n1 1 1 1 \"1 (border)\";\n"
+        );
+
+        assert_eq!(game.complete_border(Player::Odd), 1);
+        assert!(game.border().is_empty());
+        assert_eq!(game[n1].owner(), Player::Odd);
+        assert_eq!(game[n1].successors(), &[n1]);
+        assert_eq!(
+            game.to_string(),
+            "parity 2;\n0 0 0 1 \"0\";\n1 1 1 1 \"1\";\n"
+        );
+    }
+
+    /// Test that deadlocks are read and completed with self loops won by the given player.
+    #[test]
+    fn test_complete_deadlocks() {
+        let mut game: LabelledGame<NodeIndex> = "0 1 0 1,2;\n1 2 1;\n2 2 0 2;\n".parse().unwrap();
+        assert!(game.border().is_empty());
+        assert_eq!(game.deadlocks().nodes().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(
+            game.to_string(),
+            "parity 3;\n0 1 0 1,2 \"0\";\n1 2 1  \"1\";\n2 2 0 2 \"2\";\n"
+        );
+
+        assert_eq!(game.complete_deadlocks(Player::Odd), 1);
+        assert!(game.deadlocks().is_empty());
+        assert_eq!(game[1].owner(), Player::Odd);
+        assert_eq!(game[1].color(), 1);
+        assert_eq!(game[1].successors(), &[1]);
+        assert_eq!(game.nodes_with_color(1).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(game.nodes_with_color(2).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(game.complete_deadlocks(Player::Even), 0);
+    }
+
+    /// Test that mapping the labels keeps the nodes, edges and border of the game.
+    #[test]
+    fn test_map_labels() {
+        let mut game = LabelledGame::default();
+        let n0 = game.add_node(0, Player::Odd, 3);
+        let (n1, _) = game.add_border_node(1);
+        game.add_edge(n0, n1);
+        game.add_edge(n0, n0);
+        game.set_initial_node(n0);
+
+        let mut mapped = game.map_labels(|&label| label + 10);
+        assert_eq!(mapped.initial_node(), n0);
+        assert_eq!(*mapped[n1].label(), 11);
+        assert_eq!(mapped[n0].successors(), &[n1, n0]);
+        assert_eq!(mapped.nodes_with_color(3).collect::<Vec<_>>(), vec![n0]);
+        assert_eq!(mapped.border().nodes().collect::<Vec<_>>(), vec![n1]);
+        assert_eq!(mapped.add_border_node(11), (n1, false));
+    }
+
     /// Test attractor computation on a parity game.
     #[test]
     fn test_attractor() {
@@ -571,4 +995,66 @@ mod tests {
         assert!(attractor_even[n6]);
         assert!(attractor_odd[n6]);
     }
+
+    /// Test reachability from the initial node of a parity game.
+    #[test]
+    fn test_reachable() {
+        let game: LabelledGame<NodeIndex> = "0 0 0 1;\n1 1 1 2,3;\n2 2 0 2;\n3 3 1 3;\n4 4 0 0;\n"
+            .parse()
+            .unwrap();
+        let reachable = Region::reachable(&game, &Region::new());
+        assert_eq!(reachable.nodes().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+
+        let mut blocked = Region::with_capacity(game.num_nodes());
+        blocked.insert(1);
+        let reachable = Region::reachable(&game, &blocked);
+        assert_eq!(reachable.nodes().collect::<Vec<_>>(), vec![0, 1]);
+        let unreachable = reachable.complement(game.num_nodes());
+        assert_eq!(unreachable.nodes().collect::<Vec<_>>(), vec![2, 3, 4]);
+    }
+
+    /// Test set operations and the text format of regions.
+    #[test]
+    fn test_region() {
+        let r1 = Region::parse("{ 0 2 4 }", 5).unwrap();
+        let r2: Region = [1, 2, 3].iter().copied().collect();
+        assert_eq!(r1.union(&r2), Region::parse("{ 0 1 2 3 4 }", 5).unwrap());
+        assert_eq!(r1.intersection(&r2).nodes().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(r1.difference(&r2).to_string(), "{ 0 4 }");
+        assert_eq!(r1.complement(6), Region::parse("{ 1 3 5 }", 6).unwrap());
+        assert!(r1.intersection(&r2).is_subset(&r1));
+        assert!(r1.difference(&r2).is_disjoint(&r2));
+        assert!(!r1.contains(5) && !r1[100]);
+
+        let mut r3 = Region::new();
+        r3.insert(7);
+        r3.set(2, true);
+        r3.remove(7);
+        assert_eq!(r3, Region::parse("{2}", 3).unwrap());
+        assert_eq!(Region::with_capacity(10), Region::new());
+        assert!(Region::parse("{ 1 x }", 3).is_err());
+        assert!(Region::parse("1 2", 3).is_err());
+        assert!(Region::parse("{ 3 }", 3).is_err());
+        assert!(Region::parse(&format!("{{ {} }}", usize::MAX), 3).is_err());
+    }
+
+    /// Test parsing a game in PGSolver format.
+    #[test]
+    fn test_parse_game() {
+        let text = "parity 3;\nstart 5;\n2 1 0 5,7 \"a;b\";\n5 2 1 2;\n7 0 0 7;\n";
+        let game: LabelledGame<NodeIndex> = text.parse().unwrap();
+        assert_eq!(game.num_nodes(), 3);
+        assert!(game.border().is_empty());
+        let initial_node = game.initial_node();
+        assert_eq!(*game[initial_node].label(), 5);
+        assert_eq!(game[initial_node].owner(), Player::Odd);
+        assert_eq!(game[initial_node].color(), 2);
+        assert_eq!(game[0].successors(), &[initial_node, 2]);
+
+        assert!("0 1 0 1;".parse::<LabelledGame<NodeIndex>>().is_err());
+        assert!("0 1 2 0;".parse::<LabelledGame<NodeIndex>>().is_err());
+        assert!("0 1 0 0;\n0 1 0 0;"
+            .parse::<LabelledGame<NodeIndex>>()
+            .is_err());
+    }
 }
@@ -0,0 +1,297 @@
+//! A compact binary format for parity games, which keeps the border and the labels
+//! of all nodes, e.g. for checkpointing games or exchanging large games.
+//!
+//! A game starts with the magic bytes [`MAGIC`], followed by the number of nodes and
+//! the initial node plus one, or zero if there is no initial node. Each node is then
+//! written in order of its index as a tag byte, which is `0` for a node in the border
+//! and `1` or `2` for a node owned by the even or odd player, followed by its color,
+//! its number of successors and its successors for a node that is not in the border,
+//! and finally its label. All numbers are unsigned LEB128 variable-length integers.
+
+use std::convert::TryFrom;
+use std::hash::Hash;
+use std::io;
+
+use super::{Game, LabelledGame, Node, NodeIndex, Player};
+
+/// The magic bytes at the start of a game in binary format, including the version.
+pub const MAGIC: &[u8] = b"STRIXPG\x01";
+
+/// The maximal number of nodes for which space is reserved before reading them,
+/// such that a corrupt number of nodes does not exhaust the memory.
+const MAX_RESERVED_NODES: usize = 1 << 16;
+
+/// Returns whether the given data starts with the magic bytes of the binary format.
+pub fn is_binary_game(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Writes a number as unsigned LEB128 variable-length integer.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurs during writing.
+pub fn write_number<W: io::Write>(writer: &mut W, mut number: u64) -> io::Result<()> {
+    loop {
+        let byte = (number & 0x7f) as u8;
+        number >>= 7;
+        if number == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+/// Reads a number written as unsigned LEB128 variable-length integer.
+///
+/// # Errors
+///
+/// Returns an error if an I/O error occurs during reading or the number does not fit
+/// into 64 bits.
+pub fn read_number<R: io::Read>(reader: &mut R) -> io::Result<u64> {
+    let mut number = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        let value = u64::from(byte[0] & 0x7f);
+        if value << shift >> shift != value {
+            break;
+        }
+        number |= value << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(number);
+        }
+    }
+    Err(invalid_data("number too large"))
+}
+
+fn write_index<W: io::Write>(writer: &mut W, index: usize) -> io::Result<()> {
+    write_number(writer, index as u64)
+}
+
+fn read_index<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+    usize::try_from(read_number(reader)?).map_err(|_| invalid_data("index too large"))
+}
+
+/// A label of nodes that can be written in the binary format of parity games.
+pub trait BinaryLabel: Sized {
+    /// Writes the label.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during writing.
+    fn write_label<W: io::Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    /// Reads a label written with [`Self::write_label`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during reading or the label is invalid.
+    fn read_label<R: io::Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+impl BinaryLabel for NodeIndex {
+    fn write_label<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_index(writer, *self)
+    }
+
+    fn read_label<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        read_index(reader)
+    }
+}
+
+/// A node as read from the binary format, which is either in the border or has
+/// an owner, a color and successors.
+struct NodeRecord<L> {
+    node: Option<(Player, usize, Vec<NodeIndex>)>,
+    label: L,
+}
+
+impl<L: BinaryLabel> LabelledGame<L> {
+    /// Writes this game in binary format, including the border and the labels.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during writing.
+    pub fn write_binary<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        write_index(&mut writer, self.num_nodes())?;
+        write_index(&mut writer, self.initial_node.map_or(0, |index| index + 1))?;
+        for i in self.nodes() {
+            let node = &self[i];
+            if self.border()[i] {
+                writer.write_all(&[0])?;
+            } else {
+                let tag = match node.owner() {
+                    Player::Even => 1,
+                    Player::Odd => 2,
+                };
+                writer.write_all(&[tag])?;
+                write_index(&mut writer, node.color())?;
+                write_index(&mut writer, node.successors().len())?;
+                for &j in node.successors() {
+                    write_index(&mut writer, j)?;
+                }
+            }
+            node.label().write_label(&mut writer)?;
+        }
+        writer.flush()
+    }
+}
+
+impl<L: BinaryLabel + Hash + Eq + Clone> LabelledGame<L> {
+    /// Reads a game written with [`Self::write_binary`].
+    ///
+    /// The nodes keep their indices, border, owners, colors, successors and labels,
+    /// but the predecessors of each node are ordered by the index of the predecessor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error occurs during reading or the data is not
+    /// a valid game in binary format.
+    pub fn read_binary<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid_data("not a parity game in binary format"));
+        }
+        let n = read_index(&mut reader)?;
+        let initial_node = read_index(&mut reader)?.checked_sub(1);
+        let mut records = Vec::with_capacity(n.min(MAX_RESERVED_NODES));
+        for i in 0..n {
+            let mut tag = [0];
+            reader.read_exact(&mut tag)?;
+            let owner = match tag[0] {
+                0 => None,
+                1 => Some(Player::Even),
+                2 => Some(Player::Odd),
+                _ => return Err(invalid_data(format!("invalid tag of node {}", i))),
+            };
+            let node = match owner {
+                Some(owner) => {
+                    let color = read_index(&mut reader)?;
+                    let num_successors = read_index(&mut reader)?;
+                    let mut successors = Vec::with_capacity(num_successors.min(MAX_RESERVED_NODES));
+                    for _ in 0..num_successors {
+                        let j = read_index(&mut reader)?;
+                        if j >= n {
+                            return Err(invalid_data(format!(
+                                "successor {} of node {} out of range",
+                                j, i
+                            )));
+                        }
+                        successors.push(j);
+                    }
+                    Some((owner, color, successors))
+                }
+                None => None,
+            };
+            let label = L::read_label(&mut reader)?;
+            records.push(NodeRecord { node, label });
+        }
+
+        let mut game = Self::default();
+        for (i, record) in records.iter().enumerate() {
+            let (index, new_node) = game.add_border_node(record.label.clone());
+            if !new_node {
+                return Err(invalid_data(format!(
+                    "node {} has the same label as node {}",
+                    i, index
+                )));
+            }
+            if let Some((owner, color, _)) = record.node {
+                game.update_node(index, owner, color);
+            }
+        }
+        for (i, record) in records.iter().enumerate() {
+            if let Some((_, _, successors)) = &record.node {
+                for &j in successors {
+                    game.add_edge(i, j);
+                }
+            }
+        }
+        if let Some(index) = initial_node {
+            if index >= n {
+                return Err(invalid_data(format!("initial node {} out of range", index)));
+            }
+            game.set_initial_node(index);
+        }
+        Ok(game)
+    }
+}
+
+/// Tests for the binary format of parity games.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that numbers are written as variable-length integers.
+    #[test]
+    fn test_numbers() {
+        for &number in &[0, 1, 127, 128, 300, u64::from(u32::MAX), u64::MAX] {
+            let mut data = Vec::new();
+            write_number(&mut data, number).unwrap();
+            assert_eq!(read_number(&mut data.as_slice()).unwrap(), number);
+        }
+        let mut data = Vec::new();
+        write_number(&mut data, 300).unwrap();
+        assert_eq!(data, vec![0xac, 0x02]);
+        assert!(read_number(&mut &[0xff; 11][..]).is_err());
+        assert!(read_number(&mut &[0x80][..]).is_err());
+    }
+
+    /// Test that a game with a border is written and read again with all labels.
+    #[test]
+    fn test_binary_format() {
+        let mut game: LabelledGame<NodeIndex> = LabelledGame::default();
+        let (n0, _) = game.add_border_node(10);
+        let (n1, _) = game.add_border_node(20);
+        let (n2, _) = game.add_border_node(30);
+        game.update_node(n0, Player::Odd, 3);
+        game.update_node(n1, Player::Even, 200);
+        game.add_edge(n0, n1);
+        game.add_edge(n0, n2);
+        game.add_edge(n1, n0);
+        game.set_initial_node(n1);
+
+        let mut data = Vec::new();
+        game.write_binary(&mut data).unwrap();
+        assert!(is_binary_game(&data));
+        let read = LabelledGame::<NodeIndex>::read_binary(data.as_slice()).unwrap();
+        assert_eq!(read.num_nodes(), 3);
+        assert_eq!(read.initial_node(), n1);
+        assert_eq!(read.border().nodes().collect::<Vec<_>>(), vec![n2]);
+        assert_eq!(*read[n2].label(), 30);
+        assert_eq!(read[n0].owner(), Player::Odd);
+        assert_eq!(read[n1].color(), 200);
+        assert_eq!(read[n0].successors(), &[n1, n2]);
+        assert_eq!(read[n0].predecessors(), &[n1]);
+        assert_eq!(read.nodes_with_color(3).collect::<Vec<_>>(), vec![n0]);
+        assert_eq!(read.to_string(), game.to_string());
+
+        let truncated = &data[..data.len() - 1];
+        assert!(LabelledGame::<NodeIndex>::read_binary(truncated).is_err());
+        assert!(LabelledGame::<NodeIndex>::read_binary(&b"parity 1;"[..]).is_err());
+    }
+
+    /// Test that games with duplicate labels or successors out of range are rejected.
+    #[test]
+    fn test_binary_format_invalid() {
+        let mut duplicate = MAGIC.to_vec();
+        duplicate.extend(&[2, 0, 0, 5, 0, 5]);
+        assert!(LabelledGame::<NodeIndex>::read_binary(duplicate.as_slice()).is_err());
+
+        let mut out_of_range = MAGIC.to_vec();
+        out_of_range.extend(&[1, 1, 1, 0, 1, 1, 0]);
+        assert!(LabelledGame::<NodeIndex>::read_binary(out_of_range.as_slice()).is_err());
+
+        let mut valid = MAGIC.to_vec();
+        valid.extend(&[1, 1, 1, 0, 1, 0, 0]);
+        let game = LabelledGame::<NodeIndex>::read_binary(valid.as_slice()).unwrap();
+        assert_eq!(game[0].successors(), &[0]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Generation of random parity games, e.g. for testing parity game solvers.
+
+use owl::automaton::Color;
+
+use super::{LabelledGame, NodeIndex, Player};
+
+/// A simple pseudo-random number generator based on SplitMix64.
+///
+/// The generator is used instead of an external crate such that games
+/// can be reproduced from a seed independent of the platform and of
+/// the versions of any dependencies.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Creates a new generator with the given seed.
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next pseudo-random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudo-random number in the range `0..n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        (self.next_u64() % (n as u64)) as usize
+    }
+}
+
+/// The parameters for generating random parity games.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneratorParameters {
+    /// The number of nodes of each game.
+    pub num_nodes: usize,
+    /// The maximal number of successors of each node.
+    pub max_degree: usize,
+    /// The number of colors, such that the color of each node is less than this number.
+    pub num_colors: Color,
+}
+
+impl Default for GeneratorParameters {
+    fn default() -> Self {
+        Self {
+            num_nodes: 50,
+            max_degree: 3,
+            num_colors: 8,
+        }
+    }
+}
+
+/// A generator for random parity games.
+///
+/// Each generated game is complete, i.e. it has no border and every node
+/// has at least one successor. The labels of the nodes are their indices,
+/// and the initial node is the node with index 0.
+#[derive(Debug, Clone)]
+pub struct GameGenerator {
+    parameters: GeneratorParameters,
+    random: SplitMix64,
+}
+
+impl GameGenerator {
+    /// Creates a new generator with the given parameters and seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of nodes, the maximal degree or the number of colors is zero.
+    pub fn new(parameters: GeneratorParameters, seed: u64) -> Self {
+        assert!(parameters.num_nodes > 0, "games need at least one node");
+        assert!(
+            parameters.max_degree > 0,
+            "nodes need at least one successor"
+        );
+        assert!(parameters.num_colors > 0, "games need at least one color");
+        Self {
+            parameters,
+            random: SplitMix64::new(seed),
+        }
+    }
+
+    /// Generates the next random parity game.
+    pub fn generate(&mut self) -> LabelledGame<NodeIndex> {
+        let n = self.parameters.num_nodes;
+        let mut game = LabelledGame::default();
+        for i in 0..n {
+            let (index, _) = game.add_border_node(i);
+            let owner = Player::PLAYERS[self.random.below(2)];
+            let color = self.random.below(self.parameters.num_colors);
+            game.update_node(index, owner, color);
+        }
+        for i in 0..n {
+            let degree = 1 + self.random.below(self.parameters.max_degree.min(n));
+            let mut successors: Vec<_> = (0..degree).map(|_| self.random.below(n)).collect();
+            successors.sort_unstable();
+            successors.dedup();
+            for j in successors {
+                game.add_edge(i, j);
+            }
+        }
+        game.set_initial_node(0);
+        game
+    }
+}
+
+impl Iterator for GameGenerator {
+    type Item = LabelledGame<NodeIndex>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.generate())
+    }
+}
@@ -1,8 +1,15 @@
 //! Parity games and parity game solvers.
 
+pub mod fuzz;
 pub mod game;
+pub mod reduce;
+pub mod solution;
 pub(crate) mod solver;
 
+pub use solver::{
+    AlgorithmStats, ParseStrategyError, SolverTelemetry, Strategy, TelemetrySink, WinningRegion,
+};
+
 use std::fmt;
 
 use owl::automaton::Color;
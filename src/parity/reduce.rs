@@ -0,0 +1,203 @@
+//! Reduction of parity games to small subgames that still exhibit a failure,
+//! e.g. a disagreement between parity game solvers, for diagnosing bugs.
+
+use log::debug;
+
+use super::game::{Game, LabelledGame, Node, NodeIndex, Region};
+
+/// An edge of a parity game from the first to the second node.
+type Edge = (NodeIndex, NodeIndex);
+
+/// Reduces the given complete parity game to a subgame on which `fails` still returns `true`.
+///
+/// The reduction first removes nodes and then edges of the game by delta debugging,
+/// i.e. it tries to remove chunks of decreasing size and keeps each removal for which
+/// the subgame still fails, until no single node or edge can be removed any more.
+/// Nodes without remaining successors are removed together with their incoming edges,
+/// such that each subgame is again complete, and the initial node is never removed.
+///
+/// In the subgame, the initial node has index 0 and all nodes keep their labels,
+/// so they can be related to the nodes of the original game.
+///
+/// # Panics
+///
+/// Panics if the game is not complete, i.e. if its border is not empty.
+pub fn reduce_game<F>(game: &LabelledGame<NodeIndex>, fails: F) -> LabelledGame<NodeIndex>
+where
+    F: FnMut(&LabelledGame<NodeIndex>) -> bool,
+{
+    assert!(game.border().is_empty(), "game must be complete");
+    let mut reducer = Reducer {
+        game,
+        fails,
+        num_tests: 0,
+    };
+    let nodes: Vec<_> = game.nodes().collect();
+    let edges: Vec<_> = game
+        .nodes()
+        .flat_map(|i| game[i].successors().iter().map(move |&j| (i, j)))
+        .collect();
+    let (mut nodes, mut edges) = reducer.restrict(&nodes, &edges).unwrap();
+    loop {
+        let size = (nodes.len(), edges.len());
+        let reduced_nodes =
+            reducer.remove_chunks(nodes, |reducer, candidate| reducer.test(candidate, &edges));
+        let (restricted_nodes, restricted_edges) =
+            reducer.restrict(&reduced_nodes, &edges).unwrap();
+        nodes = restricted_nodes;
+        edges = restricted_edges;
+        let reduced_edges =
+            reducer.remove_chunks(edges, |reducer, candidate| reducer.test(&nodes, candidate));
+        let (restricted_nodes, restricted_edges) =
+            reducer.restrict(&nodes, &reduced_edges).unwrap();
+        nodes = restricted_nodes;
+        edges = restricted_edges;
+        if (nodes.len(), edges.len()) == size {
+            break;
+        }
+    }
+    debug!(
+        "Reduced game from {} to {} nodes with {} tests",
+        game.num_nodes(),
+        nodes.len(),
+        reducer.num_tests
+    );
+    reducer.build(&nodes, &edges)
+}
+
+/// The state of the reduction of a game.
+struct Reducer<'a, F> {
+    game: &'a LabelledGame<NodeIndex>,
+    fails: F,
+    num_tests: usize,
+}
+
+impl<'a, F> Reducer<'a, F>
+where
+    F: FnMut(&LabelledGame<NodeIndex>) -> bool,
+{
+    /// Restricts the game to the given nodes and edges between them, and then
+    /// removes all nodes without successors until the subgame is complete.
+    ///
+    /// Returns the remaining nodes and edges, or `None` if the initial node is removed.
+    fn restrict(&self, nodes: &[NodeIndex], edges: &[Edge]) -> Option<(Vec<NodeIndex>, Vec<Edge>)> {
+        let mut region: Region = nodes.iter().copied().collect();
+        region.insert(self.game.initial_node());
+        let mut edges: Vec<_> = edges
+            .iter()
+            .copied()
+            .filter(|&(i, j)| region[i] && region[j])
+            .collect();
+        loop {
+            let mut with_successors = Region::with_capacity(self.game.num_nodes());
+            with_successors.extend(edges.iter().map(|&(i, _)| i));
+            if with_successors == region {
+                break;
+            }
+            region = with_successors;
+            edges.retain(|&(_, j)| region[j]);
+        }
+        if region.contains(self.game.initial_node()) {
+            Some((region.nodes().collect(), edges))
+        } else {
+            None
+        }
+    }
+
+    /// Builds the subgame with the given nodes and edges, where the initial node
+    /// is the first node of the subgame.
+    fn build(&self, nodes: &[NodeIndex], edges: &[Edge]) -> LabelledGame<NodeIndex> {
+        let initial_node = self.game.initial_node();
+        let mut subgame = LabelledGame::default();
+        let mut indices = vec![0; self.game.num_nodes()];
+        let order = std::iter::once(initial_node)
+            .chain(nodes.iter().copied().filter(|&i| i != initial_node));
+        for i in order {
+            let node = &self.game[i];
+            let (index, _) = subgame.add_border_node(*node.label());
+            subgame.update_node(index, node.owner(), node.color());
+            indices[i] = index;
+        }
+        for &(i, j) in edges {
+            subgame.add_edge(indices[i], indices[j]);
+        }
+        subgame.set_initial_node(0);
+        subgame
+    }
+
+    /// Returns whether the subgame with the given nodes and edges exists and fails.
+    fn test(&mut self, nodes: &[NodeIndex], edges: &[Edge]) -> bool {
+        match self.restrict(nodes, edges) {
+            Some((nodes, edges)) => {
+                let subgame = self.build(&nodes, &edges);
+                self.num_tests += 1;
+                (self.fails)(&subgame)
+            }
+            None => false,
+        }
+    }
+
+    /// Removes chunks of decreasing size from the given items as long as
+    /// `test` returns `true` for the remaining items.
+    fn remove_chunks<T: Copy>(
+        &mut self,
+        mut items: Vec<T>,
+        mut test: impl FnMut(&mut Self, &[T]) -> bool,
+    ) -> Vec<T> {
+        let mut chunk_size = (items.len() / 2).max(1);
+        while !items.is_empty() {
+            let mut removed = false;
+            let mut start = 0;
+            while start < items.len() {
+                let end = (start + chunk_size).min(items.len());
+                let candidate: Vec<_> = items[..start]
+                    .iter()
+                    .chain(&items[end..])
+                    .copied()
+                    .collect();
+                if test(self, &candidate) {
+                    items = candidate;
+                    removed = true;
+                } else {
+                    start = end;
+                }
+            }
+            if !removed {
+                if chunk_size == 1 {
+                    break;
+                }
+                chunk_size /= 2;
+            }
+        }
+        items
+    }
+}
+
+/// Tests for the reduction of parity games.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a game is reduced to the nodes needed for a failure.
+    #[test]
+    fn test_reduce_game() {
+        let game: LabelledGame<NodeIndex> =
+            "0 0 0 1,2;\n1 1 1 0,3;\n2 2 0 4;\n3 3 1 1,4;\n4 4 0 4,2;\n5 5 1 3;\n"
+                .parse()
+                .unwrap();
+        // the failure occurs if there is a node with color 4 reachable from the initial node
+        let fails = |subgame: &LabelledGame<NodeIndex>| {
+            Region::reachable(subgame, &Region::new())
+                .nodes()
+                .any(|i| subgame[i].color() == 4)
+        };
+        assert!(fails(&game));
+        let reduced = reduce_game(&game, &fails);
+        assert!(fails(&reduced));
+        assert!(reduced.border().is_empty());
+        assert_eq!(
+            reduced.to_string(),
+            "parity 3;\n0 0 0 1 \"0\";\n1 2 0 2 \"2\";\n2 4 0 1 \"4\";\n"
+        );
+    }
+}
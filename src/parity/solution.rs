@@ -0,0 +1,196 @@
+//! Solving of complete parity games given in PGSolver format.
+
+use std::fmt;
+
+use log::info;
+
+use super::game::{Game, LabelledGame, Node, NodeIndex, Player, Region};
+use super::solver::{
+    FpiSolver, OinkSolver, ParityGameSolver, SiSolver, Strategy, WinningRegion, ZlkSolver,
+};
+use crate::options::Solver;
+
+/// The solution of a parity game with the winner and a strategy for each node.
+///
+/// The solution of a partially explored or partially solved game may leave nodes
+/// undecided, which have no winner and are omitted when formatting the solution.
+#[derive(Debug, Clone)]
+pub struct GameSolution {
+    /// The identifiers of the nodes in the game.
+    ids: Vec<NodeIndex>,
+    /// The winner of each node, if the node is decided.
+    winners: Vec<Option<Player>>,
+    /// The successor chosen by the winner at each node owned by the winner,
+    /// if strategies are computed by the solver.
+    strategy: Vec<Option<NodeIndex>>,
+}
+
+impl GameSolution {
+    /// Creates a solution without decided nodes for a game with nodes with the given identifiers.
+    pub(crate) fn undecided(ids: Vec<NodeIndex>) -> Self {
+        let n = ids.len();
+        Self {
+            ids,
+            winners: vec![None; n],
+            strategy: vec![None; n],
+        }
+    }
+
+    /// Decides the nodes in the given region as won by the player, where the first
+    /// successor of the given strategy is chosen at the nodes owned by the player.
+    pub(crate) fn add_region<'a, G: Game<'a>>(
+        &mut self,
+        game: &'a G,
+        player: Player,
+        region: &Region,
+        strategy: Option<&Strategy>,
+    ) {
+        for i in region.nodes() {
+            self.winners[i] = Some(player);
+            if let Some(strategy) = strategy {
+                if game[i].owner() == player {
+                    self.strategy[i] = strategy[i].first().copied();
+                }
+            }
+        }
+    }
+
+    /// Returns the winner of the node with the given index, or `None` if the node is undecided.
+    pub fn winner(&self, node: NodeIndex) -> Option<Player> {
+        self.winners[node]
+    }
+
+    /// Returns the successor chosen by the winner at the node with the given index,
+    /// if the node is decided, owned by its winner and a strategy has been computed.
+    pub fn successor(&self, node: NodeIndex) -> Option<NodeIndex> {
+        self.strategy[node]
+    }
+
+    /// Returns the number of decided nodes.
+    pub fn num_decided(&self) -> usize {
+        self.winners.iter().filter(|w| w.is_some()).count()
+    }
+
+    /// Returns the winning regions of both players, where nodes are identified by their
+    /// position in the solution, i.e. by their index in the game for the solution of a
+    /// synthesis run, such that they can be given as hints to another run.
+    pub fn winning_region(&self) -> WinningRegion {
+        let mut winning = WinningRegion::with_capacity(self.winners.len());
+        for (i, winner) in self.winners.iter().enumerate() {
+            if let &Some(player) = winner {
+                winning[player].insert(i);
+            }
+        }
+        winning
+    }
+}
+
+impl fmt::Display for GameSolution {
+    /// Formats the solution in the PGSolver format for solutions,
+    /// using the identifiers of the nodes in the game.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "paritysol {};", self.ids.len())?;
+        for (i, &id) in self.ids.iter().enumerate() {
+            let winner = match self.winners[i] {
+                Some(winner) => winner,
+                None => continue,
+            };
+            write!(f, "{} {}", id, u32::from(winner))?;
+            if let Some(successor) = self.strategy[i] {
+                write!(f, " {}", self.ids[successor])?;
+            }
+            writeln!(f, ";")?;
+        }
+        Ok(())
+    }
+}
+
+fn solve_with<S: ParityGameSolver>(game: &LabelledGame<NodeIndex>, mut solver: S) -> GameSolution {
+    let disabled = Region::with_capacity(game.num_nodes());
+    let mut solution = GameSolution::undecided(game.nodes().map(|i| *game[i].label()).collect());
+    for &player in &Player::PLAYERS {
+        let (region, strategy) = solver.solve(game, &disabled, player, true);
+        solution.add_region(game, player, &region, strategy.as_ref());
+    }
+    solution
+}
+
+/// Solves the given complete parity game with the given solver, where the
+/// solver within Oink for [`Solver::Oink`] can be set with `oink_solver`.
+///
+/// Strategies for both players are included in the solution for all solvers.
+///
+/// # Panics
+///
+/// Panics if the game is not complete, i.e. has nodes in the border, or has deadlocks,
+/// which need to be completed with [`LabelledGame::complete_deadlocks`] before.
+pub fn solve_game(
+    game: &LabelledGame<NodeIndex>,
+    solver: Solver,
+    oink_solver: Option<String>,
+) -> GameSolution {
+    assert!(game.border().is_empty(), "game is not complete");
+    assert!(game.deadlocks().is_empty(), "game has deadlocks");
+    info!("Solving game with {} nodes", game.num_nodes());
+    let solution = match solver {
+        Solver::Fpi => solve_with(game, FpiSolver::new()),
+        Solver::Zlk => solve_with(game, ZlkSolver::new()),
+        Solver::Si => solve_with(game, SiSolver::new()),
+        Solver::Oink => solve_with(game, OinkSolver::new(oink_solver)),
+    };
+    info!(
+        "Game solved, winner of initial node is {}",
+        solution
+            .winner(game.initial_node())
+            .expect("complete game is solved")
+    );
+    solution
+}
+
+/// Tests for solving parity games.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that the solution of a small game is written in PGSolver format.
+    #[test]
+    fn test_solve_game() {
+        let game: LabelledGame<NodeIndex> = "3 2 0 3,5;\n5 1 1 5;\n".parse().unwrap();
+        let solution = solve_game(&game, Solver::Fpi, None);
+        assert_eq!(solution.winner(0), Some(Player::Even));
+        assert_eq!(solution.winner(1), Some(Player::Odd));
+        assert_eq!(solution.successor(0), Some(0));
+        assert_eq!(solution.to_string(), "paritysol 2;\n3 0 3;\n5 1 5;\n");
+        let solution_zlk = solve_game(&game, Solver::Zlk, None);
+        assert_eq!(solution_zlk.to_string(), solution.to_string());
+    }
+
+    /// Test that deadlocks are won by the given player with all solvers.
+    #[test]
+    fn test_solve_deadlocks() {
+        for &player in &Player::PLAYERS {
+            let mut game: LabelledGame<NodeIndex> = "0 2 1 0,1;\n1 1 0;\n".parse().unwrap();
+            assert_eq!(game.complete_deadlocks(player), 1);
+            for &solver in &[Solver::Fpi, Solver::Zlk, Solver::Si] {
+                let solution = solve_game(&game, solver, None);
+                assert_eq!(solution.winner(1), Some(player));
+                assert_eq!(solution.winner(0), Some(player));
+                assert_eq!(solution.successor(1), Some(1));
+            }
+        }
+    }
+
+    /// Test that undecided nodes are omitted from the solution of a partially solved game.
+    #[test]
+    fn test_partial_solution() {
+        let game: LabelledGame<NodeIndex> = "3 2 0 3,5;\n5 1 1 5;\n".parse().unwrap();
+        let mut solution = GameSolution::undecided(vec![3, 5]);
+        let mut region = Region::with_capacity(2);
+        region.insert(1);
+        solution.add_region(&game, Player::Odd, &region, None);
+        assert_eq!(solution.winner(0), None);
+        assert_eq!(solution.winner(1), Some(Player::Odd));
+        assert_eq!(solution.num_decided(), 1);
+        assert_eq!(solution.to_string(), "paritysol 2;\n5 1;\n");
+    }
+}
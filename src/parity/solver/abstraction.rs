@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::parity::game::{Game, LabelledGame, Node, NodeIndex, Player, Region};
+use crate::parity::solver::{MergingStats, ParityGameSolver, Quotient, WinningRegion};
+
+/// The maximum number of refinements of the abstraction in one call to decide nodes.
+const MAX_REFINEMENTS: usize = 8;
+
+/// Statistics on the nodes decided by solving abstract games.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AbstractionStats {
+    classes: usize,
+    refinements: usize,
+    nodes: usize,
+    decided: usize,
+    time: Duration,
+}
+
+impl AbstractionStats {
+    /// Returns the number of classes of the abstraction.
+    pub(crate) fn classes(&self) -> usize {
+        self.classes
+    }
+
+    /// Returns the number of refinements of the abstraction.
+    pub(crate) fn refinements(&self) -> usize {
+        self.refinements
+    }
+
+    /// Returns the number of nodes of the last solved abstract game after merging
+    /// equivalent nodes.
+    pub(crate) fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Returns the number of nodes decided by solving abstract games.
+    pub(crate) fn decided(&self) -> usize {
+        self.decided
+    }
+
+    /// Returns the time spent on constructing and solving abstract games.
+    pub(crate) fn time(&self) -> Duration {
+        self.time
+    }
+}
+
+impl fmt::Display for AbstractionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "classes: {}, refinements: {}, abstract nodes: {}, decided: {}, time: {:.2}",
+            self.classes(),
+            self.refinements(),
+            self.nodes(),
+            self.decided(),
+            self.time().as_secs_f32(),
+        )
+    }
+}
+
+/// An abstraction of a parity game that groups nodes into classes, e.g. the nodes
+/// for automaton states that only differ in some components of their label.
+///
+/// For a player, the abstract game replaces each edge to a grouped node by an edge
+/// to a node for its class, where the opponent chooses the node of the class to
+/// continue from. As the opponent resolves the abstraction, nodes won by a player in
+/// its abstract game are also won in the original game, where the border is lost
+/// for the player. The abstract games are solved on their quotient under bisimulation,
+/// which is much smaller than the original game if many grouped nodes lead to nodes
+/// of the same classes.
+///
+/// Classes whose node is won by neither player in their abstract game are inconclusive
+/// and are refined by splitting their nodes that are not equivalent in the abstract game.
+/// Fully refined, the abstract games are equivalent to the original game.
+pub(crate) struct Abstraction {
+    /// The class of each node, if the node is grouped.
+    classes: Vec<Option<usize>>,
+    /// The nodes for which a class has been assigned.
+    assigned: Region,
+    /// The class for new nodes with each key.
+    keys: HashMap<i32, usize>,
+    num_classes: usize,
+    merging: MergingStats,
+    stats: AbstractionStats,
+}
+
+impl Abstraction {
+    pub(crate) fn new() -> Self {
+        Self {
+            classes: Vec::new(),
+            assigned: Region::new(),
+            keys: HashMap::new(),
+            num_classes: 0,
+            merging: MergingStats::default(),
+            stats: AbstractionStats::default(),
+        }
+    }
+
+    /// Returns whether a class has been assigned to the node with the given index.
+    pub(crate) fn is_assigned(&self, index: NodeIndex) -> bool {
+        self.assigned[index]
+    }
+
+    /// Assigns the node with the given index to the class for the given key,
+    /// or leaves it ungrouped if no key is given.
+    pub(crate) fn assign(&mut self, index: NodeIndex, key: Option<i32>) {
+        if index >= self.classes.len() {
+            self.classes.resize(index + 1, None);
+        }
+        self.classes[index] = key.map(|key| {
+            let num_classes = &mut self.num_classes;
+            *self.keys.entry(key).or_insert_with(|| {
+                *num_classes += 1;
+                *num_classes - 1
+            })
+        });
+        self.assigned.insert(index);
+    }
+
+    pub(crate) fn stats(&self) -> &AbstractionStats {
+        &self.stats
+    }
+
+    /// Returns the class of the node with the given index.
+    fn class(&self, index: NodeIndex) -> Option<usize> {
+        self.classes.get(index).copied().flatten()
+    }
+
+    /// Returns the abstract game of the given game for the given player, where the node
+    /// for class `c` has index `n + c` for a game with `n` nodes.
+    fn abstract_game<'a, G: Game<'a>>(
+        &self,
+        game: &'a G,
+        player: Player,
+    ) -> LabelledGame<NodeIndex> {
+        let n = game.num_nodes();
+        let mut abstract_game = LabelledGame::default();
+        for index in 0..n + self.num_classes {
+            abstract_game.add_border_node(index);
+        }
+        for i in game.nodes() {
+            if !game.border()[i] {
+                let node = &game[i];
+                abstract_game.update_node(i, node.owner(), node.color());
+                let mut successors: Vec<_> = node
+                    .successors()
+                    .iter()
+                    .map(|&j| self.class(j).map_or(j, |c| n + c))
+                    .collect();
+                successors.sort_unstable();
+                successors.dedup();
+                for j in successors {
+                    abstract_game.add_edge(i, j);
+                }
+            }
+        }
+        // the opponent chooses the node of a class, which has the neutral color
+        for c in 0..self.num_classes {
+            abstract_game.update_node(n + c, !player, 0);
+        }
+        for i in game.nodes() {
+            if let Some(c) = self.class(i) {
+                abstract_game.add_edge(n + c, i);
+            }
+        }
+        abstract_game.set_initial_node(game.initial_node());
+        abstract_game
+    }
+
+    /// Decides nodes of the game by solving the abstract games for both players, where
+    /// the nodes in the given winning regions are already decided, and refines the
+    /// abstraction until the initial node is decided, no inconclusive class can be
+    /// refined or the maximum number of refinements is reached.
+    ///
+    /// Returns the winning regions of the decided nodes.
+    pub(crate) fn decide<'a, G: Game<'a>, S: ParityGameSolver>(
+        &mut self,
+        game: &'a G,
+        solver: &mut S,
+        winning: &WinningRegion,
+    ) -> WinningRegion {
+        let start = Instant::now();
+        let n = game.num_nodes();
+        let mut decided = WinningRegion::with_capacity(n);
+        for refinement in 0..=MAX_REFINEMENTS {
+            let mut won = WinningRegion::with_capacity(n + self.num_classes);
+            let mut equivalent = Vec::new();
+            for &player in &Player::PLAYERS {
+                let abstract_game = self.abstract_game(game, player);
+                let mut disabled = winning[!player].union(abstract_game.border());
+                disabled.attract_mut(&abstract_game, !player);
+                let quotient = Quotient::new(&abstract_game, &disabled, &mut self.merging);
+                let (winning_new, _) =
+                    solver.solve(quotient.game(), quotient.disabled(), player, false);
+                won[player] = quotient.lift_region(&winning_new);
+                if player == Player::Even {
+                    equivalent = (0..n).map(|i| quotient.class(i)).collect();
+                }
+                self.stats.nodes = self.merging.classes();
+            }
+            for &player in &Player::PLAYERS {
+                decided[player].extend(won[player].nodes().filter(|&i| i < n));
+            }
+            if decided.winner(game.initial_node()).is_some()
+                || refinement == MAX_REFINEMENTS
+                || !self.refine(n, &won, &equivalent)
+            {
+                break;
+            }
+            self.stats.refinements += 1;
+        }
+        self.stats.classes = self.num_classes;
+        self.stats.decided = decided.decided().size();
+        self.stats.time += start.elapsed();
+        decided
+    }
+
+    /// Splits the nodes of each inconclusive class by their classes of equivalent nodes
+    /// in the abstract game, and returns whether any class was split.
+    fn refine(&mut self, n: usize, won: &WinningRegion, equivalent: &[NodeIndex]) -> bool {
+        let mut splits: HashMap<(usize, NodeIndex), usize> = HashMap::new();
+        let mut first: HashMap<usize, NodeIndex> = HashMap::new();
+        for (i, &equivalent_node) in equivalent.iter().enumerate().take(n) {
+            if let Some(c) = self.class(i) {
+                if won.winner(n + c).is_some() {
+                    continue;
+                }
+                // nodes equivalent to the first node of the class stay in the class
+                let representative = *first.entry(c).or_insert(equivalent_node);
+                if equivalent_node != representative {
+                    let num_classes = &mut self.num_classes;
+                    let new_class = *splits.entry((c, equivalent_node)).or_insert_with(|| {
+                        *num_classes += 1;
+                        *num_classes - 1
+                    });
+                    self.classes[i] = Some(new_class);
+                }
+            }
+        }
+        !splits.is_empty()
+    }
+}
+
+/// Tests for abstractions of parity games.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parity::solver::ZlkSolver;
+
+    /// Test that a class of nodes with different winners is refined
+    /// until the initial node is decided.
+    #[test]
+    fn test_abstraction_refinement() {
+        // nodes 0 and 2 are grouped, where node 0 is won by even and node 2 by odd
+        let game: LabelledGame<NodeIndex> =
+            "0 0 0 1;\n1 2 0 0;\n2 0 0 3;\n3 1 0 2;\n".parse().unwrap();
+        let mut abstraction = Abstraction::new();
+        abstraction.assign(0, Some(7));
+        abstraction.assign(2, Some(7));
+        assert!(abstraction.is_assigned(2));
+
+        let decided = abstraction.decide(&game, &mut ZlkSolver::new(), &WinningRegion::new());
+        assert_eq!(decided.winner(0), Some(Player::Even));
+        assert_eq!(decided.winner(2), Some(Player::Odd));
+        assert_eq!(abstraction.stats().refinements(), 1);
+        assert_eq!(abstraction.stats().classes(), 2);
+    }
+
+    /// Test that nodes of a class are decided without refinement if the
+    /// opponent can not exploit the abstraction.
+    #[test]
+    fn test_abstraction_sound() {
+        // nodes 0 and 1 are grouped and both won by even
+        let game: LabelledGame<NodeIndex> =
+            "0 0 0 2;\n1 0 0 3;\n2 2 0 1;\n3 0 0 0;\n".parse().unwrap();
+        let mut abstraction = Abstraction::new();
+        abstraction.assign(0, Some(0));
+        abstraction.assign(1, Some(0));
+        abstraction.assign(2, None);
+        let decided = abstraction.decide(&game, &mut ZlkSolver::new(), &WinningRegion::new());
+        assert_eq!(decided.winner(0), Some(Player::Even));
+        assert_eq!(decided.winner(1), Some(Player::Even));
+        assert_eq!(abstraction.stats().refinements(), 0);
+    }
+}
@@ -20,7 +20,7 @@
 use owl::automaton::Color;
 
 use crate::parity::game::{Game, Node, NodeIndex, Player, Region};
-use crate::parity::solver::{ParityGameSolver, Strategy};
+use crate::parity::solver::{AlgorithmStats, ParityGameSolver, Strategy};
 use crate::parity::Parity;
 
 struct FpiSolverInstance<'a, 'b, G> {
@@ -28,6 +28,7 @@ struct FpiSolverInstance<'a, 'b, G> {
     disabled: &'b Region,
     frozen: Vec<Color>,
     distraction: Vec<bool>,
+    iterations: usize,
 }
 
 impl<'a, 'b, G: Game<'a>> FpiSolverInstance<'a, 'b, G> {
@@ -37,6 +38,7 @@ impl<'a, 'b, G: Game<'a>> FpiSolverInstance<'a, 'b, G> {
             disabled,
             frozen: vec![0; game.num_nodes()],
             distraction: vec![false; game.num_nodes()],
+            iterations: 0,
         }
     }
 
@@ -122,6 +124,7 @@ impl<'a, 'b, G: Game<'a>> FpiSolverInstance<'a, 'b, G> {
         // Main loop
         let mut c = 0;
         while c < self.game.num_colors() {
+            self.iterations += 1;
             if self.update_block(strategy.as_mut(), player, c) {
                 c += 1;
             } else {
@@ -142,11 +145,15 @@ impl<'a, 'b, G: Game<'a>> FpiSolverInstance<'a, 'b, G> {
     }
 }
 
-pub(crate) struct FpiSolver {}
+pub(crate) struct FpiSolver {
+    stats: AlgorithmStats,
+}
 
 impl FpiSolver {
     pub(crate) fn new() -> Self {
-        Self {}
+        Self {
+            stats: AlgorithmStats::default(),
+        }
     }
 }
 
@@ -158,6 +165,14 @@ impl ParityGameSolver for FpiSolver {
         player: Player,
         compute_strategy: bool,
     ) -> (Region, Option<Strategy>) {
-        FpiSolverInstance::new(game, disabled).run(player, compute_strategy)
+        let mut fpi = FpiSolverInstance::new(game, disabled);
+        let (winning, strategy) = fpi.run(player, compute_strategy);
+        self.stats.iterations += fpi.iterations;
+        self.stats.add_strategy(strategy.as_ref());
+        (winning, strategy)
+    }
+
+    fn algorithm_stats(&self) -> AlgorithmStats {
+        self.stats
     }
 }
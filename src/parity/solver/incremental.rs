@@ -1,57 +1,381 @@
-use std::time::Instant;
+use std::fmt;
+use std::time::{Duration, Instant};
 
-use crate::parity::game::{Game, Player};
-use crate::parity::solver::{ParityGameSolver, SolvingStats, Strategy, WinningRegion};
+use crate::parity::game::{Game, Node, Player, Region};
+use crate::parity::solver::{
+    preprocess, Abstraction, MergingStats, ParityGameSolver, PreprocessingStats, Quotient,
+    SolvingStats, Strategy, WinningRegion,
+};
 
 pub(crate) trait IncrementalParityGameSolver {
     fn solve<'a, G: Game<'a>>(&mut self, game: &'a G) -> Option<Player>;
     fn strategy<'a, G: Game<'a>>(&mut self, game: &'a G, player: Player) -> Strategy;
 }
 
-pub(crate) struct IncrementalSolver<S: ParityGameSolver> {
+/// A record of a single call to solve the game in the incremental solver.
+#[derive(Debug, Clone, Default)]
+pub struct SolverTelemetry {
+    iteration: usize,
+    nodes: usize,
+    new_won_even: usize,
+    new_won_odd: usize,
+    time: Duration,
+}
+
+impl SolverTelemetry {
+    /// Returns the number of the call to solve the game, starting from 1.
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Returns the number of nodes of the game, including the border.
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Returns the number of nodes newly won by the even player in this call.
+    pub fn new_won_even(&self) -> usize {
+        self.new_won_even
+    }
+
+    /// Returns the number of nodes newly won by the odd player in this call.
+    pub fn new_won_odd(&self) -> usize {
+        self.new_won_odd
+    }
+
+    /// Returns the time spent in this call.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+}
+
+impl fmt::Display for SolverTelemetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "iteration: {}, |V+B|: {}, new |W_even|: {}, new |W_odd|: {}, time: {:.2}",
+            self.iteration(),
+            self.nodes(),
+            self.new_won_even(),
+            self.new_won_odd(),
+            self.time().as_secs_f32(),
+        )
+    }
+}
+
+/// A receiver for the telemetry of the incremental solver, which is notified
+/// after each call to solve the game.
+pub trait TelemetrySink {
+    /// Records the telemetry of a call to solve the game.
+    fn record(&mut self, telemetry: &SolverTelemetry);
+}
+
+impl<F: FnMut(&SolverTelemetry)> TelemetrySink for F {
+    fn record(&mut self, telemetry: &SolverTelemetry) {
+        self(telemetry)
+    }
+}
+
+/// Statistics on the hints for the winning regions given to the incremental solver.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HintStats {
+    hinted: usize,
+    accepted: usize,
+    rejected: usize,
+}
+
+impl HintStats {
+    /// Returns the number of hinted nodes.
+    pub(crate) fn hinted(&self) -> usize {
+        self.hinted
+    }
+
+    /// Returns the number of hinted nodes verified and added to the winning regions.
+    pub(crate) fn accepted(&self) -> usize {
+        self.accepted
+    }
+
+    /// Returns the number of hinted nodes rejected as won by the other player.
+    pub(crate) fn rejected(&self) -> usize {
+        self.rejected
+    }
+}
+
+impl fmt::Display for HintStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hinted: {}, accepted: {}, rejected: {}",
+            self.hinted(),
+            self.accepted(),
+            self.rejected(),
+        )
+    }
+}
+
+pub(crate) struct IncrementalSolver<'t, S: ParityGameSolver> {
     winning: WinningRegion,
     solver: S,
     stats: SolvingStats,
+    /// Whether solving and strategy computation are restricted to the nodes
+    /// reachable from the initial node.
+    reachable_only: bool,
+    /// Whether solving stops once the initial node is decided, and strategies are
+    /// computed within the winning regions determined so far.
+    initial_only: bool,
+    /// The statistics of preprocessing the game before each call to the solver,
+    /// if preprocessing is enabled.
+    preprocessing: Option<PreprocessingStats>,
+    /// The statistics of merging equivalent nodes before each call to the inner solver,
+    /// if merging is enabled.
+    merging: Option<MergingStats>,
+    /// The hints for the winning regions that are not yet verified or rejected,
+    /// together with their statistics, if hints are given.
+    hints: Option<(WinningRegion, HintStats)>,
+    /// The telemetry of the last call to solve the game.
+    telemetry: SolverTelemetry,
+    /// The sink notified with the telemetry after each call to solve the game.
+    sink: Option<&'t mut dyn TelemetrySink>,
 }
 
-impl<S: ParityGameSolver> IncrementalSolver<S> {
+impl<'t, S: ParityGameSolver> IncrementalSolver<'t, S> {
     pub(crate) fn new(solver: S) -> Self {
         Self {
             winning: WinningRegion::new(),
             solver,
             stats: SolvingStats::default(),
+            reachable_only: false,
+            initial_only: false,
+            preprocessing: None,
+            merging: None,
+            hints: None,
+            telemetry: SolverTelemetry::default(),
+            sink: None,
+        }
+    }
+
+    /// Registers a sink that is notified with the telemetry after each call
+    /// to solve the game.
+    pub(crate) fn register_telemetry(&mut self, sink: &'t mut dyn TelemetrySink) {
+        self.sink = Some(sink);
+    }
+
+    /// Restricts solving and strategy computation to the subgame reachable from the
+    /// initial node, which is recomputed for every call to solve the game.
+    ///
+    /// For solving, nodes that are only reachable through already won nodes are skipped
+    /// as well. Nodes outside the subgame are left undecided, but may be solved in a
+    /// later call if they become reachable again after further exploration.
+    pub(crate) fn restrict_to_reachable(&mut self, reachable_only: bool) {
+        self.reachable_only = reachable_only;
+    }
+
+    /// Stops each call to solve the game as soon as the initial node is decided, e.g.
+    /// by the attractors of the winning regions or the solution for the first player,
+    /// instead of also classifying the remaining nodes.
+    ///
+    /// The winning regions then only need to contain the initial node, so strategies
+    /// are computed within the winning region of the player determined so far.
+    pub(crate) fn stop_at_decided_initial(&mut self, initial_only: bool) {
+        self.initial_only = initial_only;
+    }
+
+    /// Returns whether solving stops at the decided initial node and the initial node
+    /// of the given game is decided.
+    fn initial_decided<'a, G: Game<'a>>(&self, game: &'a G) -> bool {
+        self.initial_only && self.winning.winner(game.initial_node()).is_some()
+    }
+
+    /// Decides nodes by cheap reductions before each call to the solver, i.e. forced
+    /// self-loops, cycles of nodes with a single successor and their attractors.
+    pub(crate) fn enable_preprocessing(&mut self, preprocessing: bool) {
+        self.preprocessing = preprocessing.then(PreprocessingStats::default);
+    }
+
+    /// Solves the quotient of the game under bisimulation instead of the game itself
+    /// in each call to the inner solver, where equivalent nodes are merged into a
+    /// single node and the solution is lifted back to the game.
+    pub(crate) fn enable_merging(&mut self, merging: bool) {
+        self.merging = merging.then(MergingStats::default);
+    }
+
+    /// Gives hints for the winning regions of both players, e.g. from a previous run on
+    /// a related specification, which are verified before each call to solve the game.
+    ///
+    /// Hinted nodes are only added to the winning regions once verified, and hints that
+    /// are incorrect are rejected, so hints do not change the solution of the game.
+    pub(crate) fn set_hints(&mut self, hints: WinningRegion) {
+        let stats = HintStats {
+            hinted: hints.decided().size(),
+            ..HintStats::default()
+        };
+        self.hints = Some((hints, stats));
+    }
+
+    /// Verifies the hints for the undecided nodes of the game and adds the verified
+    /// nodes to the winning regions.
+    ///
+    /// For each player, the hinted nodes are first restricted to the largest subset in
+    /// which the player can stay and which the opponent can not leave, except into the
+    /// winning region of the player. The subgame on this subset is then solved for the
+    /// player, and as the opponent can not escape, its winning region is also won in
+    /// the game. Hinted nodes that are not won in the subgame remain for later calls.
+    fn verify_hints<'a, G: Game<'a>>(&mut self, game: &'a G) {
+        let (mut hints, mut stats) = match self.hints.take() {
+            Some(hints) => hints,
+            None => return,
+        };
+        let n = game.num_nodes();
+        for &player in &Player::PLAYERS {
+            let undecided = self.winning.decided().union(game.border()).complement(n);
+            let mut candidates = hints[player].intersection(&undecided);
+            loop {
+                let won = &self.winning[player];
+                let inside = |j: &usize| candidates[*j] || won[*j];
+                let invalid: Vec<_> = candidates
+                    .nodes()
+                    .filter(|&i| {
+                        let successors = game[i].successors();
+                        if game[i].owner() == player {
+                            !successors.iter().any(inside)
+                        } else {
+                            !successors.iter().all(inside)
+                        }
+                    })
+                    .collect();
+                if invalid.is_empty() {
+                    break;
+                }
+                for i in invalid {
+                    candidates.remove(i);
+                }
+            }
+            if candidates.is_empty() {
+                continue;
+            }
+
+            let disabled = candidates.complement(n);
+            let (winning_new, _) = self.solve_inner(game, &disabled, player, false);
+            stats.accepted += winning_new.size();
+            hints[player].difference_with(&winning_new);
+            self.winning[player].union_with(&winning_new);
+            self.winning[player].attract_mut(game, player);
+        }
+        self.hints = Some((hints, stats));
+    }
+
+    /// Rejects the hinted nodes that are won by the other player, and removes the
+    /// decided nodes from the hints.
+    fn reject_hints(&mut self) {
+        if let Some((hints, stats)) = &mut self.hints {
+            for &player in &Player::PLAYERS {
+                let rejected = hints[player].intersection(&self.winning[!player]);
+                stats.rejected += rejected.size();
+                hints[player].difference_with(&self.winning.decided());
+            }
+        }
+    }
+
+    /// Solves the game without the disabled nodes with the inner solver, or its
+    /// quotient if merging is enabled.
+    fn solve_inner<'a, G: Game<'a>>(
+        &mut self,
+        game: &'a G,
+        disabled: &Region,
+        player: Player,
+        compute_strategy: bool,
+    ) -> (Region, Option<Strategy>) {
+        match &mut self.merging {
+            Some(stats) => {
+                let quotient = Quotient::new(game, disabled, stats);
+                let (winning, strategy) = self.solver.solve(
+                    quotient.game(),
+                    quotient.disabled(),
+                    player,
+                    compute_strategy,
+                );
+                (
+                    quotient.lift_region(&winning),
+                    strategy.map(|strategy| quotient.lift_strategy(game, &strategy)),
+                )
+            }
+            None => self.solver.solve(game, disabled, player, compute_strategy),
+        }
+    }
+
+    /// Returns the region of nodes that are not reachable from the initial node
+    /// without passing through the given blocked region, if solving is restricted
+    /// to the reachable subgame, and an empty region otherwise.
+    fn unreachable<'a, G: Game<'a>>(&self, game: &'a G, blocked: &Region) -> Region {
+        let n = game.num_nodes();
+        if self.reachable_only {
+            Region::reachable(game, blocked).complement(n)
+        } else {
+            Region::with_capacity(n)
         }
     }
 }
 
-impl<S: ParityGameSolver> IncrementalParityGameSolver for IncrementalSolver<S> {
+impl<'t, S: ParityGameSolver> IncrementalParityGameSolver for IncrementalSolver<'t, S> {
     fn solve<'a, G: Game<'a>>(&mut self, game: &'a G) -> Option<Player> {
         let start = Instant::now();
 
         let n = game.num_nodes();
+        let won_even = self.winning[Player::Even].size();
+        let won_odd = self.winning[Player::Odd].size();
 
+        if let Some(stats) = &mut self.preprocessing {
+            preprocess(game, &mut self.winning, stats);
+        }
         for &player in &Player::PLAYERS {
             // extend winning region with attractor
             self.winning[player].grow(n);
             self.winning[player].attract_mut(game, player);
         }
+        if !self.initial_decided(game) {
+            self.verify_hints(game);
+        }
+        // skip nodes only reachable through already won nodes or the border
+        let decided = self.winning[Player::Even]
+            .union(&self.winning[Player::Odd])
+            .union(game.border());
+        let unreachable = self.unreachable(game, &decided);
         for &player in &Player::PLAYERS {
-            // Remove corresponding border attractor and already won nodes
+            if self.initial_decided(game) {
+                self.stats.skipped_solves += 1;
+                continue;
+            }
+            // Remove corresponding border attractor, already won and unreachable nodes
             let mut disabled = self.winning[!player].union(game.border());
             disabled.attract_mut(game, !player);
             disabled.union_with(&self.winning[player]);
+            disabled.union_with(&unreachable);
 
             let start_inner = Instant::now();
-            let (winning_new, _) = self.solver.solve(game, &disabled, player, false);
+            let (winning_new, _) = self.solve_inner(game, &disabled, player, false);
             self.stats.time_inner_solver += start_inner.elapsed();
 
             // add new winning region to existing region
             self.winning[player].union_with(&winning_new);
         }
+        self.reject_hints();
+        let time = start.elapsed();
         self.stats.nodes = n;
-        self.stats.time += start.elapsed();
+        self.stats.time += time;
         self.stats.nodes_won_even = self.winning[Player::Even].size();
         self.stats.nodes_won_odd = self.winning[Player::Odd].size();
+        self.stats.algorithm = self.solver.algorithm_stats();
+
+        self.telemetry = SolverTelemetry {
+            iteration: self.telemetry.iteration + 1,
+            nodes: n,
+            new_won_even: self.stats.nodes_won_even - won_even,
+            new_won_odd: self.stats.nodes_won_odd - won_odd,
+            time,
+        };
+        if let Some(sink) = &mut self.sink {
+            sink.record(&self.telemetry);
+        }
 
         // Get winner of initial node
         let node = game.initial_node();
@@ -67,16 +391,157 @@ impl<S: ParityGameSolver> IncrementalParityGameSolver for IncrementalSolver<S> {
     fn strategy<'a, G: Game<'a>>(&mut self, game: &'a G, player: Player) -> Strategy {
         let start = Instant::now();
 
-        let border = game.border().attract(game, !player);
-        let (_, strategy) = self.solver.solve(game, &border, player, true);
+        let mut disabled = game.border().attract(game, !player);
+        disabled.union_with(&self.unreachable(game, game.border()));
+        if self.initial_only {
+            let n = game.num_nodes();
+            disabled.union_with(&self.winning[player].complement(n));
+        }
+        let (_, strategy) = self.solve_inner(game, &disabled, player, true);
 
         self.stats.time_strategy += start.elapsed();
+        self.stats.algorithm = self.solver.algorithm_stats();
         strategy.expect("no winning strategy")
     }
 }
 
-impl<S: ParityGameSolver> IncrementalSolver<S> {
+impl<'t, S: ParityGameSolver> IncrementalSolver<'t, S> {
     pub(crate) fn stats(&self) -> &SolvingStats {
         &self.stats
     }
+
+    /// Returns the statistics of preprocessing, if preprocessing is enabled.
+    pub(crate) fn preprocessing_stats(&self) -> Option<&PreprocessingStats> {
+        self.preprocessing.as_ref()
+    }
+
+    /// Returns the statistics of merging equivalent nodes, if merging is enabled.
+    pub(crate) fn merging_stats(&self) -> Option<&MergingStats> {
+        self.merging.as_ref()
+    }
+
+    /// Returns the statistics of the hints for the winning regions, if hints are given.
+    pub(crate) fn hint_stats(&self) -> Option<&HintStats> {
+        self.hints.as_ref().map(|(_, stats)| stats)
+    }
+
+    /// Returns the telemetry of the last call to solve the game.
+    pub(crate) fn telemetry(&self) -> &SolverTelemetry {
+        &self.telemetry
+    }
+
+    /// Returns the winning regions of both players determined so far.
+    pub(crate) fn winning(&self) -> &WinningRegion {
+        &self.winning
+    }
+
+    /// Returns the region of nodes for which the winner is already determined.
+    pub(crate) fn decided(&self) -> Region {
+        self.winning.decided()
+    }
+
+    /// Decides nodes of the game by solving the abstract games of the given abstraction
+    /// with the inner solver, and adds them to the winning regions of both players,
+    /// such that they are skipped by the next call to solve the game.
+    pub(crate) fn solve_abstraction<'a, G: Game<'a>>(
+        &mut self,
+        game: &'a G,
+        abstraction: &mut Abstraction,
+    ) {
+        let decided = abstraction.decide(game, &mut self.solver, &self.winning);
+        for &player in &Player::PLAYERS {
+            self.winning[player].union_with(&decided[player]);
+        }
+    }
+}
+
+/// Tests for the incremental solver.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parity::game::{LabelledGame, Node, NodeIndex};
+    use crate::parity::solver::{FpiSolver, SiSolver, ZlkSolver};
+
+    /// Solves the game with the given solver and checks that the strategy of the odd
+    /// player only chooses successors within its winning region.
+    fn check_odd_strategy<S: ParityGameSolver>(solver: S) {
+        let game: LabelledGame<NodeIndex> =
+            "0 0 0 1,2;\n1 2 1 3,4;\n2 1 1 1,2;\n3 3 0 1;\n4 2 1 0,3;\n"
+                .parse()
+                .unwrap();
+        let mut solver = IncrementalSolver::new(solver);
+        assert_eq!(solver.solve(&game), Some(Player::Odd));
+        let winning = solver.winning[Player::Odd].clone();
+        assert_eq!(winning, Region::parse("{ 0 1 2 3 4 }", 5).unwrap());
+        let strategy = solver.strategy(&game, Player::Odd);
+        for i in winning.nodes().filter(|&i| game[i].owner() == Player::Odd) {
+            assert!(!strategy[i].is_empty());
+            assert!(strategy[i].iter().all(|&j| winning[j]));
+        }
+        assert_eq!(strategy[4], vec![3]);
+        let algorithm = solver.stats().algorithm();
+        assert!(algorithm.iterations() > 0);
+        assert!(algorithm.strategy_edges() > 0);
+    }
+
+    /// Test that verified hints are accepted and incorrect hints are rejected without
+    /// changing the solution of the game.
+    #[test]
+    fn test_hints() {
+        let game: LabelledGame<NodeIndex> =
+            "0 0 0 1,2;\n1 2 1 3,4;\n2 1 1 1,2;\n3 3 0 1;\n4 2 1 0,3;\n"
+                .parse()
+                .unwrap();
+        let mut solver = IncrementalSolver::new(ZlkSolver::new());
+        solver.set_hints(WinningRegion::parse("even { 0 1 }\nodd { 2 3 }", 5).unwrap());
+        assert_eq!(solver.solve(&game), Some(Player::Odd));
+        assert_eq!(
+            solver.winning[Player::Odd],
+            Region::parse("{ 0 1 2 3 4 }", 5).unwrap()
+        );
+        let stats = solver.hint_stats().unwrap();
+        assert_eq!(stats.hinted(), 4);
+        assert_eq!(stats.accepted(), 1);
+        assert_eq!(stats.rejected(), 2);
+    }
+
+    /// Test that solving stops once the initial node is decided, and that the strategy
+    /// stays within the winning region determined so far.
+    #[test]
+    fn test_stop_at_decided_initial() {
+        // node 0 is won by the even player through the attractor of the self-loop at
+        // node 1, while nodes 2 and 3 are only decided by the solver
+        let game: LabelledGame<NodeIndex> = "0 0 1 1;\n1 2 0 1;\n2 1 0 3;\n3 0 1 2,3;\n"
+            .parse()
+            .unwrap();
+        let mut full = IncrementalSolver::new(ZlkSolver::new());
+        assert_eq!(full.solve(&game), Some(Player::Even));
+        assert_eq!(
+            full.winning[Player::Odd],
+            Region::parse("{ 2 3 }", 4).unwrap()
+        );
+        assert_eq!(full.stats().skipped_solves(), 0);
+
+        let mut solver = IncrementalSolver::new(ZlkSolver::new());
+        solver.enable_preprocessing(true);
+        solver.stop_at_decided_initial(true);
+        assert_eq!(solver.solve(&game), Some(Player::Even));
+        assert_eq!(
+            solver.winning[Player::Even],
+            Region::parse("{ 0 1 }", 4).unwrap()
+        );
+        assert!(solver.winning[Player::Odd].is_empty());
+        assert_eq!(solver.stats().skipped_solves(), 2);
+        let strategy = solver.strategy(&game, Player::Even);
+        assert_eq!(strategy.domain(), Region::parse("{ 1 }", 4).unwrap());
+        assert_eq!(strategy[1], vec![1]);
+    }
+
+    /// Test strategies for the odd player with all internal solvers.
+    #[test]
+    fn test_strategy_odd() {
+        check_odd_strategy(FpiSolver::new());
+        check_odd_strategy(SiSolver::new());
+        check_odd_strategy(ZlkSolver::new());
+    }
 }
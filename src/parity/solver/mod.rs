@@ -1,15 +1,27 @@
+mod abstraction;
 mod fpi;
 mod incremental;
+mod oink;
+mod preprocess;
+mod quotient;
+mod safety;
 mod si;
 mod zlk;
 
+use std::collections::VecDeque;
 use std::fmt;
 use std::ops::{Index, IndexMut};
 use std::time::Duration;
 
-use crate::parity::game::{Game, NodeIndex, Player, Region};
+use crate::parity::game::{Game, Node, NodeIndex, ParseRegionError, Player, Region};
+pub(crate) use abstraction::Abstraction;
 pub(crate) use fpi::FpiSolver;
 pub(crate) use incremental::{IncrementalParityGameSolver, IncrementalSolver};
+pub use incremental::{SolverTelemetry, TelemetrySink};
+pub(crate) use oink::OinkSolver;
+pub(crate) use preprocess::{preprocess, PreprocessingStats};
+pub(crate) use quotient::{MergingStats, Quotient};
+pub(crate) use safety::SafetySolver;
 pub(crate) use si::SiSolver;
 pub(crate) use zlk::ZlkSolver;
 
@@ -21,71 +33,310 @@ pub trait ParityGameSolver {
         player: Player,
         compute_strategy: bool,
     ) -> (Region, Option<Strategy>);
+
+    /// Returns the metrics of the algorithm accumulated over all solved games.
+    fn algorithm_stats(&self) -> AlgorithmStats {
+        AlgorithmStats::default()
+    }
 }
-#[derive(Debug, Clone)]
+
+/// A strategy for a player in a parity game, which gives the chosen successors
+/// for each node of the game.
+///
+/// A strategy may choose several successors at a node, in which case every
+/// choice is allowed. Nodes without chosen successors are not controlled
+/// by the strategy.
+///
+/// A strategy can be indexed by the index of a game node, which returns the
+/// successors chosen at that node. It is formatted with a header `strategy <n>;`,
+/// where `<n>` is the number of nodes, followed by one line of the form
+/// `<node> <successor>,<successor>,...;` for each node with chosen successors,
+/// and can be parsed from this format. Parsing only allocates memory for the nodes
+/// up to the last node with chosen successors, not for all nodes of the header.
+#[derive(Debug, Clone, Default)]
 pub struct Strategy {
+    nodes: usize,
     data: Vec<Vec<NodeIndex>>,
 }
 
+/// The successors of nodes beyond the allocated part of a strategy.
+static NO_SUCCESSORS: Vec<NodeIndex> = Vec::new();
+
 impl Strategy {
-    fn new() -> Self {
-        Self { data: Vec::new() }
+    /// Creates a new strategy for a game without nodes.
+    pub fn new() -> Self {
+        Self {
+            nodes: 0,
+            data: Vec::new(),
+        }
     }
 
-    fn empty<'a, G: Game<'a>>(game: &G) -> Self {
+    /// Creates a new strategy without chosen successors for a game with `n` nodes.
+    pub fn with_nodes(n: usize) -> Self {
         Self {
-            data: vec![Vec::new(); game.num_nodes()],
+            nodes: n,
+            data: vec![Vec::new(); n],
         }
     }
 
-    fn grow(&mut self, n: usize) {
+    fn empty<'a, G: Game<'a>>(game: &G) -> Self {
+        Self::with_nodes(game.num_nodes())
+    }
+
+    /// Grows this strategy to a game with `n` nodes.
+    pub fn grow(&mut self, n: usize) {
         if n > self.data.len() {
             self.data.resize(n, Vec::new());
         }
+        self.nodes = self.nodes.max(n);
+    }
+
+    /// Returns the number of nodes of the game for this strategy.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Returns an iterator over the nodes with chosen successors in increasing order,
+    /// together with the chosen successors.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeIndex, &[NodeIndex])> + '_ {
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(_, successors)| !successors.is_empty())
+            .map(|(i, successors)| (i, successors.as_slice()))
+    }
+
+    /// Returns the region of nodes with chosen successors.
+    pub fn domain(&self) -> Region {
+        self.iter().map(|(i, _)| i).collect()
+    }
+
+    /// Returns whether at most one successor is chosen at each node.
+    pub fn is_deterministic(&self) -> bool {
+        self.data.iter().all(|successors| successors.len() <= 1)
+    }
+
+    /// Removes the chosen successors at all nodes outside the given region.
+    pub fn restrict_to(&mut self, region: &Region) {
+        for (i, successors) in self.data.iter_mut().enumerate() {
+            if !region[i] {
+                successors.clear();
+            }
+        }
+    }
+
+    /// Returns the nodes reachable from the initial node if the given player
+    /// follows this strategy, at which a decision depends on the unexplored part
+    /// of the game.
+    ///
+    /// A decision at a node depends on the unexplored part if the node has a successor
+    /// in the border, as the strategy was then chosen without knowledge of the game
+    /// behind that successor. Nodes of the opponent are included as well, as the
+    /// opponent may move into the border from there.
+    pub fn border_dependencies<'a, G: Game<'a>>(&self, game: &'a G, player: Player) -> Region {
+        let n = game.num_nodes();
+        let border = game.border();
+        let mut dependencies = Region::with_capacity(n);
+        let mut visited = Region::with_capacity(n);
+        let mut queue = VecDeque::with_capacity(n);
+        let initial_node = game.initial_node();
+        visited.insert(initial_node);
+        queue.push_back(initial_node);
+        while let Some(i) = queue.pop_front() {
+            let node = &game[i];
+            if node.successors().iter().any(|&j| border[j]) {
+                dependencies.insert(i);
+            }
+            let successors: &[NodeIndex] = if node.owner() == player {
+                &self[i]
+            } else {
+                node.successors()
+            };
+            for &j in successors {
+                if !border[j] && !visited[j] {
+                    visited.insert(j);
+                    queue.push_back(j);
+                }
+            }
+        }
+        dependencies
+    }
+}
+
+impl PartialEq for Strategy {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes && self.iter().eq(other.iter())
     }
 }
 
+impl Eq for Strategy {}
+
 impl Index<NodeIndex> for Strategy {
     type Output = Vec<NodeIndex>;
 
     fn index(&self, index: NodeIndex) -> &Self::Output {
-        &self.data[index]
+        assert!(index < self.nodes, "node {} out of range", index);
+        self.data.get(index).unwrap_or(&NO_SUCCESSORS)
     }
 }
 
 impl IndexMut<NodeIndex> for Strategy {
     fn index_mut(&mut self, index: NodeIndex) -> &mut Self::Output {
+        assert!(index < self.nodes, "node {} out of range", index);
+        if index >= self.data.len() {
+            self.data.resize(index + 1, Vec::new());
+        }
         &mut self.data[index]
     }
 }
 
-#[derive(Debug, Clone)]
-struct WinningRegion {
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "strategy {};", self.num_nodes())?;
+        for (i, successors) in self.iter() {
+            write!(f, "{} ", i)?;
+            for (k, j) in successors.iter().enumerate() {
+                if k > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", j)?;
+            }
+            writeln!(f, ";")?;
+        }
+        Ok(())
+    }
+}
+
+/// An error which can be returned when parsing a strategy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStrategyError(String);
+
+impl fmt::Display for ParseStrategyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid strategy: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseStrategyError {}
+
+impl std::str::FromStr for Strategy {
+    type Err = ParseStrategyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_index = |token: &str| {
+            token
+                .trim()
+                .parse::<NodeIndex>()
+                .map_err(|_| ParseStrategyError(format!("invalid node index '{}'", token)))
+        };
+        let mut statements = s.split(';').map(str::trim).filter(|s| !s.is_empty());
+        let n = statements
+            .next()
+            .and_then(|header| header.strip_prefix("strategy "))
+            .ok_or_else(|| ParseStrategyError("missing header".to_string()))
+            .and_then(parse_index)?;
+        let mut strategy = Self {
+            nodes: n,
+            data: Vec::new(),
+        };
+        for statement in statements {
+            let (node, successors) = statement.split_once(' ').ok_or_else(|| {
+                ParseStrategyError(format!("node {} has no successors", statement))
+            })?;
+            let i = parse_index(node)?;
+            if i >= n {
+                return Err(ParseStrategyError(format!("node {} out of range", i)));
+            }
+            if !strategy[i].is_empty() {
+                return Err(ParseStrategyError(format!("node {} defined twice", i)));
+            }
+            for successor in successors.split(',') {
+                let j = parse_index(successor)?;
+                if j >= n {
+                    return Err(ParseStrategyError(format!("node {} out of range", j)));
+                }
+                strategy[i].push(j);
+            }
+        }
+        Ok(strategy)
+    }
+}
+
+/// The winning regions of both players in a parity game.
+///
+/// The region of a player can be obtained by indexing with the player. A winning
+/// region is formatted as two lines `even <region>` and `odd <region>`, using the
+/// format of [`Region`], and can be parsed from this format with [`WinningRegion::parse`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WinningRegion {
     even: Region,
     odd: Region,
 }
 
 impl WinningRegion {
-    fn new() -> Self {
+    /// Creates new empty winning regions.
+    pub fn new() -> Self {
         Self {
             even: Region::new(),
             odd: Region::new(),
         }
     }
 
-    fn with_capacity(n: usize) -> Self {
+    /// Creates new empty winning regions with capacity for `n` nodes.
+    pub fn with_capacity(n: usize) -> Self {
         Self {
             even: Region::with_capacity(n),
             odd: Region::with_capacity(n),
         }
     }
 
-    fn of(self, player: Player) -> Region {
+    /// Returns the winning region of the given player.
+    pub fn of(self, player: Player) -> Region {
         match player {
             Player::Even => self.even,
             Player::Odd => self.odd,
         }
     }
+
+    /// Returns the winner of the node with the given index, if it is in the
+    /// winning region of a player.
+    pub fn winner(&self, index: NodeIndex) -> Option<Player> {
+        if self.even[index] {
+            Some(Player::Even)
+        } else if self.odd[index] {
+            Some(Player::Odd)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the region of nodes with a winner.
+    pub fn decided(&self) -> Region {
+        self.even.union(&self.odd)
+    }
+
+    /// Parses the winning regions of a game with `n` nodes from their text format,
+    /// rejecting node indices of `n` or above.
+    pub fn parse(s: &str, n: usize) -> Result<Self, ParseRegionError> {
+        let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+        let mut parse_line = |prefix: &str| -> Result<Region, ParseRegionError> {
+            let region = lines
+                .next()
+                .and_then(|line| line.strip_prefix(prefix))
+                .ok_or_else(|| ParseRegionError(format!("missing region of {} player", prefix)))?;
+            Region::parse(region, n)
+        };
+        let even = parse_line("even")?;
+        let odd = parse_line("odd")?;
+        Ok(Self { even, odd })
+    }
+}
+
+impl fmt::Display for WinningRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "even {}", self.even)?;
+        writeln!(f, "odd {}", self.odd)
+    }
 }
 
 impl Index<Player> for WinningRegion {
@@ -108,6 +359,75 @@ impl IndexMut<Player> for WinningRegion {
     }
 }
 
+/// Metrics of a parity game algorithm, which measure the work done by the
+/// algorithm independently of the running time.
+///
+/// Not every algorithm fills every metric: the iterations count the fixpoint
+/// iterations of FPI, the recursive calls of Zielonka's algorithm and the
+/// improvement steps of strategy iteration, and the other metrics stay zero
+/// for algorithms without attractor computations or recursion.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AlgorithmStats {
+    iterations: usize,
+    attractors: usize,
+    max_depth: usize,
+    strategy_edges: usize,
+}
+
+impl AlgorithmStats {
+    /// Returns the number of iterations of the algorithm.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Returns the number of attractor computations.
+    pub fn attractors(&self) -> usize {
+        self.attractors
+    }
+
+    /// Returns the maximal recursion depth.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Returns the number of edges of all computed strategies.
+    pub fn strategy_edges(&self) -> usize {
+        self.strategy_edges
+    }
+
+    /// Adds the metrics of another run of an algorithm, where the recursion depth
+    /// is the maximum of both runs.
+    pub fn add(&mut self, other: &Self) {
+        self.iterations += other.iterations;
+        self.attractors += other.attractors;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.strategy_edges += other.strategy_edges;
+    }
+
+    /// Adds the edges of the given strategy to the strategy edges.
+    pub(crate) fn add_strategy(&mut self, strategy: Option<&Strategy>) {
+        if let Some(strategy) = strategy {
+            self.strategy_edges += strategy
+                .iter()
+                .map(|(_, successors)| successors.len())
+                .sum::<usize>();
+        }
+    }
+}
+
+impl fmt::Display for AlgorithmStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "iterations: {}, attractors: {}, depth: {}, strategy edges: {}",
+            self.iterations(),
+            self.attractors(),
+            self.max_depth(),
+            self.strategy_edges(),
+        )
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct SolvingStats {
     nodes: usize,
@@ -116,6 +436,8 @@ pub struct SolvingStats {
     time: Duration,
     time_inner_solver: Duration,
     time_strategy: Duration,
+    skipped_solves: usize,
+    algorithm: AlgorithmStats,
 }
 
 impl SolvingStats {
@@ -142,19 +464,79 @@ impl SolvingStats {
     pub fn time_strategy(&self) -> Duration {
         self.time_strategy
     }
+
+    /// Returns the number of calls to the inner solver that were skipped as the
+    /// initial node was already decided.
+    pub fn skipped_solves(&self) -> usize {
+        self.skipped_solves
+    }
+
+    pub fn algorithm(&self) -> &AlgorithmStats {
+        &self.algorithm
+    }
 }
 
 impl fmt::Display for SolvingStats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "|V+B|: {}, |W_even|: {}, |W_odd|: {}, solver time: {:.2}, inner solver time: {:.2}, strategy solver time: {:.2}",
+            "|V+B|: {}, |W_even|: {}, |W_odd|: {}, solver time: {:.2}, inner solver time: {:.2}, strategy solver time: {:.2}, skipped solves: {}, {}",
             self.nodes(),
             self.nodes_won_even(),
             self.nodes_won_odd(),
             self.time().as_secs_f32(),
             self.time_inner_solver().as_secs_f32(),
             self.time_strategy().as_secs_f32(),
+            self.skipped_solves(),
+            self.algorithm(),
         )
     }
 }
+
+/// Tests for strategies and winning regions.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that strategies are written and parsed in their text format.
+    #[test]
+    fn test_strategy_format() {
+        let mut strategy = Strategy::with_nodes(4);
+        strategy[0].push(1);
+        strategy[2].extend(&[0, 3]);
+        let text = strategy.to_string();
+        assert_eq!(text, "strategy 4;\n0 1;\n2 0,3;\n");
+        assert_eq!(text.parse::<Strategy>().unwrap(), strategy);
+        assert_eq!(strategy.domain(), Region::parse("{ 0 2 }", 4).unwrap());
+        assert!(!strategy.is_deterministic());
+
+        strategy.restrict_to(&Region::parse("{ 0 }", 4).unwrap());
+        assert!(strategy.is_deterministic());
+        assert_eq!(strategy.iter().collect::<Vec<_>>(), vec![(0, &[1][..])]);
+
+        assert!("strategy 2;\n0 2;".parse::<Strategy>().is_err());
+        assert!("strategy 2;\n0 1;\n0 1;".parse::<Strategy>().is_err());
+        assert!("0 1;".parse::<Strategy>().is_err());
+
+        let sparse: Strategy = format!("strategy {};\n1 0;", usize::MAX).parse().unwrap();
+        assert_eq!(sparse.num_nodes(), usize::MAX);
+        assert_eq!(sparse.iter().collect::<Vec<_>>(), vec![(1, &[0][..])]);
+        assert!(sparse[1000].is_empty());
+    }
+
+    /// Test that winning regions are written and parsed in their text format.
+    #[test]
+    fn test_winning_region_format() {
+        let mut winning = WinningRegion::with_capacity(3);
+        winning[Player::Even].insert(0);
+        winning[Player::Odd].insert(2);
+        let text = winning.to_string();
+        assert_eq!(text, "even { 0 }\nodd { 2 }\n");
+        assert_eq!(WinningRegion::parse(&text, 3).unwrap(), winning);
+        assert_eq!(winning.winner(0), Some(Player::Even));
+        assert_eq!(winning.winner(1), None);
+        assert_eq!(winning.decided(), Region::parse("{ 0 2 }", 3).unwrap());
+        assert!(WinningRegion::parse("odd { 2 }\neven { 0 }", 3).is_err());
+        assert!(WinningRegion::parse(&text, 2).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use fs_err as fs;
+use log::{debug, warn};
+
+use crate::parity::game::{Game, Node, NodeIndex, Player, Region};
+use crate::parity::solver::{AlgorithmStats, FpiSolver, ParityGameSolver, Strategy};
+
+/// Counter to obtain unique names for temporary files.
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary file with a unique name, which is removed when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(extension: &str) -> Self {
+        Self(std::env::temp_dir().join(format!(
+            "strix-oink-{}-{}.{}",
+            std::process::id(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed),
+            extension
+        )))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // the file does not exist if it was not written
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn invalid_data<E: std::fmt::Display>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+/// Writes the game without the disabled nodes to the given path in the PGSolver format.
+///
+/// Edges to disabled nodes are removed, and nodes without any remaining successor
+/// are connected to a sink that is losing for the owner of the node.
+///
+/// Returns the indices of the written nodes, where the position in the returned vector
+/// corresponds to the index of the node in the written file.
+fn write_game<'a, G: Game<'a>>(
+    game: &'a G,
+    disabled: &Region,
+    path: &Path,
+) -> io::Result<Vec<NodeIndex>> {
+    let nodes: Vec<_> = game.nodes().filter(|&i| !disabled[i]).collect();
+    let mut index = vec![0; game.num_nodes()];
+    for (k, &i) in nodes.iter().enumerate() {
+        index[i] = k;
+    }
+    let sink_even = nodes.len();
+    let sink_odd = nodes.len() + 1;
+
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+    // the header contains the highest identifier of a node, which is the odd sink
+    writeln!(writer, "parity {};", sink_odd)?;
+    for (k, &i) in nodes.iter().enumerate() {
+        let node = &game[i];
+        write!(
+            writer,
+            "{} {} {} ",
+            k,
+            node.color(),
+            u32::from(node.owner())
+        )?;
+        let mut successors = node
+            .successors()
+            .iter()
+            .filter(|&&j| !disabled[j])
+            .map(|&j| index[j])
+            .peekable();
+        if successors.peek().is_none() {
+            let sink = match node.owner() {
+                Player::Even => sink_even,
+                Player::Odd => sink_odd,
+            };
+            write!(writer, "{}", sink)?;
+        }
+        for (l, j) in successors.enumerate() {
+            if l > 0 {
+                write!(writer, ",")?;
+            }
+            write!(writer, "{}", j)?;
+        }
+        writeln!(writer, ";")?;
+    }
+    writeln!(writer, "{} 1 1 {};", sink_even, sink_even)?;
+    writeln!(writer, "{} 0 0 {};", sink_odd, sink_odd)?;
+    writer.flush()?;
+    Ok(nodes)
+}
+
+/// Reads a solution in the format written by Oink from the given path
+/// for a game previously written with [`write_game`].
+fn read_solution<'a, G: Game<'a>>(
+    game: &'a G,
+    nodes: &[NodeIndex],
+    path: &Path,
+    player: Player,
+    compute_strategy: bool,
+) -> io::Result<(Region, Option<Strategy>)> {
+    let mut winning_region = Region::with_capacity(game.num_nodes());
+    let mut strategy = compute_strategy.then(|| Strategy::empty(game));
+
+    for line in BufReader::new(fs::File::open(path)?).lines() {
+        let line = line?;
+        let line = line.trim().trim_end_matches(';');
+        if line.is_empty() || line.starts_with("paritysol") {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let k: usize = parts
+            .next()
+            .ok_or_else(|| invalid_data("missing node in solution"))?
+            .parse()
+            .map_err(invalid_data)?;
+        let winner: u32 = parts
+            .next()
+            .ok_or_else(|| invalid_data("missing winner in solution"))?
+            .parse()
+            .map_err(invalid_data)?;
+        let target = parts
+            .next()
+            .map(|t| t.parse::<usize>().map_err(invalid_data))
+            .transpose()?;
+
+        // ignore the sinks
+        if k >= nodes.len() || winner != u32::from(player) {
+            continue;
+        }
+        let i = nodes[k];
+        winning_region.insert(i);
+        if let (Some(strat), Some(t)) = (strategy.as_mut(), target) {
+            if t < nodes.len() {
+                strat[i].push(nodes[t]);
+            }
+        }
+    }
+    Ok((winning_region, strategy))
+}
+
+/// A parity game solver that invokes the external solvers of
+/// [Oink](https://github.com/trolando/oink).
+///
+/// If Oink is not installed or fails, the solver falls back to the
+/// internal FPI solver.
+pub(crate) struct OinkSolver {
+    solver: Option<String>,
+    available: bool,
+    fallback: FpiSolver,
+    stats: AlgorithmStats,
+}
+
+impl OinkSolver {
+    /// Creates a new solver using the given Oink solver, or the default
+    /// solver of Oink if no solver is given.
+    pub(crate) fn new(solver: Option<String>) -> Self {
+        Self {
+            solver,
+            available: true,
+            fallback: FpiSolver::new(),
+            stats: AlgorithmStats::default(),
+        }
+    }
+
+    /// Solves the game with Oink, where the solver is no longer used if Oink is
+    /// not installed.
+    fn solve_external<'a, G: Game<'a>>(
+        &mut self,
+        game: &'a G,
+        disabled: &Region,
+        player: Player,
+        compute_strategy: bool,
+    ) -> io::Result<(Region, Option<Strategy>)> {
+        let game_file = TempFile::new("pg");
+        let solution_file = TempFile::new("sol");
+
+        let nodes = write_game(game, disabled, game_file.path())?;
+        let mut command = Command::new("oink");
+        if let Some(solver) = &self.solver {
+            command.arg(format!("--{}", solver));
+        }
+        debug!("Invoking Oink on game with {} nodes", nodes.len());
+        let spawned = command
+            .arg(game_file.path())
+            .arg(solution_file.path())
+            .stdout(Stdio::null())
+            .status();
+        let status = match spawned {
+            Ok(status) => status,
+            Err(err) => {
+                // other failures may be transient, so Oink is invoked again for later games
+                if err.kind() == io::ErrorKind::NotFound {
+                    self.available = false;
+                }
+                return Err(err);
+            }
+        };
+        if status.success() {
+            read_solution(game, &nodes, solution_file.path(), player, compute_strategy)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("oink exited with {}", status),
+            ))
+        }
+    }
+}
+
+impl ParityGameSolver for OinkSolver {
+    fn solve<'a, G: Game<'a>>(
+        &mut self,
+        game: &'a G,
+        disabled: &Region,
+        player: Player,
+        compute_strategy: bool,
+    ) -> (Region, Option<Strategy>) {
+        if self.available {
+            match self.solve_external(game, disabled, player, compute_strategy) {
+                Ok(result) => {
+                    self.stats.add_strategy(result.1.as_ref());
+                    return result;
+                }
+                Err(e) => warn!("Could not solve game with Oink, falling back to FPI: {}", e),
+            }
+        }
+        self.fallback
+            .solve(game, disabled, player, compute_strategy)
+    }
+
+    /// Returns the strategy edges of the solutions of Oink, together with the
+    /// metrics of the fallback solver, as Oink does not report its metrics.
+    fn algorithm_stats(&self) -> AlgorithmStats {
+        let mut stats = self.stats;
+        stats.add(&self.fallback.algorithm_stats());
+        stats
+    }
+}
+
+/// Tests for the interface to Oink.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parity::game::LabelledGame;
+
+    /// Test that games are written without disabled nodes and with sinks for
+    /// deadlocks, and that solutions are read back for the original nodes.
+    #[test]
+    fn test_write_game_read_solution() {
+        // nodes 3 and 4 of the even and odd player are deadlocks without node 1
+        let game: LabelledGame<NodeIndex> =
+            "0 2 0 1,2;\n1 1 1 0;\n2 0 1 3,4;\n3 1 0 1;\n4 0 1 1;\n"
+                .parse()
+                .unwrap();
+        let mut disabled = Region::with_capacity(5);
+        disabled.insert(1);
+
+        let game_file = TempFile::new("pg");
+        let nodes = write_game(&game, &disabled, game_file.path()).unwrap();
+        assert_eq!(nodes, vec![0, 2, 3, 4]);
+        assert_eq!(
+            fs::read_to_string(game_file.path()).unwrap(),
+            "parity 5;\n0 2 0 1;\n1 0 1 2,3;\n2 1 0 4;\n3 0 1 5;\n4 1 1 4;\n5 0 0 5;\n"
+        );
+
+        let solution_file = TempFile::new("sol");
+        fs::write(
+            solution_file.path(),
+            "paritysol 5;\n0 1;\n1 1 2;\n2 1;\n3 0;\n4 1 4;\n5 0 5;\n",
+        )
+        .unwrap();
+        let (odd, odd_strategy) =
+            read_solution(&game, &nodes, solution_file.path(), Player::Odd, true).unwrap();
+        assert_eq!(odd.nodes().collect::<Vec<_>>(), vec![0, 2, 3]);
+        let odd_strategy = odd_strategy.unwrap();
+        assert_eq!(odd_strategy[2], vec![3]);
+        assert!(odd_strategy[0].is_empty() && odd_strategy[3].is_empty());
+        let (even, even_strategy) =
+            read_solution(&game, &nodes, solution_file.path(), Player::Even, false).unwrap();
+        assert_eq!(even.nodes().collect::<Vec<_>>(), vec![4]);
+        assert!(even_strategy.is_none());
+
+        let path = game_file.path().to_path_buf();
+        drop(game_file);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::parity::game::{Game, Node, Player, Region};
+use crate::parity::solver::WinningRegion;
+use crate::parity::Parity;
+
+/// Statistics on the nodes decided by preprocessing a parity game.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PreprocessingStats {
+    self_loops: usize,
+    cycles: usize,
+    attracted: usize,
+    time: Duration,
+}
+
+impl PreprocessingStats {
+    /// Returns the number of nodes decided by a forced self-loop.
+    pub(crate) fn self_loops(&self) -> usize {
+        self.self_loops
+    }
+
+    /// Returns the number of nodes decided as part of a cycle of nodes
+    /// with a single successor.
+    pub(crate) fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// Returns the number of nodes decided by attraction to decided nodes.
+    pub(crate) fn attracted(&self) -> usize {
+        self.attracted
+    }
+
+    /// Returns the total number of nodes decided by preprocessing.
+    pub(crate) fn decided(&self) -> usize {
+        self.self_loops + self.cycles + self.attracted
+    }
+
+    /// Returns the time spent on preprocessing.
+    pub(crate) fn time(&self) -> Duration {
+        self.time
+    }
+}
+
+impl fmt::Display for PreprocessingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "decided: {} (self-loops: {}, cycles: {}, attracted: {}), time: {:.2}",
+            self.decided(),
+            self.self_loops(),
+            self.cycles(),
+            self.attracted(),
+            self.time().as_secs_f32(),
+        )
+    }
+}
+
+/// Extends the winning regions with nodes of the game that are decided by cheap
+/// reductions, and records the number of newly decided nodes in the statistics.
+///
+/// A node is decided if it has a self-loop that is either its only successor or
+/// chosen by its owner for a color of the owner's parity, or if it lies on a cycle
+/// of nodes with a single successor, which is won by the parity of the maximal
+/// color on the cycle. Afterwards, the winning regions are extended with their
+/// attractors.
+///
+/// The border is never decided, and as successors of nodes outside the border do
+/// not change, the decided nodes remain won if the game is explored further.
+pub(crate) fn preprocess<'a, G: Game<'a>>(
+    game: &'a G,
+    winning: &mut WinningRegion,
+    stats: &mut PreprocessingStats,
+) {
+    let start = Instant::now();
+    let n = game.num_nodes();
+    for &player in &Player::PLAYERS {
+        winning[player].grow(n);
+    }
+    let mut decided = winning.decided().union(game.border());
+
+    // forced self-loops
+    for i in decided.complement(n).nodes() {
+        let node = &game[i];
+        let successors = node.successors();
+        let winner = Player::from(node.parity());
+        if successors.contains(&i) && (successors.len() == 1 || node.owner() == winner) {
+            winning[winner].insert(i);
+            decided.insert(i);
+            stats.self_loops += 1;
+        }
+    }
+
+    // cycles of nodes with a single successor
+    let single = |i| !decided[i] && game[i].successors().len() == 1;
+    let mut visited = Region::with_capacity(n);
+    let mut path = Vec::new();
+    for i in 0..n {
+        if visited[i] || !single(i) {
+            continue;
+        }
+        let mut j = i;
+        while !visited[j] && single(j) {
+            visited.insert(j);
+            path.push(j);
+            j = game[j].successors()[0];
+        }
+        // a cycle is closed if the walk ends at a node of the current path
+        if let Some(pos) = path.iter().position(|&k| k == j) {
+            let cycle = &path[pos..];
+            let color = cycle.iter().map(|&k| game[k].color()).max().unwrap();
+            let winner = Player::from(Parity::of(color));
+            winning[winner].extend(cycle.iter().copied());
+            stats.cycles += cycle.len();
+        }
+        path.clear();
+    }
+
+    // attractors to decided nodes
+    for &player in &Player::PLAYERS {
+        let size = winning[player].size();
+        winning[player].attract_mut(game, player);
+        stats.attracted += winning[player].size() - size;
+    }
+    stats.time += start.elapsed();
+}
+
+/// Tests for preprocessing of parity games.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parity::game::{LabelledGame, NodeIndex};
+
+    /// Test that forced self-loops, single-successor cycles and their attractors
+    /// are decided, while the remaining nodes are left to the solver.
+    #[test]
+    fn test_preprocess() {
+        let game: LabelledGame<NodeIndex> =
+            "0 0 0 1,2;\n1 1 1 1,0;\n2 2 1 3;\n3 5 0 4;\n4 4 0 2;\n5 3 0 5,6;\n6 6 0 5;\n"
+                .parse()
+                .unwrap();
+        let mut winning = WinningRegion::new();
+        let mut stats = PreprocessingStats::default();
+        preprocess(&game, &mut winning, &mut stats);
+
+        // node 1 is an odd self-loop of the odd player, nodes 2-4 are a cycle
+        // with maximal color 5, and node 0 is attracted by the odd player
+        assert_eq!(
+            winning[Player::Odd],
+            Region::parse("{ 0 1 2 3 4 }", 5).unwrap()
+        );
+        assert_eq!(winning[Player::Even], Region::new());
+        assert_eq!(stats.self_loops(), 1);
+        assert_eq!(stats.cycles(), 3);
+        assert_eq!(stats.attracted(), 1);
+        assert_eq!(stats.decided(), 5);
+    }
+}
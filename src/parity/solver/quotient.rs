@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::parity::game::{Game, LabelledGame, Node, NodeIndex, Player, Region};
+use crate::parity::solver::Strategy;
+
+/// Statistics on the nodes merged by solving the quotient of a parity game.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MergingStats {
+    nodes: usize,
+    classes: usize,
+    time: Duration,
+}
+
+impl MergingStats {
+    /// Returns the number of nodes of the game in the last quotient.
+    pub(crate) fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Returns the number of classes of equivalent nodes in the last quotient.
+    pub(crate) fn classes(&self) -> usize {
+        self.classes
+    }
+
+    /// Returns the time spent on computing quotients.
+    pub(crate) fn time(&self) -> Duration {
+        self.time
+    }
+}
+
+impl fmt::Display for MergingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "nodes: {}, classes: {}, time: {:.2}",
+            self.nodes(),
+            self.classes(),
+            self.time().as_secs_f32(),
+        )
+    }
+}
+
+/// The signature of a node before refinement by the classes of its successors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Signature {
+    /// All disabled nodes form a single class.
+    Disabled,
+    /// Enabled nodes in the border are never merged.
+    Border(NodeIndex),
+    /// Enabled explored nodes with their owner and color.
+    Explored(u32, usize),
+}
+
+/// The quotient of a parity game without some disabled nodes under bisimulation,
+/// where nodes are equivalent if they have the same owner and color and their
+/// successors lie in the same classes of equivalent nodes.
+///
+/// Equivalent nodes are won by the same player, so solving the quotient and
+/// lifting the winning region and the strategy decides the original game.
+/// Equivalent nodes arise when nodes with different labels, e.g. for different
+/// paths in the edge tree of an automaton state, lead to the same successors.
+pub(crate) struct Quotient {
+    /// The class of each node of the original game.
+    classes: Vec<NodeIndex>,
+    /// The game on the classes, where each node is labelled with its class.
+    game: LabelledGame<NodeIndex>,
+    /// The disabled nodes of the quotient.
+    disabled: Region,
+}
+
+impl Quotient {
+    /// Computes the quotient of the game without the disabled nodes by refining
+    /// the classes of nodes with the classes of their successors until the number
+    /// of classes does not change, and records its size in the statistics.
+    pub(crate) fn new<'a, G: Game<'a>>(
+        game: &'a G,
+        disabled: &Region,
+        stats: &mut MergingStats,
+    ) -> Self {
+        let start = Instant::now();
+        let n = game.num_nodes();
+        let mut signatures = HashMap::new();
+        let mut classes: Vec<_> = game
+            .nodes()
+            .map(|i| {
+                let signature = if disabled[i] {
+                    Signature::Disabled
+                } else if game.border()[i] {
+                    Signature::Border(i)
+                } else {
+                    let node = &game[i];
+                    Signature::Explored(u32::from(node.owner()), node.color())
+                };
+                let next = signatures.len();
+                *signatures.entry(signature).or_insert(next)
+            })
+            .collect();
+        let mut num_classes = signatures.len();
+        loop {
+            let mut refined = HashMap::with_capacity(num_classes);
+            let new_classes: Vec<_> = game
+                .nodes()
+                .map(|i| {
+                    let mut successors: Vec<_> = if disabled[i] {
+                        Vec::new()
+                    } else {
+                        game[i].successors().iter().map(|&j| classes[j]).collect()
+                    };
+                    successors.sort_unstable();
+                    successors.dedup();
+                    let next = refined.len();
+                    *refined.entry((classes[i], successors)).or_insert(next)
+                })
+                .collect();
+            classes = new_classes;
+            if refined.len() == num_classes {
+                break;
+            }
+            num_classes = refined.len();
+        }
+
+        let mut quotient = LabelledGame::default();
+        let mut quotient_disabled = Region::with_capacity(num_classes);
+        let mut representatives = vec![None; num_classes];
+        for i in game.nodes() {
+            if representatives[classes[i]].is_none() {
+                representatives[classes[i]] = Some(i);
+                quotient.add_border_node(classes[i]);
+            }
+        }
+        for (class, representative) in representatives.into_iter().enumerate() {
+            let i = representative.unwrap();
+            if disabled[i] {
+                // disabled nodes are ignored by the solvers
+                quotient_disabled.insert(class);
+                quotient.update_node(class, Player::Even, 0);
+                quotient.add_edge(class, class);
+            } else if !game.border()[i] {
+                let node = &game[i];
+                quotient.update_node(class, node.owner(), node.color());
+                let mut successors: Vec<_> =
+                    node.successors().iter().map(|&j| classes[j]).collect();
+                successors.sort_unstable();
+                successors.dedup();
+                for successor in successors {
+                    quotient.add_edge(class, successor);
+                }
+            }
+        }
+        if n > 0 {
+            quotient.set_initial_node(classes[game.initial_node()]);
+        }
+
+        stats.nodes = n;
+        stats.classes = num_classes;
+        stats.time += start.elapsed();
+        Self {
+            classes,
+            game: quotient,
+            disabled: quotient_disabled,
+        }
+    }
+
+    /// Returns the game on the classes of equivalent nodes.
+    pub(crate) fn game(&self) -> &LabelledGame<NodeIndex> {
+        &self.game
+    }
+
+    /// Returns the disabled nodes of the game on the classes.
+    pub(crate) fn disabled(&self) -> &Region {
+        &self.disabled
+    }
+
+    /// Returns the class of the node of the original game with the given index.
+    pub(crate) fn class(&self, index: NodeIndex) -> NodeIndex {
+        self.classes[index]
+    }
+
+    /// Returns the region of nodes of the original game whose class is in the given region.
+    pub(crate) fn lift_region(&self, region: &Region) -> Region {
+        let mut lifted = Region::with_capacity(self.classes.len());
+        for (i, &class) in self.classes.iter().enumerate() {
+            if region[class] {
+                lifted.insert(i);
+            }
+        }
+        lifted
+    }
+
+    /// Returns the strategy for the original game that chooses the successors
+    /// of a node in the classes chosen by the given strategy for its class.
+    pub(crate) fn lift_strategy<'a, G: Game<'a>>(
+        &self,
+        game: &'a G,
+        strategy: &Strategy,
+    ) -> Strategy {
+        let mut lifted = Strategy::with_nodes(self.classes.len());
+        for i in game.nodes() {
+            let chosen = &strategy[self.classes[i]];
+            if !chosen.is_empty() && !self.disabled[self.classes[i]] {
+                lifted[i] = game[i]
+                    .successors()
+                    .iter()
+                    .copied()
+                    .filter(|&j| chosen.contains(&self.classes[j]))
+                    .collect();
+            }
+        }
+        lifted
+    }
+}
+
+/// Tests for the quotient of parity games.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parity::solver::{ParityGameSolver, ZlkSolver};
+
+    /// Test that nodes with the same owner, color and successor classes are merged,
+    /// and that the solution of the quotient is lifted to the original game.
+    #[test]
+    fn test_quotient() {
+        // nodes 1 and 2 are equivalent, and so are nodes 3 and 4
+        let game: LabelledGame<NodeIndex> =
+            "0 0 1 1,2;\n1 1 0 3;\n2 1 0 4;\n3 2 0 3;\n4 2 0 4;\n5 1 0 5;\n"
+                .parse()
+                .unwrap();
+        let mut stats = MergingStats::default();
+        let quotient = Quotient::new(&game, &Region::with_capacity(6), &mut stats);
+        assert_eq!(stats.nodes(), 6);
+        assert_eq!(stats.classes(), 4);
+        assert_eq!(quotient.classes[1], quotient.classes[2]);
+        assert_eq!(quotient.classes[3], quotient.classes[4]);
+        assert_ne!(quotient.classes[0], quotient.classes[5]);
+
+        let (winning, strategy) =
+            ZlkSolver::new().solve(quotient.game(), quotient.disabled(), Player::Even, true);
+        let winning = quotient.lift_region(&winning);
+        assert_eq!(winning, Region::parse("{ 0 1 2 3 4 }", 5).unwrap());
+        let strategy = quotient.lift_strategy(&game, &strategy.unwrap());
+        assert_eq!(strategy[3], vec![3]);
+        assert_eq!(strategy[4], vec![4]);
+    }
+
+    /// Test that disabled nodes are not merged with enabled nodes.
+    #[test]
+    fn test_quotient_disabled() {
+        let game: LabelledGame<NodeIndex> = "0 0 0 1,2;\n1 1 0 1;\n2 1 0 2;\n".parse().unwrap();
+        let mut disabled = Region::with_capacity(3);
+        disabled.insert(2);
+        let quotient = Quotient::new(&game, &disabled, &mut MergingStats::default());
+        assert_ne!(quotient.classes[1], quotient.classes[2]);
+        assert!(quotient.disabled()[quotient.classes[2]]);
+        assert!(!quotient.disabled()[quotient.classes[1]]);
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::VecDeque;
+
+use crate::parity::game::{Game, Node, NodeIndex, Player, Region};
+use crate::parity::solver::{AlgorithmStats, ParityGameSolver, Strategy};
+use crate::parity::Parity;
+
+/// A solver that solves safety games by a single attractor computation, and
+/// delegates all other games to an inner solver.
+///
+/// A game is a safety game if every play reaching a node with an odd color
+/// afterwards visits odd colors infinitely often. The odd player then wins exactly
+/// from its attractor to the nodes reachable from odd colors, and the even player
+/// wins by avoiding them. The games of safety formulas usually have this form, where
+/// odd colors only occur on the edges to the rejecting sink of the automaton.
+pub(crate) struct SafetySolver<S> {
+    solver: S,
+    enabled: bool,
+    stats: AlgorithmStats,
+}
+
+impl<S: ParityGameSolver> SafetySolver<S> {
+    /// Creates a new solver with the given inner solver, where safety games are
+    /// only recognized if `enabled` is true.
+    pub(crate) fn new(solver: S, enabled: bool) -> Self {
+        Self {
+            solver,
+            enabled,
+            stats: AlgorithmStats::default(),
+        }
+    }
+
+    /// Returns the enabled nodes reachable from nodes with an odd color if the game
+    /// is a safety game, and `None` otherwise.
+    ///
+    /// The game is a safety game if all reachable nodes have an odd color or color
+    /// zero, and the reachable nodes with color zero do not form a cycle.
+    fn unsafe_nodes<'a, G: Game<'a>>(game: &'a G, disabled: &Region) -> Option<Region> {
+        let n = game.num_nodes();
+        let mut unsafe_nodes = Region::with_capacity(n);
+        let mut queue: VecDeque<NodeIndex> = game
+            .nodes()
+            .filter(|&i| !disabled[i] && game[i].parity() == Parity::Odd)
+            .collect();
+        unsafe_nodes.extend(queue.iter().copied());
+        while let Some(i) = queue.pop_front() {
+            for &j in game[i].successors() {
+                if disabled[j] || unsafe_nodes[j] {
+                    continue;
+                }
+                if game[j].color() != 0 && game[j].parity() == Parity::Even {
+                    return None;
+                }
+                unsafe_nodes.insert(j);
+                queue.push_back(j);
+            }
+        }
+
+        // remove nodes with color zero without remaining successors of color zero,
+        // which removes all of them unless they contain a cycle
+        let zero = |i: NodeIndex| !disabled[i] && unsafe_nodes[i] && game[i].color() == 0;
+        let mut count = vec![0; n];
+        let mut remaining = 0;
+        for i in unsafe_nodes.nodes().filter(|&i| zero(i)) {
+            count[i] = game[i].successors().iter().filter(|&&j| zero(j)).count();
+            remaining += 1;
+            if count[i] == 0 {
+                queue.push_back(i);
+            }
+        }
+        while let Some(i) = queue.pop_front() {
+            remaining -= 1;
+            for &j in game[i].predecessors().iter().filter(|&&j| zero(j)) {
+                count[j] -= 1;
+                if count[j] == 0 {
+                    queue.push_back(j);
+                }
+            }
+        }
+        if remaining == 0 {
+            Some(unsafe_nodes)
+        } else {
+            None
+        }
+    }
+
+    /// Solves the safety game with the given unsafe nodes, and returns the winning
+    /// region of the given player together with its strategy if requested.
+    ///
+    /// The even player chooses all successors within its winning region, while
+    /// the odd player chooses a successor that is attracted earlier to the unsafe
+    /// nodes, or any successor within the unsafe nodes.
+    fn solve_safety<'a, G: Game<'a>>(
+        &mut self,
+        game: &'a G,
+        disabled: &Region,
+        mut losing: Region,
+        player: Player,
+        compute_strategy: bool,
+    ) -> (Region, Option<Strategy>) {
+        let n = game.num_nodes();
+        let mut strategy = compute_strategy.then(|| Strategy::with_nodes(n));
+        let mut odd_strategy = match &mut strategy {
+            Some(strategy) if player == Player::Odd => Some(strategy),
+            _ => None,
+        };
+        if let Some(strategy) = &mut odd_strategy {
+            for i in losing.nodes().filter(|&i| game[i].owner() == Player::Odd) {
+                strategy[i].extend(game[i].successors().iter().filter(|&&j| !disabled[j]));
+            }
+        }
+
+        // attractor of the odd player to the unsafe nodes
+        let mut count: Vec<isize> = vec![-1; n];
+        let mut queue: VecDeque<NodeIndex> = losing.nodes().collect();
+        while let Some(i) = queue.pop_front() {
+            for &j in game[i].predecessors() {
+                if disabled[j] || losing[j] {
+                    continue;
+                }
+                let controllable = game[j].owner() == Player::Odd;
+                if !controllable {
+                    if count[j] == -1 {
+                        count[j] = game[j]
+                            .successors()
+                            .iter()
+                            .filter(|&&k| !disabled[k])
+                            .count() as isize;
+                    }
+                    count[j] -= 1;
+                }
+                if controllable || count[j] == 0 {
+                    if let (true, Some(strategy)) = (controllable, &mut odd_strategy) {
+                        strategy[j].push(i);
+                    }
+                    losing.insert(j);
+                    queue.push_back(j);
+                }
+            }
+        }
+        self.stats.attractors += 1;
+
+        let winning = match player {
+            Player::Odd => losing,
+            Player::Even => {
+                let mut safe = Region::with_capacity(n);
+                safe.extend(game.nodes().filter(|&i| !disabled[i] && !losing[i]));
+                if let Some(strategy) = strategy.as_mut() {
+                    for i in safe.nodes().filter(|&i| game[i].owner() == Player::Even) {
+                        strategy[i].extend(game[i].successors().iter().filter(|&&j| safe[j]));
+                    }
+                }
+                safe
+            }
+        };
+        self.stats.add_strategy(strategy.as_ref());
+        (winning, strategy)
+    }
+}
+
+impl<S: ParityGameSolver> ParityGameSolver for SafetySolver<S> {
+    fn solve<'a, G: Game<'a>>(
+        &mut self,
+        game: &'a G,
+        disabled: &Region,
+        player: Player,
+        compute_strategy: bool,
+    ) -> (Region, Option<Strategy>) {
+        let unsafe_nodes = if self.enabled {
+            Self::unsafe_nodes(game, disabled)
+        } else {
+            None
+        };
+        match unsafe_nodes {
+            Some(unsafe_nodes) => {
+                self.solve_safety(game, disabled, unsafe_nodes, player, compute_strategy)
+            }
+            None => self.solver.solve(game, disabled, player, compute_strategy),
+        }
+    }
+
+    fn algorithm_stats(&self) -> AlgorithmStats {
+        let mut stats = self.solver.algorithm_stats();
+        stats.add(&self.stats);
+        stats
+    }
+}
+
+/// Tests for solving safety games.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parity::game::LabelledGame;
+    use crate::parity::solver::FpiSolver;
+
+    /// Test that safety games are solved by the attractor to the unsafe nodes.
+    #[test]
+    fn test_safety_game() {
+        // the odd player can force a visit to the unsafe sink of nodes 4 and 5 from
+        // nodes 2 and 3, while the even player stays at nodes 0 and 1
+        let game: LabelledGame<NodeIndex> =
+            "0 0 0 1,2;\n1 2 1 0;\n2 0 1 3,0;\n3 0 0 4;\n4 1 1 5;\n5 0 0 4;\n"
+                .parse()
+                .unwrap();
+        let disabled = Region::with_capacity(6);
+        let mut solver = SafetySolver::new(FpiSolver::new(), true);
+        let (even, even_strategy) = solver.solve(&game, &disabled, Player::Even, true);
+        let (odd, odd_strategy) = solver.solve(&game, &disabled, Player::Odd, true);
+        assert_eq!(even.nodes().collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(odd.nodes().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+        assert_eq!(even_strategy.unwrap()[0], vec![1]);
+        let odd_strategy = odd_strategy.unwrap();
+        assert_eq!(odd_strategy[2], vec![3]);
+        assert_eq!(odd_strategy[4], vec![5]);
+        assert_eq!(solver.algorithm_stats().attractors(), 2);
+        assert_eq!(solver.algorithm_stats().iterations(), 0);
+
+        // the same game without the fast path is solved by the inner solver
+        let mut solver = SafetySolver::new(FpiSolver::new(), false);
+        let (even, _) = solver.solve(&game, &disabled, Player::Even, false);
+        assert_eq!(even.nodes().collect::<Vec<_>>(), vec![0, 1]);
+        assert!(solver.algorithm_stats().iterations() > 0);
+    }
+
+    /// Test that games where plays may avoid odd colors after reaching them are
+    /// delegated to the inner solver.
+    #[test]
+    fn test_non_safety_game() {
+        // the odd player wins by staying on the cycle between nodes 0 and 1 with color 1
+        let game: LabelledGame<NodeIndex> = "0 1 1 1,2;\n1 0 1 0,2;\n2 2 0 2;\n".parse().unwrap();
+        let disabled = Region::with_capacity(3);
+        let mut solver = SafetySolver::new(FpiSolver::new(), true);
+        let (odd, _) = solver.solve(&game, &disabled, Player::Odd, false);
+        assert_eq!(odd.nodes().collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(solver.algorithm_stats().attractors(), 0);
+        assert!(solver.algorithm_stats().iterations() > 0);
+
+        // the even player wins by staying on the cycle between nodes 1 and 2 with color 0
+        let game: LabelledGame<NodeIndex> = "0 1 1 1;\n1 0 0 2;\n2 0 1 1;\n".parse().unwrap();
+        let (even, _) = solver.solve(&game, &disabled, Player::Even, false);
+        assert_eq!(even.nodes().collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(solver.algorithm_stats().attractors(), 0);
+    }
+}
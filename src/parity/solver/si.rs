@@ -6,7 +6,8 @@ use tinyvec::TinyVec;
 use owl::automaton::Color;
 
 use crate::parity::game::{Game, Node, NodeIndex, Player, Region};
-use crate::parity::solver::{ParityGameSolver, Strategy};
+use crate::parity::solver::{AlgorithmStats, FpiSolver, ParityGameSolver, Strategy};
+use crate::parity::Parity;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Valuation {
@@ -110,6 +111,7 @@ struct SiSolverInstance<'a, 'b, 'c, G: Game<'a>> {
     game: &'a G,
     disabled: &'b Region,
     strategy: &'c mut Strategy,
+    iterations: usize,
 }
 
 impl<'a, 'b, 'c, G: Game<'a>> SiSolverInstance<'a, 'b, 'c, G> {
@@ -119,12 +121,14 @@ impl<'a, 'b, 'c, G: Game<'a>> SiSolverInstance<'a, 'b, 'c, G> {
             game,
             disabled,
             strategy: initial_strategy,
+            iterations: 0,
         }
     }
 
-    fn run(mut self, player: Player) -> Region {
+    fn run(&mut self, player: Player) -> Region {
         let mut valuation;
         loop {
+            self.iterations += 1;
             valuation = self.bellman_ford(player);
             if !self.strategy_improvement(player, &valuation) {
                 break;
@@ -265,9 +269,145 @@ impl<'a, 'b, 'c, G: Game<'a>> SiSolverInstance<'a, 'b, 'c, G> {
     }
 }
 
+/// Marker for nodes without index or group.
+const UNDEFINED: usize = usize::MAX;
+
+/// Scratch buffers for computing strongly connected components with Tarjan's algorithm,
+/// which are shared between the searches on different subgraphs of the game.
+struct Components {
+    /// The group of each node, where a search only follows edges within a group.
+    group: Vec<usize>,
+    index: Vec<usize>,
+    lowlink: Vec<usize>,
+    stack: Vec<NodeIndex>,
+    on_stack: Region,
+    calls: Vec<(NodeIndex, usize)>,
+}
+
+impl Components {
+    fn new(n: usize) -> Self {
+        Self {
+            group: vec![UNDEFINED; n],
+            index: vec![UNDEFINED; n],
+            lowlink: vec![UNDEFINED; n],
+            stack: Vec::new(),
+            on_stack: Region::with_capacity(n),
+            calls: Vec::new(),
+        }
+    }
+
+    /// Returns the strongly connected components of the subgraph induced by the given
+    /// nodes, which all belong to the same group, that contain a cycle.
+    fn cycles<'a, G: Game<'a>>(&mut self, game: &'a G, nodes: &[NodeIndex]) -> Vec<Vec<NodeIndex>> {
+        let mut components = Vec::new();
+        let mut next = 0;
+        for &i in nodes {
+            self.index[i] = UNDEFINED;
+        }
+        for &root in nodes {
+            if self.index[root] != UNDEFINED {
+                continue;
+            }
+            self.open(root, &mut next);
+            while let Some((i, pos)) = self.calls.pop() {
+                let successors = game[i].successors();
+                if let Some(&j) = successors.get(pos) {
+                    self.calls.push((i, pos + 1));
+                    if self.group[j] != self.group[i] {
+                        continue;
+                    }
+                    if self.index[j] == UNDEFINED {
+                        self.open(j, &mut next);
+                    } else if self.on_stack[j] {
+                        self.lowlink[i] = self.lowlink[i].min(self.index[j]);
+                    }
+                    continue;
+                }
+                if let Some(&(parent, _)) = self.calls.last() {
+                    self.lowlink[parent] = self.lowlink[parent].min(self.lowlink[i]);
+                }
+                if self.lowlink[i] == self.index[i] {
+                    let start = self.stack.iter().rposition(|&j| j == i).unwrap();
+                    for &j in &self.stack[start..] {
+                        self.on_stack.remove(j);
+                    }
+                    if self.stack.len() - start > 1 || successors.contains(&i) {
+                        components.push(self.stack[start..].to_vec());
+                    }
+                    self.stack.truncate(start);
+                }
+            }
+        }
+        components
+    }
+
+    fn open(&mut self, i: NodeIndex, next: &mut usize) {
+        self.index[i] = *next;
+        self.lowlink[i] = *next;
+        *next += 1;
+        self.stack.push(i);
+        self.on_stack.insert(i);
+        self.calls.push((i, 0));
+    }
+}
+
+/// Returns the nodes on cycles of the game without the disabled nodes that only consist
+/// of nodes where the opponent of the player chooses the successor, and on which the
+/// largest color has the parity of the opponent.
+///
+/// The opponent wins from these nodes by staying on the cycle, which cannot be detected
+/// by the valuations, as they decrease indefinitely on such cycles. These cycles can not
+/// occur in games where the players alternate, but e.g. in random games.
+///
+/// The cycles are found by decomposing the strongly connected components of these nodes:
+/// a component whose largest color has the parity of the opponent consists of such
+/// cycles, while in the other components the cycles are searched again without the nodes
+/// of the largest color. This needs one search for each level of colors.
+fn opponent_cycles<'a, G: Game<'a>>(game: &'a G, disabled: &Region, player: Player) -> Region {
+    let n = game.num_nodes();
+    let mut components = Components::new(n);
+    let nodes: Vec<_> = game
+        .nodes()
+        .filter(|&i| {
+            let node = &game[i];
+            !disabled[i] && node.owner() != player && node.successors().len() > 1
+        })
+        .collect();
+    for &i in &nodes {
+        components.group[i] = 0;
+    }
+    let mut cycles = Region::with_capacity(n);
+    let mut groups = 1;
+    let mut pending = vec![nodes];
+    while let Some(nodes) = pending.pop() {
+        for component in components.cycles(game, &nodes) {
+            let color = component.iter().map(|&i| game[i].color()).max().unwrap();
+            if Player::from(Parity::of(color)) != player {
+                for &i in &component {
+                    cycles.insert(i);
+                }
+            } else {
+                // search the remaining nodes of the component in a new group
+                let remaining: Vec<_> = component
+                    .into_iter()
+                    .filter(|&i| {
+                        let below = game[i].color() < color;
+                        components.group[i] = if below { groups } else { UNDEFINED };
+                        below
+                    })
+                    .collect();
+                groups += 1;
+                pending.push(remaining);
+            }
+        }
+    }
+    cycles
+}
+
 pub(crate) struct SiSolver {
     strat_even: Strategy,
     strat_odd: Strategy,
+    stats: AlgorithmStats,
 }
 
 impl SiSolver {
@@ -275,6 +415,7 @@ impl SiSolver {
         Self {
             strat_even: Strategy::new(),
             strat_odd: Strategy::new(),
+            stats: AlgorithmStats::default(),
         }
     }
 }
@@ -291,8 +432,81 @@ impl ParityGameSolver for SiSolver {
             Player::Even => &mut self.strat_even,
             Player::Odd => &mut self.strat_odd,
         };
-        let solver = SiSolverInstance::new(game, disabled, strategy);
+        // exclude the nodes from which the opponent can force a play into its cycles
+        let mut lost = opponent_cycles(game, disabled, player);
+        lost.attract_mut_without(game, !player, disabled);
+        let disabled = disabled.union(&lost);
+        let mut solver = SiSolverInstance::new(game, &disabled, strategy);
         let winning = solver.run(player);
-        (winning, compute_strategy.then(|| strategy.clone()))
+        self.stats.iterations += solver.iterations;
+        self.stats.attractors += 1;
+        // The non-deterministic strategy may choose any successor with an infinite
+        // valuation, which may close a cycle between won nodes that is losing for the
+        // player. The strategy is thus computed with FPI on the winning region, which
+        // the player wins from all nodes.
+        let strategy = compute_strategy.then(|| {
+            let outside = winning.complement(game.num_nodes());
+            let (_, strategy) = FpiSolver::new().solve(game, &outside, player, true);
+            strategy.expect("no strategy")
+        });
+        self.stats.add_strategy(strategy.as_ref());
+        (winning, strategy)
+    }
+
+    fn algorithm_stats(&self) -> AlgorithmStats {
+        self.stats
+    }
+}
+
+/// Tests for the strategy iteration solver.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parity::game::LabelledGame;
+
+    /// Test that cycles of the opponent are detected and not evaluated indefinitely.
+    #[test]
+    fn test_opponent_cycles() {
+        // the odd player wins by staying on the cycle of its nodes 0 and 1 with
+        // largest color 1
+        let game: LabelledGame<NodeIndex> = "0 1 1 1,2;\n1 0 1 0,2;\n2 2 0 2;\n".parse().unwrap();
+        let disabled = Region::with_capacity(3);
+        let (even, even_strategy) = SiSolver::new().solve(&game, &disabled, Player::Even, true);
+        let (odd, odd_strategy) = SiSolver::new().solve(&game, &disabled, Player::Odd, true);
+        assert_eq!(even.nodes().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(odd.nodes().collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(even_strategy.unwrap()[2], vec![2]);
+        let odd_strategy = odd_strategy.unwrap();
+        assert_eq!(odd_strategy[0], vec![1]);
+        assert_eq!(odd_strategy[1], vec![0]);
+    }
+
+    /// Test that cycles of the opponent are found within components of its nodes whose
+    /// largest color has the parity of the player.
+    #[test]
+    fn test_nested_opponent_cycles() {
+        // all nodes of the odd player form a component with largest color 2, which
+        // contains the cycle between nodes 0 and 1 with largest color 1
+        let game: LabelledGame<NodeIndex> = "0 1 1 1,2;\n1 0 1 0,3;\n2 2 1 0,3;\n3 4 0 3;\n"
+            .parse()
+            .unwrap();
+        let disabled = Region::with_capacity(4);
+        let cycles = opponent_cycles(&game, &disabled, Player::Even);
+        assert_eq!(cycles.nodes().collect::<Vec<_>>(), vec![0, 1]);
+        assert!(opponent_cycles(&game, &disabled, Player::Odd).is_empty());
+    }
+
+    /// Test that the strategy does not close a losing cycle between won nodes.
+    #[test]
+    fn test_strategy_within_winning_region() {
+        // the odd player wins from all nodes, but loses when choosing node 0 at node 4
+        let game: LabelledGame<NodeIndex> =
+            "0 0 0 1,2;\n1 2 1 3,4;\n2 1 1 1,2;\n3 3 0 1;\n4 2 1 0,3;\n"
+                .parse()
+                .unwrap();
+        let disabled = Region::with_capacity(5);
+        let (odd, strategy) = SiSolver::new().solve(&game, &disabled, Player::Odd, true);
+        assert_eq!(odd.size(), 5);
+        assert_eq!(strategy.unwrap()[4], vec![3]);
     }
 }
@@ -1,38 +1,163 @@
-use crate::parity::game::{Game, Player, Region};
-use crate::parity::solver::{ParityGameSolver, Strategy, WinningRegion};
+use std::collections::VecDeque;
+
+use crate::parity::game::{Game, Node, NodeIndex, Player, Region};
+use crate::parity::solver::{AlgorithmStats, ParityGameSolver, Strategy, WinningRegion};
 use crate::parity::Parity;
 
 use owl::automaton::Color;
 
+/// A call of Zielonka's algorithm on the subgame without the disabled nodes,
+/// which is kept on an explicit stack instead of the call stack, such that
+/// games with long chains of colors do not overflow the stack.
+struct Frame {
+    /// The disabled nodes of the subgame.
+    disabled: Region,
+    /// The largest color in the subgame.
+    color: Color,
+    /// The player with the parity of the largest color in the subgame.
+    player: Player,
+    /// The attractor of the player to the nodes with the largest colors.
+    attractor: Region,
+    /// The nodes with the largest colors in the attractor.
+    targets: Region,
+    /// The subgame whose solution the call is waiting for.
+    stage: Stage,
+}
+
+enum Stage {
+    /// Waiting for the solution of the subgame without the attractor.
+    WithoutAttractor,
+    /// Waiting for the solution of the subgame without the given
+    /// region won by the opponent.
+    WithoutOpponent(Region),
+}
+
 struct ZlkSolverInstance<'a, G> {
     game: &'a G,
+    /// The strategy of the owner of each node, which is only valid for nodes
+    /// won by their owner, if strategies are computed.
+    strategy: Option<Strategy>,
+    /// Regions of finished calls, which are reused to avoid repeated allocations.
+    regions: Vec<Region>,
+    /// The number of successors of each node outside of the current attractor,
+    /// or `-1` if the node has not been reached during the current attractor computation.
+    count: Vec<isize>,
+    /// The nodes with a count different from `-1`, which are reset after each attractor computation.
+    counted: Vec<NodeIndex>,
+    /// The queue of the current attractor computation.
+    queue: VecDeque<NodeIndex>,
+    /// The metrics of the calls, attractors and stack depth.
+    stats: AlgorithmStats,
 }
 
 impl<'a, G: Game<'a>> ZlkSolverInstance<'a, G> {
-    fn new(game: &'a G) -> Self {
-        ZlkSolverInstance { game }
+    fn new(game: &'a G, compute_strategy: bool) -> Self {
+        let n = game.num_nodes();
+        ZlkSolverInstance {
+            game,
+            strategy: compute_strategy.then(|| Strategy::empty(game)),
+            regions: Vec::new(),
+            count: vec![-1; n],
+            counted: Vec::new(),
+            queue: VecDeque::with_capacity(n),
+            stats: AlgorithmStats::default(),
+        }
     }
 
-    fn largest_color(&self, disabled: &Region) -> Option<Color> {
-        (0..self.game.num_colors())
+    /// Returns an empty region, reusing a released region if possible.
+    fn region(&mut self) -> Region {
+        match self.regions.pop() {
+            Some(mut region) => {
+                region.clear();
+                region.grow(self.game.num_nodes());
+                region
+            }
+            None => Region::with_capacity(self.game.num_nodes()),
+        }
+    }
+
+    /// Releases the region for reuse.
+    fn release(&mut self, region: Region) {
+        self.regions.push(region);
+    }
+
+    /// Returns the largest color below the given bound of the nodes that are not disabled.
+    fn largest_color(&self, disabled: &Region, bound: Color) -> Option<Color> {
+        (0..bound)
             .rev()
             .find(|&c| self.game.nodes_with_color(c).any(|i| !disabled[i]))
     }
 
-    fn attractor(&self, disabled: &Region, color: Color, parity: Parity, player: Player) -> Region {
-        let n = self.game.num_nodes();
-        let mut a = Region::with_capacity(n);
-        let mut dis = disabled.clone();
+    /// Extends the region with the attractor for the player in the game without
+    /// the disabled nodes, and sets the strategy for all attracted nodes of the player
+    /// to the successor through which they are attracted.
+    ///
+    /// Returns whether the region changed.
+    fn attract(&mut self, region: &mut Region, player: Player, disabled: &Region) -> bool {
+        let game = self.game;
+        let mut change = false;
+        self.stats.attractors += 1;
+        self.queue.extend(region.nodes());
+        while let Some(i) = self.queue.pop_front() {
+            for &j in game[i].predecessors().iter().filter(|&&j| !disabled[j]) {
+                if !region[j] {
+                    let controllable = player == game[j].owner();
+                    if !controllable {
+                        if self.count[j] == -1 {
+                            self.count[j] = game[j]
+                                .successors()
+                                .iter()
+                                .filter(|&&k| !disabled[k])
+                                .count() as isize;
+                            self.counted.push(j);
+                        }
+                        self.count[j] -= 1;
+                    }
+                    if controllable || self.count[j] == 0 {
+                        if controllable {
+                            if let Some(strategy) = &mut self.strategy {
+                                strategy[j] = vec![i];
+                            }
+                        }
+                        change = true;
+                        region.insert(j);
+                        self.queue.push_back(j);
+                    }
+                }
+            }
+        }
+        for j in self.counted.drain(..) {
+            self.count[j] = -1;
+        }
+        change
+    }
+
+    /// Returns the attractor for the player to the nodes with the largest colors
+    /// up to the given color of the given parity, together with these nodes.
+    fn attractor(
+        &mut self,
+        disabled: &Region,
+        color: Color,
+        parity: Parity,
+        player: Player,
+    ) -> (Region, Region) {
+        let mut a = self.region();
+        let mut targets = self.region();
+        let mut dis = self.region();
+        dis.union_with(disabled);
+        let mut nodes = self.region();
         for c in (0..=color).rev() {
-            let mut nodes = Region::with_capacity(n);
+            nodes.clear();
             let mut empty = true;
-            for i in self.game.nodes_with_color(c).filter(|&i| !disabled[i]) {
+            // nodes already in the attractor are not targets again, as they are disabled
+            for i in self.game.nodes_with_color(c).filter(|&i| !dis[i]) {
                 nodes.insert(i);
                 empty = false;
             }
             if !empty {
                 if Parity::of(c) == parity {
-                    nodes.attract_mut_without(self.game, player, &dis);
+                    targets.union_with(&nodes);
+                    self.attract(&mut nodes, player, &dis);
                     a.union_with(&nodes);
                     dis.union_with(&a);
                 } else {
@@ -40,39 +165,127 @@ impl<'a, G: Game<'a>> ZlkSolverInstance<'a, G> {
                 }
             }
         }
-        a
+        self.release(dis);
+        self.release(nodes);
+        (a, targets)
     }
 
-    fn run(&self, disabled: &Region) -> WinningRegion {
-        match self.largest_color(disabled) {
-            None => WinningRegion::with_capacity(self.game.num_nodes()),
-            Some(color) => {
+    /// Solves the subgame without the disabled nodes.
+    ///
+    /// Each call first solves the subgame without the attractor to the nodes with
+    /// the largest colors. If the opponent of the player with the parity of these colors
+    /// can attract further nodes to its winning region of this subgame, the call solves
+    /// the subgame without the extended winning region of the opponent. The calls on
+    /// subgames are pushed on a stack, and their solutions are passed back to the waiting
+    /// calls once a subgame without nodes is reached.
+    fn run(&mut self, disabled: &Region) -> WinningRegion {
+        let n = self.game.num_nodes();
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut disabled = {
+            let mut region = self.region();
+            region.union_with(disabled);
+            region
+        };
+        let mut bound = self.game.num_colors();
+        loop {
+            while let Some(color) = self.largest_color(&disabled, bound) {
                 let parity = Parity::of(color);
                 let player = Player::from(parity);
-                let a = self.attractor(disabled, color, parity, player);
-
-                let disabled1 = disabled.union(&a);
-                let mut won = self.run(&disabled1);
-                let change = won[!player].attract_mut_without(self.game, !player, disabled);
-                if change {
-                    let disabled2 = disabled.union(&won[!player]);
-                    let won2 = self.run(&disabled2);
-                    won[!player].union_with(&won2[!player]);
-                    won[player] = won2.of(player);
-                } else {
-                    won[player].union_with(&a);
+                let (attractor, targets) = self.attractor(&disabled, color, parity, player);
+                let mut subgame = self.region();
+                subgame.union_with(&disabled);
+                subgame.union_with(&attractor);
+                stack.push(Frame {
+                    disabled: std::mem::replace(&mut disabled, subgame),
+                    color,
+                    player,
+                    attractor,
+                    targets,
+                    stage: Stage::WithoutAttractor,
+                });
+                self.stats.iterations += 1;
+                self.stats.max_depth = self.stats.max_depth.max(stack.len());
+                // all nodes with the largest color are in the attractor
+                bound = color;
+            }
+            self.release(disabled);
+
+            // pass the solution to the waiting calls until one solves another subgame
+            let mut won = WinningRegion::with_capacity(n);
+            loop {
+                let Frame {
+                    disabled: frame_disabled,
+                    color,
+                    player,
+                    attractor,
+                    targets,
+                    stage,
+                } = match stack.pop() {
+                    Some(frame) => frame,
+                    None => return won,
+                };
+                match stage {
+                    Stage::WithoutAttractor => {
+                        let mut won_opponent = std::mem::take(&mut won[!player]);
+                        if self.attract(&mut won_opponent, !player, &frame_disabled) {
+                            let mut subgame = self.region();
+                            subgame.union_with(&frame_disabled);
+                            subgame.union_with(&won_opponent);
+                            let won_player = std::mem::take(&mut won[player]);
+                            self.release(won_player);
+                            stack.push(Frame {
+                                disabled: frame_disabled,
+                                color,
+                                player,
+                                attractor,
+                                targets,
+                                stage: Stage::WithoutOpponent(won_opponent),
+                            });
+                            disabled = subgame;
+                            bound = color + 1;
+                            break;
+                        }
+                        won[!player] = won_opponent;
+                        won[player].union_with(&attractor);
+                        // nodes with the largest colors may move anywhere in the winning region
+                        if let Some(strategy) = &mut self.strategy {
+                            for i in targets.nodes() {
+                                let node = &self.game[i];
+                                if node.owner() == player {
+                                    strategy[i] = node
+                                        .successors()
+                                        .iter()
+                                        .copied()
+                                        .filter(|&j| won[player][j])
+                                        .collect();
+                                }
+                            }
+                        }
+                    }
+                    Stage::WithoutOpponent(mut won_opponent) => {
+                        // the player keeps its winning region of the subgame
+                        won_opponent.union_with(&won[!player]);
+                        let won_subgame = std::mem::replace(&mut won[!player], won_opponent);
+                        self.release(won_subgame);
+                    }
                 }
-                won
+                self.release(frame_disabled);
+                self.release(attractor);
+                self.release(targets);
             }
         }
     }
 }
 
-pub(crate) struct ZlkSolver {}
+pub(crate) struct ZlkSolver {
+    stats: AlgorithmStats,
+}
 
 impl ZlkSolver {
     pub(crate) fn new() -> Self {
-        Self {}
+        Self {
+            stats: AlgorithmStats::default(),
+        }
     }
 }
 
@@ -84,10 +297,59 @@ impl ParityGameSolver for ZlkSolver {
         player: Player,
         compute_strategy: bool,
     ) -> (Region, Option<Strategy>) {
-        // TODO add strategy computation
-        assert!(!compute_strategy);
-        let zlk = ZlkSolverInstance::new(game);
-        let winning = zlk.run(disabled);
-        (winning.of(player), None)
+        let mut zlk = ZlkSolverInstance::new(game, compute_strategy);
+        let winning = zlk.run(disabled).of(player);
+        // only keep the strategy of the player in its winning region
+        let strategy = zlk.strategy.map(|mut strategy| {
+            for i in game.nodes() {
+                if !winning[i] || game[i].owner() != player {
+                    strategy[i].clear();
+                }
+            }
+            strategy
+        });
+        self.stats.add(&zlk.stats);
+        self.stats.add_strategy(strategy.as_ref());
+        (winning, strategy)
+    }
+
+    fn algorithm_stats(&self) -> AlgorithmStats {
+        self.stats
+    }
+}
+
+/// Tests for Zielonka's algorithm.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parity::game::LabelledGame;
+
+    /// Test that a game with a long chain of colors is solved without overflowing
+    /// the stack, where each node is won by its owner with a self-loop.
+    #[test]
+    fn test_long_color_chain() {
+        let n = 3_000;
+        let game: LabelledGame<NodeIndex> = (0..n)
+            .map(|i| {
+                if i + 1 < n {
+                    format!("{} {} {} {},{};\n", i, i, i % 2, i, i + 1)
+                } else {
+                    format!("{} {} {} {};\n", i, i, i % 2, i)
+                }
+            })
+            .collect::<String>()
+            .parse()
+            .unwrap();
+        for &player in &Player::PLAYERS {
+            let (winning, strategy) =
+                ZlkSolver::new().solve(&game, &Region::with_capacity(n), player, true);
+            let strategy = strategy.unwrap();
+            for i in 0..n {
+                assert_eq!(winning[i], game[i].owner() == player);
+                if winning[i] {
+                    assert_eq!(strategy[i], vec![i]);
+                }
+            }
+        }
     }
 }
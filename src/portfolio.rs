@@ -0,0 +1,266 @@
+//! Candidate controllers of the portfolio approach and their Pareto frontier.
+//!
+//! The portfolio approach constructs aiger circuits from several machines, which
+//! differ in their minimization and their structured labels. Instead of selecting
+//! a single circuit, the candidates that are Pareto-optimal for a set of metrics
+//! can be kept with the library option
+//! [`SynthesisOptions::pareto_metrics`](crate::options::SynthesisOptions::pareto_metrics).
+
+use std::fmt;
+
+use crate::controller::aiger::AigerController;
+use crate::options::{AigerMetric, AigerObjective, AigerThresholds, LabelStructure};
+
+/// The origin of a candidate controller in the portfolio approach.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    minimized: bool,
+    labels: LabelStructure,
+}
+
+impl Provenance {
+    pub(crate) fn new(minimized: bool, labels: LabelStructure) -> Self {
+        Self { minimized, labels }
+    }
+
+    /// Returns whether the machine of the candidate was minimized with don't cares.
+    pub fn minimized(&self) -> bool {
+        self.minimized
+    }
+
+    /// Returns the structure of the labels of the machine of the candidate,
+    /// which are encoded in the latches of the circuit.
+    pub fn labels(&self) -> LabelStructure {
+        self.labels
+    }
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let machine = if self.minimized {
+            "minimized"
+        } else {
+            "unminimized"
+        };
+        write!(f, "{} machine with labels {}", machine, self.labels)
+    }
+}
+
+/// The metrics of a candidate controller.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CandidateMetrics {
+    /// The number of states of the machine.
+    pub states: usize,
+    /// The number of and gates of the circuit.
+    pub gates: usize,
+    /// The number of latches of the circuit.
+    pub latches: usize,
+    /// The number of inputs of the circuit.
+    pub inputs: usize,
+    /// The depth of the circuit in and gates.
+    pub depth: usize,
+    /// The maximum number of and gates on a path from an input to an output.
+    pub io_delay: usize,
+    /// The maximum number of and gates on a path from a latch to an output.
+    pub latch_delay: usize,
+}
+
+impl CandidateMetrics {
+    pub(crate) fn new(states: usize, aiger: &AigerController) -> Self {
+        let size = aiger.size();
+        Self {
+            states,
+            gates: size.num_ands() as usize,
+            latches: size.num_latches() as usize,
+            inputs: size.num_inputs() as usize,
+            depth: size.depth() as usize,
+            io_delay: size.io_delay() as usize,
+            latch_delay: size.latch_delay() as usize,
+        }
+    }
+
+    /// Returns the value of the given metric.
+    pub fn metric(&self, metric: AigerMetric) -> usize {
+        match metric {
+            AigerMetric::Size => self.gates + self.latches,
+            AigerMetric::Gates => self.gates,
+            AigerMetric::Latches => self.latches,
+            AigerMetric::Depth => self.depth,
+            AigerMetric::Inputs => self.inputs,
+            AigerMetric::States => self.states,
+            AigerMetric::IoDelay => self.io_delay,
+            AigerMetric::LatchDelay => self.latch_delay,
+        }
+    }
+
+    /// Returns whether the values of all metrics of the thresholds are at most their bounds.
+    pub fn meets(&self, thresholds: &AigerThresholds) -> bool {
+        thresholds
+            .bounds()
+            .iter()
+            .all(|&(m, bound)| self.metric(m) <= bound)
+    }
+
+    /// Returns whether these metrics dominate the other metrics for the given metrics,
+    /// i.e. are at most as large for all and smaller for at least one of the metrics.
+    pub fn dominates(&self, other: &Self, metrics: &[AigerMetric]) -> bool {
+        metrics.iter().all(|&m| self.metric(m) <= other.metric(m))
+            && metrics.iter().any(|&m| self.metric(m) < other.metric(m))
+    }
+
+    /// Returns the values of the metrics of the objective in lexicographic order,
+    /// such that smaller keys are preferred by the objective.
+    pub(crate) fn objective_key(&self, objective: &AigerObjective) -> Vec<usize> {
+        objective
+            .metrics()
+            .iter()
+            .map(|&m| self.metric(m))
+            .collect()
+    }
+}
+
+impl fmt::Display for CandidateMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(#states: {}, #ands: {}, #latches: {}, #inputs: {}, depth: {}, io delay: {}, latch delay: {})",
+            self.states,
+            self.gates,
+            self.latches,
+            self.inputs,
+            self.depth,
+            self.io_delay,
+            self.latch_delay
+        )
+    }
+}
+
+/// A candidate controller of the portfolio approach with its metrics and provenance.
+#[derive(Clone)]
+pub struct Candidate {
+    provenance: Provenance,
+    metrics: CandidateMetrics,
+    controller: AigerController,
+}
+
+impl Candidate {
+    pub(crate) fn new(provenance: Provenance, states: usize, controller: AigerController) -> Self {
+        Self {
+            provenance,
+            metrics: CandidateMetrics::new(states, &controller),
+            controller,
+        }
+    }
+
+    /// Returns the origin of the candidate.
+    pub fn provenance(&self) -> Provenance {
+        self.provenance
+    }
+
+    /// Returns the metrics of the candidate.
+    pub fn metrics(&self) -> &CandidateMetrics {
+        &self.metrics
+    }
+
+    /// Returns the aiger circuit of the candidate.
+    pub fn controller(&self) -> &AigerController {
+        &self.controller
+    }
+
+    /// Returns the aiger circuit of the candidate, consuming the candidate.
+    pub fn into_controller(self) -> AigerController {
+        self.controller
+    }
+}
+
+/// Returns the candidates that are not dominated by another candidate for the given
+/// metrics, in their original order.
+///
+/// Candidates with equal values for all metrics are all kept, as they do not
+/// dominate each other.
+pub fn pareto_frontier(candidates: Vec<Candidate>, metrics: &[AigerMetric]) -> Vec<Candidate> {
+    let dominated: Vec<bool> = candidates
+        .iter()
+        .map(|c| {
+            candidates
+                .iter()
+                .any(|other| other.metrics.dominates(&c.metrics, metrics))
+        })
+        .collect();
+    candidates
+        .into_iter()
+        .zip(dominated)
+        .filter(|(_, dominated)| !dominated)
+        .map(|(c, _)| c)
+        .collect()
+}
+
+/// Tests for the Pareto frontier of candidates.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(states: usize, gates: usize, depth: usize) -> CandidateMetrics {
+        CandidateMetrics {
+            states,
+            gates,
+            latches: 1,
+            inputs: 1,
+            depth,
+            io_delay: depth,
+            latch_delay: 1,
+        }
+    }
+
+    /// Test the dominance of metrics for different sets of metrics.
+    #[test]
+    fn test_dominates() {
+        let small = metrics(2, 10, 5);
+        let shallow = metrics(2, 20, 3);
+        let metrics_all = [AigerMetric::Gates, AigerMetric::Depth];
+        assert!(!small.dominates(&shallow, &metrics_all));
+        assert!(!shallow.dominates(&small, &metrics_all));
+        assert!(small.dominates(&shallow, &[AigerMetric::Size]));
+        assert!(!small.dominates(&small, &metrics_all));
+        assert!(!small.dominates(&shallow, &[AigerMetric::States]));
+        let objective: AigerObjective = "depth,gates".parse().unwrap();
+        assert_eq!(shallow.objective_key(&objective), vec![3, 20]);
+    }
+
+    /// Test that thresholds bound the values of their metrics.
+    #[test]
+    fn test_meets_thresholds() {
+        let small = metrics(2, 10, 5);
+        assert!(small.meets(&AigerThresholds::default()));
+        let thresholds: AigerThresholds = "io-delay=5,gates=10".parse().unwrap();
+        assert!(small.meets(&thresholds));
+        assert_eq!(thresholds.to_string(), "io-delay=5,gates=10");
+        let thresholds: AigerThresholds = "latch-delay=0".parse().unwrap();
+        assert!(!small.meets(&thresholds));
+        assert!("delay=1".parse::<AigerThresholds>().is_err());
+        assert!("io-delay".parse::<AigerThresholds>().is_err());
+        assert!("io-delay=x".parse::<AigerThresholds>().is_err());
+    }
+
+    /// Test that the frontier only keeps candidates that are not dominated.
+    #[test]
+    fn test_pareto_frontier() {
+        let aag = "aag 3 1 1 1 1\n2\n4 6\n6\n6 2 5\n";
+        let controller = AigerController::read(aag.as_bytes()).unwrap();
+        let provenance = Provenance::new(true, LabelStructure::None);
+        let candidates: Vec<_> = [1, 3, 2]
+            .iter()
+            .map(|&states| Candidate::new(provenance, states, controller.clone()))
+            .collect();
+        let frontier = pareto_frontier(candidates.clone(), &[AigerMetric::States]);
+        assert_eq!(frontier.len(), 1);
+        assert_eq!(frontier[0].metrics().states, 1);
+        assert_eq!(frontier[0].metrics().gates, 1);
+        let frontier = pareto_frontier(candidates, &[AigerMetric::Gates]);
+        assert_eq!(frontier.len(), 3);
+        assert_eq!(
+            frontier[0].provenance().to_string(),
+            "minimized machine with labels none"
+        );
+    }
+}
@@ -0,0 +1,666 @@
+//! Differential testing of the synthesis procedure on random LTL specifications.
+//!
+//! Random formulas are generated up to a bounded depth with configurable weights of
+//! the operators. Each specification is synthesized with several configurations that
+//! differ in the exploration, the simplification of the formula and the parity game
+//! solver, and that have to agree on the realizability status. The status of the first
+//! configuration is additionally cross-checked by bounded synthesis, which does not
+//! depend on the game construction and solving, see [`crate::crosscheck`].
+//!
+//! A specification on which the configurations disagree is reduced by replacing its
+//! subformulas with their operands or constants as long as the disagreement persists.
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+
+use log::{debug, info};
+
+use crate::crosscheck::CrossCheck;
+use crate::options::{
+    ExplorationStrategy, OnTheFlyLimit, Simplification, Solver, SynthesisOptions,
+};
+use crate::parity::game::generator::SplitMix64;
+use crate::{try_synthesize_with_callback, Status};
+
+/// The weights with which the operators are chosen for random formulas, where an
+/// operator with weight zero does not occur in the formulas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperatorWeights {
+    /// The weight of atomic propositions.
+    pub proposition: u32,
+    /// The weight of the constants `true` and `false`.
+    pub constant: u32,
+    /// The weight of negation.
+    pub not: u32,
+    /// The weight of conjunction.
+    pub and: u32,
+    /// The weight of disjunction.
+    pub or: u32,
+    /// The weight of the next operator `X`.
+    pub next: u32,
+    /// The weight of the eventually operator `F`.
+    pub finally: u32,
+    /// The weight of the globally operator `G`.
+    pub globally: u32,
+    /// The weight of the until operator `U`.
+    pub until: u32,
+    /// The weight of the release operator `R`.
+    pub release: u32,
+}
+
+impl Default for OperatorWeights {
+    fn default() -> Self {
+        Self {
+            proposition: 4,
+            constant: 1,
+            not: 2,
+            and: 3,
+            or: 3,
+            next: 3,
+            finally: 2,
+            globally: 2,
+            until: 1,
+            release: 1,
+        }
+    }
+}
+
+impl OperatorWeights {
+    /// The names of the operators for parsing and displaying the weights.
+    const NAMES: [&'static str; 10] = [
+        "prop", "const", "not", "and", "or", "next", "finally", "globally", "until", "release",
+    ];
+
+    fn weights(&self) -> [u32; 10] {
+        [
+            self.proposition,
+            self.constant,
+            self.not,
+            self.and,
+            self.or,
+            self.next,
+            self.finally,
+            self.globally,
+            self.until,
+            self.release,
+        ]
+    }
+
+    fn weight_mut(&mut self, name: &str) -> Option<&mut u32> {
+        match name {
+            "prop" => Some(&mut self.proposition),
+            "const" => Some(&mut self.constant),
+            "not" => Some(&mut self.not),
+            "and" => Some(&mut self.and),
+            "or" => Some(&mut self.or),
+            "next" => Some(&mut self.next),
+            "finally" => Some(&mut self.finally),
+            "globally" => Some(&mut self.globally),
+            "until" => Some(&mut self.until),
+            "release" => Some(&mut self.release),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OperatorWeights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let weights: Vec<_> = Self::NAMES
+            .iter()
+            .zip(self.weights().iter())
+            .map(|(name, weight)| format!("{}={}", name, weight))
+            .collect();
+        write!(f, "{}", weights.join(","))
+    }
+}
+
+/// An error which can be returned when parsing operator weights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWeightsError(String);
+impl fmt::Display for ParseWeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid operator weight '{}'", self.0)
+    }
+}
+impl std::error::Error for ParseWeightsError {}
+
+/// Parses a comma-separated list of weights `operator=weight`, where the weights of
+/// operators that are not given keep their default value.
+impl FromStr for OperatorWeights {
+    type Err = ParseWeightsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = Self::default();
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let invalid = || ParseWeightsError(entry.to_string());
+            let (name, weight) = entry.split_once('=').ok_or_else(invalid)?;
+            let weight = weight.trim().parse().map_err(|_| invalid())?;
+            *weights.weight_mut(name.trim()).ok_or_else(invalid)? = weight;
+        }
+        Ok(weights)
+    }
+}
+
+/// The parameters for generating random specifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormulaParameters {
+    /// The number of input propositions `i0`, `i1`, ... of each specification.
+    pub num_inputs: usize,
+    /// The number of output propositions `o0`, `o1`, ... of each specification.
+    pub num_outputs: usize,
+    /// The maximal depth of operators in each formula.
+    pub max_depth: usize,
+    /// The weights of the operators.
+    pub weights: OperatorWeights,
+}
+
+impl Default for FormulaParameters {
+    fn default() -> Self {
+        Self {
+            num_inputs: 2,
+            num_outputs: 2,
+            max_depth: 4,
+            weights: OperatorWeights::default(),
+        }
+    }
+}
+
+impl FormulaParameters {
+    /// Returns the input propositions of the specifications.
+    pub fn inputs(&self) -> Vec<String> {
+        (0..self.num_inputs).map(|i| format!("i{}", i)).collect()
+    }
+
+    /// Returns the output propositions of the specifications.
+    pub fn outputs(&self) -> Vec<String> {
+        (0..self.num_outputs).map(|i| format!("o{}", i)).collect()
+    }
+}
+
+/// A formula as generated, which can be written in the syntax of Owl and reduced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Formula {
+    Constant(bool),
+    Proposition(String),
+    Unary(&'static str, Box<Formula>),
+    Binary(&'static str, Box<Formula>, Box<Formula>),
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Constant(value) => write!(f, "{}", value),
+            Self::Proposition(name) => write!(f, "{}", name),
+            Self::Unary(op, operand) => write!(f, "{} ({})", op, operand),
+            Self::Binary(op, left, right) => write!(f, "({}) {} ({})", left, op, right),
+        }
+    }
+}
+
+impl Formula {
+    /// Returns the formulas obtained by replacing one subformula with one of its
+    /// operands or with a constant, in order of increasing depth of the subformula.
+    fn reductions(&self) -> Vec<Self> {
+        let mut reductions = Vec::new();
+        match self {
+            Self::Constant(_) => (),
+            Self::Proposition(_) => {
+                reductions.push(Self::Constant(true));
+                reductions.push(Self::Constant(false));
+            }
+            Self::Unary(op, operand) => {
+                reductions.push((**operand).clone());
+                reductions.push(Self::Constant(true));
+                reductions.push(Self::Constant(false));
+                for reduced in operand.reductions() {
+                    reductions.push(Self::Unary(op, Box::new(reduced)));
+                }
+            }
+            Self::Binary(op, left, right) => {
+                reductions.push((**left).clone());
+                reductions.push((**right).clone());
+                reductions.push(Self::Constant(true));
+                reductions.push(Self::Constant(false));
+                for reduced in left.reductions() {
+                    reductions.push(Self::Binary(op, Box::new(reduced), right.clone()));
+                }
+                for reduced in right.reductions() {
+                    reductions.push(Self::Binary(op, left.clone(), Box::new(reduced)));
+                }
+            }
+        }
+        reductions
+    }
+}
+
+/// A generator for random LTL formulas.
+#[derive(Debug, Clone)]
+pub struct FormulaGenerator {
+    parameters: FormulaParameters,
+    random: SplitMix64,
+}
+
+impl FormulaGenerator {
+    /// Creates a new generator with the given parameters and seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no propositions, or the weights of the propositions and
+    /// the constants are both zero.
+    pub fn new(parameters: FormulaParameters, seed: u64) -> Self {
+        assert!(
+            parameters.num_inputs + parameters.num_outputs > 0,
+            "formulas need at least one proposition"
+        );
+        let weights = parameters.weights;
+        assert!(
+            weights.proposition > 0 || weights.constant > 0,
+            "formulas need propositions or constants"
+        );
+        Self {
+            parameters,
+            random: SplitMix64::new(seed),
+        }
+    }
+
+    /// Generates the next random formula in the syntax of Owl.
+    pub fn generate(&mut self) -> String {
+        self.generate_formula().to_string()
+    }
+
+    fn generate_formula(&mut self) -> Formula {
+        self.generate_at_depth(self.parameters.max_depth)
+    }
+
+    fn generate_at_depth(&mut self, depth: usize) -> Formula {
+        let mut weights = self.parameters.weights.weights();
+        if depth == 0 {
+            // only propositions and constants at the maximal depth
+            weights[2..].iter_mut().for_each(|w| *w = 0);
+        }
+        let total: u64 = weights.iter().map(|&w| u64::from(w)).sum();
+        let mut choice = self.random.next_u64() % total;
+        let mut operator = 0;
+        for (i, &w) in weights.iter().enumerate() {
+            if choice < u64::from(w) {
+                operator = i;
+                break;
+            }
+            choice -= u64::from(w);
+        }
+        let unary = |op, generator: &mut Self| {
+            Formula::Unary(op, Box::new(generator.generate_at_depth(depth - 1)))
+        };
+        match operator {
+            0 => {
+                let num_inputs = self.parameters.num_inputs;
+                let index = self.random.below(num_inputs + self.parameters.num_outputs);
+                if index < num_inputs {
+                    Formula::Proposition(format!("i{}", index))
+                } else {
+                    Formula::Proposition(format!("o{}", index - num_inputs))
+                }
+            }
+            1 => Formula::Constant(self.random.below(2) == 1),
+            2 => unary("!", self),
+            5 => unary("X", self),
+            6 => unary("F", self),
+            7 => unary("G", self),
+            _ => {
+                let op = match operator {
+                    3 => "&",
+                    4 => "|",
+                    8 => "U",
+                    _ => "R",
+                };
+                let left = self.generate_at_depth(depth - 1);
+                let right = self.generate_at_depth(depth - 1);
+                Formula::Binary(op, Box::new(left), Box::new(right))
+            }
+        }
+    }
+}
+
+impl Iterator for FormulaGenerator {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.generate())
+    }
+}
+
+/// The configuration for testing the synthesis procedure on random specifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelftestConfig {
+    /// The parameters for generating random specifications.
+    pub parameters: FormulaParameters,
+    /// The number of random specifications to check.
+    pub num_specs: usize,
+    /// The seed of the first specification, where the seed is increased by one for
+    /// each further specification.
+    pub seed: u64,
+    /// The maximal number of states for cross-checking the status by bounded synthesis.
+    pub cross_check_states: usize,
+}
+
+impl Default for SelftestConfig {
+    fn default() -> Self {
+        Self {
+            parameters: FormulaParameters::default(),
+            num_specs: 100,
+            seed: 0,
+            cross_check_states: 2,
+        }
+    }
+}
+
+/// A failure found by the self-test, i.e. a disagreement between configurations,
+/// a status contradicted by bounded synthesis, an error or a panic.
+#[derive(Debug, Clone)]
+pub struct SelftestFailure {
+    /// The seed from which the specification can be generated again.
+    pub seed: u64,
+    /// The description of the failure.
+    pub description: String,
+    /// The formula of the specification.
+    pub formula: String,
+    /// A reduced formula on which the failure persists.
+    pub reduced_formula: String,
+    /// The input propositions of the specification.
+    pub inputs: Vec<String>,
+    /// The output propositions of the specification.
+    pub outputs: Vec<String>,
+}
+
+impl fmt::Display for SelftestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} on specification with seed {}:",
+            self.description, self.seed
+        )?;
+        writeln!(f, "{}", self.formula)?;
+        writeln!(f, "Reduced formula with failure:")?;
+        writeln!(f, "{}", self.reduced_formula)?;
+        writeln!(
+            f,
+            "Inputs: {}; outputs: {}",
+            self.inputs.join(", "),
+            self.outputs.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for SelftestFailure {}
+
+/// The outcome of a successful self-test.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelftestReport {
+    /// The number of checked specifications.
+    pub num_specs: usize,
+    /// The number of realizable specifications.
+    pub realizable: usize,
+    /// The number of specifications whose status was confirmed by bounded synthesis.
+    pub confirmed: usize,
+}
+
+impl fmt::Display for SelftestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Checked {} specifications, {} realizable and {} unrealizable, \
+             {} confirmed by bounded synthesis",
+            self.num_specs,
+            self.realizable,
+            self.num_specs - self.realizable,
+            self.confirmed
+        )
+    }
+}
+
+/// Returns the configurations that are compared on each specification, where only
+/// the realizability status is determined.
+fn configurations(cross_check_states: usize) -> Vec<(&'static str, SynthesisOptions)> {
+    let base = SynthesisOptions {
+        only_realizability: true,
+        ..SynthesisOptions::default()
+    };
+    vec![
+        (
+            "default",
+            SynthesisOptions {
+                cross_check: Some(cross_check_states),
+                ..base.clone()
+            },
+        ),
+        (
+            "dfs with zlk",
+            SynthesisOptions {
+                exploration_strategy: ExplorationStrategy::Dfs,
+                exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+                parity_solver: Solver::Zlk,
+                ..base.clone()
+            },
+        ),
+        (
+            "complete exploration with si",
+            SynthesisOptions {
+                exploration_on_the_fly: OnTheFlyLimit::None,
+                parity_solver: Solver::Si,
+                ..base.clone()
+            },
+        ),
+        (
+            "no simplification with fpi",
+            SynthesisOptions {
+                ltl_simplification: Simplification::None,
+                exploration_strategy: ExplorationStrategy::MinMax,
+                parity_solver: Solver::Fpi,
+                ..base.clone()
+            },
+        ),
+        (
+            "simulation-guided exploration",
+            SynthesisOptions {
+                exploration_strategy: ExplorationStrategy::Sim(16),
+                ..base.clone()
+            },
+        ),
+        (
+            "language simplification",
+            SynthesisOptions {
+                ltl_simplification: Simplification::Language,
+                exploration_strategy: ExplorationStrategy::Bdfs(2),
+                ..base
+            },
+        ),
+    ]
+}
+
+/// The outcome of checking a single specification.
+struct Outcome {
+    status: Status,
+    confirmed: bool,
+}
+
+/// Checks the specification with all configurations, and returns the agreed status
+/// or a description of the failure.
+fn check_spec(
+    ltl: &str,
+    ins: &[&str],
+    outs: &[&str],
+    configurations: &[(&'static str, SynthesisOptions)],
+) -> Result<Outcome, String> {
+    let mut outcome: Option<Outcome> = None;
+    for (name, options) in configurations {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            try_synthesize_with_callback(ltl, ins, outs, options, |_| ())
+        }))
+        .map_err(|_| format!("Configuration {} panicked", name))?
+        .map_err(|e| format!("Configuration {} failed: {}", name, e))?;
+        let status = result.status();
+        if let Some(CrossCheck::Contradicted(states)) = result.cross_check() {
+            return Err(format!(
+                "Bounded synthesis with {} states contradicted status {:?} of configuration {}",
+                states, status, name
+            ));
+        }
+        match &outcome {
+            Some(o) if o.status != status => {
+                return Err(format!(
+                    "Configuration {} found status {:?} instead of {:?}",
+                    name, status, o.status
+                ))
+            }
+            Some(_) => (),
+            None => {
+                outcome = Some(Outcome {
+                    status,
+                    confirmed: matches!(result.cross_check(), Some(CrossCheck::Confirmed(_))),
+                })
+            }
+        }
+    }
+    Ok(outcome.expect("no configurations"))
+}
+
+/// Reduces the formula by replacing subformulas as long as the check still fails.
+fn reduce_formula<F: FnMut(&str) -> bool>(formula: &Formula, mut fails: F) -> Formula {
+    let mut reduced = formula.clone();
+    while let Some(smaller) = reduced
+        .reductions()
+        .into_iter()
+        .find(|candidate| fails(&candidate.to_string()))
+    {
+        debug!("Reduced formula to {}", smaller);
+        reduced = smaller;
+    }
+    reduced
+}
+
+/// Checks random specifications generated with the given configuration.
+///
+/// # Errors
+///
+/// Returns the first failure, i.e. a specification on which the configurations
+/// disagree on the realizability status, whose status is contradicted by bounded
+/// synthesis, or on which a configuration returns an error or panics.
+pub fn selftest(config: &SelftestConfig) -> Result<SelftestReport, SelftestFailure> {
+    let inputs = config.parameters.inputs();
+    let outputs = config.parameters.outputs();
+    let ins: Vec<_> = inputs.iter().map(String::as_str).collect();
+    let outs: Vec<_> = outputs.iter().map(String::as_str).collect();
+    let configurations = configurations(config.cross_check_states);
+    let mut report = SelftestReport::default();
+    for k in 0..config.num_specs {
+        let seed = config.seed.wrapping_add(k as u64);
+        let formula = FormulaGenerator::new(config.parameters, seed).generate_formula();
+        let ltl = formula.to_string();
+        debug!("Checking specification with seed {}: {}", seed, ltl);
+        match check_spec(&ltl, &ins, &outs, &configurations) {
+            Ok(outcome) => {
+                report.num_specs += 1;
+                if outcome.status == Status::Realizable {
+                    report.realizable += 1;
+                }
+                if outcome.confirmed {
+                    report.confirmed += 1;
+                }
+            }
+            Err(description) => {
+                info!("{} on specification with seed {}", description, seed);
+                let reduced = reduce_formula(&formula, |candidate| {
+                    check_spec(candidate, &ins, &outs, &configurations).is_err()
+                });
+                return Err(SelftestFailure {
+                    seed,
+                    description,
+                    formula: ltl,
+                    reduced_formula: reduced.to_string(),
+                    inputs,
+                    outputs,
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Tests for the self-test on random specifications.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that formulas are reproducible from their seed and respect the depth.
+    #[test]
+    fn test_generator() {
+        let parameters = FormulaParameters {
+            max_depth: 0,
+            ..FormulaParameters::default()
+        };
+        for formula in FormulaGenerator::new(parameters, 3).take(20) {
+            assert!(["i0", "i1", "o0", "o1", "true", "false"].contains(&formula.as_str()));
+        }
+        let parameters = FormulaParameters::default();
+        let first: Vec<_> = FormulaGenerator::new(parameters, 7).take(5).collect();
+        let second: Vec<_> = FormulaGenerator::new(parameters, 7).take(5).collect();
+        assert_eq!(first, second);
+    }
+
+    /// Test that operators with weight zero do not occur in formulas.
+    #[test]
+    fn test_weights() {
+        let weights: OperatorWeights = "next=9, until=0,release=0".parse().unwrap();
+        assert_eq!(weights.next, 9);
+        assert_eq!(weights.until, 0);
+        assert_eq!(weights.and, OperatorWeights::default().and);
+        assert_eq!(weights.to_string().parse(), Ok(weights));
+        assert!("next".parse::<OperatorWeights>().is_err());
+        assert!("weak=1".parse::<OperatorWeights>().is_err());
+
+        let parameters = FormulaParameters {
+            weights: "next=0,finally=0,globally=0,until=0,release=0"
+                .parse()
+                .unwrap(),
+            ..FormulaParameters::default()
+        };
+        for formula in FormulaGenerator::new(parameters, 0).take(50) {
+            assert!(!formula.contains(|c| "XFGUR".contains(c)), "{}", formula);
+        }
+    }
+
+    /// Test that a formula is reduced to a minimal subformula on which a check fails.
+    #[test]
+    fn test_reduce_formula() {
+        let formula = Formula::Binary(
+            "&",
+            Box::new(Formula::Unary(
+                "X",
+                Box::new(Formula::Proposition("i0".to_string())),
+            )),
+            Box::new(Formula::Proposition("o1".to_string())),
+        );
+        assert_eq!(formula.to_string(), "(X (i0)) & (o1)");
+        let reduced = reduce_formula(&formula, |candidate| candidate.contains("X"));
+        assert_eq!(reduced.to_string(), "X (true)");
+    }
+
+    /// Test that the configurations agree on a few small random specifications.
+    #[test]
+    fn test_selftest() {
+        let config = SelftestConfig {
+            parameters: FormulaParameters {
+                num_inputs: 1,
+                num_outputs: 1,
+                max_depth: 3,
+                ..FormulaParameters::default()
+            },
+            num_specs: 5,
+            ..SelftestConfig::default()
+        };
+        let report = selftest(&config).unwrap();
+        assert_eq!(report.num_specs, 5);
+        assert!(report.realizable <= 5);
+    }
+}
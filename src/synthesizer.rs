@@ -0,0 +1,264 @@
+//! Builder-style entry point to the synthesis procedure with validation of the input.
+
+use std::collections::HashSet;
+
+use crate::events::EventSink;
+use crate::options::SynthesisOptions;
+use crate::parity::TelemetrySink;
+use crate::{
+    synthesize_with_callback, synthesize_with_events, synthesize_with_telemetry,
+    try_synthesize_with_callback, Status, SynthesisError, SynthesisResult,
+};
+
+/// A validated specification together with options for the synthesis procedure.
+///
+/// A synthesizer is obtained from a [`SynthesizerBuilder`], which checks the
+/// specification and the combination of options before any work is started.
+///
+/// # Examples
+///
+/// ```no_run
+/// use strix::options::OutputFormat;
+/// use strix::synthesizer::Synthesizer;
+///
+/// let synthesizer = Synthesizer::builder()
+///     .formula("G (req -> F grant)")
+///     .inputs(&["req"])
+///     .outputs(&["grant"])
+///     .option(|o| o.output_format = OutputFormat::Aag)
+///     .build()
+///     .unwrap();
+/// let result = synthesizer.synthesize();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Synthesizer {
+    formula: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    options: SynthesisOptions,
+}
+
+impl Synthesizer {
+    /// Returns a new builder for a synthesizer.
+    pub fn builder() -> SynthesizerBuilder {
+        SynthesizerBuilder::default()
+    }
+
+    /// Returns the LTL formula of the specification.
+    pub fn formula(&self) -> &str {
+        &self.formula
+    }
+
+    /// Returns the input atomic propositions of the specification.
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// Returns the output atomic propositions of the specification.
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    /// Returns the options for the synthesis procedure.
+    pub fn options(&self) -> &SynthesisOptions {
+        &self.options
+    }
+
+    /// Runs the synthesis procedure on the specification.
+    ///
+    /// Returns the result of the synthesis procedure.
+    pub fn synthesize(&self) -> SynthesisResult {
+        self.synthesize_with_callback(|_| ())
+    }
+
+    /// Runs the synthesis procedure on the specification, where `on_status` is
+    /// invoked with the realizability status as soon as it is determined,
+    /// before the controller is constructed.
+    ///
+    /// Returns the result of the synthesis procedure.
+    pub fn synthesize_with_callback<F: FnMut(Status)>(&self, on_status: F) -> SynthesisResult {
+        let ins: Vec<_> = self.inputs.iter().map(String::as_str).collect();
+        let outs: Vec<_> = self.outputs.iter().map(String::as_str).collect();
+        synthesize_with_callback(&self.formula, &ins, &outs, &self.options, on_status)
+    }
+
+    /// Runs the synthesis procedure on the specification as for
+    /// [`Synthesizer::synthesize_with_callback`], where a failure of Owl to parse the
+    /// formula or translate it to an automaton is returned as an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SynthesisError::Translation`] if Owl fails on the specification,
+    /// see [`try_synthesize_with_callback`].
+    pub fn try_synthesize_with_callback<F: FnMut(Status)>(
+        &self,
+        on_status: F,
+    ) -> Result<SynthesisResult, SynthesisError> {
+        let ins: Vec<_> = self.inputs.iter().map(String::as_str).collect();
+        let outs: Vec<_> = self.outputs.iter().map(String::as_str).collect();
+        try_synthesize_with_callback(&self.formula, &ins, &outs, &self.options, on_status)
+    }
+
+    /// Runs the synthesis procedure on the specification, where `on_status` is
+    /// invoked as for [`Synthesizer::synthesize_with_callback`] and `telemetry`
+    /// is notified after each time the parity game is solved.
+    ///
+    /// Returns the result of the synthesis procedure.
+    pub fn synthesize_with_telemetry<F: FnMut(Status), T: TelemetrySink>(
+        &self,
+        on_status: F,
+        telemetry: T,
+    ) -> SynthesisResult {
+        let ins: Vec<_> = self.inputs.iter().map(String::as_str).collect();
+        let outs: Vec<_> = self.outputs.iter().map(String::as_str).collect();
+        synthesize_with_telemetry(
+            &self.formula,
+            &ins,
+            &outs,
+            &self.options,
+            on_status,
+            telemetry,
+        )
+    }
+
+    /// Runs the synthesis procedure on the specification, where `on_status` is
+    /// invoked as for [`Synthesizer::synthesize_with_callback`] and `events`
+    /// receives the events at the stage boundaries of the synthesis procedure.
+    ///
+    /// Returns the result of the synthesis procedure.
+    pub fn synthesize_with_events<F: FnMut(Status), E: EventSink>(
+        &self,
+        on_status: F,
+        events: E,
+    ) -> SynthesisResult {
+        let ins: Vec<_> = self.inputs.iter().map(String::as_str).collect();
+        let outs: Vec<_> = self.outputs.iter().map(String::as_str).collect();
+        synthesize_with_events(&self.formula, &ins, &outs, &self.options, on_status, events)
+    }
+}
+
+/// A builder for a [`Synthesizer`].
+#[derive(Debug, Clone, Default)]
+pub struct SynthesizerBuilder {
+    formula: Option<String>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    options: SynthesisOptions,
+}
+
+impl SynthesizerBuilder {
+    /// Sets the LTL formula of the specification.
+    pub fn formula<S: Into<String>>(mut self, formula: S) -> Self {
+        self.formula = Some(formula.into());
+        self
+    }
+
+    /// Sets the input atomic propositions of the specification.
+    pub fn inputs<S: AsRef<str>>(mut self, inputs: &[S]) -> Self {
+        self.inputs = inputs.iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Sets the output atomic propositions of the specification.
+    pub fn outputs<S: AsRef<str>>(mut self, outputs: &[S]) -> Self {
+        self.outputs = outputs.iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Sets all options for the synthesis procedure.
+    pub fn options(mut self, options: SynthesisOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Modifies the options for the synthesis procedure with the given function.
+    pub fn option<F: FnOnce(&mut SynthesisOptions)>(mut self, f: F) -> Self {
+        f(&mut self.options);
+        self
+    }
+
+    /// Validates the specification and options and builds the synthesizer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no formula is given, if an atomic proposition is given
+    /// more than once, or if the combination of options is not supported,
+    /// see [`SynthesisOptions::validate`].
+    pub fn build(self) -> Result<Synthesizer, SynthesisError> {
+        let formula = self.formula.ok_or(SynthesisError::MissingFormula)?;
+        let mut propositions = HashSet::new();
+        for ap in self.inputs.iter().chain(self.outputs.iter()) {
+            if !propositions.insert(ap) {
+                return Err(SynthesisError::DuplicateProposition(ap.clone()));
+            }
+        }
+        self.options.validate()?;
+        Ok(Synthesizer {
+            formula,
+            inputs: self.inputs,
+            outputs: self.outputs,
+            options: self.options,
+        })
+    }
+}
+
+/// Tests for validation in the synthesizer builder.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{MinimizationMethod, OutputFormat, Solver};
+
+    /// Test that a missing formula is rejected.
+    #[test]
+    fn test_missing_formula() {
+        let result = Synthesizer::builder().inputs(&["a"]).build();
+        assert_eq!(result.unwrap_err(), SynthesisError::MissingFormula);
+    }
+
+    /// Test that an atomic proposition used as input and output is rejected.
+    #[test]
+    fn test_duplicate_proposition() {
+        let result = Synthesizer::builder()
+            .formula("G (a <-> b)")
+            .inputs(&["a", "b"])
+            .outputs(&["b"])
+            .build();
+        assert_eq!(
+            result.unwrap_err(),
+            SynthesisError::DuplicateProposition("b".to_string())
+        );
+    }
+
+    /// Test that unsupported combinations of options are rejected.
+    #[test]
+    fn test_incompatible_options() {
+        let oink_solver = Synthesizer::builder()
+            .formula("G (a <-> b)")
+            .option(|o| o.oink_solver = Some("npp".to_string()))
+            .build();
+        assert!(matches!(
+            oink_solver,
+            Err(SynthesisError::IncompatibleOptions(_))
+        ));
+
+        let pg_minimization = Synthesizer::builder()
+            .formula("G (a <-> b)")
+            .option(|o| {
+                o.output_format = OutputFormat::Pg;
+                o.machine_minimization = MinimizationMethod::Both;
+            })
+            .build();
+        assert!(matches!(
+            pg_minimization,
+            Err(SynthesisError::IncompatibleOptions(_))
+        ));
+
+        let zlk_strategy = Synthesizer::builder()
+            .formula("G (a <-> b)")
+            .inputs(&["a"])
+            .outputs(&["b"])
+            .option(|o| o.parity_solver = Solver::Zlk)
+            .build();
+        assert!(zlk_strategy.is_ok());
+    }
+}
@@ -0,0 +1,319 @@
+//! A harness for end-to-end tests of specifications, which synthesizes controllers and
+//! verifies them against the specification with external model checkers.
+//!
+//! A checker is a command that is called with the file of the controller, the formula,
+//! the comma-separated inputs and outputs and the expected status `REALIZABLE` or
+//! `UNREALIZABLE`, and that exits successfully if the controller is correct. For an
+//! unrealizable specification, the controller is a counter-strategy, whose inputs
+//! are the outputs of the specification and vice versa. The scripts
+//! `scripts/verify_aiger.sh` and `scripts/verify_hoa.sh` of Strix, which are used by
+//! default, check circuits in AIGER format and machines in HOA format with
+//! [Spot](https://spot.lrde.epita.fr/) and [nuXmv](https://nuxmv.fbk.eu/), see
+//! `scripts/install_verification_tools.sh`.
+//!
+//! ```ignore
+//! let verifier = Verifier::new().with_aiger_checker("/opt/checkers/verify_aiger.sh");
+//! let options = SynthesisOptions {
+//!     output_format: OutputFormat::Aag,
+//!     ..SynthesisOptions::default()
+//! };
+//! verifier
+//!     .verify_aiger("G (r -> F g)", &["r"], &["g"], Status::Realizable, &options)
+//!     .unwrap();
+//! ```
+//!
+//! This module is only available with the `testing` feature.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use crate::controller::AigerController;
+use crate::options::{OutputFormat, SynthesisOptions};
+use crate::{convert_controller, synthesize_with, Controller, Status, SynthesisResult};
+
+/// An error when verifying a specification.
+#[derive(Debug)]
+pub enum VerificationError {
+    /// Synthesis determined a different realizability status than expected.
+    Status {
+        /// The expected status.
+        expected: Status,
+        /// The status determined by synthesis.
+        actual: Status,
+    },
+    /// Synthesis did not produce a controller of the given kind.
+    MissingController(&'static str),
+    /// The controller could not be written, converted or passed to the checker.
+    Io(io::Error),
+    /// The checker rejected the controller.
+    Rejected {
+        /// The command of the checker.
+        checker: PathBuf,
+        /// The exit status of the checker.
+        status: ExitStatus,
+    },
+}
+
+impl Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { expected, actual } => {
+                write!(f, "expected status {}, but got {}", expected, actual)
+            }
+            Self::MissingController(kind) => write!(f, "no {} controller produced", kind),
+            Self::Io(err) => write!(f, "could not run checker: {}", err),
+            Self::Rejected { checker, status } => write!(
+                f,
+                "controller rejected by {} with {}",
+                checker.display(),
+                status
+            ),
+        }
+    }
+}
+
+impl Error for VerificationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for VerificationError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Synthesizes a controller for the given specification with the given options, and
+/// checks that the realizability status is the expected status.
+///
+/// # Errors
+///
+/// Returns [`VerificationError::Status`] if synthesis determines a different status.
+pub fn synthesize_expecting(
+    ltl: &str,
+    ins: &[&str],
+    outs: &[&str],
+    expected_status: Status,
+    options: &SynthesisOptions,
+) -> Result<SynthesisResult, VerificationError> {
+    let result = synthesize_with(ltl, ins, outs, options);
+    if result.status() == expected_status {
+        Ok(result)
+    } else {
+        Err(VerificationError::Status {
+            expected: expected_status,
+            actual: result.status(),
+        })
+    }
+}
+
+/// A verifier of controllers, with the commands of the checkers for circuits in AIGER
+/// format and for machines in HOA format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verifier {
+    aiger_checker: PathBuf,
+    hoa_checker: PathBuf,
+}
+
+impl Default for Verifier {
+    fn default() -> Self {
+        let scripts = Path::new(env!("CARGO_MANIFEST_DIR")).join("scripts");
+        Self {
+            aiger_checker: scripts.join("verify_aiger.sh"),
+            hoa_checker: scripts.join("verify_hoa.sh"),
+        }
+    }
+}
+
+impl Verifier {
+    /// Creates a verifier with the verification scripts of Strix as checkers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the command of the checker for circuits in AIGER format.
+    pub fn with_aiger_checker<P: Into<PathBuf>>(mut self, checker: P) -> Self {
+        self.aiger_checker = checker.into();
+        self
+    }
+
+    /// Sets the command of the checker for machines in HOA format.
+    pub fn with_hoa_checker<P: Into<PathBuf>>(mut self, checker: P) -> Self {
+        self.hoa_checker = checker.into();
+        self
+    }
+
+    /// Checks the given controller, written to a temporary file, with the given checker
+    /// against the specification and status.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerificationError::Rejected`] if the checker does not exit successfully,
+    /// and [`VerificationError::Io`] if the checker can not be executed.
+    pub fn check_controller<T: Display>(
+        &self,
+        controller: &T,
+        checker: &Path,
+        ltl: &str,
+        ins: &[&str],
+        outs: &[&str],
+        status: Status,
+    ) -> Result<(), VerificationError> {
+        let mut implementation_file = tempfile::NamedTempFile::new()?;
+        write!(implementation_file, "{}", controller)?;
+        implementation_file.flush()?;
+
+        let checker_status = Command::new(checker)
+            .arg(implementation_file.path())
+            .arg(ltl)
+            .arg(ins.join(","))
+            .arg(outs.join(","))
+            .arg(status.to_string())
+            .status()?;
+        if checker_status.success() {
+            Ok(())
+        } else {
+            Err(VerificationError::Rejected {
+                checker: checker.to_path_buf(),
+                status: checker_status,
+            })
+        }
+    }
+
+    /// Checks the given aiger circuit with the checker for circuits against the
+    /// specification and status, see [`Verifier::check_controller`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the circuit is rejected or the checker can not be executed.
+    pub fn check_aiger(
+        &self,
+        aiger: &AigerController,
+        ltl: &str,
+        ins: &[&str],
+        outs: &[&str],
+        status: Status,
+    ) -> Result<(), VerificationError> {
+        self.check_controller(aiger, &self.aiger_checker, ltl, ins, outs, status)
+    }
+
+    /// Synthesizes an aiger circuit for the given specification with the given options,
+    /// and verifies the circuit against the specification and expected status.
+    ///
+    /// The options should already have the output format set to `aag` or `aig`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if synthesis determines a different status or produces no
+    /// circuit, or if the circuit is rejected by the checker.
+    pub fn verify_aiger(
+        &self,
+        ltl: &str,
+        ins: &[&str],
+        outs: &[&str],
+        expected_status: Status,
+        options: &SynthesisOptions,
+    ) -> Result<(), VerificationError> {
+        let result = synthesize_expecting(ltl, ins, outs, expected_status, options)?;
+        match result.controller() {
+            Some(Controller::Aiger(aiger)) => {
+                self.check_aiger(aiger, ltl, ins, outs, expected_status)
+            }
+            _ => Err(VerificationError::MissingController("aiger")),
+        }
+    }
+
+    /// Synthesizes a machine for the given specification with the given options, and
+    /// verifies the machine in HOA format against the specification and expected status.
+    ///
+    /// The options should already have the output format set to `hoa`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if synthesis determines a different status or produces no
+    /// machine, or if the machine is rejected by the checker.
+    pub fn verify_hoa(
+        &self,
+        ltl: &str,
+        ins: &[&str],
+        outs: &[&str],
+        expected_status: Status,
+        options: &SynthesisOptions,
+    ) -> Result<(), VerificationError> {
+        let result = synthesize_expecting(ltl, ins, outs, expected_status, options)?;
+        match result.controller() {
+            Some(Controller::Machine(machine)) => {
+                self.check_controller(machine, &self.hoa_checker, ltl, ins, outs, expected_status)
+            }
+            _ => Err(VerificationError::MissingController("machine")),
+        }
+    }
+
+    /// Synthesizes a machine for the given specification with the given options,
+    /// converts the machine written in HOA format to an aiger circuit, and verifies the
+    /// circuit against the specification and expected status.
+    ///
+    /// The options should already have the output format set to `hoa`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if synthesis determines a different status or produces no
+    /// machine, if the machine can not be converted, or if the circuit is rejected by
+    /// the checker.
+    pub fn verify_converted_aiger(
+        &self,
+        ltl: &str,
+        ins: &[&str],
+        outs: &[&str],
+        expected_status: Status,
+        options: &SynthesisOptions,
+    ) -> Result<(), VerificationError> {
+        let result = synthesize_expecting(ltl, ins, outs, expected_status, options)?;
+        let machine = match result.controller() {
+            Some(Controller::Machine(machine)) => machine.to_string(),
+            _ => return Err(VerificationError::MissingController("machine")),
+        };
+        let options = SynthesisOptions {
+            output_format: OutputFormat::Aag,
+            ..SynthesisOptions::default()
+        };
+        match convert_controller(machine.as_bytes(), &options)? {
+            Controller::Aiger(aiger) => self.check_aiger(&aiger, ltl, ins, outs, expected_status),
+            _ => Err(VerificationError::MissingController("aiger")),
+        }
+    }
+}
+
+/// Tests for the verification harness.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that the exit status of the checker decides the verification.
+    #[test]
+    fn test_check_controller() {
+        let aag = "aag 1 1 0 1 0\n2\n2\ni0 a\no0 b\n";
+        let aiger = AigerController::read(aag.as_bytes()).unwrap();
+        let accepting = Verifier::new().with_aiger_checker("true");
+        assert!(accepting
+            .check_aiger(&aiger, "G (a <-> b)", &["a"], &["b"], Status::Realizable)
+            .is_ok());
+        let rejecting = Verifier::new().with_aiger_checker("false");
+        assert!(matches!(
+            rejecting.check_aiger(&aiger, "G (a <-> b)", &["a"], &["b"], Status::Realizable),
+            Err(VerificationError::Rejected { .. })
+        ));
+        let missing = Verifier::new().with_aiger_checker("/nonexistent/checker");
+        assert!(matches!(
+            missing.check_aiger(&aiger, "G (a <-> b)", &["a"], &["b"], Status::Realizable),
+            Err(VerificationError::Io(_))
+        ));
+    }
+}
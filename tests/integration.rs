@@ -1,69 +1,32 @@
 //! Integration test that verify controllers in AIGER or HOA format against
-//! external model checkers.
+//! external model checkers, using the harness of module `testing`, and test the
+//! synthesis functions of the library end to end.
 
-use std::io::Write;
-use std::path::PathBuf;
-use std::process::Command;
+use std::fs;
 
+use owl::graal::OwlError;
+use strix::crosscheck::Robustness;
+use strix::events::PipelineEvent;
 use strix::options::*;
+use strix::parity::game::Player;
+use strix::parity::SolverTelemetry;
+use strix::testing::{synthesize_expecting, Verifier};
 use strix::{
-    synthesize_with, Controller,
+    convert_controller, synthesize_monitor_with_callback, synthesize_with,
+    synthesize_with_callback, synthesize_with_events, synthesize_with_telemetry,
+    try_synthesize_with_callback, weaken_assumptions, Controller,
     Status::{self, Realizable, Unrealizable},
+    SynthesisError,
 };
 
 /// Synthesize the given specification, only testing realizability,
 /// and check the returned status against the expected status.
 fn verify_realizability(ltl: &str, ins: &[&str], outs: &[&str], expected_status: Status) {
-    verify_realizability_with(
-        ltl,
-        ins,
-        outs,
-        expected_status,
-        &SynthesisOptions {
-            only_realizability: true,
-            ..SynthesisOptions::default()
-        },
-    );
-}
-
-/// Synthesize the given specification with the given options, only testing realizability,
-/// and check the returned status against the expected status.
-fn verify_realizability_with(
-    ltl: &str,
-    ins: &[&str],
-    outs: &[&str],
-    expected_status: Status,
-    options: &SynthesisOptions,
-) {
-    let result = synthesize_with(ltl, ins, outs, options);
-    assert_eq!(result.status(), expected_status);
-}
-
-/// Verify the given controller using the given script against the
-/// given specification and status.
-fn verify_controller<T: std::fmt::Display>(
-    controller: T,
-    script_file: &str,
-    ltl: &str,
-    ins: &[&str],
-    outs: &[&str],
-    status: Status,
-) {
-    let mut implementation_file = tempfile::NamedTempFile::new().unwrap();
-    write!(implementation_file, "{}", controller).unwrap();
-
-    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    let script = root.join("scripts").join(script_file);
-
-    let verification_status = Command::new(script)
-        .arg(implementation_file.path())
-        .arg(ltl)
-        .arg(ins.join(","))
-        .arg(outs.join(","))
-        .arg(status.to_string())
-        .status()
-        .expect("failed to execute verification script");
-    assert!(verification_status.success());
+    let options = SynthesisOptions {
+        only_realizability: true,
+        ..SynthesisOptions::default()
+    };
+    synthesize_expecting(ltl, ins, outs, expected_status, &options).unwrap();
 }
 
 /// Synthesize the given specification, producing an aiger circuit, and verify
@@ -93,13 +56,9 @@ fn verify_aiger_with(
     expected_status: Status,
     options: &SynthesisOptions,
 ) {
-    let result = synthesize_with(ltl, ins, outs, options);
-    assert_eq!(result.status(), expected_status);
-    if let Some(Controller::Aiger(aiger)) = result.controller() {
-        verify_controller(aiger, "verify_aiger.sh", ltl, ins, outs, expected_status);
-    } else {
-        panic!("no aiger controller produced");
-    }
+    Verifier::new()
+        .verify_aiger(ltl, ins, outs, expected_status, options)
+        .unwrap();
 }
 
 /// Synthesize the given specification, producing a machine in HOA format, and verify
@@ -128,13 +87,9 @@ fn verify_hoa_with(
     expected_status: Status,
     options: &SynthesisOptions,
 ) {
-    let result = synthesize_with(ltl, ins, outs, options);
-    assert_eq!(result.status(), expected_status);
-    if let Some(Controller::Machine(machine)) = result.controller() {
-        verify_controller(machine, "verify_hoa.sh", ltl, ins, outs, expected_status);
-    } else {
-        panic!("no machine controller produced");
-    }
+    Verifier::new()
+        .verify_hoa(ltl, ins, outs, expected_status, options)
+        .unwrap();
 }
 
 /// Synthesize the given specification, producing a parity game.
@@ -144,8 +99,7 @@ fn verify_pg(ltl: &str, ins: &[&str], outs: &[&str], expected_status: Status) {
         output_format: OutputFormat::Pg,
         ..SynthesisOptions::default()
     };
-    let result = synthesize_with(ltl, ins, outs, &options);
-    assert_eq!(result.status(), expected_status);
+    let result = synthesize_expecting(ltl, ins, outs, expected_status, &options).unwrap();
     // can not verify parity game itself currently
     assert!(matches!(
         result.controller(),
@@ -160,14 +114,38 @@ fn verify_bdd(ltl: &str, ins: &[&str], outs: &[&str], expected_status: Status) {
         output_format: OutputFormat::Bdd,
         ..SynthesisOptions::default()
     };
-    let result = synthesize_with(ltl, ins, outs, &options);
-    assert_eq!(result.status(), expected_status);
+    let result = synthesize_expecting(ltl, ins, outs, expected_status, &options).unwrap();
     // can not verify BDD itself currently
     assert!(matches!(result.controller(), Some(Controller::Bdd(_))));
 }
 
-/// Generate tests for the given list of specifications, testing
-/// realizability, aiger circuit synthesis and HOA machine synthesis.
+/// Synthesize the given specification, producing a network of lookup tables.
+/// The network is currently *not* verified.
+fn verify_blif(ltl: &str, ins: &[&str], outs: &[&str], expected_status: Status) {
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Blif,
+        ..SynthesisOptions::default()
+    };
+    let result = synthesize_expecting(ltl, ins, outs, expected_status, &options).unwrap();
+    // can not verify the network itself currently
+    assert!(matches!(result.controller(), Some(Controller::Lut(_))));
+}
+
+/// Synthesize the given specification, producing a machine in HOA format, convert
+/// the written machine to an aiger circuit, and verify the circuit against the
+/// specification and given status.
+fn verify_converted_aiger(ltl: &str, ins: &[&str], outs: &[&str], expected_status: Status) {
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Hoa,
+        ..SynthesisOptions::default()
+    };
+    Verifier::new()
+        .verify_converted_aiger(ltl, ins, outs, expected_status, &options)
+        .unwrap();
+}
+
+/// Generate tests for the given list of specifications, testing realizability,
+/// aiger circuit synthesis, HOA machine synthesis and conversion of machines.
 macro_rules! synt_tests {
     ($($name:ident: ($ltl:expr, $ins:expr, $outs:expr, $expected_status:expr),)*) => {
         mod realizability {
@@ -199,6 +177,16 @@ macro_rules! synt_tests {
                 }
             )*
         }
+
+        mod convert {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    verify_converted_aiger($ltl, $ins, $outs, $expected_status);
+                }
+            )*
+        }
     }
 }
 
@@ -437,7 +425,6 @@ macro_rules! option_tests {
             use super::*;
             $(
                 #[test]
-                #[ignore] // ZLK does not yet provide a strategy
                 fn $name() {
                     let options = SynthesisOptions {
                         output_format: OutputFormat::Aag,
@@ -518,6 +505,34 @@ macro_rules! option_tests {
                 }
             )*
         }
+        mod label_inner {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        label_structure: LabelStructure::Inner,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod label_scored {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        label_structure: LabelStructure::Scored,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
         mod label_structured_lookahead_infinity {
             use super::*;
             $(
@@ -705,6 +720,15 @@ macro_rules! option_tests {
                 }
             )*
         }
+        mod output_blif {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    verify_blif($ltl, $ins, $outs, $expected_status);
+                }
+            )*
+        }
         mod output_aig {
             use super::*;
             $(
@@ -873,6 +897,261 @@ macro_rules! option_tests {
                 }
             )*
         }
+        mod border_free {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+                        exploration_border_free: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod determinization_low_toggle {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        machine_determinization_policy: DeterminizationPolicy::LowToggle,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod stage_budgets {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        aiger_portfolio: true,
+                        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+                        exploration_border_free: true,
+                        budget_solving: Some(0),
+                        budget_minimization: Some(0),
+                        budget_reordering: Some(0),
+                        budget_compression: Some(0),
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod assumption_dontcares {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        machine_minimization: MinimizationMethod::Both,
+                        assumption_dontcares: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod solve_reachable_onthefly_node1 {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+                        solve_reachable: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod solve_reachable_border_free {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+                        exploration_border_free: true,
+                        solve_reachable: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod compression_sequential {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        aiger_compression: AigerCompression::Sequential,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod prune_queue_onthefly_node1 {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+                        exploration_pruning: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod prune_queue_border_free_max {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        exploration_strategy: ExplorationStrategy::Max,
+                        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+                        exploration_border_free: true,
+                        exploration_pruning: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod exploration_bdfs {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        exploration_strategy: ExplorationStrategy::Bdfs(3),
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod strategy_propagation {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        strategy_propagation: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod hoa_implicit_else {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Hoa,
+                        hoa_implicit_else: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_hoa_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod aiger_exact_synthesis {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        aiger_exact_synthesis: Some(4),
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod bdd_partition {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        bdd_partition_threshold: Some(2),
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod depth_bounded_exploration {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        depth_bounded_exploration: std::num::NonZeroUsize::new(4),
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod game_preprocessing {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+                        game_preprocessing: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
+        mod abstraction_refinement {
+            use super::*;
+            $(
+                #[test]
+                fn $name() {
+                    let options = SynthesisOptions {
+                        output_format: OutputFormat::Aag,
+                        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+                        abstraction_refinement: true,
+                        ..SynthesisOptions::default()
+                    };
+                    verify_aiger_with($ltl, $ins, $outs, $expected_status, &options);
+                }
+            )*
+        }
     }
 }
 
@@ -942,3 +1221,456 @@ option_tests! {
     ltl2dba_c2_2: ("((G F p0) & (G F p1)) <-> G F acc", &["p0", "p1"], &["acc"], Realizable),
     ltl2dba_theta_2: ("!((G F p0) & (G F p1) & G (q -> F r)) <-> G F acc", &["r", "q", "p0", "p1"], &["acc"], Unrealizable),
 }
+
+/// Test that concurrent synthesis calls from several threads, each with its own
+/// GraalVM and compressing the circuits with ABC, produce correct controllers.
+#[test]
+fn concurrent_synthesis() {
+    const SPECS: [(&str, &[&str], &[&str], Status); 4] = [
+        ("G (r -> F g)", &["r"], &["g"], Realizable),
+        ("(G F p) <-> G F acc", &["p"], &["acc"], Realizable),
+        ("G (r <-> X g)", &["r"], &["g"], Realizable),
+        ("G (g <-> X r)", &["r"], &["g"], Unrealizable),
+    ];
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            std::thread::spawn(move || {
+                let options = SynthesisOptions {
+                    output_format: OutputFormat::Aag,
+                    aiger_compression: AigerCompression::More,
+                    ..SynthesisOptions::default()
+                };
+                for k in 0..SPECS.len() {
+                    let (ltl, ins, outs, status) = SPECS[(i + k) % SPECS.len()];
+                    let result = synthesize_with(ltl, ins, outs, &options);
+                    assert_eq!(result.status(), status);
+                    assert!(matches!(result.controller(), Some(Controller::Aiger(_))));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+/// Test that the status is reported once through the callback before synthesis finishes.
+#[test]
+fn status_callback() {
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Aag,
+        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+        exploration_border_free: true,
+        ..SynthesisOptions::default()
+    };
+    let mut statuses = Vec::new();
+    let result = synthesize_with_callback("G (r -> F g)", &["r"], &["g"], &options, |status| {
+        statuses.push(status)
+    });
+    assert_eq!(statuses, vec![Status::Realizable]);
+    assert_eq!(result.status(), Status::Realizable);
+    assert!(result.controller().is_some());
+}
+
+/// Test that a formula rejected by Owl is reported as an error instead of a panic.
+#[test]
+fn translation_error() {
+    let options = SynthesisOptions::default();
+    let error = try_synthesize_with_callback("G (r -> F g)\0", &["r"], &["g"], &options, |_| ())
+        .err()
+        .unwrap();
+    match error {
+        SynthesisError::Translation(err) => {
+            assert!(matches!(err.cause(), OwlError::Parse(_)));
+            assert_eq!(err.subformula(), None);
+        }
+        _ => panic!("unexpected error {}", error),
+    }
+}
+
+/// Test that an assumption on the input makes an unrealizable specification realizable.
+#[test]
+fn assumption_weakening() {
+    let options = SynthesisOptions::default();
+    let assumptions = weaken_assumptions(
+        "G F (r & g)",
+        &["r"],
+        &["g"],
+        &["G !{}", "G F {}"],
+        1,
+        &options,
+    );
+    assert_eq!(assumptions, Some(vec!["G F r".to_string()]));
+    let assumptions = weaken_assumptions("G F (r & g)", &["r"], &["g"], &["G !{}"], 1, &options);
+    assert_eq!(assumptions, None);
+}
+
+/// Test that the telemetry is recorded once for each call to solve the game.
+#[test]
+fn solver_telemetry() {
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Aag,
+        exploration_on_the_fly: OnTheFlyLimit::Nodes(1),
+        ..SynthesisOptions::default()
+    };
+    let mut records = Vec::new();
+    let result = synthesize_with_telemetry(
+        "G (r -> F g)",
+        &["r"],
+        &["g"],
+        &options,
+        |_| (),
+        |telemetry: &SolverTelemetry| records.push(telemetry.clone()),
+    );
+    assert_eq!(result.status(), Status::Realizable);
+    assert!(!records.is_empty());
+    for (i, record) in records.iter().enumerate() {
+        assert_eq!(record.iteration(), i + 1);
+    }
+    assert!(records.windows(2).all(|w| w[0].nodes() <= w[1].nodes()));
+    assert!(records.iter().map(|r| r.new_won_even()).sum::<usize>() > 0);
+}
+
+/// Test that safety formulas are solved on the fast path for all solvers.
+#[test]
+fn safety_fast_path() {
+    for &parity_solver in &[Solver::Fpi, Solver::Zlk, Solver::Si] {
+        let options = SynthesisOptions {
+            output_format: OutputFormat::Aag,
+            parity_solver,
+            ..SynthesisOptions::default()
+        };
+        let result = synthesize_with("G (r -> X g)", &["r"], &["g"], &options);
+        assert_eq!(result.status(), Status::Realizable);
+        assert!(result.controller().is_some());
+        let result = synthesize_with("G (r -> g) & G (r -> X !g)", &["r"], &["g"], &options);
+        assert_eq!(result.status(), Status::Unrealizable);
+        assert!(result.controller().is_some());
+    }
+}
+
+/// Test that the events of all stages are emitted in order with increasing times.
+#[test]
+fn pipeline_events() {
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Aag,
+        machine_minimization: MinimizationMethod::Both,
+        ..SynthesisOptions::default()
+    };
+    let mut events = Vec::new();
+    let result = synthesize_with_events(
+        "G (r -> F g)",
+        &["r"],
+        &["g"],
+        &options,
+        |_| (),
+        |elapsed, event: &PipelineEvent| events.push((elapsed, event.clone())),
+    );
+    assert_eq!(result.status(), Status::Realizable);
+    let names: Vec<_> = events.iter().map(|(_, event)| event.name()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "vm_initialized",
+            "formula_classified",
+            "automaton_created",
+            "game_solved",
+            "machine_constructed",
+            "machine_minimized",
+            "bdd_constructed",
+            "aiger_constructed",
+            "finished",
+        ]
+    );
+    assert!(events.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert_eq!(
+        events.last().unwrap().1,
+        PipelineEvent::Finished {
+            status: Status::Realizable
+        }
+    );
+}
+
+/// Test that machines exceeding the state limit are not minimized.
+#[test]
+fn minimization_state_limit() {
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Aag,
+        machine_minimization: MinimizationMethod::Both,
+        minimization_max_states: Some(0),
+        ..SynthesisOptions::default()
+    };
+    let mut events = Vec::new();
+    let result = synthesize_with_events(
+        "G (r -> F g)",
+        &["r"],
+        &["g"],
+        &options,
+        |_| (),
+        |_, event: &PipelineEvent| events.push(event.name()),
+    );
+    assert_eq!(result.status(), Status::Realizable);
+    assert!(matches!(result.controller(), Some(Controller::Aiger(_))));
+    assert!(!events.contains(&"machine_minimized"));
+}
+
+/// Test that the strategies of both players are written for unrealizable specifications.
+#[test]
+fn strategies_output() {
+    let path = std::env::temp_dir().join("strix-test-strategies.sol");
+    let options = SynthesisOptions {
+        strategies_output: Some(path.to_string_lossy().into_owned()),
+        ..SynthesisOptions::default()
+    };
+    let result = synthesize_with("G (r -> F g) & F r", &["r"], &["g"], &options);
+    assert_eq!(result.status(), Status::Unrealizable);
+    assert!(matches!(result.controller(), Some(Controller::Machine(_))));
+    let solution = result.solution().unwrap();
+    assert_eq!(solution.winner(0), Some(Player::Odd));
+    assert!(solution.num_decided() > 0);
+    assert_eq!(fs::read_to_string(&path).unwrap(), solution.to_string());
+    fs::remove_file(&path).unwrap();
+}
+
+/// Test that the Pareto frontier of the portfolio candidates is kept in the result.
+#[test]
+fn pareto_frontier() {
+    let metrics = vec![AigerMetric::Gates, AigerMetric::Depth, AigerMetric::States];
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Aag,
+        aiger_portfolio: true,
+        pareto_metrics: metrics.clone(),
+        ..SynthesisOptions::default()
+    };
+    let result = synthesize_with("G (r -> F g)", &["r"], &["g"], &options);
+    assert_eq!(result.status(), Status::Realizable);
+    let frontier = result.pareto_frontier().unwrap();
+    assert!(!frontier.is_empty());
+    for c in frontier {
+        assert!(!frontier
+            .iter()
+            .any(|other| other.metrics().dominates(c.metrics(), &metrics)));
+        assert_ne!(c.provenance().labels(), LabelStructure::Scored);
+    }
+    assert!(
+        synthesize_with("G (r -> F g)", &["r"], &["g"], &SynthesisOptions::default())
+            .pareto_frontier()
+            .is_none()
+    );
+}
+
+/// Test that controllers for additional outputs are constructed in one call.
+#[test]
+fn artifacts() {
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Hoa,
+        outputs: vec!["aag:ctrl.aag".parse().unwrap(), "bdd".parse().unwrap()],
+        ..SynthesisOptions::default()
+    };
+    options.validate().unwrap();
+    let result = synthesize_with("G (r -> F g)", &["r"], &["g"], &options);
+    assert_eq!(result.status(), Status::Realizable);
+    assert!(matches!(result.controller(), Some(Controller::Machine(_))));
+    let artifacts = result.artifacts();
+    assert_eq!(artifacts.len(), 2);
+    assert_eq!(artifacts[0].target().file.as_deref(), Some("ctrl.aag"));
+    assert!(matches!(artifacts[0].controller(), Controller::Aiger(_)));
+    assert!(matches!(artifacts[1].controller(), Controller::Bdd(_)));
+
+    let duplicate = SynthesisOptions {
+        output_format: OutputFormat::Aag,
+        outputs: vec!["aag".parse().unwrap()],
+        ..SynthesisOptions::default()
+    };
+    assert!(duplicate.validate().is_err());
+    assert!("pdf:ctrl.pdf".parse::<OutputTarget>().is_err());
+}
+
+/// Test that the incompletely specified machine is written in KISS2 format
+/// alongside the determinized machine.
+#[test]
+fn kiss2_output() {
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Kiss2,
+        outputs: vec!["hoa".parse().unwrap()],
+        ..SynthesisOptions::default()
+    };
+    options.validate().unwrap();
+    let result = synthesize_with("G (r -> F g)", &["r"], &["g"], &options);
+    assert_eq!(result.status(), Status::Realizable);
+    let controller = result.controller().unwrap();
+    assert!(matches!(controller, Controller::IncompleteMachine(_)));
+    let mut kiss = Vec::new();
+    controller.write(&mut kiss, result.status(), false).unwrap();
+    let kiss = String::from_utf8(kiss).unwrap();
+    assert!(kiss.starts_with(".i 1\n.o 1\n"));
+    assert!(kiss.ends_with(".e\n"));
+    assert!(matches!(
+        result.artifacts()[0].controller(),
+        Controller::Machine(_)
+    ));
+}
+
+/// Test that a machine in KISS2 format is converted to an aiger circuit and back.
+#[test]
+fn kiss2_conversion() {
+    let kiss = ".i 1\n.o 1\n.p 2\n.s 2\n.r s0\n1 s0 s1 -\n- s1 s0 1\n.e\n";
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Aag,
+        ..SynthesisOptions::default()
+    };
+    let controller = convert_controller(kiss.as_bytes(), &options).unwrap();
+    assert!(matches!(controller, Controller::Aiger(_)));
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Kiss2,
+        ..SynthesisOptions::default()
+    };
+    let controller = convert_controller(kiss.as_bytes(), &options).unwrap();
+    assert!(matches!(controller, Controller::IncompleteMachine(_)));
+    assert!(convert_controller(b".i 1\n.o 1\n0 s0\n", &options).is_err());
+}
+
+/// Test that a controller drives the controllable inputs of a safety monitor.
+#[test]
+fn monitor_synthesis() {
+    let monitor = "aag 4 2 1 1 1\n2\n4\n6 8\n6\n8 2 5\ni0 a\ni1 controllable_b\no0 bad\n";
+    let options = SynthesisOptions {
+        output_format: OutputFormat::Aag,
+        ..SynthesisOptions::default()
+    };
+    let mut statuses = Vec::new();
+    let result = synthesize_monitor_with_callback(monitor.as_bytes(), &options, |status| {
+        statuses.push(status)
+    })
+    .unwrap();
+    assert_eq!(statuses, vec![Status::Realizable]);
+    let aiger = match result.controller() {
+        Some(Controller::Aiger(aiger)) => aiger,
+        _ => panic!("no aiger circuit constructed"),
+    };
+    assert_eq!(aiger.num_inputs(), 1);
+    assert_eq!(aiger.num_outputs(), 1);
+    let inputs = vec![vec![true], vec![false], vec![true], vec![true]];
+    assert!(aiger.simulate(&inputs).iter().all(|outputs| !outputs[0]));
+
+    let unrealizable = "aag 2 2 0 1 0\n2\n4\n2\ni0 a\ni1 controllable_b\no0 bad\n";
+    let result =
+        synthesize_monitor_with_callback(unrealizable.as_bytes(), &options, |_| ()).unwrap();
+    assert_eq!(result.status(), Status::Unrealizable);
+    assert!(result.controller().is_none());
+    assert!(matches!(
+        synthesize_monitor_with_callback(b"aag 0 0 0 0 0\n", &options, |_| ()),
+        Err(SynthesisError::InvalidMonitor(_))
+    ));
+}
+
+/// Test that the environment chooses among the given initial states of the automaton.
+#[test]
+fn initial_states() {
+    let options = SynthesisOptions {
+        initial_states: vec![0, 1],
+        ..SynthesisOptions::default()
+    };
+    options.validate().unwrap();
+    // state 1 is reached after a request and needs a grant in the next step
+    let result = synthesize_with("G (r -> X g)", &["r"], &["g"], &options);
+    assert_eq!(result.status(), Status::Realizable);
+    assert!(matches!(result.controller(), Some(Controller::Machine(_))));
+    // state 1 is reached after a grant and needs a request in the next step
+    let result = synthesize_with("G (g -> X r)", &["r"], &["g"], &options);
+    assert_eq!(result.status(), Status::Unrealizable);
+    let result = synthesize_with("G (g -> X r)", &["r"], &["g"], &SynthesisOptions::default());
+    assert_eq!(result.status(), Status::Realizable);
+
+    let conflicting = SynthesisOptions {
+        cross_check: Some(2),
+        ..options
+    };
+    assert!(conflicting.validate().is_err());
+}
+
+/// Test that a controller that copies its input is not robust to glitches.
+#[test]
+fn glitch_window() {
+    let options = SynthesisOptions {
+        glitch_window: Some(2),
+        glitch_resynthesis: true,
+        ..SynthesisOptions::default()
+    };
+    options.validate().unwrap();
+    let result = synthesize_with("G (r <-> g)", &["r"], &["g"], &options);
+    assert_eq!(result.status(), Status::Realizable);
+    assert_eq!(result.robustness(), Some(Robustness::Violated));
+    let result = synthesize_with("G F g", &["r"], &["g"], &options);
+    assert_eq!(result.robustness(), Some(Robustness::Robust));
+    let result = synthesize_with("G (r & g)", &["r"], &["g"], &options);
+    assert_eq!(result.status(), Status::Unrealizable);
+    assert_eq!(result.robustness(), None);
+
+    let conflicting = SynthesisOptions {
+        glitch_window: None,
+        ..options
+    };
+    assert!(conflicting.validate().is_err());
+}
+
+/// Test that fairness assumptions on inputs make specifications realizable.
+#[test]
+fn fair_inputs() {
+    // the output can only be set together with the input if it holds infinitely often
+    let ltl = "G F (ready & g)";
+    let options = SynthesisOptions {
+        fair_inputs: vec![Fairness::Unconditional("ready".to_string())],
+        ..SynthesisOptions::default()
+    };
+    let result = synthesize_with(ltl, &["ready"], &["g"], &SynthesisOptions::default());
+    assert_eq!(result.status(), Status::Unrealizable);
+    let result = synthesize_with(ltl, &["ready"], &["g"], &options);
+    assert_eq!(result.status(), Status::Realizable);
+    let options = SynthesisOptions {
+        fair_inputs: vec![Fairness::Strong("req".to_string(), "ready".to_string())],
+        ..SynthesisOptions::default()
+    };
+    let result = synthesize_with(
+        "G F req -> G F (ready & g)",
+        &["req", "ready"],
+        &["g"],
+        &options,
+    );
+    assert_eq!(result.status(), Status::Realizable);
+
+    let options = SynthesisOptions {
+        fair_inputs: vec![Fairness::Unconditional("g".to_string())],
+        ..SynthesisOptions::default()
+    };
+    let error = try_synthesize_with_callback(ltl, &["ready"], &["g"], &options, |_| ());
+    assert_eq!(
+        error.err(),
+        Some(SynthesisError::UnknownFairInput("g".to_string()))
+    );
+}
+
+/// Test that undeclared propositions are rejected or added to the inputs or outputs.
+#[test]
+fn undeclared_propositions() {
+    let ltl = "G (r -> F g)";
+    let options = SynthesisOptions::default();
+    let error = try_synthesize_with_callback(ltl, &["r"], &[], &options, |_| ());
+    assert_eq!(
+        error.err(),
+        Some(SynthesisError::UndeclaredPropositions(
+            vec!["g".to_string()]
+        ))
+    );
+    for &(undeclared, status) in &[
+        (Undeclared::Outputs, Status::Realizable),
+        (Undeclared::Inputs, Status::Unrealizable),
+    ] {
+        let options = SynthesisOptions {
+            undeclared,
+            ..SynthesisOptions::default()
+        };
+        let result = synthesize_with(ltl, &["r"], &[], &options);
+        assert_eq!(result.status(), status);
+    }
+}